@@ -27,8 +27,9 @@ use client::{
 use codec::Decode;
 use consensus_common::{evaluation};
 use inherents::InherentData;
+use lazy_static::lazy_static;
 use log::{error, info, debug, trace};
-use primitives::{H256, Blake2Hasher, ExecutionContext};
+use primitives::{H256, Blake2Hasher};
 use sr_primitives::{
 	traits::{Block as BlockT, Hash as HashT, Header as HeaderT, ProvideRuntimeApi, DigestFor, BlakeTwo256},
 	generic::BlockId,
@@ -37,6 +38,13 @@ use sr_primitives::{
 use transaction_pool::txpool::{self, Pool as TransactionPool};
 use substrate_telemetry::{telemetry, CONSENSUS_INFO};
 
+lazy_static! {
+	static ref PROPOSAL_TIME: Arc<prometheus::Histogram> = prometheus::duration_histogram(
+		"substrate_block_proposal_time_seconds",
+		"Time taken to build a block proposal",
+	);
+}
+
 /// Proposer factory.
 pub struct ProposerFactory<C, A> where A: txpool::ChainApi {
 	/// The client instance.
@@ -113,8 +121,15 @@ where
 		max_duration: time::Duration,
 	) -> Self::Create {
 		// leave some time for evaluation and block finalization (33%)
-		let deadline = (self.now)() + max_duration - max_duration / 3;
-		futures::future::ready(self.propose_with(inherent_data, inherent_digests, deadline))
+		let now = (self.now)();
+		let deadline = now + max_duration - max_duration / 3;
+		// The runtime rejects transactions exceeding the maximum weight or length of
+		// their dispatch class with `FullBlock`. Once the block seems full, we try a few more
+		// transactions, then keep looking for transactions that still fit (e.g. smaller or
+		// operational ones) until the soft deadline.
+		let soft_deadline = now + (max_duration - max_duration / 3) / 2;
+		let _timer = PROPOSAL_TIME.start_timer();
+		futures::future::ready(self.propose_with(inherent_data, inherent_digests, deadline, soft_deadline))
 	}
 }
 
@@ -132,9 +147,11 @@ impl<Block, B, E, RA, A> Proposer<Block, SubstrateClient<B, E, Block, RA>, A>	wh
 		inherent_data: InherentData,
 		inherent_digests: DigestFor<Block>,
 		deadline: time::Instant,
+		soft_deadline: time::Instant,
 	) -> Result<Block, error::Error> {
-		/// If the block is full we will attempt to push at most
-		/// this number of transactions before quitting for real.
+		/// If the block is full we will attempt to push at least
+		/// this number of transactions, and more until the soft
+		/// deadline, before quitting for real.
 		/// It allows us to increase block utilization.
 		const MAX_SKIPPED_TRANSACTIONS: usize = 8;
 
@@ -142,15 +159,7 @@ impl<Block, B, E, RA, A> Proposer<Block, SubstrateClient<B, E, Block, RA>, A>	wh
 
 		// We don't check the API versions any further here since the dispatch compatibility
 		// check should be enough.
-		for extrinsic in self.client.runtime_api()
-			.inherent_extrinsics_with_context(
-				&self.parent_id,
-				ExecutionContext::BlockConstruction,
-				inherent_data
-			)?
-		{
-			block_builder.push(extrinsic)?;
-		}
+		block_builder.push_inherents(inherent_data)?;
 
 		// proceed with transactions
 		let mut is_first = true;
@@ -180,6 +189,8 @@ impl<Block, B, E, RA, A> Proposer<Block, SubstrateClient<B, E, Block, RA>, A>	wh
 							"Block seems full, but will try {} more transactions before quitting.",
 							MAX_SKIPPED_TRANSACTIONS - skipped
 						);
+					} else if (self.now)() < soft_deadline {
+						debug!("Block seems full, but there is still time before the soft deadline, keep trying.");
 					} else {
 						debug!("Block is full, proceed with proposing.");
 						break;
@@ -231,7 +242,8 @@ mod tests {
 
 	use std::cell::RefCell;
 	use consensus_common::{Environment, Proposer};
-	use test_client::{self, runtime::{Extrinsic, Transfer}, AccountKeyring};
+	use sr_primitives::transaction_validity::TransactionSource;
+	use test_client::{self, runtime::{Block, Extrinsic, Transfer}, AccountKeyring};
 
 	fn extrinsic(nonce: u64) -> Extrinsic {
 		Transfer {
@@ -249,7 +261,7 @@ mod tests {
 		let chain_api = transaction_pool::ChainApi::new(client.clone());
 		let txpool = Arc::new(TransactionPool::new(Default::default(), chain_api));
 
-		txpool.submit_at(&BlockId::number(0), vec![extrinsic(0), extrinsic(1)]).unwrap();
+		txpool.submit_at(&BlockId::number(0), TransactionSource::External, vec![extrinsic(0), extrinsic(1)]).unwrap();
 
 		let mut proposer_factory = ProposerFactory {
 			client: client.clone(),
@@ -275,4 +287,74 @@ mod tests {
 		assert_eq!(block.extrinsics().len(), 1);
 		assert_eq!(txpool.ready().count(), 2);
 	}
+
+	fn full_block_proposer(
+		now: Box<dyn Fn() -> time::Instant>,
+	) -> (Block, Arc<TransactionPool<transaction_pool::ChainApi<test_client::TestClient, Block>>>) {
+		let client = Arc::new(test_client::new());
+		let chain_api = transaction_pool::ChainApi::new(client.clone());
+		let txpool = Arc::new(TransactionPool::new(Default::default(), chain_api));
+
+		// the first transfer fills the block up, the next ten are rejected with `FullBlock`
+		// and a transfer of Bob which still fits comes last
+		let transfer = |from: AccountKeyring, amount, nonce| Transfer {
+			amount,
+			nonce,
+			from: from.into(),
+			to: Default::default(),
+		};
+		let mut extrinsics = vec![transfer(AccountKeyring::Alice, 2, 0).into_signed_tx()];
+		extrinsics.extend(
+			(1..11).map(|nonce| transfer(AccountKeyring::Alice, 1, nonce).into_resources_exhausting_tx())
+		);
+		extrinsics.push(transfer(AccountKeyring::Bob, 0, 0).into_signed_tx());
+		txpool.submit_at(&BlockId::number(0), TransactionSource::External, extrinsics).unwrap();
+
+		let mut proposer_factory = ProposerFactory {
+			client: client.clone(),
+			transaction_pool: txpool.clone(),
+		};
+		let mut proposer = proposer_factory.init(
+			&client.header(&BlockId::number(0)).unwrap().unwrap(),
+		).unwrap();
+		proposer.now = now;
+
+		let block = futures::executor::block_on(
+			proposer.propose(Default::default(), Default::default(), time::Duration::from_secs(3))
+		).unwrap();
+		(block, txpool)
+	}
+
+	#[test]
+	fn should_keep_trying_transactions_in_a_full_block_until_the_soft_deadline() {
+		// given
+		let start = time::Instant::now();
+
+		// when
+		let (block, txpool) = full_block_proposer(Box::new(move || start));
+
+		// then
+		// Bob's transfer is included although more than `MAX_SKIPPED_TRANSACTIONS` were skipped.
+		assert_eq!(block.extrinsics().len(), 2);
+		assert_eq!(AccountKeyring::from_public(&block.extrinsics()[1].transfer().from), Some(AccountKeyring::Bob));
+		// the skipped transactions are not removed from the pool.
+		assert_eq!(txpool.ready().count(), 12);
+	}
+
+	#[test]
+	fn should_stop_trying_transactions_in_a_full_block_after_the_soft_deadline() {
+		// given
+		let start = time::Instant::now();
+		let called = RefCell::new(false);
+
+		// when
+		// the soft deadline passes right after the proposing starts, but not the deadline.
+		let (block, txpool) = full_block_proposer(Box::new(move || {
+			if called.replace(true) { start + time::Duration::from_millis(1500) } else { start }
+		}));
+
+		// then
+		assert_eq!(block.extrinsics().len(), 1);
+		assert_eq!(txpool.ready().count(), 12);
+	}
 }
@@ -0,0 +1,72 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The runtime benchmarking api primitives.
+//!
+//! A benchmark is identified by the name of a module and of one of its extrinsics. Every
+//! benchmark has a set of components (e.g. the number of entries in a storage map the
+//! extrinsic iterates) each with an inclusive range of values. The node picks points in these
+//! ranges and measures the time it takes to execute the extrinsic on top of the state prepared
+//! for that point.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+use client::decl_runtime_apis;
+use codec::{Encode, Decode};
+use rstd::vec::Vec;
+
+/// A component of a benchmark and the inclusive range of values it can take.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct ComponentRange {
+	/// The name of the component.
+	pub name: Vec<u8>,
+	/// The lowest value of the component.
+	pub low: u32,
+	/// The highest value of the component.
+	pub high: u32,
+}
+
+/// The values of all components of a benchmark, in the order given by `benchmark_components`.
+pub type ComponentValues = Vec<(Vec<u8>, u32)>;
+
+decl_runtime_apis! {
+	/// The runtime benchmarking api.
+	///
+	/// `benchmark_setup` and `benchmark_dispatch` are executed on top of the same state, the
+	/// node measures the execution time of the extrinsic as the difference of both calls.
+	pub trait Benchmark {
+		/// Returns the components of the benchmark of `extrinsic` in `module` or `None` if there
+		/// is no such benchmark.
+		fn benchmark_components(module: Vec<u8>, extrinsic: Vec<u8>) -> Option<Vec<ComponentRange>>;
+
+		/// Prepares the state for the benchmark of `extrinsic` in `module` at the given
+		/// component values.
+		fn benchmark_setup(
+			module: Vec<u8>,
+			extrinsic: Vec<u8>,
+			components: ComponentValues,
+		) -> Result<(), Vec<u8>>;
+
+		/// Prepares the state like `benchmark_setup` and dispatches the extrinsic afterwards.
+		fn benchmark_dispatch(
+			module: Vec<u8>,
+			extrinsic: Vec<u8>,
+			components: ComponentValues,
+		) -> Result<(), Vec<u8>>;
+	}
+}
@@ -36,7 +36,58 @@ pub enum Error {
 	Input(String),
 	/// Invalid listen multiaddress
 	#[display(fmt="Invalid listen multiaddress")]
-	InvalidListenMultiaddress
+	InvalidListenMultiaddress,
+	/// The tasks of the node didn't finish in time during shutdown.
+	#[display(fmt="Shutdown timed out")]
+	ShutdownTimeout,
+}
+
+/// Process exit codes for the different classes of errors, see `Error::exit_code`.
+pub mod exit_code {
+	/// Any error that isn't covered by a more specific exit code.
+	pub const OTHER: i32 = 1;
+	/// Invalid command line parameters or chain specification.
+	pub const INVALID_INPUT: i32 = 2;
+	/// The database could not be opened or is corrupted.
+	pub const DATABASE: i32 = 3;
+	/// A network or RPC port is already in use.
+	pub const PORT_IN_USE: i32 = 4;
+	/// The tasks of the node didn't finish in time during shutdown.
+	pub const SHUTDOWN_TIMEOUT: i32 = 5;
+}
+
+impl Error {
+	/// Returns the process exit code for this error.
+	pub fn exit_code(&self) -> i32 {
+		fn io_exit_code(err: &std::io::Error) -> i32 {
+			match err.kind() {
+				std::io::ErrorKind::AddrInUse => exit_code::PORT_IN_USE,
+				_ => exit_code::OTHER,
+			}
+		}
+
+		fn client_exit_code(err: &client::error::Error) -> i32 {
+			match err {
+				client::error::Error::Backend(_) => exit_code::DATABASE,
+				client::error::Error::Blockchain(err) => client_exit_code(err),
+				client::error::Error::GenesisInvalid => exit_code::INVALID_INPUT,
+				_ => exit_code::OTHER,
+			}
+		}
+
+		match self {
+			Error::Io(err) => io_exit_code(err),
+			Error::Cli(_) | Error::Input(_) | Error::InvalidListenMultiaddress => exit_code::INVALID_INPUT,
+			Error::Client(err) => client_exit_code(err),
+			Error::Service(service::Error::Client(err)) => client_exit_code(err),
+			Error::Service(service::Error::Io(err)) => io_exit_code(err),
+			Error::Service(service::Error::Network(network::error::Error::Io(err))) => io_exit_code(err),
+			Error::Service(service::Error::Network(network::error::Error::Client(err))) =>
+				client_exit_code(err),
+			Error::Service(_) => exit_code::OTHER,
+			Error::ShutdownTimeout => exit_code::SHUTDOWN_TIMEOUT,
+		}
+	}
 }
 
 impl std::error::Error for Error {
@@ -48,6 +99,7 @@ impl std::error::Error for Error {
 			Error::Client(ref err) => Some(err),
 			Error::Input(_) => None,
 			Error::InvalidListenMultiaddress => None,
+			Error::ShutdownTimeout => None,
 		}
 	}
 }
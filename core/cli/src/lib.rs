@@ -27,35 +27,43 @@ pub mod informant;
 
 use client::ExecutionStrategies;
 use service::{
-	ServiceFactory, FactoryFullConfiguration, RuntimeGenesis,
-	FactoryGenesis, PruningMode, ChainSpec,
+	ServiceFactory, FactoryFullConfiguration, RuntimeGenesis, ChainSpecExtension,
+	FactoryChainSpec, PruningMode, ChainSpec,
 };
 use network::{
 	self, multiaddr::Protocol,
 	config::{NetworkConfiguration, TransportConfig, NonReservedPeerMode, NodeKeyConfig, build_multiaddr},
 };
-use primitives::H256;
+use codec::Decode;
+use sr_primitives::{generic::BlockId, traits::Block as BlockT};
+use primitives::{
+	H256, ed25519, sr25519, ecdsa, Pair, hexdisplay::HexDisplay,
+	crypto::{Ss58Codec, Ss58AddressFormat, Public, set_default_ss58_version},
+};
 
 use std::{
+	collections::HashSet, convert::TryFrom,
 	io::{Write, Read, Seek, Cursor, stdin, stdout, ErrorKind}, iter, fs::{self, File},
-	net::{Ipv4Addr, SocketAddr}, path::{Path, PathBuf}, str::FromStr,
+	net::{Ipv4Addr, SocketAddr}, path::{Path, PathBuf}, str::FromStr, time::Duration,
 };
 
+use bip39::{Mnemonic, Language, MnemonicType};
 use names::{Generator, Name};
 use regex::Regex;
 use structopt::{StructOpt, clap::AppSettings};
 #[doc(hidden)]
 pub use structopt::clap::App;
 use params::{
-	RunCmd, PurgeChainCmd, RevertCmd, ImportBlocksCmd, ExportBlocksCmd, BuildSpecCmd,
+	RunCmd, PurgeChainCmd, RevertCmd, ForkOffCmd, TryRuntimeCmd, StateDiffCmd, ImportBlocksCmd, ExportBlocksCmd,
+	BuildSpecCmd,
 	NetworkConfigurationParams, MergeParameters, TransactionPoolParams,
-	NodeKeyParams, NodeKeyType, Cors,
+	NodeKeyParams, NodeKeyType, Cors, KeySubcommand, GenerateKeyCmd, InspectKeyCmd, InsertKeyCmd,
+	CryptoScheme, BenchmarkCmd, InspectCmd, SignCmd, VerifyCmd, MessageParams,
 };
 pub use params::{NoCustom, CoreParams, SharedParams, ExecutionStrategy as ExecutionStrategyParam};
 pub use traits::{GetLogFilter, AugmentClap};
 use app_dirs::{AppInfo, AppDataType};
-use log::info;
-use lazy_static::lazy_static;
+use log::{info, warn};
 
 use futures::Future;
 use substrate_telemetry::TelemetryEndpoints;
@@ -119,17 +127,43 @@ fn generate_node_name() -> String {
 	result
 }
 
-fn load_spec<F, G>(cli: &SharedParams, factory: F) -> error::Result<ChainSpec<G>>
-	where G: RuntimeGenesis, F: FnOnce(&str) -> Result<Option<ChainSpec<G>>, String>,
+fn load_spec<F, G, E>(cli: &SharedParams, factory: F) -> error::Result<ChainSpec<G, E>>
+	where G: RuntimeGenesis, E: ChainSpecExtension, F: FnOnce(&str) -> Result<Option<ChainSpec<G, E>>, String>,
 {
 	let chain_key = get_chain_key(cli);
 	let spec = match factory(&chain_key)? {
 		Some(spec) => spec,
 		None => ChainSpec::from_json_file(PathBuf::from(chain_key))?
 	};
+	if let Some(format) = ss58_format(&spec.properties())? {
+		set_default_ss58_version(format);
+	}
 	Ok(spec)
 }
 
+/// Returns the SS58 address format given by the `ss58Format` property of the chain spec.
+fn ss58_format(properties: &service::Properties) -> error::Result<Option<Ss58AddressFormat>> {
+	match properties.get("ss58Format") {
+		None => Ok(None),
+		Some(value) => value.as_u64()
+			.and_then(|prefix| u8::try_from(prefix).ok())
+			.map(|prefix| Some(
+				Ss58AddressFormat::try_from(prefix).unwrap_or(Ss58AddressFormat::Custom(prefix))
+			))
+			.ok_or_else(|| error::Error::Input(format!("Invalid ss58Format in chain spec: {}", value))),
+	}
+}
+
+/// Use the SS58 address format given by `--network`, if any.
+fn set_network(params: &params::KeySchemeParams) -> error::Result<()> {
+	if let Some(network) = &params.network {
+		let format = Ss58AddressFormat::try_from(network.as_str())
+			.map_err(|_| error::Error::Input(format!("Invalid network: {}", network)))?;
+		set_default_ss58_version(format);
+	}
+	Ok(())
+}
+
 fn base_path(cli: &SharedParams, version: &VersionInfo) -> PathBuf {
 	cli.base_path.clone()
 		.unwrap_or_else(||
@@ -190,11 +224,11 @@ pub fn parse_and_execute<'a, F, CC, RP, S, RS, E, I, T>(
 ) -> error::Result<Option<CC>>
 where
 	F: ServiceFactory,
-	S: FnOnce(&str) -> Result<Option<ChainSpec<FactoryGenesis<F>>>, String>,
+	S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
 	CC: StructOpt + Clone + GetLogFilter,
 	RP: StructOpt + Clone + AugmentClap,
 	E: IntoExit,
-	RS: FnOnce(E, RunCmd, RP, FactoryFullConfiguration<F>) -> Result<(), String>,
+	RS: FnOnce(E, RunCmd, RP, FactoryFullConfiguration<F>) -> error::Result<()>,
 	I: IntoIterator<Item = T>,
 	T: Into<std::ffi::OsString> + Clone,
 {
@@ -216,7 +250,10 @@ where
 		.get_matches_from(args);
 	let cli_args = CoreParams::<CC, RP>::from_clap(&matches);
 
-	init_logger(cli_args.get_log_filter().as_ref().map(|v| v.as_ref()).unwrap_or(""));
+	init_logger(
+		cli_args.get_log_filter().as_ref().map(|v| v.as_ref()).unwrap_or(""),
+		cli_args.get_log_format().unwrap_or(params::LogFormat::Human),
+	);
 	fdlimit::raise_fd_limit();
 
 	match cli_args {
@@ -233,6 +270,21 @@ where
 			purge_chain::<F, _>(params, spec_factory, version).map(|_| None),
 		params::CoreParams::Revert(params) =>
 			revert_chain::<F, _>(params, spec_factory, version).map(|_| None),
+		params::CoreParams::ForkOff(params) =>
+			fork_off::<F, _>(params, spec_factory, version).map(|_| None),
+		params::CoreParams::TryRuntime(params) =>
+			try_runtime::<F, _>(params, spec_factory, version).map(|_| None),
+		params::CoreParams::StateDiff(params) =>
+			state_diff::<F, _>(params, spec_factory, version).map(|_| None),
+		params::CoreParams::Key(params) =>
+			key::<F, _>(params, spec_factory, version).map(|_| None),
+		params::CoreParams::Benchmark(params) =>
+			benchmark::<F, _>(params, spec_factory, version).map(|_| None),
+		params::CoreParams::Inspect(params) =>
+			inspect::<F, _>(params, spec_factory, version).map(|_| None),
+		params::CoreParams::Sign(params) =>
+			sign::<F, _>(params, spec_factory, version).map(|_| None),
+		params::CoreParams::Verify(params) => verify(params).map(|_| None),
 		params::CoreParams::Custom(params) => Ok(Some(params)),
 	}
 }
@@ -305,6 +357,8 @@ fn fill_transaction_pool_configuration<F: ServiceFactory>(
 	options.transaction_pool.future.count = params.pool_limit / factor;
 	options.transaction_pool.future.total_bytes = params.pool_kbytes * 1024 / factor;
 
+	options.transaction_pool.per_sender = params.pool_per_sender;
+
 	Ok(())
 }
 
@@ -366,18 +420,40 @@ fn input_keystore_password() -> Result<String, String> {
 		.map_err(|e| format!("{:?}", e))
 }
 
+/// Get the keystore password from the terminal, a password file or the command line, in this
+/// order. Defaults to the empty password.
+fn keystore_password(
+	interactive: bool,
+	password_filename: Option<&PathBuf>,
+	password: Option<&String>,
+) -> error::Result<String> {
+	if interactive {
+		Ok(input_keystore_password()?)
+	} else if let Some(filename) = password_filename {
+		let password = fs::read_to_string(filename)
+			.map_err(|e| error::Error::Input(
+				format!("Failed to read password file {}: {}", filename.display(), e)
+			))?;
+		Ok(password.trim_end_matches(|c| c == '\n' || c == '\r').to_string())
+	} else {
+		Ok(password.cloned().unwrap_or_default())
+	}
+}
+
 fn create_run_node_config<F, S>(
 	cli: RunCmd, spec_factory: S, impl_name: &'static str, version: &VersionInfo
 ) -> error::Result<FactoryFullConfiguration<F>>
 where
 	F: ServiceFactory,
-	S: FnOnce(&str) -> Result<Option<ChainSpec<FactoryGenesis<F>>>, String>,
+	S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
 {
 	let spec = load_spec(&cli.shared_params, spec_factory)?;
 	let mut config = service::Configuration::default_with_spec(spec.clone());
-	if cli.interactive_password {
-		config.password = input_keystore_password()?.into()
-	}
+	config.password = keystore_password(
+		cli.interactive_password,
+		cli.password_filename.as_ref(),
+		cli.password.as_ref(),
+	)?.into();
 
 	config.impl_name = impl_name;
 	config.impl_commit = version.commit;
@@ -402,10 +478,12 @@ where
 	let base_path = base_path(&cli.shared_params, version);
 
 	config.keystore_path = cli.keystore_path.or_else(|| Some(keystore_path(&base_path, config.chain_spec.id())));
+	config.keystore_uri = cli.keystore_uri;
 
 	config.database_path = db_path(&base_path, config.chain_spec.id());
 	config.database_cache_size = cli.database_cache_size;
 	config.state_cache_size = cli.state_cache_size;
+	config.trie_node_cache_size = cli.trie_node_cache_size;
 	config.pruning = match cli.pruning {
 		Some(ref s) if s == "archive" => PruningMode::ArchiveAll,
 		None => PruningMode::default(),
@@ -413,6 +491,7 @@ where
 			s.parse().map_err(|_| error::Error::Input("Invalid pruning mode specified".to_string()))?
 		),
 	};
+	config.prune_headers = cli.prune_headers;
 
 	let role =
 		if cli.light {
@@ -432,6 +511,9 @@ where
 		offchain_worker: exec_all_or(exec.execution_offchain_worker),
 		other: exec_all_or(exec.execution_other),
 	};
+	config.wasm_method = cli.wasm_method.into();
+	config.default_heap_pages = cli.default_heap_pages.map(Into::into);
+	config.max_memory_pages = cli.max_memory_pages;
 
 	config.offchain_worker = match (cli.offchain_worker, role) {
 		(params::OffchainWorkerEnabled::WhenValidating, service::Roles::AUTHORITY) => true,
@@ -439,6 +521,11 @@ where
 		(params::OffchainWorkerEnabled::Never, _) => false,
 		(params::OffchainWorkerEnabled::WhenValidating, _) => false,
 	};
+	config.offchain_worker_best_only = match cli.offchain_worker_trigger {
+		params::OffchainWorkerTrigger::EveryBlock => false,
+		params::OffchainWorkerTrigger::NewBestBlock => true,
+	};
+	config.offchain_worker_max_concurrency = cli.offchain_worker_max_concurrency;
 
 	config.roles = role;
 	config.disable_grandpa = cli.no_grandpa;
@@ -484,6 +571,20 @@ where
 		parse_address(&format!("{}:{}", ws_interface, 9944), cli.ws_port)?
 	);
 	config.rpc_ws_max_connections = cli.ws_max_connections;
+	config.rpc_limits = service::RpcLimits {
+		calls_per_second: cli.rpc_rate_limit,
+		max_response_size: cli.rpc_max_response_size,
+		restricted_methods: cli.rpc_restricted_methods,
+		allow_unsafe: cli.rpc_allow_unsafe,
+	};
+	config.rpc_auth_token = cli.rpc_auth_token;
+	config.rpc_trust_proxy = cli.rpc_trust_proxy;
+	if let Some(port) = cli.prometheus_port {
+		let prometheus_interface: &str = if cli.prometheus_external { "0.0.0.0" } else { "127.0.0.1" };
+		config.prometheus_endpoint = Some(
+			parse_address(&format!("{}:{}", prometheus_interface, port), None)?
+		);
+	}
 	config.rpc_cors = cli.rpc_cors.unwrap_or_else(|| if is_dev {
 		log::warn!("Running in --dev mode, RPC CORS has been disabled.");
 		Cors::All
@@ -523,12 +624,32 @@ where
 	RP: StructOpt + Clone,
 	F: ServiceFactory,
 	E: IntoExit,
-	S: FnOnce(&str) -> Result<Option<ChainSpec<FactoryGenesis<F>>>, String>,
-	RS: FnOnce(E, RunCmd, RP, FactoryFullConfiguration<F>) -> Result<(), String>,
+	S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
+	RS: FnOnce(E, RunCmd, RP, FactoryFullConfiguration<F>) -> error::Result<()>,
  {
 	let config = create_run_node_config::<F, _>(cli.left.clone(), spec_factory, impl_name, version)?;
 
-	run_service(exit, cli.left, cli.right, config).map_err(Into::into)
+	run_service(exit, cli.left, cli.right, config)
+}
+
+/// Shut `runtime` down once all of its tasks finished, giving up after `timeout`.
+///
+/// Tasks that are still running after `timeout` are abandoned and `Error::ShutdownTimeout` is
+/// returned.
+pub fn shutdown_runtime(runtime: tokio::runtime::Runtime, timeout: Duration) -> error::Result<()> {
+	let (done_send, done) = std::sync::mpsc::channel();
+	std::thread::spawn(move || {
+		let _ = runtime.shutdown_on_idle().wait();
+		let _ = done_send.send(());
+	});
+
+	match done.recv_timeout(timeout) {
+		Ok(()) => Ok(()),
+		Err(_) => {
+			warn!("Tasks didn't finish within {} seconds, exiting anyway", timeout.as_secs());
+			Err(error::Error::ShutdownTimeout)
+		},
+	}
 }
 
 //
@@ -540,7 +661,7 @@ where
 // 9926-9949		Unassigned
 
 fn with_default_boot_node<F>(
-	spec: &mut ChainSpec<FactoryGenesis<F>>,
+	spec: &mut FactoryChainSpec<F>,
 	cli: BuildSpecCmd,
 	version: &VersionInfo,
 ) -> error::Result<()>
@@ -570,13 +691,23 @@ fn build_spec<F, S>(
 ) -> error::Result<()>
 where
 	F: ServiceFactory,
-	S: FnOnce(&str) -> Result<Option<ChainSpec<FactoryGenesis<F>>>, String>,
+	S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
 {
 	info!("Building chain spec");
-	let raw_output = cli.raw;
+	let mut raw_output = cli.raw;
 	let mut spec = load_spec(&cli.shared_params, spec_factory)?;
-	with_default_boot_node::<F>(&mut spec, cli, version)?;
-	let json = service::chain_ops::build_spec::<FactoryGenesis<F>>(spec, raw_output)?;
+
+	if let Some(path) = &cli.storage_overrides {
+		let overrides = serde_json::from_reader(File::open(path)?)
+			.map_err(|e| error::Error::Input(format!("Error parsing storage overrides: {}", e)))?;
+		spec.apply_storage_overrides(overrides)?;
+		raw_output = true;
+	}
+
+	if !cli.disable_default_bootnode {
+		with_default_boot_node::<F>(&mut spec, cli, version)?;
+	}
+	let json = service::chain_ops::build_spec(spec, raw_output)?;
 
 	print!("{}", json);
 
@@ -589,7 +720,7 @@ pub fn create_config_with_db_path<F, S>(
 ) -> error::Result<FactoryFullConfiguration<F>>
 where
 	F: ServiceFactory,
-	S: FnOnce(&str) -> Result<Option<ChainSpec<FactoryGenesis<F>>>, String>,
+	S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
 {
 	let spec = load_spec(cli, spec_factory)?;
 	let base_path = base_path(cli, version);
@@ -609,7 +740,7 @@ fn export_blocks<F, E, S>(
 where
 	F: ServiceFactory,
 	E: IntoExit,
-	S: FnOnce(&str) -> Result<Option<ChainSpec<FactoryGenesis<F>>>, String>,
+	S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
 {
 	let config = create_config_with_db_path::<F, _>(spec_factory, &cli.shared_params, version)?;
 
@@ -642,7 +773,7 @@ fn import_blocks<F, E, S>(
 where
 	F: ServiceFactory,
 	E: IntoExit,
-	S: FnOnce(&str) -> Result<Option<ChainSpec<FactoryGenesis<F>>>, String>,
+	S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
 {
 	let mut config = create_config_with_db_path::<F, _>(spec_factory, &cli.shared_params, version)?;
 	config.execution_strategies = ExecutionStrategies {
@@ -650,6 +781,9 @@ where
 		other: cli.execution.into(),
 		..Default::default()
 	};
+	config.wasm_method = cli.wasm_method.into();
+	config.default_heap_pages = cli.default_heap_pages.map(Into::into);
+	config.max_memory_pages = cli.max_memory_pages;
 
 	let file: Box<dyn ReadPlusSeek> = match cli.input {
 		Some(filename) => Box::new(File::open(filename)?),
@@ -672,13 +806,114 @@ fn revert_chain<F, S>(
 ) -> error::Result<()>
 where
 	F: ServiceFactory,
-	S: FnOnce(&str) -> Result<Option<ChainSpec<FactoryGenesis<F>>>, String>,
+	S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
 {
 	let config = create_config_with_db_path::<F, _>(spec_factory, &cli.shared_params, version)?;
 	let blocks = cli.num;
 	Ok(service::chain_ops::revert_chain::<F>(config, blocks.into())?)
 }
 
+fn fork_off<F, S>(
+	cli: ForkOffCmd,
+	spec_factory: S,
+	version: &VersionInfo,
+) -> error::Result<()>
+where
+	F: ServiceFactory,
+	S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
+{
+	let config = create_config_with_db_path::<F, _>(spec_factory, &cli.shared_params, version)?;
+	let overrides = match &cli.storage_overrides {
+		Some(path) => serde_json::from_reader(File::open(path)?)
+			.map_err(|e| error::Error::Input(format!("Error parsing storage overrides: {}", e)))?,
+		None => Default::default(),
+	};
+	let json = service::chain_ops::fork_off::<F>(config, cli.at.map(Into::into), overrides)?;
+
+	print!("{}", json);
+
+	Ok(())
+}
+
+fn try_runtime<F, S>(
+	cli: TryRuntimeCmd,
+	spec_factory: S,
+	version: &VersionInfo,
+) -> error::Result<()>
+where
+	F: ServiceFactory,
+	S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
+{
+	let config = create_config_with_db_path::<F, _>(spec_factory, &cli.shared_params, version)?;
+	let code = fs::read(&cli.runtime)?;
+	let result = service::chain_ops::try_runtime::<F>(config, cli.at.map(Into::into), code)?;
+
+	for change in &result.changes {
+		print_storage_change(change);
+	}
+	info!(
+		"Runtime upgrade changed {} storage entries in {} ms, initializing a block takes {} ms without it",
+		result.changes.len(),
+		result.nanos / 1_000_000,
+		result.baseline_nanos / 1_000_000,
+	);
+	match result.weight {
+		Some(weight) => info!("Runtime upgrade registered a weight of {}", weight),
+		None => info!("Runtime upgrade registered no weight"),
+	}
+
+	Ok(())
+}
+
+fn state_diff<F, S>(
+	cli: StateDiffCmd,
+	spec_factory: S,
+	version: &VersionInfo,
+) -> error::Result<()>
+where
+	F: ServiceFactory,
+	S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
+{
+	let config = create_config_with_db_path::<F, _>(spec_factory, &cli.shared_params, version)?;
+	let diff = service::chain_ops::state_diff::<F>(config, cli.from.into(), cli.to.map(Into::into))?;
+
+	for (item, changes) in &diff.items {
+		match item {
+			Some(item) => match &item.key_type {
+				Some(key_type) => println!("{} {}: map {} => {}", item.module, item.name, key_type, item.value_type),
+				None => println!("{} {}: {}", item.module, item.name, item.value_type),
+			},
+			None => println!("Unknown items:"),
+		}
+		for change in changes {
+			print!("  ");
+			print_storage_change(change);
+		}
+	}
+	info!(
+		"{} storage entries of {} storage items changed",
+		diff.items.values().map(Vec::len).sum::<usize>(),
+		diff.items.keys().filter(|item| item.is_some()).count(),
+	);
+
+	Ok(())
+}
+
+/// Print a storage change as `[0x<child>] 0x<key>: <before> -> <after>`.
+fn print_storage_change(change: &service::chain_ops::StorageChange) {
+	let hex = |data: &Option<primitives::storage::StorageData>| data.as_ref()
+		.map_or_else(|| "none".into(), |data| format!("0x{}", HexDisplay::from(&data.0)));
+	if let Some(child) = &change.child {
+		print!("0x{} ", HexDisplay::from(&child.0));
+	}
+	println!(
+		"0x{}: {} -> {}",
+		HexDisplay::from(&change.key.0),
+		hex(&change.before),
+		hex(&change.after),
+	);
+}
+
 fn purge_chain<F, S>(
 	cli: PurgeChainCmd,
 	spec_factory: S,
@@ -686,7 +921,7 @@ fn purge_chain<F, S>(
 ) -> error::Result<()>
 where
 	F: ServiceFactory,
-	S: FnOnce(&str) -> Result<Option<ChainSpec<FactoryGenesis<F>>>, String>,
+	S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
 {
 	let config = create_config_with_db_path::<F, _>(spec_factory, &cli.shared_params, version)?;
 	let db_path = config.database_path;
@@ -721,6 +956,412 @@ where
 	}
 }
 
+fn key<F, S>(
+	cli: KeySubcommand,
+	spec_factory: S,
+	version: &VersionInfo,
+) -> error::Result<()>
+where
+	F: ServiceFactory,
+	S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
+{
+	match cli {
+		KeySubcommand::Generate(cmd) => generate_key(cmd),
+		KeySubcommand::Inspect(cmd) => inspect_key(cmd),
+		KeySubcommand::Insert(cmd) => insert_key::<F, _>(cmd, spec_factory, version),
+	}
+}
+
+fn generate_key(cli: GenerateKeyCmd) -> error::Result<()> {
+	let words = MnemonicType::for_word_count(cli.words).map_err(|_| error::Error::Input(
+		"Invalid number of words given for phrase: must be 12/15/18/21/24".into()
+	))?;
+	let mnemonic = Mnemonic::new(words, Language::English);
+	let password = cli.scheme_params.password.as_ref().map(String::as_str);
+	set_network(&cli.scheme_params)?;
+
+	match cli.scheme_params.scheme {
+		CryptoScheme::Ed25519 => print_key::<ed25519::Pair>(mnemonic.phrase(), password),
+		CryptoScheme::Sr25519 => print_key::<sr25519::Pair>(mnemonic.phrase(), password),
+		CryptoScheme::Ecdsa => print_key::<ecdsa::Pair>(mnemonic.phrase(), password),
+	}
+}
+
+fn inspect_key(cli: InspectKeyCmd) -> error::Result<()> {
+	let password = cli.scheme_params.password.as_ref().map(String::as_str);
+	set_network(&cli.scheme_params)?;
+
+	match cli.scheme_params.scheme {
+		CryptoScheme::Ed25519 => print_key::<ed25519::Pair>(&cli.uri, password),
+		CryptoScheme::Sr25519 => print_key::<sr25519::Pair>(&cli.uri, password),
+		CryptoScheme::Ecdsa => print_key::<ecdsa::Pair>(&cli.uri, password),
+	}
+}
+
+/// Print the secret and public forms of the key given by `uri`, which is either a secret
+/// phrase, a secret key URI or a public key URI.
+fn print_key<P: Pair>(uri: &str, password: Option<&str>) -> error::Result<()>
+	where P::Public: Ss58Codec
+{
+	if let Ok((pair, seed)) = P::from_phrase(uri, password) {
+		println!("Secret phrase `{}` is account:\n  Secret seed: 0x{}\n  Public key (hex): 0x{}\n  Address (SS58): {}",
+			uri,
+			HexDisplay::from(&seed.as_ref()),
+			HexDisplay::from(&pair.public().as_slice()),
+			pair.public().to_ss58check(),
+		);
+	} else if let Ok(pair) = P::from_string(uri, password) {
+		println!("Secret Key URI `{}` is account:\n  Public key (hex): 0x{}\n  Address (SS58): {}",
+			uri,
+			HexDisplay::from(&pair.public().as_slice()),
+			pair.public().to_ss58check(),
+		);
+	} else if let Ok(public) = P::Public::from_string(uri) {
+		println!("Public Key URI `{}` is account:\n  Public key (hex): 0x{}\n  Address (SS58): {}",
+			uri,
+			HexDisplay::from(&public.as_slice()),
+			public.to_ss58check(),
+		);
+	} else {
+		return Err(error::Error::Input(format!("Invalid phrase/URI given: {}", uri)));
+	}
+
+	Ok(())
+}
+
+fn insert_key<F, S>(
+	cli: InsertKeyCmd,
+	spec_factory: S,
+	version: &VersionInfo,
+) -> error::Result<()>
+where
+	F: ServiceFactory,
+	S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
+{
+	if cli.scheme_params.password.is_some() {
+		return Err(error::Error::Input(
+			"Keys in the keystore use the keystore password, see --keystore-password".into()
+		));
+	}
+	let spec = load_spec(&cli.shared_params, spec_factory)?;
+	set_network(&cli.scheme_params)?;
+	let path = cli.keystore_path.unwrap_or_else(||
+		keystore_path(&base_path(&cli.shared_params, version), spec.id())
+	);
+	let password = keystore_password(
+		cli.interactive_password,
+		cli.password_filename.as_ref(),
+		cli.keystore_password.as_ref(),
+	)?;
+
+	let store = keystore::Store::open(path.clone(), password.into())
+		.map_err(|e| error::Error::Input(format!("Failed to open keystore: {}", e)))?;
+	let suri = &cli.suri;
+	let public = match cli.scheme_params.scheme {
+		CryptoScheme::Ed25519 => store.insert::<ed25519::Pair>(suri)
+			.map(|pair| pair.public().to_ss58check()),
+		CryptoScheme::Sr25519 => store.insert::<sr25519::Pair>(suri)
+			.map(|pair| pair.public().to_ss58check()),
+		CryptoScheme::Ecdsa => store.insert::<ecdsa::Pair>(suri)
+			.map(|pair| pair.public().to_ss58check()),
+	}.map_err(|e| error::Error::Input(format!("Failed to insert key: {}", e)))?;
+
+	info!("Inserted key {} into {}", public, path.display());
+	Ok(())
+}
+
+fn read_message(params: &MessageParams) -> error::Result<Vec<u8>> {
+	let mut message = match &params.message {
+		Some(message) => message.as_bytes().to_vec(),
+		None => {
+			let mut message = Vec::new();
+			stdin().lock().read_to_end(&mut message)?;
+			message
+		},
+	};
+	if params.hex {
+		let hex_message = String::from_utf8_lossy(&message).trim().to_string();
+		let hex_message = if hex_message.starts_with("0x") { &hex_message[2..] } else { &hex_message[..] };
+		message = hex::decode(hex_message)
+			.map_err(|e| error::Error::Input(format!("Invalid hex encoded message: {}", e)))?;
+	}
+	Ok(message)
+}
+
+/// Parse a public key given either as SS58 address or hex string.
+fn parse_public<P: Public + Ss58Codec + Default>(public: &str) -> error::Result<P> {
+	if let Ok(public) = P::from_string(public) {
+		return Ok(public);
+	}
+
+	let hex_public = if public.starts_with("0x") { &public[2..] } else { public };
+	match hex::decode(hex_public) {
+		Ok(ref bytes) if bytes.len() == P::default().as_ref().len() => Ok(P::from_slice(bytes)),
+		_ => Err(error::Error::Input(format!("Invalid public key: {}", public))),
+	}
+}
+
+fn sign<F, S>(
+	cli: SignCmd,
+	spec_factory: S,
+	version: &VersionInfo,
+) -> error::Result<()>
+where
+	F: ServiceFactory,
+	S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
+{
+	fn sign_with<P: Pair, F, S>(
+		cli: &SignCmd,
+		spec_factory: S,
+		version: &VersionInfo,
+		message: &[u8],
+	) -> error::Result<Vec<u8>>
+	where
+		P::Public: Ss58Codec + Default,
+		P::Signature: AsRef<[u8]>,
+		F: ServiceFactory,
+		S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
+	{
+		let password = cli.scheme_params.password.as_ref().map(String::as_str);
+		let pair = match (&cli.suri, &cli.public) {
+			(Some(suri), _) => P::from_string(suri, password)
+				.map_err(|_| error::Error::Input("Invalid secret key URI".into()))?,
+			(None, Some(public)) => {
+				let public = parse_public::<P::Public>(public)?;
+				let spec = load_spec(&cli.shared_params, spec_factory)?;
+				let path = cli.keystore_path.clone().unwrap_or_else(||
+					keystore_path(&base_path(&cli.shared_params, version), spec.id())
+				);
+				let keystore_password = keystore_password(
+					cli.interactive_password,
+					cli.password_filename.as_ref(),
+					cli.keystore_password.as_ref(),
+				)?;
+				keystore::Store::open(path, keystore_password.into())
+					.and_then(|store| store.load::<P>(&public))
+					.map_err(|e| error::Error::Input(format!("Failed to load key: {}", e)))?
+			},
+			(None, None) => return Err(error::Error::Input("Either --suri or --public is required".into())),
+		};
+		Ok(pair.sign(message).as_ref().to_vec())
+	}
+
+	let message = read_message(&cli.message_params)?;
+	let signature = match cli.scheme_params.scheme {
+		CryptoScheme::Ed25519 => sign_with::<ed25519::Pair, F, _>(&cli, spec_factory, version, &message)?,
+		CryptoScheme::Sr25519 => sign_with::<sr25519::Pair, F, _>(&cli, spec_factory, version, &message)?,
+		CryptoScheme::Ecdsa => sign_with::<ecdsa::Pair, F, _>(&cli, spec_factory, version, &message)?,
+	};
+	println!("0x{}", HexDisplay::from(&signature));
+	Ok(())
+}
+
+fn verify(cli: VerifyCmd) -> error::Result<()> {
+	fn verify_with<P: Pair>(signature: &[u8], message: &[u8], public: &str) -> error::Result<bool>
+		where P::Public: Ss58Codec + Default
+	{
+		let public = parse_public::<P::Public>(public)?;
+		Ok(P::verify_weak(signature, message, public))
+	}
+
+	let message = read_message(&cli.message_params)?;
+	let hex_signature = if cli.signature.starts_with("0x") { &cli.signature[2..] } else { &cli.signature[..] };
+	let signature = hex::decode(hex_signature)
+		.map_err(|e| error::Error::Input(format!("Invalid hex encoded signature: {}", e)))?;
+
+	let valid = match cli.scheme_params.scheme {
+		CryptoScheme::Ed25519 => verify_with::<ed25519::Pair>(&signature, &message, &cli.public)?,
+		CryptoScheme::Sr25519 => verify_with::<sr25519::Pair>(&signature, &message, &cli.public)?,
+		CryptoScheme::Ecdsa => verify_with::<ecdsa::Pair>(&signature, &message, &cli.public)?,
+	};
+
+	if valid {
+		println!("Signature verifies correctly.");
+		Ok(())
+	} else {
+		Err(error::Error::Input("Signature invalid.".into()))
+	}
+}
+
+fn benchmark<F, S>(
+	cli: BenchmarkCmd,
+	spec_factory: S,
+	version: &VersionInfo,
+) -> error::Result<()>
+where
+	F: ServiceFactory,
+	S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
+{
+	let config = create_config_with_db_path::<F, _>(spec_factory, &cli.shared_params, version)?;
+	info!("DB path: {}", config.database_path.display());
+
+	let mut benchmarked = HashSet::new();
+	for module in &cli.module {
+		let mut weights = format!(
+			"//! Weights for the `{}` module, generated by the `benchmark` subcommand.\n\n\
+			use sr_primitives::weights::Weight;\n",
+			module,
+		);
+		for extrinsic in &cli.extrinsic {
+			let benchmark = service::chain_ops::benchmark::<F>(
+				config.clone(),
+				module,
+				extrinsic,
+				cli.steps,
+				cli.repeat,
+				cli.execution.into(),
+			)?;
+			if let Some((ranges, results)) = benchmark {
+				info!("Benchmarked {}::{}", module, extrinsic);
+				weights.push_str(&weight_fn(extrinsic, &ranges, &results));
+				benchmarked.insert(extrinsic);
+			}
+		}
+
+		match cli.output {
+			Some(ref directory) => File::create(directory.join(format!("{}.rs", module)))?
+				.write_all(weights.as_bytes())?,
+			None => print!("{}", weights),
+		}
+	}
+
+	match cli.extrinsic.iter().find(|extrinsic| !benchmarked.contains(extrinsic)) {
+		Some(extrinsic) => Err(error::Error::Input(format!(
+			"No benchmark for {} in the modules {}",
+			extrinsic,
+			cli.module.join(", "),
+		))),
+		None => Ok(()),
+	}
+}
+
+/// Fit the execution time of each component linearly and render the result as a weight function.
+///
+/// The slope of a component is taken from the measurements in which only this component varied.
+fn weight_fn(
+	extrinsic: &str,
+	ranges: &[benchmarking::ComponentRange],
+	results: &[service::chain_ops::BenchmarkResult],
+) -> String {
+	let value = |r: &service::chain_ops::BenchmarkResult, index: usize| r.components[index].1 as f64;
+	let mut slopes = Vec::with_capacity(ranges.len());
+	let mut base = None;
+	for (index, range) in ranges.iter().enumerate() {
+		let points: Vec<_> = results.iter()
+			.filter(|r| r.components.iter().enumerate()
+				.all(|(i, (_, v))| i == index || *v == ranges[i].high))
+			.map(|r| (value(r, index), r.nanos as f64))
+			.collect();
+		let n = points.len() as f64;
+		let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+		let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+		let variance = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum::<f64>();
+		let slope = if variance > 0.0 {
+			(points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum::<f64>() / variance).max(0.0)
+		} else {
+			0.0
+		};
+		slopes.push((range.name.clone(), slope));
+		if base.is_none() {
+			base = Some(mean_y - slope * mean_x);
+		}
+	}
+
+	let base = match base {
+		// The intercept of the first component still contains all others at their highest value.
+		Some(intercept) => slopes.iter().zip(ranges).skip(1)
+			.fold(intercept, |base, ((_, slope), range)| base - slope * range.high as f64),
+		None => results.iter().map(|r| r.nanos as f64).sum::<f64>() / results.len().max(1) as f64,
+	};
+
+	let args = slopes.iter()
+		.map(|(name, _)| format!("{}: u32", String::from_utf8_lossy(name)))
+		.collect::<Vec<_>>()
+		.join(", ");
+	let mut body = format!("\t({} as Weight)", base.max(0.0).round() as u64);
+	for (name, slope) in &slopes {
+		body.push_str(&format!(
+			"\n\t\t.saturating_add(({} as Weight).saturating_mul({}))",
+			slope.round() as u64,
+			String::from_utf8_lossy(name),
+		));
+	}
+
+	format!("\n/// Weight of `{}` in nanoseconds.\npub fn {}({}) -> Weight {{\n{}\n}}\n", extrinsic, extrinsic, args, body)
+}
+
+/// What the `inspect` command prints.
+#[derive(Debug, PartialEq)]
+enum Inspected<B: BlockT> {
+	/// The block with the given id, read from the database.
+	Block(BlockId<B>),
+	/// A decoded extrinsic.
+	Extrinsic(B::Extrinsic),
+}
+
+/// Parse the input of the `inspect` command.
+///
+/// A number is a block number and a `0x` prefixed 32 byte hex string a block hash, unless
+/// `extrinsic` is set. Everything else is a hex encoded extrinsic.
+fn parse_inspect_input<B: BlockT>(input: &str, extrinsic: bool) -> error::Result<Inspected<B>> {
+	let input = input.trim();
+	let hex_input = if input.starts_with("0x") { &input[2..] } else { input };
+
+	if !extrinsic {
+		if let Ok(number) = input.parse::<u32>() {
+			return Ok(Inspected::Block(BlockId::Number(number.into())));
+		}
+		if input.starts_with("0x") && hex_input.len() == 64 {
+			let bytes = hex::decode(hex_input)
+				.map_err(|e| error::Error::Input(format!("Invalid block hash: {}", e)))?;
+			let hash = B::Hash::decode(&mut &bytes[..])
+				.map_err(|e| error::Error::Input(format!("Invalid block hash: {}", e.what())))?;
+			return Ok(Inspected::Block(BlockId::Hash(hash)));
+		}
+	}
+
+	let bytes = hex::decode(hex_input)
+		.map_err(|e| error::Error::Input(format!("Invalid hex encoded extrinsic: {}", e)))?;
+	let extrinsic = B::Extrinsic::decode(&mut &bytes[..])
+		.map_err(|e| error::Error::Input(format!("Unable to decode extrinsic: {}", e.what())))?;
+	Ok(Inspected::Extrinsic(extrinsic))
+}
+
+fn inspect<F, S>(
+	cli: InspectCmd,
+	spec_factory: S,
+	version: &VersionInfo,
+) -> error::Result<()>
+where
+	F: ServiceFactory,
+	S: FnOnce(&str) -> Result<Option<FactoryChainSpec<F>>, String>,
+{
+	let id = match parse_inspect_input::<F::Block>(&cli.input, cli.extrinsic)? {
+		Inspected::Block(id) => id,
+		Inspected::Extrinsic(extrinsic) => {
+			// Only loaded to print addresses in the SS58 format of the chain.
+			load_spec(&cli.shared_params, spec_factory)?;
+			println!("{:#?}", extrinsic);
+			return Ok(());
+		},
+	};
+
+	let config = create_config_with_db_path::<F, _>(spec_factory, &cli.shared_params, version)?;
+	let client = service::new_client::<F>(&config)?;
+	let block = client.block(&id)?
+		.ok_or_else(|| error::Error::Input(format!("Block {} not found", id)))?;
+	let (header, extrinsics) = block.block.deconstruct();
+	println!("Block {}:\n{:#?}", id, header);
+	for (index, extrinsic) in extrinsics.iter().enumerate() {
+		println!("Extrinsic #{}:\n{:#?}", index, extrinsic);
+	}
+	if let Some(justification) = block.justification {
+		println!("Justification: 0x{}", HexDisplay::from(&justification));
+	}
+
+	Ok(())
+}
+
 fn parse_address(
 	address: &str,
 	port: Option<u16>,
@@ -759,67 +1400,8 @@ fn network_path(base_path: &Path, chain_id: &str) -> PathBuf {
 	path
 }
 
-fn init_logger(pattern: &str) {
-	use ansi_term::Colour;
-
-	let mut builder = env_logger::Builder::new();
-	// Disable info logging by default for some modules:
-	builder.filter(Some("ws"), log::LevelFilter::Off);
-	builder.filter(Some("hyper"), log::LevelFilter::Warn);
-	// Enable info for others.
-	builder.filter(None, log::LevelFilter::Info);
-
-	if let Ok(lvl) = std::env::var("RUST_LOG") {
-		builder.parse_filters(&lvl);
-	}
-
-	builder.parse_filters(pattern);
-	let isatty = atty::is(atty::Stream::Stderr);
-	let enable_color = isatty;
-
-	builder.format(move |buf, record| {
-		let now = time::now();
-		let timestamp =
-			time::strftime("%Y-%m-%d %H:%M:%S", &now)
-				.expect("Error formatting log timestamp");
-
-		let mut output = if log::max_level() <= log::LevelFilter::Info {
-			format!("{} {}", Colour::Black.bold().paint(timestamp), record.args())
-		} else {
-			let name = ::std::thread::current()
-				.name()
-				.map_or_else(Default::default, |x| format!("{}", Colour::Blue.bold().paint(x)));
-			let millis = (now.tm_nsec as f32 / 1000000.0).round() as usize;
-			let timestamp = format!("{}.{:03}", timestamp, millis);
-			format!(
-				"{} {} {} {}  {}",
-				Colour::Black.bold().paint(timestamp),
-				name,
-				record.level(),
-				record.target(),
-				record.args()
-			)
-		};
-
-		if !enable_color {
-			output = kill_color(output.as_ref());
-		}
-
-		if !isatty && record.level() <= log::Level::Info && atty::is(atty::Stream::Stdout) {
-			// duplicate INFO/WARN output to console
-			println!("{}", output);
-		}
-		writeln!(buf, "{}", output)
-	});
-
-	builder.init();
-}
-
-fn kill_color(s: &str) -> String {
-	lazy_static! {
-		static ref RE: Regex = Regex::new("\x1b\\[[^m]+m").expect("Error initializing color regex");
-	}
-	RE.replace_all(s, "").to_string()
+fn init_logger(pattern: &str, format: params::LogFormat) {
+	substrate_logger::init(pattern, format.into());
 }
 
 #[cfg(test)]
@@ -949,4 +1531,108 @@ mod tests {
 		assert!(no_config_dir().is_ok());
 		assert!(some_config_dir("x".to_string()).is_ok());
 	}
+
+	#[test]
+	fn ss58_format_is_read_from_properties() {
+		let properties = |json: &str| -> service::Properties { serde_json::from_str(json).unwrap() };
+
+		assert!(ss58_format(&properties("{}")).unwrap().is_none());
+		assert!(
+			ss58_format(&properties(r#"{"ss58Format": 2}"#)).unwrap() ==
+				Some(Ss58AddressFormat::KusamaAccountDirect)
+		);
+		assert!(
+			ss58_format(&properties(r#"{"ss58Format": 7}"#)).unwrap() ==
+				Some(Ss58AddressFormat::Custom(7))
+		);
+		assert!(ss58_format(&properties(r#"{"ss58Format": 256}"#)).is_err());
+		assert!(ss58_format(&properties(r#"{"ss58Format": "kusama"}"#)).is_err());
+	}
+
+	#[test]
+	fn ecdsa_public_keys_are_parsed_from_hex_and_ss58() {
+		let public = ecdsa::Pair::from_string("//Alice", None).unwrap().public();
+		let hex_public = format!("0x{}", HexDisplay::from(&public.as_slice()));
+
+		assert!(parse_public::<ecdsa::Public>(&hex_public).unwrap() == public);
+		assert!(parse_public::<ecdsa::Public>(&public.to_ss58check()).unwrap() == public);
+		// A 32 byte key is an ed25519 or sr25519 key.
+		assert!(parse_public::<ecdsa::Public>(&hex_public[..66]).is_err());
+		assert!(parse_public::<sr25519::Public>(&hex_public).is_err());
+	}
+
+	#[test]
+	fn ecdsa_signatures_are_verified() {
+		let pair = ecdsa::Pair::from_string("//Alice", None).unwrap();
+		let verify_cmd = |signature: &[u8], message: &str| VerifyCmd {
+			signature: format!("0x{}", HexDisplay::from(&signature)),
+			public: pair.public().to_ss58check(),
+			message_params: MessageParams { message: Some(message.into()), hex: false },
+			scheme_params: params::KeySchemeParams { scheme: CryptoScheme::Ecdsa, password: None, network: None },
+		};
+		let signature = pair.sign(b"hello");
+
+		assert!(verify(verify_cmd(signature.as_ref(), "hello")).is_ok());
+		assert!(verify(verify_cmd(signature.as_ref(), "goodbye")).is_err());
+	}
+
+	#[test]
+	fn benchmark_takes_several_modules() {
+		let cmd = BenchmarkCmd::from_iter(&[
+			"benchmark", "--module", "system", "--module", "balances", "--extrinsic", "set_storage",
+		]);
+
+		assert_eq!(cmd.module, vec!["system".to_string(), "balances".to_string()]);
+		assert_eq!(cmd.extrinsic, vec!["set_storage".to_string()]);
+		assert!(BenchmarkCmd::from_iter_safe(&["benchmark", "--extrinsic", "set_storage"]).is_err());
+	}
+
+	#[test]
+	fn weights_are_fitted_linearly() {
+		let ranges = vec![
+			benchmarking::ComponentRange { name: b"a".to_vec(), low: 0, high: 10 },
+			benchmarking::ComponentRange { name: b"b".to_vec(), low: 0, high: 10 },
+		];
+		// 100 + 3 * a + 5 * b nanoseconds.
+		let result = |a: u32, b: u32| service::chain_ops::BenchmarkResult {
+			components: vec![(b"a".to_vec(), a), (b"b".to_vec(), b)],
+			nanos: u128::from(100 + 3 * a + 5 * b),
+		};
+		let results: Vec<_> = (0..=10).step_by(5).map(|a| result(a, 10))
+			.chain((0..=10).step_by(5).map(|b| result(10, b)))
+			.collect();
+
+		assert_eq!(
+			weight_fn("transfer", &ranges, &results),
+			"\n/// Weight of `transfer` in nanoseconds.\n\
+			pub fn transfer(a: u32, b: u32) -> Weight {\n\
+			\t(100 as Weight)\n\
+			\t\t.saturating_add((3 as Weight).saturating_mul(a))\n\
+			\t\t.saturating_add((5 as Weight).saturating_mul(b))\n\
+			}\n",
+		);
+	}
+
+	#[test]
+	fn inspect_input_is_a_block_or_an_extrinsic() {
+		use codec::Encode;
+		use sr_primitives::testing::{Block, ExtrinsicWrapper};
+		type TestBlock = Block<ExtrinsicWrapper<u64>>;
+		let parse = |input: &str, extrinsic| parse_inspect_input::<TestBlock>(input, extrinsic);
+		let hash = H256::repeat_byte(0x11);
+		let hash_input = format!(" 0x{} ", HexDisplay::from(hash.as_fixed_bytes()));
+		let xt = format!("0x{}", HexDisplay::from(&42u64.encode()));
+
+		assert_eq!(parse("12", false).unwrap(), Inspected::Block(BlockId::Number(12)));
+		assert_eq!(parse(&hash_input, false).unwrap(), Inspected::Block(BlockId::Hash(hash)));
+		assert_eq!(parse(&xt, false).unwrap(), Inspected::Extrinsic(42.into()));
+		assert_eq!(parse(&xt[2..], false).unwrap(), Inspected::Extrinsic(42.into()));
+		// Too short for an extrinsic once it isn't read as a block number.
+		assert!(parse("12", true).is_err());
+		assert!(parse("0xzz", false).is_err());
+
+		let cmd = InspectCmd::from_iter(&["inspect", "--extrinsic", xt.as_str()]);
+		assert!(cmd.extrinsic);
+		assert_eq!(cmd.input, xt);
+	}
 }
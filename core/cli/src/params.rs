@@ -27,6 +27,10 @@ macro_rules! impl_get_log_filter {
 			fn get_log_filter(&self) -> Option<String> {
 				self.shared_params.get_log_filter()
 			}
+
+			fn get_log_format(&self) -> Option<$crate::params::LogFormat> {
+				self.shared_params.get_log_format()
+			}
 		}
 	}
 }
@@ -40,6 +44,7 @@ arg_enum! {
 		Wasm,
 		Both,
 		NativeElseWasm,
+		BothOrError,
 	}
 }
 
@@ -50,6 +55,43 @@ impl Into<client::ExecutionStrategy> for ExecutionStrategy {
 			ExecutionStrategy::Wasm => client::ExecutionStrategy::AlwaysWasm,
 			ExecutionStrategy::Both => client::ExecutionStrategy::Both,
 			ExecutionStrategy::NativeElseWasm => client::ExecutionStrategy::NativeElseWasm,
+			ExecutionStrategy::BothOrError => client::ExecutionStrategy::BothOrError,
+		}
+	}
+}
+
+arg_enum! {
+	/// How to execute Wasm runtime code
+	#[allow(missing_docs)]
+	#[derive(Debug, Clone, Copy)]
+	pub enum WasmExecutionMethod {
+		Interpreted,
+	}
+}
+
+impl Into<service::WasmExecutionMethod> for WasmExecutionMethod {
+	fn into(self) -> service::WasmExecutionMethod {
+		match self {
+			WasmExecutionMethod::Interpreted => service::WasmExecutionMethod::Interpreted,
+		}
+	}
+}
+
+arg_enum! {
+	/// How to write log lines
+	#[allow(missing_docs)]
+	#[derive(Debug, Clone, Copy)]
+	pub enum LogFormat {
+		Human,
+		Json,
+	}
+}
+
+impl Into<substrate_logger::LogFormat> for LogFormat {
+	fn into(self) -> substrate_logger::LogFormat {
+		match self {
+			LogFormat::Human => substrate_logger::LogFormat::Human,
+			LogFormat::Json => substrate_logger::LogFormat::Json,
 		}
 	}
 }
@@ -64,6 +106,15 @@ arg_enum! {
 	}
 }
 
+arg_enum! {
+	/// At which imported blocks to run offchain workers
+	#[derive(Debug, Clone)]
+	pub enum OffchainWorkerTrigger {
+		EveryBlock,
+		NewBestBlock,
+	}
+}
+
 /// Shared parameters used by all `CoreParams`.
 #[derive(Debug, StructOpt, Clone)]
 pub struct SharedParams {
@@ -82,12 +133,27 @@ pub struct SharedParams {
 	/// Sets a custom logging filter
 	#[structopt(short = "l", long = "log", value_name = "LOG_PATTERN")]
 	pub log: Option<String>,
+
+	/// Sets the format of log lines
+	#[structopt(
+		long = "log-format",
+		value_name = "FORMAT",
+		raw(
+			possible_values = "&LogFormat::variants()",
+			case_insensitive = "true",
+		)
+	)]
+	pub log_format: Option<LogFormat>,
 }
 
 impl GetLogFilter for SharedParams {
 	fn get_log_filter(&self) -> Option<String> {
 		self.log.clone()
 	}
+
+	fn get_log_format(&self) -> Option<LogFormat> {
+		self.log_format
+	}
 }
 
 /// Parameters used to create the network configuration.
@@ -219,6 +285,11 @@ pub struct TransactionPoolParams {
 	/// Maximum number of kilobytes of all transactions stored in the pool.
 	#[structopt(long = "pool-kbytes", value_name = "COUNT", default_value="10240")]
 	pub pool_kbytes: usize,
+	/// Maximum number of pending transactions a single transaction may depend on, 0 for no limit.
+	///
+	/// For account-nonce based runtimes that's the number of pending transactions per sender.
+	#[structopt(long = "pool-per-sender", value_name = "COUNT", default_value = "64")]
+	pub pool_per_sender: usize,
 }
 
 /// Execution strategies parameters.
@@ -306,10 +377,21 @@ pub struct ExecutionStrategies {
 /// The `run` command used to run a node.
 #[derive(Debug, StructOpt, Clone)]
 pub struct RunCmd {
+	/// Maximum number of seconds to wait for the tasks of the node to finish during shutdown.
+	#[structopt(long = "shutdown-timeout", value_name = "SECONDS", default_value = "60")]
+	pub shutdown_timeout: u64,
+
 	/// Specify custom keystore path
 	#[structopt(long = "keystore-path", value_name = "PATH", parse(from_os_str))]
 	pub keystore_path: Option<PathBuf>,
 
+	/// Use an external signer instead of the local keystore.
+	///
+	/// Supported URIs are `unix:///path/to/socket` and `tcp://127.0.0.1:PORT`. The consensus
+	/// engines and the offchain workers sign through the external signer.
+	#[structopt(long = "keystore-uri", value_name = "URI", raw(conflicts_with = r#""keystore_path""#))]
+	pub keystore_uri: Option<String>,
+
 	/// Specify additional key seed
 	#[structopt(long = "key", value_name = "STRING")]
 	pub key: Option<String>,
@@ -339,6 +421,10 @@ pub struct RunCmd {
 	#[structopt(long = "state-cache-size", value_name = "Bytes", default_value = "67108864")]
 	pub state_cache_size: usize,
 
+	/// Specify the size of the cache of trie nodes, shared between block import and RPC (0 disables it)
+	#[structopt(long = "trie-cache-size", value_name = "Bytes", default_value = "67108864")]
+	pub trie_node_cache_size: usize,
+
 	/// Listen to all RPC interfaces (default is local)
 	#[structopt(long = "rpc-external")]
 	pub rpc_external: bool,
@@ -355,6 +441,14 @@ pub struct RunCmd {
 	#[structopt(long = "ws-port", value_name = "PORT")]
 	pub ws_port: Option<u16>,
 
+	/// Expose Prometheus metrics on this TCP port. Metrics are disabled if not given.
+	#[structopt(long = "prometheus-port", value_name = "PORT")]
+	pub prometheus_port: Option<u16>,
+
+	/// Listen to all Prometheus interfaces (default is local)
+	#[structopt(long = "prometheus-external")]
+	pub prometheus_external: bool,
+
 	/// Maximum number of WS RPC server connections.
 	#[structopt(long = "ws-max-connections", value_name = "COUNT")]
 	pub ws_max_connections: Option<usize>,
@@ -367,10 +461,46 @@ pub struct RunCmd {
 	#[structopt(long = "rpc-cors", value_name = "ORIGINS", parse(try_from_str = "parse_cors"))]
 	pub rpc_cors: Option<Cors>,
 
+	/// Maximum number of RPC calls per second a connection may make. All HTTP requests share
+	/// a single limit. Unlimited by default.
+	#[structopt(long = "rpc-rate-limit", value_name = "CALLS")]
+	pub rpc_rate_limit: Option<u32>,
+
+	/// Maximum size of an RPC response in bytes. Unlimited by default.
+	#[structopt(long = "rpc-max-response-size", value_name = "BYTES")]
+	pub rpc_max_response_size: Option<usize>,
+
+	/// Comma-separated list of RPC methods that are rejected on unauthenticated connections.
+	/// Value of `expensive` stands for all methods that are expensive to serve, `unsafe` for all
+	/// methods that change the node.
+	/// Connections to servers listening on a local interface are always authenticated.
+	#[structopt(long = "rpc-restricted-methods", value_name = "METHODS", raw(use_delimiter = "true"))]
+	pub rpc_restricted_methods: Vec<String>,
+
+	/// Allow RPC methods that change the node (e.g. `author_rotateKeys` or
+	/// `system_addReservedPeer`) on unauthenticated connections. Denied by default.
+	#[structopt(long = "rpc-allow-unsafe")]
+	pub rpc_allow_unsafe: bool,
+
+	/// Token that authenticates HTTP RPC requests, passed as `Authorization: Bearer <TOKEN>`,
+	/// and WS connections, passed as `bearer.<TOKEN>` subprotocol.
+	#[structopt(long = "rpc-auth-token", value_name = "TOKEN")]
+	pub rpc_auth_token: Option<String>,
+
+	/// The HTTP RPC server is reached through a reverse proxy that sets the `X-Forwarded-For`
+	/// header. Requests are rate limited per forwarded address instead of all together.
+	#[structopt(long = "rpc-trust-proxy")]
+	pub rpc_trust_proxy: bool,
+
 	/// Specify the pruning mode, a number of blocks to keep or 'archive'. Default is 256.
 	#[structopt(long = "pruning", value_name = "PRUNING_MODE")]
 	pub pruning: Option<String>,
 
+	/// Prune the headers of finalized blocks older than two CHTs (of 2048 blocks each). Their
+	/// hashes can still be proven to light clients, but their headers can't be served.
+	#[structopt(long = "prune-headers")]
+	pub prune_headers: bool,
+
 	/// The human-readable name for this node, as reported to the telemetry server, if enabled
 	#[structopt(long = "name", value_name = "NAME")]
 	pub name: Option<String>,
@@ -398,10 +528,48 @@ pub struct RunCmd {
 	)]
 	pub offchain_worker: OffchainWorkerEnabled,
 
+	/// Should offchain workers run at every imported block or only at new best blocks.
+	#[structopt(
+		long = "offchain-worker-trigger",
+		value_name = "TRIGGER",
+		raw(
+			possible_values = "&OffchainWorkerTrigger::variants()",
+			case_insensitive = "true",
+			default_value = r#""EveryBlock""#
+		)
+	)]
+	pub offchain_worker_trigger: OffchainWorkerTrigger,
+
+	/// Maximum number of offchain workers running at the same time.
+	///
+	/// Offchain workers for blocks imported while this many workers are still running are skipped.
+	#[structopt(long = "offchain-worker-max-concurrency", value_name = "COUNT")]
+	pub offchain_worker_max_concurrency: Option<usize>,
+
 	#[allow(missing_docs)]
 	#[structopt(flatten)]
 	pub execution_strategies: ExecutionStrategies,
 
+	/// Method for executing Wasm runtime code.
+	#[structopt(
+		long = "wasm-execution",
+		value_name = "METHOD",
+		raw(
+			possible_values = "&WasmExecutionMethod::variants()",
+			case_insensitive = "true",
+			default_value = r#""Interpreted""#
+		)
+	)]
+	pub wasm_method: WasmExecutionMethod,
+
+	/// The default number of 64KB pages to ever allocate for Wasm execution. Don't alter this unless you know what you're doing.
+	#[structopt(long = "default-heap-pages", value_name = "COUNT")]
+	pub default_heap_pages: Option<u32>,
+
+	/// The maximum number of 64KB pages of memory, including the heap, a Wasm runtime instance may have.
+	#[structopt(long = "max-memory-pages", value_name = "COUNT")]
+	pub max_memory_pages: Option<u32>,
+
 	#[allow(missing_docs)]
 	#[structopt(flatten)]
 	pub shared_params: SharedParams,
@@ -422,9 +590,30 @@ pub struct RunCmd {
 	#[structopt(long = "force-authoring")]
 	pub force_authoring: bool,
 
-	/// Interactive password for validator key.
-	#[structopt(short = "i")]
+	/// Read the keystore password of the node from the terminal.
+	#[structopt(
+		short = "i",
+		long = "password-interactive",
+		raw(conflicts_with_all = r#"&["password", "password_filename"]"#)
+	)]
 	pub interactive_password: bool,
+
+	/// Password used to encrypt and decrypt the keys of the keystore.
+	#[structopt(
+		long = "password",
+		value_name = "PASSWORD",
+		raw(conflicts_with_all = r#"&["interactive_password", "password_filename"]"#)
+	)]
+	pub password: Option<String>,
+
+	/// File that contains the password used to encrypt and decrypt the keys of the keystore.
+	#[structopt(
+		long = "password-filename",
+		value_name = "PATH",
+		parse(from_os_str),
+		raw(conflicts_with_all = r#"&["interactive_password", "password"]"#)
+	)]
+	pub password_filename: Option<PathBuf>,
 }
 
 /// Stores all required Cli values for a keyring test account.
@@ -560,6 +749,19 @@ pub struct BuildSpecCmd {
 	#[structopt(long = "raw")]
 	pub raw: bool,
 
+	/// Disable adding the default bootnode to the specification.
+	///
+	/// By default the `/ip4/127.0.0.1/tcp/30333/p2p/NODE_PEER_ID` bootnode is added to the
+	/// specification when no bootnode exists.
+	#[structopt(long = "disable-default-bootnode")]
+	pub disable_default_bootnode: bool,
+
+	/// JSON file of hex encoded storage keys mapped to hex encoded values (or `null` to remove
+	/// the key) that are applied to the genesis storage. The key of a child trie is mapped to
+	/// such an object of the overrides of its entries. Implies `--raw`.
+	#[structopt(long = "storage-overrides", value_name = "PATH", parse(from_os_str))]
+	pub storage_overrides: Option<PathBuf>,
+
 	#[allow(missing_docs)]
 	#[structopt(flatten)]
 	pub shared_params: SharedParams,
@@ -586,7 +788,7 @@ pub struct ExportBlocksCmd {
 	#[structopt(long = "to", value_name = "BLOCK")]
 	pub to: Option<u32>,
 
-	/// Use JSON output rather than binary.
+	/// Use JSON output rather than binary. Only the binary format can be read by `import-blocks`.
 	#[structopt(long = "json")]
 	pub json: bool,
 
@@ -608,6 +810,22 @@ pub struct ImportBlocksCmd {
 	#[structopt(long = "default-heap-pages", value_name = "COUNT")]
 	pub default_heap_pages: Option<u32>,
 
+	/// Method for executing Wasm runtime code.
+	#[structopt(
+		long = "wasm-execution",
+		value_name = "METHOD",
+		raw(
+			possible_values = "&WasmExecutionMethod::variants()",
+			case_insensitive = "true",
+			default_value = r#""Interpreted""#
+		)
+	)]
+	pub wasm_method: WasmExecutionMethod,
+
+	/// The maximum number of 64KB pages of memory, including the heap, a Wasm runtime instance may have.
+	#[structopt(long = "max-memory-pages", value_name = "COUNT")]
+	pub max_memory_pages: Option<u32>,
+
 	#[allow(missing_docs)]
 	#[structopt(flatten)]
 	pub shared_params: SharedParams,
@@ -641,6 +859,63 @@ pub struct RevertCmd {
 
 impl_get_log_filter!(RevertCmd);
 
+/// The `fork-off` command used to build a raw chain spec from the state of the local chain.
+#[derive(Debug, StructOpt, Clone)]
+pub struct ForkOffCmd {
+	/// The block whose state becomes the genesis storage. Best block by default.
+	#[structopt(long = "at", value_name = "BLOCK")]
+	pub at: Option<u32>,
+
+	/// JSON file of hex encoded storage keys mapped to hex encoded values (or `null` to remove
+	/// the key) that are applied to the forked storage, e.g. to replace the sudo key or the
+	/// validators. The key of a child trie is mapped to such an object of the overrides of its
+	/// entries.
+	#[structopt(long = "storage-overrides", value_name = "PATH", parse(from_os_str))]
+	pub storage_overrides: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl_get_log_filter!(ForkOffCmd);
+
+/// The `try-runtime` command used to try a runtime upgrade against the state of the local chain.
+#[derive(Debug, StructOpt, Clone)]
+pub struct TryRuntimeCmd {
+	/// The Wasm blob of the new runtime.
+	#[structopt(long = "runtime", value_name = "PATH", parse(from_os_str))]
+	pub runtime: PathBuf,
+
+	/// The block whose state is upgraded. Best block by default.
+	#[structopt(long = "at", value_name = "BLOCK")]
+	pub at: Option<u32>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl_get_log_filter!(TryRuntimeCmd);
+
+/// The `state-diff` command used to compare the state of two blocks of the local chain.
+#[derive(Debug, StructOpt, Clone)]
+pub struct StateDiffCmd {
+	/// The block whose state is the base of the diff.
+	#[structopt(long = "from", value_name = "BLOCK")]
+	pub from: u32,
+
+	/// The block whose state is compared to the base. Best block by default.
+	#[structopt(long = "to", value_name = "BLOCK")]
+	pub to: Option<u32>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl_get_log_filter!(StateDiffCmd);
+
 /// The `purge-chain` command used to remove the whole chain.
 #[derive(Debug, StructOpt, Clone)]
 pub struct PurgeChainCmd {
@@ -655,6 +930,276 @@ pub struct PurgeChainCmd {
 
 impl_get_log_filter!(PurgeChainCmd);
 
+arg_enum! {
+	/// The cryptography scheme of a key.
+	#[allow(missing_docs)]
+	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+	pub enum CryptoScheme {
+		Ed25519,
+		Sr25519,
+		Ecdsa,
+	}
+}
+
+/// Parameters shared by all `key` subcommands.
+#[derive(Debug, StructOpt, Clone)]
+pub struct KeySchemeParams {
+	/// The cryptography scheme of the key.
+	#[structopt(
+		long = "scheme",
+		value_name = "SCHEME",
+		raw(
+			possible_values = "&CryptoScheme::variants()",
+			case_insensitive = "true",
+			default_value = r#""Sr25519""#
+		)
+	)]
+	pub scheme: CryptoScheme,
+
+	/// The password for the secret key URI. Overrides any `///password` given in the URI.
+	///
+	/// Keys in the keystore use the keystore password instead, see `--keystore-password`.
+	#[structopt(long = "password", value_name = "PASSWORD")]
+	pub password: Option<String>,
+
+	/// The SS58 address format used for printing addresses: `substrate`, `polkadot`, `kusama` or
+	/// a numeric prefix. Overrides the `ss58Format` property of the chain spec.
+	#[structopt(long = "network", value_name = "NETWORK")]
+	pub network: Option<String>,
+}
+
+/// The `key generate` command used to generate a new key.
+#[derive(Debug, StructOpt, Clone)]
+pub struct GenerateKeyCmd {
+	/// The number of words in the generated phrase (12, 15, 18, 21 or 24).
+	#[structopt(long = "words", value_name = "WORDS", default_value = "12")]
+	pub words: usize,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub scheme_params: KeySchemeParams,
+}
+
+/// The `key inspect` command used to print the public data of a key.
+#[derive(Debug, StructOpt, Clone)]
+pub struct InspectKeyCmd {
+	/// A secret key URI (phrase or seed with an optional derivation path) or an SS58 public key.
+	#[structopt(value_name = "URI")]
+	pub uri: String,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub scheme_params: KeySchemeParams,
+}
+
+/// The `key insert` command used to insert a key into the keystore of a node.
+#[derive(Debug, StructOpt, Clone)]
+pub struct InsertKeyCmd {
+	/// The secret key URI (phrase or seed with an optional derivation path) of the key.
+	#[structopt(long = "suri", value_name = "SURI")]
+	pub suri: String,
+
+	/// Specify custom keystore path.
+	#[structopt(long = "keystore-path", value_name = "PATH", parse(from_os_str))]
+	pub keystore_path: Option<PathBuf>,
+
+	/// Read the keystore password of the node from the terminal.
+	#[structopt(long = "password-interactive", raw(conflicts_with = r#""password_filename""#))]
+	pub interactive_password: bool,
+
+	/// Read the keystore password of the node from the given file.
+	#[structopt(long = "password-filename", value_name = "PATH", parse(from_os_str))]
+	pub password_filename: Option<PathBuf>,
+
+	/// The keystore password of the node. It encrypts the keystore files, and is the password
+	/// of the secret key URIs of the keys in the keystore.
+	#[structopt(long = "keystore-password", value_name = "PASSWORD")]
+	pub keystore_password: Option<String>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub scheme_params: KeySchemeParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl_get_log_filter!(InsertKeyCmd);
+
+/// The `key` subcommands used to manage keys.
+#[derive(Debug, StructOpt, Clone)]
+pub enum KeySubcommand {
+	/// Generate a random key and print its secret phrase and public data.
+	#[structopt(name = "generate")]
+	Generate(GenerateKeyCmd),
+
+	/// Print the public data of a secret key URI or a public key.
+	#[structopt(name = "inspect")]
+	Inspect(InspectKeyCmd),
+
+	/// Insert a key into the keystore of a node.
+	#[structopt(name = "insert")]
+	Insert(InsertKeyCmd),
+}
+
+impl GetLogFilter for KeySubcommand {
+	fn get_log_filter(&self) -> Option<String> {
+		match self {
+			KeySubcommand::Insert(c) => c.get_log_filter(),
+			KeySubcommand::Generate(_) | KeySubcommand::Inspect(_) => None,
+		}
+	}
+
+	fn get_log_format(&self) -> Option<LogFormat> {
+		match self {
+			KeySubcommand::Insert(c) => c.get_log_format(),
+			KeySubcommand::Generate(_) | KeySubcommand::Inspect(_) => None,
+		}
+	}
+}
+
+/// The `benchmark` command used to benchmark extrinsics of the runtime.
+#[derive(Debug, StructOpt, Clone)]
+pub struct BenchmarkCmd {
+	/// The names of the modules to benchmark.
+	#[structopt(long = "module", value_name = "MODULE", raw(required = "true"))]
+	pub module: Vec<String>,
+
+	/// The extrinsics to benchmark, in each of the modules that has a benchmark for them.
+	#[structopt(long = "extrinsic", value_name = "EXTRINSIC")]
+	pub extrinsic: Vec<String>,
+
+	/// The number of points to measure in the range of each component.
+	#[structopt(long = "steps", value_name = "COUNT", default_value = "10")]
+	pub steps: u32,
+
+	/// How many times to measure each point.
+	#[structopt(long = "repeat", value_name = "COUNT", default_value = "1")]
+	pub repeat: u32,
+
+	/// Output directory for the generated weight files, one `<MODULE>.rs` per module, or stdout
+	/// if unspecified.
+	#[structopt(long = "output", parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	/// The means of execution used when calling into the runtime.
+	#[structopt(
+		long = "execution",
+		value_name = "STRATEGY",
+		raw(
+			possible_values = "&ExecutionStrategy::variants()",
+			case_insensitive = "true",
+			default_value = r#""Wasm""#
+		)
+	)]
+	pub execution: ExecutionStrategy,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl_get_log_filter!(BenchmarkCmd);
+
+/// The `inspect` command used to print decoded blocks and extrinsics.
+#[derive(Debug, StructOpt, Clone)]
+pub struct InspectCmd {
+	/// A block number, a `0x` prefixed block hash or a hex encoded extrinsic.
+	#[structopt(value_name = "BLOCK_OR_EXTRINSIC")]
+	pub input: String,
+
+	/// Interpret the input as a hex encoded extrinsic, even if it looks like a block hash.
+	#[structopt(long = "extrinsic")]
+	pub extrinsic: bool,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl_get_log_filter!(InspectCmd);
+
+/// Parameters to read the message of the `sign` and `verify` commands.
+#[derive(Debug, StructOpt, Clone)]
+pub struct MessageParams {
+	/// The message to sign or verify, read from stdin if unspecified.
+	#[structopt(long = "message", value_name = "MESSAGE")]
+	pub message: Option<String>,
+
+	/// The message is hex encoded, e.g. a pre-serialized extrinsic payload.
+	#[structopt(long = "hex")]
+	pub hex: bool,
+}
+
+/// The `sign` command used to sign a message.
+#[derive(Debug, StructOpt, Clone)]
+pub struct SignCmd {
+	/// The secret key URI of the signing key.
+	#[structopt(long = "suri", value_name = "SURI", raw(required_unless = r#""public""#))]
+	pub suri: Option<String>,
+
+	/// The SS58 address or hex encoded public key of a key in the keystore to sign with.
+	#[structopt(long = "public", value_name = "PUBLIC", raw(conflicts_with = r#""suri""#))]
+	pub public: Option<String>,
+
+	/// Specify custom keystore path.
+	#[structopt(long = "keystore-path", value_name = "PATH", parse(from_os_str))]
+	pub keystore_path: Option<PathBuf>,
+
+	/// Read the keystore password of the node from the terminal.
+	#[structopt(long = "password-interactive", raw(conflicts_with = r#""password_filename""#))]
+	pub interactive_password: bool,
+
+	/// Read the keystore password of the node from the given file.
+	#[structopt(long = "password-filename", value_name = "PATH", parse(from_os_str))]
+	pub password_filename: Option<PathBuf>,
+
+	/// The keystore password of the node, used with `--public`.
+	#[structopt(long = "keystore-password", value_name = "PASSWORD")]
+	pub keystore_password: Option<String>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub message_params: MessageParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub scheme_params: KeySchemeParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl_get_log_filter!(SignCmd);
+
+/// The `verify` command used to verify a signature.
+#[derive(Debug, StructOpt, Clone)]
+pub struct VerifyCmd {
+	/// The hex encoded signature.
+	#[structopt(value_name = "SIGNATURE")]
+	pub signature: String,
+
+	/// The SS58 address or hex encoded public key of the signer.
+	#[structopt(value_name = "PUBLIC")]
+	pub public: String,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub message_params: MessageParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub scheme_params: KeySchemeParams,
+}
+
+impl GetLogFilter for VerifyCmd {
+	fn get_log_filter(&self) -> Option<String> {
+		None
+	}
+}
+
 /// All core commands that are provided by default.
 ///
 /// The core commands are split into multiple subcommands and `Run` is the default subcommand. From
@@ -677,9 +1222,33 @@ pub enum CoreParams<CC, RP> {
 	/// Revert chain to the previous state.
 	Revert(RevertCmd),
 
+	/// Build a raw spec.json file from the state of the local chain, outputing to stdout.
+	ForkOff(ForkOffCmd),
+
+	/// Try a runtime upgrade against the state of the local chain.
+	TryRuntime(TryRuntimeCmd),
+
+	/// Compare the state of two blocks of the local chain.
+	StateDiff(StateDiffCmd),
+
 	/// Remove the whole chain data.
 	PurgeChain(PurgeChainCmd),
 
+	/// Manage keys.
+	Key(KeySubcommand),
+
+	/// Benchmark extrinsics of the runtime.
+	Benchmark(BenchmarkCmd),
+
+	/// Print decoded blocks and extrinsics.
+	Inspect(InspectCmd),
+
+	/// Sign a message.
+	Sign(SignCmd),
+
+	/// Verify a signature.
+	Verify(VerifyCmd),
+
 	/// Further custom subcommands.
 	Custom(CC),
 }
@@ -709,10 +1278,43 @@ impl<CC, RP> StructOpt for CoreParams<CC, RP> where
 			RevertCmd::augment_clap(SubCommand::with_name("revert"))
 				.about("Revert chain to the previous state.")
 		)
+		.subcommand(
+			ForkOffCmd::augment_clap(SubCommand::with_name("fork-off"))
+				.about("Build a raw spec.json file from the state of the local chain, outputing to stdout.")
+		)
+		.subcommand(
+			TryRuntimeCmd::augment_clap(SubCommand::with_name("try-runtime"))
+				.about("Try a runtime upgrade against the state of the local chain, without changing it.")
+		)
+		.subcommand(
+			StateDiffCmd::augment_clap(SubCommand::with_name("state-diff"))
+				.about("Print the storage changes between two blocks of the local chain, by storage item.")
+		)
 		.subcommand(
 			PurgeChainCmd::augment_clap(SubCommand::with_name("purge-chain"))
 				.about("Remove the whole chain data.")
 		)
+		.subcommand(
+			KeySubcommand::clap().name("key")
+				.about("Generate, inspect and insert keys.")
+				.setting(AppSettings::SubcommandRequiredElseHelp)
+		)
+		.subcommand(
+			BenchmarkCmd::augment_clap(SubCommand::with_name("benchmark"))
+				.about("Benchmark extrinsics of the runtime and write the resulting weights.")
+		)
+		.subcommand(
+			InspectCmd::augment_clap(SubCommand::with_name("inspect"))
+				.about("Print decoded blocks and extrinsics.")
+		)
+		.subcommand(
+			SignCmd::augment_clap(SubCommand::with_name("sign"))
+				.about("Sign a message with a secret key URI or a key of the keystore.")
+		)
+		.subcommand(
+			VerifyCmd::augment_clap(SubCommand::with_name("verify"))
+				.about("Verify a signature of a message.")
+		)
 	}
 
 	fn from_clap(matches: &::structopt::clap::ArgMatches) -> Self {
@@ -724,8 +1326,19 @@ impl<CC, RP> StructOpt for CoreParams<CC, RP> where
 			("import-blocks", Some(matches)) =>
 				CoreParams::ImportBlocks(ImportBlocksCmd::from_clap(matches)),
 			("revert", Some(matches)) => CoreParams::Revert(RevertCmd::from_clap(matches)),
+			("fork-off", Some(matches)) => CoreParams::ForkOff(ForkOffCmd::from_clap(matches)),
+			("try-runtime", Some(matches)) =>
+				CoreParams::TryRuntime(TryRuntimeCmd::from_clap(matches)),
+			("state-diff", Some(matches)) =>
+				CoreParams::StateDiff(StateDiffCmd::from_clap(matches)),
 			("purge-chain", Some(matches)) =>
 				CoreParams::PurgeChain(PurgeChainCmd::from_clap(matches)),
+			("key", Some(matches)) => CoreParams::Key(KeySubcommand::from_clap(matches)),
+			("benchmark", Some(matches)) =>
+				CoreParams::Benchmark(BenchmarkCmd::from_clap(matches)),
+			("inspect", Some(matches)) => CoreParams::Inspect(InspectCmd::from_clap(matches)),
+			("sign", Some(matches)) => CoreParams::Sign(SignCmd::from_clap(matches)),
+			("verify", Some(matches)) => CoreParams::Verify(VerifyCmd::from_clap(matches)),
 			(_, None) => CoreParams::Run(MergeParameters::from_clap(matches)),
 			_ => CoreParams::Custom(CC::from_clap(matches)),
 		}
@@ -741,9 +1354,37 @@ impl<CC, RP> GetLogFilter for CoreParams<CC, RP> where CC: GetLogFilter {
 			CoreParams::ImportBlocks(c) => c.get_log_filter(),
 			CoreParams::PurgeChain(c) => c.get_log_filter(),
 			CoreParams::Revert(c) => c.get_log_filter(),
+			CoreParams::ForkOff(c) => c.get_log_filter(),
+			CoreParams::TryRuntime(c) => c.get_log_filter(),
+			CoreParams::StateDiff(c) => c.get_log_filter(),
+			CoreParams::Key(c) => c.get_log_filter(),
+			CoreParams::Benchmark(c) => c.get_log_filter(),
+			CoreParams::Inspect(c) => c.get_log_filter(),
+			CoreParams::Sign(c) => c.get_log_filter(),
+			CoreParams::Verify(c) => c.get_log_filter(),
 			CoreParams::Custom(c) => c.get_log_filter(),
 		}
 	}
+
+	fn get_log_format(&self) -> Option<LogFormat> {
+		match self {
+			CoreParams::Run(c) => c.left.get_log_format(),
+			CoreParams::BuildSpec(c) => c.get_log_format(),
+			CoreParams::ExportBlocks(c) => c.get_log_format(),
+			CoreParams::ImportBlocks(c) => c.get_log_format(),
+			CoreParams::PurgeChain(c) => c.get_log_format(),
+			CoreParams::Revert(c) => c.get_log_format(),
+			CoreParams::ForkOff(c) => c.get_log_format(),
+			CoreParams::TryRuntime(c) => c.get_log_format(),
+			CoreParams::StateDiff(c) => c.get_log_format(),
+			CoreParams::Key(c) => c.get_log_format(),
+			CoreParams::Benchmark(c) => c.get_log_format(),
+			CoreParams::Inspect(c) => c.get_log_format(),
+			CoreParams::Sign(c) => c.get_log_format(),
+			CoreParams::Verify(c) => c.get_log_format(),
+			CoreParams::Custom(c) => c.get_log_format(),
+		}
+	}
 }
 
 /// A special commandline parameter that expands to nothing.
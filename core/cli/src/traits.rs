@@ -41,4 +41,9 @@ macro_rules! impl_augment_clap {
 pub trait GetLogFilter {
 	/// Returns the set log filter.
 	fn get_log_filter(&self) -> Option<String>;
+
+	/// Returns the set log format.
+	fn get_log_format(&self) -> Option<crate::params::LogFormat> {
+		None
+	}
 }
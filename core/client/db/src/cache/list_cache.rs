@@ -129,6 +129,11 @@ impl<Block: BlockT, T: CacheItemT, S: Storage<Block, T>> ListCache<Block, T, S>
 		&self.storage
 	}
 
+	/// Get the block from which the best finalized value is valid.
+	pub fn best_finalized_entry_block(&self) -> Option<&ComplexBlockId<Block>> {
+		self.best_finalized_entry.as_ref().map(|entry| &entry.valid_from)
+	}
+
 	/// Get value valid at block.
 	pub fn value_at_block(&self, at: &ComplexBlockId<Block>) -> ClientResult<Option<T>> {
 		let head = if at.number <= self.best_finalized_block.number {
@@ -277,6 +277,14 @@ pub struct DbCacheSync<Block: BlockT>(pub RwLock<DbCache<Block>>);
 impl<Block: BlockT> BlockchainCache<Block> for DbCacheSync<Block> {
 	fn initialize(&self, key: &CacheKeyId, data: Vec<u8>) -> ClientResult<()> {
 		let mut cache = self.0.write();
+		// when the chain has been started from a checkpoint, the finalized value is valid from
+		// the checkpoint and a genesis value can't be inserted behind it
+		let from_checkpoint = cache.get_cache(*key).best_finalized_entry_block()
+			.map_or(false, |block| !block.number.is_zero());
+		if from_checkpoint {
+			return Ok(());
+		}
+
 		let genesis_hash = cache.genesis_hash;
 		let cache_contents = vec![(*key, data)].into_iter().collect();
 		let db = cache.db.clone();
@@ -39,9 +39,10 @@ use std::io;
 use std::collections::HashMap;
 
 use client::backend::NewBlockState;
+use client::cht;
 use client::blockchain::HeaderBackend;
 use client::ExecutionStrategies;
-use client::backend::{StorageCollection, ChildStorageCollection};
+use client::backend::{StorageCollection, ChildStorageCollection, OffchainChangesCollection};
 use codec::{Decode, Encode};
 use hash_db::{Hasher, Prefix};
 use kvdb::{KeyValueDB, DBTransaction};
@@ -64,7 +65,7 @@ use client::leaves::{LeafSet, FinalizationDisplaced};
 use client::children;
 use state_db::StateDb;
 use consensus_common::well_known_cache_keys;
-use crate::storage_cache::{CachingState, SharedCache, new_shared_cache};
+use crate::storage_cache::{CachingState, SharedCache, TrieNodeCache, new_shared_cache};
 use log::{trace, debug, warn};
 pub use state_db::PruningMode;
 
@@ -183,10 +184,15 @@ pub struct DatabaseSettings {
 	pub state_cache_size: usize,
 	/// Ratio of cache size dedicated to child tries.
 	pub state_cache_child_ratio: Option<(usize, usize)>,
+	/// Trie node cache size in bytes.
+	pub trie_node_cache_size: usize,
 	/// Path to the database.
 	pub path: PathBuf,
 	/// Pruning mode.
 	pub pruning: PruningMode,
+	/// Whether the headers of finalized blocks are pruned once they are replaced by a CHT.
+	/// Pruned blocks can still be proven with their CHT, but their headers can't be served.
+	pub prune_headers: bool,
 }
 
 /// Create an instance of db-backed client.
@@ -195,6 +201,7 @@ pub fn new_client<E, S, Block, RA>(
 	executor: E,
 	genesis_storage: S,
 	execution_strategies: ExecutionStrategies,
+	code_substitutes: HashMap<u64, Vec<u8>>,
 ) -> Result<
 	client::Client<Backend<Block>,
 	client::LocalCallExecutor<Backend<Block>, E>, Block, RA>, client::error::Error
@@ -205,7 +212,8 @@ pub fn new_client<E, S, Block, RA>(
 		S: BuildStorage,
 {
 	let backend = Arc::new(Backend::new(settings, CANONICALIZATION_DELAY)?);
-	let executor = client::LocalCallExecutor::new(backend.clone(), executor);
+	let executor = client::LocalCallExecutor::new(backend.clone(), executor)
+		.with_code_substitutes(code_substitutes);
 	Ok(client::Client::new(backend, executor, genesis_storage, execution_strategies)?)
 }
 
@@ -222,8 +230,13 @@ pub(crate) mod columns {
 	pub const AUX: Option<u32> = Some(8);
 	/// Offchain workers local storage
 	pub const OFFCHAIN: Option<u32> = Some(9);
+	/// Roots and nodes of the canonical hash tries.
+	pub const CHT: Option<u32> = Some(10);
 }
 
+/// Prefix for headers CHT.
+const HEADER_CHT_PREFIX: u8 = 0;
+
 struct PendingBlock<Block: BlockT> {
 	header: Block::Header,
 	justification: Option<Justification>,
@@ -303,12 +316,8 @@ impl<Block: BlockT> client::blockchain::HeaderBackend<Block> for BlockchainDb<Bl
 
 	fn status(&self, id: BlockId<Block>) -> Result<client::blockchain::BlockStatus, client::error::Error> {
 		let exists = match id {
-			BlockId::Hash(_) => read_db(
-				&*self.db,
-				columns::KEY_LOOKUP,
-				columns::HEADER,
-				id
-			)?.is_some(),
+			// the headers replaced by a CHT are pruned, but not their keys
+			BlockId::Hash(_) => block_id_to_lookup_key(&*self.db, columns::KEY_LOOKUP, id)?.is_some(),
 			BlockId::Number(n) => n <= self.meta.read().best_number,
 		};
 		match exists {
@@ -327,10 +336,10 @@ impl<Block: BlockT> client::blockchain::HeaderBackend<Block> for BlockchainDb<Bl
 	}
 
 	fn hash(&self, number: NumberFor<Block>) -> Result<Option<Block::Hash>, client::error::Error> {
-		self.header(BlockId::Number(number)).and_then(|maybe_header| match maybe_header {
-			Some(header) => Ok(Some(header.hash().clone())),
-			None => Ok(None),
-		})
+		// the lookup key ends with the hash, which is kept when the header is pruned
+		block_id_to_lookup_key::<Block>(&*self.db, columns::KEY_LOOKUP, BlockId::Number(number))?
+			.map(|lookup_key| utils::lookup_key_to_hash(&lookup_key))
+			.transpose()
 	}
 }
 
@@ -374,6 +383,16 @@ impl<Block: BlockT> client::blockchain::Backend<Block> for BlockchainDb<Block> {
 	fn children(&self, parent_hash: Block::Hash) -> Result<Vec<Block::Hash>, client::error::Error> {
 		children::read_children(&*self.db, columns::META, meta_keys::CHILDREN_PREFIX, parent_hash)
 	}
+
+	fn header_cht(&self, cht_number: NumberFor<Block>) -> Result<Option<(Block::Hash, Vec<Vec<u8>>)>, client::error::Error> {
+		let cht_start = cht::start_number(cht::size(), cht_number);
+		match self.db.get(columns::CHT, &utils::cht_key(HEADER_CHT_PREFIX, cht_start)?).map_err(db_err)? {
+			Some(cht) => Decode::decode(&mut &cht[..])
+				.map(Some)
+				.map_err(|_| client::error::Error::Backend(format!("Invalid CHT #{}", cht_number))),
+			None => Ok(None),
+		}
+	}
 }
 
 impl<Block: BlockT> client::blockchain::ProvideCache<Block> for BlockchainDb<Block> {
@@ -391,6 +410,7 @@ pub struct BlockImportOperation<Block: BlockT, H: Hasher> {
 	changes_trie_updates: MemoryDB<H>,
 	pending_block: Option<PendingBlock<Block>>,
 	aux_ops: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+	offchain_storage_updates: OffchainChangesCollection,
 	finalized_blocks: Vec<(BlockId<Block>, Option<Justification>)>,
 	set_head: Option<BlockId<Block>>,
 }
@@ -404,6 +424,16 @@ impl<Block: BlockT, H: Hasher> BlockImportOperation<Block, H> {
 			}
 		}
 	}
+
+	fn apply_offchain(&mut self, transaction: &mut DBTransaction) {
+		for (key, maybe_val) in self.offchain_storage_updates.drain(..) {
+			let key: Vec<u8> = primitives::offchain::STORAGE_PREFIX.iter().chain(&key).cloned().collect();
+			match maybe_val {
+				Some(val) => transaction.put_vec(columns::OFFCHAIN, &key, val),
+				None => transaction.delete(columns::OFFCHAIN, &key),
+			}
+		}
+	}
 }
 
 impl<Block> client::backend::BlockImportOperation<Block, Blake2Hasher>
@@ -476,6 +506,14 @@ where Block: BlockT<Hash=H256>,
 		Ok(())
 	}
 
+	fn update_offchain_storage(
+		&mut self,
+		update: OffchainChangesCollection,
+	) -> Result<(), client::error::Error> {
+		self.offchain_storage_updates = update;
+		Ok(())
+	}
+
 	fn insert_aux<I>(&mut self, ops: I) -> Result<(), client::error::Error>
 		where I: IntoIterator<Item=(Vec<u8>, Option<Vec<u8>>)>
 	{
@@ -508,13 +546,21 @@ where Block: BlockT<Hash=H256>,
 struct StorageDb<Block: BlockT> {
 	pub db: Arc<dyn KeyValueDB>,
 	pub state_db: StateDb<Block::Hash, Vec<u8>>,
+	pub trie_node_cache: TrieNodeCache,
 }
 
 impl<Block: BlockT> state_machine::Storage<Blake2Hasher> for StorageDb<Block> {
 	fn get(&self, key: &H256, prefix: Prefix) -> Result<Option<DBValue>, String> {
 		let key = prefixed_key::<Blake2Hasher>(key, prefix);
-		self.state_db.get(&key, self).map(|r| r.map(|v| DBValue::from_slice(&v)))
-			.map_err(|e| format!("Database backend error: {:?}", e))
+		if let Some(node) = self.trie_node_cache.get(&key) {
+			return Ok(Some(node));
+		}
+		let node = self.state_db.get(&key, self).map(|r| r.map(|v| DBValue::from_slice(&v)))
+			.map_err(|e| format!("Database backend error: {:?}", e))?;
+		if let Some(node) = node.as_ref() {
+			self.trie_node_cache.insert(key, node.clone());
+		}
+		Ok(node)
 	}
 }
 
@@ -696,6 +742,7 @@ pub struct Backend<Block: BlockT> {
 	changes_trie_config: Mutex<Option<Option<ChangesTrieConfiguration>>>,
 	blockchain: BlockchainDb<Block>,
 	canonicalization_delay: u64,
+	prune_headers: bool,
 	shared_cache: SharedCache<Block, Blake2Hasher>,
 	import_lock: Mutex<()>,
 }
@@ -729,13 +776,31 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 	/// Creates a client backend with test settings.
 	#[cfg(any(test, feature = "test-helpers"))]
 	pub fn new_test_db(keep_blocks: u32, canonicalization_delay: u64, db: Arc<dyn KeyValueDB>) -> Self {
+		Self::new_test_db_with_header_pruning(keep_blocks, canonicalization_delay, db, false)
+	}
 
+	/// Create new memory-backed client backend for tests that prunes the headers replaced by a CHT.
+	#[cfg(any(test, feature = "test-helpers"))]
+	pub fn new_test_with_header_pruning(keep_blocks: u32, canonicalization_delay: u64) -> Self {
+		let db = Arc::new(kvdb_memorydb::create(crate::utils::NUM_COLUMNS));
+		Self::new_test_db_with_header_pruning(keep_blocks, canonicalization_delay, db as Arc<_>, true)
+	}
+
+	#[cfg(any(test, feature = "test-helpers"))]
+	fn new_test_db_with_header_pruning(
+		keep_blocks: u32,
+		canonicalization_delay: u64,
+		db: Arc<dyn KeyValueDB>,
+		prune_headers: bool,
+	) -> Self {
 		let db_setting = DatabaseSettings {
 			cache_size: None,
 			state_cache_size: 16777216,
 			state_cache_child_ratio: Some((50, 100)),
+			trie_node_cache_size: 16777216,
 			path: Default::default(),
 			pruning: PruningMode::keep_blocks(keep_blocks),
+			prune_headers,
 		};
 		Self::from_kvdb(
 			db,
@@ -757,6 +822,7 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 		let storage_db = StorageDb {
 			db: db.clone(),
 			state_db,
+			trie_node_cache: TrieNodeCache::new(config.trie_node_cache_size),
 		};
 		let offchain_storage = offchain::LocalStorage::new(db.clone());
 		let changes_tries_storage = DbChangesTrieStorage {
@@ -773,6 +839,7 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 			changes_trie_config: Mutex::new(None),
 			blockchain,
 			canonicalization_delay,
+			prune_headers: config.prune_headers,
 			shared_cache: new_shared_cache(
 				config.state_cache_size,
 				config.state_cache_child_ratio.unwrap_or(DEFAULT_CHILD_RATIO),
@@ -985,7 +1052,7 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 			trace!(target: "db", "Canonicalize block #{} ({:?})", new_canonical, hash);
 			let commit = self.storage.state_db.canonicalize_block(&hash)
 				.map_err(|e: state_db::Error<io::Error>| client::error::Error::from(format!("State database error: {:?}", e)))?;
-			apply_state_commit(transaction, commit);
+			apply_state_commit(transaction, commit, &self.storage.trie_node_cache);
 		};
 
 		Ok(())
@@ -998,6 +1065,7 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 		let mut finalization_displaced_leaves = None;
 
 		operation.apply_aux(&mut transaction);
+		operation.apply_offchain(&mut transaction);
 
 		let mut meta_updates = Vec::new();
 		let mut last_finalized_hash = self.blockchain.meta.read().finalized_hash;
@@ -1064,7 +1132,7 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 			let number_u64 = number.saturated_into::<u64>();
 			let commit = self.storage.state_db.insert_block(&hash, number_u64, &pending_block.header.parent_hash(), changeset)
 				.map_err(|e: state_db::Error<io::Error>| client::error::Error::from(format!("State database error: {:?}", e)))?;
-			apply_state_commit(&mut transaction, commit);
+			apply_state_commit(&mut transaction, commit, &self.storage.trie_node_cache);
 
 			// Check if need to finalize. Genesis is always finalized instantly.
 			let finalized = number_u64 == 0 || pending_block.leaf_state.is_final();
@@ -1193,7 +1261,7 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 
 			let commit = self.storage.state_db.canonicalize_block(&f_hash)
 				.map_err(|e: state_db::Error<io::Error>| client::error::Error::from(format!("State database error: {:?}", e)))?;
-			apply_state_commit(transaction, commit);
+			apply_state_commit(transaction, commit, &self.storage.trie_node_cache);
 
 			let changes_trie_config = self.changes_trie_config(parent_hash)?;
 			if let Some(changes_trie_config) = changes_trie_config {
@@ -1201,6 +1269,10 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 			}
 		}
 
+		if let Some(new_cht_number) = cht::is_build_required(cht::size(), f_num) {
+			self.note_header_cht(transaction, new_cht_number)?;
+		}
+
 		let new_displaced = self.blockchain.leaves.write().finalize_height(f_num);
 		match displaced {
 			x @ &mut None => *x = Some(new_displaced),
@@ -1209,13 +1281,54 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 
 		Ok(())
 	}
+
+	// write the header CHT with given number to a transaction, and prune the headers
+	// it replaces if headers are pruned.
+	fn note_header_cht(
+		&self,
+		transaction: &mut DBTransaction,
+		cht_number: NumberFor<Block>,
+	) -> Result<(), client::error::Error> {
+		let cht_start: NumberFor<Block> = cht::start_number(cht::size(), cht_number);
+		let mut current_num = cht_start;
+		let cht_range = ::std::iter::from_fn(|| {
+			let old_current_num = current_num;
+			current_num = current_num + One::one();
+			Some(old_current_num)
+		});
+		let root_and_nodes = cht::compute_root_and_nodes::<Block::Header, Blake2Hasher, _>(
+			cht::size(), cht_number, cht_range.map(|num| self.blockchain.hash(num))
+		)?;
+		transaction.put(columns::CHT, &utils::cht_key(HEADER_CHT_PREFIX, cht_start)?, &root_and_nodes.encode());
+
+		if self.prune_headers {
+			let cht_end = cht::end_number(cht::size(), cht_number);
+			trace!(target: "db", "Replacing headers [{}..{}] with CHT#{}", cht_start, cht_end, cht_number);
+
+			let mut prune_block = cht_start;
+			while prune_block <= cht_end {
+				let lookup_key = block_id_to_lookup_key::<Block>(&*self.storage.db, columns::KEY_LOOKUP, BlockId::Number(prune_block))?;
+				if let Some(lookup_key) = lookup_key {
+					transaction.delete(columns::HEADER, &lookup_key);
+				}
+				prune_block += One::one();
+			}
+		}
+
+		Ok(())
+	}
 }
 
-fn apply_state_commit(transaction: &mut DBTransaction, commit: state_db::CommitSet<Vec<u8>>) {
+fn apply_state_commit(
+	transaction: &mut DBTransaction,
+	commit: state_db::CommitSet<Vec<u8>>,
+	trie_node_cache: &TrieNodeCache,
+) {
 	for (key, val) in commit.data.inserted.into_iter() {
 		transaction.put(columns::STATE, &key[..], &val);
 	}
 	for key in commit.data.deleted.into_iter() {
+		trie_node_cache.remove(&key);
 		transaction.delete(columns::STATE, &key[..]);
 	}
 	for (key, val) in commit.meta.inserted.into_iter() {
@@ -1267,6 +1380,7 @@ impl<Block> client::backend::Backend<Block, Blake2Hasher> for Backend<Block> whe
 			child_storage_updates: Default::default(),
 			changes_trie_updates: MemoryDB::default(),
 			aux_ops: Vec::new(),
+			offchain_storage_updates: Vec::new(),
 			finalized_blocks: Vec::new(),
 			set_head: None,
 		})
@@ -1346,7 +1460,7 @@ impl<Block> client::backend::Backend<Block, Blake2Hasher> for Backend<Block> whe
 			let mut transaction = DBTransaction::new();
 			match self.storage.state_db.revert_one() {
 				Some(commit) => {
-					apply_state_commit(&mut transaction, commit);
+					apply_state_commit(&mut transaction, commit, &self.storage.trie_node_cache);
 					let removed = self.blockchain.header(BlockId::Number(best))?.ok_or_else(
 						|| client::error::Error::UnknownBlock(
 							format!("Error reverting to {}. Block hash not found.", best)))?;
@@ -1746,6 +1860,11 @@ mod tests {
 				columns::STATE,
 				&trie::prefixed_key::<Blake2Hasher>(&key, EMPTY_PREFIX)
 			).unwrap().is_some());
+			// reading the node caches it
+			assert!(state_machine::Storage::get(&*backend.storage, &key, EMPTY_PREFIX).unwrap().is_some());
+			assert!(backend.storage.trie_node_cache.get(
+				&trie::prefixed_key::<Blake2Hasher>(&key, EMPTY_PREFIX)
+			).is_some());
 			hash
 		};
 
@@ -1780,6 +1899,10 @@ mod tests {
 				columns::STATE,
 				&trie::prefixed_key::<Blake2Hasher>(&key, EMPTY_PREFIX)
 			).unwrap().is_none());
+			// the pruned node is evicted from the cache
+			assert!(backend.storage.trie_node_cache.get(
+				&trie::prefixed_key::<Blake2Hasher>(&key, EMPTY_PREFIX)
+			).is_none());
 		}
 
 		backend.finalize_block(BlockId::Number(1), None).unwrap();
@@ -2192,4 +2315,48 @@ mod tests {
 			backend.commit_operation(op).unwrap_err();
 		}
 	}
+
+	#[test]
+	fn finalized_ancient_headers_are_replaced_with_cht() {
+		let db = Arc::new(kvdb_memorydb::create(crate::utils::NUM_COLUMNS));
+		let settings = DatabaseSettings {
+			cache_size: None,
+			state_cache_size: 16777216,
+			state_cache_child_ratio: Some((50, 100)),
+			trie_node_cache_size: 16777216,
+			path: Default::default(),
+			pruning: PruningMode::keep_blocks(10),
+			prune_headers: true,
+		};
+		let backend = Backend::<Block>::from_kvdb(db, 10, &settings).unwrap();
+		let cht_size: u64 = cht::size();
+
+		let mut hashes = vec![insert_header(&backend, 0, Default::default(), Default::default(), Default::default())];
+		for number in 1..=(2 * cht_size) {
+			let hash = insert_header(&backend, number, hashes[number as usize - 1], Default::default(), Default::default());
+			backend.finalize_block(BlockId::Hash(hash), None).unwrap();
+			hashes.push(hash);
+		}
+		assert!(backend.blockchain().header_cht(0).unwrap().is_none());
+		assert!(backend.blockchain().header(BlockId::Number(1)).unwrap().is_some());
+
+		// finalizing block #{2 * cht::size() + 1} creates the first CHT and prunes its headers
+		let hash = insert_header(&backend, 2 * cht_size + 1, hashes[2 * cht_size as usize], Default::default(), Default::default());
+		backend.finalize_block(BlockId::Hash(hash), None).unwrap();
+
+		let blockchain = backend.blockchain();
+		assert!((1..=cht_size).all(|number| blockchain.header(BlockId::Number(number)).unwrap().is_none()));
+		assert!(blockchain.header(BlockId::Number(cht_size + 1)).unwrap().is_some());
+		assert!((1..=cht_size).all(|number| blockchain.hash(number).unwrap() == Some(hashes[number as usize])));
+		assert_eq!(blockchain.number(hashes[1]).unwrap(), Some(1));
+		assert_eq!(blockchain.status(BlockId::Hash(hashes[1])).unwrap(), client::blockchain::BlockStatus::InChain);
+
+		// the pruned blocks can still be proven with the stored CHT
+		let (root, nodes) = blockchain.header_cht(0).unwrap().unwrap();
+		let hashes_of_cht = hashes[1..].iter().map(|hash| Ok(Some(*hash)));
+		assert_eq!(root, cht::compute_root::<Header, Blake2Hasher, _>(cht_size, 0, hashes_of_cht).unwrap());
+		let proof = cht::build_proof_from_nodes::<Header, Blake2Hasher, _>(cht_size, 0, root, nodes, vec![cht_size / 2]).unwrap();
+		assert!(cht::check_proof::<Header, Blake2Hasher>(root, cht_size / 2, hashes[cht_size as usize / 2], proof).is_ok());
+		assert!(blockchain.header_cht(1).unwrap().is_none());
+	}
 }
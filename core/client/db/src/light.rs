@@ -17,7 +17,6 @@
 //! RocksDB-based light client blockchain storage.
 
 use std::{sync::Arc, collections::HashMap};
-use std::convert::TryInto;
 use parking_lot::RwLock;
 
 use kvdb::{KeyValueDB, DBTransaction};
@@ -35,7 +34,7 @@ use sr_primitives::generic::{DigestItem, BlockId};
 use sr_primitives::traits::{Block as BlockT, Header as HeaderT, Zero, One, NumberFor};
 use consensus_common::well_known_cache_keys;
 use crate::cache::{DbCacheSync, DbCache, ComplexBlockId, EntryType as CacheEntryType};
-use crate::utils::{self, meta_keys, Meta, db_err, read_db, block_id_to_lookup_key, read_meta};
+use crate::utils::{self, meta_keys, Meta, db_err, read_db, block_id_to_lookup_key, read_meta, cht_key};
 use crate::DatabaseSettings;
 use log::{trace, warn, debug};
 
@@ -277,8 +276,16 @@ impl<Block: BlockT> LightStorage<Block> {
 		let lookup_key = utils::number_and_hash_to_lookup_key(header.number().clone(), hash)?;
 		transaction.put(columns::META, meta_keys::FINALIZED_BLOCK, &lookup_key);
 
-		// build new CHT(s) if required
-		if let Some(new_cht_number) = cht::is_build_required(cht::size(), *header.number()) {
+		// build new CHT(s) if required, unless the first headers they cover are not stored
+		// because syncing has started from a checkpoint after them
+		let mut new_cht_number = cht::is_build_required(cht::size(), *header.number());
+		if let Some(cht_number) = new_cht_number {
+			if self.hash(cht::start_number(cht::size(), cht_number))?.is_none() {
+				trace!(target: "db", "Not building CHT#{}: its headers precede the checkpoint", cht_number);
+				new_cht_number = None;
+			}
+		}
+		if let Some(new_cht_number) = new_cht_number {
 			let new_cht_start: NumberFor<Block> = cht::start_number(cht::size(), new_cht_number);
 
 			let mut current_num = new_cht_start;
@@ -489,6 +496,89 @@ impl<Block> LightBlockchainStorage<Block> for LightStorage<Block>
 		Ok(())
 	}
 
+	fn import_checkpoint(
+		&self,
+		header: Block::Header,
+		cache_at: HashMap<well_known_cache_keys::Id, Vec<u8>>,
+		aux_ops: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+	) -> ClientResult<()> {
+		let mut transaction = DBTransaction::new();
+
+		let hash = header.hash();
+		let number = *header.number();
+		let last_finalized = {
+			let meta = self.meta.read();
+			ComplexBlockId::new(meta.finalized_hash, meta.finalized_number)
+		};
+		if number <= last_finalized.number {
+			return Err(ClientError::NonSequentialFinalization(
+				format!("Checkpoint {:?} is not above the last finalized {:?}",
+					hash, last_finalized.hash),
+			).into())
+		}
+
+		for (key, maybe_val) in aux_ops {
+			match maybe_val {
+				Some(val) => transaction.put_vec(columns::AUX, &key, val),
+				None => transaction.delete(columns::AUX, &key),
+			}
+		}
+
+		let lookup_key = utils::number_and_hash_to_lookup_key(number, &hash)?;
+		transaction.put(columns::META, meta_keys::BEST_BLOCK, &lookup_key);
+		transaction.put(columns::META, meta_keys::FINALIZED_BLOCK, &lookup_key);
+		utils::insert_number_to_key_mapping(
+			&mut transaction,
+			columns::KEY_LOOKUP,
+			number,
+			hash,
+		)?;
+		utils::insert_hash_to_key_mapping(
+			&mut transaction,
+			columns::KEY_LOOKUP,
+			number,
+			hash,
+		)?;
+		transaction.put(columns::HEADER, &lookup_key, &header.encode());
+
+		{
+			let mut leaves = self.leaves.write();
+
+			// the cache doesn't know about the missing ancestors of the checkpoint, so it is
+			// inserted as the child of the last finalized block
+			let mut cache = self.cache.0.write();
+			let cache_ops = cache.transaction(&mut transaction)
+				.on_block_insert(
+					last_finalized,
+					ComplexBlockId::new(hash, number),
+					cache_at,
+					CacheEntryType::Final,
+				)?
+				.into_ops();
+
+			let displaced_leaf = leaves.import(hash, number, *header.parent_hash());
+			let finalization_displaced = leaves.finalize_height(number);
+
+			debug!("Light DB Commit checkpoint {:?} ({})", hash, number);
+			let write_result = self.db.write(transaction).map_err(db_err);
+			if let Err(e) = write_result {
+				let mut undo = leaves.undo();
+				if let Some(displaced_leaf) = displaced_leaf {
+					undo.undo_import(displaced_leaf);
+				}
+				undo.undo_finalization(finalization_displaced);
+
+				return Err(e);
+			}
+
+			cache.commit(cache_ops);
+		}
+
+		self.update_meta(hash, number, true, true);
+
+		Ok(())
+	}
+
 	fn set_head(&self, id: BlockId<Block>) -> ClientResult<()> {
 		if let Some(header) = self.header(id)? {
 			let hash = header.hash();
@@ -561,13 +651,6 @@ impl<Block> LightBlockchainStorage<Block> for LightStorage<Block>
 	}
 }
 
-/// Build the key for inserting header-CHT at given block.
-fn cht_key<N: TryInto<u32>>(cht_type: u8, block: N) -> ClientResult<[u8; 5]> {
-	let mut key = [cht_type; 5];
-	key[1..].copy_from_slice(&utils::number_index_key(block)?);
-	Ok(key)
-}
-
 #[cfg(test)]
 pub(crate) mod tests {
 	use client::cht;
@@ -1090,6 +1173,46 @@ pub(crate) mod tests {
 		assert_eq!(db.leaves.read().hashes(), vec![block2_a, block2_b, block2_c]);
 	}
 
+	#[test]
+	fn checkpoint_is_imported_without_its_ancestors() {
+		let db = LightStorage::<Block>::new_test();
+		let cht_size: u64 = cht::size();
+		insert_final_block(&db, HashMap::new(), || default_header(&Default::default(), 0));
+		db.cache().initialize(b"test", vec![1]).unwrap();
+
+		// the checkpoint follows the first headers of CHT#0
+		let checkpoint = default_header(&Hash::random(), cht_size / 2);
+		let checkpoint_hash = checkpoint.hash();
+		let mut cache = HashMap::new();
+		cache.insert(*b"test", vec![2]);
+		db.import_checkpoint(checkpoint, cache, vec![(b"key".to_vec(), Some(b"value".to_vec()))]).unwrap();
+
+		let info = db.info();
+		assert_eq!((info.best_number, info.best_hash), (cht_size / 2, checkpoint_hash));
+		assert_eq!((info.finalized_number, info.finalized_hash), (cht_size / 2, checkpoint_hash));
+		assert_eq!(db.hash(cht_size / 2).unwrap(), Some(checkpoint_hash));
+		assert_eq!(db.hash(1).unwrap(), None);
+		assert_eq!(db.get_aux(b"key").unwrap(), Some(b"value".to_vec()));
+		assert_eq!(db.cache().get_at(b"test", &BlockId::Hash(checkpoint_hash)), Some(vec![2]));
+		assert_eq!(db.leaves.read().hashes(), vec![checkpoint_hash]);
+
+		// the genesis value of the cache is not initialized behind the checkpoint
+		db.cache().initialize(b"test", vec![3]).unwrap();
+		assert_eq!(db.cache().get_at(b"test", &BlockId::Hash(checkpoint_hash)), Some(vec![2]));
+
+		// a checkpoint can't revert the finalized blocks
+		assert!(db.import_checkpoint(default_header(&Hash::random(), 1), HashMap::new(), Vec::new()).is_err());
+
+		// CHT#0 is not built when its blocks are finalized, since its first headers are unknown
+		let mut prev_hash = checkpoint_hash;
+		for number in (cht_size / 2 + 1)..(2 * cht_size + 2) {
+			prev_hash = insert_final_block(&db, HashMap::new(), || default_header(&prev_hash, number));
+		}
+		assert_eq!(db.info().finalized_hash, prev_hash);
+		assert_eq!(db.db.iter(columns::CHT).count(), 0);
+		assert_eq!(db.cache().get_at(b"test", &BlockId::Hash(prev_hash)), Some(vec![2]));
+	}
+
 	#[test]
 	fn cache_can_be_initialized_after_genesis_inserted() {
 		let db = LightStorage::<Block>::new_test();
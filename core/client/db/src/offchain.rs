@@ -75,6 +75,16 @@ impl client::backend::OffchainStorage for LocalStorage {
 			.map(|v| v.to_vec())
 	}
 
+	fn remove(&mut self, prefix: &[u8], key: &[u8]) {
+		let key: Vec<u8> = prefix.iter().chain(key).cloned().collect();
+		let mut tx = self.db.transaction();
+		tx.delete(columns::OFFCHAIN, &key);
+
+		if let Err(e) = self.db.write(tx) {
+			log::warn!("Error writing to the offchain DB: {:?}", e);
+		}
+	}
+
 	fn compare_and_set(
 		&mut self,
 		prefix: &[u8],
@@ -22,7 +22,7 @@ use parking_lot::{Mutex, RwLock, RwLockUpgradableReadGuard};
 use linked_hash_map::{LinkedHashMap, Entry};
 use hash_db::Hasher;
 use sr_primitives::traits::{Block as BlockT, Header};
-use state_machine::{backend::Backend as StateBackend, TrieBackend};
+use state_machine::{backend::Backend as StateBackend, TrieBackend, DBValue};
 use log::trace;
 use super::{StorageCollection, ChildStorageCollection};
 use std::hash::Hash as StdHash;
@@ -71,6 +71,12 @@ impl EstimateSize for Option<Vec<u8>> {
 	}
 }
 
+impl EstimateSize for DBValue {
+	fn estimate_size(&self) -> usize {
+		self.len()
+	}
+}
+
 struct OptionHOut<T: AsRef<[u8]>>(Option<T>);
 
 impl<T: AsRef<[u8]>> EstimateSize for OptionHOut<T> {
@@ -233,6 +239,65 @@ pub fn new_shared_cache<B: BlockT, H: Hasher>(
 	}))
 }
 
+/// Number of independently locked shards of the trie node cache.
+const TRIE_NODE_CACHE_SHARDS: usize = 16;
+
+/// Cache of encoded trie nodes, by their prefixed database key.
+///
+/// Nodes are addressed by the hash of their content, so a cached node is valid for every block
+/// of every fork: the cache is never invalidated, and it is shared between block import and the
+/// states queried through RPC. Nodes are only evicted once the pruning of the state db deletes
+/// them from the database, so that the cache isn't spent on the nodes of pruned states. A hit saves the lookup in the state db overlay and the read from
+/// the database. The nodes are kept encoded because the decoded nodes of the trie borrow their
+/// encoding; the hashes of the values are already kept by the shared state cache.
+///
+/// Looking up a node refreshes its position in the LRU, so every access mutates the cache. It is
+/// split in shards with their own lock, selected by the last byte of the node hash, so that the
+/// concurrent readers rarely wait for each other.
+pub struct TrieNodeCache(Vec<Mutex<LRUMap<Vec<u8>, DBValue>>>);
+
+impl TrieNodeCache {
+	/// Create a trie node cache using at most `size` bytes. A size of 0 disables the cache.
+	pub fn new(size: usize) -> Self {
+		let shards = if size == 0 { 0 } else { TRIE_NODE_CACHE_SHARDS };
+		TrieNodeCache((0..shards)
+			.map(|_| Mutex::new(LRUMap(LinkedHashMap::new(), 0, size / TRIE_NODE_CACHE_SHARDS)))
+			.collect())
+	}
+
+	fn shard(&self, key: &[u8]) -> Option<&Mutex<LRUMap<Vec<u8>, DBValue>>> {
+		if self.0.is_empty() {
+			return None;
+		}
+		let index = key.last().map_or(0, |byte| *byte as usize) % self.0.len();
+		Some(&self.0[index])
+	}
+
+	/// Get the node with the prefixed key `key`.
+	pub fn get(&self, key: &[u8]) -> Option<DBValue> {
+		self.shard(key)?.lock().get(key).cloned()
+	}
+
+	/// Cache the node `node` with the prefixed key `key`.
+	pub fn insert(&self, key: Vec<u8>, node: DBValue) {
+		if let Some(shard) = self.shard(&key) {
+			shard.lock().add(key, node);
+		}
+	}
+
+	/// Evict the node with the prefixed key `key`.
+	pub fn remove(&self, key: &[u8]) {
+		if let Some(shard) = self.shard(key) {
+			shard.lock().remove(&key.to_vec());
+		}
+	}
+
+	/// Returns the used memory size of the cache in bytes.
+	pub fn used_size(&self) -> usize {
+		self.0.iter().map(|shard| shard.lock().used_size()).sum()
+	}
+}
+
 #[derive(Debug)]
 /// Accumulates a list of storage changed in a block.
 struct BlockChanges<B: Header> {
@@ -714,4 +779,61 @@ mod tests {
 		// 32 key, 2 byte size
 		assert_eq!(shared.lock().used_storage_cache_size(), 34 /* bytes */);
 	}
+
+	#[test]
+	fn trie_node_cache_evicts_least_recently_used_nodes() {
+		// every key ends with the same byte, so they all go to the same shard
+		let key = |index: u8| {
+			let mut key = H256::random()[..].to_vec();
+			key[31] = 7;
+			key[0] = index;
+			key
+		};
+		let cache = TrieNodeCache::new(3 * 36 * TRIE_NODE_CACHE_SHARDS);
+		let keys: Vec<_> = (0..4).map(key).collect();
+		for key in &keys[..3] {
+			cache.insert(key.clone(), DBValue::from_slice(&[1, 2, 3, 4]));
+		}
+		assert_eq!(cache.used_size(), 3 * 36);
+
+		// The first node was used last, so the second one is evicted.
+		assert!(cache.get(&keys[0]).is_some());
+		cache.insert(keys[3].clone(), DBValue::from_slice(&[5, 6, 7, 8]));
+		assert_eq!(cache.get(&keys[0]).map(|node| node.to_vec()), Some(vec![1, 2, 3, 4]));
+		assert!(cache.get(&keys[1]).is_none());
+		assert_eq!(cache.get(&keys[3]).map(|node| node.to_vec()), Some(vec![5, 6, 7, 8]));
+		assert_eq!(cache.used_size(), 3 * 36);
+	}
+
+	#[test]
+	fn trie_node_cache_shards_are_sized_independently() {
+		let cache = TrieNodeCache::new(36 * TRIE_NODE_CACHE_SHARDS);
+		let keys: Vec<_> = (0..TRIE_NODE_CACHE_SHARDS as u8).map(|index| vec![index; 32]).collect();
+		for key in &keys {
+			cache.insert(key.clone(), DBValue::from_slice(&[1, 2, 3, 4]));
+		}
+		assert_eq!(cache.used_size(), 36 * TRIE_NODE_CACHE_SHARDS);
+		assert!(keys.iter().all(|key| cache.get(key).is_some()));
+	}
+
+	#[test]
+	fn trie_node_cache_evicts_removed_nodes() {
+		let cache = TrieNodeCache::new(36 * TRIE_NODE_CACHE_SHARDS);
+		cache.insert(vec![1; 32], DBValue::from_slice(&[1, 2, 3, 4]));
+		cache.insert(vec![2; 32], DBValue::from_slice(&[5, 6, 7, 8]));
+
+		cache.remove(&[1; 32]);
+		cache.remove(&[3; 32]);
+		assert!(cache.get(&[1; 32]).is_none());
+		assert!(cache.get(&[2; 32]).is_some());
+		assert_eq!(cache.used_size(), 36);
+	}
+
+	#[test]
+	fn trie_node_cache_of_size_zero_is_disabled() {
+		let cache = TrieNodeCache::new(0);
+		cache.insert(vec![1; 32], DBValue::from_slice(&[1, 2, 3, 4]));
+		assert!(cache.get(&[1; 32]).is_none());
+		assert_eq!(cache.used_size(), 0);
+	}
 }
@@ -39,7 +39,7 @@ use crate::DatabaseSettings;
 
 /// Number of columns in the db. Must be the same for both full && light dbs.
 /// Otherwise RocksDb will fail to open database && check its type.
-pub const NUM_COLUMNS: u32 = 10;
+pub const NUM_COLUMNS: u32 = 11;
 /// Meta column. The set of keys in the column is shared by full && light storages.
 pub const COLUMN_META: Option<u32> = Some(0);
 
@@ -125,6 +125,22 @@ pub fn lookup_key_to_number<N>(key: &[u8]) -> client::error::Result<N> where
 		| (key[3] as u32)).map(Into::into)
 }
 
+/// Convert block lookup key into block hash.
+/// all block lookup keys end with the block hash.
+pub fn lookup_key_to_hash<H: Decode>(key: &[u8]) -> client::error::Result<H> {
+	if key.len() < 4 {
+		return Err(client::error::Error::Backend("Invalid block key".into()));
+	}
+	H::decode(&mut &key[4..]).map_err(|_| client::error::Error::Backend("Invalid block key".into()))
+}
+
+/// Build the key for inserting the CHT of given type that starts at given block.
+pub fn cht_key<N: TryInto<u32>>(cht_type: u8, block: N) -> client::error::Result<[u8; 5]> {
+	let mut key = [cht_type; 5];
+	key[1..].copy_from_slice(&number_index_key(block)?);
+	Ok(key)
+}
+
 /// Delete number to hash mapping in DB transaction.
 pub fn remove_number_to_key_mapping<N: TryInto<u32>>(
 	transaction: &mut DBTransaction,
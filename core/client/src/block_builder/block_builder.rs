@@ -14,10 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-use super::api::BlockBuilder as BlockBuilderApi;
+use super::api::{BlockBuilder as BlockBuilderApi, InherentData};
 use std::vec::Vec;
 use codec::Encode;
-use sr_primitives::ApplyOutcome;
+use sr_primitives::{ApplyOutcome, ApplyError};
 use sr_primitives::generic::BlockId;
 use sr_primitives::traits::{
 	Header as HeaderT, Hash, Block as BlockT, One, HashFor, ProvideRuntimeApi, ApiRef, DigestFor,
@@ -27,12 +27,16 @@ use crate::blockchain::HeaderBackend;
 use crate::runtime_api::{Core, ApiExt};
 use crate::error;
 
+/// Returns the error to fail pushing an extrinsic with, instead of applying it.
+type ApplyErrorHook<'a, Extrinsic> = Box<dyn FnMut(&Extrinsic) -> Option<ApplyError> + Send + 'a>;
+
 /// Utility for building new (valid) blocks from a stream of extrinsics.
 pub struct BlockBuilder<'a, Block, A: ProvideRuntimeApi> where Block: BlockT {
 	header: <Block as BlockT>::Header,
 	extrinsics: Vec<<Block as BlockT>::Extrinsic>,
 	api: ApiRef<'a, A::Api>,
 	block_id: BlockId<Block>,
+	apply_error_hook: Option<ApplyErrorHook<'a, <Block as BlockT>::Extrinsic>>,
 }
 
 impl<'a, Block, A> BlockBuilder<'a, Block, A>
@@ -88,6 +92,7 @@ where
 			extrinsics: Vec::new(),
 			api,
 			block_id: *block_id,
+			apply_error_hook: None,
 		})
 	}
 
@@ -95,6 +100,10 @@ where
 	///
 	/// This will ensure the extrinsic can be validly executed (by executing it);
 	pub fn push(&mut self, xt: <Block as BlockT>::Extrinsic) -> error::Result<()> {
+		if let Some(e) = self.apply_error_hook.as_mut().and_then(|hook| hook(&xt)) {
+			return Err(error::Error::ApplyExtrinsicFailed(e));
+		}
+
 		let block_id = &self.block_id;
 		let extrinsics = &mut self.extrinsics;
 
@@ -115,6 +124,44 @@ where
 		})
 	}
 
+	/// Create the inherent extrinsics for the given inherent data, e.g. a chosen timestamp or
+	/// slot, and push them onto the block's list of extrinsics.
+	pub fn push_inherents(&mut self, inherent_data: InherentData) -> error::Result<()> {
+		let inherents = self.api.inherent_extrinsics_with_context(
+			&self.block_id,
+			ExecutionContext::BlockConstruction,
+			inherent_data,
+		)?;
+		for xt in inherents {
+			self.push(xt)?;
+		}
+		Ok(())
+	}
+
+	/// Make `push` fail with the error `hook` returns for an extrinsic, without applying it.
+	///
+	/// Lets tests simulate `apply_extrinsic` failures, the extrinsics for which `hook` returns
+	/// `None` are applied as usual.
+	pub fn set_apply_error_hook(
+		&mut self,
+		hook: impl FnMut(&<Block as BlockT>::Extrinsic) -> Option<ApplyError> + Send + 'a,
+	) {
+		self.apply_error_hook = Some(Box::new(hook));
+	}
+
+	/// The extrinsics pushed so far.
+	pub fn extrinsics(&self) -> &[<Block as BlockT>::Extrinsic] {
+		&self.extrinsics
+	}
+
+	/// The runtime api the block is built with.
+	///
+	/// Calls made at the parent block see the state left by the extrinsics pushed so far, which
+	/// lets tests inspect the state between extrinsics.
+	pub fn api(&self) -> &A::Api {
+		&self.api
+	}
+
 	/// Consume the builder to return a valid `Block` containing all pushed extrinsics.
 	pub fn bake(mut self) -> error::Result<Block> {
 		self.bake_impl()?;
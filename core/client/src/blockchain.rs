@@ -90,6 +90,12 @@ pub trait Backend<Block: BlockT>: HeaderBackend<Block> {
 
 	/// Return hashes of all blocks that are children of the block with `parent_hash`.
 	fn children(&self, parent_hash: Block::Hash) -> Result<Vec<Block::Hash>>;
+
+	/// Returns the root and the nodes of the header CHT with the given number, if they are stored.
+	/// Only CHTs of the default size are stored.
+	fn header_cht(&self, _cht_number: NumberFor<Block>) -> Result<Option<(Block::Hash, Vec<Vec<u8>>)>> {
+		Ok(None)
+	}
 }
 
 /// Provides access to the optional cache.
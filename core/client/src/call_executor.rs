@@ -14,10 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{sync::Arc, cmp::Ord, panic::UnwindSafe, result, cell::RefCell, rc::Rc};
+use std::{sync::Arc, cmp::Ord, panic::UnwindSafe, result, cell::RefCell, rc::Rc, collections::HashMap};
 use codec::{Encode, Decode};
 use sr_primitives::{
-	generic::BlockId, traits::Block as BlockT,
+	generic::BlockId, traits::{Block as BlockT, SaturatedConversion},
 };
 use state_machine::{
 	self, OverlayedChanges, Ext, CodeExecutor, ExecutionManager,
@@ -27,9 +27,11 @@ use executor::{RuntimeVersion, RuntimeInfo, NativeVersion};
 use hash_db::Hasher;
 use trie::MemoryDB;
 use primitives::{offchain, H256, Blake2Hasher, NativeOrEncoded, NeverNativeValue};
+use primitives::storage::well_known_keys;
 
 use crate::runtime_api::{ProofRecorder, InitializeBlock};
 use crate::backend;
+use crate::blockchain::HeaderBackend;
 use crate::error;
 
 /// Method call executor.
@@ -143,6 +145,86 @@ where
 
 	/// Get runtime version if supported.
 	fn native_runtime_version(&self) -> Option<&NativeVersion>;
+
+	/// Get the runtime code that replaces the on-chain `:code` when executing on top of the
+	/// state of the given block, if any.
+	fn code_substitute(&self, _id: &BlockId<B>) -> Result<Option<Vec<u8>>, error::Error> {
+		Ok(None)
+	}
+}
+
+/// State backend that serves a substitute runtime code instead of the `:code` stored in the
+/// wrapped state.
+pub struct SubstituteCode<'a, S> {
+	state: &'a S,
+	code: Option<Vec<u8>>,
+}
+
+impl<'a, S> SubstituteCode<'a, S> {
+	/// Wrap the given state, replacing its runtime code with `code` if it is `Some`.
+	pub fn new(state: &'a S, code: Option<Vec<u8>>) -> Self {
+		SubstituteCode { state, code }
+	}
+}
+
+impl<'a, S, H> state_machine::Backend<H> for SubstituteCode<'a, S>
+where
+	S: state_machine::Backend<H>,
+	H: Hasher,
+{
+	type Error = S::Error;
+	type Transaction = S::Transaction;
+	type TrieBackendStorage = S::TrieBackendStorage;
+
+	fn storage(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+		match self.code {
+			Some(ref code) if key == well_known_keys::CODE => Ok(Some(code.clone())),
+			_ => self.state.storage(key),
+		}
+	}
+
+	fn storage_hash(&self, key: &[u8]) -> Result<Option<H::Out>, Self::Error> {
+		match self.code {
+			Some(ref code) if key == well_known_keys::CODE => Ok(Some(H::hash(code))),
+			_ => self.state.storage_hash(key),
+		}
+	}
+
+	fn child_storage(&self, storage_key: &[u8], key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+		self.state.child_storage(storage_key, key)
+	}
+
+	fn for_keys_in_child_storage<F: FnMut(&[u8])>(&self, storage_key: &[u8], f: F) {
+		self.state.for_keys_in_child_storage(storage_key, f)
+	}
+
+	fn for_keys_with_prefix<F: FnMut(&[u8])>(&self, prefix: &[u8], f: F) {
+		self.state.for_keys_with_prefix(prefix, f)
+	}
+
+	fn storage_root<I>(&self, delta: I) -> (H::Out, Self::Transaction)
+	where
+		I: IntoIterator<Item=(Vec<u8>, Option<Vec<u8>>)>,
+		H::Out: Ord,
+	{
+		self.state.storage_root(delta)
+	}
+
+	fn child_storage_root<I>(&self, storage_key: &[u8], delta: I) -> (Vec<u8>, bool, Self::Transaction)
+	where
+		I: IntoIterator<Item=(Vec<u8>, Option<Vec<u8>>)>,
+		H::Out: Ord,
+	{
+		self.state.child_storage_root(storage_key, delta)
+	}
+
+	fn pairs(&self) -> Vec<(Vec<u8>, Vec<u8>)> {
+		self.state.pairs()
+	}
+
+	fn as_trie_backend(&mut self) -> Option<&state_machine::TrieBackend<Self::TrieBackendStorage, H>> {
+		None
+	}
 }
 
 /// Call executor that executes methods locally, querying all required
@@ -150,6 +232,7 @@ where
 pub struct LocalCallExecutor<B, E> {
 	backend: Arc<B>,
 	executor: E,
+	code_substitutes: Arc<HashMap<u64, Vec<u8>>>,
 }
 
 impl<B, E> LocalCallExecutor<B, E> {
@@ -158,8 +241,16 @@ impl<B, E> LocalCallExecutor<B, E> {
 		LocalCallExecutor {
 			backend,
 			executor,
+			code_substitutes: Default::default(),
 		}
 	}
+
+	/// Use the given runtime code instead of the on-chain one when executing on top of the
+	/// state of the blocks with the given numbers.
+	pub fn with_code_substitutes(mut self, code_substitutes: HashMap<u64, Vec<u8>>) -> Self {
+		self.code_substitutes = Arc::new(code_substitutes);
+		self
+	}
 }
 
 impl<B, E> Clone for LocalCallExecutor<B, E> where E: Clone {
@@ -167,6 +258,7 @@ impl<B, E> Clone for LocalCallExecutor<B, E> where E: Clone {
 		LocalCallExecutor {
 			backend: self.backend.clone(),
 			executor: self.executor.clone(),
+			code_substitutes: self.code_substitutes.clone(),
 		}
 	}
 }
@@ -189,20 +281,23 @@ where
 	) -> error::Result<Vec<u8>> {
 		let mut changes = OverlayedChanges::default();
 		let state = self.backend.state_at(*id)?;
-		let return_data = state_machine::new(
-			&state,
-			self.backend.changes_trie_storage(),
-			side_effects_handler,
-			&mut changes,
-			&self.executor,
-			method,
-			call_data,
-		).execute_using_consensus_failure_handler::<_, NeverNativeValue, fn() -> _>(
-			strategy.get_manager(),
-			false,
-			None,
-		)
-		.map(|(result, _, _)| result)?;
+		let return_data = {
+			let state = SubstituteCode::new(&state, self.code_substitute(id)?);
+			state_machine::new(
+				&state,
+				self.backend.changes_trie_storage(),
+				side_effects_handler,
+				&mut changes,
+				&self.executor,
+				method,
+				call_data,
+			).execute_using_consensus_failure_handler::<_, NeverNativeValue, fn() -> _>(
+				strategy.get_manager(),
+				false,
+				None,
+			)
+			.map(|(result, _, _)| result)?
+		};
 		self.backend.destroy_state(state)?;
 		Ok(return_data.into_encoded())
 	}
@@ -271,28 +366,32 @@ where
 				.map(|(result, _, _)| result)
 				.map_err(Into::into)
 			}
-			None => state_machine::new(
-				&state,
-				self.backend.changes_trie_storage(),
-				side_effects_handler,
-				&mut *changes.borrow_mut(),
-				&self.executor,
-				method,
-				call_data,
-			)
-			.execute_using_consensus_failure_handler(
-				execution_manager,
-				false,
-				native_call,
-			)
-			.map(|(result, _, _)| result)
-			.map_err(Into::into)
+			None => {
+				let state = SubstituteCode::new(&state, self.code_substitute(at)?);
+				state_machine::new(
+					&state,
+					self.backend.changes_trie_storage(),
+					side_effects_handler,
+					&mut *changes.borrow_mut(),
+					&self.executor,
+					method,
+					call_data,
+				)
+				.execute_using_consensus_failure_handler(
+					execution_manager,
+					false,
+					native_call,
+				)
+				.map(|(result, _, _)| result)
+				.map_err(Into::into)
+			}
 		}
 	}
 
 	fn runtime_version(&self, id: &BlockId<Block>) -> error::Result<RuntimeVersion> {
 		let mut overlay = OverlayedChanges::default();
 		let state = self.backend.state_at(*id)?;
+		let state = SubstituteCode::new(&state, self.code_substitute(id)?);
 		let mut ext = Ext::new(&mut overlay, &state, self.backend.changes_trie_storage(), NeverOffchainExt::new());
 		self.executor.runtime_version(&mut ext).ok_or(error::Error::VersionInvalid.into())
 	}
@@ -360,4 +459,43 @@ where
 	fn native_runtime_version(&self) -> Option<&NativeVersion> {
 		Some(self.executor.native_version())
 	}
+
+	fn code_substitute(&self, id: &BlockId<Block>) -> error::Result<Option<Vec<u8>>> {
+		if self.code_substitutes.is_empty() {
+			return Ok(None);
+		}
+
+		let number = self.backend.blockchain().expect_block_number_from_id(id)?;
+		Ok(self.code_substitutes.get(&number.saturated_into::<u64>()).cloned())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use state_machine::backend::InMemory;
+
+	#[test]
+	fn substitute_code_only_replaces_the_runtime_code() {
+		let state: InMemory<Blake2Hasher> = vec![
+			(well_known_keys::CODE.to_vec(), vec![1]),
+			(b"key".to_vec(), vec![2]),
+		].into_iter().collect::<HashMap<_, _>>().into();
+
+		let substituted = SubstituteCode::new(&state, Some(vec![3]));
+		assert_eq!(substituted.storage(well_known_keys::CODE).unwrap(), Some(vec![3]));
+		assert_eq!(
+			substituted.storage_hash(well_known_keys::CODE).unwrap(),
+			Some(Blake2Hasher::hash(&[3])),
+		);
+		assert_eq!(substituted.storage(b"key").unwrap(), Some(vec![2]));
+		assert_eq!(substituted.storage_hash(b"key").unwrap(), state.storage_hash(b"key").unwrap());
+
+		let unchanged = SubstituteCode::new(&state, None);
+		assert_eq!(unchanged.storage(well_known_keys::CODE).unwrap(), Some(vec![1]));
+		assert_eq!(
+			unchanged.storage_hash(well_known_keys::CODE).unwrap(),
+			state.storage_hash(well_known_keys::CODE).unwrap(),
+		);
+	}
 }
@@ -27,12 +27,12 @@ use std::collections::HashSet;
 
 use hash_db;
 use codec::Encode;
-use trie;
+use trie::{self, TrieMut, HashDBT, EMPTY_PREFIX};
 
 use primitives::{H256, convert_hash};
 use sr_primitives::traits::{Header as HeaderT, SimpleArithmetic, Zero, One};
 use state_machine::backend::InMemory as InMemoryState;
-use state_machine::{MemoryDB, TrieBackend, Backend as StateBackend,
+use state_machine::{MemoryDB, TrieBackend, TrieBackendStorage, Backend as StateBackend,
 	prove_read_on_trie_backend, read_proof_check, read_proof_check_on_proving_backend};
 
 use crate::error::{Error as ClientError, Result as ClientResult};
@@ -84,6 +84,36 @@ pub fn compute_root<Header, Hasher, I>(
 	))
 }
 
+/// Compute a CHT root from an iterator of block hashes, like `compute_root`, but also
+/// return the trie's nodes, from which proofs can be built with `build_proof_from_nodes`.
+pub fn compute_root_and_nodes<Header, Hasher, I>(
+	cht_size: Header::Number,
+	cht_num: Header::Number,
+	hashes: I,
+) -> ClientResult<(Hasher::Out, Vec<Vec<u8>>)>
+	where
+		Header: HeaderT,
+		Hasher: hash_db::Hasher,
+		Hasher::Out: Ord,
+		I: IntoIterator<Item=ClientResult<Option<Header::Hash>>>,
+{
+	let mut db = MemoryDB::<Hasher>::default();
+	let mut root = Default::default();
+	{
+		let mut trie = trie::trie_types::TrieDBMut::<Hasher>::new(&mut db, &mut root);
+		for (key, value) in build_pairs::<Header, I>(cht_size, cht_num, hashes)? {
+			trie.insert(&key, &value)
+				.map_err(|e| ClientError::Backend(format!("Failed to build CHT: {:?}", e)))?;
+		}
+	}
+	let nodes = db.drain()
+		.into_iter()
+		.filter(|(_, (_, rc))| *rc > 0)
+		.map(|(_, (node, _))| node.to_vec())
+		.collect();
+	Ok((root, nodes))
+}
+
 /// Build CHT-based header proof.
 pub fn build_proof<Header, Hasher, BlocksI, HashesI>(
 	cht_size: Header::Number,
@@ -105,12 +135,54 @@ pub fn build_proof<Header, Hasher, BlocksI, HashesI>(
 	let mut storage = InMemoryState::<Hasher>::default().update(transaction);
 	let trie_storage = storage.as_trie_backend()
 		.expect("InMemoryState::as_trie_backend always returns Some; qed");
+	prove_blocks::<Header, _, _, _>(cht_size, cht_num, trie_storage, blocks)
+}
+
+/// Build CHT-based header proof from the root and the nodes of the CHT, as returned by
+/// `compute_root_and_nodes`.
+pub fn build_proof_from_nodes<Header, Hasher, BlocksI>(
+	cht_size: Header::Number,
+	cht_num: Header::Number,
+	root: Hasher::Out,
+	nodes: Vec<Vec<u8>>,
+	blocks: BlocksI,
+) -> ClientResult<Vec<Vec<u8>>>
+	where
+		Header: HeaderT,
+		Hasher: hash_db::Hasher,
+		Hasher::Out: Ord,
+		BlocksI: IntoIterator<Item=Header::Number>,
+{
+	let mut storage = MemoryDB::<Hasher>::default();
+	for node in nodes {
+		storage.insert(EMPTY_PREFIX, &node);
+	}
+	let trie_storage = TrieBackend::new(storage, root);
+	prove_blocks::<Header, _, _, _>(cht_size, cht_num, &trie_storage, blocks)
+}
+
+/// Prove the hashes of the given blocks with the CHT in `trie_storage`.
+fn prove_blocks<Header, Hasher, S, BlocksI>(
+	cht_size: Header::Number,
+	cht_num: Header::Number,
+	trie_storage: &TrieBackend<S, Hasher>,
+	blocks: BlocksI,
+) -> ClientResult<Vec<Vec<u8>>>
+	where
+		Header: HeaderT,
+		Hasher: hash_db::Hasher,
+		Hasher::Out: Ord,
+		S: TrieBackendStorage<Hasher>,
+		BlocksI: IntoIterator<Item=Header::Number>,
+{
 	let mut total_proof = HashSet::new();
 	for block in blocks.into_iter() {
 		debug_assert_eq!(block_to_cht_number(cht_size, block), Some(cht_num));
 
 		let (value, proof) = prove_read_on_trie_backend(trie_storage, &encode_cht_key(block))?;
-		assert!(value.is_some(), "we have just built trie that includes the value for block");
+		if value.is_none() {
+			return Err(ClientError::MissingHashRequiredForCHT);
+		}
 		total_proof.extend(proof);
 	}
 	Ok(total_proof.into_iter().collect())
@@ -387,6 +459,35 @@ mod tests {
 		).is_ok());
 	}
 
+	#[test]
+	fn build_proof_from_nodes_works() {
+		let hashes = || (1..=SIZE as u64).map(|number| Ok(Some(H256::from_low_u64_be(number))));
+		let (root, nodes) = compute_root_and_nodes::<Header, Blake2Hasher, _>(SIZE as _, 0, hashes()).unwrap();
+		assert_eq!(root, compute_root::<Header, Blake2Hasher, _>(SIZE as _, 0, hashes()).unwrap());
+
+		let proof = build_proof_from_nodes::<Header, Blake2Hasher, _>(
+			SIZE as _,
+			0,
+			root,
+			nodes.clone(),
+			vec![(SIZE / 2) as u64],
+		).unwrap();
+		assert!(check_proof::<Header, Blake2Hasher>(
+			root,
+			(SIZE / 2) as u64,
+			H256::from_low_u64_be((SIZE / 2) as u64),
+			proof,
+		).is_ok());
+
+		assert!(build_proof_from_nodes::<Header, Blake2Hasher, _>(
+			SIZE as _,
+			0,
+			root,
+			nodes[1..].to_vec(),
+			(1..=SIZE as u64).collect::<Vec<_>>(),
+		).is_err());
+	}
+
 	#[test]
 	#[should_panic]
 	fn for_each_cht_group_panics() {
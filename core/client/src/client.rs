@@ -32,6 +32,7 @@ use consensus::{
 	Error as ConsensusError, BlockImportParams,
 	ImportResult, BlockOrigin, ForkChoiceStrategy,
 	well_known_cache_keys::Id as CacheKeyId,
+	import_queue::{ImportPhase, time_phase},
 	SelectChain, self,
 };
 use sr_primitives::traits::{
@@ -61,13 +62,13 @@ use hash_db::{Hasher, Prefix};
 
 use crate::backend::{
 	self, BlockImportOperation, PrunableStateChangesTrieStorage,
-	StorageCollection, ChildStorageCollection
+	StorageCollection, ChildStorageCollection, OffchainChangesCollection,
 };
 use crate::blockchain::{
 	self, Info as ChainInfo, Backend as ChainBackend,
 	HeaderBackend as ChainHeaderBackend, ProvideCache, Cache,
 };
-use crate::call_executor::{CallExecutor, LocalCallExecutor};
+use crate::call_executor::{CallExecutor, LocalCallExecutor, SubstituteCode};
 use executor::{RuntimeVersion, RuntimeInfo};
 use crate::notifications::{StorageNotifications, StorageEventStream};
 use crate::light::{call_executor::prove_execution, fetcher::ChangesProof};
@@ -466,7 +467,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 	}
 
 	/// Reads given header and generates CHT-based header proof.
-	pub fn header_proof(&self, id: &BlockId<Block>) -> error::Result<(Block::Header, Vec<Vec<u8>>)> {
+	pub fn header_proof(&self, id: &BlockId<Block>) -> error::Result<(Option<Block::Header>, Vec<Vec<u8>>)> {
 		self.header_proof_with_cht_size(id, cht::size())
 	}
 
@@ -478,15 +479,35 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 	}
 
 	/// Reads given header and generates CHT-based header proof for CHT of given size.
+	///
+	/// The proof of a header that has been pruned is built from its stored CHT, and is
+	/// returned without the header.
 	pub fn header_proof_with_cht_size(
 		&self,
 		id: &BlockId<Block>,
 		cht_size: NumberFor<Block>,
-	) -> error::Result<(Block::Header, Vec<Vec<u8>>)> {
+	) -> error::Result<(Option<Block::Header>, Vec<Vec<u8>>)> {
 		let proof_error = || error::Error::Backend(format!("Failed to generate header proof for {:?}", id));
-		let header = self.backend.blockchain().expect_header(*id)?;
-		let block_num = *header.number();
+		// the number of a pruned header can still be looked up, but not through the header
+		let block_num = match *id {
+			BlockId::Hash(hash) => self.backend.blockchain().number(hash)?
+				.ok_or_else(|| error::Error::UnknownBlock(format!("{}", id)))?,
+			BlockId::Number(number) => number,
+		};
 		let cht_num = cht::block_to_cht_number(cht_size, block_num).ok_or_else(proof_error)?;
+		if cht_size == cht::size() {
+			if let Some((root, nodes)) = self.backend.blockchain().header_cht(cht_num)? {
+				let proof = cht::build_proof_from_nodes::<Block::Header, Blake2Hasher, _>(
+					cht_size,
+					cht_num,
+					convert_hash(&root),
+					nodes,
+					::std::iter::once(block_num),
+				)?;
+				return Ok((self.backend.blockchain().header(*id)?, proof));
+			}
+		}
+		let header = self.backend.blockchain().expect_header(*id)?;
 		let cht_start = cht::start_number(cht_size, cht_num);
 		let mut current_num = cht_start;
 		let cht_range = ::std::iter::from_fn(|| {
@@ -496,7 +517,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		});
 		let headers = cht_range.map(|num| self.block_hash(num));
 		let proof = cht::build_proof::<Block::Header, Blake2Hasher, _, _>(cht_size, cht_num, ::std::iter::once(block_num), headers)?;
-		Ok((header, proof))
+		Ok((Some(header), proof))
 	}
 
 	/// Get longest range within [first; last] that is possible to use in `key_changes`
@@ -776,7 +797,14 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			let r = f(&mut op)?;
 
 			let ClientImportOperation { op, notify_imported, notify_finalized } = op;
+			// only time the commits of imported blocks, not of finality or head changes.
+			let commit_timer = if self.importing_block.read().is_some() {
+				Some(time_phase(ImportPhase::Commit))
+			} else {
+				None
+			};
 			self.backend.commit_operation(op)?;
+			drop(commit_timer);
 			self.notify_finalized(notify_finalized)?;
 
 			if let Some(notify_imported) = notify_imported {
@@ -921,7 +949,10 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		}
 
 		// FIXME #1232: correct path logic for when to execute this function
-		let (storage_update,changes_update,storage_changes) = self.block_execution(&operation.op, &import_headers, origin, hash, body.clone())?;
+		let execution_timer = time_phase(ImportPhase::Execution);
+		let (storage_update, changes_update, storage_changes, offchain_update) =
+			self.block_execution(&operation.op, &import_headers, origin, hash, body.clone())?;
+		drop(execution_timer);
 
 		let is_new_best = finalized || match fork_choice {
 			ForkChoiceStrategy::LongestChain => import_headers.post().number() > &last_best_number,
@@ -954,6 +985,9 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		if let Some(Some(changes_update)) = changes_update {
 			operation.op.update_changes_trie(changes_update)?;
 		}
+		if let Some(offchain_update) = offchain_update {
+			operation.op.update_offchain_storage(offchain_update)?;
+		}
 
 		operation.op.insert_aux(aux)?;
 
@@ -981,7 +1015,8 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		Option<(
 			Vec<(Vec<u8>, Option<Vec<u8>>)>,
 			Vec<(Vec<u8>, Vec<(Vec<u8>, Option<Vec<u8>>)>)>
-		)>
+		)>,
+		Option<OffchainChangesCollection>,
 	)>
 		where
 			E: CallExecutor<Block, Blake2Hasher> + Send + Sync + Clone,
@@ -1007,10 +1042,16 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 							);
 							wasm_result
 						}),
+						ExecutionStrategy::BothOrError => ExecutionManager::BothOrError,
 					}
 				};
-				let (_, storage_update, changes_update) = self.executor.call_at_state::<_, _, _, NeverNativeValue, fn() -> _>(
+				let parent = BlockId::Hash(*import_headers.pre().parent_hash());
+				let transaction_state = SubstituteCode::new(
 					transaction_state,
+					self.executor.code_substitute(&parent)?,
+				);
+				let (_, storage_update, changes_update) = self.executor.call_at_state::<_, _, _, NeverNativeValue, fn() -> _>(
+					&transaction_state,
 					&mut overlay,
 					"Core_execute_block",
 					&<Block as BlockT>::new(import_headers.pre().clone(), body.unwrap_or_default()).encode(),
@@ -1024,15 +1065,23 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 
 				overlay.commit_prospective();
 
+				let offchain_update = overlay.offchain_index_changes()
+					.map(|(k, v)| (k.to_vec(), v.map(<[u8]>::to_vec)))
+					.collect();
 				let (top, children) = overlay.into_committed();
 				let children = children.map(|(sk, it)| (sk, it.collect())).collect();
 				if import_headers.post().state_root() != &storage_update.1 {
 					return Err(error::Error::InvalidStateRoot);
 				}
 
-				Ok((Some(storage_update.0), Some(changes_update), Some((top.collect(), children))))
+				Ok((
+					Some(storage_update.0),
+					Some(changes_update),
+					Some((top.collect(), children)),
+					Some(offchain_update),
+				))
 			},
-			None => Ok((None, None, None))
+			None => Ok((None, None, None, None))
 		}
 	}
 
@@ -2004,6 +2053,63 @@ pub(crate) mod tests {
 		assert_eq!(client.body(&BlockId::Number(1)).unwrap().unwrap().len(), 1)
 	}
 
+	#[test]
+	fn block_builder_exposes_state_between_extrinsics() {
+		let client = test_client::new();
+		let genesis = BlockId::Number(0);
+
+		let mut builder = client.new_block(Default::default()).unwrap();
+		builder.push_inherents(Default::default()).unwrap();
+		builder.push_transfer(Transfer {
+			from: AccountKeyring::Alice.into(),
+			to: AccountKeyring::Ferdie.into(),
+			amount: 42,
+			nonce: 0,
+		}).unwrap();
+		assert_eq!(builder.api().balance_of(&genesis, AccountKeyring::Ferdie.into()).unwrap(), 42);
+
+		// A failing extrinsic leaves the state untouched.
+		assert!(builder.push_transfer(Transfer {
+			from: AccountKeyring::Ferdie.into(),
+			to: AccountKeyring::Alice.into(),
+			amount: 43,
+			nonce: 0,
+		}).is_err());
+		assert_eq!(builder.api().balance_of(&genesis, AccountKeyring::Ferdie.into()).unwrap(), 42);
+		assert_eq!(builder.extrinsics().len(), 1);
+	}
+
+	#[test]
+	fn block_builder_injects_apply_errors() {
+		let client = test_client::new();
+		let genesis = BlockId::Number(0);
+		let transfer = |amount| Transfer {
+			from: AccountKeyring::Alice.into(),
+			to: AccountKeyring::Ferdie.into(),
+			amount,
+			nonce: 0,
+		};
+
+		let mut builder = client.new_block(Default::default()).unwrap();
+		builder.set_apply_error_hook(|xt| match xt {
+			runtime::Extrinsic::Transfer(transfer, _) if transfer.amount == 13 =>
+				Some(sr_primitives::ApplyError::CantPay),
+			_ => None,
+		});
+
+		match builder.push_transfer(transfer(13)) {
+			Err(error::Error::ApplyExtrinsicFailed(sr_primitives::ApplyError::CantPay)) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+		assert_eq!(builder.api().balance_of(&genesis, AccountKeyring::Ferdie.into()).unwrap(), 0);
+		assert!(builder.extrinsics().is_empty());
+
+		builder.push_transfer(transfer(42)).unwrap();
+		assert_eq!(builder.api().balance_of(&genesis, AccountKeyring::Ferdie.into()).unwrap(), 42);
+		client.import(BlockOrigin::Own, builder.bake().unwrap()).unwrap();
+		assert_eq!(client.body(&BlockId::Number(1)).unwrap().unwrap().len(), 1);
+	}
+
 	#[test]
 	fn best_containing_with_genesis_block() {
 		// block tree:
@@ -2750,4 +2856,84 @@ pub(crate) mod tests {
 		client.set_head(BlockId::hash(b1.hash())).unwrap();
 		assert_eq!(950, current_balance());
 	}
+
+	#[test]
+	fn code_substitute_is_used_exactly_on_top_of_the_configured_block() {
+		let _ = env_logger::try_init();
+
+		// build the chain with a client that uses the on-chain code only
+		let client = test_client::new();
+		let mut blocks = Vec::new();
+		for _ in 0..3 {
+			let block = client.new_block(Default::default()).unwrap().bake().unwrap();
+			client.import(BlockOrigin::Own, block.clone()).unwrap();
+			blocks.push(block);
+		}
+
+		// the substitute is not a valid runtime, so any execution that uses it fails
+		let backend = Arc::new(test_client::Backend::new_test(std::u32::MAX, std::u64::MAX));
+		let executor = LocalCallExecutor::new(
+			backend.clone(),
+			executor::NativeExecutor::<test_client::LocalExecutor>::new(None),
+		).with_code_substitutes(vec![(2, vec![1, 2, 3])].into_iter().collect());
+		let client = TestClientBuilder::<test_client::Executor, _>::with_backend(backend)
+			.build_with_executor::<Block, RuntimeApi>(executor)
+			.0;
+
+		// blocks 1 and 2 are executed on top of blocks without a substitute
+		client.import(BlockOrigin::Own, blocks[0].clone()).unwrap();
+		client.import(BlockOrigin::Own, blocks[1].clone()).unwrap();
+
+		let call = |number| client.executor().call::<NeverOffchainExt>(
+			&BlockId::Number(number),
+			"Core_version",
+			&[],
+			ExecutionStrategy::NativeElseWasm,
+			None,
+		);
+		assert!(call(1).is_ok());
+		assert!(call(2).is_err());
+
+		assert!(client.runtime_version_at(&BlockId::Number(1)).is_ok());
+		assert!(client.runtime_version_at(&BlockId::Number(2)).is_err());
+
+		assert!(client.runtime_api().balance_of(&BlockId::Number(1), AccountKeyring::Alice.into()).is_ok());
+		assert!(client.runtime_api().balance_of(&BlockId::Number(2), AccountKeyring::Alice.into()).is_err());
+
+		// block 3 is executed on top of block 2 and so with the substitute
+		assert!(client.import(BlockOrigin::Own, blocks[2].clone()).is_err());
+		assert_eq!(client.info().chain.best_number, 2);
+	}
+
+	#[test]
+	fn header_proof_of_pruned_header_is_built_from_stored_cht() {
+		let backend = Arc::new(test_client::Backend::new_test_with_header_pruning(std::u32::MAX, std::u64::MAX));
+		let client = TestClientBuilder::with_backend(backend).build();
+		let cht_size: u64 = cht::size();
+
+		// finalizing block #{2 * cht::size() + 1} creates the first CHT and prunes its headers
+		let mut hashes = vec![client.genesis_hash()];
+		for number in 1..=(2 * cht_size + 1) {
+			let block = client.new_block(Default::default()).unwrap().bake().unwrap();
+			hashes.push(block.hash());
+			client.import(BlockOrigin::Own, block).unwrap();
+			client.finalize_block(BlockId::Number(number), None, false).unwrap();
+		}
+		assert!(client.header(&BlockId::Number(1)).unwrap().is_none());
+
+		let cht_root = cht::compute_root::<runtime::Header, Blake2Hasher, _>(
+			cht_size,
+			0,
+			hashes[1..].iter().map(|hash| Ok(Some(*hash))),
+		).unwrap();
+		for id in vec![BlockId::Number(1), BlockId::Hash(hashes[1])] {
+			let (header, proof) = client.header_proof(&id).unwrap();
+			assert!(header.is_none());
+			assert!(cht::check_proof::<runtime::Header, Blake2Hasher>(cht_root, 1, hashes[1], proof).is_ok());
+		}
+
+		// headers that are still stored are returned with their proof
+		let (header, _) = client.header_proof(&BlockId::Number(cht_size + 1)).unwrap();
+		assert_eq!(header.map(|header| header.hash()), Some(hashes[cht_size as usize + 1]));
+	}
 }
@@ -30,7 +30,9 @@ use trie::MemoryDB;
 use consensus::well_known_cache_keys::Id as CacheKeyId;
 
 use crate::error;
-use crate::backend::{self, NewBlockState, StorageCollection, ChildStorageCollection};
+use crate::backend::{
+	self, NewBlockState, StorageCollection, ChildStorageCollection, OffchainChangesCollection,
+};
 use crate::light;
 use crate::leaves::LeafSet;
 use crate::blockchain::{self, BlockStatus, HeaderBackend};
@@ -400,6 +402,30 @@ impl<Block: BlockT> light::blockchain::Storage<Block> for Blockchain<Block>
 		Ok(())
 	}
 
+	fn import_checkpoint(
+		&self,
+		header: Block::Header,
+		_cache: HashMap<CacheKeyId, Vec<u8>>,
+		aux_ops: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+	) -> error::Result<()> {
+		let hash = header.hash();
+		let number = header.number().clone();
+		{
+			let mut storage = self.storage.write();
+			storage.leaves = LeafSet::new();
+			storage.leaves.import(hash.clone(), number.clone(), header.parent_hash().clone());
+			storage.hashes.insert(number.clone(), hash.clone());
+			storage.blocks.insert(hash.clone(), StoredBlock::new(header, None, None));
+			storage.best_hash = hash.clone();
+			storage.best_number = number.clone();
+			storage.finalized_hash = hash;
+			storage.finalized_number = number;
+		}
+
+		self.write_aux(aux_ops);
+		Ok(())
+	}
+
 	fn set_head(&self, id: BlockId<Block>) -> error::Result<()> {
 		Blockchain::set_head(self, id)
 	}
@@ -489,6 +515,11 @@ where
 		Ok(())
 	}
 
+	fn update_offchain_storage(&mut self, _update: OffchainChangesCollection) -> error::Result<()> {
+		// there's no offchain storage in memory => ignore changes
+		Ok(())
+	}
+
 	fn reset_storage(&mut self, top: StorageOverlay, children: ChildrenStorageOverlay) -> error::Result<H::Out> {
 		check_genesis_storage(&top, &children)?;
 
@@ -793,6 +824,11 @@ impl backend::OffchainStorage for OffchainStorage {
 		self.storage.get(&key).cloned()
 	}
 
+	fn remove(&mut self, prefix: &[u8], key: &[u8]) {
+		let key: Vec<u8> = prefix.iter().chain(key).cloned().collect();
+		self.storage.remove(&key);
+	}
+
 	fn compare_and_set(
 		&mut self,
 		prefix: &[u8],
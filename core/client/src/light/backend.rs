@@ -27,12 +27,12 @@ use sr_primitives::traits::{Block as BlockT, NumberFor, Zero, Header};
 use crate::in_mem::{self, check_genesis_storage};
 use crate::backend::{
 	AuxStore, Backend as ClientBackend, BlockImportOperation, RemoteBackend, NewBlockState,
-	StorageCollection, ChildStorageCollection,
+	StorageCollection, ChildStorageCollection, OffchainChangesCollection,
 };
 use crate::blockchain::HeaderBackend as BlockchainHeaderBackend;
 use crate::error::{Error as ClientError, Result as ClientResult};
 use crate::light::blockchain::{Blockchain, Storage as BlockchainStorage};
-use crate::light::fetcher::{Fetcher, RemoteReadRequest};
+use crate::light::fetcher::{Fetcher, RemoteReadRequest, RemoteReadChildRequest};
 use hash_db::Hasher;
 use trie::MemoryDB;
 use consensus::well_known_cache_keys;
@@ -285,6 +285,11 @@ where
 		Ok(())
 	}
 
+	fn update_offchain_storage(&mut self, _update: OffchainChangesCollection) -> ClientResult<()> {
+		// we're not executing blocks => there are no changes
+		Ok(())
+	}
+
 	fn reset_storage(&mut self, top: StorageOverlay, children: ChildrenStorageOverlay) -> ClientResult<H::Out> {
 		check_genesis_storage(&top, &children)?;
 
@@ -337,6 +342,26 @@ where
 	}
 }
 
+impl<Block, S, F> OnDemandState<Block, S, F>
+where
+	Block: BlockT,
+	S: BlockchainStorage<Block>,
+	F: Fetcher<Block>,
+{
+	/// Returns the header of the state block, fetching it from the blockchain on first use.
+	fn header(&self) -> ClientResult<Block::Header> {
+		if let Some(header) = self.cached_header.read().clone() {
+			return Ok(header);
+		}
+
+		let header = self.blockchain.upgrade()
+			.ok_or_else(|| ClientError::UnknownBlock(format!("{}", self.block)))
+			.and_then(|blockchain| blockchain.expect_header(BlockId::Hash(self.block)))?;
+		*self.cached_header.write() = Some(header.clone());
+		Ok(header)
+	}
+}
+
 impl<Block, S, F, H> StateBackend<H> for OnDemandState<Block, S, F>
 where
 	Block: BlockT,
@@ -349,28 +374,30 @@ where
 	type TrieBackendStorage = MemoryDB<H>;
 
 	fn storage(&self, key: &[u8]) -> ClientResult<Option<Vec<u8>>> {
-		let mut header = self.cached_header.read().clone();
-		if header.is_none() {
-			let cached_header = self.blockchain.upgrade()
-				.ok_or_else(|| ClientError::UnknownBlock(format!("{}", self.block)))
-				.and_then(|blockchain| blockchain.expect_header(BlockId::Hash(self.block)))?;
-			header = Some(cached_header.clone());
-			*self.cached_header.write() = Some(cached_header);
-		}
-
+		let header = self.header()?;
 		futures::executor::block_on(
 			self.fetcher.upgrade().ok_or(ClientError::NotAvailableOnLightClient)?
 				.remote_read(RemoteReadRequest {
 					block: self.block,
-					header: header.expect("if block above guarantees that header is_some(); qed"),
+					header,
 					key: key.to_vec(),
 					retry_count: None,
 				})
 		)
 	}
 
-	fn child_storage(&self, _storage_key: &[u8], _key: &[u8]) -> ClientResult<Option<Vec<u8>>> {
-		Err(ClientError::NotAvailableOnLightClient.into())
+	fn child_storage(&self, storage_key: &[u8], key: &[u8]) -> ClientResult<Option<Vec<u8>>> {
+		let header = self.header()?;
+		futures::executor::block_on(
+			self.fetcher.upgrade().ok_or(ClientError::NotAvailableOnLightClient)?
+				.remote_read_child(RemoteReadChildRequest {
+					block: self.block,
+					header,
+					storage_key: storage_key.to_vec(),
+					key: key.to_vec(),
+					retry_count: None,
+				})
+		)
 	}
 
 	fn for_keys_with_prefix<A: FnMut(&[u8])>(&self, _prefix: &[u8], _action: A) {
@@ -45,6 +45,18 @@ pub trait Storage<Block: BlockT>: AuxStore + BlockchainHeaderBackend<Block> {
 		aux_ops: Vec<(Vec<u8>, Option<Vec<u8>>)>,
 	) -> ClientResult<()>;
 
+	/// Store the header of a finalized block whose ancestors are not stored, as the new best
+	/// and finalized block. Used to start syncing from a checkpoint instead of the genesis block.
+	///
+	/// Takes the values of the cache at the block and any auxiliary storage updates to place in
+	/// the same operation.
+	fn import_checkpoint(
+		&self,
+		header: Block::Header,
+		cache: HashMap<well_known_cache_keys::Id, Vec<u8>>,
+		aux_ops: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+	) -> ClientResult<()>;
+
 	/// Set an existing block as new best block.
 	fn set_head(&self, block: BlockId<Block>) -> ClientResult<()>;
 
@@ -278,6 +290,15 @@ pub mod tests {
 			Ok(())
 		}
 
+		fn import_checkpoint(
+			&self,
+			_header: Header,
+			_cache: HashMap<well_known_cache_keys::Id, Vec<u8>>,
+			_aux_ops: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+		) -> ClientResult<()> {
+			Err(ClientError::Backend("Test error".into()))
+		}
+
 		fn set_head(&self, _block: BlockId<Block>) -> ClientResult<()> {
 			Err(ClientError::Backend("Test error".into()))
 		}
@@ -28,7 +28,7 @@ pub use sr_primitives::{
 		Block as BlockT, GetNodeBlockType, GetRuntimeBlockType,
 		Header as HeaderT, ApiRef, RuntimeApiInfo, Hash as HashT,
 	},
-	generic::BlockId, transaction_validity::TransactionValidity,
+	generic::BlockId, transaction_validity::{TransactionValidity, TransactionSource},
 };
 #[doc(hidden)]
 pub use primitives::{offchain, ExecutionContext};
@@ -179,9 +179,25 @@ decl_runtime_apis! {
 	}
 
 	/// The `TaggedTransactionQueue` api trait for interfering with the new transaction queue.
+	#[api_version(2)]
 	pub trait TaggedTransactionQueue {
 		/// Validate the given transaction.
+		#[changed_in(2)]
 		fn validate_transaction(tx: <Block as BlockT>::Extrinsic) -> TransactionValidity;
+
+		/// Validate the given transaction.
+		///
+		/// The `source` describes where the transaction came from (local node, network or block).
+		fn validate_transaction(
+			source: TransactionSource,
+			tx: <Block as BlockT>::Extrinsic,
+		) -> TransactionValidity;
+	}
+
+	/// The `SessionKeys` api trait that describes the session keys of a runtime.
+	pub trait SessionKeys {
+		/// Returns the key types of the session keys, in the order in which they are encoded.
+		fn session_key_types() -> rstd::vec::Vec<sr_primitives::KeyTypeId>;
 	}
 }
 
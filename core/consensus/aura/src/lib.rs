@@ -37,6 +37,7 @@ use consensus_common::{self, BlockImport, Environment, Proposer,
 };
 use consensus_common::import_queue::{
 	Verifier, BasicQueue, BoxBlockImport, BoxJustificationImport, BoxFinalityProofImport,
+	PreVerifiedBlocks,
 };
 use client::{
 	block_builder::api::BlockBuilder as BlockBuilderApi,
@@ -49,7 +50,8 @@ use client::{
 use sr_primitives::{generic::{self, BlockId, OpaqueDigestItemId}, Justification};
 use sr_primitives::traits::{Block as BlockT, Header, DigestItemFor, ProvideRuntimeApi, Zero, Member};
 
-use primitives::Pair;
+use primitives::{Pair, crypto::Public};
+use keystore::Keystore;
 use inherents::{InherentDataProviders, InherentData};
 
 use futures::{prelude::*, future};
@@ -129,9 +131,11 @@ impl SlotCompatible for AuraSlotCompatible {
 }
 
 /// Start the aura worker. The returned future should be run in a futures executor.
+///
+/// Blocks are authored in the slots of the authorities whose keys of type `P` are in `keystore`.
 pub fn start_aura<B, C, SC, E, I, P, SO, Error, H>(
 	slot_duration: SlotDuration,
-	local_key: Arc<P>,
+	keystore: Arc<dyn Keystore>,
 	client: Arc<C>,
 	select_chain: SC,
 	block_import: I,
@@ -159,9 +163,10 @@ pub fn start_aura<B, C, SC, E, I, P, SO, Error, H>(
 		client: client.clone(),
 		block_import: Arc::new(Mutex::new(block_import)),
 		env,
-		local_key,
+		keystore,
 		sync_oracle: sync_oracle.clone(),
 		force_authoring,
+		_marker: PhantomData,
 	};
 	register_aura_inherent_data_provider(
 		&inherent_data_providers,
@@ -181,9 +186,10 @@ struct AuraWorker<C, E, I, P, SO> {
 	client: Arc<C>,
 	block_import: Arc<Mutex<I>>,
 	env: E,
-	local_key: Arc<P>,
+	keystore: Arc<dyn Keystore>,
 	sync_oracle: SO,
 	force_authoring: bool,
+	_marker: PhantomData<P>,
 }
 
 impl<H, B, C, E, I, P, Error, SO> SlotWorker<B> for AuraWorker<C, E, I, P, SO> where
@@ -208,8 +214,7 @@ impl<H, B, C, E, I, P, Error, SO> SlotWorker<B> for AuraWorker<C, E, I, P, SO> w
 		chain_head: B::Header,
 		slot_info: SlotInfo,
 	) -> Self::OnSlot {
-		let pair = self.local_key.clone();
-		let public_key = self.local_key.public();
+		let keystore = self.keystore.clone();
 		let client = self.client.clone();
 		let block_import = self.block_import.clone();
 
@@ -239,9 +244,9 @@ impl<H, B, C, E, I, P, Error, SO> SlotWorker<B> for AuraWorker<C, E, I, P, SO> w
 			return Box::pin(future::ready(Ok(())));
 		}
 		let maybe_author = slot_author::<P>(slot_num, &authorities);
-		let proposal_work = match maybe_author {
+		let (proposal_work, author) = match maybe_author {
 			None => return Box::pin(future::ready(Ok(()))),
-			Some(author) => if author == &public_key {
+			Some(author) => if keystore.has_key(P::KEY_TYPE, author.as_slice()) {
 				debug!(
 					target: "aura", "Starting authorship at slot {}; timestamp = {}",
 					slot_num,
@@ -266,7 +271,7 @@ impl<H, B, C, E, I, P, Error, SO> SlotWorker<B> for AuraWorker<C, E, I, P, SO> w
 				let remaining_duration = slot_info.remaining_duration();
 				// deadline our production to approx. the end of the
 				// slot
-				futures::future::select(
+				let proposal_work = futures::future::select(
 					proposer.propose(
 						slot_info.inherent_data,
 						generic::Digest {
@@ -283,7 +288,8 @@ impl<H, B, C, E, I, P, Error, SO> SlotWorker<B> for AuraWorker<C, E, I, P, SO> w
 					futures::future::Either::Right((Ok(_), _)) =>
 						Err(consensus_common::Error::ClientImport("Timeout in the AuRa proposer".into())),
 					futures::future::Either::Right((Err(err), _)) => Err(err),
-				})
+				});
+				(proposal_work, author.clone())
 			} else {
 				return Box::pin(future::ready(Ok(())));
 			}
@@ -319,7 +325,22 @@ impl<H, B, C, E, I, P, Error, SO> SlotWorker<B> for AuraWorker<C, E, I, P, SO> w
 			// sign the pre-sealed hash of the block and then
 			// add it to a digest item.
 			let header_hash = header.hash();
-			let signature = pair.sign(header_hash.as_ref());
+			let signature: P::Signature = match keystore.sign::<P>(&author, header_hash.as_ref()) {
+				Ok(signature) => match Decode::decode(&mut &signature[..]) {
+					Ok(signature) => signature,
+					Err(_) => {
+						error!(target: "aura", "Keystore returned a malformed signature");
+						return
+					},
+				},
+				Err(e) => {
+					warn!(target: "aura", "Failed to seal block at {}: {}", header_num, e);
+					telemetry!(CONSENSUS_WARN; "aura.err_sealing_block";
+						"header_num" => ?header_num, "err" => ?e
+					);
+					return
+				},
+			};
 			let signature_digest_item = <DigestItemFor<B> as CompatibleDigestItem<P>>::aura_seal(signature);
 
 			let import_block: BlockImportParams<B> = BlockImportParams {
@@ -381,9 +402,27 @@ fn find_pre_digest<B: BlockT, P: Pair>(header: &B::Header) -> Result<u64, String
 }
 
 
+/// Extract the authorities change digest from the given header, if it exists.
+fn find_authorities_change<B: BlockT, P: Pair>(header: &B::Header) -> Option<Vec<AuthorityId<P>>>
+	where P::Public: Decode,
+{
+	header.digest()
+		.logs()
+		.iter()
+		.filter_map(|l| l.try_to::<ConsensusLog<AuthorityId<P>>>(
+			OpaqueDigestItemId::Consensus(&AURA_ENGINE_ID)
+		))
+		.find_map(|l| match l {
+			ConsensusLog::AuthoritiesChange(a) => Some(a),
+			_ => None,
+		})
+}
+
 /// check a header has been signed by the right key. If the slot is too far in the future, an error will be returned.
 /// if it's successful, returns the pre-header and the digest item containing the seal.
 ///
+/// The signature isn't checked again if `seal_checked`, i.e. `check_seal` passed.
+///
 /// This digest item will always return `Some` when used with `as_aura_seal`.
 //
 // FIXME #1018 needs misbehavior types
@@ -393,6 +432,7 @@ fn check_header<C, B: BlockT, P: Pair>(
 	mut header: B::Header,
 	hash: B::Hash,
 	authorities: &[AuthorityId<P>],
+	seal_checked: bool,
 ) -> Result<CheckedHeader<B::Header, (u64, DigestItemFor<B>)>, String> where
 	DigestItemFor<B>: CompatibleDigestItem<P>,
 	P::Signature: Decode,
@@ -423,26 +463,61 @@ fn check_header<C, B: BlockT, P: Pair>(
 
 		let pre_hash = header.hash();
 
-		if P::verify(&sig, pre_hash.as_ref(), expected_author) {
-			if let Some(equivocation_proof) = check_equivocation(
-				client,
-				slot_now,
-				slot_num,
-				&header,
-				expected_author,
-			).map_err(|e| e.to_string())? {
-				info!(
-					"Slot author is equivocating at slot {} with headers {:?} and {:?}",
-					slot_num,
-					equivocation_proof.fst_header().hash(),
-					equivocation_proof.snd_header().hash(),
-				);
-			}
+		if !seal_checked && !P::verify(&sig, pre_hash.as_ref(), expected_author) {
+			return Err(format!("Bad signature on {:?}", hash));
+		}
 
-			Ok(CheckedHeader::Checked(header, (slot_num, seal)))
-		} else {
-			Err(format!("Bad signature on {:?}", hash))
+		if let Some(equivocation_proof) = check_equivocation(
+			client,
+			slot_now,
+			slot_num,
+			&header,
+			expected_author,
+		).map_err(|e| e.to_string())? {
+			info!(
+				"Slot author is equivocating at slot {} with headers {:?} and {:?}",
+				slot_num,
+				equivocation_proof.fst_header().hash(),
+				equivocation_proof.snd_header().hash(),
+			);
 		}
+
+		Ok(CheckedHeader::Checked(header, (slot_num, seal)))
+	}
+}
+
+/// Check that the seal of a header, its last digest, was signed by the author of its slot.
+///
+/// Unlike `check_header`, this doesn't need the current slot, so the import queue can run it
+/// ahead of the block import.
+fn check_seal<B: BlockT, P: Pair>(
+	mut header: B::Header,
+	authorities: &[AuthorityId<P>],
+) -> Result<(), String> where
+	DigestItemFor<B>: CompatibleDigestItem<P>,
+	P::Signature: Decode,
+	P::Public: AsRef<P::Public> + Encode + Decode + PartialEq + Clone,
+{
+	let hash = header.hash();
+	let seal = match header.digest_mut().pop() {
+		Some(x) => x,
+		None => return Err(format!("Header {:?} is unsealed", hash)),
+	};
+
+	let sig = seal.as_aura_seal().ok_or_else(|| {
+		aura_err!("Header {:?} has a bad seal", hash)
+	})?;
+
+	let slot_num = find_pre_digest::<B, _>(&header)?;
+	let expected_author = match slot_author::<P>(slot_num, &authorities) {
+		None => return Err("Slot Author not found".to_string()),
+		Some(author) => author,
+	};
+
+	if P::verify(&sig, header.hash().as_ref(), expected_author) {
+		Ok(())
+	} else {
+		Err(format!("Bad signature on {:?}", hash))
 	}
 }
 
@@ -451,6 +526,7 @@ pub struct AuraVerifier<C, P> {
 	client: Arc<C>,
 	phantom: PhantomData<P>,
 	inherent_data_providers: inherents::InherentDataProviders,
+	pre_verified: PreVerifiedBlocks,
 }
 
 impl<C, P> AuraVerifier<C, P>
@@ -538,6 +614,7 @@ impl<B: BlockT, C, P> Verifier<B> for AuraVerifier<C, P> where
 			header,
 			hash,
 			&authorities[..],
+			self.pre_verified.take(hash),
 		)?;
 		match checked_header {
 			CheckedHeader::Checked(pre_header, (slot_num, seal)) => {
@@ -570,18 +647,8 @@ impl<B: BlockT, C, P> Verifier<B> for AuraVerifier<C, P> where
 				telemetry!(CONSENSUS_TRACE; "aura.checked_and_importing"; "pre_header" => ?pre_header);
 
 				// Look for an authorities-change log.
-				let maybe_keys = pre_header.digest()
-					.logs()
-					.iter()
-					.filter_map(|l| l.try_to::<ConsensusLog<AuthorityId<P>>>(
-						OpaqueDigestItemId::Consensus(&AURA_ENGINE_ID)
-					))
-					.find_map(|l| match l {
-						ConsensusLog::AuthoritiesChange(a) => Some(
-							vec![(well_known_cache_keys::AUTHORITIES, a.encode())]
-						),
-						_ => None,
-					});
+				let maybe_keys = find_authorities_change::<B, P>(&pre_header)
+					.map(|a| vec![(well_known_cache_keys::AUTHORITIES, a.encode())]);
 
 				let import_block = BlockImportParams {
 					origin,
@@ -605,6 +672,22 @@ impl<B: BlockT, C, P> Verifier<B> for AuraVerifier<C, P> where
 			}
 		}
 	}
+
+	fn pre_verify(
+		&self,
+		header: &B::Header,
+		_body: Option<&[B::Extrinsic]>,
+		ancestry: &[B::Header],
+	) -> Result<(), String> {
+		// Without the authorities in the cache, `verify` checks the seal with the runtime.
+		let authorities = match ancestry_authorities::<B, C, P>(self.client.as_ref(), header, ancestry) {
+			Some(authorities) => authorities,
+			None => return Ok(()),
+		};
+		check_seal::<B, P>(header.clone(), &authorities[..])?;
+		self.pre_verified.insert(header.hash());
+		Ok(())
+	}
 }
 
 fn initialize_authorities_cache<A, B, C>(client: &C) -> Result<(), ConsensusError> where
@@ -647,14 +730,42 @@ fn authorities<A, B, C>(client: &C, at: &BlockId<B>) -> Result<Vec<A>, Consensus
 	C: ProvideRuntimeApi + ProvideCache<B>,
 	C::Api: AuraApi<B, A>,
 {
+	cached_authorities(client, at)
+		.or_else(|| AuraApi::authorities(&*client.runtime_api(), at).ok())
+		.ok_or_else(|| consensus_common::Error::InvalidAuthoritiesSet.into())
+}
+
+/// The authorities of the parent of `header`: the last authorities change of its ancestors in
+/// `ancestry`, which aren't imported yet, or the authorities in the cache at its nearest imported
+/// ancestor.
+fn ancestry_authorities<B: BlockT, C: ProvideCache<B>, P: Pair>(
+	client: &C,
+	header: &B::Header,
+	ancestry: &[B::Header],
+) -> Option<Vec<AuthorityId<P>>> where
+	P::Public: Decode,
+{
+	let mut parent_hash = *header.parent_hash();
+	for ancestor in ancestry.iter().rev() {
+		if ancestor.hash() != parent_hash {
+			continue;
+		}
+		if let Some(authorities) = find_authorities_change::<B, P>(ancestor) {
+			return Some(authorities);
+		}
+		parent_hash = *ancestor.parent_hash();
+	}
+	cached_authorities(client, &BlockId::Hash(parent_hash))
+}
+
+/// Read the authorities from the cache, without falling back to the runtime.
+fn cached_authorities<A: Codec, B: BlockT, C: ProvideCache<B>>(client: &C, at: &BlockId<B>) -> Option<Vec<A>> {
 	client
 		.cache()
 		.and_then(|cache| cache
 			.get_at(&well_known_cache_keys::AUTHORITIES, at)
 			.and_then(|v| Decode::decode(&mut &v[..]).ok())
 		)
-		.or_else(|| AuraApi::authorities(&*client.runtime_api(), at).ok())
-		.ok_or_else(|| consensus_common::Error::InvalidAuthoritiesSet.into())
 }
 
 /// The Aura import queue type.
@@ -700,6 +811,7 @@ pub fn import_queue<B, C, P>(
 			client: client.clone(),
 			inherent_data_providers,
 			phantom: PhantomData,
+			pre_verified: Default::default(),
 		}
 	);
 	Ok(BasicQueue::new(
@@ -721,6 +833,7 @@ mod tests {
 	use parking_lot::Mutex;
 	use tokio::runtime::current_thread;
 	use keyring::sr25519::Keyring;
+	use keystore::memory::MemoryStore;
 	use primitives::sr25519;
 	use client::{LongestChain, BlockchainEvents};
 	use test_client;
@@ -799,6 +912,7 @@ mod tests {
 						client,
 						inherent_data_providers,
 						phantom: Default::default(),
+						pre_verified: Default::default(),
 					})
 				},
 				PeersClient::Light(_) => unreachable!("No (yet) tests for light client + Aura"),
@@ -855,9 +969,12 @@ mod tests {
 				&inherent_data_providers, slot_duration.get()
 			).expect("Registers aura inherent data provider");
 
+			let keystore = MemoryStore::new();
+			keystore.insert::<sr25519::Pair>(&key.to_seed()).expect("Inserts the key");
+
 			let aura = start_aura::<_, _, _, _, _, sr25519::Pair, _, _, _>(
 				slot_duration,
-				Arc::new(key.clone().into()),
+				Arc::new(keystore),
 				client.clone(),
 				select_chain,
 				client,
@@ -879,6 +996,70 @@ mod tests {
 			.map(|_| Ok::<(), ()>(())).compat()).unwrap();
 	}
 
+	#[test]
+	fn check_seal_checks_the_signature_of_the_slot_author() {
+		let authorities: Vec<AuthorityId<sr25519::Pair>> = vec![
+			Keyring::Alice.into(),
+			Keyring::Bob.into(),
+		];
+		let sealed = |slot_num: u64, keyring: Keyring| {
+			let pre_digest: DigestItemFor<TestBlock> =
+				CompatibleDigestItem::<sr25519::Pair>::aura_pre_digest(slot_num);
+			let mut header = <TestBlock as BlockT>::Header::new(
+				1,
+				Default::default(),
+				Default::default(),
+				Default::default(),
+				generic::Digest { logs: vec![pre_digest] },
+			);
+			let seal: DigestItemFor<TestBlock> =
+				CompatibleDigestItem::<sr25519::Pair>::aura_seal(keyring.pair().sign(header.hash().as_ref()));
+			header.digest_mut().push(seal);
+			header
+		};
+
+		assert_eq!(check_seal::<TestBlock, sr25519::Pair>(sealed(2, Keyring::Alice), &authorities), Ok(()));
+		assert_eq!(check_seal::<TestBlock, sr25519::Pair>(sealed(3, Keyring::Bob), &authorities), Ok(()));
+		assert!(check_seal::<TestBlock, sr25519::Pair>(sealed(3, Keyring::Alice), &authorities).is_err());
+
+		let mut unsealed = sealed(2, Keyring::Alice);
+		unsealed.digest_mut().pop();
+		assert!(check_seal::<TestBlock, sr25519::Pair>(unsealed, &authorities).is_err());
+	}
+
+	#[test]
+	fn ancestry_authorities_follow_the_changes_of_unimported_ancestors() {
+		let client = test_client::new();
+		initialize_authorities_cache(&client).unwrap();
+		let cached: Vec<AuthorityId<sr25519::Pair>> =
+			cached_authorities(&client, &BlockId::Number(0)).expect("The cache is initialized");
+		let changed: Vec<AuthorityId<sr25519::Pair>> = vec![Keyring::Dave.into()];
+
+		let header = |number, parent_hash, logs| <TestBlock as BlockT>::Header::new(
+			number,
+			Default::default(),
+			Default::default(),
+			parent_hash,
+			generic::Digest { logs },
+		);
+		let change: DigestItemFor<TestBlock> = DigestItemFor::<TestBlock>::Consensus(
+			AURA_ENGINE_ID,
+			ConsensusLog::AuthoritiesChange(changed.clone()).encode(),
+		);
+		let first = header(1, client.info().chain.genesis_hash, vec![]);
+		let second = header(2, first.hash(), vec![change]);
+		let third = header(3, second.hash(), vec![]);
+		let fork = header(3, first.hash(), vec![]);
+
+		let authorities = |header, ancestry: &[_]| {
+			ancestry_authorities::<TestBlock, _, sr25519::Pair>(&client, header, ancestry)
+		};
+		assert_eq!(authorities(&first, &[]), Some(cached.clone()));
+		assert_eq!(authorities(&second, &[first.clone()]), Some(cached.clone()));
+		assert_eq!(authorities(&third, &[first.clone(), second.clone()]), Some(changed));
+		assert_eq!(authorities(&fork, &[first, second]), Some(cached));
+	}
+
 	#[test]
 	fn authorities_call_works() {
 		let client = test_client::new();
@@ -35,8 +35,8 @@ use std::{collections::HashMap, sync::Arc, u64, fmt::{Debug, Display}, pin::Pin,
 use runtime_support::serde::{Serialize, Deserialize};
 use codec::{Decode, Encode};
 use parking_lot::{Mutex, MutexGuard};
-use primitives::{Blake2Hasher, H256, Pair, Public, sr25519};
-use merlin::Transcript;
+use primitives::{Blake2Hasher, H256, Pair, Public, sr25519, crypto::key_types};
+use keystore::{Keystore, VrfTranscriptData};
 use inherents::{InherentDataProviders, InherentData};
 use substrate_telemetry::{
 	telemetry,
@@ -45,12 +45,7 @@ use substrate_telemetry::{
 	CONSENSUS_WARN,
 	CONSENSUS_INFO,
 };
-use schnorrkel::{
-	keys::Keypair,
-	vrf::{
-		VRFProof, VRFProofBatchable, VRFInOut,
-	},
-};
+use schnorrkel::vrf::{VRFOutput, VRFProof, VRFInOut};
 use consensus_common::{
 	self, BlockImport, Environment, Proposer,
 	ForkChoiceStrategy, BlockImportParams, BlockOrigin, Error as ConsensusError,
@@ -60,7 +55,7 @@ use srml_babe::{
 	timestamp::{TimestampInherentData, InherentType as TimestampInherent}
 };
 use consensus_common::{SelectChain, well_known_cache_keys};
-use consensus_common::import_queue::{Verifier, BasicQueue};
+use consensus_common::import_queue::{Verifier, BasicQueue, PreVerifiedBlocks};
 use client::{
 	block_builder::api::BlockBuilder as BlockBuilderApi,
 	blockchain::{self, HeaderBackend, ProvideCache},
@@ -140,8 +135,8 @@ pub struct BabeParams<C, E, I, SO, SC> {
 	/// other parameters.
 	pub config: Config,
 
-	/// The key of the node we are running on.
-	pub local_key: Arc<sr25519::Pair>,
+	/// The keystore that holds the key of the node we are running on.
+	pub keystore: Arc<dyn Keystore>,
 
 	/// The client to use
 	pub client: Arc<C>,
@@ -171,7 +166,7 @@ pub struct BabeParams<C, E, I, SO, SC> {
 /// Start the babe worker. The returned future should be run in a tokio runtime.
 pub fn start_babe<B, C, SC, E, I, SO, Error, H>(BabeParams {
 	config,
-	local_key,
+	keystore,
 	client,
 	select_chain,
 	block_import,
@@ -200,7 +195,7 @@ pub fn start_babe<B, C, SC, E, I, SO, Error, H>(BabeParams {
 		client: client.clone(),
 		block_import: Arc::new(Mutex::new(block_import)),
 		env,
-		local_key,
+		keystore,
 		sync_oracle: sync_oracle.clone(),
 		force_authoring,
 		c: config.c(),
@@ -220,7 +215,7 @@ struct BabeWorker<C, E, I, SO> {
 	client: Arc<C>,
 	block_import: Arc<Mutex<I>>,
 	env: E,
-	local_key: Arc<sr25519::Pair>,
+	keystore: Arc<dyn Keystore>,
 	sync_oracle: SO,
 	force_authoring: bool,
 	c: (u64, u64),
@@ -248,7 +243,7 @@ impl<Hash, H, B, C, E, I, Error, SO> SlotWorker<B> for BabeWorker<C, E, I, SO> w
 		chain_head: B::Header,
 		slot_info: SlotInfo,
 	) -> Self::OnSlot {
-		let pair = self.local_key.clone();
+		let keystore = self.keystore.clone();
 		let ref client = self.client;
 		let block_import = self.block_import.clone();
 
@@ -285,13 +280,13 @@ impl<Hash, H, B, C, E, I, Error, SO> SlotWorker<B> for BabeWorker<C, E, I, SO> w
 			return Box::pin(future::ready(Ok(())));
 		}
 
-		let proposal_work = if let Some(claim) = claim_slot(
+		let (proposal_work, author) = if let Some(claim) = claim_slot(
 			slot_info.number,
 			epoch,
-			&pair,
+			&*keystore,
 			self.c,
 		) {
-			let ((inout, vrf_proof, _batchable_proof), authority_index) = claim;
+			let ((inout, vrf_proof), authority_index, author) = claim;
 
 			debug!(
 				target: "babe", "Starting authorship at slot {}; timestamp = {}",
@@ -327,7 +322,7 @@ impl<Hash, H, B, C, E, I, Error, SO> SlotWorker<B> for BabeWorker<C, E, I, SO> w
 
 			// deadline our production to approx. the end of the slot
 			let remaining_duration = slot_info.remaining_duration();
-			futures::future::select(
+			let proposal_work = futures::future::select(
 				proposer.propose(
 					slot_info.inherent_data,
 					generic::Digest {
@@ -344,7 +339,8 @@ impl<Hash, H, B, C, E, I, Error, SO> SlotWorker<B> for BabeWorker<C, E, I, SO> w
 				futures::future::Either::Right((Ok(_), _)) =>
 					Err(consensus_common::Error::ClientImport("Timeout in the BaBe proposer".into())),
 				futures::future::Either::Right((Err(err), _)) => Err(err),
-			})
+			});
+			(proposal_work, author)
 		} else {
 			return Box::pin(future::ready(Ok(())));
 		};
@@ -372,7 +368,16 @@ impl<Hash, H, B, C, E, I, Error, SO> SlotWorker<B> for BabeWorker<C, E, I, SO> w
 			// sign the pre-sealed hash of the block and then
 			// add it to a digest item.
 			let header_hash = header.hash();
-			let signature = pair.sign(header_hash.as_ref());
+			let signature = match keystore.sign::<sr25519::Pair>(&author, header_hash.as_ref()) {
+				Ok(signature) => sr25519::Signature::from_slice(&signature),
+				Err(e) => {
+					warn!(target: "babe", "Failed to seal block at {}: {}", header_num, e);
+					telemetry!(CONSENSUS_WARN; "babe.err_sealing_block";
+						"header_num" => ?header_num, "err" => ?e
+					);
+					return;
+				}
+			};
 			let signature_digest_item = DigestItemFor::<B>::babe_seal(signature);
 
 			let import_block = BlockImportParams::<B> {
@@ -460,6 +465,8 @@ fn find_next_epoch_digest<B: BlockT>(header: &B::Header) -> Result<Option<Epoch>
 /// The seal must be the last digest.  Otherwise, the whole header is considered
 /// unsigned.  This is required for security and must not be changed.
 ///
+/// The signature and VRF aren't checked again if `seal_checked`, i.e. `check_seal` passed.
+///
 /// This digest item will always return `Some` when used with `as_babe_pre_digest`.
 // FIXME #1018 needs misbehavior types
 fn check_header<B: BlockT + Sized, C: AuxStore>(
@@ -471,6 +478,7 @@ fn check_header<B: BlockT + Sized, C: AuxStore>(
 	randomness: [u8; 32],
 	epoch_index: u64,
 	c: (u64, u64),
+	seal_checked: bool,
 ) -> Result<CheckedHeader<B::Header, (DigestItemFor<B>, DigestItemFor<B>)>, String>
 	where DigestItemFor<B>: CompatibleDigestItem,
 {
@@ -486,59 +494,110 @@ fn check_header<B: BlockT + Sized, C: AuxStore>(
 
 	let pre_digest = find_pre_digest::<B>(&header)?;
 
-	let BabePreDigest { slot_number, authority_index, ref vrf_proof, ref vrf_output } = pre_digest;
+	let BabePreDigest { slot_number, authority_index, .. } = pre_digest;
 
 	if slot_number > slot_now {
 		header.digest_mut().push(seal);
 		Ok(CheckedHeader::Deferred(header, slot_number))
-	} else if authority_index > authorities.len() as u32 {
+	} else if authority_index >= authorities.len() as u32 {
 		Err(babe_err!("Slot author not found"))
 	} else {
-		let (pre_hash, author) = (header.hash(), &authorities[authority_index as usize].0);
-
-		if sr25519::Pair::verify(&sig, pre_hash, author.clone()) {
-			let (inout, _batchable_proof) = {
-				let transcript = make_transcript(
-					&randomness,
-					slot_number,
-					epoch_index,
-				);
+		let author = &authorities[authority_index as usize].0;
 
-				schnorrkel::PublicKey::from_bytes(author.as_slice()).and_then(|p| {
-					p.vrf_verify(transcript, vrf_output, vrf_proof)
-				}).map_err(|s| {
-					babe_err!("VRF verification failed: {:?}", s)
-				})?
-			};
-
-			let threshold = calculate_threshold(c, authorities, authority_index as usize);
-			if !check(&inout, threshold) {
-				return Err(babe_err!("VRF verification of block by author {:?} failed: \
-									  threshold {} exceeded", author, threshold));
-			}
+		if !seal_checked {
+			check_slot_claim::<B>(&header, &sig, &pre_digest, authorities, randomness, epoch_index, c)?;
+		}
 
-			if let Some(equivocation_proof) = check_equivocation(
-				client,
-				slot_now,
-				slot_number,
-				&header,
+		if let Some(equivocation_proof) = check_equivocation(
+			client,
+			slot_now,
+			slot_number,
+			&header,
+			author,
+		).map_err(|e| e.to_string())? {
+			info!(
+				"Slot author {:?} is equivocating at slot {} with headers {:?} and {:?}",
 				author,
-			).map_err(|e| e.to_string())? {
-				info!(
-					"Slot author {:?} is equivocating at slot {} with headers {:?} and {:?}",
-					author,
-					slot_number,
-					equivocation_proof.fst_header().hash(),
-					equivocation_proof.snd_header().hash(),
-				);
-			}
-
-			let pre_digest = CompatibleDigestItem::babe_pre_digest(pre_digest);
-			Ok(CheckedHeader::Checked(header, (pre_digest, seal)))
-		} else {
-			Err(babe_err!("Bad signature on {:?}", hash))
+				slot_number,
+				equivocation_proof.fst_header().hash(),
+				equivocation_proof.snd_header().hash(),
+			);
 		}
+
+		let pre_digest = CompatibleDigestItem::babe_pre_digest(pre_digest);
+		Ok(CheckedHeader::Checked(header, (pre_digest, seal)))
+	}
+}
+
+/// Check that the pre-header `header` was signed with `sig` by the author of its slot, and that
+/// the VRF output of its pre-digest is valid and below the threshold of the author.
+fn check_slot_claim<B: BlockT>(
+	header: &B::Header,
+	sig: &sr25519::Signature,
+	pre_digest: &BabePreDigest,
+	authorities: &[(AuthorityId, BabeWeight)],
+	randomness: [u8; 32],
+	epoch_index: u64,
+	c: (u64, u64),
+) -> Result<(), String> {
+	let BabePreDigest { slot_number, authority_index, ref vrf_proof, ref vrf_output } = *pre_digest;
+	let author = match authorities.get(authority_index as usize) {
+		Some((author, _)) => author,
+		None => return Err(babe_err!("Slot author not found")),
+	};
+
+	let pre_hash = header.hash();
+	if !sr25519::Pair::verify(sig, pre_hash, author.clone()) {
+		return Err(babe_err!("Bad signature on {:?}", pre_hash));
+	}
+
+	let (inout, _batchable_proof) = {
+		let transcript = make_transcript(
+			&randomness,
+			slot_number,
+			epoch_index,
+		).transcript();
+
+		schnorrkel::PublicKey::from_bytes(author.as_slice()).and_then(|p| {
+			p.vrf_verify(transcript, vrf_output, vrf_proof)
+		}).map_err(|s| {
+			babe_err!("VRF verification failed: {:?}", s)
+		})?
+	};
+
+	let threshold = calculate_threshold(c, authorities, authority_index as usize);
+	if !check(&inout, threshold) {
+		return Err(babe_err!("VRF verification of block by author {:?} failed: \
+							  threshold {} exceeded", author, threshold));
 	}
+
+	Ok(())
+}
+
+/// Check that the seal of a header, its last digest, was signed by the author of its slot, who
+/// claimed the slot with the VRF output of the pre-digest.
+///
+/// Unlike `check_header`, this doesn't need the current slot or the state of the chain beyond
+/// the epoch, so the import queue can run it ahead of the block import.
+fn check_seal<B: BlockT>(
+	mut header: B::Header,
+	epoch: &Epoch,
+	c: (u64, u64),
+) -> Result<(), String>
+	where DigestItemFor<B>: CompatibleDigestItem,
+{
+	let hash = header.hash();
+	let seal = match header.digest_mut().pop() {
+		Some(x) => x,
+		None => return Err(babe_err!("Header {:?} is unsealed", hash)),
+	};
+
+	let sig = seal.as_babe_seal().ok_or_else(|| {
+		babe_err!("Header {:?} has a bad seal", hash)
+	})?;
+
+	let pre_digest = find_pre_digest::<B>(&header)?;
+	check_slot_claim::<B>(&header, &sig, &pre_digest, &epoch.authorities, epoch.randomness, epoch.epoch_index, c)
 }
 
 /// State that must be shared between the import queue and the authoring logic.
@@ -551,6 +610,7 @@ pub struct BabeVerifier<C> {
 	inherent_data_providers: inherents::InherentDataProviders,
 	config: Config,
 	time_source: BabeLink,
+	pre_verified: PreVerifiedBlocks,
 }
 
 impl<C> BabeVerifier<C> {
@@ -669,6 +729,7 @@ impl<B: BlockT, C> Verifier<B> for BabeVerifier<C> where
 			randomness,
 			epoch_index,
 			self.config.c(),
+			self.pre_verified.take(hash),
 		)?;
 
 		match checked_header {
@@ -721,6 +782,22 @@ impl<B: BlockT, C> Verifier<B> for BabeVerifier<C> where
 			}
 		}
 	}
+
+	fn pre_verify(
+		&self,
+		header: &B::Header,
+		_body: Option<&[B::Extrinsic]>,
+		ancestry: &[B::Header],
+	) -> Result<(), String> {
+		// Without the epoch, `verify` checks the seal once the parent is imported.
+		let epoch = match ancestry_epoch::<B, C>(self.api.as_ref(), header, ancestry) {
+			Some(epoch) => epoch,
+			None => return Ok(()),
+		};
+		check_seal::<B>(header.clone(), &epoch, self.config.c())?;
+		self.pre_verified.insert(header.hash());
+		Ok(())
+	}
 }
 
 /// Extract current epoch data from cache and fallback to querying the runtime
@@ -730,10 +807,7 @@ fn epoch<B, C>(client: &C, at: &BlockId<B>) -> Result<Epoch, ConsensusError> whe
 	C: ProvideRuntimeApi + ProvideCache<B>,
 	C::Api: BabeApi<B>,
 {
-	client
-		.cache()
-		.and_then(|cache| cache.get_at(&well_known_cache_keys::EPOCH, at)
-			.and_then(|v| Decode::decode(&mut &v[..]).ok()))
+	cached_epoch(client, at)
 		.or_else(|| {
 			if client.runtime_api().has_api::<dyn BabeApi<B>>(at).unwrap_or(false) {
 				let s = BabeApi::epoch(&*client.runtime_api(), at).ok()?;
@@ -750,6 +824,50 @@ fn epoch<B, C>(client: &C, at: &BlockId<B>) -> Result<Epoch, ConsensusError> whe
 		}).ok_or(consensus_common::Error::InvalidAuthoritiesSet)
 }
 
+/// The epoch at the parent of `header`, as `epoch` reads it once the parent is imported.
+///
+/// The ancestors of `header` in `ancestry` aren't imported yet. The last of them signalling an
+/// epoch enacts the epoch signalled by the previous one. Without epoch signal, the epoch is in
+/// the cache at the nearest imported ancestor. With a single signal, the enacted epoch was
+/// signalled before the imported ancestors and `None` is returned, as without cache.
+fn ancestry_epoch<B: BlockT, C: ProvideCache<B>>(
+	client: &C,
+	header: &B::Header,
+	ancestry: &[B::Header],
+) -> Option<Epoch> where
+	DigestItemFor<B>: CompatibleDigestItem,
+{
+	// The epochs signalled by the ancestors of `header`, from the latest.
+	let mut signalled = Vec::new();
+	let mut parent_hash = *header.parent_hash();
+	for ancestor in ancestry.iter().rev() {
+		if ancestor.hash() != parent_hash {
+			continue;
+		}
+		if let Some(epoch) = find_next_epoch_digest::<B>(ancestor).ok()? {
+			signalled.push(epoch);
+			if signalled.len() == 2 {
+				return signalled.pop();
+			}
+		}
+		parent_hash = *ancestor.parent_hash();
+	}
+
+	if signalled.is_empty() {
+		cached_epoch(client, &BlockId::Hash(parent_hash))
+	} else {
+		None
+	}
+}
+
+/// Read the epoch data from the cache, without falling back to the runtime.
+fn cached_epoch<B: BlockT, C: ProvideCache<B>>(client: &C, at: &BlockId<B>) -> Option<Epoch> {
+	client
+		.cache()
+		.and_then(|cache| cache.get_at(&well_known_cache_keys::EPOCH, at)
+			.and_then(|v| Decode::decode(&mut &v[..]).ok()))
+}
+
 /// The BABE import queue type.
 pub type BabeImportQueue<B> = BasicQueue<B>;
 
@@ -769,21 +887,19 @@ fn register_babe_inherent_data_provider(
 	}
 }
 
-fn get_keypair(q: &sr25519::Pair) -> &Keypair {
-	q.as_ref()
-}
-
-#[allow(deprecated)]
 fn make_transcript(
 	randomness: &[u8],
 	slot_number: u64,
 	epoch: u64,
-) -> Transcript {
-	let mut transcript = Transcript::new(&BABE_ENGINE_ID);
-	transcript.commit_bytes(b"slot number", &slot_number.to_le_bytes());
-	transcript.commit_bytes(b"current epoch", &epoch.to_le_bytes());
-	transcript.commit_bytes(b"chain randomness", randomness);
-	transcript
+) -> VrfTranscriptData {
+	VrfTranscriptData {
+		label: &BABE_ENGINE_ID,
+		items: vec![
+			(b"slot number", slot_number.to_le_bytes().to_vec()),
+			(b"current epoch", epoch.to_le_bytes().to_vec()),
+			(b"chain randomness", randomness.to_vec()),
+		],
+	}
 }
 
 fn check(inout: &VRFInOut, threshold: u128) -> bool {
@@ -818,27 +934,44 @@ fn calculate_threshold(
 /// Claim a slot if it is our turn.  Returns `None` if it is not our turn.
 ///
 /// This hashes the slot number, epoch, genesis hash, and chain randomness into
-/// the VRF.  If the VRF produces a value less than `threshold`, it is our turn,
-/// so it returns `Some(_)`.  Otherwise, it returns `None`.
+/// the VRF of the first authority whose key is in `keystore`.  If the VRF
+/// produces a value less than `threshold`, it is our turn, so it returns the
+/// VRF, the index of the authority and its key.  Otherwise, it returns `None`.
 fn claim_slot(
 	slot_number: u64,
 	Epoch { ref authorities, ref randomness, epoch_index, .. }: Epoch,
-	key: &sr25519::Pair,
+	keystore: &dyn Keystore,
 	c: (u64, u64),
-) -> Option<((VRFInOut, VRFProof, VRFProofBatchable), usize)> {
-	let public = &key.public();
-	let authority_index = authorities.iter().position(|s| &s.0 == public)?;
+) -> Option<((VRFInOut, VRFProof), usize, AuthorityId)> {
+	let keys = keystore.public_keys_by_type(key_types::SR25519)
+		.map_err(|e| warn!(target: "babe", "Unable to get keys from the keystore: {}", e))
+		.ok()?;
+	let authority_index = authorities.iter().position(|s| keys.iter().any(|k| &k[..] == s.0.as_slice()))?;
+	let author = authorities[authority_index].0.clone();
 	let transcript = make_transcript(randomness, slot_number, epoch_index);
 
 	// Compute the threshold we will use.
 	//
-	// We already checked that authorities contains `key.public()`, so it can't
+	// We already checked that authorities contains our key, so it can't
 	// be empty.  Therefore, this division in `calculate_threshold` is safe.
 	let threshold = calculate_threshold(c, authorities, authority_index);
 
-	get_keypair(key)
-		.vrf_sign_n_check(transcript, |inout| check(inout, threshold))
-		.map(|s|(s, authority_index))
+	let signature = keystore.sr25519_vrf_sign(author.as_slice(), &transcript)
+		.map_err(|e| warn!(target: "babe", "Unable to compute the VRF of slot {}: {}", slot_number, e))
+		.ok()?;
+	// The keystore might be an external signer, so the VRF is checked like one of a
+	// block we import.
+	let output = VRFOutput::from_bytes(signature.output.as_bytes()).ok()?;
+	let proof = VRFProof::from_bytes(signature.proof.as_bytes()).ok()?;
+	let (inout, _) = schnorrkel::PublicKey::from_bytes(author.as_slice())
+		.and_then(|p| p.vrf_verify(transcript.transcript(), &output, &proof))
+		.ok()?;
+
+	if check(&inout, threshold) {
+		Some(((inout, proof), authority_index, author))
+	} else {
+		None
+	}
 }
 
 fn initialize_authorities_cache<B, C>(client: &C) -> Result<(), ConsensusError> where
@@ -1116,6 +1249,54 @@ impl<B, E, Block, I, RA, PRA> BlockImport<Block> for BabeBlockImport<B, E, Block
 	}
 }
 
+/// Move the epoch changes onto the finalized block when none of their roots is known locally,
+/// which happens when the chain has been started from a light sync state instead of genesis.
+/// The epoch signalled before the finalized block is kept, the later ones are tracked again
+/// when their blocks are imported.
+fn rebase_epoch_changes<B, E, Block: BlockT<Hash=H256>, RA>(
+	client: &Client<B, E, Block, RA>,
+	epoch_changes: &SharedEpochChanges<Block>,
+) -> ClientResult<()> where
+	B: Backend<Block, Blake2Hasher>,
+	E: CallExecutor<Block, Blake2Hasher> + Send + Sync,
+{
+	let mut epoch_changes = epoch_changes.lock();
+	for (root, _, _) in epoch_changes.roots() {
+		if client.header(&BlockId::Hash(*root))?.is_some() {
+			return Ok(());
+		}
+	}
+
+	let info = client.info().chain;
+	let finalized_epoch = epoch_changes.roots()
+		.filter(|(_, number, _)| **number <= info.finalized_number)
+		.max_by_key(|(_, number, _)| **number)
+		.map(|(_, _, epoch)| epoch.clone());
+	let finalized_epoch = match finalized_epoch {
+		Some(epoch) => epoch,
+		None => return Ok(()),
+	};
+
+	info!(target: "babe",
+		"Moving the BABE epoch changes onto the finalized block #{}",
+		info.finalized_number,
+	);
+
+	let mut rebased = EpochChanges::<Block>::new();
+	rebased.import(
+		info.finalized_hash,
+		info.finalized_number,
+		finalized_epoch,
+		&|_, _| Ok::<_, client::error::Error>(false),
+	).map_err(|e| client::error::Error::Backend(e.to_string()))?;
+	*epoch_changes = rebased;
+
+	aux_schema::write_epoch_changes::<Block, _, _>(
+		&*epoch_changes,
+		|insert| client.insert_aux(insert, &[]),
+	)
+}
+
 /// Start an import queue for the BABE consensus algorithm. This method returns
 /// the import queue, some data that needs to be passed to the block authoring
 /// logic (`BabeLink`), a `BabeBlockImport` which should be used by the
@@ -1152,10 +1333,12 @@ pub fn import_queue<B, E, Block: BlockT<Hash=H256>, I, RA, PRA>(
 		inherent_data_providers,
 		time_source: Default::default(),
 		config,
+		pre_verified: Default::default(),
 	};
 
 	#[allow(deprecated)]
 	let epoch_changes = aux_schema::load_epoch_changes(&**client.backend())?;
+	rebase_epoch_changes(&*client, &epoch_changes)?;
 
 	let block_import = BabeBlockImport::new(
 		client.clone(),
@@ -1201,7 +1384,7 @@ pub mod test_helpers {
 		client: &C,
 		at: &BlockId<B>,
 		slot_number: u64,
-		key: &sr25519::Pair,
+		keystore: &dyn Keystore,
 		c: (u64, u64),
 	) -> Option<BabePreDigest> where
 		B: BlockT,
@@ -1213,9 +1396,9 @@ pub mod test_helpers {
 		super::claim_slot(
 			slot_number,
 			epoch,
-			key,
+			keystore,
 			c,
-		).map(|((inout, vrf_proof, _), authority_index)| {
+		).map(|((inout, vrf_proof), authority_index, _)| {
 			BabePreDigest {
 				vrf_proof,
 				vrf_output: inout.to_output(),
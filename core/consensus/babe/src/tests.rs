@@ -29,6 +29,7 @@ use sr_primitives::traits::{Block as BlockT, DigestFor};
 use network::config::ProtocolConfig;
 use tokio::runtime::current_thread;
 use keyring::sr25519::Keyring;
+use keystore::memory::MemoryStore;
 use super::generic::DigestItem;
 use client::BlockchainEvents;
 use test_client;
@@ -106,6 +107,15 @@ impl Verifier<TestBlock> for TestVerifier {
 		cb(&mut header);
 		Ok(self.inner.verify(origin, header, justification, body).expect("verification failed!"))
 	}
+
+	fn pre_verify(
+		&self,
+		header: &TestHeader,
+		body: Option<&[TestExtrinsic]>,
+		ancestry: &[TestHeader],
+	) -> Result<(), String> {
+		self.inner.pre_verify(header, body, ancestry)
+	}
 }
 
 impl TestNetFactory for BabeTestNet {
@@ -142,6 +152,7 @@ impl TestNetFactory for BabeTestNet {
 				inherent_data_providers,
 				config,
 				time_source: Default::default(),
+				pre_verified: Default::default(),
 			},
 			mutator: MUTATOR.with(|s| s.borrow().clone()),
 		})
@@ -212,9 +223,12 @@ fn run_one_test() {
 		#[allow(deprecated)]
 		let select_chain = LongestChain::new(client.backend().clone());
 
+		let keystore = MemoryStore::new();
+		keystore.insert::<sr25519::Pair>(&key.to_seed()).expect("Inserts the key");
+
 		runtime.spawn(start_babe(BabeParams {
 			config,
-			local_key: Arc::new(key.clone().into()),
+			keystore: Arc::new(keystore),
 			block_import: client.clone(),
 			select_chain,
 			client,
@@ -305,17 +319,18 @@ fn sig_is_not_pre_digest() {
 #[test]
 fn can_author_block() {
 	let _ = env_logger::try_init();
-	let (pair, _) = sr25519::Pair::generate();
+	let keystore = MemoryStore::new();
+	let public = keystore.generate_by_type(key_types::SR25519).unwrap();
 	let mut i = 0;
 	let epoch = Epoch {
 		start_slot: 0,
-		authorities: vec![(pair.public(), 1)],
+		authorities: vec![(sr25519::Public::from_slice(&public), 1)],
 		randomness: [0; 32],
 		epoch_index: 1,
 		duration: 100,
 	};
 	loop {
-		match claim_slot(i, epoch.clone(), &pair, (3, 10)) {
+		match claim_slot(i, epoch.clone(), &keystore, (3, 10)) {
 			None => i += 1,
 			Some(s) => {
 				debug!(target: "babe", "Authored block {:?}", s);
@@ -325,6 +340,81 @@ fn can_author_block() {
 	}
 }
 
+#[test]
+fn check_seal_checks_the_signature_of_the_slot_author() {
+	let _ = env_logger::try_init();
+	let keystore = MemoryStore::new();
+	let pair: sr25519::Pair = keystore.insert("//Alice").unwrap();
+	let epoch = Epoch {
+		start_slot: 0,
+		authorities: vec![(pair.public(), 1)],
+		randomness: [0; 32],
+		epoch_index: 1,
+		duration: 100,
+	};
+	let (slot_number, ((inout, vrf_proof), authority_index, _)) = (0..)
+		.find_map(|slot_number| claim_slot(slot_number, epoch.clone(), &keystore, (3, 10))
+			.map(|claim| (slot_number, claim)))
+		.unwrap();
+	let pre_digest = BabePreDigest {
+		vrf_proof,
+		vrf_output: inout.to_output(),
+		authority_index: authority_index as u32,
+		slot_number,
+	};
+
+	let mut header = TestHeader::new(
+		1,
+		Default::default(),
+		Default::default(),
+		Default::default(),
+		generic::Digest { logs: vec![DigestItem::babe_pre_digest(pre_digest)] },
+	);
+	let unsealed = header.clone();
+	let signature = pair.sign(header.hash().as_ref());
+	header.digest_mut().push(DigestItem::babe_seal(signature));
+
+	assert_eq!(check_seal::<TestBlock>(header.clone(), &epoch, (3, 10)), Ok(()));
+	assert!(check_seal::<TestBlock>(unsealed, &epoch, (3, 10)).is_err());
+	let other_authorities = Epoch { authorities: vec![(sr25519::Pair::generate().0.public(), 1)], ..epoch.clone() };
+	assert!(check_seal::<TestBlock>(header.clone(), &other_authorities, (3, 10)).is_err());
+
+	// The VRF output must be computed from the randomness of the epoch.
+	let other_randomness = Epoch { randomness: [1; 32], ..epoch.clone() };
+	assert!(check_seal::<TestBlock>(header, &other_randomness, (3, 10)).is_err());
+}
+
+#[test]
+fn ancestry_epoch_follows_the_epoch_changes_of_unimported_ancestors() {
+	let _ = env_logger::try_init();
+	let client = test_client::new();
+	initialize_authorities_cache(&client).unwrap();
+	let cached = cached_epoch(&client, &BlockId::Number(0)).expect("The cache is initialized");
+
+	let next_epoch = |epoch_index| Epoch { epoch_index, ..cached.clone() };
+	let signal = |epoch: Epoch| vec![
+		Item::Consensus(BABE_ENGINE_ID, ConsensusLog::NextEpochData(epoch).encode()),
+	];
+	let header = |number, parent_hash, logs| TestHeader::new(
+		number,
+		Default::default(),
+		Default::default(),
+		parent_hash,
+		generic::Digest { logs },
+	);
+	let first = header(1, client.info().chain.genesis_hash, vec![]);
+	let second = header(2, first.hash(), signal(next_epoch(5)));
+	let third = header(3, second.hash(), signal(next_epoch(6)));
+	let fourth = header(4, third.hash(), vec![]);
+
+	let epoch = |header, ancestry: &[_]| ancestry_epoch::<TestBlock, _>(&client, header, ancestry);
+	assert_eq!(epoch(&first, &[]), Some(cached.clone()));
+	assert_eq!(epoch(&second, &[first.clone()]), Some(cached));
+	// The epoch signalled before the imported ancestors isn't known.
+	assert_eq!(epoch(&third, &[first.clone(), second.clone()]), None);
+	assert_eq!(epoch(&fourth, &[first, second, third]), Some(next_epoch(5)));
+}
+
 #[test]
 fn authorities_call_works() {
 	let _ = env_logger::try_init();
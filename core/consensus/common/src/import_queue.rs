@@ -25,8 +25,11 @@
 //! instantiated. The `BasicQueue` and `BasicVerifier` traits allow serial
 //! queues to be instantiated simply.
 
-use std::{sync::Arc, collections::HashMap};
-use sr_primitives::{Justification, traits::{Block as BlockT, Header as _, NumberFor}};
+use std::{sync::Arc, collections::{HashMap, VecDeque}};
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use codec::Encode;
+use sr_primitives::{Justification, traits::{Block as BlockT, Header as _, Hash as _, HashFor, NumberFor}};
 use crate::{error::Error as ConsensusError, well_known_cache_keys::Id as CacheKeyId};
 use crate::block_import::{
 	BlockImport, BlockOrigin, BlockImportParams, ImportedAux, JustificationImport, ImportResult,
@@ -34,9 +37,22 @@ use crate::block_import::{
 };
 
 pub use basic_queue::BasicQueue;
+pub use timings::{ImportPhase, ImportTimings, PhaseTimer, time_phase, SLOW_PHASE_THRESHOLD};
 
 mod basic_queue;
 pub mod buffered_link;
+mod timings;
+
+lazy_static! {
+	static ref BLOCK_IMPORT_TIME: Arc<prometheus::Histogram> = prometheus::duration_histogram(
+		"substrate_block_import_time_seconds",
+		"Time taken to verify and import a block",
+	);
+	static ref BLOCKS_IMPORTED: Arc<prometheus::Counter> = prometheus::counter(
+		"substrate_blocks_imported_total",
+		"Number of blocks imported by the import queue",
+	);
+}
 
 /// Shared block import struct used by the queue.
 pub type BoxBlockImport<B> = Box<dyn BlockImport<B, Error = ConsensusError> + Send + Sync>;
@@ -77,6 +93,65 @@ pub trait Verifier<B: BlockT>: Send + Sync {
 		justification: Option<Justification>,
 		body: Option<Vec<B::Extrinsic>>,
 	) -> Result<(BlockImportParams<B>, Option<Vec<(CacheKeyId, Vec<u8>)>>), String>;
+
+	/// Check what doesn't depend on the state of the parent block, e.g. signatures.
+	///
+	/// The queue runs it on worker threads, ahead of `verify`, while the previous blocks are
+	/// imported. `ancestry` holds the headers queued before this one that aren't imported yet,
+	/// oldest first, so the parent of `header` is either imported or in `ancestry`.
+	fn pre_verify(
+		&self,
+		_header: &B::Header,
+		_body: Option<&[B::Extrinsic]>,
+		_ancestry: &[B::Header],
+	) -> Result<(), String> {
+		Ok(())
+	}
+}
+
+/// The blocks that passed `Verifier::pre_verify`, so that `verify` doesn't check their seal
+/// again.
+///
+/// Only the last `capacity` blocks are remembered: the blocks whose import was cancelled after
+/// their pre-verification are forgotten eventually.
+pub struct PreVerifiedBlocks {
+	hashes: Mutex<VecDeque<Vec<u8>>>,
+	capacity: usize,
+}
+
+impl PreVerifiedBlocks {
+	/// Remember up to `capacity` blocks.
+	pub fn new(capacity: usize) -> Self {
+		PreVerifiedBlocks { hashes: Mutex::new(VecDeque::with_capacity(capacity)), capacity }
+	}
+
+	/// Remember that the block `hash` passed the pre-verification.
+	pub fn insert(&self, hash: impl AsRef<[u8]>) {
+		let mut hashes = self.hashes.lock();
+		if hashes.len() >= self.capacity {
+			hashes.pop_front();
+		}
+		hashes.push_back(hash.as_ref().to_vec());
+	}
+
+	/// Whether the block `hash` passed the pre-verification. It is forgotten afterwards.
+	pub fn take(&self, hash: impl AsRef<[u8]>) -> bool {
+		let mut hashes = self.hashes.lock();
+		match hashes.iter().position(|h| h[..] == *hash.as_ref()) {
+			Some(index) => {
+				hashes.remove(index);
+				true
+			},
+			None => false,
+		}
+	}
+}
+
+impl Default for PreVerifiedBlocks {
+	fn default() -> Self {
+		// The import queue pre-verifies a handful of blocks ahead of the imported one.
+		PreVerifiedBlocks::new(64)
+	}
 }
 
 /// Blocks import queue API.
@@ -165,6 +240,29 @@ pub enum BlockImportError {
 	Other(ConsensusError),
 }
 
+/// Check a block without the state of its parent: its body must match the extrinsics root of
+/// its header, and pass the `pre_verify` checks of the verifier.
+///
+/// A block without header passes: the import reports it. `ancestry` is given to `pre_verify`.
+pub fn pre_verify_block<B: BlockT, V: Verifier<B>>(
+	block: &IncomingBlock<B>,
+	verifier: &V,
+	ancestry: &[B::Header],
+) -> Result<(), String> {
+	let header = match block.header.as_ref() {
+		Some(header) => header,
+		None => return Ok(()),
+	};
+	let _timer = time_phase(ImportPhase::PreVerification);
+	if let Some(body) = block.body.as_ref() {
+		let extrinsics_root = HashFor::<B>::ordered_trie_root(body.iter().map(Encode::encode));
+		if extrinsics_root != *header.extrinsics_root() {
+			return Err(format!("Body of {:?} doesn't match its extrinsics root", block.hash));
+		}
+	}
+	verifier.pre_verify(header, block.body.as_ref().map(|body| &body[..]), ancestry)
+}
+
 /// Single block import function.
 pub fn import_single_block<B: BlockT, V: Verifier<B>>(
 	import_handle: &mut dyn BlockImport<B, Error = ConsensusError>,
@@ -219,20 +317,109 @@ pub fn import_single_block<B: BlockT, V: Verifier<B>>(
 		r => return Ok(r), // Any other successful result means that the block is already imported.
 	}
 
-	let (import_block, maybe_keys) = verifier.verify(block_origin, header, justification, block.body)
-		.map_err(|msg| {
-			if let Some(ref peer) = peer {
-				trace!(target: "sync", "Verifying {}({}) from {} failed: {}", number, hash, peer, msg);
+	let body = block.body;
+	let _timer = BLOCK_IMPORT_TIME.start_timer();
+	let (result, timings) = ImportTimings::collect(|| {
+		let verification_timer = time_phase(ImportPhase::Verification);
+		let (import_block, maybe_keys) = verifier.verify(block_origin, header, justification, body)
+			.map_err(|msg| {
+				if let Some(ref peer) = peer {
+					trace!(target: "sync", "Verifying {}({}) from {} failed: {}", number, hash, peer, msg);
+				} else {
+					trace!(target: "sync", "Verifying {}({}) failed: {}", number, hash, msg);
+				}
+				BlockImportError::VerificationFailed(peer.clone(), msg)
+			})?;
+		drop(verification_timer);
+
+		let mut cache = HashMap::new();
+		if let Some(keys) = maybe_keys {
+			cache.extend(keys.into_iter());
+		}
+
+		import_error(import_handle.import_block(import_block, cache))
+	});
+	if let Ok(BlockImportResult::ImportedUnknown(..)) = result {
+		BLOCKS_IMPORTED.inc();
+		timings.warn_if_slow(number, hash);
+	}
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sr_primitives::testing::{Block as RawBlock, ExtrinsicWrapper, Header, H256};
+	use sr_primitives::traits::{BlakeTwo256, Hash as _, Header as HeaderT};
+
+	type Block = RawBlock<ExtrinsicWrapper<u64>>;
+
+	struct RejectOddBlocks;
+
+	impl Verifier<Block> for RejectOddBlocks {
+		fn verify(
+			&self,
+			_origin: BlockOrigin,
+			_header: Header,
+			_justification: Option<Justification>,
+			_body: Option<Vec<ExtrinsicWrapper<u64>>>,
+		) -> Result<(BlockImportParams<Block>, Option<Vec<(CacheKeyId, Vec<u8>)>>), String> {
+			Err("Not pre-verification".into())
+		}
+
+		fn pre_verify(
+			&self,
+			header: &Header,
+			_body: Option<&[ExtrinsicWrapper<u64>]>,
+			_ancestry: &[Header],
+		) -> Result<(), String> {
+			if header.number % 2 == 1 {
+				Err("Odd block".into())
 			} else {
-				trace!(target: "sync", "Verifying {}({}) failed: {}", number, hash, msg);
+				Ok(())
 			}
-			BlockImportError::VerificationFailed(peer.clone(), msg)
-		})?;
+		}
+	}
+
+	fn incoming_block(number: u64, body: Vec<u64>, extrinsics_root: H256) -> IncomingBlock<Block> {
+		let header = Header::new(number, extrinsics_root, Default::default(), Default::default(), Default::default());
+		IncomingBlock {
+			hash: header.hash(),
+			header: Some(header),
+			body: Some(body.into_iter().map(Into::into).collect()),
+			justification: None,
+			origin: None,
+		}
+	}
+
+	#[test]
+	fn pre_verification_checks_the_body_and_the_verifier() {
+		let body = vec![1u64, 2];
+		let root = BlakeTwo256::ordered_trie_root(body.iter().map(|xt| ExtrinsicWrapper::from(*xt).encode()));
 
-	let mut cache = HashMap::new();
-	if let Some(keys) = maybe_keys {
-		cache.extend(keys.into_iter());
+		assert_eq!(pre_verify_block(&incoming_block(2, body.clone(), root), &RejectOddBlocks, &[]), Ok(()));
+		assert!(pre_verify_block(&incoming_block(2, vec![1, 3], root), &RejectOddBlocks, &[]).is_err());
+		assert_eq!(
+			pre_verify_block(&incoming_block(3, body, root), &RejectOddBlocks, &[]),
+			Err("Odd block".to_string()),
+		);
+
+		let mut headerless = incoming_block(3, Vec::new(), Default::default());
+		headerless.header = None;
+		assert_eq!(pre_verify_block(&headerless, &RejectOddBlocks, &[]), Ok(()));
 	}
 
-	import_error(import_handle.import_block(import_block, cache))
+	#[test]
+	fn pre_verified_blocks_are_taken_once_and_bounded() {
+		let pre_verified = PreVerifiedBlocks::new(2);
+		pre_verified.insert(H256::repeat_byte(1));
+		pre_verified.insert(H256::repeat_byte(2));
+		assert!(pre_verified.take(H256::repeat_byte(1)));
+		assert!(!pre_verified.take(H256::repeat_byte(1)));
+
+		pre_verified.insert(H256::repeat_byte(3));
+		pre_verified.insert(H256::repeat_byte(4));
+		assert!(!pre_verified.take(H256::repeat_byte(2)));
+		assert!(pre_verified.take(H256::repeat_byte(4)));
+	}
 }
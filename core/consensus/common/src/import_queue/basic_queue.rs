@@ -14,8 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{mem, pin::Pin, sync::Arc, time::Duration};
-use futures::{prelude::*, channel::mpsc, task::SpawnExt as _, task::Context, task::Poll};
+use std::{mem, pin::Pin, sync::Arc, time::{Duration, Instant}, collections::VecDeque};
+use futures::{prelude::*, channel::{mpsc, oneshot}, task::SpawnExt as _, task::Context, task::Poll};
+use futures::executor::ThreadPool;
 use futures_timer::Delay;
 use sr_primitives::{Justification, traits::{Block as BlockT, Header as HeaderT, NumberFor}};
 
@@ -23,12 +24,18 @@ use crate::block_import::BlockOrigin;
 use crate::import_queue::{
 	BlockImportResult, BlockImportError, Verifier, BoxBlockImport, BoxFinalityProofImport,
 	BoxJustificationImport, ImportQueue, Link, Origin,
-	IncomingBlock, import_single_block,
+	IncomingBlock, import_single_block, pre_verify_block,
 	buffered_link::{self, BufferedLinkSender, BufferedLinkReceiver}
 };
 
+/// The number of blocks pre-verified ahead of the block being imported.
+const PRE_VERIFICATION_LOOKAHEAD: usize = 4;
+
 /// Interface to a basic block import queue that is importing blocks sequentially in a separate
 /// task, with pluggable verification.
+///
+/// The checks that don't depend on the state of the parent block run on a pool of threads,
+/// for a few blocks ahead of the block being imported.
 pub struct BasicQueue<B: BlockT> {
 	/// Channel to send messages to the background task.
 	sender: mpsc::UnboundedSender<ToWorkerMsg<B>>,
@@ -39,7 +46,7 @@ pub struct BasicQueue<B: BlockT> {
 	/// `poll_actions`.
 	manual_poll: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
 	/// A thread pool where the background worker is being run.
-	pool: Option<futures::executor::ThreadPool>,
+	pool: Option<ThreadPool>,
 }
 
 impl<B: BlockT> BasicQueue<B> {
@@ -54,15 +61,21 @@ impl<B: BlockT> BasicQueue<B> {
 		finality_proof_import: Option<BoxFinalityProofImport<B>>,
 	) -> Self {
 		let (result_sender, result_port) = buffered_link::buffered_link();
+		let verification_pool = ThreadPool::builder()
+			.name_prefix("import-queue-verifier-")
+			.pool_size(PRE_VERIFICATION_LOOKAHEAD)
+			.create()
+			.ok();
 		let (future, worker_sender) = BlockImportWorker::new(
 			result_sender,
 			verifier,
+			verification_pool,
 			block_import,
 			justification_import,
 			finality_proof_import,
 		);
 
-		let mut pool = futures::executor::ThreadPool::builder()
+		let mut pool = ThreadPool::builder()
 			.name_prefix("import-queue-worker-")
 			.pool_size(1)
 			.create()
@@ -139,6 +152,8 @@ struct BlockImportWorker<B: BlockT, V: Verifier<B>> {
 	justification_import: Option<BoxJustificationImport<B>>,
 	finality_proof_import: Option<BoxFinalityProofImport<B>>,
 	verifier: Arc<V>,
+	/// A thread pool where the blocks are pre-verified, if it could be created.
+	verification_pool: Option<ThreadPool>,
 	delay_between_blocks: Duration,
 }
 
@@ -146,6 +161,7 @@ impl<B: BlockT, V: 'static + Verifier<B>> BlockImportWorker<B, V> {
 	fn new(
 		result_sender: BufferedLinkSender<B>,
 		verifier: Arc<V>,
+		verification_pool: Option<ThreadPool>,
 		block_import: BoxBlockImport<B>,
 		justification_import: Option<BoxJustificationImport<B>>,
 		finality_proof_import: Option<BoxFinalityProofImport<B>>,
@@ -155,6 +171,7 @@ impl<B: BlockT, V: 'static + Verifier<B>> BlockImportWorker<B, V> {
 		let mut worker = BlockImportWorker {
 			result_sender,
 			verifier,
+			verification_pool,
 			justification_import,
 			finality_proof_import,
 			delay_between_blocks: Duration::new(0, 0),
@@ -244,7 +261,14 @@ impl<B: BlockT, V: 'static + Verifier<B>> BlockImportWorker<B, V> {
 	) -> impl Future<Output = BoxBlockImport<B>> {
 		let mut result_sender = self.result_sender.clone();
 
-		import_many_blocks(block_import, origin, blocks, self.verifier.clone(), self.delay_between_blocks)
+		import_many_blocks(
+			block_import,
+			origin,
+			blocks,
+			self.verifier.clone(),
+			self.verification_pool.clone(),
+			self.delay_between_blocks,
+		)
 			.then(move |(imported, count, results, block_import)| {
 				result_sender.blocks_processed(imported, count, results);
 				future::ready(block_import)
@@ -303,11 +327,16 @@ impl<B: BlockT, V: 'static + Verifier<B>> BlockImportWorker<B, V> {
 ///
 /// The returned `Future` yields at every imported block, which makes the execution more
 /// fine-grained and making it possible to interrupt the process.
-fn import_many_blocks<B: BlockT, V: Verifier<B>>(
+///
+/// Up to `PRE_VERIFICATION_LOOKAHEAD` blocks are pre-verified on `verification_pool` while the
+/// block before them is imported, or in place if there is no pool. Each block is pre-verified
+/// with the headers of the blocks before it that aren't imported yet.
+fn import_many_blocks<B: BlockT, V: 'static + Verifier<B>>(
 	import_handle: BoxBlockImport<B>,
 	blocks_origin: BlockOrigin,
 	blocks: Vec<IncomingBlock<B>>,
 	verifier: Arc<V>,
+	mut verification_pool: Option<ThreadPool>,
 	delay_between_blocks: Duration,
 ) -> impl Future<Output = (usize, usize, Vec<(
 	Result<BlockImportResult<NumberFor<B>>, BlockImportError>,
@@ -325,11 +354,13 @@ fn import_many_blocks<B: BlockT, V: Verifier<B>>(
 	};
 
 	trace!(target: "sync", "Starting import of {} blocks {}", count, blocks_range);
+	let started = Instant::now();
 
 	let mut imported = 0;
 	let mut results = vec![];
 	let mut has_error = false;
 	let mut blocks = blocks.into_iter();
+	let mut pre_verifying = VecDeque::new();
 	let mut import_handle = Some(import_handle);
 	let mut waiting = None;
 
@@ -345,11 +376,53 @@ fn import_many_blocks<B: BlockT, V: Verifier<B>>(
 		}
 		waiting = None;
 
+		// Once a block failed to be imported, the next ones are cancelled.
+		if has_error {
+			results.extend(pre_verifying.drain(..).map(|(hash, _, _)| (Err(BlockImportError::Cancelled), hash)));
+			results.extend(blocks.by_ref().map(|block| (Err(BlockImportError::Cancelled), block.hash)));
+		}
+
+		// Pre-verify the next blocks, while the previous ones are imported.
+		while pre_verifying.len() < PRE_VERIFICATION_LOOKAHEAD {
+			let block = match blocks.next() {
+				Some(b) => b,
+				None => break,
+			};
+			let ancestry = pre_verifying.iter()
+				.filter_map(|(_, header, _): &(_, Option<B::Header>, _)| header.clone())
+				.collect::<Vec<_>>();
+			let (sender, receiver) = oneshot::channel();
+			pre_verifying.push_back((block.hash, block.header.clone(), receiver));
+
+			let verifier = verifier.clone();
+			let pre_verify = move || {
+				let result = pre_verify_block(&block, &*verifier, &ancestry);
+				let _ = sender.send((block, result));
+			};
+			match verification_pool.as_mut() {
+				Some(pool) => pool.spawn(future::lazy(move |_| pre_verify()))
+					// TODO: this expect() can be removed once
+					// https://github.com/rust-lang-nursery/futures-rs/pull/1750 is merged and deployed
+					.expect("ThreadPool can never fail to spawn tasks; QED"),
+				None => pre_verify(),
+			}
+		}
+
 		// Is there any block left to import?
-		let block = match blocks.next() {
-			Some(b) => b,
+		let pre_verified = match pre_verifying.front_mut() {
+			Some((_, _, receiver)) => match Future::poll(Pin::new(receiver), cx) {
+				Poll::Ready(pre_verified) => pre_verified,
+				Poll::Pending => return Poll::Pending,
+			},
 			None => {
 				// No block left to import, success!
+				let elapsed = started.elapsed();
+				let millis = elapsed.as_secs() * 1000 + u64::from(elapsed.subsec_millis());
+				debug!(
+					target: "sync",
+					"Imported {} of {} blocks{} in {}ms ({} blocks/s)",
+					imported, count, blocks_range, millis, imported as u64 * 1000 / millis.max(1),
+				);
 				let import_handle = import_handle.take()
 					.expect("Future polled again after it has finished");
 				let results = mem::replace(&mut results, Vec::new());
@@ -363,18 +436,28 @@ fn import_many_blocks<B: BlockT, V: Verifier<B>>(
 		let import_handle = import_handle.as_mut()
 			.expect("Future polled again after it has finished");
 
-		let block_number = block.header.as_ref().map(|h| h.number().clone());
-		let block_hash = block.hash;
-		let import_result = if has_error {
-			Err(BlockImportError::Cancelled)
-		} else {
-			// The actual import.
-			import_single_block(
-				&mut **import_handle,
-				blocks_origin.clone(),
-				block,
-				verifier.clone(),
-			)
+		let (block_hash, _, _) = pre_verifying.pop_front()
+			.expect("The pre-verification of the block was just polled; qed");
+		let mut block_number = None;
+		let import_result = match pre_verified {
+			Ok((block, Ok(()))) => {
+				block_number = block.header.as_ref().map(|h| h.number().clone());
+				// The actual import.
+				import_single_block(
+					&mut **import_handle,
+					blocks_origin.clone(),
+					block,
+					verifier.clone(),
+				)
+			},
+			Ok((block, Err(msg))) => {
+				trace!(target: "sync", "Pre-verifying {:?} failed: {}", block_hash, msg);
+				Err(BlockImportError::VerificationFailed(block.origin, msg))
+			},
+			Err(_) => Err(BlockImportError::VerificationFailed(
+				None,
+				format!("Pre-verification of {:?} was interrupted", block_hash),
+			)),
 		};
 
 		if import_result.is_ok() {
@@ -395,3 +478,143 @@ fn import_many_blocks<B: BlockT, V: Verifier<B>>(
 		Poll::Pending
 	})
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{collections::HashMap, sync::mpsc as std_mpsc, thread, time::Instant};
+	use parking_lot::Mutex;
+	use sr_primitives::testing::{Block as RawBlock, ExtrinsicWrapper, Header, H256};
+	use crate::block_import::{BlockImport, BlockImportParams, ForkChoiceStrategy, ImportResult};
+	use crate::{error::Error as ConsensusError, well_known_cache_keys::Id as CacheKeyId};
+
+	type Block = RawBlock<ExtrinsicWrapper<u64>>;
+
+	/// Accepts every block, and records the blocks it pre-verifies.
+	#[derive(Default)]
+	struct RecordingVerifier {
+		pre_verified: Mutex<Vec<u64>>,
+		ancestries: Mutex<Vec<(u64, Vec<u64>)>>,
+	}
+
+	impl Verifier<Block> for RecordingVerifier {
+		fn verify(
+			&self,
+			origin: BlockOrigin,
+			header: Header,
+			justification: Option<Justification>,
+			body: Option<Vec<ExtrinsicWrapper<u64>>>,
+		) -> Result<(BlockImportParams<Block>, Option<Vec<(CacheKeyId, Vec<u8>)>>), String> {
+			let import_block = BlockImportParams {
+				origin,
+				header,
+				justification,
+				post_digests: Vec::new(),
+				body,
+				finalized: false,
+				auxiliary: Vec::new(),
+				fork_choice: ForkChoiceStrategy::LongestChain,
+			};
+			Ok((import_block, None))
+		}
+
+		fn pre_verify(
+			&self,
+			header: &Header,
+			_body: Option<&[ExtrinsicWrapper<u64>]>,
+			ancestry: &[Header],
+		) -> Result<(), String> {
+			self.pre_verified.lock().push(header.number);
+			self.ancestries.lock().push((header.number, ancestry.iter().map(|h| h.number).collect()));
+			Ok(())
+		}
+	}
+
+	/// Holds the import of the first block until it's released, and records the imported blocks.
+	struct GatedImport {
+		gate: Mutex<std_mpsc::Receiver<()>>,
+		imported: Arc<Mutex<Vec<u64>>>,
+	}
+
+	impl BlockImport<Block> for GatedImport {
+		type Error = ConsensusError;
+
+		fn check_block(&mut self, _hash: H256, _parent_hash: H256) -> Result<ImportResult, Self::Error> {
+			Ok(ImportResult::Imported(Default::default()))
+		}
+
+		fn import_block(
+			&mut self,
+			block: BlockImportParams<Block>,
+			_cache: HashMap<CacheKeyId, Vec<u8>>,
+		) -> Result<ImportResult, Self::Error> {
+			if block.header.number == 1 {
+				let _ = self.gate.lock().recv();
+			}
+			self.imported.lock().push(block.header.number);
+			Ok(ImportResult::Imported(Default::default()))
+		}
+	}
+
+	fn incoming_block(number: u64) -> IncomingBlock<Block> {
+		let header = Header::new(number, Default::default(), Default::default(), Default::default(), Default::default());
+		IncomingBlock {
+			hash: header.hash(),
+			header: Some(header),
+			body: None,
+			justification: None,
+			origin: None,
+		}
+	}
+
+	#[test]
+	fn pre_verifies_a_bounded_number_of_blocks_during_the_import() {
+		let verifier = Arc::new(RecordingVerifier::default());
+		let imported = Arc::new(Mutex::new(Vec::new()));
+		let (release, gate) = std_mpsc::channel();
+		let block_import: BoxBlockImport<Block> =
+			Box::new(GatedImport { gate: Mutex::new(gate), imported: imported.clone() });
+		let verification_pool = ThreadPool::builder()
+			.pool_size(PRE_VERIFICATION_LOOKAHEAD)
+			.create()
+			.unwrap();
+
+		let import = import_many_blocks(
+			block_import,
+			BlockOrigin::NetworkInitialSync,
+			(1..=10).map(incoming_block).collect(),
+			verifier.clone(),
+			Some(verification_pool),
+			Duration::new(0, 0),
+		);
+		let importer = thread::spawn(move || futures::executor::block_on(import));
+
+		// The next blocks are pre-verified while the first one is being imported...
+		let deadline = Instant::now() + Duration::from_secs(10);
+		while verifier.pre_verified.lock().len() < PRE_VERIFICATION_LOOKAHEAD {
+			assert!(Instant::now() < deadline, "The next blocks weren't pre-verified during the import");
+			thread::sleep(Duration::from_millis(10));
+		}
+
+		// ...but not more than the lookahead.
+		thread::sleep(Duration::from_millis(100));
+		let mut pre_verified = verifier.pre_verified.lock().clone();
+		pre_verified.sort();
+		assert_eq!(pre_verified, (1..=PRE_VERIFICATION_LOOKAHEAD as u64).collect::<Vec<_>>());
+		assert!(imported.lock().is_empty());
+
+		release.send(()).unwrap();
+		let (imported_count, count, results, _) = importer.join().unwrap();
+		assert_eq!((imported_count, count), (10, 10));
+		assert!(results.iter().all(|(result, _)| result.is_ok()));
+		assert_eq!(*imported.lock(), (1..=10).collect::<Vec<_>>());
+		assert_eq!(verifier.pre_verified.lock().len(), 10);
+
+		// Each block was pre-verified with the blocks before it that weren't imported yet.
+		let mut ancestries = verifier.ancestries.lock().clone();
+		ancestries.sort();
+		assert_eq!(ancestries[0], (1, vec![]));
+		assert_eq!(ancestries[3], (4, vec![1, 2, 3]));
+		assert!(ancestries[4..].iter().all(|(number, ancestry)| ancestry.last() == Some(&(number - 1))));
+	}
+}
@@ -0,0 +1,209 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Timings of the phases of a block import.
+//!
+//! Every phase is timed with [`time_phase`], which feeds a histogram per phase. Verification is
+//! timed by the import queue, while execution and state commit are timed by the `BlockImport`
+//! implementation, i.e. the client. While the import queue imports a block, the timings of all
+//! phases are additionally collected, so that a slow import can be reported with its breakdown.
+
+use std::{cell::RefCell, fmt, sync::Arc, time::{Duration, Instant}};
+use lazy_static::lazy_static;
+
+/// Time spent in a single phase above which the import of a block is reported as slow.
+pub const SLOW_PHASE_THRESHOLD: Duration = Duration::from_secs(1);
+
+lazy_static! {
+	static ref PRE_VERIFICATION_TIME: Arc<prometheus::Histogram> = prometheus::duration_histogram(
+		"substrate_block_pre_verification_time_seconds",
+		"Time taken to check a block ahead of its import, without the state of its parent",
+	);
+	static ref VERIFICATION_TIME: Arc<prometheus::Histogram> = prometheus::duration_histogram(
+		"substrate_block_verification_time_seconds",
+		"Time taken to verify a block before importing it",
+	);
+	static ref EXECUTION_TIME: Arc<prometheus::Histogram> = prometheus::duration_histogram(
+		"substrate_block_execution_time_seconds",
+		"Time taken to execute a block on top of its parent state",
+	);
+	static ref COMMIT_TIME: Arc<prometheus::Histogram> = prometheus::duration_histogram(
+		"substrate_block_commit_time_seconds",
+		"Time taken to write an imported block and its state changes to the database",
+	);
+}
+
+thread_local! {
+	/// The timings of the block that is currently imported by the import queue on this thread.
+	static CURRENT: RefCell<Option<ImportTimings>> = RefCell::new(None);
+}
+
+/// A phase of a block import.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportPhase {
+	/// Checking the block ahead of its import, on a worker thread, e.g. its seal.
+	PreVerification,
+	/// Checking the block before it is imported, e.g. its seal.
+	Verification,
+	/// Executing the block on top of its parent state.
+	Execution,
+	/// Writing the block and its state changes to the database.
+	Commit,
+}
+
+impl ImportPhase {
+	fn histogram(self) -> &'static prometheus::Histogram {
+		match self {
+			ImportPhase::PreVerification => &PRE_VERIFICATION_TIME,
+			ImportPhase::Verification => &VERIFICATION_TIME,
+			ImportPhase::Execution => &EXECUTION_TIME,
+			ImportPhase::Commit => &COMMIT_TIME,
+		}
+	}
+}
+
+impl fmt::Display for ImportPhase {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ImportPhase::PreVerification => write!(f, "pre-verification"),
+			ImportPhase::Verification => write!(f, "verification"),
+			ImportPhase::Execution => write!(f, "execution"),
+			ImportPhase::Commit => write!(f, "state commit"),
+		}
+	}
+}
+
+/// Records the time spent in a phase when dropped.
+pub struct PhaseTimer {
+	phase: ImportPhase,
+	started: Instant,
+}
+
+/// Start timing `phase`. The phase ends when the returned timer is dropped.
+pub fn time_phase(phase: ImportPhase) -> PhaseTimer {
+	PhaseTimer { phase, started: Instant::now() }
+}
+
+impl Drop for PhaseTimer {
+	fn drop(&mut self) {
+		let elapsed = self.started.elapsed();
+		self.phase.histogram().observe(elapsed);
+		CURRENT.with(|current| if let Some(timings) = current.borrow_mut().as_mut() {
+			timings.add(self.phase, elapsed);
+		});
+	}
+}
+
+/// Time spent in each phase of a block import.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ImportTimings {
+	phases: Vec<(ImportPhase, Duration)>,
+}
+
+impl ImportTimings {
+	/// Run `f` and return the timings of the phases that were timed on this thread meanwhile.
+	pub(crate) fn collect<R, F: FnOnce() -> R>(f: F) -> (R, ImportTimings) {
+		let previous = CURRENT.with(|current| current.replace(Some(Default::default())));
+		let result = f();
+		let timings = CURRENT.with(|current| current.replace(previous)).unwrap_or_default();
+		(result, timings)
+	}
+
+	fn add(&mut self, phase: ImportPhase, elapsed: Duration) {
+		match self.phases.iter_mut().find(|(p, _)| *p == phase) {
+			Some((_, total)) => *total += elapsed,
+			None => self.phases.push((phase, elapsed)),
+		}
+	}
+
+	/// Time spent in `phase`, if it was timed at all.
+	pub fn get(&self, phase: ImportPhase) -> Option<Duration> {
+		self.phases.iter().find(|(p, _)| *p == phase).map(|(_, elapsed)| *elapsed)
+	}
+
+	/// Phases that took longer than `SLOW_PHASE_THRESHOLD`.
+	pub fn slow_phases(&self) -> Vec<ImportPhase> {
+		self.phases.iter()
+			.filter(|(_, elapsed)| *elapsed > SLOW_PHASE_THRESHOLD)
+			.map(|(phase, _)| *phase)
+			.collect()
+	}
+
+	/// Warn about the import of the given block if any of its phases was slow.
+	pub(crate) fn warn_if_slow(&self, number: impl fmt::Display, hash: impl fmt::Display) {
+		let slow = self.slow_phases();
+		if slow.is_empty() {
+			return;
+		}
+
+		let slow = slow.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+		warn!(
+			target: "sync",
+			"Slow {} of block #{} ({}): {}",
+			slow, number, hash, self,
+		);
+	}
+}
+
+impl fmt::Display for ImportTimings {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let total = self.phases.iter().map(|(_, elapsed)| *elapsed).fold(Duration::default(), |a, b| a + b);
+		write!(f, "took {}", format_duration(total))?;
+		for (phase, elapsed) in &self.phases {
+			write!(f, ", {} {}", phase, format_duration(*elapsed))?;
+		}
+		Ok(())
+	}
+}
+
+fn format_duration(duration: Duration) -> String {
+	format!("{}ms", duration.as_secs() * 1000 + u64::from(duration.subsec_millis()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn collects_phases_timed_on_this_thread() {
+		drop(time_phase(ImportPhase::Verification));
+
+		let ((), timings) = ImportTimings::collect(|| {
+			drop(time_phase(ImportPhase::Execution));
+			drop(time_phase(ImportPhase::Commit));
+			drop(time_phase(ImportPhase::Commit));
+		});
+
+		assert_eq!(timings.get(ImportPhase::Verification), None);
+		assert!(timings.get(ImportPhase::Execution).is_some());
+		assert!(timings.get(ImportPhase::Commit).is_some());
+		assert!(timings.slow_phases().is_empty());
+	}
+
+	#[test]
+	fn reports_slow_phases() {
+		let mut timings = ImportTimings::default();
+		timings.add(ImportPhase::Verification, Duration::from_millis(20));
+		timings.add(ImportPhase::Execution, Duration::from_millis(5200));
+		timings.add(ImportPhase::Commit, Duration::from_millis(800));
+
+		assert_eq!(timings.slow_phases(), vec![ImportPhase::Execution]);
+		assert_eq!(
+			timings.to_string(),
+			"took 6020ms, verification 20ms, execution 5200ms, state commit 800ms",
+		);
+	}
+}
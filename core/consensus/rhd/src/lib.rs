@@ -53,6 +53,7 @@ use sr_primitives::traits::{
 	BlockNumberToHash, SaturatedConversion
 };
 use sr_primitives::Justification;
+use sr_primitives::transaction_validity::TransactionSource;
 use primitives::{AuthorityId, ed25519, Blake2Hasher, ed25519::LocalizedSignature};
 use srml_system::Trait as SystemT;
 
@@ -1290,7 +1291,7 @@ impl<C, A> LocalProposer<<C as AuthoringApi>::Block> for Proposer<C, A> where
 			let uxt: <<C as AuthoringApi>::Block as BlockT>::Extrinsic = Decode::decode(
 				&mut extrinsic.encode().as_slice()).expect("Encoded extrinsic is valid");
 			let hash = BlockId::<<C as AuthoringApi>::Block>::hash(self.parent_hash);
-			if let Err(e) = self.transaction_pool.submit_one(&hash, uxt) {
+			if let Err(e) = self.transaction_pool.submit_one(&hash, TransactionSource::Local, uxt) {
 				warn!("Error importing misbehavior report: {:?}", e);
 			}
 		}
@@ -9,7 +9,8 @@ use rstd::{vec::Vec, slice, vec};
 
 use runtime_io::{
 	set_storage, storage, clear_prefix, print, blake2_128, blake2_256,
-	twox_128, twox_256, ed25519_verify, sr25519_verify, enumerated_trie_root
+	twox_128, twox_256, ed25519_verify, sr25519_verify, sr25519_vrf_verify, enumerated_trie_root,
+	VrfSignature,
 };
 
 macro_rules! impl_stubs {
@@ -92,6 +93,18 @@ impl_stubs!(
 		let msg = b"all ok!";
 		[sr25519_verify(&sig, &msg[..], &pubkey) as u8].to_vec()
 	},
+	test_sr25519_vrf_verify => |input: &[u8]| {
+		let mut pubkey = [0; 32];
+		pubkey.copy_from_slice(&input[0..32]);
+		let signature = VrfSignature {
+			output: primitives::H256::from_slice(&input[32..64]),
+			proof: primitives::H512::from_slice(&input[64..128]),
+		};
+
+		sr25519_vrf_verify(b"test", b"all ok!", &signature, &pubkey)
+			.map(|randomness| randomness.to_vec())
+			.unwrap_or_default()
+	},
 	test_enumerated_trie_root => |_| {
 		enumerated_trie_root::<primitives::Blake2Hasher>(
 			&[
@@ -32,6 +32,14 @@ pub enum Error {
 	Trap(wasmi::Trap),
 	/// Wasmi loading/instantiating error
 	Wasmi(wasmi::Error),
+	/// Execution trapped after the runtime printed some output, e.g. a panic message.
+	#[display(fmt="{}, runtime output: {}", error, output)]
+	Trapped {
+		/// The error the execution failed with.
+		error: wasmi::Error,
+		/// Everything the runtime printed before trapping.
+		output: String,
+	},
 	/// Error in the API. Parameter is an error message.
 	ApiError(String),
 	/// Method is not found
@@ -76,6 +84,14 @@ pub enum Error {
 	/// Someone tried to allocate more memory than the allowed maximum per allocation.
 	#[display(fmt="Requested allocation size is too large")]
 	RequestedAllocationTooLarge,
+	/// The runtime would need more memory than allowed.
+	#[display(fmt="Runtime needs {} memory pages, but only {} are allowed", pages, limit)]
+	MemoryLimitExceeded {
+		/// The number of pages the runtime instance would have.
+		pages: usize,
+		/// The maximum number of pages allowed.
+		limit: u32,
+	},
 }
 
 impl std::error::Error for Error {
@@ -84,6 +100,7 @@ impl std::error::Error for Error {
 			Error::InvalidData(ref err) => Some(err),
 			Error::Trap(ref err) => Some(err),
 			Error::Wasmi(ref err) => Some(err),
+			Error::Trapped { ref error, .. } => Some(error),
 			_ => None,
 		}
 	}
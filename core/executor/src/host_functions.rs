@@ -0,0 +1,216 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Additional host functions that node builders can expose to the Wasm runtime.
+//!
+//! Besides the functions built into the executor, a runtime may import functions that are
+//! registered through [`NativeExecutionDispatch::host_functions`](../trait.NativeExecutionDispatch.html).
+//!
+//! A host function can be provided in several versions. The runtime imports version `1` of a
+//! function under its plain name and any later version `n` as `<name>_version_<n>`, so a runtime
+//! built against an older node keeps working after a newer version of a function is added, as long
+//! as the node still registers the old one.
+
+use crate::error::{Error, Result};
+use wasmi::{
+	Externals, FuncInstance, FuncRef, ModuleImportResolver, RuntimeArgs, RuntimeValue, Signature, Trap,
+};
+
+/// Index at which the wasm function indices of registered host functions start.
+///
+/// Indices below belong to the functions built into the executor.
+const HOST_FUNCTIONS_INDEX_BASE: usize = 1 << 16;
+
+/// Access to the memory of the runtime instance that called a host function.
+pub trait FunctionContext {
+	/// Read `len` bytes from the runtime memory at `ptr`.
+	fn read_memory(&self, ptr: u32, len: u32) -> Result<Vec<u8>>;
+	/// Write `data` into the runtime memory at `ptr`.
+	fn write_memory(&mut self, ptr: u32, data: &[u8]) -> Result<()>;
+	/// Allocate `size` bytes on the runtime heap and return the pointer to them.
+	fn allocate_memory(&mut self, size: u32) -> Result<u32>;
+	/// Free memory that was allocated on the runtime heap.
+	fn deallocate_memory(&mut self, ptr: u32) -> Result<()>;
+}
+
+/// A function that is provided by the host and callable from the runtime.
+pub trait HostFunction: Send + Sync {
+	/// The name of the function.
+	fn name(&self) -> &'static str;
+
+	/// The version of the function. Defaults to `1`.
+	fn version(&self) -> u32 {
+		1
+	}
+
+	/// The wasm signature of the function.
+	fn signature(&self) -> Signature;
+
+	/// Execute the function with the given arguments.
+	///
+	/// The arguments are guaranteed to match `signature`.
+	fn execute(
+		&self,
+		context: &mut dyn FunctionContext,
+		args: &[RuntimeValue],
+	) -> Result<Option<RuntimeValue>>;
+}
+
+/// The name under which the runtime imports the given version of a host function.
+pub fn import_name(name: &str, version: u32) -> String {
+	if version <= 1 {
+		name.to_string()
+	} else {
+		format!("{}_version_{}", name, version)
+	}
+}
+
+/// A resolver that resolves the given host functions after the built-in ones.
+pub(crate) struct Resolver<'a> {
+	pub builtin: &'static dyn ModuleImportResolver,
+	pub host_functions: &'a [&'static dyn HostFunction],
+}
+
+impl<'a> ModuleImportResolver for Resolver<'a> {
+	fn resolve_func(&self, name: &str, signature: &Signature) -> std::result::Result<FuncRef, wasmi::Error> {
+		let builtin_err = match self.builtin.resolve_func(name, signature) {
+			Ok(func) => return Ok(func),
+			Err(err) => err,
+		};
+
+		let found = self.host_functions.iter()
+			.enumerate()
+			.find(|(_, f)| import_name(f.name(), f.version()) == name);
+
+		match found {
+			Some((index, function)) => {
+				let expected = function.signature();
+				if signature != &expected {
+					return Err(wasmi::Error::Instantiation(
+						format!("Export {} has different signature {:?}", name, signature),
+					));
+				}
+				Ok(FuncInstance::alloc_host(expected, HOST_FUNCTIONS_INDEX_BASE + index))
+			}
+			None => Err(builtin_err),
+		}
+	}
+}
+
+/// Externals that dispatch calls of registered host functions and forward everything else to
+/// the built-in ones.
+pub(crate) struct WithHostFunctions<'a, T> {
+	pub builtin: &'a mut T,
+	pub host_functions: &'a [&'static dyn HostFunction],
+}
+
+impl<'a, T: Externals + FunctionContext> Externals for WithHostFunctions<'a, T> {
+	fn invoke_index(
+		&mut self,
+		index: usize,
+		args: RuntimeArgs,
+	) -> std::result::Result<Option<RuntimeValue>, Trap> {
+		if index < HOST_FUNCTIONS_INDEX_BASE {
+			return self.builtin.invoke_index(index, args);
+		}
+
+		let function = self.host_functions.get(index - HOST_FUNCTIONS_INDEX_BASE)
+			.ok_or_else(|| Trap::from(Error::Other("Host function index out of range")))?;
+		function.execute(&mut *self.builtin, args.as_ref()).map_err(Trap::from)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::wasm_executor::WasmExecutor;
+	use primitives::Blake2Hasher;
+	use state_machine::TestExternalities as CoreTestExternalities;
+	use wasmi::ValueType;
+
+	type TestExternalities<H> = CoreTestExternalities<H, u64>;
+
+	struct Double;
+
+	impl HostFunction for Double {
+		fn name(&self) -> &'static str {
+			"ext_double"
+		}
+
+		fn version(&self) -> u32 {
+			2
+		}
+
+		fn signature(&self) -> Signature {
+			Signature::new(&[ValueType::I32][..], Some(ValueType::I32))
+		}
+
+		fn execute(
+			&self,
+			_context: &mut dyn FunctionContext,
+			args: &[RuntimeValue],
+		) -> Result<Option<RuntimeValue>> {
+			match args {
+				[RuntimeValue::I32(v)] => Ok(Some(RuntimeValue::I32(v * 2))),
+				_ => Err(Error::Other("invalid arguments")),
+			}
+		}
+	}
+
+	const TEST_CODE: &str = r#"
+	(module
+		(import "env" "ext_double_version_2" (func $ext_double (param i32) (result i32)))
+		(memory (export "memory") 1)
+		(global (export "__heap_base") i32 (i32.const 1024))
+		(func (export "test_double") (param i32) (result i32)
+			(call $ext_double (get_local 0))
+		)
+	)
+	"#;
+
+	fn call_double(executor: &WasmExecutor, value: i32) -> Result<i32> {
+		let mut ext = TestExternalities::<Blake2Hasher>::default();
+		let code = wabt::wat2wasm(TEST_CODE).unwrap();
+		executor.call_with_custom_signature(
+			&mut ext,
+			8,
+			&code,
+			"test_double",
+			|_| Ok(vec![RuntimeValue::I32(value)]),
+			|res, _| match res {
+				Some(RuntimeValue::I32(v)) => Ok(Some(v)),
+				_ => Ok(None),
+			},
+		)
+	}
+
+	#[test]
+	fn import_names_are_versioned() {
+		assert_eq!(import_name("ext_my_hash", 1), "ext_my_hash");
+		assert_eq!(import_name("ext_my_hash", 2), "ext_my_hash_version_2");
+	}
+
+	#[test]
+	fn registered_host_functions_are_callable() {
+		let executor = WasmExecutor::new().with_host_functions(vec![&Double as &dyn HostFunction]);
+		assert_eq!(call_double(&executor, 21).unwrap(), 42);
+	}
+
+	#[test]
+	fn unregistered_host_functions_fail_instantiation() {
+		assert!(call_double(&WasmExecutor::new(), 21).is_err());
+	}
+}
@@ -38,10 +38,11 @@ mod allocator;
 mod wasm_runtimes_cache;
 
 pub mod error;
+pub mod host_functions;
 pub use wasmi;
 pub use wasm_executor::WasmExecutor;
 pub use native_executor::{with_native_environment, NativeExecutor, NativeExecutionDispatch};
-pub use wasm_runtimes_cache::RuntimesCache;
+pub use wasm_runtimes_cache::{RuntimesCache, WasmExecutionMethod};
 pub use state_machine::Externalities;
 pub use runtime_version::{RuntimeVersion, NativeVersion};
 pub use codec::Codec;
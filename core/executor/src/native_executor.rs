@@ -24,7 +24,8 @@ use crate::RuntimeInfo;
 use primitives::{Blake2Hasher, NativeOrEncoded};
 use log::trace;
 
-use crate::RuntimesCache;
+use crate::{RuntimesCache, WasmExecutionMethod};
+use crate::host_functions::HostFunction;
 
 thread_local! {
 	static RUNTIMES_CACHE: RefCell<RuntimesCache> = RefCell::new(RuntimesCache::new());
@@ -57,6 +58,14 @@ pub trait NativeExecutionDispatch: Send + Sync {
 
 	/// Provide native runtime version.
 	fn native_version() -> NativeVersion;
+
+	/// Additional host functions the Wasm runtime may import.
+	///
+	/// Only used when executing the Wasm runtime; the native runtime calls the corresponding
+	/// Rust code directly.
+	fn host_functions() -> Vec<&'static dyn HostFunction> {
+		Vec::new()
+	}
 }
 
 /// A generic `CodeExecutor` implementation that uses a delegate to determine wasm code equivalence
@@ -71,18 +80,33 @@ pub struct NativeExecutor<D> {
 	native_version: NativeVersion,
 	/// The number of 64KB pages to allocate for Wasm execution.
 	default_heap_pages: Option<u64>,
+	/// The method used to execute the runtime Wasm code.
+	wasm_method: WasmExecutionMethod,
 }
 
 impl<D: NativeExecutionDispatch> NativeExecutor<D> {
-	/// Create new instance.
+	/// Create new instance that interprets the runtime Wasm code.
 	pub fn new(default_heap_pages: Option<u64>) -> Self {
+		Self::new_with_wasm_method(WasmExecutionMethod::Interpreted, default_heap_pages)
+	}
+
+	/// Create new instance that executes the runtime Wasm code using the given method.
+	pub fn new_with_wasm_method(wasm_method: WasmExecutionMethod, default_heap_pages: Option<u64>) -> Self {
 		NativeExecutor {
 			_dummy: Default::default(),
-			fallback: WasmExecutor::new(),
+			fallback: WasmExecutor::new().with_host_functions(D::host_functions()),
 			native_version: D::native_version(),
 			default_heap_pages: default_heap_pages,
+			wasm_method,
 		}
 	}
+
+	/// Limit the linear memory of runtime instances, including the heap, to the given number
+	/// of 64KB pages. `None` means no limit.
+	pub fn with_max_memory_pages(mut self, max_memory_pages: Option<u32>) -> Self {
+		self.fallback = self.fallback.with_max_memory_pages(max_memory_pages);
+		self
+	}
 }
 
 impl<D: NativeExecutionDispatch> Clone for NativeExecutor<D> {
@@ -92,6 +116,7 @@ impl<D: NativeExecutionDispatch> Clone for NativeExecutor<D> {
 			fallback: self.fallback.clone(),
 			native_version: D::native_version(),
 			default_heap_pages: self.default_heap_pages,
+			wasm_method: self.wasm_method,
 		}
 	}
 }
@@ -107,7 +132,7 @@ impl<D: NativeExecutionDispatch> RuntimeInfo for NativeExecutor<D> {
 	) -> Option<RuntimeVersion> {
 		RUNTIMES_CACHE.with(|cache| {
 			let cache = &mut cache.borrow_mut();
-			cache.fetch_runtime(&self.fallback, ext, self.default_heap_pages)
+			cache.fetch_runtime(&self.fallback, ext, self.wasm_method, self.default_heap_pages)
 				.ok()?.version().clone()
 		})
 	}
@@ -132,7 +157,7 @@ impl<D: NativeExecutionDispatch> CodeExecutor<Blake2Hasher> for NativeExecutor<D
 		RUNTIMES_CACHE.with(|cache| {
 			let cache = &mut cache.borrow_mut();
 			let cached_runtime = match cache.fetch_runtime(
-				&self.fallback, ext, self.default_heap_pages,
+				&self.fallback, ext, self.wasm_method, self.default_heap_pages,
 			) {
 				Ok(cached_runtime) => cached_runtime,
 				Err(e) => return (Err(e), false),
@@ -203,14 +228,23 @@ impl<D: NativeExecutionDispatch> CodeExecutor<Blake2Hasher> for NativeExecutor<D
 }
 
 /// Implements a `NativeExecutionDispatch` for provided parameters.
+///
+/// An optional last argument lists additional host functions (`Vec<&'static dyn HostFunction>`)
+/// that are made available to the Wasm runtime.
 #[macro_export]
 macro_rules! native_executor_instance {
 	( $pub:vis $name:ident, $dispatcher:path, $version:path, $code:expr) => {
+		$crate::native_executor_instance!($pub $name, $dispatcher, $version, $code, Vec::new());
+	};
+	( $pub:vis $name:ident, $dispatcher:path, $version:path, $code:expr, $host_functions:expr) => {
 		/// A unit struct which implements `NativeExecutionDispatch` feeding in the hard-coded runtime.
 		$pub struct $name;
-		$crate::native_executor_instance!(IMPL $name, $dispatcher, $version, $code);
+		$crate::native_executor_instance!(IMPL $name, $dispatcher, $version, $code, $host_functions);
 	};
 	(IMPL $name:ident, $dispatcher:path, $version:path, $code:expr) => {
+		$crate::native_executor_instance!(IMPL $name, $dispatcher, $version, $code, Vec::new());
+	};
+	(IMPL $name:ident, $dispatcher:path, $version:path, $code:expr, $host_functions:expr) => {
 		impl $crate::NativeExecutionDispatch for $name {
 			fn native_equivalent() -> &'static [u8] {
 				// WARNING!!! This assumes that the runtime was built *before* the main project. Until we
@@ -230,6 +264,10 @@ macro_rules! native_executor_instance {
 			fn native_version() -> $crate::NativeVersion {
 				$version()
 			}
+
+			fn host_functions() -> Vec<&'static dyn $crate::host_functions::HostFunction> {
+				$host_functions
+			}
 		}
 	}
 }
@@ -26,16 +26,19 @@ use wasmi::{
 };
 use state_machine::{Externalities, ChildStorageKey};
 use crate::error::{Error, Result};
-use codec::Encode;
+use codec::{Encode, Decode};
 use primitives::{blake2_128, blake2_256, twox_64, twox_128, twox_256, ed25519, sr25519, Pair};
 use primitives::offchain;
 use primitives::hexdisplay::HexDisplay;
 use primitives::sandbox as sandbox_primitives;
+use primitives::batch_verifier::{BatchVerifier, SignatureKind};
+use primitives::tracing;
 use primitives::{H256, Blake2Hasher};
 use trie::{TrieConfiguration, trie_types::Layout};
 use crate::sandbox;
 use crate::allocator;
-use log::trace;
+use crate::host_functions::{self, FunctionContext, HostFunction};
+use log::{trace, debug, info};
 
 #[cfg(feature="wasm-extern-trace")]
 macro_rules! debug_trace {
@@ -46,6 +49,9 @@ macro_rules! debug_trace {
 	( $( $x:tt )* ) => ()
 }
 
+/// Maximum number of messages printed by the runtime that are kept for error reporting.
+const MAX_RUNTIME_OUTPUT_MESSAGES: usize = 32;
+
 struct FunctionExecutor<'e, E: Externalities<Blake2Hasher> + 'e> {
 	sandbox_store: sandbox::Store,
 	heap: allocator::FreeingBumpHeapAllocator,
@@ -53,6 +59,8 @@ struct FunctionExecutor<'e, E: Externalities<Blake2Hasher> + 'e> {
 	table: Option<TableRef>,
 	ext: &'e mut E,
 	hash_lookup: HashMap<Vec<u8>, Vec<u8>>,
+	output: Vec<String>,
+	batch_verifier: Option<BatchVerifier>,
 }
 
 impl<'e, E: Externalities<Blake2Hasher>> FunctionExecutor<'e, E> {
@@ -64,8 +72,19 @@ impl<'e, E: Externalities<Blake2Hasher>> FunctionExecutor<'e, E> {
 			table: t,
 			ext: e,
 			hash_lookup: HashMap::new(),
+			output: Vec::new(),
+			batch_verifier: None,
 		})
 	}
+
+	/// Log a message printed by the runtime and keep it for reporting a possible trap.
+	fn runtime_print(&mut self, message: String) {
+		info!(target: "runtime", "{}", message);
+		if self.output.len() == MAX_RUNTIME_OUTPUT_MESSAGES {
+			self.output.remove(0);
+		}
+		self.output.push(message);
+	}
 }
 
 impl<'e, E: Externalities<Blake2Hasher>> sandbox::SandboxCapabilities for FunctionExecutor<'e, E> {
@@ -89,6 +108,21 @@ impl<'e, E: Externalities<Blake2Hasher>> sandbox::SandboxCapabilities for Functi
 	}
 }
 
+impl<'e, E: Externalities<Blake2Hasher>> FunctionContext for FunctionExecutor<'e, E> {
+	fn read_memory(&self, ptr: u32, len: u32) -> Result<Vec<u8>> {
+		self.memory.get(ptr, len as usize).map_err(Into::into)
+	}
+	fn write_memory(&mut self, ptr: u32, data: &[u8]) -> Result<()> {
+		self.memory.set(ptr, data).map_err(Into::into)
+	}
+	fn allocate_memory(&mut self, size: u32) -> Result<u32> {
+		self.heap.allocate(size)
+	}
+	fn deallocate_memory(&mut self, ptr: u32) -> Result<()> {
+		self.heap.deallocate(ptr)
+	}
+}
+
 trait WritePrimitive<T: Sized> {
 	fn write_primitive(&self, offset: u32, t: T) -> Result<()>;
 }
@@ -126,19 +160,19 @@ impl_function_executor!(this: FunctionExecutor<'e, E>,
 	ext_print_utf8(utf8_data: *const u8, utf8_len: u32) => {
 		if let Ok(utf8) = this.memory.get(utf8_data, utf8_len as usize) {
 			if let Ok(message) = String::from_utf8(utf8) {
-				println!("{}", message);
+				this.runtime_print(message);
 			}
 		}
 		Ok(())
 	},
 	ext_print_hex(data: *const u8, len: u32) => {
 		if let Ok(hex) = this.memory.get(data, len as usize) {
-			println!("{}", HexDisplay::from(&hex));
+			this.runtime_print(format!("{}", HexDisplay::from(&hex)));
 		}
 		Ok(())
 	},
 	ext_print_num(number: u64) => {
-		println!("{}", number);
+		this.runtime_print(number.to_string());
 		Ok(())
 	},
 	ext_malloc(size: usize) -> *mut u8 => {
@@ -282,6 +316,20 @@ impl_function_executor!(this: FunctionExecutor<'e, E>,
 		this.ext.clear_prefix(&prefix);
 		Ok(())
 	},
+	ext_offchain_index_set(key_data: *const u8, key_len: u32, value_data: *const u8, value_len: u32) => {
+		let key = this.memory.get(key_data, key_len as usize)
+			.map_err(|_| "Invalid attempt to determine key in ext_offchain_index_set")?;
+		let value = this.memory.get(value_data, value_len as usize)
+			.map_err(|_| "Invalid attempt to determine value in ext_offchain_index_set")?;
+		this.ext.set_offchain_index(&key, Some(&value));
+		Ok(())
+	},
+	ext_offchain_index_clear(key_data: *const u8, key_len: u32) => {
+		let key = this.memory.get(key_data, key_len as usize)
+			.map_err(|_| "Invalid attempt to determine key in ext_offchain_index_clear")?;
+		this.ext.set_offchain_index(&key, None);
+		Ok(())
+	},
 	ext_kill_child_storage(storage_key_data: *const u8, storage_key_len: u32) => {
 		let storage_key = this.memory.get(
 			storage_key_data,
@@ -536,6 +584,18 @@ impl_function_executor!(this: FunctionExecutor<'e, E>,
 	ext_chain_id() -> u64 => {
 		Ok(this.ext.chain_id())
 	},
+	ext_trace_enter_span(name_data: *const u8, name_len: u32) => {
+		if tracing::is_enabled() {
+			let name = this.memory.get(name_data, name_len as usize)
+				.map_err(|_| "Invalid attempt to get name in ext_trace_enter_span")?;
+			tracing::enter_span(&String::from_utf8_lossy(&name));
+		}
+		Ok(())
+	},
+	ext_trace_exit_span() => {
+		tracing::exit_span();
+		Ok(())
+	},
 	ext_twox_64(data: *const u8, len: u32, out: *mut u8) => {
 		let result: [u8; 8] = if len == 0 {
 			let hashed = twox_64(&[0u8; 0]);
@@ -673,6 +733,78 @@ impl_function_executor!(this: FunctionExecutor<'e, E>,
 			5
 		})
 	},
+	ext_sr25519_vrf_verify(
+		context_data: *const u8,
+		context_len: u32,
+		msg_data: *const u8,
+		msg_len: u32,
+		sig_data: *const u8,
+		pubkey_data: *const u8,
+		randomness_out: *mut u8
+	) -> u32 => {
+		let context = this.memory.get(context_data, context_len as usize)
+			.map_err(|_| "Invalid attempt to get context in ext_sr25519_vrf_verify")?;
+		let msg = this.memory.get(msg_data, msg_len as usize)
+			.map_err(|_| "Invalid attempt to get message in ext_sr25519_vrf_verify")?;
+		let sig = this.memory.get(sig_data, 96)
+			.map_err(|_| "Invalid attempt to get signature in ext_sr25519_vrf_verify")?;
+		let mut pubkey = [0u8; 32];
+		this.memory.get_into(pubkey_data, &mut pubkey[..])
+			.map_err(|_| "Invalid attempt to get pubkey in ext_sr25519_vrf_verify")?;
+
+		let randomness = sr25519::VrfSignature::decode(&mut &sig[..])
+			.ok()
+			.and_then(|sig| sr25519::vrf_verify(&pubkey, &context, &msg, &sig));
+		match randomness {
+			Some(randomness) => {
+				this.memory.set(randomness_out, &randomness)
+					.map_err(|_| "Invalid attempt to set randomness in ext_sr25519_vrf_verify")?;
+				Ok(0)
+			},
+			None => Ok(5),
+		}
+	},
+	ext_start_batch_verify() => {
+		this.batch_verifier = Some(BatchVerifier::new());
+		Ok(())
+	},
+	ext_batch_verify(
+		kind: u32,
+		msg_data: *const u8,
+		msg_len: u32,
+		sig_data: *const u8,
+		pubkey_data: *const u8
+	) -> u32 => {
+		let kind = SignatureKind::try_from(kind)
+			.map_err(|_| "Invalid signature kind passed to ext_batch_verify")?;
+		let mut sig = [0u8; 64];
+		this.memory.get_into(sig_data, &mut sig[..])
+			.map_err(|_| "Invalid attempt to get signature in ext_batch_verify")?;
+		let mut pubkey = [0u8; 32];
+		this.memory.get_into(pubkey_data, &mut pubkey[..])
+			.map_err(|_| "Invalid attempt to get pubkey in ext_batch_verify")?;
+		let msg = this.memory.get(msg_data, msg_len as usize)
+			.map_err(|_| "Invalid attempt to get message in ext_batch_verify")?;
+
+		let valid = match this.batch_verifier {
+			Some(ref mut batch) => {
+				batch.push(kind, sig, msg, pubkey);
+				true
+			},
+			None => match kind {
+				SignatureKind::Ed25519 => ed25519::Pair::verify_weak(&sig, &msg, &pubkey),
+				SignatureKind::Sr25519 => sr25519::Pair::verify_weak(&sig, &msg, &pubkey),
+				SignatureKind::Sr25519OrEd25519 => sr25519::Pair::verify_weak(&sig, &msg, &pubkey)
+					|| ed25519::Pair::verify_weak(&sig, &msg, &pubkey),
+			},
+		};
+		Ok(if valid { 0 } else { 5 })
+	},
+	ext_finish_batch_verify() -> u32 => {
+		let batch = this.batch_verifier.take()
+			.ok_or("ext_finish_batch_verify called without a batch being started")?;
+		Ok(if batch.verify() { 0 } else { 5 })
+	},
 	ext_secp256k1_ecdsa_recover(msg_data: *const u8, sig_data: *const u8, pubkey_data: *mut u8) -> u32 => {
 		let mut sig = [0u8; 65];
 		this.memory.get_into(sig_data, &mut sig[..])
@@ -852,6 +984,56 @@ impl_function_executor!(this: FunctionExecutor<'e, E>,
 
 		Ok(offset)
 	},
+	ext_keystore_public_keys(
+		key_type: u32,
+		written_out: *mut u32
+	) -> *mut u8 => {
+		let res = this.ext.offchain()
+			.map(|api| api.keystore_public_keys(key_type))
+			.ok_or_else(|| "Calling unavailable API ext_keystore_public_keys: wasm")?;
+
+		let encoded = res.encode();
+		let len = encoded.len() as u32;
+		let offset = this.heap.allocate(len)? as u32;
+		this.memory.set(offset, &encoded)
+			.map_err(|_| "Invalid attempt to set memory in ext_keystore_public_keys")?;
+		this.memory.write_primitive(written_out, len)
+			.map_err(|_| "Invalid attempt to write written_out in ext_keystore_public_keys")?;
+		Ok(offset)
+	},
+	ext_keystore_sign(
+		key_type: u32,
+		public: *const u8,
+		public_len: u32,
+		data: *const u8,
+		data_len: u32,
+		sig_data_len: *mut u32
+	) -> *mut u8 => {
+		let public = this.memory.get(public, public_len as usize)
+			.map_err(|_| "OOB while ext_keystore_sign: wasm")?;
+		let message = this.memory.get(data, data_len as usize)
+			.map_err(|_| "OOB while ext_keystore_sign: wasm")?;
+
+		let res = this.ext.offchain()
+			.map(|api| api.keystore_sign(key_type, &public, &message))
+			.ok_or_else(|| "Calling unavailable API ext_keystore_sign: wasm")?;
+
+		let (offset, len) = match res {
+			Ok(signature) => {
+				let len = signature.len() as u32;
+				let offset = this.heap.allocate(len)? as u32;
+				this.memory.set(offset, &signature)
+					.map_err(|_| "Invalid attempt to set memory in ext_keystore_sign")?;
+				(offset, len)
+			},
+			Err(()) => (0, u32::max_value()),
+		};
+
+		this.memory.write_primitive(sig_data_len, len)
+			.map_err(|_| "Invalid attempt to write sig_data_len in ext_keystore_sign")?;
+
+		Ok(offset)
+	},
 	ext_verify(
 		key: u64,
 		msg: *const u8,
@@ -876,6 +1058,34 @@ impl_function_executor!(this: FunctionExecutor<'e, E>,
 			Err(()) => Ok(u32::max_value()),
 		}
 	},
+	ext_vrf_sign(
+		key: u64,
+		context_data: *const u8,
+		context_len: u32,
+		msg_data: *const u8,
+		msg_len: u32,
+		sig_out: *mut u8
+	) -> u32 => {
+		let key = offchain::CryptoKey::try_from(key)
+			.map_err(|_| "Key OOB while ext_vrf_sign: wasm")?;
+		let context = this.memory.get(context_data, context_len as usize)
+			.map_err(|_| "OOB while ext_vrf_sign: wasm")?;
+		let message = this.memory.get(msg_data, msg_len as usize)
+			.map_err(|_| "OOB while ext_vrf_sign: wasm")?;
+
+		let res = this.ext.offchain()
+			.map(|api| api.vrf_sign(key, &*context, &*message))
+			.ok_or_else(|| "Calling unavailable API ext_vrf_sign: wasm")?;
+
+		match res {
+			Ok(signature) => {
+				this.memory.set(sig_out, &signature.encode())
+					.map_err(|_| "Invalid attempt to set memory in ext_vrf_sign")?;
+				Ok(0)
+			},
+			Err(()) => Ok(u32::max_value()),
+		}
+	},
 	ext_timestamp() -> u64 => {
 		let timestamp = this.ext.offchain()
 			.map(|api| api.timestamp())
@@ -1253,14 +1463,49 @@ impl_function_executor!(this: FunctionExecutor<'e, E>,
 /// Wasm rust executor for contracts.
 ///
 /// Executes the provided code in a sandboxed wasm runtime.
-#[derive(Debug, Clone)]
-pub struct WasmExecutor;
+#[derive(Clone)]
+pub struct WasmExecutor {
+	/// Host functions callable by the runtime in addition to the built-in ones.
+	host_functions: Vec<&'static dyn HostFunction>,
+	/// The maximum number of 64KB pages of linear memory a runtime instance may have.
+	max_memory_pages: Option<u32>,
+}
+
+impl std::fmt::Debug for WasmExecutor {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		f.debug_struct("WasmExecutor")
+			.field(
+				"host_functions",
+				&self.host_functions.iter()
+					.map(|func| host_functions::import_name(func.name(), func.version()))
+					.collect::<Vec<_>>(),
+			)
+			.field("max_memory_pages", &self.max_memory_pages)
+			.finish()
+	}
+}
 
 impl WasmExecutor {
 
 	/// Create a new instance.
 	pub fn new() -> Self {
-		WasmExecutor
+		WasmExecutor {
+			host_functions: Vec::new(),
+			max_memory_pages: None,
+		}
+	}
+
+	/// Limit the linear memory of runtime instances, including the heap, to the given number
+	/// of 64KB pages. `None` means no limit.
+	pub fn with_max_memory_pages(mut self, max_memory_pages: Option<u32>) -> Self {
+		self.max_memory_pages = max_memory_pages;
+		self
+	}
+
+	/// Register additional host functions the runtime may import.
+	pub fn with_host_functions(mut self, host_functions: Vec<&'static dyn HostFunction>) -> Self {
+		self.host_functions.extend(host_functions);
+		self
 	}
 
 	/// Call a given method in the given code.
@@ -1277,7 +1522,7 @@ impl WasmExecutor {
 		data: &[u8],
 	) -> Result<Vec<u8>> {
 		let module = ::wasmi::Module::from_buffer(code)?;
-		let module = Self::instantiate_module::<E>(heap_pages, &module)?;
+		let module = self.instantiate_module::<E>(heap_pages, &module)?;
 		self.call_in_wasm_module(ext, &module, method, data)
 	}
 
@@ -1299,7 +1544,7 @@ impl WasmExecutor {
 		filter_result: FR,
 	) -> Result<R> {
 		let module = wasmi::Module::from_buffer(code)?;
-		let module = Self::instantiate_module::<E>(heap_pages, &module)?;
+		let module = self.instantiate_module::<E>(heap_pages, &module)?;
 		self.call_in_wasm_module_with_custom_signature(
 			ext,
 			&module,
@@ -1394,7 +1639,10 @@ impl WasmExecutor {
 		let result = module_instance.invoke_export(
 			method,
 			&parameters,
-			&mut fec
+			&mut host_functions::WithHostFunctions {
+				builtin: &mut fec,
+				host_functions: &self.host_functions,
+			},
 		);
 		let result = match result {
 			Ok(val) => match filter_result(val, &memory)? {
@@ -1403,7 +1651,27 @@ impl WasmExecutor {
 			},
 			Err(e) => {
 				trace!(target: "wasm-executor", "Failed to execute code with {} pages", memory.current_size().0);
-				Err(e.into())
+				let out_of_memory = e.as_host_error()
+					.and_then(|err| err.downcast_ref::<Error>())
+					.map_or(false, |err| match err {
+						Error::AllocatorOutOfSpace | Error::RequestedAllocationTooLarge => true,
+						_ => false,
+					});
+				if out_of_memory {
+					debug!(
+						target: "wasm-executor",
+						"Runtime ran out of memory in {} with {} pages",
+						method,
+						memory.current_size().0,
+					);
+					Err(Error::AllocatorOutOfSpace)
+				} else if fec.output.is_empty() {
+					Err(e.into())
+				} else {
+					let output = fec.output.join("\n");
+					debug!(target: "wasm-executor", "Runtime trapped in {}: {}, output: {}", method, e, output);
+					Err(Error::Trapped { error: e, output })
+				}
 			},
 		};
 
@@ -1412,14 +1680,19 @@ impl WasmExecutor {
 
 	/// Prepare module instance
 	pub fn instantiate_module<E: Externalities<Blake2Hasher>>(
+		&self,
 		heap_pages: usize,
 		module: &Module,
 	) -> Result<ModuleRef> {
+		let resolver = host_functions::Resolver {
+			builtin: FunctionExecutor::<E>::resolver(),
+			host_functions: &self.host_functions,
+		};
 		// start module instantiation. Don't run 'start' function yet.
 		let intermediate_instance = ModuleInstance::new(
 			module,
 			&ImportsBuilder::new()
-			.with_resolver("env", FunctionExecutor::<E>::resolver())
+			.with_resolver("env", &resolver)
 		)?;
 
 		// Verify that the module has the heap base global variable.
@@ -1427,6 +1700,12 @@ impl WasmExecutor {
 
 		// Extract a reference to a linear memory.
 		let memory = Self::get_mem_instance(intermediate_instance.not_started_instance())?;
+		if let Some(limit) = self.max_memory_pages {
+			let pages = memory.current_size().0 + heap_pages;
+			if pages > limit as usize {
+				return Err(Error::MemoryLimitExceeded { pages, limit });
+			}
+		}
 		memory.grow(Pages(heap_pages)).map_err(|_| Error::Runtime)?;
 
 		if intermediate_instance.has_start() {
@@ -1477,6 +1756,29 @@ mod tests {
 		assert!(output.is_err());
 	}
 
+	#[test]
+	fn instantiation_respects_max_memory_pages() {
+		let mut ext = TestExternalities::default();
+		let test_code = WASM_BINARY;
+
+		let executor = WasmExecutor::new().with_max_memory_pages(Some(8));
+		match executor.call(&mut ext, 8, &test_code[..], "test_empty_return", &[]) {
+			Err(Error::MemoryLimitExceeded { limit: 8, .. }) => {},
+			other => panic!("Expected the memory limit to be exceeded, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn panicking_reports_runtime_output() {
+		let mut ext = TestExternalities::default();
+		let test_code = WASM_BINARY;
+
+		match WasmExecutor::new().call(&mut ext, 8, &test_code[..], "test_panic", &[]) {
+			Err(Error::Trapped { output, .. }) => assert!(output.contains("lib.rs")),
+			other => panic!("Expected a trap with runtime output, got {:?}", other),
+		}
+	}
+
 	#[test]
 	fn storage_should_work() {
 		let mut ext = TestExternalities::default();
@@ -1626,6 +1928,32 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn sr25519_vrf_verify_should_work() {
+		let mut ext = TestExternalities::<Blake2Hasher>::default();
+		let test_code = WASM_BINARY;
+		let key = sr25519::Pair::from_seed(&blake2_256(b"test"));
+		let signature = key.vrf_sign(b"test", b"all ok!");
+		let mut calldata = vec![];
+		calldata.extend_from_slice(key.public().as_ref());
+		calldata.extend_from_slice(&signature.encode());
+
+		assert_eq!(
+			WasmExecutor::new().call(&mut ext, 8, &test_code[..], "test_sr25519_vrf_verify", &calldata).unwrap(),
+			sr25519::vrf_verify(key.public().as_ref(), b"test", b"all ok!", &signature).unwrap().to_vec(),
+		);
+
+		let other_signature = key.vrf_sign(b"test", b"all is not ok!");
+		let mut calldata = vec![];
+		calldata.extend_from_slice(key.public().as_ref());
+		calldata.extend_from_slice(&other_signature.encode());
+
+		assert_eq!(
+			WasmExecutor::new().call(&mut ext, 8, &test_code[..], "test_sr25519_vrf_verify", &calldata).unwrap(),
+			Vec::<u8>::new(),
+		);
+	}
+
 	#[test]
 	fn enumerated_trie_root_should_work() {
 		let mut ext = TestExternalities::<Blake2Hasher>::default();
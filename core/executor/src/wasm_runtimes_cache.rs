@@ -20,6 +20,8 @@ use crate::error::Error;
 use crate::wasm_executor::WasmExecutor;
 use log::{trace, warn};
 use codec::Decode;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
 use parity_wasm::elements::{deserialize_buffer, DataSegment, Instruction, Module as RawModule};
 use primitives::storage::well_known_keys;
 use primitives::Blake2Hasher;
@@ -28,8 +30,79 @@ use state_machine::Externalities;
 use std::collections::hash_map::{Entry, HashMap};
 use std::mem;
 use std::rc::Rc;
+use std::sync::Arc;
 use wasmi::{Module as WasmModule, ModuleRef as WasmModuleInstanceRef, RuntimeValue};
 
+/// Maximum number of distinct runtime codes whose prepared modules are kept around.
+///
+/// Runtime upgrades are rare, so in practice only the current and maybe the previous runtime are
+/// in use; the limit just keeps a node that executes many different codes (e.g. while syncing
+/// a long history) from growing the cache without bound.
+const MAX_PREPARED_MODULES: usize = 8;
+
+lazy_static! {
+	/// Prepared modules shared by the runtime caches of all threads.
+	///
+	/// Instances can't be shared between threads, but the costly part of creating one - parsing and
+	/// validating the code - only needs to happen once per process.
+	static ref PREPARED_MODULES: Mutex<HashMap<[u8; 32], Arc<PreparedModule>>> = Mutex::new(HashMap::new());
+}
+
+/// A runtime code that was parsed and validated and is ready to be instantiated.
+struct PreparedModule {
+	/// The validated wasm module.
+	module: WasmModule,
+	/// The data segments of the module, used for taking state snapshots.
+	data_segments: Vec<DataSegment>,
+}
+
+impl PreparedModule {
+	/// Fetch the prepared module for `code_hash` from the shared cache, or prepare the code returned
+	/// by `code` and insert it.
+	fn fetch<F: FnOnce() -> Option<Vec<u8>>>(
+		code_hash: [u8; 32],
+		code: F,
+	) -> Result<Arc<PreparedModule>, CacheError> {
+		if let Some(prepared) = PREPARED_MODULES.lock().get(&code_hash) {
+			return Ok(prepared.clone());
+		}
+
+		let code = code().ok_or(CacheError::CodeNotFound)?;
+		let module = WasmModule::from_buffer(&code).map_err(|_| CacheError::InvalidModule)?;
+
+		// Extract the data segments from the wasm code.
+		//
+		// A return of this error actually indicates that there is a problem in logic, since
+		// we just loaded and validated the `module` above.
+		let data_segments = extract_data_segments(&code).ok_or(CacheError::CantDeserializeWasm)?;
+
+		let prepared = Arc::new(PreparedModule { module, data_segments });
+		let mut modules = PREPARED_MODULES.lock();
+		if modules.len() >= MAX_PREPARED_MODULES && !modules.contains_key(&code_hash) {
+			// Instances that are already created keep their own copy alive, so it is fine to
+			// simply start over.
+			modules.clear();
+		}
+		modules.insert(code_hash, prepared.clone());
+		Ok(prepared)
+	}
+}
+
+/// Specification of different methods of executing the runtime Wasm code.
+///
+/// Only the interpreter is available until a compiling backend is part of the executor.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum WasmExecutionMethod {
+	/// Uses the Wasmi interpreter.
+	Interpreted,
+}
+
+impl Default for WasmExecutionMethod {
+	fn default() -> WasmExecutionMethod {
+		WasmExecutionMethod::Interpreted
+	}
+}
+
 #[derive(Debug)]
 enum CacheError {
 	CodeNotFound,
@@ -37,6 +110,7 @@ enum CacheError {
 	InvalidModule,
 	CantDeserializeWasm,
 	Instantiation(Error),
+	MemoryLimitExceeded { pages: usize, limit: u32 },
 }
 
 /// A runtime along with its version and initial state snapshot.
@@ -190,13 +264,18 @@ const DEFAULT_HEAP_PAGES: u64 = 1024;
 /// reset to the initial memory. So, one runtime instance is reused for
 /// every fetch request.
 ///
+/// The parsed and validated code is additionally shared between the caches of all threads, so
+/// each thread only has to instantiate it.
+///
 /// For now the cache grows indefinitely, but that should be fine for now since runtimes can only be
 /// upgraded rarely and there are no other ways to make the node to execute some other runtime.
 pub struct RuntimesCache {
 	/// A cache of runtime instances along with metadata, ready to be reused.
 	///
-	/// Instances are keyed by the hash of their code.
-	instances: HashMap<[u8; 32], Result<Rc<CachedRuntime>, CacheError>>,
+	/// Instances are keyed by the execution method, the hash of their code and the number of heap
+	/// pages they were created with, so that a change of any of these never hands out a runtime
+	/// prepared for different parameters.
+	instances: HashMap<(WasmExecutionMethod, [u8; 32], u64), Result<Rc<CachedRuntime>, CacheError>>,
 }
 
 impl RuntimesCache {
@@ -225,6 +304,8 @@ impl RuntimesCache {
 	/// up an initial runtime instance. The parameter is only needed for calling
 	/// into the Wasm module to find out the `Core_version`.
 	///
+	/// `wasm_method` - The method used to execute the runtime code.
+	///
 	/// `default_heap_pages` - Number of 64KB pages to allocate for Wasm execution.
 	/// Defaults to `DEFAULT_HEAP_PAGES` if `None` is provided.
 	///
@@ -234,16 +315,20 @@ impl RuntimesCache {
 	/// returned. `RuntimeVersion` is contained if the call to `Core_version` returned
 	/// a version.
 	///
-	/// In case of failure one of two errors can be returned:
+	/// In case of failure one of the following errors can be returned:
 	///
 	/// `Err::InvalidCode` is returned for runtime code issues.
 	///
 	/// `Error::InvalidMemoryReference` is returned if no memory export with the
 	/// identifier `memory` can be found in the runtime.
+	///
+	/// `Error::MemoryLimitExceeded` is returned if the runtime would need more memory than
+	/// `wasm_executor` allows.
 	pub fn fetch_runtime<E: Externalities<Blake2Hasher>>(
 		&mut self,
 		wasm_executor: &WasmExecutor,
 		ext: &mut E,
+		wasm_method: WasmExecutionMethod,
 		default_heap_pages: Option<u64>,
 	) -> Result<Rc<CachedRuntime>, Error> {
 		let code_hash = ext
@@ -253,15 +338,27 @@ impl RuntimesCache {
 		// This is direct result from fighting with borrowck.
 		let handle_result =
 			|cached_result: &Result<Rc<CachedRuntime>, CacheError>| match *cached_result {
+				Err(CacheError::MemoryLimitExceeded { pages, limit }) =>
+					Err(Error::MemoryLimitExceeded { pages, limit }),
 				Err(_) => Err(Error::InvalidCode),
 				Ok(ref cached_runtime) => Ok(Rc::clone(cached_runtime)),
 			};
 
-		match self.instances.entry(code_hash.into()) {
+		let heap_pages = ext
+			.storage(well_known_keys::HEAP_PAGES)
+			.and_then(|pages| u64::decode(&mut &pages[..]).ok())
+			.or(default_heap_pages)
+			.unwrap_or(DEFAULT_HEAP_PAGES);
+
+		let code_hash: [u8; 32] = code_hash.into();
+		match self.instances.entry((wasm_method, code_hash, heap_pages)) {
 			Entry::Occupied(o) => handle_result(o.get()),
 			Entry::Vacant(v) => {
 				trace!(target: "runtimes_cache", "no instance found in cache, creating now.");
-				let result = Self::create_wasm_instance(wasm_executor, ext, default_heap_pages);
+				let result = match wasm_method {
+					WasmExecutionMethod::Interpreted =>
+						Self::create_wasm_instance(wasm_executor, ext, code_hash, heap_pages),
+				};
 				if let Err(ref err) = result {
 					warn!(target: "runtimes_cache", "cannot create a runtime: {:?}", err);
 				}
@@ -273,31 +370,20 @@ impl RuntimesCache {
 	fn create_wasm_instance<E: Externalities<Blake2Hasher>>(
 		wasm_executor: &WasmExecutor,
 		ext: &mut E,
-		default_heap_pages: Option<u64>,
+		code_hash: [u8; 32],
+		heap_pages: u64,
 	) -> Result<Rc<CachedRuntime>, CacheError> {
-		let code = ext
-			.original_storage(well_known_keys::CODE)
-			.ok_or(CacheError::CodeNotFound)?;
-		let module = WasmModule::from_buffer(&code).map_err(|_| CacheError::InvalidModule)?;
-
-		// Extract the data segments from the wasm code.
-		//
-		// A return of this error actually indicates that there is a problem in logic, since
-		// we just loaded and validated the `module` above.
-		let data_segments = extract_data_segments(&code).ok_or(CacheError::CantDeserializeWasm)?;
-
-		let heap_pages = ext
-			.storage(well_known_keys::HEAP_PAGES)
-			.and_then(|pages| u64::decode(&mut &pages[..]).ok())
-			.or(default_heap_pages)
-			.unwrap_or(DEFAULT_HEAP_PAGES);
+		let prepared = PreparedModule::fetch(code_hash, || ext.original_storage(well_known_keys::CODE))?;
 
 		// Instantiate this module.
-		let instance = WasmExecutor::instantiate_module::<E>(heap_pages as usize, &module)
-			.map_err(CacheError::Instantiation)?;
+		let instance = wasm_executor.instantiate_module::<E>(heap_pages as usize, &prepared.module)
+			.map_err(|err| match err {
+				Error::MemoryLimitExceeded { pages, limit } => CacheError::MemoryLimitExceeded { pages, limit },
+				err => CacheError::Instantiation(err),
+			})?;
 
 		// Take state snapshot before executing anything.
-		let state_snapshot = StateSnapshot::take(&instance, data_segments, heap_pages as u32)
+		let state_snapshot = StateSnapshot::take(&instance, prepared.data_segments.clone(), heap_pages as u32)
 			.expect(
 				"`take` returns `Err` if the module is not valid;
 				we already loaded module above, thus the `Module` is proven to be valid at this point;
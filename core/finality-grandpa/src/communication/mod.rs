@@ -33,10 +33,11 @@ use grandpa::{voter, voter_set::VoterSet};
 use grandpa::Message::{Prevote, Precommit, PrimaryPropose};
 use futures::prelude::*;
 use futures::sync::{oneshot, mpsc};
-use log::{debug, trace};
+use log::{debug, trace, warn};
 use tokio_executor::Executor;
 use codec::{Encode, Decode};
 use primitives::{ed25519, Pair};
+use keystore::Keystore;
 use substrate_telemetry::{telemetry, CONSENSUS_DEBUG, CONSENSUS_INFO};
 use sr_primitives::traits::{Block as BlockT, Hash as HashT, Header as HeaderT};
 use network::{consensus_gossip as network_gossip, NetworkService};
@@ -336,12 +337,15 @@ impl<B: BlockT, N: Network<B>> NetworkBridge<B, N> {
 	}
 
 	/// Get the round messages for a round in the current set ID. These are signature-checked.
+	///
+	/// Outgoing messages are signed with the key `locals.1` of the keystore `locals.0`, if it is
+	/// a voter.
 	pub(crate) fn round_communication(
 		&self,
 		round: Round,
 		set_id: SetId,
 		voters: Arc<VoterSet<AuthorityId>>,
-		local_key: Option<Arc<ed25519::Pair>>,
+		locals: Option<(Arc<dyn Keystore>, AuthorityId)>,
 		has_voted: HasVoted<B>,
 	) -> (
 		impl Stream<Item=SignedMessage<B>,Error=Error>,
@@ -353,15 +357,7 @@ impl<B: BlockT, N: Network<B>> NetworkBridge<B, N> {
 			&*voters,
 		);
 
-		let locals = local_key.and_then(|pair| {
-			let public = pair.public();
-			let id = AuthorityId(public.0);
-			if voters.contains_key(&id) {
-				Some((pair, id))
-			} else {
-				None
-			}
-		});
+		let locals = locals.filter(|(_, id)| voters.contains_key(id));
 
 		let topic = round_topic::<B>(round.0, set_id.0);
 		let incoming = self.service.messages_for(topic)
@@ -653,7 +649,7 @@ pub(crate) fn check_message_sig<Block: BlockT>(
 struct OutgoingMessages<Block: BlockT, N: Network<Block>> {
 	round: u64,
 	set_id: u64,
-	locals: Option<(Arc<ed25519::Pair>, AuthorityId)>,
+	locals: Option<(Arc<dyn Keystore>, AuthorityId)>,
 	sender: mpsc::UnboundedSender<SignedMessage<Block>>,
 	network: N,
 	has_voted: HasVoted<Block>,
@@ -682,9 +678,15 @@ impl<Block: BlockT, N: Network<Block>> Sink for OutgoingMessages<Block, N>
 		}
 
 		// when locals exist, sign messages on import
-		if let Some((ref pair, ref local_id)) = self.locals {
+		if let Some((ref keystore, ref local_id)) = self.locals {
 			let encoded = localized_payload(self.round, self.set_id, &msg);
-			let signature = pair.sign(&encoded[..]);
+			let signature = match keystore.sign::<ed25519::Pair>(local_id, &encoded[..]) {
+				Ok(signature) => AuthoritySignature::from_slice(&signature),
+				Err(e) => {
+					warn!(target: "afg", "Failed to sign vote in round {}: {}", self.round, e);
+					return Ok(AsyncSink::Ready);
+				},
+			};
 
 			let target_hash = msg.target().0.clone();
 			let signed = SignedMessage::<Block> {
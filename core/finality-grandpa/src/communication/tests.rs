@@ -133,7 +133,7 @@ fn config() -> crate::Config {
 	crate::Config {
 		gossip_duration: std::time::Duration::from_millis(10),
 		justification_period: 256,
-		local_key: None,
+		keystore: None,
 		name: None,
 	}
 }
@@ -153,6 +153,35 @@ impl<B, E, Block: BlockT<Hash=H256>, RA> FinalityProofProvider<B, E, Block, RA>
 	}
 }
 
+impl<B, E, Block: BlockT<Hash=H256>, RA> FinalityProofProvider<B, E, Block, RA>
+	where
+		NumberFor<Block>: BlockNumberOps,
+		B: Backend<Block, Blake2Hasher> + Send + Sync + 'static,
+		E: CallExecutor<Block, Blake2Hasher> + 'static + Clone + Send + Sync,
+		RA: Send + Sync,
+{
+	/// Prepare proof-of-finality for the best possible block in the range: (begin; end].
+	///
+	/// The caller is expected to know the finality of the block `begin` and the GRANDPA
+	/// authorities set `authorities_set_id`. Returns the encoded proof, or None if there are no
+	/// finalized blocks after `begin`.
+	pub fn prove_finality_range(
+		&self,
+		begin: Block::Hash,
+		end: Block::Hash,
+		authorities_set_id: u64,
+	) -> ClientResult<Option<Vec<u8>>> {
+		prove_finality::<_, _, GrandpaJustification<Block>>(
+			#[allow(deprecated)]
+			&*self.client.backend().blockchain(),
+			&*self.authority_provider,
+			authorities_set_id,
+			begin,
+			end,
+		)
+	}
+}
+
 impl<B, E, Block, RA> network::FinalityProofProvider<Block> for FinalityProofProvider<B, E, Block, RA>
 	where
 		Block: BlockT<Hash=H256>,
@@ -172,13 +201,10 @@ impl<B, E, Block, RA> network::FinalityProofProvider<Block> for FinalityProofPro
 				ClientError::Backend(format!("Invalid finality proof request"))
 			})?;
 		match request {
-			FinalityProofRequest::Original(request) => prove_finality::<_, _, GrandpaJustification<Block>>(
-				#[allow(deprecated)]
-				&*self.client.backend().blockchain(),
-				&*self.authority_provider,
-				request.authorities_set_id,
+			FinalityProofRequest::Original(request) => self.prove_finality_range(
 				request.last_finalized,
 				for_block,
+				request.authorities_set_id,
 			),
 		}
 	}
@@ -65,7 +65,8 @@ use fg_primitives::GrandpaApi;
 use inherents::InherentDataProviders;
 use sr_primitives::generic::BlockId;
 use consensus_common::SelectChain;
-use primitives::{ed25519, H256, Pair, Blake2Hasher};
+use primitives::{H256, Blake2Hasher};
+use keystore::Keystore;
 use substrate_telemetry::{telemetry, CONSENSUS_INFO, CONSENSUS_DEBUG, CONSENSUS_WARN};
 use serde_json;
 
@@ -90,6 +91,8 @@ mod light_import;
 mod observer;
 mod until_imported;
 
+pub mod rpc;
+
 #[cfg(feature="service-integration")]
 mod service_integration;
 #[cfg(feature="service-integration")]
@@ -198,8 +201,8 @@ pub struct Config {
 	/// at least every justification_period blocks. There are some other events which might cause
 	/// justification generation.
 	pub justification_period: u32,
-	/// The local signing key.
-	pub local_key: Option<Arc<ed25519::Pair>>,
+	/// The keystore that holds the local signing key.
+	pub keystore: Option<Arc<dyn Keystore>>,
 	/// Some local identifier of the voter.
 	pub name: Option<String>,
 }
@@ -395,8 +398,19 @@ where
 	))
 }
 
+/// Returns the first key of `keystore` that is a voter of `voters`.
+pub(crate) fn local_authority_id(
+	voters: &VoterSet<AuthorityId>,
+	keystore: Option<&Arc<dyn Keystore>>,
+) -> Option<AuthorityId> {
+	let keys = keystore?.public_keys::<AuthorityId>()
+		.map_err(|e| warn!(target: "afg", "Unable to get keys from the keystore: {}", e))
+		.ok()?;
+	keys.into_iter().find(|key| voters.contains_key(key))
+}
+
 fn global_communication<Block: BlockT<Hash=H256>, B, E, N, RA>(
-	local_key: Option<&Arc<ed25519::Pair>>,
+	keystore: Option<&Arc<dyn Keystore>>,
 	set_id: u64,
 	voters: &Arc<VoterSet<AuthorityId>>,
 	client: &Arc<Client<B, E, Block, RA>>,
@@ -418,9 +432,7 @@ fn global_communication<Block: BlockT<Hash=H256>, B, E, N, RA>(
 	NumberFor<Block>: BlockNumberOps,
 {
 
-	let is_voter = local_key
-		.map(|pair| voters.contains_key(&pair.public().into()))
-		.unwrap_or(false);
+	let is_voter = local_authority_id(voters, keystore).is_some();
 
 	// verification stream
 	let (global_in, global_out) = network.global_communication(
@@ -571,7 +583,7 @@ pub fn run_grandpa_voter<B, E, Block: BlockT<Hash=H256>, N, RA, SC, X>(
 	initial_environment.update_voter_set_state(|voter_set_state| {
 		match voter_set_state {
 			VoterSetState::Live { current_round: HasVoted::Yes(id, _), completed_rounds } => {
-				let local_id = config.local_key.clone().map(|pair| pair.public());
+				let local_id = local_authority_id(&initial_environment.voters, config.keystore.as_ref());
 				let has_voted = match local_id {
 					Some(local_id) => if *id == local_id {
 						// keep the previous votes
@@ -611,7 +623,7 @@ pub fn run_grandpa_voter<B, E, Block: BlockT<Hash=H256>, N, RA, SC, X>(
 				);
 
 				let global_comms = global_communication(
-					config.local_key.as_ref(),
+					config.keystore.as_ref(),
 					env.set_id,
 					&env.voters,
 					&client,
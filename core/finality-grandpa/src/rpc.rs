@@ -0,0 +1,106 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! GRANDPA RPC API.
+//!
+//! Lets external verifiers, such as bridges to other chains, fetch proofs of finality for
+//! blocks of this chain.
+
+use std::sync::Arc;
+
+use client::{backend::Backend, CallExecutor, error::Error as ClientError};
+use grandpa::BlockNumberOps;
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use primitives::{Blake2Hasher, Bytes, H256};
+use sr_primitives::traits::{Block as BlockT, NumberFor};
+
+use crate::FinalityProofProvider;
+
+pub use self::gen_client::Client as GrandpaClient;
+
+/// Base code for all GRANDPA errors.
+const BASE_ERROR: i64 = 6000;
+
+/// GRANDPA RPC API.
+#[rpc]
+pub trait GrandpaApi<Hash> {
+	/// Prove finality of the best block finalized in the range (`begin`; `end`].
+	///
+	/// The caller is expected to know the finality of `begin` and the authorities set
+	/// `authorities_set_id`. Returns the SCALE-encoded proof: one fragment with a justification,
+	/// the headers it finalizes and the proof of the new authorities for every change of the
+	/// authorities set within the range, the last one finalizing the best possible block.
+	/// Returns `None` if no block has been finalized after `begin`.
+	#[rpc(name = "grandpa_proveFinality")]
+	fn prove_finality(&self, begin: Hash, end: Hash, authorities_set_id: u64) -> Result<Option<Bytes>>;
+}
+
+/// Implementation of the GRANDPA RPC API on top of a finality proof provider.
+pub struct Grandpa<B, E, Block: BlockT<Hash=H256>, RA> {
+	provider: Arc<FinalityProofProvider<B, E, Block, RA>>,
+}
+
+impl<B, E, Block: BlockT<Hash=H256>, RA> Grandpa<B, E, Block, RA> {
+	/// Create new GRANDPA RPC handler serving the proofs of `provider`.
+	pub fn new(provider: Arc<FinalityProofProvider<B, E, Block, RA>>) -> Self {
+		Grandpa { provider }
+	}
+}
+
+impl<B, E, Block, RA> GrandpaApi<Block::Hash> for Grandpa<B, E, Block, RA>
+	where
+		Block: BlockT<Hash=H256> + 'static,
+		NumberFor<Block>: BlockNumberOps,
+		B: Backend<Block, Blake2Hasher> + Send + Sync + 'static,
+		E: CallExecutor<Block, Blake2Hasher> + 'static + Clone + Send + Sync,
+		RA: Send + Sync + 'static,
+{
+	fn prove_finality(
+		&self,
+		begin: Block::Hash,
+		end: Block::Hash,
+		authorities_set_id: u64,
+	) -> Result<Option<Bytes>> {
+		self.provider.prove_finality_range(begin, end, authorities_set_id)
+			.map(|proof| proof.map(Into::into))
+			.map_err(proof_error)
+	}
+}
+
+fn proof_error(e: ClientError) -> Error {
+	Error {
+		code: ErrorCode::ServerError(BASE_ERROR + 1),
+		message: format!("Failed to prove finality: {}", e),
+		data: None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use test_client::runtime::Block;
+
+	#[test]
+	fn proves_nothing_when_no_block_is_finalized_after_begin() {
+		let client = Arc::new(test_client::new());
+		let genesis = client.info().chain.genesis_hash;
+		let grandpa = Grandpa::new(Arc::new(FinalityProofProvider::<_, _, Block, _>::new(client.clone(), client)));
+
+		assert_eq!(grandpa.prove_finality(genesis, genesis, 0), Ok(None));
+		assert!(grandpa.prove_finality(Default::default(), genesis, 0).is_err());
+	}
+}
@@ -24,6 +24,7 @@ use parking_lot::Mutex;
 use futures03::{StreamExt as _, TryStreamExt as _};
 use tokio::runtime::current_thread;
 use keyring::Ed25519Keyring;
+use keystore::memory::MemoryStore;
 use client::{
 	error::Result,
 	runtime_api::{Core, RuntimeVersion, ApiExt},
@@ -37,7 +38,7 @@ use std::result;
 use codec::Decode;
 use sr_primitives::traits::{ApiRef, ProvideRuntimeApi, Header as HeaderT};
 use sr_primitives::generic::BlockId;
-use primitives::{NativeOrEncoded, ExecutionContext};
+use primitives::{NativeOrEncoded, ExecutionContext, ed25519};
 use fg_primitives::AuthorityId;
 
 use authorities::AuthoritySet;
@@ -342,6 +343,12 @@ impl AuthoritySetForFinalityChecker<Block> for TestApi {
 
 const TEST_GOSSIP_DURATION: Duration = Duration::from_millis(500);
 
+fn create_keystore(authority: Ed25519Keyring) -> Arc<dyn Keystore> {
+	let keystore = MemoryStore::new();
+	keystore.insert::<ed25519::Pair>(&authority.to_seed()).expect("Inserts the key");
+	Arc::new(keystore)
+}
+
 fn make_ids(keys: &[Ed25519Keyring]) -> Vec<(primitives::ed25519::Public, u64)> {
 	keys.iter()
 		.map(|key| AuthorityId::from_raw(key.to_raw_public()))
@@ -405,7 +412,7 @@ fn run_to_completion_with<F>(
 			config: Config {
 				gossip_duration: TEST_GOSSIP_DURATION,
 				justification_period: 32,
-				local_key: Some(Arc::new(key.clone().into())),
+				keystore: Some(create_keystore(*key)),
 				name: Some(format!("peer#{}", peer_id)),
 			},
 			link: link,
@@ -482,10 +489,10 @@ fn finalize_3_voters_1_full_observer() {
 
 	let all_peers = peers.iter()
 		.cloned()
-		.map(|key| Some(Arc::new(key.into())))
+		.map(|key| Some(create_keystore(key)))
 		.chain(::std::iter::once(None));
 
-	for (peer_id, local_key) in all_peers.enumerate() {
+	for (peer_id, keystore) in all_peers.enumerate() {
 		let (client, net_service, link) = {
 			let net = net.lock();
 			let link = net.peers[peer_id].data.lock().take().expect("link initialized at startup; qed");
@@ -506,7 +513,7 @@ fn finalize_3_voters_1_full_observer() {
 			config: Config {
 				gossip_duration: TEST_GOSSIP_DURATION,
 				justification_period: 32,
-				local_key,
+				keystore,
 				name: Some(format!("peer#{}", peer_id)),
 			},
 			link: link,
@@ -640,10 +647,10 @@ fn transition_3_voters_twice_1_full_observer() {
 		.cloned()
 		.collect::<HashSet<_>>() // deduplicate
 		.into_iter()
-		.map(|key| Some(Arc::new(key.into())))
+		.map(|key| Some(create_keystore(key)))
 		.enumerate();
 
-	for (peer_id, local_key) in all_peers {
+	for (peer_id, keystore) in all_peers {
 		let (client, net_service, link) = {
 			let net = net.lock();
 			let link = net.peers[peer_id].data.lock().take().expect("link initialized at startup; qed");
@@ -674,7 +681,7 @@ fn transition_3_voters_twice_1_full_observer() {
 			config: Config {
 				gossip_duration: TEST_GOSSIP_DURATION,
 				justification_period: 32,
-				local_key,
+				keystore,
 				name: Some(format!("peer#{}", peer_id)),
 			},
 			link: link,
@@ -1095,7 +1102,7 @@ fn voter_persists_its_votes() {
 				config: Config {
 					gossip_duration: TEST_GOSSIP_DURATION,
 					justification_period: 32,
-					local_key: Some(Arc::new(peers[0].clone().into())),
+					keystore: Some(create_keystore(peers[0])),
 					name: Some(format!("peer#{}", 0)),
 				},
 				link: link,
@@ -1147,7 +1154,7 @@ fn voter_persists_its_votes() {
 		let config = Config {
 			gossip_duration: TEST_GOSSIP_DURATION,
 			justification_period: 32,
-			local_key: Some(Arc::new(peers[1].clone().into())),
+			keystore: Some(create_keystore(peers[1])),
 			name: Some(format!("peer#{}", 1)),
 		};
 
@@ -1170,7 +1177,7 @@ fn voter_persists_its_votes() {
 			communication::Round(1),
 			communication::SetId(0),
 			Arc::new(VoterSet::from_iter(voters)),
-			Some(config.local_key.unwrap()),
+			Some((config.keystore.unwrap(), peers[1].into())),
 			HasVoted::No,
 		);
 
@@ -1296,7 +1303,7 @@ fn finalize_3_voters_1_light_observer() {
 				Config {
 					gossip_duration: TEST_GOSSIP_DURATION,
 					justification_period: 32,
-					local_key: None,
+					keystore: None,
 					name: Some("observer".to_string()),
 				},
 				link,
@@ -1417,12 +1424,12 @@ fn voter_catches_up_to_latest_round_when_behind() {
 	let net = Arc::new(Mutex::new(net));
 	let mut finality_notifications = Vec::new();
 
-	let voter = |local_key, peer_id, link, net: Arc<Mutex<GrandpaTestNet>>| -> Box<dyn Future<Item=(), Error=()> + Send> {
+	let voter = |keystore, peer_id, link, net: Arc<Mutex<GrandpaTestNet>>| -> Box<dyn Future<Item=(), Error=()> + Send> {
 		let grandpa_params = GrandpaParams {
 			config: Config {
 				gossip_duration: TEST_GOSSIP_DURATION,
 				justification_period: 32,
-				local_key,
+				keystore,
 				name: Some(format!("peer#{}", peer_id)),
 			},
 			link: link,
@@ -1453,7 +1460,7 @@ fn voter_catches_up_to_latest_round_when_behind() {
 				.for_each(move |_| Ok(()))
 		);
 
-		let voter = voter(Some(Arc::new((*key).into())), peer_id, link, net.clone());
+		let voter = voter(Some(create_keystore(*key)), peer_id, link, net.clone());
 
 		runtime.spawn(voter);
 	}
@@ -75,8 +75,13 @@ impl Keyring {
 		Pair::from(self).sign(msg)
 	}
 
+	/// The secret key URI of the account, e.g. to insert it into a keystore.
+	pub fn to_seed(self) -> String {
+		format!("//{}", <&'static str>::from(self))
+	}
+
 	pub fn pair(self) -> Pair {
-		Pair::from_string(&format!("//{}", <&'static str>::from(self)), None)
+		Pair::from_string(&self.to_seed(), None)
 			.expect("static values are known good; qed")
 	}
 
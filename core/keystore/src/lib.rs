@@ -15,15 +15,32 @@
 // along with Substrate. If not, see <http://www.gnu.org/licenses/>.
 
 //! Keystore (and session key management) for ed25519 based chains like Polkadot.
+//!
+//! Components that need keys use the [`Keystore`] trait, which addresses keys by their key type
+//! and raw public key. It is implemented by the filesystem [`Store`], the in-memory
+//! [`memory::MemoryStore`] used in tests and the external [`remote::RemoteSigner`].
+//!
+//! Keys are stored encrypted with the keystore password. The encryption key is derived from the
+//! password with PBKDF2-HMAC-SHA256 and the secret is sealed with ChaCha20-Poly1305. Key files
+//! written by older versions contain the secret in plain text. They can still be loaded and are
+//! encrypted on their first successful load.
 
 #![warn(missing_docs)]
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::num::NonZeroU32;
+use std::sync::RwLock;
+use std::path::{Path, PathBuf};
 use std::fs::{self, File};
 use std::io::{self, Write};
 
-use primitives::crypto::{KeyTypeId, Pair, Public};
+use primitives::crypto::{KeyTypeId, Pair, Public, Protected, key_types};
+use primitives::{ed25519, sr25519, H256, H512};
+use ring::{aead, digest, pbkdf2, rand::{SecureRandom, SystemRandom}};
+use serde::{Serialize, Deserialize};
+
+pub mod memory;
+pub mod remote;
 
 /// Keystore error.
 #[derive(Debug, derive_more::Display, derive_more::From)]
@@ -41,6 +58,24 @@ pub enum Error {
 	/// Invalid seed
 	#[display(fmt="Invalid seed")]
 	InvalidSeed,
+	/// Encrypting or decrypting a key file failed.
+	#[display(fmt="Key file encryption failed")]
+	Crypto,
+	/// The key file has an unsupported format.
+	#[display(fmt="Unsupported key file version {}", _0)]
+	UnsupportedVersion(u32),
+	/// Error of the external signer.
+	#[display(fmt="Remote signer: {}", _0)]
+	Remote(String),
+	/// The key type is not supported by the keystore.
+	#[display(fmt="Unsupported key type {}", _0)]
+	UnsupportedKeyType(KeyTypeId),
+	/// The requested key is not held by the keystore.
+	#[display(fmt="Key not found")]
+	KeyNotFound,
+	/// The keystore can't generate keys.
+	#[display(fmt="Key generation is not supported by this keystore")]
+	GenerationUnsupported,
 }
 
 /// Keystore Result
@@ -56,22 +91,261 @@ impl std::error::Error for Error {
 	}
 }
 
+/// Something that holds keys and signs with them.
+///
+/// Keys are addressed by their key type and their raw public key. The supported key types are
+/// `key_types::ED25519` and `key_types::SR25519`.
+pub trait Keystore: Send + Sync {
+	/// Generate a new key of the given type and return its raw public key.
+	fn generate_by_type(&self, key_type: KeyTypeId) -> Result<Vec<u8>>;
+
+	/// Get the raw public keys of all keys of the given type.
+	fn public_keys_by_type(&self, key_type: KeyTypeId) -> Result<Vec<Vec<u8>>>;
+
+	/// Check whether the keystore holds the key `public` of the given type.
+	fn has_key(&self, key_type: KeyTypeId, public: &[u8]) -> bool {
+		self.public_keys_by_type(key_type)
+			.map(|keys| keys.iter().any(|key| &key[..] == public))
+			.unwrap_or(false)
+	}
+
+	/// Sign `message` with the key `public` of the given type and return the raw signature.
+	fn sign_by_type(&self, key_type: KeyTypeId, public: &[u8], message: &[u8]) -> Result<Vec<u8>>;
+
+	/// Compute the VRF output and proof of `transcript` with the sr25519 key `public`.
+	fn sr25519_vrf_sign(
+		&self,
+		public: &[u8],
+		transcript: &VrfTranscriptData,
+	) -> Result<sr25519::VrfSignature>;
+}
+
+/// The messages of a VRF transcript.
+///
+/// The transcript is described by its messages rather than passed as `merlin::Transcript`, so
+/// it can be sent to an external signer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VrfTranscriptData {
+	/// The label of the transcript.
+	pub label: &'static [u8],
+	/// The messages appended to the transcript, with their labels.
+	pub items: Vec<(&'static [u8], Vec<u8>)>,
+}
+
+impl VrfTranscriptData {
+	/// Build the transcript.
+	pub fn transcript(&self) -> merlin::Transcript {
+		let mut transcript = merlin::Transcript::new(self.label);
+		for &(label, ref message) in &self.items {
+			transcript.append_message(label, message);
+		}
+		transcript
+	}
+}
+
+impl dyn Keystore {
+	/// Generate a new key of type `TPair`.
+	pub fn generate<TPair: Pair>(&self) -> Result<TPair::Public> {
+		self.generate_by_type(TPair::KEY_TYPE).map(|public| TPair::Public::from_slice(&public))
+	}
+
+	/// Get the public keys of all keys of type `TPublic`.
+	pub fn public_keys<TPublic: Public>(&self) -> Result<Vec<TPublic>> {
+		self.public_keys_by_type(TPublic::KEY_TYPE)
+			.map(|keys| keys.iter().map(|key| TPublic::from_slice(key)).collect())
+	}
+
+	/// Sign `message` with the key `public` of type `TPair`.
+	pub fn sign<TPair: Pair>(&self, public: &TPair::Public, message: &[u8]) -> Result<Vec<u8>> {
+		self.sign_by_type(TPair::KEY_TYPE, public.as_slice(), message)
+	}
+}
+
+/// Check that `public` is a valid public key of the given type.
+///
+/// All supported key types use 32 byte public keys.
+fn check_public(key_type: KeyTypeId, public: &[u8]) -> Result<()> {
+	match key_type {
+		key_types::ED25519 | key_types::SR25519 if public.len() == 32 => Ok(()),
+		key_types::ED25519 | key_types::SR25519 => Err(Error::KeyNotFound),
+		_ => Err(Error::UnsupportedKeyType(key_type)),
+	}
+}
+
+/// Verify the raw `signature` of `message` by the key `public` of the given type.
+fn verify_by_type(key_type: KeyTypeId, signature: &[u8], message: &[u8], public: &[u8]) -> bool {
+	match key_type {
+		key_types::ED25519 => ed25519::Pair::verify_weak(signature, message, public),
+		key_types::SR25519 => sr25519::Pair::verify_weak(signature, message, public),
+		_ => false,
+	}
+}
+
+/// Compute the VRF output and proof of `transcript` with `pair`.
+fn vrf_sign(pair: &sr25519::Pair, transcript: &VrfTranscriptData) -> sr25519::VrfSignature {
+	let keypair: &schnorrkel::Keypair = pair.as_ref();
+	let (inout, proof, _) = keypair.vrf_sign(transcript.transcript());
+	sr25519::VrfSignature {
+		output: H256::from(*inout.to_output().as_bytes()),
+		proof: H512::from(proof.to_bytes()),
+	}
+}
+
+/// Verify the VRF `signature` of `transcript` by the sr25519 key `public`.
+fn vrf_verify(public: &[u8], transcript: &VrfTranscriptData, signature: &sr25519::VrfSignature) -> bool {
+	let verify = || -> std::result::Result<_, schnorrkel::SignatureError> {
+		let public = schnorrkel::PublicKey::from_bytes(public)?;
+		let output = schnorrkel::vrf::VRFOutput::from_bytes(signature.output.as_bytes())?;
+		let proof = schnorrkel::vrf::VRFProof::from_bytes(signature.proof.as_bytes())?;
+		public.vrf_verify(transcript.transcript(), &output, &proof)
+	};
+	verify().is_ok()
+}
+
+/// The version of the encrypted key file format.
+const KEY_FILE_VERSION: u32 = 1;
+
+/// Number of PBKDF2 iterations used to derive the encryption key from the password.
+const PBKDF2_ITERATIONS: u32 = 100_000;
+
+/// Length of the random salt used for the key derivation.
+const SALT_LENGTH: usize = 32;
+
+/// Length of the ChaCha20-Poly1305 key.
+const KEY_LENGTH: usize = 32;
+
+/// A secret encrypted with the keystore password, as it is stored on disk.
+#[derive(Serialize, Deserialize)]
+struct EncryptedSecret {
+	version: u32,
+	iterations: u32,
+	salt: String,
+	nonce: String,
+	ciphertext: String,
+}
+
+/// The content of a key file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeyFile {
+	/// A secret encrypted with the keystore password.
+	Encrypted(EncryptedSecret),
+	/// A plain text secret, as written by older versions of the keystore.
+	Plain(String),
+}
+
+fn derive_key(password: &str, salt: &[u8], iterations: u32) -> Result<[u8; KEY_LENGTH]> {
+	let iterations = NonZeroU32::new(iterations).ok_or(Error::Crypto)?;
+	let mut key = [0u8; KEY_LENGTH];
+	pbkdf2::derive(&digest::SHA256, iterations, salt, password.as_bytes(), &mut key);
+	Ok(key)
+}
+
+impl EncryptedSecret {
+	/// Encrypt `secret` with `password`.
+	fn encrypt(secret: &str, password: &str) -> Result<Self> {
+		let rng = SystemRandom::new();
+		let mut salt = [0u8; SALT_LENGTH];
+		let mut nonce = [0u8; aead::NONCE_LEN];
+		rng.fill(&mut salt).map_err(|_| Error::Crypto)?;
+		rng.fill(&mut nonce).map_err(|_| Error::Crypto)?;
+
+		let key = derive_key(password, &salt, PBKDF2_ITERATIONS)?;
+		let key = aead::SealingKey::new(&aead::CHACHA20_POLY1305, &key).map_err(|_| Error::Crypto)?;
+		let tag_len = aead::CHACHA20_POLY1305.tag_len();
+		let mut in_out = secret.as_bytes().to_vec();
+		in_out.resize(in_out.len() + tag_len, 0);
+		let len = aead::seal_in_place(
+			&key,
+			aead::Nonce::assume_unique_for_key(nonce),
+			aead::Aad::empty(),
+			&mut in_out,
+			tag_len,
+		).map_err(|_| Error::Crypto)?;
+		in_out.truncate(len);
+
+		Ok(EncryptedSecret {
+			version: KEY_FILE_VERSION,
+			iterations: PBKDF2_ITERATIONS,
+			salt: hex::encode(salt),
+			nonce: hex::encode(nonce),
+			ciphertext: hex::encode(in_out),
+		})
+	}
+
+	/// Decrypt the secret with `password`.
+	///
+	/// Returns `Error::InvalidPassword` if the secret was encrypted with another password.
+	fn decrypt(&self, password: &str) -> Result<String> {
+		if self.version != KEY_FILE_VERSION {
+			return Err(Error::UnsupportedVersion(self.version));
+		}
+
+		let salt = hex::decode(&self.salt).map_err(|_| Error::Crypto)?;
+		let nonce = hex::decode(&self.nonce).map_err(|_| Error::Crypto)?;
+		let mut in_out = hex::decode(&self.ciphertext).map_err(|_| Error::Crypto)?;
+		if nonce.len() != aead::NONCE_LEN {
+			return Err(Error::Crypto);
+		}
+		let mut nonce_bytes = [0u8; aead::NONCE_LEN];
+		nonce_bytes.copy_from_slice(&nonce);
+
+		let key = derive_key(password, &salt, self.iterations)?;
+		let key = aead::OpeningKey::new(&aead::CHACHA20_POLY1305, &key).map_err(|_| Error::Crypto)?;
+		let secret = aead::open_in_place(
+			&key,
+			aead::Nonce::assume_unique_for_key(nonce_bytes),
+			aead::Aad::empty(),
+			0,
+			&mut in_out,
+		).map_err(|_| Error::InvalidPassword)?;
+
+		String::from_utf8(secret.to_vec()).map_err(|_| Error::InvalidPhrase)
+	}
+}
+
+/// Write `secret` encrypted with `password` to the key file at `path`.
+///
+/// The secret is written to a temporary file which then replaces the key file, so that a legacy
+/// key file is never left truncated if the node stops while encrypting it.
+fn write_key_file(path: &Path, secret: &str, password: &str) -> Result<()> {
+	let encrypted = EncryptedSecret::encrypt(secret, password)?;
+	let temp_path = path.with_extension("tmp");
+	let result = File::create(&temp_path).map_err(Error::from).and_then(|mut file| {
+		::serde_json::to_writer(&file, &encrypted)?;
+		file.flush()?;
+		file.sync_all()?;
+		fs::rename(&temp_path, path)?;
+		Ok(())
+	});
+	if result.is_err() {
+		let _ = fs::remove_file(&temp_path);
+	}
+	result
+}
+
 /// Key store.
+///
+/// Keys are kept in files below `path`, encrypted with the keystore password. A key is
+/// decrypted on its first load only, and then kept in memory.
 pub struct Store {
 	path: PathBuf,
+	password: Protected<String>,
 	additional: HashMap<(KeyTypeId, Vec<u8>), Vec<u8>>,
+	loaded: RwLock<HashMap<(KeyTypeId, Vec<u8>), Vec<u8>>>,
 }
 
 impl Store {
-	/// Create a new store at the given path.
-	pub fn open(path: PathBuf) -> Result<Self> {
+	/// Create a new store at the given path, using `password` to encrypt the key files.
+	pub fn open(path: PathBuf, password: Protected<String>) -> Result<Self> {
 		fs::create_dir_all(&path)?;
-		Ok(Store { path, additional: HashMap::new() })
+		Ok(Store { path, password, additional: HashMap::new(), loaded: RwLock::new(HashMap::new()) })
 	}
 
 	fn get_pair<TPair: Pair>(&self, public: &TPair::Public) -> Result<Option<TPair>> {
 		let key = (TPair::KEY_TYPE, public.to_raw_vec());
-		if let Some(bytes) = self.additional.get(&key) {
+		let loaded = self.loaded.read().expect("Lock is never poisoned; qed");
+		if let Some(bytes) = self.additional.get(&key).or_else(|| loaded.get(&key)) {
 			let pair = TPair::from_seed_slice(bytes)
 				.map_err(|_| Error::InvalidSeed)?;
 			return Ok(Some(pair));
@@ -85,11 +359,22 @@ impl Store {
 	}
 
 	/// Generate a new key, placing it into the store.
-	pub fn generate<TPair: Pair>(&self, password: &str) -> Result<TPair> {
+	pub fn generate<TPair: Pair>(&self) -> Result<TPair> {
+		let password = self.password.as_ref();
 		let (pair, phrase, _) = TPair::generate_with_phrase(Some(password));
-		let mut file = File::create(self.key_file_path::<TPair>(&pair.public()))?;
-		::serde_json::to_writer(&file, &phrase)?;
-		file.flush()?;
+		write_key_file(&self.key_file_path::<TPair>(&pair.public()), &phrase, password)?;
+		Ok(pair)
+	}
+
+	/// Insert the key given by the secret key URI `suri` into the store.
+	///
+	/// The key is derived with the keystore password overriding any password given in `suri`,
+	/// as this is how it is loaded again by `load`.
+	pub fn insert<TPair: Pair>(&self, suri: &str) -> Result<TPair> {
+		let password = self.password.as_ref();
+		let pair = TPair::from_string(suri, Some(password))
+			.ok().ok_or(Error::InvalidSeed)?;
+		write_key_file(&self.key_file_path::<TPair>(&pair.public()), suri, password)?;
 		Ok(pair)
 	}
 
@@ -102,35 +387,49 @@ impl Store {
 	}
 
 	/// Load a key file with given public key.
-	pub fn load<TPair: Pair>(&self, public: &TPair::Public, password: &str) -> Result<TPair> {
+	pub fn load<TPair: Pair>(&self, public: &TPair::Public) -> Result<TPair> {
 		if let Some(pair) = self.get_pair(public)? {
 			return Ok(pair)
 		}
 
+		let password = self.password.as_ref();
+
 		let path = self.key_file_path::<TPair>(public);
-		let file = File::open(path)?;
+		let file = File::open(&path)?;
 
-		let phrase: String = ::serde_json::from_reader(&file)?;
-		let (pair, _) = TPair::from_phrase(&phrase, Some(password))
+		let (phrase, encrypted) = match ::serde_json::from_reader(&file)? {
+			KeyFile::Encrypted(secret) => (secret.decrypt(password)?, true),
+			KeyFile::Plain(phrase) => (phrase, false),
+		};
+		let pair = TPair::from_string(&phrase, Some(password))
 			.ok().ok_or(Error::InvalidPhrase)?;
 		if &pair.public() != public {
 			return Err(Error::InvalidPassword);
 		}
+
+		if !encrypted {
+			// The password is known to be correct now, so we can encrypt the legacy key file.
+			write_key_file(&path, &phrase, password)?;
+		}
+		self.loaded.write().expect("Lock is never poisoned; qed")
+			.insert((TPair::KEY_TYPE, public.to_raw_vec()), pair.to_raw_vec());
 		Ok(pair)
 	}
 
 	/// Get public keys of all stored keys.
 	pub fn contents<TPublic: Public>(&self) -> Result<Vec<TPublic>> {
-		let mut public_keys: Vec<TPublic> = self.additional.keys()
-			.filter_map(|(ty, public)| {
-				if *ty != TPublic::KEY_TYPE {
-					return None
-				}
-				Some(TPublic::from_slice(public))
-			})
+		self.raw_contents(TPublic::KEY_TYPE)
+			.map(|keys| keys.iter().map(|key| TPublic::from_slice(key)).collect())
+	}
+
+	/// Get the raw public keys of all stored keys of the given type.
+	fn raw_contents(&self, key_type: KeyTypeId) -> Result<Vec<Vec<u8>>> {
+		let mut public_keys: Vec<Vec<u8>> = self.additional.keys()
+			.filter(|(ty, _)| *ty == key_type)
+			.map(|(_, public)| public.clone())
 			.collect();
 
-		let key_type: [u8; 4] = TPublic::KEY_TYPE.to_le_bytes();
+		let key_type: [u8; 4] = key_type.to_le_bytes();
 		for entry in fs::read_dir(&self.path)? {
 			let entry = entry?;
 			let path = entry.path();
@@ -139,9 +438,8 @@ impl Store {
 			if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
 				match hex::decode(name) {
 					Ok(ref hex) => {
-						if hex[0..4] != key_type { continue	}
-						let public = TPublic::from_slice(&hex[4..]);
-						public_keys.push(public);
+						if hex.len() <= 4 || hex[0..4] != key_type { continue	}
+						public_keys.push(hex[4..].to_vec());
 					}
 					_ => continue,
 				}
@@ -161,38 +459,199 @@ impl Store {
 	}
 }
 
+impl Store {
+	/// Load the key `public` of type `TPair`, failing with `KeyNotFound` if there is none.
+	fn load_raw<TPair: Pair>(&self, public: &[u8]) -> Result<TPair> {
+		match self.load::<TPair>(&TPair::Public::from_slice(public)) {
+			Err(Error::Io(ref err)) if err.kind() == io::ErrorKind::NotFound => Err(Error::KeyNotFound),
+			pair => pair,
+		}
+	}
+}
+
+impl Keystore for Store {
+	fn generate_by_type(&self, key_type: KeyTypeId) -> Result<Vec<u8>> {
+		match key_type {
+			key_types::ED25519 => self.generate::<ed25519::Pair>().map(|pair| pair.public().to_raw_vec()),
+			key_types::SR25519 => self.generate::<sr25519::Pair>().map(|pair| pair.public().to_raw_vec()),
+			_ => Err(Error::UnsupportedKeyType(key_type)),
+		}
+	}
+
+	fn public_keys_by_type(&self, key_type: KeyTypeId) -> Result<Vec<Vec<u8>>> {
+		self.raw_contents(key_type)
+	}
+
+	fn sign_by_type(&self, key_type: KeyTypeId, public: &[u8], message: &[u8]) -> Result<Vec<u8>> {
+		check_public(key_type, public)?;
+		match key_type {
+			key_types::ED25519 => self.load_raw::<ed25519::Pair>(public)
+				.map(|pair| pair.sign(message).as_ref().to_vec()),
+			key_types::SR25519 => self.load_raw::<sr25519::Pair>(public)
+				.map(|pair| pair.sign(message).as_ref().to_vec()),
+			_ => Err(Error::UnsupportedKeyType(key_type)),
+		}
+	}
+
+	fn sr25519_vrf_sign(
+		&self,
+		public: &[u8],
+		transcript: &VrfTranscriptData,
+	) -> Result<sr25519::VrfSignature> {
+		check_public(key_types::SR25519, public)?;
+		self.load_raw::<sr25519::Pair>(public).map(|pair| vrf_sign(&pair, transcript))
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
 	use tempdir::TempDir;
-	use primitives::ed25519;
 	use primitives::crypto::Ss58Codec;
 
+	fn open(path: &Path, password: &str) -> Store {
+		Store::open(path.to_owned(), password.to_string().into()).unwrap()
+	}
+
 	#[test]
 	fn basic_store() {
 		let temp_dir = TempDir::new("keystore").unwrap();
-		let store = Store::open(temp_dir.path().to_owned()).unwrap();
+		let store = open(temp_dir.path(), "thepassword");
 
 		assert!(store.contents::<ed25519::Public>().unwrap().is_empty());
 
-		let key: ed25519::Pair = store.generate("thepassword").unwrap();
-		let key2: ed25519::Pair = store.load(&key.public(), "thepassword").unwrap();
+		let key: ed25519::Pair = store.generate().unwrap();
+		let key2: ed25519::Pair = store.load(&key.public()).unwrap();
 
-		assert!(store.load::<ed25519::Pair>(&key.public(), "notthepassword").is_err());
+		let wrong_store = open(temp_dir.path(), "notthepassword");
+		assert!(wrong_store.load::<ed25519::Pair>(&key.public()).is_err());
 
 		assert_eq!(key.public(), key2.public());
 
 		assert_eq!(store.contents::<ed25519::Public>().unwrap()[0], key.public());
 	}
 
+	#[test]
+	fn insert_and_load_derived_key() {
+		let temp_dir = TempDir::new("keystore").unwrap();
+		let store = open(temp_dir.path(), "thepassword");
+
+		let suri = "bottom drive obey lake curtain smoke basket hold race lonely fit walk//Alice";
+		let key: ed25519::Pair = store.insert(suri).unwrap();
+		let loaded: ed25519::Pair = store.load(&key.public()).unwrap();
+
+		assert_eq!(key.public(), loaded.public());
+		let wrong_store = open(temp_dir.path(), "notthepassword");
+		assert!(wrong_store.load::<ed25519::Pair>(&key.public()).is_err());
+		assert_eq!(store.contents::<ed25519::Public>().unwrap(), vec![key.public()]);
+	}
+
+	#[test]
+	fn key_files_are_encrypted() {
+		let temp_dir = TempDir::new("keystore").unwrap();
+		let store = open(temp_dir.path(), "thepassword");
+
+		let suri = "bottom drive obey lake curtain smoke basket hold race lonely fit walk//Alice";
+		let key: ed25519::Pair = store.insert(suri).unwrap();
+
+		let content = fs::read_to_string(store.key_file_path::<ed25519::Pair>(&key.public())).unwrap();
+		assert!(!content.contains("bottom drive obey lake"));
+		match open(temp_dir.path(), "notthepassword").load::<ed25519::Pair>(&key.public()) {
+			Err(Error::InvalidPassword) => {},
+			_ => panic!("Loading with the wrong password must fail"),
+		}
+	}
+
+	#[test]
+	fn plain_key_files_are_encrypted_on_load() {
+		let temp_dir = TempDir::new("keystore").unwrap();
+		let store = open(temp_dir.path(), "thepassword");
+
+		let suri = "bottom drive obey lake curtain smoke basket hold race lonely fit walk//Alice";
+		let key = ed25519::Pair::from_string(suri, Some("thepassword")).unwrap();
+		let path = store.key_file_path::<ed25519::Pair>(&key.public());
+		fs::write(&path, serde_json::to_string(suri).unwrap()).unwrap();
+
+		let loaded: ed25519::Pair = store.load(&key.public()).unwrap();
+		assert_eq!(key.public(), loaded.public());
+		assert!(!fs::read_to_string(&path).unwrap().contains("bottom drive obey lake"));
+		assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+
+		let loaded: ed25519::Pair = store.load(&key.public()).unwrap();
+		assert_eq!(key.public(), loaded.public());
+	}
+
+	#[test]
+	fn loaded_keys_are_decrypted_once() {
+		let temp_dir = TempDir::new("keystore").unwrap();
+		let store = open(temp_dir.path(), "thepassword");
+
+		let key: sr25519::Pair = store.generate().unwrap();
+		let public = key.public();
+		store.sign_by_type(key_types::SR25519, public.as_slice(), b"message").unwrap();
+
+		// the key file isn't read again.
+		fs::remove_file(store.key_file_path::<sr25519::Pair>(&public)).unwrap();
+		let signature = store.sign_by_type(key_types::SR25519, public.as_slice(), b"message").unwrap();
+		assert!(sr25519::Pair::verify(&sr25519::Signature::from_slice(&signature), b"message", &public));
+	}
+
 	#[test]
 	fn test_generate_from_seed() {
 		let temp_dir = TempDir::new("keystore").unwrap();
-		let mut store = Store::open(temp_dir.path().to_owned()).unwrap();
+		let mut store = open(temp_dir.path(), "thepassword");
 
 		let pair: ed25519::Pair = store
 			.generate_from_seed("0x3d97c819d68f9bafa7d6e79cb991eebcd77d966c5334c0b94d9e1fa7ad0869dc")
 			.unwrap();
 		assert_eq!("5DKUrgFqCPV8iAXx9sjy1nyBygQCeiUYRFWurZGhnrn3HJCA", pair.public().to_ss58check());
 	}
+
+	#[test]
+	fn keystore_trait_signs_with_stored_keys() {
+		let temp_dir = TempDir::new("keystore").unwrap();
+		let store: Box<dyn Keystore> = Box::new(open(temp_dir.path(), "thepassword"));
+
+		for &key_type in &[key_types::ED25519, key_types::SR25519] {
+			let public = store.generate_by_type(key_type).unwrap();
+			assert_eq!(store.public_keys_by_type(key_type).unwrap(), vec![public.clone()]);
+			assert!(store.has_key(key_type, &public));
+
+			let signature = store.sign_by_type(key_type, &public, b"hello").unwrap();
+			assert!(verify_by_type(key_type, &signature, b"hello", &public));
+		}
+
+		match store.sign_by_type(key_types::ED25519, &[0; 32], b"hello") {
+			Err(Error::KeyNotFound) => {},
+			_ => panic!("Signing with an unknown key must fail"),
+		}
+		match store.generate_by_type(0) {
+			Err(Error::UnsupportedKeyType(0)) => {},
+			_ => panic!("Unknown key types must be rejected"),
+		}
+	}
+	#[test]
+	fn keystore_trait_computes_vrf_with_stored_keys() {
+		let temp_dir = TempDir::new("keystore").unwrap();
+		let store: Box<dyn Keystore> = Box::new(open(temp_dir.path(), "thepassword"));
+		let public = store.generate_by_type(key_types::SR25519).unwrap();
+		let transcript = VrfTranscriptData {
+			label: b"test",
+			items: vec![(b"slot number", 42u64.to_le_bytes().to_vec())],
+		};
+
+		let signature = store.sr25519_vrf_sign(&public, &transcript).unwrap();
+		assert!(vrf_verify(&public, &transcript, &signature));
+
+		let other_transcript = VrfTranscriptData {
+			label: b"test",
+			items: vec![(b"slot number", 43u64.to_le_bytes().to_vec())],
+		};
+		assert!(!vrf_verify(&public, &other_transcript, &signature));
+
+		match store.sr25519_vrf_sign(&[0; 32], &transcript) {
+			Err(Error::KeyNotFound) => {},
+			_ => panic!("Signing with an unknown key must fail"),
+		}
+	}
 }
@@ -0,0 +1,133 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate. If not, see <http://www.gnu.org/licenses/>.
+
+//! A keystore that keeps its keys in memory only, for tests.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use primitives::crypto::{KeyTypeId, Pair, Public, key_types};
+use primitives::{ed25519, sr25519};
+
+use crate::{Error, Keystore, Result, VrfTranscriptData, check_public, vrf_sign};
+
+/// A keystore that keeps its keys in memory only.
+///
+/// Keys are lost when the store is dropped, so it should only be used in tests.
+#[derive(Default)]
+pub struct MemoryStore {
+	keys: RwLock<HashMap<KeyTypeId, HashMap<Vec<u8>, String>>>,
+}
+
+impl MemoryStore {
+	/// Create an empty store.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Insert the key given by the secret key URI `suri` into the store.
+	pub fn insert<TPair: Pair>(&self, suri: &str) -> Result<TPair> {
+		let pair = TPair::from_string(suri, None).ok().ok_or(Error::InvalidSeed)?;
+		self.keys.write().expect("Lock is never poisoned; qed")
+			.entry(TPair::KEY_TYPE)
+			.or_default()
+			.insert(pair.public().to_raw_vec(), suri.to_string());
+		Ok(pair)
+	}
+
+	fn generate<TPair: Pair>(&self) -> Vec<u8> {
+		let (pair, phrase, _) = TPair::generate_with_phrase(None);
+		let public = pair.public().to_raw_vec();
+		self.keys.write().expect("Lock is never poisoned; qed")
+			.entry(TPair::KEY_TYPE)
+			.or_default()
+			.insert(public.clone(), phrase);
+		public
+	}
+
+	fn pair<TPair: Pair>(&self, public: &[u8]) -> Result<TPair> {
+		let keys = self.keys.read().expect("Lock is never poisoned; qed");
+		let suri = keys.get(&TPair::KEY_TYPE)
+			.and_then(|keys| keys.get(public))
+			.ok_or(Error::KeyNotFound)?;
+		TPair::from_string(suri, None).ok().ok_or(Error::InvalidSeed)
+	}
+
+	fn sign<TPair: Pair>(&self, public: &[u8], message: &[u8]) -> Result<Vec<u8>> {
+		self.pair::<TPair>(public).map(|pair| pair.sign(message).as_ref().to_vec())
+	}
+}
+
+impl Keystore for MemoryStore {
+	fn generate_by_type(&self, key_type: KeyTypeId) -> Result<Vec<u8>> {
+		match key_type {
+			key_types::ED25519 => Ok(self.generate::<ed25519::Pair>()),
+			key_types::SR25519 => Ok(self.generate::<sr25519::Pair>()),
+			_ => Err(Error::UnsupportedKeyType(key_type)),
+		}
+	}
+
+	fn public_keys_by_type(&self, key_type: KeyTypeId) -> Result<Vec<Vec<u8>>> {
+		let keys = self.keys.read().expect("Lock is never poisoned; qed");
+		Ok(keys.get(&key_type).map(|keys| keys.keys().cloned().collect()).unwrap_or_default())
+	}
+
+	fn sign_by_type(&self, key_type: KeyTypeId, public: &[u8], message: &[u8]) -> Result<Vec<u8>> {
+		check_public(key_type, public)?;
+		match key_type {
+			key_types::ED25519 => self.sign::<ed25519::Pair>(public, message),
+			key_types::SR25519 => self.sign::<sr25519::Pair>(public, message),
+			_ => Err(Error::UnsupportedKeyType(key_type)),
+		}
+	}
+
+	fn sr25519_vrf_sign(
+		&self,
+		public: &[u8],
+		transcript: &VrfTranscriptData,
+	) -> Result<sr25519::VrfSignature> {
+		check_public(key_types::SR25519, public)?;
+		self.pair::<sr25519::Pair>(public).map(|pair| vrf_sign(&pair, transcript))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::verify_by_type;
+
+	#[test]
+	fn inserted_keys_can_sign() {
+		let store = MemoryStore::new();
+		let pair: sr25519::Pair = store.insert("//Alice").unwrap();
+		let public = pair.public().to_raw_vec();
+
+		assert!(store.has_key(key_types::SR25519, &public));
+		assert!(!store.has_key(key_types::ED25519, &public));
+
+		let signature = store.sign_by_type(key_types::SR25519, &public, b"hello").unwrap();
+		assert!(verify_by_type(key_types::SR25519, &signature, b"hello", &public));
+	}
+
+	#[test]
+	fn generated_keys_are_listed() {
+		let store = MemoryStore::new();
+		let public = store.generate_by_type(key_types::ED25519).unwrap();
+
+		assert_eq!(store.public_keys_by_type(key_types::ED25519).unwrap(), vec![public]);
+		assert!(store.public_keys_by_type(key_types::SR25519).unwrap().is_empty());
+	}
+}
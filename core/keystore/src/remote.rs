@@ -0,0 +1,293 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate. If not, see <http://www.gnu.org/licenses/>.
+
+//! Client for an external signer that holds the keys of the node.
+//!
+//! The signer is reached through a local socket, given as `unix:///path/to/socket` or
+//! `tcp://127.0.0.1:port`. Every request is a single line of JSON-RPC 2.0 and is answered by a
+//! single line. The signer has to support the following methods:
+//!
+//! - `signer_publicKeys(key_type: u32) -> [hex]` - The public keys of the given key type.
+//! - `signer_sign(key_type: u32, public: hex, message: hex) -> hex` - Sign `message` with the
+//!   key `public`.
+//! - `signer_vrfSign(public: hex, label: hex, items: [[hex, hex]]) -> hex` - Compute the VRF of
+//!   the merlin transcript `label` with the `[label, message]` items appended, with the sr25519
+//!   key `public`. The result is the VRF output followed by the proof.
+//!
+//! Hex values are `0x` prefixed. Signatures are checked before they are handed out, so a
+//! misbehaving signer can't make the node gossip invalid signatures.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use primitives::crypto::{KeyTypeId, Pair, Public, key_types};
+use primitives::{sr25519, H256, H512};
+use serde_json::{json, Value};
+
+use crate::{Error, Keystore, Result, VrfTranscriptData, check_public, verify_by_type, vrf_verify};
+
+/// How long we wait for the signer to answer a request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The address of the external signer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Endpoint {
+	#[cfg(unix)]
+	Unix(String),
+	Tcp(String),
+}
+
+/// A connection to an external signer.
+#[derive(Debug)]
+pub struct RemoteSigner {
+	endpoint: Endpoint,
+	next_id: AtomicU64,
+}
+
+impl RemoteSigner {
+	/// Create a client for the signer at `uri`.
+	///
+	/// No connection is made until the first request, so the signer may be (re)started
+	/// independently of the node.
+	pub fn new(uri: &str) -> Result<Self> {
+		let endpoint = if uri.starts_with("unix://") {
+			#[cfg(unix)]
+			{ Endpoint::Unix(uri["unix://".len()..].to_string()) }
+			#[cfg(not(unix))]
+			{ return Err(Error::Remote("Unix sockets are not supported on this platform".into())) }
+		} else if uri.starts_with("tcp://") {
+			Endpoint::Tcp(uri["tcp://".len()..].to_string())
+		} else {
+			return Err(Error::Remote(format!("Unsupported signer URI: {}", uri)));
+		};
+
+		Ok(RemoteSigner { endpoint, next_id: AtomicU64::new(0) })
+	}
+
+	/// Get the public keys of all keys of type `TPublic` held by the signer.
+	pub fn contents<TPublic: Public>(&self) -> Result<Vec<TPublic>> {
+		self.public_keys_by_type(TPublic::KEY_TYPE)
+			.map(|keys| keys.iter().map(|key| TPublic::from_slice(key)).collect())
+	}
+
+	/// Sign `message` with the key `public` of type `TPair`.
+	///
+	/// Returns the raw signature, after checking that it is valid.
+	pub fn sign<TPair: Pair>(&self, public: &TPair::Public, message: &[u8]) -> Result<Vec<u8>> {
+		self.sign_by_type(TPair::KEY_TYPE, public.as_slice(), message)
+	}
+
+	fn request(&self, method: &str, params: Value) -> Result<Value> {
+		let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+		let mut request = json!({
+			"jsonrpc": "2.0",
+			"id": id,
+			"method": method,
+			"params": params,
+		}).to_string();
+		request.push('\n');
+
+		let response = match self.endpoint {
+			#[cfg(unix)]
+			Endpoint::Unix(ref path) => {
+				let stream = UnixStream::connect(path)?;
+				stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+				exchange(stream, &request)?
+			},
+			Endpoint::Tcp(ref address) => {
+				let stream = TcpStream::connect(address)?;
+				stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+				exchange(stream, &request)?
+			},
+		};
+
+		let mut response: Value = serde_json::from_str(&response)?;
+		if response["id"] != json!(id) {
+			return Err(Error::Remote("Response id does not match the request".into()));
+		}
+		if let Some(error) = response.get("error") {
+			return Err(Error::Remote(format!("Signer returned an error: {}", error)));
+		}
+		match response.get_mut("result") {
+			Some(result) => Ok(result.take()),
+			None => Err(Error::Remote("Response contains no result".into())),
+		}
+	}
+}
+
+impl Keystore for RemoteSigner {
+	fn generate_by_type(&self, _key_type: KeyTypeId) -> Result<Vec<u8>> {
+		Err(Error::GenerationUnsupported)
+	}
+
+	fn public_keys_by_type(&self, key_type: KeyTypeId) -> Result<Vec<Vec<u8>>> {
+		let keys = self.request("signer_publicKeys", json!([key_type]))?;
+		keys.as_array()
+			.ok_or_else(|| Error::Remote("Expected a list of public keys".into()))?
+			.iter()
+			.map(|key| {
+				let key = decode_hex(key)?;
+				check_public(key_type, &key)
+					.map_err(|_| Error::Remote("Public key has an invalid length".into()))?;
+				Ok(key)
+			})
+			.collect()
+	}
+
+	fn sign_by_type(&self, key_type: KeyTypeId, public: &[u8], message: &[u8]) -> Result<Vec<u8>> {
+		check_public(key_type, public)?;
+		let signature = self.request(
+			"signer_sign",
+			json!([key_type, encode_hex(public), encode_hex(message)]),
+		)?;
+		let signature = decode_hex(&signature)?;
+
+		if !verify_by_type(key_type, &signature, message, public) {
+			return Err(Error::Remote("Signer returned an invalid signature".into()));
+		}
+		Ok(signature)
+	}
+	fn sr25519_vrf_sign(
+		&self,
+		public: &[u8],
+		transcript: &VrfTranscriptData,
+	) -> Result<sr25519::VrfSignature> {
+		check_public(key_types::SR25519, public)?;
+		let items: Vec<_> = transcript.items.iter()
+			.map(|(label, message)| json!([encode_hex(label), encode_hex(message)]))
+			.collect();
+		let signature = self.request(
+			"signer_vrfSign",
+			json!([encode_hex(public), encode_hex(transcript.label), items]),
+		)?;
+		let signature = decode_hex(&signature)?;
+		if signature.len() != sr25519::VRF_OUTPUT_LENGTH + sr25519::VRF_PROOF_LENGTH {
+			return Err(Error::Remote("VRF signature has an invalid length".into()));
+		}
+		let (output, proof) = signature.split_at(sr25519::VRF_OUTPUT_LENGTH);
+		let signature = sr25519::VrfSignature {
+			output: H256::from_slice(output),
+			proof: H512::from_slice(proof),
+		};
+
+		if !vrf_verify(public, transcript, &signature) {
+			return Err(Error::Remote("Signer returned an invalid VRF signature".into()));
+		}
+		Ok(signature)
+	}
+}
+
+/// Write `request` to `stream` and read a single line as response.
+fn exchange<S: std::io::Read + Write>(mut stream: S, request: &str) -> Result<String> {
+	stream.write_all(request.as_bytes())?;
+	stream.flush()?;
+
+	let mut response = String::new();
+	BufReader::new(stream).read_line(&mut response)?;
+	Ok(response)
+}
+
+fn encode_hex(data: &[u8]) -> String {
+	format!("0x{}", hex::encode(data))
+}
+
+fn decode_hex(value: &Value) -> Result<Vec<u8>> {
+	let value = value.as_str().ok_or_else(|| Error::Remote("Expected a hex string".into()))?;
+	let value = if value.starts_with("0x") { &value[2..] } else { value };
+	hex::decode(value).map_err(|_| Error::Remote("Invalid hex string".into()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::net::TcpListener;
+	use std::thread;
+	use primitives::ed25519;
+
+	/// Serve `requests` requests of a signer holding `pair`.
+	fn serve(listener: TcpListener, pair: ed25519::Pair, requests: usize) {
+		for stream in listener.incoming().take(requests) {
+			let mut stream = stream.unwrap();
+			let mut request = String::new();
+			BufReader::new(&stream).read_line(&mut request).unwrap();
+			let request: Value = serde_json::from_str(&request).unwrap();
+
+			let result = match request["method"].as_str().unwrap() {
+				"signer_publicKeys" => json!([encode_hex(pair.public().as_slice())]),
+				"signer_sign" => {
+					let message = decode_hex(&request["params"][2]).unwrap();
+					json!(encode_hex(pair.sign(&message).as_ref()))
+				},
+				_ => unreachable!(),
+			};
+			let response = json!({ "jsonrpc": "2.0", "id": request["id"], "result": result });
+			writeln!(stream, "{}", response).unwrap();
+		}
+	}
+
+	#[test]
+	fn remote_signing_works() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let uri = format!("tcp://{}", listener.local_addr().unwrap());
+		let pair = ed25519::Pair::from_seed(&[1; 32]);
+		let public = pair.public();
+		let server = thread::spawn(move || serve(listener, pair, 2));
+
+		let signer = RemoteSigner::new(&uri).unwrap();
+		assert_eq!(signer.contents::<ed25519::Public>().unwrap(), vec![public.clone()]);
+
+		let signature = signer.sign::<ed25519::Pair>(&public, b"hello").unwrap();
+		assert!(ed25519::Pair::verify_weak(&signature, b"hello", &public));
+
+		server.join().unwrap();
+	}
+
+	#[test]
+	fn remote_vrf_signatures_are_checked() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let uri = format!("tcp://{}", listener.local_addr().unwrap());
+		let public = sr25519::Pair::from_seed(&[1; 32]).public();
+		let transcript = VrfTranscriptData { label: b"test", items: vec![(b"slot", vec![1])] };
+		let server = thread::spawn(move || {
+			let mut stream = listener.incoming().next().unwrap().unwrap();
+			let mut request = String::new();
+			BufReader::new(&stream).read_line(&mut request).unwrap();
+			let request: Value = serde_json::from_str(&request).unwrap();
+			assert_eq!(request["method"], "signer_vrfSign");
+
+			let result = encode_hex(&[0; sr25519::VRF_OUTPUT_LENGTH + sr25519::VRF_PROOF_LENGTH]);
+			let response = json!({ "jsonrpc": "2.0", "id": request["id"], "result": result });
+			writeln!(stream, "{}", response).unwrap();
+		});
+
+		let signer = RemoteSigner::new(&uri).unwrap();
+		match signer.sr25519_vrf_sign(public.as_slice(), &transcript) {
+			Err(Error::Remote(_)) => {},
+			_ => panic!("Invalid VRF signatures must be rejected"),
+		}
+
+		server.join().unwrap();
+	}
+
+	#[test]
+	fn unsupported_uri_is_rejected() {
+		assert!(RemoteSigner::new("http://localhost:8000").is_err());
+	}
+}
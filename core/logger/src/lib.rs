@@ -0,0 +1,286 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Logger of Substrate nodes.
+//!
+//! Log lines are written to stderr, either in a human readable form or as one JSON object per
+//! line. The log filter uses the `RUST_LOG` syntax (`target=level,...`) and can be changed at
+//! runtime with [`add_filter`] and [`reset_filter`].
+//!
+//! JSON log lines have the fields `timestamp`, `level`, `target`, `thread` and `message`. If the
+//! message mentions a block hash (`0x` followed by 64 hex digits) or a peer id, the first of each
+//! is also given as `block_hash` or `peer_id`.
+
+#![warn(missing_docs)]
+
+use std::io::Write;
+
+use ansi_term::Colour;
+use env_logger::filter::{Builder as FilterBuilder, Filter};
+use lazy_static::lazy_static;
+use log::{Log, Metadata, Record, LevelFilter};
+use parking_lot::RwLock;
+use regex::Regex;
+use serde_json::{json, Value};
+
+/// Filter directives that are applied before any user given directives.
+const DEFAULT_DIRECTIVES: &str = "ws=off,hyper=warn,info";
+
+lazy_static! {
+	static ref FILTER: RwLock<FilterState> = RwLock::new(FilterState::new(String::new()));
+}
+
+/// How log lines are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+	/// Human readable, coloured if stderr is a terminal.
+	Human,
+	/// One JSON object per line.
+	Json,
+}
+
+/// The current log filter.
+struct FilterState {
+	/// The directives given at startup.
+	base: String,
+	/// The directives added at runtime.
+	added: Vec<String>,
+	filter: Filter,
+}
+
+impl FilterState {
+	fn new(base: String) -> Self {
+		let mut state = FilterState { base, added: Vec::new(), filter: FilterBuilder::new().build() };
+		state.rebuild();
+		state
+	}
+
+	fn rebuild(&mut self) {
+		let mut builder = FilterBuilder::new();
+		builder.parse(DEFAULT_DIRECTIVES);
+		builder.parse(&self.base);
+		for directives in &self.added {
+			builder.parse(directives);
+		}
+		self.filter = builder.build();
+		log::set_max_level(self.filter.filter());
+	}
+}
+
+/// Initialize the global logger.
+///
+/// `pattern` is applied after the `RUST_LOG` environment variable, so it takes precedence.
+pub fn init(pattern: &str, format: LogFormat) {
+	let mut base = std::env::var("RUST_LOG").unwrap_or_default();
+	if !pattern.is_empty() {
+		base = format!("{},{}", base, pattern);
+	}
+	*FILTER.write() = FilterState::new(base);
+
+	let isatty = atty::is(atty::Stream::Stderr);
+	let logger = Logger { format, isatty };
+	if log::set_boxed_logger(Box::new(logger)).is_ok() {
+		log::set_max_level(FILTER.read().filter.filter());
+	}
+}
+
+/// Add filter directives, e.g. `sync=trace,afg=debug`.
+///
+/// Added directives take precedence over the directives given at startup and over directives
+/// added earlier.
+pub fn add_filter(directives: &str) -> Result<(), String> {
+	validate_directives(directives)?;
+	let mut state = FILTER.write();
+	state.added.push(directives.to_string());
+	state.rebuild();
+	Ok(())
+}
+
+/// Remove all directives added with [`add_filter`].
+pub fn reset_filter() {
+	let mut state = FILTER.write();
+	state.added.clear();
+	state.rebuild();
+}
+
+/// Check that all levels in `directives` are valid, as `env_logger` silently ignores them.
+fn validate_directives(directives: &str) -> Result<(), String> {
+	let directives = directives.split('/').next().unwrap_or("");
+	for directive in directives.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+		let mut parts = directive.splitn(2, '=');
+		let first = parts.next().unwrap_or("");
+		match parts.next() {
+			Some(_) if first.is_empty() => {
+				return Err(format!("Missing log target in `{}`", directive));
+			},
+			Some(level) => if level.parse::<LevelFilter>().is_err() {
+				return Err(format!("Invalid log level `{}` in `{}`", level, directive));
+			},
+			// A lone word is either a level or a target that is logged at all levels.
+			None => if first.is_empty() {
+				return Err(format!("Invalid log directive `{}`", directive));
+			},
+		}
+	}
+	Ok(())
+}
+
+struct Logger {
+	format: LogFormat,
+	isatty: bool,
+}
+
+impl Log for Logger {
+	fn enabled(&self, metadata: &Metadata) -> bool {
+		FILTER.read().filter.enabled(metadata)
+	}
+
+	fn log(&self, record: &Record) {
+		if !FILTER.read().filter.matches(record) {
+			return
+		}
+
+		let output = match self.format {
+			LogFormat::Human => format_human(record, self.isatty),
+			LogFormat::Json => format_json(record).to_string(),
+		};
+
+		if !self.isatty && record.level() <= log::Level::Info && atty::is(atty::Stream::Stdout) {
+			// duplicate INFO/WARN output to console
+			println!("{}", output);
+		}
+		let stderr = std::io::stderr();
+		let _ = writeln!(stderr.lock(), "{}", output);
+	}
+
+	fn flush(&self) {
+		let _ = std::io::stderr().flush();
+	}
+}
+
+fn format_human(record: &Record, enable_color: bool) -> String {
+	let now = time::now();
+	let timestamp =
+		time::strftime("%Y-%m-%d %H:%M:%S", &now)
+			.expect("Error formatting log timestamp");
+
+	let output = if log::max_level() <= LevelFilter::Info {
+		format!("{} {}", Colour::Black.bold().paint(timestamp), record.args())
+	} else {
+		let name = ::std::thread::current()
+			.name()
+			.map_or_else(Default::default, |x| format!("{}", Colour::Blue.bold().paint(x)));
+		let millis = (now.tm_nsec as f32 / 1000000.0).round() as usize;
+		let timestamp = format!("{}.{:03}", timestamp, millis);
+		format!(
+			"{} {} {} {}  {}",
+			Colour::Black.bold().paint(timestamp),
+			name,
+			record.level(),
+			record.target(),
+			record.args()
+		)
+	};
+
+	if enable_color {
+		output
+	} else {
+		kill_color(output.as_ref())
+	}
+}
+
+fn format_json(record: &Record) -> Value {
+	lazy_static! {
+		static ref BLOCK_HASH: Regex = Regex::new(r"\b0x[0-9a-fA-F]{64}\b")
+			.expect("Error initializing block hash regex");
+		static ref PEER_ID: Regex = Regex::new(r"\bQm[1-9A-HJ-NP-Za-km-z]{44}\b")
+			.expect("Error initializing peer id regex");
+	}
+
+	let now = time::now_utc();
+	let timestamp = format!(
+		"{}.{:03}Z",
+		time::strftime("%Y-%m-%dT%H:%M:%S", &now).expect("Error formatting log timestamp"),
+		now.tm_nsec / 1_000_000,
+	);
+	let message = kill_color(&record.args().to_string());
+
+	let mut line = json!({
+		"timestamp": timestamp,
+		"level": record.level().to_string(),
+		"target": record.target(),
+		"thread": std::thread::current().name(),
+		"message": message,
+	});
+	if let Some(hash) = BLOCK_HASH.find(&message) {
+		line["block_hash"] = hash.as_str().into();
+	}
+	if let Some(peer) = PEER_ID.find(&message) {
+		line["peer_id"] = peer.as_str().into();
+	}
+	line
+}
+
+fn kill_color(s: &str) -> String {
+	lazy_static! {
+		static ref RE: Regex = Regex::new("\x1b\\[[^m]+m").expect("Error initializing color regex");
+	}
+	RE.replace_all(s, "").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn invalid_directives_are_rejected() {
+		assert!(validate_directives("sync=trace,afg=debug").is_ok());
+		assert!(validate_directives("trace").is_ok());
+		assert!(validate_directives("sync=loud").is_err());
+		assert!(validate_directives("=debug").is_err());
+	}
+
+	#[test]
+	fn added_filters_take_precedence() {
+		let mut state = FilterState::new("sync=warn".into());
+		let metadata = |level| Metadata::builder().target("sync").level(level).build();
+		assert!(!state.filter.enabled(&metadata(log::Level::Debug)));
+
+		state.added.push("sync=debug".into());
+		state.rebuild();
+		assert!(state.filter.enabled(&metadata(log::Level::Debug)));
+	}
+
+	#[test]
+	fn json_lines_contain_block_hash_and_peer_id() {
+		let hash = format!("0x{}", "ab".repeat(32));
+		let peer = "QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV";
+		let message = format!("Imported {} from {}", hash, peer);
+		let line = format_json(
+			&Record::builder()
+				.args(format_args!("{}", message))
+				.level(log::Level::Info)
+				.target("sync")
+				.build()
+		);
+
+		assert_eq!(line["level"], "INFO");
+		assert_eq!(line["target"], "sync");
+		assert_eq!(line["message"], message.as_str());
+		assert_eq!(line["block_hash"], hash.as_str());
+		assert_eq!(line["peer_id"], peer);
+	}
+}
@@ -199,6 +199,7 @@ where TSubstream: AsyncRead + AsyncWrite, B: BlockT {
 		// Note that `inner` is wrapped in a `Fuse`, therefore we can poll it forever.
 		match self.inner.poll()? {
 			Async::Ready(Some(data)) => {
+				// Trailing bytes are ignored, as newer versions of the protocol may append fields.
 				let message = <Message<B> as Decode>::decode(&mut &data[..])
 					.map_err(|err| {
 						warn!(
@@ -48,8 +48,10 @@ use crate::chain::{Client, FinalityProofProvider};
 use client::light::fetcher::{FetchChecker, ChangesProof};
 use crate::error;
 use util::LruHashSet;
+use submissions::{Submissions, Verdict};
 
 mod util;
+mod submissions;
 pub mod consensus_gossip;
 pub mod message;
 pub mod event;
@@ -64,7 +66,7 @@ const TICK_TIMEOUT: time::Duration = time::Duration::from_millis(1100);
 const PROPAGATE_TIMEOUT: time::Duration = time::Duration::from_millis(2900);
 
 /// Current protocol version.
-pub(crate) const CURRENT_VERSION: u32 = 3;
+pub(crate) const CURRENT_VERSION: u32 = 4;
 /// Lowest version we support
 pub(crate) const MIN_VERSION: u32 = 2;
 
@@ -112,6 +114,8 @@ pub struct Protocol<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> {
 	finality_proof_provider: Option<Arc<dyn FinalityProofProvider<B>>>,
 	/// Handles opening the unique substream and sending and receiving raw messages.
 	behaviour: CustomProto<B, Substream<StreamMuxerBox>>,
+	/// Extrinsics submitted to full peers, waiting for their confirmation.
+	submissions: Submissions<H>,
 }
 
 /// A peer that we are connected to
@@ -383,6 +387,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 			finality_proof_provider,
 			peerset_handle: peerset_handle.clone(),
 			behaviour,
+			submissions: Submissions::new(),
 		};
 
 		Ok((protocol, peerset_handle))
@@ -542,7 +547,12 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 				self.on_finality_proof_request(who, request),
 			GenericMessage::FinalityProofResponse(response) =>
 				return self.on_finality_proof_response(who, response),
-			GenericMessage::RemoteReadChildRequest(_) => {}
+			GenericMessage::RemoteReadChildRequest(request) =>
+				self.on_remote_read_child_request(who, request),
+			GenericMessage::TransactionSubmission(request) =>
+				self.on_transaction_submission(who, request),
+			GenericMessage::TransactionSubmissionResponse(response) =>
+				self.on_transaction_submission_response(who, response),
 			GenericMessage::Consensus(msg) => {
 				if self.context_data.peers.get(&who).map_or(false, |peer| peer.info.protocol_version > 2) {
 					self.consensus_gossip.on_incoming(
@@ -622,6 +632,9 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 			self.handshaking_peers.remove(&peer);
 			self.context_data.peers.remove(&peer)
 		};
+		for (hash, reason) in self.submissions.on_disconnect(&peer) {
+			self.on_extrinsic_rejected(hash, reason);
+		}
 		if let Some(peer_data) = removed {
 			let mut context = ProtocolContext::new(&mut self.context_data, &mut self.behaviour, &self.peerset_handle);
 			if peer_data.info.protocol_version > 2 {
@@ -823,6 +836,9 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		self.specialization.maintain_peers(
 			&mut ProtocolContext::new(&mut self.context_data, &mut self.behaviour, &self.peerset_handle)
 		);
+		for (hash, reason) in self.submissions.expire(tick) {
+			self.on_extrinsic_rejected(hash, reason);
+		}
 		for p in aborting {
 			self.behaviour.disconnect_peer(&p);
 			self.peerset_handle.report_peer(p, TIMEOUT_REPUTATION_CHANGE);
@@ -944,6 +960,12 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 			trace!(target: "sync", "{} Ignoring extrinsics while syncing", who);
 			return;
 		}
+
+		// light nodes would have to validate every extrinsic with a remote call
+		if self.config.roles.is_light() {
+			trace!(target: "sync", "{} Ignoring extrinsics on light node", who);
+			return;
+		}
 		trace!(target: "sync", "Received {} extrinsics from {}", extrinsics.len(), who);
 		if let Some(ref mut peer) = self.context_data.peers.get_mut(&who) {
 			for t in extrinsics {
@@ -957,6 +979,69 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		}
 	}
 
+	/// Called when a light client submits an extrinsic and waits for a confirmation.
+	fn on_transaction_submission(
+		&mut self,
+		who: PeerId,
+		request: message::TransactionSubmission<B::Extrinsic>,
+	) {
+		trace!(target: "sync", "Transaction submission {} from {}", request.id, who);
+		let result = import_submitted_extrinsic(
+			&*self.transaction_pool,
+			self.config.roles,
+			self.sync.status().state,
+			&request.extrinsic,
+		);
+		let result = match result {
+			Ok(hash) => {
+				self.peerset_handle.report_peer(who.clone(), NEW_EXTRINSIC_REPUTATION_CHANGE);
+				if let Some(ref mut peer) = self.context_data.peers.get_mut(&who) {
+					peer.known_extrinsics.insert(hash);
+				}
+				Ok(())
+			},
+			Err(reason) => {
+				trace!(target: "sync", "Transaction submission {} from {} rejected: {}", request.id, who, reason);
+				Err(reason)
+			},
+		};
+
+		self.send_message(
+			who,
+			GenericMessage::TransactionSubmissionResponse(message::TransactionSubmissionResponse {
+				id: request.id,
+				result,
+			}),
+		);
+	}
+
+	/// Called when a full node confirms an extrinsic we submitted.
+	fn on_transaction_submission_response(
+		&mut self,
+		who: PeerId,
+		response: message::TransactionSubmissionResponse,
+	) {
+		match self.submissions.on_answer(&who, response.id, response.result) {
+			Verdict::Unexpected =>
+				trace!(target: "sync", "Unexpected transaction submission response {} from {}", response.id, who),
+			Verdict::Pending =>
+				trace!(target: "sync", "Transaction submission {} rejected by {}", response.id, who),
+			Verdict::Accepted(hash) => {
+				debug!(target: "sync", "Transaction {:?} accepted by {}", hash, who);
+				let mut propagations = HashMap::new();
+				propagations.insert(hash, vec![who.to_base58()]);
+				self.transaction_pool.on_broadcasted(propagations);
+			},
+			Verdict::Rejected(hash, reason) => self.on_extrinsic_rejected(hash, reason),
+		}
+	}
+
+	/// Called when every full peer rejected an extrinsic we submitted.
+	fn on_extrinsic_rejected(&mut self, hash: H, reason: String) {
+		debug!(target: "sync", "Transaction {:?} rejected by all peers: {}", hash, reason);
+		self.transaction_pool.on_rejected(&hash, reason);
+	}
+
 	/// Call when we must propagate ready extrinsics to peers.
 	pub fn propagate_extrinsics(
 		&mut self,
@@ -969,8 +1054,42 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		}
 
 		let extrinsics = self.transaction_pool.transactions();
+		let is_light = self.config.roles.is_light();
+		let now = time::Instant::now();
 		let mut propagated_to = HashMap::new();
 		for (who, peer) in self.context_data.peers.iter_mut() {
+			// light peers don't relay extrinsics, they only submit their own
+			if peer.info.roles.is_light() {
+				continue;
+			}
+
+			// light nodes ask the peers that support it to confirm each extrinsic, and report it
+			// as broadcast once confirmed
+			if is_light && peer.info.protocol_version > 3 {
+				for (hash, extrinsic) in &extrinsics {
+					if !self.submissions.has_capacity(who) {
+						trace!(target: "sync", "Too many transactions waiting for a confirmation from {}", who);
+						break;
+					}
+					if !peer.known_extrinsics.insert(hash.clone()) {
+						continue;
+					}
+
+					trace!(target: "sync", "Submitting transaction {:?} to {}", hash, who);
+					let id = peer.next_request_id;
+					peer.next_request_id += 1;
+					self.submissions.submit(who.clone(), id, hash.clone(), now);
+					self.behaviour.send_packet(
+						who,
+						GenericMessage::TransactionSubmission(message::TransactionSubmission {
+							id,
+							extrinsic: extrinsic.clone(),
+						}),
+					);
+				}
+				continue;
+			}
+
 			let (hashes, to_send): (Vec<_>, Vec<_>) = extrinsics
 				.iter()
 				.filter(|&(ref hash, _)| peer.known_extrinsics.insert(hash.clone()))
@@ -978,12 +1097,13 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 				.unzip();
 
 			if !to_send.is_empty() {
-				for hash in hashes {
+				for hash in &hashes {
 					propagated_to
-						.entry(hash)
+						.entry(hash.clone())
 						.or_insert_with(Vec::new)
 						.push(who.to_base58());
 				}
+
 				trace!(target: "sync", "Sending {} transactions to {}", to_send.len(), who);
 				self.behaviour.send_packet(who, GenericMessage::Transactions(to_send))
 			}
@@ -1288,6 +1408,40 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		);
 	}
 
+	fn on_remote_read_child_request(
+		&mut self,
+		who: PeerId,
+		request: message::RemoteReadChildRequest<B::Hash>,
+	) {
+		trace!(target: "sync", "Remote read child request {} from {} ({} {} at {})",
+			request.id, who, request.storage_key.to_hex::<String>(), request.key.to_hex::<String>(), request.block);
+		let proof = match self.context_data.chain.read_child_proof(
+			&request.block,
+			&request.storage_key,
+			&request.key,
+		) {
+			Ok(proof) => proof,
+			Err(error) => {
+				trace!(target: "sync", "Remote read child request {} from {} ({} {} at {}) failed with: {}",
+					request.id,
+					who,
+					request.storage_key.to_hex::<String>(),
+					request.key.to_hex::<String>(),
+					request.block,
+					error
+				);
+				Default::default()
+			}
+		};
+		self.send_message(
+			who,
+			GenericMessage::RemoteReadResponse(message::RemoteReadResponse {
+				id: request.id,
+				proof,
+			}),
+		);
+	}
+
 	fn on_remote_read_response(
 		&mut self,
 		who: PeerId,
@@ -1308,7 +1462,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		trace!(target: "sync", "Remote header proof request {} from {} ({})",
 			request.id, who, request.block);
 		let (header, proof) = match self.context_data.chain.header_proof(request.block) {
-			Ok((header, proof)) => (Some(header), proof),
+			Ok((header, proof)) => (header, proof),
 			Err(error) => {
 				trace!(target: "sync", "Remote header proof request {} from {} ({}) failed with: {}",
 					request.id,
@@ -1478,6 +1632,22 @@ pub enum CustomMessageOutcome<B: BlockT> {
 	None,
 }
 
+/// Import an extrinsic that a light client submitted, or give the reason why it isn't imported.
+fn import_submitted_extrinsic<B: BlockT, H: ExHashT>(
+	transaction_pool: &dyn TransactionPool<H, B>,
+	roles: Roles,
+	sync_state: SyncState,
+	extrinsic: &B::Extrinsic,
+) -> Result<H, String> {
+	if roles.is_light() {
+		return Err("Light nodes don't import submitted extrinsics".into());
+	}
+	if sync_state != SyncState::Idle {
+		return Err("The node is syncing".into());
+	}
+	transaction_pool.submit(extrinsic)
+}
+
 fn send_message<B: BlockT, H: ExHashT>(
 	behaviour: &mut CustomProto<B, Substream<StreamMuxerBox>>,
 	peers: &mut HashMap<PeerId, Peer<B, H>>,
@@ -1634,3 +1804,59 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> DiscoveryNetBehaviour f
 		self.behaviour.add_discovered_nodes(peer_ids)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use test_client::runtime::{Block, Extrinsic, Hash};
+
+	/// Accepts the extrinsics that include some data.
+	struct IncludeDataPool;
+
+	impl TransactionPool<Hash, Block> for IncludeDataPool {
+		fn transactions(&self) -> Vec<(Hash, Extrinsic)> {
+			Vec::new()
+		}
+
+		fn import(&self, transaction: &Extrinsic) -> Option<Hash> {
+			match transaction {
+				Extrinsic::IncludeData(data) if !data.is_empty() => Some(Hash::repeat_byte(data[0])),
+				_ => None,
+			}
+		}
+
+		fn on_broadcasted(&self, _: HashMap<Hash, Vec<String>>) {}
+	}
+
+	#[test]
+	fn submitted_extrinsics_are_confirmed_or_rejected_with_a_reason() {
+		let accepted = Extrinsic::IncludeData(vec![1]);
+		let rejected = Extrinsic::IncludeData(Vec::new());
+
+		assert_eq!(
+			import_submitted_extrinsic(&IncludeDataPool, Roles::FULL, SyncState::Idle, &accepted),
+			Ok(Hash::repeat_byte(1)),
+		);
+		assert_eq!(
+			import_submitted_extrinsic(&IncludeDataPool, Roles::FULL, SyncState::Idle, &rejected),
+			Err("Transaction rejected by the pool".to_string()),
+		);
+		assert!(import_submitted_extrinsic(&IncludeDataPool, Roles::FULL, SyncState::Downloading, &accepted).is_err());
+		assert!(import_submitted_extrinsic(&IncludeDataPool, Roles::LIGHT, SyncState::Idle, &accepted).is_err());
+	}
+
+	#[test]
+	fn transaction_submission_messages_are_decoded() {
+		let request: Message<Block> = GenericMessage::TransactionSubmission(message::TransactionSubmission {
+			id: 7,
+			extrinsic: Extrinsic::IncludeData(vec![1]),
+		});
+		let response: Message<Block> = GenericMessage::TransactionSubmissionResponse(
+			message::TransactionSubmissionResponse { id: 7, result: Err("Invalid".into()) },
+		);
+
+		assert_eq!(message::decode::<Block>(&request.encode()).unwrap(), request);
+		assert_eq!(message::decode::<Block>(&response.encode()).unwrap(), response);
+	}
+}
@@ -24,7 +24,7 @@ pub use self::generic::{
 	RemoteHeaderRequest, RemoteHeaderResponse,
 	RemoteChangesRequest, RemoteChangesResponse,
 	FinalityProofRequest, FinalityProofResponse,
-	FromBlock, RemoteReadChildRequest,
+	FromBlock, RemoteReadChildRequest, TransactionSubmission,
 };
 
 /// A unique ID of a request.
@@ -38,6 +38,20 @@ pub type Message<B> = generic::Message<
 	<B as BlockT>::Extrinsic,
 >;
 
+/// Decode a message, strictly.
+///
+/// Fails if `data` is not exactly one valid message. Never panics, whatever `data` contains.
+/// The network itself ignores trailing bytes after a message, so that newer versions of the
+/// protocol can append fields; this is meant for fuzzing and tests.
+pub fn decode<B: BlockT>(data: &[u8]) -> Result<Message<B>, Error> {
+	let mut input = data;
+	let message = Message::<B>::decode(&mut input)?;
+	if !input.is_empty() {
+		return Err("Trailing bytes after the message".into());
+	}
+	Ok(message)
+}
+
 /// Type alias for using the status type using block type parameters.
 pub type Status<B> = generic::Status<
 	<B as BlockT>::Hash,
@@ -116,6 +130,15 @@ pub struct RemoteCallResponse {
 	pub proof: Vec<Vec<u8>>,
 }
 
+/// Confirmation of an extrinsic submission.
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+pub struct TransactionSubmissionResponse {
+	/// Id of a request this response was made for.
+	pub id: RequestId,
+	/// `Ok` if the extrinsic was accepted into the pool, the reason why it was rejected otherwise.
+	pub result: Result<(), String>,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
 /// Remote read response.
 pub struct RemoteReadResponse {
@@ -133,6 +156,7 @@ pub mod generic {
 	use super::{
 		RemoteReadResponse, Transactions, Direction,
 		RequestId, BlockAttributes, RemoteCallResponse, ConsensusEngineId,
+		TransactionSubmissionResponse,
 	};
 	/// Consensus is mostly opaque to us
 	#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
@@ -206,6 +230,10 @@ pub mod generic {
 		FinalityProofRequest(FinalityProofRequest<Hash>),
 		/// Finality proof reponse.
 		FinalityProofResponse(FinalityProofResponse<Hash>),
+		/// Extrinsic submission request.
+		TransactionSubmission(TransactionSubmission<Extrinsic>),
+		/// Extrinsic submission response.
+		TransactionSubmissionResponse(TransactionSubmissionResponse),
 		/// Chain-specific message.
 		#[codec(index = "255")]
 		ChainSpecific(Vec<u8>),
@@ -375,4 +403,13 @@ pub mod generic {
 		/// Finality proof (if available).
 		pub proof: Option<Vec<u8>>,
 	}
+
+	#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+	/// Extrinsic submitted by a light client, to be imported by a full node.
+	pub struct TransactionSubmission<E> {
+		/// Unique request id.
+		pub id: RequestId,
+		/// The submitted extrinsic.
+		pub extrinsic: E,
+	}
 }
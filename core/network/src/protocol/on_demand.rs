@@ -989,6 +989,29 @@ pub mod tests {
 		assert_eq!(response.wait().unwrap().unwrap(), Some(vec![42]));
 	}
 
+	#[test]
+	fn disconnects_from_peer_on_incorrect_read_child_proof() {
+		let mut on_demand = dummy(false);
+		let mut network_interface = DummyNetwork::default();
+		let peer0 = PeerId::random();
+		on_demand.add_request(&mut network_interface, RequestData::RemoteReadChild(RemoteReadChildRequest {
+			header: dummy_header(),
+			block: Default::default(),
+			storage_key: b":child_storage:sub".to_vec(),
+			key: b":key".to_vec(),
+			retry_count: Some(1),
+		}, oneshot::channel().0));
+
+		on_demand.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000);
+		on_demand.on_remote_read_response(&mut network_interface,
+			peer0.clone(), message::RemoteReadResponse {
+				id: 0,
+				proof: vec![vec![2]],
+		});
+		assert_disconnected_peer(&network_interface);
+		assert_eq!(on_demand.pending_requests.len(), 1);
+	}
+
 	#[test]
 	fn receives_remote_header_response() {
 		let mut on_demand = dummy(true);
@@ -0,0 +1,252 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tracking of the extrinsics a light client submitted to its full peers.
+//!
+//! An extrinsic is submitted to every full peer, and each of them answers whether it accepted the
+//! extrinsic into its pool. The extrinsic is accepted as soon as one peer accepts it, and only
+//! rejected once every peer it was submitted to rejected it: a peer may reject it for reasons that
+//! have nothing to do with its validity, for instance because it is still syncing.
+//!
+//! The number of pending submissions to each peer is bounded, and submissions that stay
+//! unanswered for too long expire.
+
+use libp2p::PeerId;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time;
+use super::message::RequestId;
+
+/// The maximum number of submissions waiting for the answer of a single peer.
+const MAX_PENDING_PER_PEER: usize = 256;
+
+/// How long a peer has to answer a submission.
+const SUBMISSION_TIMEOUT: time::Duration = time::Duration::from_secs(40);
+
+/// The outcome of an answer to a submission.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Verdict<H> {
+	/// The answer doesn't match any pending submission.
+	Unexpected,
+	/// Other peers have yet to answer.
+	Pending,
+	/// The peer accepted the extrinsic `H`.
+	Accepted(H),
+	/// Every peer the extrinsic `H` was submitted to rejected it, the last one for the given
+	/// reason.
+	Rejected(H, String),
+}
+
+/// The answers to the submissions of an extrinsic.
+#[derive(Debug, Default)]
+struct Answers {
+	/// Number of peers which have yet to answer.
+	pending: usize,
+	/// Whether a peer accepted the extrinsic.
+	accepted: bool,
+	/// The reason of the last rejection.
+	rejection: Option<String>,
+}
+
+/// The submissions waiting for the answer of a peer.
+#[derive(Debug)]
+pub(crate) struct Submissions<H: Hash + Eq> {
+	requests: HashMap<(PeerId, RequestId), (H, time::Instant)>,
+	pending_per_peer: HashMap<PeerId, usize>,
+	answers: HashMap<H, Answers>,
+}
+
+impl<H: Hash + Eq + Clone> Submissions<H> {
+	/// Create an empty set of submissions.
+	pub(crate) fn new() -> Self {
+		Submissions {
+			requests: HashMap::new(),
+			pending_per_peer: HashMap::new(),
+			answers: HashMap::new(),
+		}
+	}
+
+	/// Whether another extrinsic may be submitted to `who`.
+	pub(crate) fn has_capacity(&self, who: &PeerId) -> bool {
+		self.pending_per_peer.get(who).map_or(true, |pending| *pending < MAX_PENDING_PER_PEER)
+	}
+
+	/// Record that `hash` was submitted to `who` with the request `id`.
+	pub(crate) fn submit(&mut self, who: PeerId, id: RequestId, hash: H, now: time::Instant) {
+		*self.pending_per_peer.entry(who.clone()).or_default() += 1;
+		self.answers.entry(hash.clone()).or_default().pending += 1;
+		self.requests.insert((who, id), (hash, now));
+	}
+
+	/// Called when `who` answered the request `id`.
+	pub(crate) fn on_answer(&mut self, who: &PeerId, id: RequestId, result: Result<(), String>) -> Verdict<H> {
+		let hash = match self.remove_request(who, id) {
+			Some(hash) => hash,
+			None => return Verdict::Unexpected,
+		};
+		let answers = self.answers.get_mut(&hash).expect("answers are kept while requests are pending; qed");
+		match result {
+			Ok(()) => {
+				answers.accepted = true;
+				self.settle(&hash);
+				Verdict::Accepted(hash)
+			},
+			Err(reason) => {
+				answers.rejection = Some(reason);
+				match self.settle(&hash) {
+					Some(reason) => Verdict::Rejected(hash, reason),
+					None => Verdict::Pending,
+				}
+			},
+		}
+	}
+
+	/// Called when `who` disconnected, which won't answer its pending submissions.
+	///
+	/// Returns the extrinsics which every other peer rejected, with the reason of the last
+	/// rejection.
+	pub(crate) fn on_disconnect(&mut self, who: &PeerId) -> Vec<(H, String)> {
+		let ids: Vec<_> = self.requests.keys()
+			.filter(|(peer, _)| peer == who)
+			.map(|(_, id)| *id)
+			.collect();
+		self.remove_requests(ids.into_iter().map(|id| (who.clone(), id)))
+	}
+
+	/// Expire the submissions which have been waiting since before `now - SUBMISSION_TIMEOUT`.
+	///
+	/// Returns the extrinsics which every other peer rejected, with the reason of the last
+	/// rejection.
+	pub(crate) fn expire(&mut self, now: time::Instant) -> Vec<(H, String)> {
+		let expired: Vec<_> = self.requests.iter()
+			.filter(|(_, (_, submitted))| now.duration_since(*submitted) > SUBMISSION_TIMEOUT)
+			.map(|(key, _)| key.clone())
+			.collect();
+		self.remove_requests(expired.into_iter())
+	}
+
+	/// Remove the pending request `id` to `who`, returning the extrinsic it submitted.
+	fn remove_request(&mut self, who: &PeerId, id: RequestId) -> Option<H> {
+		let (hash, _) = self.requests.remove(&(who.clone(), id))?;
+		if let Some(pending) = self.pending_per_peer.get_mut(who) {
+			*pending -= 1;
+			if *pending == 0 {
+				self.pending_per_peer.remove(who);
+			}
+		}
+		if let Some(answers) = self.answers.get_mut(&hash) {
+			answers.pending -= 1;
+		}
+		Some(hash)
+	}
+
+	/// Remove the `requests` which won't be answered, and settle their extrinsics.
+	fn remove_requests(&mut self, requests: impl Iterator<Item = (PeerId, RequestId)>) -> Vec<(H, String)> {
+		let mut rejected = Vec::new();
+		for (who, id) in requests {
+			if let Some(hash) = self.remove_request(&who, id) {
+				if let Some(reason) = self.settle(&hash) {
+					rejected.push((hash, reason));
+				}
+			}
+		}
+		rejected
+	}
+
+	/// Forget `hash` once no peer has to answer anymore.
+	///
+	/// Returns the reason of the last rejection if no peer accepted it.
+	fn settle(&mut self, hash: &H) -> Option<String> {
+		if self.answers.get(hash).map_or(true, |answers| answers.pending > 0) {
+			return None;
+		}
+		let answers = self.answers.remove(hash)?;
+		if answers.accepted {
+			None
+		} else {
+			answers.rejection
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn extrinsics_are_rejected_once_every_peer_rejected_them() {
+		let (alice, bob) = (PeerId::random(), PeerId::random());
+		let now = time::Instant::now();
+		let mut submissions = Submissions::new();
+
+		submissions.submit(alice.clone(), 0, 1u8, now);
+		submissions.submit(bob.clone(), 0, 1u8, now);
+		submissions.submit(bob.clone(), 1, 2u8, now);
+
+		assert_eq!(submissions.on_answer(&alice, 0, Err("Syncing".into())), Verdict::Pending);
+		assert_eq!(submissions.on_answer(&bob, 0, Err("Invalid".into())), Verdict::Rejected(1, "Invalid".into()));
+		assert_eq!(submissions.on_answer(&bob, 0, Ok(())), Verdict::Unexpected);
+		assert_eq!(submissions.on_answer(&bob, 1, Ok(())), Verdict::Accepted(2));
+		assert!(submissions.answers.is_empty());
+		assert!(submissions.pending_per_peer.is_empty());
+	}
+
+	#[test]
+	fn accepted_extrinsics_are_never_rejected() {
+		let (alice, bob) = (PeerId::random(), PeerId::random());
+		let mut submissions = Submissions::new();
+
+		submissions.submit(alice.clone(), 0, 1u8, time::Instant::now());
+		submissions.submit(bob.clone(), 0, 1u8, time::Instant::now());
+
+		assert_eq!(submissions.on_answer(&alice, 0, Ok(())), Verdict::Accepted(1));
+		assert_eq!(submissions.on_answer(&bob, 0, Err("Invalid".into())), Verdict::Pending);
+	}
+
+	#[test]
+	fn unanswered_submissions_expire_or_are_dropped_with_their_peer() {
+		let (alice, bob) = (PeerId::random(), PeerId::random());
+		let now = time::Instant::now();
+		let mut submissions = Submissions::new();
+
+		submissions.submit(alice.clone(), 0, 1u8, now);
+		submissions.submit(bob.clone(), 0, 1u8, now);
+		submissions.submit(bob.clone(), 1, 2u8, now + SUBMISSION_TIMEOUT);
+		assert_eq!(submissions.on_answer(&alice, 0, Err("Invalid".into())), Verdict::Pending);
+
+		let later = now + SUBMISSION_TIMEOUT + time::Duration::from_secs(1);
+		assert_eq!(submissions.expire(later), vec![(1, "Invalid".to_string())]);
+		assert_eq!(submissions.on_disconnect(&bob), Vec::new());
+		assert!(submissions.requests.is_empty());
+		assert!(submissions.answers.is_empty());
+	}
+
+	#[test]
+	fn pending_submissions_per_peer_are_bounded() {
+		let alice = PeerId::random();
+		let mut submissions = Submissions::new();
+
+		for id in 0..MAX_PENDING_PER_PEER as u64 {
+			assert!(submissions.has_capacity(&alice));
+			submissions.submit(alice.clone(), id, id, time::Instant::now());
+		}
+		assert!(!submissions.has_capacity(&alice));
+		assert!(submissions.has_capacity(&PeerId::random()));
+
+		submissions.on_answer(&alice, 0, Ok(()));
+		assert!(submissions.has_capacity(&alice));
+	}
+}
@@ -67,8 +67,14 @@ pub trait TransactionPool<H: ExHashT, B: BlockT>: Send + Sync {
 	fn transactions(&self) -> Vec<(H, B::Extrinsic)>;
 	/// Import a transaction into the pool.
 	fn import(&self, transaction: &B::Extrinsic) -> Option<H>;
+	/// Import a transaction into the pool, returning the reason why it was rejected on failure.
+	fn submit(&self, transaction: &B::Extrinsic) -> Result<H, String> {
+		self.import(transaction).ok_or_else(|| "Transaction rejected by the pool".into())
+	}
 	/// Notify the pool about transactions broadcast.
 	fn on_broadcasted(&self, propagations: HashMap<H, Vec<String>>);
+	/// Notify the pool that every peer a transaction was submitted to rejected it.
+	fn on_rejected(&self, _hash: &H, _reason: String) {}
 }
 
 /// A cloneable handle for reporting cost/benefits of peers.
@@ -492,6 +498,15 @@ impl<B: BlockT + 'static, S: NetworkSpecialization<B>, H: ExHashT> NetworkServic
 		Ok(())
 	}
 
+	/// Adds a `PeerId` and its address to the addresses used to discover the network.
+	pub fn add_boot_node(&self, peer: String) -> Result<(), String> {
+		let (peer_id, addr) = parse_str_addr(&peer).map_err(|e| format!("{:?}", e))?;
+		let _ = self
+			.to_worker
+			.unbounded_send(ServerToWorkerMsg::AddKnownAddress(peer_id, addr));
+		Ok(())
+	}
+
 	/// Returns the number of peers we're connected to.
 	pub fn num_connected(&self) -> usize {
 		self.num_connected.load(Ordering::Relaxed)
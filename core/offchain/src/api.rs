@@ -22,7 +22,7 @@ use std::{
 	thread::sleep,
 };
 use client::backend::OffchainStorage;
-use crate::AuthorityKeyProvider;
+use crate::{AuthorityKeyProvider, ExternalKey};
 use futures::{Stream, Future, sync::mpsc};
 use log::{info, debug, warn, error};
 use codec::{Encode, Decode};
@@ -31,19 +31,24 @@ use primitives::offchain::{
 	HttpRequestId, HttpRequestStatus, HttpError,
 	Externalities as OffchainExt,
 	CryptoKind, CryptoKey,
-	StorageKind,
+	StorageKind, STORAGE_PREFIX,
 	OpaqueNetworkState, OpaquePeerId, OpaqueMultiaddr,
 };
-use primitives::crypto::{Pair, Public, Protected};
+use primitives::crypto::{KeyTypeId, Pair, Public, Protected};
 use primitives::{ed25519, sr25519};
 use sr_primitives::{
 	generic::BlockId,
-	traits::{self, Extrinsic},
+	traits::{self, Extrinsic, NumberFor, One, Saturating},
+	transaction_validity::TransactionSource,
 };
 use transaction_pool::txpool::{Pool, ChainApi};
 use network::NetworkStateInfo;
 use network::{PeerId, Multiaddr};
 
+mod http;
+
+pub(crate) use self::http::{HttpClient, http_client};
+
 /// A message between the offchain extension and the processing thread.
 enum ExtMessage {
 	SubmitExtrinsic(Vec<u8>),
@@ -126,6 +131,13 @@ impl LocalKey {
 			}
 		}
 	}
+
+	fn vrf_sign(&self, context: &[u8], message: &[u8]) -> Result<sr25519::VrfSignature, ()> {
+		match self {
+			LocalKey::Ed25519(_) => Err(()),
+			LocalKey::Sr25519(pair) => Ok(pair.vrf_sign(context, message)),
+		}
+	}
 }
 
 /// A key.
@@ -133,6 +145,8 @@ enum Key<ConsensusPair, FinalityPair> {
 	LocalKey(LocalKey),
 	AuthorityKey(ConsensusPair),
 	FgAuthorityKey(FinalityPair),
+	ExternalAuthorityKey(ExternalKey),
+	ExternalFgAuthorityKey(ExternalKey),
 }
 
 impl<ConsensusPair: Pair, FinalityPair: Pair> Key<ConsensusPair, FinalityPair> {
@@ -147,6 +161,9 @@ impl<ConsensusPair: Pair, FinalityPair: Pair> Key<ConsensusPair, FinalityPair> {
 			Key::FgAuthorityKey(pair) => {
 				Ok(pair.public().to_raw_vec())
 			}
+			Key::ExternalAuthorityKey(key) | Key::ExternalFgAuthorityKey(key) => {
+				Ok(key.public.clone())
+			}
 		}
 	}
 
@@ -161,6 +178,9 @@ impl<ConsensusPair: Pair, FinalityPair: Pair> Key<ConsensusPair, FinalityPair> {
 			Key::FgAuthorityKey(pair) => {
 				Ok(pair.sign(data).as_ref().to_vec())
 			}
+			Key::ExternalAuthorityKey(key) | Key::ExternalFgAuthorityKey(key) => {
+				(key.sign)(data)
+			}
 		}
 	}
 
@@ -175,6 +195,12 @@ impl<ConsensusPair: Pair, FinalityPair: Pair> Key<ConsensusPair, FinalityPair> {
 			Key::FgAuthorityKey(pair) => {
 				Ok(FinalityPair::verify_weak(signature, msg, pair.public()))
 			}
+			Key::ExternalAuthorityKey(key) => {
+				Ok(ConsensusPair::verify_weak(signature, msg, &key.public))
+			}
+			Key::ExternalFgAuthorityKey(key) => {
+				Ok(FinalityPair::verify_weak(signature, msg, &key.public))
+			}
 		}
 	}
 }
@@ -189,6 +215,18 @@ pub(crate) struct Api<Storage, KeyProvider, Block: traits::Block> {
 	key_provider: KeyProvider,
 	network_state: Arc<dyn NetworkStateInfo + Send + Sync>,
 	at: BlockId<Block>,
+	ancestry: Ancestry<Block>,
+	http: http::HttpApi,
+}
+
+/// The fork an offchain worker runs on, used to look up the fork-aware local storage.
+pub(crate) struct Ancestry<Block: traits::Block> {
+	/// The number of the block the worker runs at.
+	pub number: NumberFor<Block>,
+	/// The hash of the block the worker runs at.
+	pub hash: Block::Hash,
+	/// Returns the parent of the given block, if it is known.
+	pub parent: Box<dyn Fn(&Block::Hash) -> Option<Block::Hash> + Send>,
 }
 
 fn unavailable_yet<R: Default>(name: &str) -> R {
@@ -197,8 +235,20 @@ fn unavailable_yet<R: Default>(name: &str) -> R {
 	Default::default()
 }
 
-const LOCAL_DB: &str = "LOCAL (fork-aware) DB";
-const STORAGE_PREFIX: &[u8] = b"storage";
+/// Prefix of the fork-aware local storage. Values are kept per block that set them, under the
+/// block hash followed by the key.
+const LOCAL_STORAGE_PREFIX: &[u8] = b"local_storage";
+/// Prefix of the values of the fork-aware local storage as seen by the last finalized block.
+const LOCAL_STORAGE_FINALIZED_PREFIX: &[u8] = b"local_storage_finalized";
+/// Prefix of the index of the fork-aware local storage. The hashes of the blocks and the keys
+/// they set are kept under the block number, to be pruned once the number is finalized.
+const LOCAL_STORAGE_INDEX_PREFIX: &[u8] = b"local_storage_index";
+/// Key of the block numbers in the index.
+const INDEXED_NUMBERS_KEY: &[u8] = b"numbers";
+/// Key of the number of the last finalized block the local storage was pruned at.
+const FINALIZED_NUMBER_KEY: &[u8] = b"finalized";
+/// Number of ancestors searched for a value of the fork-aware local storage.
+const MAX_LOCAL_STORAGE_DEPTH: usize = 256;
 const KEYS_PREFIX: &[u8] = b"keys";
 
 const NEXT_ID: &[u8] = b"crypto_key_id";
@@ -212,6 +262,81 @@ impl<Storage, KeyProvider, Block> Api<Storage, KeyProvider, Block> where
 		Some(self.keys_password.as_ref().as_str())
 	}
 
+	/// Get a value of the fork-aware local storage, as set at the current block or the closest
+	/// ancestor that set it, or as seen by the last finalized block.
+	///
+	/// A value found elsewhere is copied to the current block, so that it stays visible to
+	/// descendants for another `MAX_LOCAL_STORAGE_DEPTH` blocks.
+	fn local_get(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+		let at = local_key(&self.ancestry.hash, key);
+		if let Some(value) = self.db.get(LOCAL_STORAGE_PREFIX, &at) {
+			return Some(value);
+		}
+
+		let value = self.ancestor_local_get(key)?;
+		if self.db.compare_and_set(LOCAL_STORAGE_PREFIX, &at, None, &value) {
+			self.index_local_key(key);
+		}
+		Some(value)
+	}
+
+	fn ancestor_local_get(&self, key: &[u8]) -> Option<Vec<u8>> {
+		// Blocks up to the finalized one are pruned, their values are merged into the finalized ones.
+		let finalized = read_index::<_, NumberFor<Block>>(&self.db, FINALIZED_NUMBER_KEY);
+		let (mut block, mut number) = (self.ancestry.hash, self.ancestry.number);
+		for _ in 0..MAX_LOCAL_STORAGE_DEPTH {
+			if finalized.map_or(false, |finalized| number <= finalized) {
+				break;
+			}
+			block = match (self.ancestry.parent)(&block) {
+				Some(parent) => parent,
+				None => break,
+			};
+			number = number.saturating_sub(One::one());
+			if let Some(value) = self.db.get(LOCAL_STORAGE_PREFIX, &local_key(&block, key)) {
+				return Some(value);
+			}
+		}
+		self.db.get(LOCAL_STORAGE_FINALIZED_PREFIX, key)
+	}
+
+	fn local_set(&mut self, key: &[u8], value: &[u8]) {
+		self.db.set(LOCAL_STORAGE_PREFIX, &local_key(&self.ancestry.hash, key), value);
+		self.index_local_key(key);
+	}
+
+	fn local_compare_and_set(&mut self, key: &[u8], old_value: Option<&[u8]>, new_value: &[u8]) -> bool {
+		// Brings the current value to the current block, where it is compared atomically.
+		if self.local_get(key).as_ref().map(|value| value.as_slice()) != old_value {
+			return false;
+		}
+		let is_set = self.db.compare_and_set(
+			LOCAL_STORAGE_PREFIX,
+			&local_key(&self.ancestry.hash, key),
+			old_value,
+			new_value,
+		);
+		if is_set {
+			self.index_local_key(key);
+		}
+		is_set
+	}
+
+	/// Add `key` of the current block to the index of the fork-aware local storage.
+	fn index_local_key(&mut self, key: &[u8]) {
+		let (number, hash) = (self.ancestry.number, self.ancestry.hash);
+		update_index(&mut self.db, &number.encode(), |entries: &mut Vec<(Block::Hash, Vec<u8>)>| {
+			if !entries.iter().any(|(block, indexed)| *block == hash && indexed.as_slice() == key) {
+				entries.push((hash, key.to_vec()));
+			}
+		});
+		update_index(&mut self.db, INDEXED_NUMBERS_KEY, |numbers: &mut Vec<NumberFor<Block>>| {
+			if !numbers.contains(&number) {
+				numbers.push(number);
+			}
+		});
+	}
+
 	fn read_key(
 		&self,
 		key: CryptoKey,
@@ -233,12 +358,18 @@ impl<Storage, KeyProvider, Block> Api<Storage, KeyProvider, Block> where
 				Ok(Key::LocalKey(key.to_local_key(self.password())?))
 			}
 			CryptoKey::AuthorityKey => {
+				if let Some(key) = self.key_provider.external_authority_key(&self.at) {
+					return Ok(Key::ExternalAuthorityKey(key));
+				}
 				let key = self.key_provider
 					.authority_key(&self.at)
 					.ok_or(())?;
 				Ok(Key::AuthorityKey(key))
 			}
 			CryptoKey::FgAuthorityKey => {
+				if let Some(key) = self.key_provider.external_fg_authority_key(&self.at) {
+					return Ok(Key::ExternalFgAuthorityKey(key));
+				}
 				let key = self.key_provider
 					.fg_authority_key(&self.at)
 					.ok_or(())?;
@@ -314,6 +445,33 @@ where
 		self.read_key(key)?.verify(msg, signature)
 	}
 
+	fn vrf_sign(
+		&mut self,
+		key: CryptoKey,
+		context: &[u8],
+		message: &[u8],
+	) -> Result<sr25519::VrfSignature, ()> {
+		match self.read_key(key)? {
+			Key::LocalKey(local) => local.vrf_sign(context, message),
+			// The authority keys are not known to be sr25519 keys.
+			Key::AuthorityKey(_) | Key::FgAuthorityKey(_) |
+			Key::ExternalAuthorityKey(_) | Key::ExternalFgAuthorityKey(_) => Err(()),
+		}
+	}
+
+	fn keystore_public_keys(&mut self, key_type: KeyTypeId) -> Result<Vec<Vec<u8>>, ()> {
+		self.key_provider.keystore_public_keys(key_type)
+	}
+
+	fn keystore_sign(
+		&mut self,
+		key_type: KeyTypeId,
+		public: &[u8],
+		data: &[u8],
+	) -> Result<Vec<u8>, ()> {
+		self.key_provider.keystore_sign(key_type, public, data)
+	}
+
 	fn timestamp(&mut self) -> Timestamp {
 		let now = SystemTime::now();
 		let epoch_duration = now.duration_since(SystemTime::UNIX_EPOCH);
@@ -347,7 +505,7 @@ where
 	fn local_storage_set(&mut self, kind: StorageKind, key: &[u8], value: &[u8]) {
 		match kind {
 			StorageKind::PERSISTENT => self.db.set(STORAGE_PREFIX, key, value),
-			StorageKind::LOCAL => unavailable_yet(LOCAL_DB),
+			StorageKind::LOCAL => self.local_set(key, value),
 		}
 	}
 
@@ -362,74 +520,133 @@ where
 			StorageKind::PERSISTENT => {
 				self.db.compare_and_set(STORAGE_PREFIX, key, old_value, new_value)
 			},
-			StorageKind::LOCAL => unavailable_yet(LOCAL_DB),
+			StorageKind::LOCAL => self.local_compare_and_set(key, old_value, new_value),
 		}
 	}
 
 	fn local_storage_get(&mut self, kind: StorageKind, key: &[u8]) -> Option<Vec<u8>> {
 		match kind {
 			StorageKind::PERSISTENT => self.db.get(STORAGE_PREFIX, key),
-			StorageKind::LOCAL => unavailable_yet(LOCAL_DB),
+			StorageKind::LOCAL => self.local_get(key),
 		}
 	}
 
 	fn http_request_start(
 		&mut self,
-		_method: &str,
-		_uri: &str,
+		method: &str,
+		uri: &str,
 		_meta: &[u8]
 	) -> Result<HttpRequestId, ()> {
-		unavailable_yet::<()>("http_request_start");
-		Err(())
+		self.http.request_start(method, uri)
 	}
 
 	fn http_request_add_header(
 		&mut self,
-		_request_id: HttpRequestId,
-		_name: &str,
-		_value: &str
+		request_id: HttpRequestId,
+		name: &str,
+		value: &str
 	) -> Result<(), ()> {
-		unavailable_yet::<()>("http_request_add_header");
-		Err(())
+		self.http.request_add_header(request_id, name, value)
 	}
 
 	fn http_request_write_body(
 		&mut self,
-		_request_id: HttpRequestId,
-		_chunk: &[u8],
+		request_id: HttpRequestId,
+		chunk: &[u8],
 		_deadline: Option<Timestamp>
 	) -> Result<(), HttpError> {
-		unavailable_yet::<()>("http_request_write_body");
-		Err(HttpError::IoError)
+		self.http.request_write_body(request_id, chunk)
 	}
 
 	fn http_response_wait(
 		&mut self,
 		ids: &[HttpRequestId],
-		_deadline: Option<Timestamp>
+		deadline: Option<Timestamp>
 	) -> Vec<HttpRequestStatus> {
-		unavailable_yet::<()>("http_response_wait");
-		ids.iter().map(|_| HttpRequestStatus::Unknown).collect()
+		self.http.response_wait(ids, deadline)
 	}
 
 	fn http_response_headers(
 		&mut self,
-		_request_id: HttpRequestId
+		request_id: HttpRequestId
 	) -> Vec<(Vec<u8>, Vec<u8>)> {
-		unavailable_yet("http_response_headers")
+		self.http.response_headers(request_id)
 	}
 
 	fn http_response_read_body(
 		&mut self,
-		_request_id: HttpRequestId,
-		_buffer: &mut [u8],
-		_deadline: Option<Timestamp>
+		request_id: HttpRequestId,
+		buffer: &mut [u8],
+		deadline: Option<Timestamp>
 	) -> Result<usize, HttpError> {
-		unavailable_yet::<()>("http_response_read_body");
-		Err(HttpError::IoError)
+		self.http.response_read_body(request_id, buffer, deadline)
+	}
+}
+
+/// Key of a value of the fork-aware local storage set at the given block.
+fn local_key<Hash: AsRef<[u8]>>(block: &Hash, key: &[u8]) -> Vec<u8> {
+	let mut local_key = block.as_ref().to_vec();
+	local_key.extend_from_slice(key);
+	local_key
+}
+
+fn read_index<Storage: OffchainStorage, T: Decode>(db: &Storage, key: &[u8]) -> Option<T> {
+	db.get(LOCAL_STORAGE_INDEX_PREFIX, key).and_then(|value| T::decode(&mut &value[..]).ok())
+}
+
+/// Update an entry of the index of the fork-aware local storage atomically, since workers of
+/// blocks with the same number may run at the same time.
+fn update_index<Storage: OffchainStorage, T: Encode + Decode + Default>(
+	db: &mut Storage,
+	key: &[u8],
+	update: impl Fn(&mut T),
+) {
+	loop {
+		let old_value = db.get(LOCAL_STORAGE_INDEX_PREFIX, key);
+		let mut entry = old_value.as_ref()
+			.and_then(|value| T::decode(&mut &value[..]).ok())
+			.unwrap_or_default();
+		update(&mut entry);
+		let old_value = old_value.as_ref().map(|value| value.as_slice());
+		if db.compare_and_set(LOCAL_STORAGE_INDEX_PREFIX, key, old_value, &entry.encode()) {
+			return;
+		}
 	}
 }
 
+/// Prune the fork-aware local storage of all blocks up to the finalized block `number`.
+///
+/// Values set at finalized blocks are merged, in order, into the values seen by the last
+/// finalized block. Values set at blocks of abandoned forks are dropped.
+pub(crate) fn prune_local_storage<Storage: OffchainStorage, Block: traits::Block>(
+	db: &mut Storage,
+	number: NumberFor<Block>,
+	canonical_hash: impl Fn(NumberFor<Block>) -> Option<Block::Hash>,
+) {
+	let mut numbers = read_index::<_, Vec<NumberFor<Block>>>(db, INDEXED_NUMBERS_KEY).unwrap_or_default();
+	numbers.retain(|indexed| *indexed <= number);
+	numbers.sort();
+
+	for indexed in &numbers {
+		let canonical = canonical_hash(*indexed);
+		let entries = read_index::<_, Vec<(Block::Hash, Vec<u8>)>>(db, &indexed.encode()).unwrap_or_default();
+		for (block, key) in entries {
+			if Some(block) == canonical {
+				if let Some(value) = db.get(LOCAL_STORAGE_PREFIX, &local_key(&block, &key)) {
+					db.set(LOCAL_STORAGE_FINALIZED_PREFIX, &key, &value);
+				}
+			}
+			db.remove(LOCAL_STORAGE_PREFIX, &local_key(&block, &key));
+		}
+		db.remove(LOCAL_STORAGE_INDEX_PREFIX, &indexed.encode());
+	}
+
+	update_index(db, INDEXED_NUMBERS_KEY, |indexed: &mut Vec<NumberFor<Block>>| {
+		indexed.retain(|indexed| !numbers.contains(indexed));
+	});
+	db.set(LOCAL_STORAGE_INDEX_PREFIX, FINALIZED_NUMBER_KEY, &number.encode());
+}
+
 /// Information about the local node's network state.
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct NetworkState {
@@ -502,6 +719,7 @@ pub(crate) struct AsyncApi<A: ChainApi> {
 	receiver: Option<mpsc::UnboundedReceiver<ExtMessage>>,
 	transaction_pool: Arc<Pool<A>>,
 	at: BlockId<A::Block>,
+	http: Option<http::HttpWorker>,
 }
 
 impl<A: ChainApi> AsyncApi<A> {
@@ -512,9 +730,12 @@ impl<A: ChainApi> AsyncApi<A> {
 		keys_password: Protected<String>,
 		key_provider: P,
 		at: BlockId<A::Block>,
+		ancestry: Ancestry<A::Block>,
 		network_state: Arc<dyn NetworkStateInfo + Send + Sync>,
+		http_client: HttpClient,
 	) -> (Api<S, P, A::Block>, AsyncApi<A>) {
 		let (sender, rx) = mpsc::unbounded();
+		let (http_api, http_worker) = http::http(http_client);
 
 		let api = Api {
 			sender,
@@ -523,12 +744,15 @@ impl<A: ChainApi> AsyncApi<A> {
 			key_provider,
 			network_state,
 			at,
+			ancestry,
+			http: http_api,
 		};
 
 		let async_api = AsyncApi {
 			receiver: Some(rx),
 			transaction_pool,
 			at,
+			http: Some(http_worker),
 		};
 
 		(api, async_api)
@@ -537,13 +761,16 @@ impl<A: ChainApi> AsyncApi<A> {
 	/// Run a processing task for the API
 	pub fn process(mut self) -> impl Future<Item=(), Error=()> {
 		let receiver = self.receiver.take().expect("Take invoked only once.");
+		let http = self.http.take().expect("Take invoked only once.");
 
-		receiver.for_each(move |msg| {
+		let extrinsics = receiver.for_each(move |msg| {
 			match msg {
 				ExtMessage::SubmitExtrinsic(ext) => self.submit_extrinsic(ext),
 			}
 			Ok(())
-		})
+		});
+
+		extrinsics.join(http).map(|_| ())
 	}
 
 	fn submit_extrinsic(&mut self, ext: Vec<u8>) {
@@ -556,7 +783,7 @@ impl<A: ChainApi> AsyncApi<A> {
 		};
 
 		info!("Submitting to the pool: {:?} (isSigned: {:?})", xt, xt.is_signed());
-		match self.transaction_pool.submit_one(&self.at, xt.clone()) {
+		match self.transaction_pool.submit_one(&self.at, TransactionSource::Local, xt.clone()) {
 			Ok(hash) => debug!("[{:?}] Offchain transaction added to the pool.", hash),
 			Err(e) => {
 				debug!("Couldn't submit transaction: {:?}", e);
@@ -573,6 +800,7 @@ mod tests {
 	use client_db::offchain::LocalStorage;
 	use crate::tests::TestProvider;
 	use network::PeerId;
+	use primitives::H256;
 	use test_client::runtime::Block;
 
 	struct MockNetworkStateInfo();
@@ -587,16 +815,54 @@ mod tests {
 		}
 	}
 
+	fn hash(block: u8) -> H256 {
+		H256::from([block; 32])
+	}
+
+	fn number(block: u8) -> u64 {
+		match block {
+			0 | 1 | 2 => block.into(),
+			3 => 2,
+			_ => 3,
+		}
+	}
+
 	fn offchain_api() -> (Api<LocalStorage, TestProvider<Block>, Block>, AsyncApi<impl ChainApi>) {
+		offchain_api_at(LocalStorage::new_test(), 0)
+	}
+
+	/// Create an API at block `at` of a test chain, in which blocks `2` and `3` are both
+	/// children of block `1`, which is the child of block `0`. Block `4` is the child of `2`.
+	fn offchain_api_at(
+		db: LocalStorage,
+		at: u8,
+	) -> (Api<LocalStorage, TestProvider<Block>, Block>, AsyncApi<impl ChainApi>) {
 		let _ = env_logger::try_init();
-		let db = LocalStorage::new_test();
 		let client = Arc::new(test_client::new());
 		let pool = Arc::new(
 			Pool::new(Default::default(), transaction_pool::ChainApi::new(client.clone()))
 		);
 
 		let mock = Arc::new(MockNetworkStateInfo());
-		AsyncApi::new(pool, db, "pass".to_owned().into(), TestProvider::default(), BlockId::Number(Zero::zero()), mock)
+		AsyncApi::new(
+			pool,
+			db,
+			"pass".to_owned().into(),
+			TestProvider::default(),
+			BlockId::Number(Zero::zero()),
+			Ancestry {
+				number: number(at),
+				hash: hash(at),
+				parent: Box::new(|block: &H256| match block.as_bytes()[0] {
+					0 => None,
+					1 => Some(hash(0)),
+					4 => Some(hash(2)),
+					_ => Some(hash(1)),
+				}),
+			},
+			mock,
+			http_client(),
+		)
 	}
 
 	#[test]
@@ -680,6 +946,59 @@ mod tests {
 		assert_eq!(api.local_storage_get(kind, key), Some(b"value".to_vec()));
 	}
 
+	#[test]
+	fn should_keep_local_storage_per_fork() {
+		let kind = StorageKind::LOCAL;
+		let db = LocalStorage::new_test();
+
+		// given a lock taken at block 1
+		let mut api = offchain_api_at(db.clone(), 1).0;
+		assert_eq!(api.local_storage_compare_and_set(kind, b"lock", None, b"1"), true);
+
+		// when it is released at block 2
+		let mut api = offchain_api_at(db.clone(), 2).0;
+		assert_eq!(api.local_storage_get(kind, b"lock"), Some(b"1".to_vec()));
+		assert_eq!(api.local_storage_compare_and_set(kind, b"lock", None, b"2"), false);
+		assert_eq!(api.local_storage_compare_and_set(kind, b"lock", Some(b"1"), b"2"), true);
+		assert_eq!(api.local_storage_get(kind, b"lock"), Some(b"2".to_vec()));
+
+		// then the competing block 3 still sees the lock of block 1
+		let mut api = offchain_api_at(db.clone(), 3).0;
+		assert_eq!(api.local_storage_get(kind, b"lock"), Some(b"1".to_vec()));
+
+		// and neither block 0 nor the persistent storage see it at all
+		let mut api = offchain_api_at(db, 0).0;
+		assert_eq!(api.local_storage_get(kind, b"lock"), None);
+		assert_eq!(api.local_storage_get(StorageKind::PERSISTENT, b"lock"), None);
+	}
+
+	#[test]
+	fn should_prune_local_storage_on_finality() {
+		let kind = StorageKind::LOCAL;
+		let mut db = LocalStorage::new_test();
+
+		// given values set on both forks
+		offchain_api_at(db.clone(), 1).0.local_storage_set(kind, b"lock", b"1");
+		offchain_api_at(db.clone(), 2).0.local_storage_set(kind, b"lock", b"2");
+		offchain_api_at(db.clone(), 3).0.local_storage_set(kind, b"lock", b"3");
+
+		// when block 2 is finalized
+		prune_local_storage::<_, Block>(&mut db, 2, |number| Some(hash(number as u8)));
+
+		// then the values of all blocks up to it are gone
+		for block in 1..4 {
+			assert_eq!(db.get(LOCAL_STORAGE_PREFIX, &local_key(&hash(block), b"lock")), None);
+			assert_eq!(db.get(LOCAL_STORAGE_INDEX_PREFIX, &number(block).encode()), None);
+		}
+
+		// and its descendants see the value of the finalized fork
+		let mut api = offchain_api_at(db.clone(), 4).0;
+		assert_eq!(api.local_storage_get(kind, b"lock"), Some(b"2".to_vec()));
+		assert_eq!(api.local_storage_compare_and_set(kind, b"lock", Some(b"2"), b"4"), true);
+		assert_eq!(api.local_storage_get(kind, b"lock"), Some(b"4".to_vec()));
+		assert_eq!(read_index::<_, Vec<u64>>(&db, INDEXED_NUMBERS_KEY), Some(vec![3]));
+	}
+
 	#[test]
 	fn should_create_a_new_key_and_sign_and_verify_stuff() {
 		let test = |kind: CryptoKind| {
@@ -0,0 +1,528 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! HTTP requests of offchain workers.
+//!
+//! The runtime talks to the [`HttpApi`], which lives on the offchain worker thread and blocks it
+//! until the deadlines given by the runtime. The requests themselves are performed by the
+//! [`HttpWorker`], a future that runs on the node's executor and drives a `hyper` client.
+//!
+//! Request bodies are buffered until the worker sends them, so writing a chunk never blocks.
+//! Both `http` and `https` URLs are supported, and responses are cut off after
+//! `MAX_RESPONSE_BODY_SIZE` bytes.
+
+use std::{
+	collections::BTreeMap,
+	io,
+	sync::mpsc as std_mpsc,
+	time::{Duration, Instant, SystemTime},
+};
+use futures::{Async, Future, Poll, Stream, future::{self, Either}, stream::FuturesUnordered, sync::mpsc};
+use hyper::{Body, Client, Method, Request, Uri, client::HttpConnector};
+use hyper::header::{HeaderName, HeaderValue};
+use hyper_rustls::HttpsConnector;
+use log::{debug, warn};
+use primitives::offchain::{HttpRequestId, HttpRequestStatus, HttpError, Timestamp};
+
+/// The maximum size of the body of a response. Longer bodies fail with an IO error once read.
+const MAX_RESPONSE_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+/// The client performing the HTTP requests of offchain workers.
+pub type HttpClient = Client<HttpsConnector<HttpConnector>>;
+
+/// Create a client supporting both `http` and `https` URLs.
+pub fn http_client() -> HttpClient {
+	Client::builder().build(HttpsConnector::new(1))
+}
+
+/// Create the API and the worker of the HTTP requests of one offchain worker run.
+pub fn http(client: HttpClient) -> (HttpApi, HttpWorker) {
+	let (to_worker, from_api) = mpsc::unbounded();
+	let (to_api, from_worker) = std_mpsc::channel();
+
+	let api = HttpApi {
+		to_worker,
+		from_worker,
+		next_id: 0,
+		requests: BTreeMap::new(),
+	};
+	let worker = HttpWorker {
+		to_api,
+		from_api,
+		client,
+		requests: FuturesUnordered::new(),
+		max_response_body_size: MAX_RESPONSE_BODY_SIZE,
+	};
+
+	(api, worker)
+}
+
+/// Messages from the API to the worker.
+enum ApiToWorker {
+	/// Perform the given request.
+	Dispatch {
+		id: HttpRequestId,
+		request: Request<Body>,
+	},
+}
+
+/// Messages from the worker to the API.
+enum WorkerToApi {
+	/// The head of the response was received, the body will follow on `body`.
+	Response {
+		id: HttpRequestId,
+		status: u16,
+		headers: Vec<(Vec<u8>, Vec<u8>)>,
+		body: std_mpsc::Receiver<Result<Vec<u8>, ()>>,
+	},
+	/// The request failed before a response was received.
+	Fail {
+		id: HttpRequestId,
+		error: hyper::Error,
+	},
+}
+
+/// State of a request known to the API.
+enum HttpApiRequest {
+	/// Headers may still be added. The body is sent once the request is dispatched.
+	NotDispatched(Request<Body>, mpsc::UnboundedSender<Vec<u8>>),
+	/// Handed over to the worker. The body sender is `None` once the body is finished.
+	Dispatched(Option<mpsc::UnboundedSender<Vec<u8>>>),
+	/// The head of the response was received.
+	Response(HttpApiResponse),
+	/// The request failed.
+	Fail,
+}
+
+/// A response whose body is being read.
+struct HttpApiResponse {
+	status: u16,
+	headers: Vec<(Vec<u8>, Vec<u8>)>,
+	body: std_mpsc::Receiver<Result<Vec<u8>, ()>>,
+	/// The part of the last received chunk that wasn't read yet.
+	buffer: Vec<u8>,
+}
+
+/// The HTTP part of the offchain externalities.
+pub struct HttpApi {
+	to_worker: mpsc::UnboundedSender<ApiToWorker>,
+	from_worker: std_mpsc::Receiver<WorkerToApi>,
+	next_id: u16,
+	requests: BTreeMap<HttpRequestId, HttpApiRequest>,
+}
+
+impl HttpApi {
+	/// Start a request. It isn't sent before its body is written or its response awaited.
+	pub fn request_start(&mut self, method: &str, uri: &str) -> Result<HttpRequestId, ()> {
+		let method = Method::from_bytes(method.as_bytes()).map_err(|_| ())?;
+		let uri = uri.parse::<Uri>().map_err(|_| ())?;
+		if uri.scheme_str() != Some("http") && uri.scheme_str() != Some("https") {
+			warn!("Offchain worker HTTP request to unsupported URL: {}", uri);
+			return Err(());
+		}
+
+		let id = self.next_free_id()?;
+		let (body_sender, body_receiver) = mpsc::unbounded();
+		let body = Body::wrap_stream(body_receiver.map_err(|()| io::Error::from(io::ErrorKind::BrokenPipe)));
+		let mut request = Request::new(body);
+		*request.method_mut() = method;
+		*request.uri_mut() = uri;
+
+		self.requests.insert(id, HttpApiRequest::NotDispatched(request, body_sender));
+		Ok(id)
+	}
+
+	fn next_free_id(&mut self) -> Result<HttpRequestId, ()> {
+		for _ in 0..=u16::max_value() {
+			let id = HttpRequestId(self.next_id);
+			self.next_id = self.next_id.wrapping_add(1);
+			if !self.requests.contains_key(&id) {
+				return Ok(id);
+			}
+		}
+		Err(())
+	}
+
+	/// Add a header to a request that wasn't dispatched yet.
+	pub fn request_add_header(&mut self, id: HttpRequestId, name: &str, value: &str) -> Result<(), ()> {
+		let request = match self.requests.get_mut(&id) {
+			Some(HttpApiRequest::NotDispatched(request, _)) => request,
+			_ => return Err(()),
+		};
+		let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| ())?;
+		let value = HeaderValue::from_str(value).map_err(|_| ())?;
+		request.headers_mut().append(name, value);
+		Ok(())
+	}
+
+	/// Write a chunk of the body of a request, dispatching it if needed. An empty chunk finishes
+	/// the body.
+	pub fn request_write_body(&mut self, id: HttpRequestId, chunk: &[u8]) -> Result<(), HttpError> {
+		self.dispatch(id);
+		let body_sender = match self.requests.get_mut(&id) {
+			Some(HttpApiRequest::Dispatched(body_sender)) => body_sender,
+			_ => return Err(HttpError::IoError),
+		};
+
+		if chunk.is_empty() {
+			*body_sender = None;
+			return Ok(());
+		}
+
+		match body_sender {
+			Some(sender) => sender.unbounded_send(chunk.to_vec()).map_err(|_| HttpError::IoError),
+			None => Err(HttpError::IoError),
+		}
+	}
+
+	/// Hand the request over to the worker if it wasn't yet.
+	fn dispatch(&mut self, id: HttpRequestId) {
+		match self.requests.remove(&id) {
+			Some(HttpApiRequest::NotDispatched(request, body_sender)) => {
+				let state = match self.to_worker.unbounded_send(ApiToWorker::Dispatch { id, request }) {
+					Ok(()) => HttpApiRequest::Dispatched(Some(body_sender)),
+					Err(_) => HttpApiRequest::Fail,
+				};
+				self.requests.insert(id, state);
+			},
+			Some(other) => {
+				self.requests.insert(id, other);
+			},
+			None => {},
+		}
+	}
+
+	/// Wait for the responses to the given requests until the deadline, dispatching them and
+	/// finishing their bodies if needed.
+	pub fn response_wait(&mut self, ids: &[HttpRequestId], deadline: Option<Timestamp>) -> Vec<HttpRequestStatus> {
+		let deadline = deadline_instant(deadline);
+		for id in ids {
+			self.dispatch(*id);
+			if let Some(HttpApiRequest::Dispatched(body_sender)) = self.requests.get_mut(id) {
+				*body_sender = None;
+			}
+		}
+
+		loop {
+			let pending = ids.iter().any(|id| match self.requests.get(id) {
+				Some(HttpApiRequest::Dispatched(_)) => true,
+				_ => false,
+			});
+			if !pending || !self.process_message(deadline) {
+				return ids.iter().map(|id| self.status(*id)).collect();
+			}
+		}
+	}
+
+	fn status(&self, id: HttpRequestId) -> HttpRequestStatus {
+		match self.requests.get(&id) {
+			None => HttpRequestStatus::Unknown,
+			Some(HttpApiRequest::NotDispatched(..)) | Some(HttpApiRequest::Dispatched(_)) =>
+				HttpRequestStatus::DeadlineReached,
+			Some(HttpApiRequest::Response(response)) => HttpRequestStatus::Finished(response.status),
+			Some(HttpApiRequest::Fail) => HttpRequestStatus::Timeout,
+		}
+	}
+
+	/// Process one message of the worker. Returns `false` if none arrived before the deadline.
+	fn process_message(&mut self, deadline: Option<Instant>) -> bool {
+		match recv_until(&self.from_worker, deadline) {
+			Ok(WorkerToApi::Response { id, status, headers, body }) => {
+				if let Some(request) = self.requests.get_mut(&id) {
+					*request = HttpApiRequest::Response(HttpApiResponse {
+						status,
+						headers,
+						body,
+						buffer: Vec::new(),
+					});
+				}
+				true
+			},
+			Ok(WorkerToApi::Fail { id, error }) => {
+				debug!("Offchain worker HTTP request {:?} failed: {}", id, error);
+				if let Some(request) = self.requests.get_mut(&id) {
+					*request = HttpApiRequest::Fail;
+				}
+				true
+			},
+			Err(std_mpsc::RecvTimeoutError::Timeout) => false,
+			Err(std_mpsc::RecvTimeoutError::Disconnected) => {
+				for request in self.requests.values_mut() {
+					if let HttpApiRequest::Dispatched(_) = request {
+						*request = HttpApiRequest::Fail;
+					}
+				}
+				false
+			},
+		}
+	}
+
+	/// The headers of the response to a request, empty if there is no response yet.
+	pub fn response_headers(&mut self, id: HttpRequestId) -> Vec<(Vec<u8>, Vec<u8>)> {
+		match self.requests.get(&id) {
+			Some(HttpApiRequest::Response(response)) => response.headers.clone(),
+			_ => Vec::new(),
+		}
+	}
+
+	/// Read a chunk of the body of the response to a request, waiting for the response if
+	/// needed. Returns `0` at the end of the body, after which the request is forgotten.
+	pub fn response_read_body(
+		&mut self,
+		id: HttpRequestId,
+		buffer: &mut [u8],
+		deadline: Option<Timestamp>,
+	) -> Result<usize, HttpError> {
+		let deadline = deadline_instant(deadline);
+		loop {
+			match self.requests.get(&id) {
+				Some(HttpApiRequest::Dispatched(_)) => if !self.process_message(deadline) {
+					return match self.requests.get(&id) {
+						Some(HttpApiRequest::Dispatched(_)) => Err(HttpError::DeadlineReached),
+						_ => Err(HttpError::IoError),
+					};
+				},
+				Some(HttpApiRequest::Response(_)) => break,
+				Some(HttpApiRequest::Fail) => {
+					self.requests.remove(&id);
+					return Err(HttpError::IoError);
+				},
+				Some(HttpApiRequest::NotDispatched(..)) | None => return Err(HttpError::IoError),
+			}
+		}
+
+		let response = match self.requests.get_mut(&id) {
+			Some(HttpApiRequest::Response(response)) => response,
+			_ => return Err(HttpError::IoError),
+		};
+		while response.buffer.is_empty() {
+			match recv_until(&response.body, deadline) {
+				Ok(Ok(chunk)) => response.buffer = chunk,
+				Ok(Err(())) => {
+					self.requests.remove(&id);
+					return Err(HttpError::IoError);
+				},
+				Err(std_mpsc::RecvTimeoutError::Timeout) => return Err(HttpError::DeadlineReached),
+				Err(std_mpsc::RecvTimeoutError::Disconnected) => {
+					self.requests.remove(&id);
+					return Ok(0);
+				},
+			}
+		}
+
+		let read = std::cmp::min(buffer.len(), response.buffer.len());
+		buffer[..read].copy_from_slice(&response.buffer[..read]);
+		response.buffer.drain(..read);
+		Ok(read)
+	}
+}
+
+/// Convert a deadline of the runtime to an instant.
+fn deadline_instant(deadline: Option<Timestamp>) -> Option<Instant> {
+	deadline.map(|deadline| {
+		let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)
+			.map(|now| now.as_secs() * 1000 + u64::from(now.subsec_millis()))
+			.unwrap_or(0);
+		Instant::now() + Duration::from_millis(deadline.unix_millis().saturating_sub(now))
+	})
+}
+
+/// Receive a message, blocking until the deadline at most.
+fn recv_until<T>(receiver: &std_mpsc::Receiver<T>, deadline: Option<Instant>) -> Result<T, std_mpsc::RecvTimeoutError> {
+	match deadline {
+		None => receiver.recv().map_err(|_| std_mpsc::RecvTimeoutError::Disconnected),
+		Some(deadline) => {
+			let now = Instant::now();
+			if deadline <= now {
+				return receiver.try_recv().map_err(|e| match e {
+					std_mpsc::TryRecvError::Empty => std_mpsc::RecvTimeoutError::Timeout,
+					std_mpsc::TryRecvError::Disconnected => std_mpsc::RecvTimeoutError::Disconnected,
+				});
+			}
+			receiver.recv_timeout(deadline - now)
+		},
+	}
+}
+
+/// Performs the requests dispatched by the [`HttpApi`]. Finishes once the API is dropped.
+pub struct HttpWorker {
+	to_api: std_mpsc::Sender<WorkerToApi>,
+	from_api: mpsc::UnboundedReceiver<ApiToWorker>,
+	client: HttpClient,
+	requests: FuturesUnordered<Box<dyn Future<Item = (), Error = ()> + Send>>,
+	max_response_body_size: usize,
+}
+
+impl HttpWorker {
+	fn request(&self, id: HttpRequestId, request: Request<Body>) -> impl Future<Item = (), Error = ()> {
+		let to_api = self.to_api.clone();
+		let max_body_size = self.max_response_body_size;
+		self.client.request(request).then(move |result| match result {
+			Ok(response) => {
+				let (head, body) = response.into_parts();
+				let headers = head.headers.iter()
+					.map(|(name, value)| (name.as_str().as_bytes().to_vec(), value.as_bytes().to_vec()))
+					.collect();
+				let (body_sender, body_receiver) = std_mpsc::channel();
+				let _ = to_api.send(WorkerToApi::Response {
+					id,
+					status: head.status.as_u16(),
+					headers,
+					body: body_receiver,
+				});
+
+				let chunks = body_sender.clone();
+				let mut body_size = 0;
+				Either::A(body
+					.map_err(move |error| debug!("Offchain worker HTTP request {:?} failed: {}", id, error))
+					.for_each(move |chunk| {
+						body_size += chunk.len();
+						if body_size > max_body_size {
+							debug!("Offchain worker HTTP response {:?} is larger than {} bytes", id, max_body_size);
+							return Err(());
+						}
+						let _ = chunks.send(Ok(chunk.to_vec()));
+						Ok(())
+					})
+					.or_else(move |()| {
+						let _ = body_sender.send(Err(()));
+						Ok::<_, ()>(())
+					}))
+			},
+			Err(error) => {
+				let _ = to_api.send(WorkerToApi::Fail { id, error });
+				Either::B(future::ok(()))
+			},
+		})
+	}
+}
+
+impl Future for HttpWorker {
+	type Item = ();
+	type Error = ();
+
+	fn poll(&mut self) -> Poll<(), ()> {
+		loop {
+			match self.from_api.poll() {
+				Ok(Async::Ready(Some(ApiToWorker::Dispatch { id, request }))) => {
+					let request = Box::new(self.request(id, request));
+					self.requests.push(request);
+				},
+				Ok(Async::Ready(None)) | Err(()) => return Ok(Async::Ready(())),
+				Ok(Async::NotReady) => break,
+			}
+		}
+
+		// Failures were already reported to the API.
+		while let Ok(Async::Ready(Some(()))) = self.requests.poll() {}
+
+		Ok(Async::NotReady)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{io::{Read, Write}, net::TcpListener, thread};
+
+	fn deadline_in(millis: u64) -> Option<Timestamp> {
+		let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap();
+		Some(Timestamp::from_unix_millis(now.as_secs() * 1000 + u64::from(now.subsec_millis()) + millis))
+	}
+
+	/// Serve a single request with a canned response, returning the raw request.
+	fn serve_once(response: &'static [u8]) -> (String, thread::JoinHandle<Vec<u8>>) {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let url = format!("http://{}/price", listener.local_addr().unwrap());
+		let server = thread::spawn(move || {
+			let (mut stream, _) = listener.accept().unwrap();
+			let mut request = Vec::new();
+			let mut buffer = [0; 1024];
+			while !request.ends_with(b"hi") {
+				let read = stream.read(&mut buffer).unwrap();
+				request.extend_from_slice(&buffer[..read]);
+			}
+			stream.write_all(response).unwrap();
+			request
+		});
+		(url, server)
+	}
+
+	#[test]
+	fn should_perform_request() {
+		let (url, server) = serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nX-Test: yes\r\n\r\nhello");
+		let mut runtime = tokio::runtime::Runtime::new().unwrap();
+		let (mut api, worker) = http(http_client());
+		runtime.spawn(worker);
+
+		let id = api.request_start("POST", &url).unwrap();
+		api.request_add_header(id, "Content-Length", "2").unwrap();
+		api.request_write_body(id, b"hi").unwrap();
+		assert_eq!(api.request_add_header(id, "X-Late", "1"), Err(()));
+		api.request_write_body(id, b"").unwrap();
+
+		assert_eq!(api.response_wait(&[id], deadline_in(5000)), vec![HttpRequestStatus::Finished(200)]);
+		assert!(api.response_headers(id).contains(&(b"x-test".to_vec(), b"yes".to_vec())));
+
+		let mut body = Vec::new();
+		let mut buffer = [0; 2];
+		loop {
+			let read = api.response_read_body(id, &mut buffer, deadline_in(5000)).unwrap();
+			if read == 0 {
+				break;
+			}
+			body.extend_from_slice(&buffer[..read]);
+		}
+		assert_eq!(body, b"hello".to_vec());
+		assert_eq!(api.response_wait(&[id], None), vec![HttpRequestStatus::Unknown]);
+
+		let request = String::from_utf8(server.join().unwrap()).unwrap();
+		assert!(request.starts_with("POST /price HTTP/1.1\r\n"));
+		assert!(request.to_lowercase().contains("content-length: 2\r\n"));
+	}
+
+	#[test]
+	fn should_fail_reading_too_large_responses() {
+		let (url, server) = serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello");
+		let mut runtime = tokio::runtime::Runtime::new().unwrap();
+		let (mut api, mut worker) = http(http_client());
+		worker.max_response_body_size = 4;
+		runtime.spawn(worker);
+
+		let id = api.request_start("POST", &url).unwrap();
+		api.request_write_body(id, b"hi").unwrap();
+		assert_eq!(api.response_wait(&[id], deadline_in(5000)), vec![HttpRequestStatus::Finished(200)]);
+		assert_eq!(api.response_read_body(id, &mut [0; 5], deadline_in(5000)), Err(HttpError::IoError));
+		server.join().unwrap();
+	}
+
+	#[test]
+	fn should_reject_unsupported_requests() {
+		let (mut api, _worker) = http(http_client());
+
+		assert!(api.request_start("GET", "https://example.com").is_ok());
+		assert_eq!(api.request_start("GET", "ftp://example.com"), Err(()));
+		assert_eq!(api.request_start("GET", "not a url"), Err(()));
+		assert_eq!(
+			api.response_wait(&[HttpRequestId(42)], deadline_in(0)),
+			vec![HttpRequestStatus::Unknown],
+		);
+		assert_eq!(
+			api.response_read_body(HttpRequestId(42), &mut [0; 1], None),
+			Err(HttpError::IoError),
+		);
+	}
+}
@@ -37,9 +37,10 @@ use std::{
 	fmt,
 	marker::PhantomData,
 	sync::Arc,
+	sync::atomic::{AtomicUsize, Ordering},
 };
 
-use client::runtime_api::ApiExt;
+use client::{blockchain::HeaderBackend, runtime_api::ApiExt};
 use log::{debug, warn};
 use primitives::{
 	ExecutionContext,
@@ -71,6 +72,44 @@ pub trait AuthorityKeyProvider<Block: traits::Block>: Clone + 'static {
 
 	/// Returns currently configured finality gadget authority key.
 	fn fg_authority_key(&self, block_id: &BlockId<Block>) -> Option<Self::FinalityPair>;
+
+	/// Returns currently configured authority key, if it is held by an external signer.
+	fn external_authority_key(&self, _block_id: &BlockId<Block>) -> Option<ExternalKey> {
+		None
+	}
+
+	/// Returns currently configured finality gadget authority key, if it is held by an external
+	/// signer.
+	fn external_fg_authority_key(&self, _block_id: &BlockId<Block>) -> Option<ExternalKey> {
+		None
+	}
+
+	/// Returns the raw public keys of all keys of the given type held by the node's keystore.
+	///
+	/// Returns an error if no keystore is configured.
+	fn keystore_public_keys(&self, _key_type: crypto::KeyTypeId) -> Result<Vec<Vec<u8>>, ()> {
+		Err(())
+	}
+
+	/// Signs `data` with the key `public` of the given type held by the node's keystore.
+	///
+	/// Returns an error if no keystore is configured or it doesn't hold the key.
+	fn keystore_sign(
+		&self,
+		_key_type: crypto::KeyTypeId,
+		_public: &[u8],
+		_data: &[u8],
+	) -> Result<Vec<u8>, ()> {
+		Err(())
+	}
+}
+
+/// A key whose secret is held by an external signer.
+pub struct ExternalKey {
+	/// The raw public key.
+	pub public: Vec<u8>,
+	/// Signs the given data with the key and returns the raw signature.
+	pub sign: Box<dyn Fn(&[u8]) -> Result<Vec<u8>, ()> + Send>,
 }
 
 /// An offchain workers manager.
@@ -84,6 +123,11 @@ pub struct OffchainWorkers<
 	db: Storage,
 	authority_key: KeyProvider,
 	keys_password: crypto::Protected<String>,
+	http_client: api::HttpClient,
+	/// Number of offchain workers that are currently running.
+	running: Arc<AtomicUsize>,
+	/// Maximum number of offchain workers running at the same time, `None` if unlimited.
+	max_concurrency: Option<usize>,
 	_block: PhantomData<Block>,
 }
 
@@ -105,9 +149,20 @@ impl<Client, Storage, KeyProvider, Block: traits::Block> OffchainWorkers<
 			db,
 			authority_key,
 			keys_password,
+			http_client: api::http_client(),
+			running: Arc::new(AtomicUsize::new(0)),
+			max_concurrency: None,
 			_block: PhantomData,
 		}
 	}
+
+	/// Limit the number of offchain workers running at the same time.
+	///
+	/// Workers for blocks imported while the limit is reached are skipped.
+	pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+		self.max_concurrency = Some(max_concurrency);
+		self
+	}
 }
 
 impl<Client, Storage, KeyProvider, Block: traits::Block> fmt::Debug for OffchainWorkers<
@@ -128,7 +183,7 @@ impl<Client, Storage, KeyProvider, Block> OffchainWorkers<
 	Block,
 > where
 	Block: traits::Block,
-	Client: ProvideRuntimeApi + Send + Sync + 'static,
+	Client: ProvideRuntimeApi + HeaderBackend<Block> + Send + Sync + 'static,
 	Client::Api: OffchainWorkerApi<Block>,
 	KeyProvider: AuthorityKeyProvider<Block> + Send,
 	Storage: client::backend::OffchainStorage + 'static,
@@ -138,29 +193,55 @@ impl<Client, Storage, KeyProvider, Block> OffchainWorkers<
 	pub fn on_block_imported<A>(
 		&self,
 		number: &<Block::Header as traits::Header>::Number,
+		hash: &Block::Hash,
 		pool: &Arc<Pool<A>>,
 		network_state: Arc<dyn NetworkStateInfo + Send + Sync>,
 	) -> impl Future<Item = (), Error = ()> where
 		A: ChainApi<Block=Block> + 'static,
 	{
 		let runtime = self.client.runtime_api();
-		let at = BlockId::number(*number);
+		// The block isn't necessarily the best one, so it is only known by its hash.
+		let at = BlockId::hash(*hash);
 		let has_api = runtime.has_api::<dyn OffchainWorkerApi<Block>>(&at);
 		debug!("Checking offchain workers at {:?}: {:?}", at, has_api);
 
 		if has_api.unwrap_or(false) {
+			let running = match RunningGuard::acquire(&self.running, self.max_concurrency) {
+				Some(running) => running,
+				None => {
+					warn!(
+						"Skipping offchain workers at {:?}: {} workers are still running",
+						at,
+						self.running.load(Ordering::SeqCst),
+					);
+					return futures::future::Either::B(futures::future::ok(()));
+				},
+			};
+			let headers = self.client.clone();
+			let ancestry = api::Ancestry {
+				number: *number,
+				hash: *hash,
+				parent: Box::new(move |hash: &Block::Hash| {
+					headers.header(BlockId::hash(*hash)).ok()
+						.and_then(|header| header)
+						.map(|header| *traits::Header::parent_hash(&header))
+				}),
+			};
 			let (api, runner) = api::AsyncApi::new(
 				pool.clone(),
 				self.db.clone(),
 				self.keys_password.clone(),
 				self.authority_key.clone(),
 				at.clone(),
+				ancestry,
 				network_state.clone(),
+				self.http_client.clone(),
 			);
 			debug!("Spawning offchain workers at {:?}", at);
 			let number = *number;
 			let client = self.client.clone();
 			spawn_worker(move || {
+				let _running = running;
 				let runtime = client.runtime_api();
 				let api = Box::new(api);
 				debug!("Running offchain workers at {:?}", at);
@@ -180,6 +261,50 @@ impl<Client, Storage, KeyProvider, Block> OffchainWorkers<
 	}
 }
 
+impl<Client, Storage, KeyProvider, Block> OffchainWorkers<
+	Client,
+	Storage,
+	KeyProvider,
+	Block,
+> where
+	Block: traits::Block,
+	Client: HeaderBackend<Block>,
+	Storage: client::backend::OffchainStorage,
+{
+	/// Prune the fork-aware local storage of the blocks up to the finalized block `number`.
+	pub fn on_block_finalized(&self, number: &<Block::Header as traits::Header>::Number) {
+		let client = &self.client;
+		api::prune_local_storage::<_, Block>(&mut self.db.clone(), *number, |number| {
+			client.hash(number).ok().and_then(|hash| hash)
+		});
+	}
+}
+
+/// Counts a running offchain worker until it is dropped.
+struct RunningGuard(Arc<AtomicUsize>);
+
+impl RunningGuard {
+	/// Count a new running worker, unless `max` workers are running already.
+	fn acquire(running: &Arc<AtomicUsize>, max: Option<usize>) -> Option<Self> {
+		let mut current = running.load(Ordering::SeqCst);
+		loop {
+			if max.map_or(false, |max| current >= max) {
+				return None;
+			}
+			match running.compare_exchange(current, current + 1, Ordering::SeqCst, Ordering::SeqCst) {
+				Ok(_) => return Some(RunningGuard(running.clone())),
+				Err(actual) => current = actual,
+			}
+		}
+	}
+}
+
+impl Drop for RunningGuard {
+	fn drop(&mut self) {
+		self.0.fetch_sub(1, Ordering::SeqCst);
+	}
+}
+
 /// Spawns a new offchain worker.
 ///
 /// We spawn offchain workers for each block in a separate thread,
@@ -251,10 +376,11 @@ mod tests {
 		let pool = Arc::new(Pool::new(Default::default(), ::transaction_pool::ChainApi::new(client.clone())));
 		let db = client_db::offchain::LocalStorage::new_test();
 		let mock = Arc::new(MockNetworkStateInfo());
+		let genesis = client.info().chain.genesis_hash;
 
 		// when
 		let offchain = OffchainWorkers::new(client, db, TestProvider::default(), "".to_owned().into());
-		runtime.executor().spawn(offchain.on_block_imported(&0u64, &pool, mock.clone()));
+		runtime.executor().spawn(offchain.on_block_imported(&0u64, &genesis, &pool, mock.clone()));
 
 		// then
 		runtime.shutdown_on_idle().wait().unwrap();
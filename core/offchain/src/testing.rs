@@ -22,6 +22,7 @@ use std::{
 };
 use client::backend::OffchainStorage;
 use parking_lot::RwLock;
+use primitives::crypto::KeyTypeId;
 use primitives::offchain::{
 	self,
 	HttpError,
@@ -185,6 +186,28 @@ impl offchain::Externalities for TestOffchainExt {
 		unimplemented!("not needed in tests so far")
 	}
 
+	fn vrf_sign(
+		&mut self,
+		_key: CryptoKey,
+		_context: &[u8],
+		_message: &[u8],
+	) -> Result<primitives::sr25519::VrfSignature, ()> {
+		unimplemented!("not needed in tests so far")
+	}
+
+	fn keystore_public_keys(&mut self, _key_type: KeyTypeId) -> Result<Vec<Vec<u8>>, ()> {
+		unimplemented!("not needed in tests so far")
+	}
+
+	fn keystore_sign(
+		&mut self,
+		_key_type: KeyTypeId,
+		_public: &[u8],
+		_data: &[u8],
+	) -> Result<Vec<u8>, ()> {
+		unimplemented!("not needed in tests so far")
+	}
+
 	fn timestamp(&mut self) -> Timestamp {
 		unimplemented!("not needed in tests so far")
 	}
@@ -0,0 +1,165 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Batched signature verification.
+//!
+//! Signatures pushed to a [`BatchVerifier`] are verified by a pool of worker threads while the
+//! caller carries on. Only the combined result is reported, once the batch is finished.
+
+use rstd::convert::TryFrom;
+#[cfg(feature = "std")]
+use std::{sync::{Arc, Mutex, mpsc}, thread};
+#[cfg(feature = "std")]
+use crate::{ed25519, sr25519, crypto::Pair};
+
+/// The signature schemes a signature in a batch is checked against.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+#[repr(C)]
+pub enum SignatureKind {
+	/// An ed25519 signature.
+	Ed25519 = 0,
+	/// An sr25519 signature.
+	Sr25519 = 1,
+	/// Either an sr25519 or an ed25519 signature made with the same public key bytes.
+	Sr25519OrEd25519 = 2,
+}
+
+impl TryFrom<u32> for SignatureKind {
+	type Error = ();
+
+	fn try_from(kind: u32) -> Result<Self, Self::Error> {
+		match kind {
+			0 => Ok(SignatureKind::Ed25519),
+			1 => Ok(SignatureKind::Sr25519),
+			2 => Ok(SignatureKind::Sr25519OrEd25519),
+			_ => Err(()),
+		}
+	}
+}
+
+impl From<SignatureKind> for u32 {
+	fn from(kind: SignatureKind) -> Self {
+		kind as u32
+	}
+}
+
+/// Number of worker threads verifying the signatures of a batch.
+#[cfg(feature = "std")]
+const WORKERS: usize = 4;
+
+#[cfg(feature = "std")]
+struct Job {
+	kind: SignatureKind,
+	signature: [u8; 64],
+	message: Vec<u8>,
+	public: [u8; 32],
+}
+
+#[cfg(feature = "std")]
+impl Job {
+	fn verify(&self) -> bool {
+		let ed25519 = || ed25519::Pair::verify_weak(&self.signature, &self.message, &self.public);
+		let sr25519 = || sr25519::Pair::verify_weak(&self.signature, &self.message, &self.public);
+		match self.kind {
+			SignatureKind::Ed25519 => ed25519(),
+			SignatureKind::Sr25519 => sr25519(),
+			SignatureKind::Sr25519OrEd25519 => sr25519() || ed25519(),
+		}
+	}
+}
+
+/// A batch of signatures that are verified in the background.
+#[cfg(feature = "std")]
+pub struct BatchVerifier {
+	sender: mpsc::Sender<Job>,
+	workers: Vec<thread::JoinHandle<bool>>,
+}
+
+#[cfg(feature = "std")]
+impl BatchVerifier {
+	/// Start a new batch.
+	pub fn new() -> Self {
+		let (sender, receiver) = mpsc::channel::<Job>();
+		let receiver = Arc::new(Mutex::new(receiver));
+		let workers = (0..WORKERS)
+			.map(|_| {
+				let receiver = receiver.clone();
+				thread::spawn(move || {
+					let mut valid = true;
+					loop {
+						let job = match receiver.lock() {
+							Ok(receiver) => receiver.recv(),
+							Err(_) => return false,
+						};
+						match job {
+							// Keep draining the queue, so that the batch finishes promptly.
+							Ok(job) => valid = valid && job.verify(),
+							Err(_) => return valid,
+						}
+					}
+				})
+			})
+			.collect();
+
+		BatchVerifier { sender, workers }
+	}
+
+	/// Queue a signature of the given kind for verification.
+	pub fn push(&mut self, kind: SignatureKind, signature: [u8; 64], message: Vec<u8>, public: [u8; 32]) {
+		let job = Job { kind, signature, message, public };
+		if let Err(mpsc::SendError(job)) = self.sender.send(job) {
+			// All workers are gone, which only happens if one of them panicked. Verifying here
+			// keeps the result of the batch correct.
+			let _ = job.verify();
+		}
+	}
+
+	/// Wait for all queued signatures to be verified and return whether all of them are valid.
+	pub fn verify(self) -> bool {
+		let BatchVerifier { sender, workers } = self;
+		drop(sender);
+		workers.into_iter().fold(true, |valid, worker| worker.join().unwrap_or(false) && valid)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn batch_reports_whether_all_signatures_are_valid() {
+		let ed = ed25519::Pair::from_seed(&[1; 32]);
+		let sr = sr25519::Pair::from_seed(&[2; 32]);
+		let message = b"hello".to_vec();
+
+		let mut batch = BatchVerifier::new();
+		batch.push(SignatureKind::Ed25519, ed.sign(&message).0, message.clone(), ed.public().0);
+		batch.push(SignatureKind::Sr25519, sr.sign(&message).0, message.clone(), sr.public().0);
+		batch.push(SignatureKind::Sr25519OrEd25519, ed.sign(&message).0, message.clone(), ed.public().0);
+		assert!(batch.verify());
+
+		let mut batch = BatchVerifier::new();
+		batch.push(SignatureKind::Ed25519, ed.sign(&message).0, message.clone(), ed.public().0);
+		batch.push(SignatureKind::Sr25519, ed.sign(&message).0, message.clone(), ed.public().0);
+		assert!(!batch.verify());
+	}
+
+	#[test]
+	fn empty_batch_is_valid() {
+		assert!(BatchVerifier::new().verify());
+	}
+}
@@ -199,6 +199,11 @@ impl DeriveJunction {
 	}
 }
 
+/// Parses a single path item of a secret URI, without its leading `/`.
+///
+/// An item starting with `/` is a hard junction, otherwise it is soft. Items that parse as `u64`
+/// are encoded as such, all others as SCALE encoded strings. This is the encoding every tool
+/// has to use to derive the same keys from the same URI.
 #[cfg(feature = "std")]
 impl<T: AsRef<str>> From<T> for DeriveJunction {
 	fn from(j: T) -> DeriveJunction {
@@ -553,6 +558,8 @@ pub trait Pair: TypedKey + Sized + Clone + Send + Sync + 'static {
 	///
 	/// - If `s` is a possibly `0x` prefixed 64-digit hex string, then it will be interpreted
 	/// directly as a `MiniSecretKey` (aka "seed" in `subkey`).
+	/// - If `s` is a `0x` prefixed hex seed followed by a path, the key is derived from the seed
+	/// along that path. Seeds don't take a password, so any password is ignored.
 	/// - If `s` is a valid BIP-39 key phrase of 12, 15, 18, 21 or 24 words, then the key will
 	/// be derived from it. In this case:
 	///   - the phrase may be followed by one or more items delimited by `/` characters.
@@ -592,8 +599,17 @@ pub trait Pair: TypedKey + Sized + Clone + Send + Sync + 'static {
 			.expect("constructed from known-good static value; qed");
 		let path = re_junction.captures_iter(&cap["path"])
 			.map(|f| DeriveJunction::from(&f[1]));
+		let phrase = cap.name("phrase").map(|r| r.as_str()).unwrap_or(DEV_PHRASE);
+
+		if phrase.starts_with("0x") {
+			let seed = hex::decode(&phrase[2..]).map_err(|_| SecretStringError::InvalidSeed)?;
+			return Self::from_seed_slice(&seed)?
+				.derive(path)
+				.map_err(|_| SecretStringError::InvalidPath)
+		}
+
 		Self::from_standard_components(
-			cap.name("phrase").map(|r| r.as_str()).unwrap_or(DEV_PHRASE),
+			phrase,
 			password_override.or_else(|| cap.name("password").map(|m| m.as_str())),
 			path,
 		)
@@ -617,6 +633,9 @@ pub mod key_types {
 
 	/// SR25519 public key.
 	pub const SR25519: KeyTypeId = 20;
+
+	/// ECDSA (secp256k1) public key.
+	pub const ECDSA: KeyTypeId = 30;
 }
 
 /// A trait for something that has a key type ID.
@@ -638,6 +657,7 @@ mod tests {
 		GeneratedFromPhrase{phrase: String, password: Option<String>},
 		Standard{phrase: String, password: Option<String>, path: Vec<DeriveJunction>},
 		Seed(Vec<u8>),
+		Derived{base: Box<TestPair>, path: Vec<DeriveJunction>},
 	}
 
 	#[derive(Clone, PartialEq, Eq, Hash)]
@@ -679,10 +699,10 @@ mod tests {
 				password: password.map(Into::into)
 			}, []))
 		}
-		fn derive<Iter: Iterator<Item=DeriveJunction>>(&self, _path: Iter)
+		fn derive<Iter: Iterator<Item=DeriveJunction>>(&self, path: Iter)
 			-> Result<Self, Self::DeriveError>
 		{
-			Err(())
+			Ok(TestPair::Derived { base: Box::new(self.clone()), path: path.collect() })
 		}
 		fn from_seed(_seed: &<TestPair as Pair>::Seed) -> Self { TestPair::Seed(vec![]) }
 		fn sign(&self, _message: &[u8]) -> Self::Signature { [] }
@@ -733,6 +753,26 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn interpret_std_seed_with_path_should_work() {
+		let seed = TestPair::Seed(hex!["0123456789abcdef"][..].to_owned());
+		assert_eq!(
+			TestPair::from_string("0x0123456789abcdef//1/DOT", None),
+			Ok(TestPair::Derived {
+				base: Box::new(seed.clone()),
+				path: vec![DeriveJunction::hard(1), DeriveJunction::soft("DOT")],
+			})
+		);
+		assert_eq!(
+			TestPair::from_string("0x0123456789abcdef//1///password", None),
+			TestPair::from_string("0x0123456789abcdef//1", None),
+		);
+		assert_eq!(
+			TestPair::from_string("0x0123456789abcdeg//1", None),
+			Err(SecretStringError::InvalidSeed),
+		);
+	}
+
 	#[test]
 	fn password_override_should_work() {
 		assert_eq!(
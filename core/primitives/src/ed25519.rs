@@ -273,6 +273,24 @@ pub enum PublicError {
 	InvalidChecksum,
 }
 
+#[cfg(feature = "arbitrary")]
+impl quickcheck::Arbitrary for Public {
+	fn arbitrary<G: quickcheck::Gen>(g: &mut G) -> Self {
+		let mut data = [0u8; 32];
+		g.fill_bytes(&mut data);
+		Public(data)
+	}
+}
+
+#[cfg(feature = "arbitrary")]
+impl quickcheck::Arbitrary for Signature {
+	fn arbitrary<G: quickcheck::Gen>(g: &mut G) -> Self {
+		let mut data = [0u8; 64];
+		g.fill_bytes(&mut data);
+		Signature(data)
+	}
+}
+
 impl Public {
 	/// A new instance from the given 32-byte `data`.
 	///
@@ -507,6 +525,15 @@ mod test {
 	use hex_literal::hex;
 	use crate::crypto::DEV_PHRASE;
 
+	#[test]
+	fn seed_with_path_should_match_phrase_with_path() {
+		let (_, seed) = Pair::from_phrase(DEV_PHRASE, None).unwrap();
+		assert_eq!(
+			Pair::from_string(&format!("0x{}//Alice//1", hex::encode(&seed)), None).unwrap().public(),
+			Pair::from_string("//Alice//1", None).unwrap().public(),
+		);
+	}
+
 	#[test]
 	fn default_phrase_should_be_used() {
 		assert_eq!(
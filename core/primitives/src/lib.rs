@@ -54,6 +54,10 @@ pub mod u32_trait;
 
 pub mod ed25519;
 pub mod sr25519;
+pub mod ecdsa;
+pub mod batch_verifier;
+#[cfg(feature = "std")]
+pub mod tracing;
 pub mod hash;
 mod hasher;
 pub mod offchain;
@@ -58,6 +58,12 @@ impl From<StorageKind> for u32 {
 	}
 }
 
+/// Prefix of the `PERSISTENT` storage in the offchain database.
+///
+/// The offchain index written by the runtime during block import is kept under the same prefix,
+/// so offchain workers read it as `PERSISTENT` storage.
+pub const STORAGE_PREFIX: &[u8] = b"storage";
+
 /// A type of supported crypto.
 #[derive(Clone, Copy, PartialEq, Eq, Encode, Decode)]
 #[cfg_attr(feature = "std", derive(Debug))]
@@ -353,6 +359,32 @@ pub trait Externalities {
 	/// lengths are incorrect or `CryptoKind` does not match.
 	fn verify(&mut self, key: CryptoKey, msg: &[u8], signature: &[u8]) -> Result<bool, ()>;
 
+	/// Sign `message` in the given `context` with the VRF of the given sr25519 key.
+	///
+	/// Returns an error if `key` is not available or does not exist, or is not a local sr25519
+	/// key.
+	fn vrf_sign(
+		&mut self,
+		key: CryptoKey,
+		context: &[u8],
+		message: &[u8],
+	) -> Result<crate::sr25519::VrfSignature, ()>;
+
+	/// Returns the raw public keys of all keys of the given type held by the node's keystore.
+	///
+	/// Returns an error if no keystore is available.
+	fn keystore_public_keys(&mut self, key_type: crypto::KeyTypeId) -> Result<Vec<Vec<u8>>, ()>;
+
+	/// Sign `data` with the key `public` of the given type held by the node's keystore.
+	///
+	/// Returns an error if no keystore is available or it doesn't hold the key.
+	fn keystore_sign(
+		&mut self,
+		key_type: crypto::KeyTypeId,
+		public: &[u8],
+		data: &[u8],
+	) -> Result<Vec<u8>, ()>;
+
 	/// Returns current UNIX timestamp (in millis)
 	fn timestamp(&mut self) -> Timestamp;
 
@@ -494,6 +526,28 @@ impl<T: Externalities + ?Sized> Externalities for Box<T> {
 		(&mut **self).verify(key, msg, signature)
 	}
 
+	fn vrf_sign(
+		&mut self,
+		key: CryptoKey,
+		context: &[u8],
+		message: &[u8],
+	) -> Result<crate::sr25519::VrfSignature, ()> {
+		(&mut **self).vrf_sign(key, context, message)
+	}
+
+	fn keystore_public_keys(&mut self, key_type: crypto::KeyTypeId) -> Result<Vec<Vec<u8>>, ()> {
+		(&mut **self).keystore_public_keys(key_type)
+	}
+
+	fn keystore_sign(
+		&mut self,
+		key_type: crypto::KeyTypeId,
+		public: &[u8],
+		data: &[u8],
+	) -> Result<Vec<u8>, ()> {
+		(&mut **self).keystore_sign(key_type, public, data)
+	}
+
 	fn timestamp(&mut self) -> Timestamp {
 		(&mut **self).timestamp()
 	}
@@ -39,6 +39,8 @@ use codec::{Encode, Decode};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 #[cfg(feature = "std")]
 use schnorrkel::keys::{MINI_SECRET_KEY_LENGTH, SECRET_KEY_LENGTH};
+#[cfg(feature = "std")]
+use schnorrkel::vrf::{VRFOutput, VRFProof};
 
 // signing context
 #[cfg(feature = "std")]
@@ -285,6 +287,24 @@ impl Derive for Public {
 	}
 }
 
+#[cfg(feature = "arbitrary")]
+impl quickcheck::Arbitrary for Public {
+	fn arbitrary<G: quickcheck::Gen>(g: &mut G) -> Self {
+		let mut data = [0u8; 32];
+		g.fill_bytes(&mut data);
+		Public(data)
+	}
+}
+
+#[cfg(feature = "arbitrary")]
+impl quickcheck::Arbitrary for Signature {
+	fn arbitrary<G: quickcheck::Gen>(g: &mut G) -> Self {
+		let mut data = [0u8; 64];
+		g.fill_bytes(&mut data);
+		Signature(data)
+	}
+}
+
 impl Public {
 	/// A new instance from the given 32-byte `data`.
 	///
@@ -508,8 +528,53 @@ impl TraitPair for Pair {
 	}
 }
 
+/// The length of a VRF output.
+pub const VRF_OUTPUT_LENGTH: usize = 32;
+
+/// The length of a VRF proof.
+pub const VRF_PROOF_LENGTH: usize = 64;
+
+/// An sr25519 VRF signature: the VRF output together with the proof that it was computed
+/// correctly.
+#[derive(PartialEq, Eq, Clone, Default, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct VrfSignature {
+	/// The VRF output.
+	pub output: H256,
+	/// The proof of the output.
+	pub proof: H512,
+}
+
+/// Verify the VRF signature of `message` in the given `context` by `public`.
+///
+/// Returns the randomness derived from the VRF output if the signature is valid.
+#[cfg(feature = "std")]
+pub fn vrf_verify(
+	public: &[u8],
+	context: &[u8],
+	message: &[u8],
+	signature: &VrfSignature,
+) -> Option<[u8; 32]> {
+	let public = PublicKey::from_bytes(public).ok()?;
+	let output = VRFOutput::from_bytes(signature.output.as_bytes()).ok()?;
+	let proof = VRFProof::from_bytes(signature.proof.as_bytes()).ok()?;
+	let (inout, _) = public.vrf_verify(signing_context(context).bytes(message), &output, &proof).ok()?;
+	Some(inout.make_bytes::<[u8; 32]>(context))
+}
+
 #[cfg(feature = "std")]
 impl Pair {
+	/// Sign `message` in the given `context` with the VRF of this key pair.
+	///
+	/// The randomness derived from the output can be recovered with [`vrf_verify`].
+	pub fn vrf_sign(&self, context: &[u8], message: &[u8]) -> VrfSignature {
+		let (inout, proof, _) = self.0.vrf_sign(signing_context(context).bytes(message));
+		VrfSignature {
+			output: H256::from(*inout.to_output().as_bytes()),
+			proof: H512::from(proof.to_bytes()),
+		}
+	}
+
 	/// Make a new key pair from binary data derived from a valid seed phrase.
 	///
 	/// This uses a key derivation function to convert the entropy into a seed, then returns
@@ -542,6 +607,30 @@ mod test {
 	use crate::crypto::{Ss58Codec, DEV_PHRASE, DEV_ADDRESS};
 	use hex_literal::hex;
 
+	#[test]
+	fn vrf_sign_and_verify_should_work() {
+		let pair = Pair::from_seed(&[7; 32]);
+		let signature = pair.vrf_sign(b"lottery", b"round 1");
+
+		let randomness = vrf_verify(&pair.public().0, b"lottery", b"round 1", &signature);
+		assert!(randomness.is_some());
+		assert_eq!(randomness, vrf_verify(&pair.public().0, b"lottery", b"round 1", &pair.vrf_sign(b"lottery", b"round 1")));
+
+		assert_eq!(vrf_verify(&pair.public().0, b"lottery", b"round 2", &signature), None);
+		assert_eq!(vrf_verify(&pair.public().0, b"election", b"round 1", &signature), None);
+		let other = Pair::from_seed(&[8; 32]);
+		assert_eq!(vrf_verify(&other.public().0, b"lottery", b"round 1", &signature), None);
+	}
+
+	#[test]
+	fn seed_with_path_should_match_phrase_with_path() {
+		let (_, seed) = Pair::from_phrase(DEV_PHRASE, None).unwrap();
+		assert_eq!(
+			Pair::from_string(&format!("0x{}//Alice/1", hex::encode(&seed)), None).unwrap().public(),
+			Pair::from_string("//Alice/1", None).unwrap().public(),
+		);
+	}
+
 	#[test]
 	fn default_phrase_should_be_used() {
 		assert_eq!(
@@ -0,0 +1,169 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tracing of runtime execution.
+//!
+//! The runtime enters and exits named spans through the `ext_trace_enter_span` and
+//! `ext_trace_exit_span` host functions, and the externalities note every storage access. All of
+//! this is only recorded while [`collect`] runs on the current thread, so tracing costs next to
+//! nothing otherwise.
+
+use std::{cell::RefCell, time::Instant};
+use serde::{Serialize, Deserialize};
+
+/// A span of runtime execution.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Span {
+	/// Name of the span, e.g. `srml_balances::transfer`.
+	pub name: String,
+	/// Number of spans that were open when this span was entered.
+	pub depth: u32,
+	/// Wall time spent in the span, in nanoseconds.
+	pub duration_nanos: u64,
+	/// Number of storage reads within the span, including the ones of nested spans.
+	pub storage_reads: u32,
+	/// Number of storage writes within the span, including the ones of nested spans.
+	pub storage_writes: u32,
+}
+
+/// Spans recorded by the running [`collect`] call.
+struct Collector {
+	spans: Vec<Span>,
+	/// Indices into `spans` of the spans that are still open, with the time they were entered.
+	open: Vec<(usize, Instant)>,
+}
+
+impl Collector {
+	fn exit(&mut self) {
+		if let Some((index, entered)) = self.open.pop() {
+			let elapsed = entered.elapsed();
+			self.spans[index].duration_nanos =
+				elapsed.as_secs() * 1_000_000_000 + u64::from(elapsed.subsec_nanos());
+		}
+	}
+
+	fn note_access(&mut self, write: bool) {
+		for &(index, _) in &self.open {
+			let span = &mut self.spans[index];
+			if write {
+				span.storage_writes += 1;
+			} else {
+				span.storage_reads += 1;
+			}
+		}
+	}
+}
+
+thread_local! {
+	static COLLECTOR: RefCell<Option<Collector>> = RefCell::new(None);
+}
+
+/// Run `f` and return the spans that were recorded on this thread while it ran, in the order
+/// they were entered.
+///
+/// Spans that are still open when `f` returns are closed.
+pub fn collect<R, F: FnOnce() -> R>(f: F) -> (R, Vec<Span>) {
+	let collector = Collector { spans: Vec::new(), open: Vec::new() };
+	let previous = COLLECTOR.with(|c| c.replace(Some(collector)));
+	let result = f();
+	let mut collector = COLLECTOR.with(|c| c.replace(previous))
+		.expect("collector is only removed by `collect`; qed");
+	while !collector.open.is_empty() {
+		collector.exit();
+	}
+	(result, collector.spans)
+}
+
+/// Whether spans are currently recorded on this thread.
+pub fn is_enabled() -> bool {
+	COLLECTOR.with(|c| c.borrow().is_some())
+}
+
+/// Enter a new span nested in the currently open one.
+pub fn enter_span(name: &str) {
+	COLLECTOR.with(|c| if let Some(collector) = c.borrow_mut().as_mut() {
+		collector.spans.push(Span {
+			name: name.to_string(),
+			depth: collector.open.len() as u32,
+			duration_nanos: 0,
+			storage_reads: 0,
+			storage_writes: 0,
+		});
+		collector.open.push((collector.spans.len() - 1, Instant::now()));
+	});
+}
+
+/// Exit the most recently entered span that is still open.
+pub fn exit_span() {
+	COLLECTOR.with(|c| if let Some(collector) = c.borrow_mut().as_mut() {
+		collector.exit();
+	});
+}
+
+/// Note a storage read in all open spans.
+pub fn note_storage_read() {
+	COLLECTOR.with(|c| if let Some(collector) = c.borrow_mut().as_mut() {
+		collector.note_access(false);
+	});
+}
+
+/// Note a storage write in all open spans.
+pub fn note_storage_write() {
+	COLLECTOR.with(|c| if let Some(collector) = c.borrow_mut().as_mut() {
+		collector.note_access(true);
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn nothing_is_recorded_outside_of_collect() {
+		enter_span("outside");
+		note_storage_read();
+		exit_span();
+		assert!(!is_enabled());
+
+		let ((), spans) = collect(|| ());
+		assert!(spans.is_empty());
+	}
+
+	#[test]
+	fn nested_spans_count_storage_accesses() {
+		let ((), spans) = collect(|| {
+			assert!(is_enabled());
+			note_storage_read();
+			enter_span("outer");
+			note_storage_read();
+			enter_span("inner");
+			note_storage_read();
+			note_storage_write();
+			exit_span();
+			note_storage_write();
+			exit_span();
+			enter_span("unclosed");
+		});
+
+		let summary: Vec<_> = spans.iter()
+			.map(|s| (s.name.as_str(), s.depth, s.storage_reads, s.storage_writes))
+			.collect();
+		assert_eq!(summary, vec![("outer", 0, 2, 2), ("inner", 1, 1, 1), ("unclosed", 0, 0, 0)]);
+		assert!(spans[0].duration_nanos >= spans[1].duration_nanos);
+		assert!(!is_enabled());
+	}
+}
@@ -0,0 +1,453 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Prometheus metrics for Substrate nodes.
+//!
+//! Metrics are registered in a process wide [`Registry`], usually from a `lazy_static` next to
+//! the code that updates them:
+//!
+//! ```
+//! use lazy_static::lazy_static;
+//! use substrate_prometheus::{self as prometheus, Counter};
+//! use std::sync::Arc;
+//!
+//! lazy_static! {
+//! 	static ref BLOCKS_IMPORTED: Arc<Counter> =
+//! 		prometheus::counter("substrate_blocks_imported_total", "Number of imported blocks");
+//! }
+//!
+//! BLOCKS_IMPORTED.inc();
+//! ```
+//!
+//! [`start_server`] serves all registered metrics in the Prometheus text format on `/metrics`.
+
+#![warn(missing_docs)]
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+use log::warn;
+use parking_lot::Mutex;
+
+#[cfg(not(target_os = "unknown"))]
+mod server;
+
+#[cfg(not(target_os = "unknown"))]
+pub use server::{Server, start_server};
+
+/// Default histogram buckets for durations, in seconds.
+pub const DURATION_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+lazy_static! {
+	static ref GLOBAL_REGISTRY: Registry = Registry::new();
+}
+
+/// A metric that only goes up.
+#[derive(Debug, Default)]
+pub struct Counter(AtomicU64);
+
+impl Counter {
+	/// Increase the counter by one.
+	pub fn inc(&self) {
+		self.inc_by(1)
+	}
+
+	/// Increase the counter by `value`.
+	pub fn inc_by(&self, value: u64) {
+		self.0.fetch_add(value, Ordering::Relaxed);
+	}
+
+	/// Get the current value.
+	pub fn get(&self) -> u64 {
+		self.0.load(Ordering::Relaxed)
+	}
+}
+
+/// A metric that can go up and down.
+#[derive(Debug, Default)]
+pub struct Gauge(AtomicI64);
+
+impl Gauge {
+	/// Set the gauge to `value`.
+	pub fn set(&self, value: i64) {
+		self.0.store(value, Ordering::Relaxed)
+	}
+
+	/// Increase the gauge by one.
+	pub fn inc(&self) {
+		self.0.fetch_add(1, Ordering::Relaxed);
+	}
+
+	/// Decrease the gauge by one.
+	pub fn dec(&self) {
+		self.0.fetch_sub(1, Ordering::Relaxed);
+	}
+
+	/// Get the current value.
+	pub fn get(&self) -> i64 {
+		self.0.load(Ordering::Relaxed)
+	}
+}
+
+/// A metric that counts observed durations in buckets.
+#[derive(Debug)]
+pub struct Histogram {
+	buckets: &'static [f64],
+	counts: Vec<AtomicU64>,
+	sum_micros: AtomicU64,
+	count: AtomicU64,
+}
+
+impl Histogram {
+	/// Create a histogram with the given upper bounds of its buckets, in seconds.
+	pub fn new(buckets: &'static [f64]) -> Self {
+		Histogram {
+			buckets,
+			counts: buckets.iter().map(|_| AtomicU64::new(0)).collect(),
+			sum_micros: AtomicU64::new(0),
+			count: AtomicU64::new(0),
+		}
+	}
+
+	/// Observe a duration.
+	pub fn observe(&self, duration: Duration) {
+		let seconds = duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) / 1_000_000_000.0;
+		if let Some(index) = self.buckets.iter().position(|bound| seconds <= *bound) {
+			self.counts[index].fetch_add(1, Ordering::Relaxed);
+		}
+		let micros = duration.as_secs() * 1_000_000 + u64::from(duration.subsec_micros());
+		self.sum_micros.fetch_add(micros, Ordering::Relaxed);
+		self.count.fetch_add(1, Ordering::Relaxed);
+	}
+
+	/// Start a timer that observes the time until it is dropped.
+	pub fn start_timer(&self) -> HistogramTimer {
+		HistogramTimer { histogram: self, start: Instant::now() }
+	}
+
+	/// Get the number of observations.
+	pub fn count(&self) -> u64 {
+		self.count.load(Ordering::Relaxed)
+	}
+
+	/// Render the histogram. `labels` is either empty or a list of labels, e.g. `method="foo"`.
+	fn render(&self, name: &str, labels: &str, out: &mut String) {
+		let (braced, prefix) = if labels.is_empty() {
+			(String::new(), String::new())
+		} else {
+			(format!("{{{}}}", labels), format!("{},", labels))
+		};
+		let mut cumulative = 0;
+		for (bound, count) in self.buckets.iter().zip(self.counts.iter()) {
+			cumulative += count.load(Ordering::Relaxed);
+			let _ = writeln!(out, "{}_bucket{{{}le=\"{}\"}} {}", name, prefix, bound, cumulative);
+		}
+		let count = self.count();
+		let _ = writeln!(out, "{}_bucket{{{}le=\"+Inf\"}} {}", name, prefix, count);
+		let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+		let _ = writeln!(out, "{}_sum{} {}", name, braced, sum);
+		let _ = writeln!(out, "{}_count{} {}", name, braced, count);
+	}
+}
+
+/// Observes the time from its creation until it is dropped in a histogram.
+pub struct HistogramTimer<'a> {
+	histogram: &'a Histogram,
+	start: Instant,
+}
+
+impl<'a> Drop for HistogramTimer<'a> {
+	fn drop(&mut self) {
+		self.histogram.observe(self.start.elapsed())
+	}
+}
+
+/// A family of counters, told apart by the value of a single label.
+#[derive(Debug)]
+pub struct CounterVec {
+	label: &'static str,
+	counters: Mutex<BTreeMap<String, Arc<Counter>>>,
+}
+
+impl CounterVec {
+	/// Create a family of counters with the given label name.
+	pub fn new(label: &'static str) -> Self {
+		CounterVec { label, counters: Default::default() }
+	}
+
+	/// Get the counter for the given label value, creating it if needed.
+	pub fn with_label(&self, value: &str) -> Arc<Counter> {
+		self.counters.lock().entry(value.to_string()).or_default().clone()
+	}
+}
+
+/// A family of histograms, told apart by the value of a single label.
+#[derive(Debug)]
+pub struct HistogramVec {
+	label: &'static str,
+	buckets: &'static [f64],
+	histograms: Mutex<BTreeMap<String, Arc<Histogram>>>,
+}
+
+impl HistogramVec {
+	/// Create a family of histograms with the given label name and buckets.
+	pub fn new(label: &'static str, buckets: &'static [f64]) -> Self {
+		HistogramVec { label, buckets, histograms: Default::default() }
+	}
+
+	/// Get the histogram for the given label value, creating it if needed.
+	pub fn with_label(&self, value: &str) -> Arc<Histogram> {
+		let buckets = self.buckets;
+		self.histograms.lock()
+			.entry(value.to_string())
+			.or_insert_with(|| Arc::new(Histogram::new(buckets)))
+			.clone()
+	}
+}
+
+/// Format a label, escaping its value as required by the text format.
+fn label(name: &str, value: &str) -> String {
+	let value = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+	format!("{}=\"{}\"", name, value)
+}
+
+#[derive(Clone)]
+enum Metric {
+	Counter(Arc<Counter>),
+	Gauge(Arc<Gauge>),
+	Histogram(Arc<Histogram>),
+	CounterVec(Arc<CounterVec>),
+	HistogramVec(Arc<HistogramVec>),
+}
+
+impl Metric {
+	fn type_name(&self) -> &'static str {
+		match self {
+			Metric::Counter(_) | Metric::CounterVec(_) => "counter",
+			Metric::Gauge(_) => "gauge",
+			Metric::Histogram(_) | Metric::HistogramVec(_) => "histogram",
+		}
+	}
+}
+
+struct Entry {
+	name: &'static str,
+	help: &'static str,
+	metric: Metric,
+}
+
+/// A set of named metrics.
+#[derive(Default)]
+pub struct Registry {
+	entries: Mutex<Vec<Entry>>,
+}
+
+impl Registry {
+	/// Create an empty registry.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Register a counter, or return the counter already registered under `name`.
+	pub fn counter(&self, name: &'static str, help: &'static str) -> Arc<Counter> {
+		match self.register(name, help, || Metric::Counter(Default::default())) {
+			Metric::Counter(counter) => counter,
+			_ => Default::default(),
+		}
+	}
+
+	/// Register a gauge, or return the gauge already registered under `name`.
+	pub fn gauge(&self, name: &'static str, help: &'static str) -> Arc<Gauge> {
+		match self.register(name, help, || Metric::Gauge(Default::default())) {
+			Metric::Gauge(gauge) => gauge,
+			_ => Default::default(),
+		}
+	}
+
+	/// Register a histogram, or return the histogram already registered under `name`.
+	pub fn histogram(
+		&self,
+		name: &'static str,
+		help: &'static str,
+		buckets: &'static [f64],
+	) -> Arc<Histogram> {
+		match self.register(name, help, || Metric::Histogram(Arc::new(Histogram::new(buckets)))) {
+			Metric::Histogram(histogram) => histogram,
+			_ => Arc::new(Histogram::new(buckets)),
+		}
+	}
+
+	/// Register a family of counters, or return the family already registered under `name`.
+	pub fn counter_vec(&self, name: &'static str, help: &'static str, label: &'static str) -> Arc<CounterVec> {
+		match self.register(name, help, || Metric::CounterVec(Arc::new(CounterVec::new(label)))) {
+			Metric::CounterVec(counters) => counters,
+			_ => Arc::new(CounterVec::new(label)),
+		}
+	}
+
+	/// Register a family of histograms, or return the family already registered under `name`.
+	pub fn histogram_vec(
+		&self,
+		name: &'static str,
+		help: &'static str,
+		label: &'static str,
+		buckets: &'static [f64],
+	) -> Arc<HistogramVec> {
+		match self.register(name, help, || Metric::HistogramVec(Arc::new(HistogramVec::new(label, buckets)))) {
+			Metric::HistogramVec(histograms) => histograms,
+			_ => Arc::new(HistogramVec::new(label, buckets)),
+		}
+	}
+
+	fn register(&self, name: &'static str, help: &'static str, new: impl FnOnce() -> Metric) -> Metric {
+		let mut entries = self.entries.lock();
+		let metric = new();
+		if let Some(entry) = entries.iter().find(|entry| entry.name == name) {
+			if entry.metric.type_name() != metric.type_name() {
+				warn!(
+					"Metric {} is already registered as a {}; the new {} is not exported",
+					name, entry.metric.type_name(), metric.type_name(),
+				);
+			}
+			return entry.metric.clone()
+		}
+		entries.push(Entry { name, help, metric: metric.clone() });
+		metric
+	}
+
+	/// Render all metrics in the Prometheus text format.
+	pub fn render(&self) -> String {
+		let mut out = String::new();
+		for entry in self.entries.lock().iter() {
+			let _ = writeln!(out, "# HELP {} {}", entry.name, entry.help);
+			let _ = writeln!(out, "# TYPE {} {}", entry.name, entry.metric.type_name());
+			match entry.metric {
+				Metric::Counter(ref counter) => { let _ = writeln!(out, "{} {}", entry.name, counter.get()); },
+				Metric::Gauge(ref gauge) => { let _ = writeln!(out, "{} {}", entry.name, gauge.get()); },
+				Metric::Histogram(ref histogram) => histogram.render(entry.name, "", &mut out),
+				Metric::CounterVec(ref counters) => for (value, counter) in counters.counters.lock().iter() {
+					let _ = writeln!(out, "{}{{{}}} {}", entry.name, label(counters.label, value), counter.get());
+				},
+				Metric::HistogramVec(ref histograms) => for (value, histogram) in histograms.histograms.lock().iter() {
+					histogram.render(entry.name, &label(histograms.label, value), &mut out);
+				},
+			}
+		}
+		out
+	}
+}
+
+/// The process wide registry.
+pub fn global() -> &'static Registry {
+	&GLOBAL_REGISTRY
+}
+
+/// Register a counter in the process wide registry.
+pub fn counter(name: &'static str, help: &'static str) -> Arc<Counter> {
+	global().counter(name, help)
+}
+
+/// Register a gauge in the process wide registry.
+pub fn gauge(name: &'static str, help: &'static str) -> Arc<Gauge> {
+	global().gauge(name, help)
+}
+
+/// Register a duration histogram with [`DURATION_BUCKETS`] in the process wide registry.
+pub fn duration_histogram(name: &'static str, help: &'static str) -> Arc<Histogram> {
+	global().histogram(name, help, DURATION_BUCKETS)
+}
+
+/// Register a family of counters in the process wide registry.
+pub fn counter_vec(name: &'static str, help: &'static str, label: &'static str) -> Arc<CounterVec> {
+	global().counter_vec(name, help, label)
+}
+
+/// Register a family of duration histograms with [`DURATION_BUCKETS`] in the process wide
+/// registry.
+pub fn duration_histogram_vec(name: &'static str, help: &'static str, label: &'static str) -> Arc<HistogramVec> {
+	global().histogram_vec(name, help, label, DURATION_BUCKETS)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn renders_text_format() {
+		let registry = Registry::new();
+		registry.counter("test_total", "A counter").inc_by(3);
+		registry.gauge("test_gauge", "A gauge").set(-2);
+		let histogram = registry.histogram("test_seconds", "A histogram", &[0.1, 1.0]);
+		histogram.observe(Duration::from_millis(50));
+		histogram.observe(Duration::from_millis(500));
+		histogram.observe(Duration::from_secs(2));
+
+		assert_eq!(registry.render(), "\
+# HELP test_total A counter
+# TYPE test_total counter
+test_total 3
+# HELP test_gauge A gauge
+# TYPE test_gauge gauge
+test_gauge -2
+# HELP test_seconds A histogram
+# TYPE test_seconds histogram
+test_seconds_bucket{le=\"0.1\"} 1
+test_seconds_bucket{le=\"1\"} 2
+test_seconds_bucket{le=\"+Inf\"} 3
+test_seconds_sum 2.55
+test_seconds_count 3
+");
+	}
+
+	#[test]
+	fn renders_labelled_metrics() {
+		let registry = Registry::new();
+		let counters = registry.counter_vec("calls_total", "Calls", "method");
+		counters.with_label("b").inc();
+		counters.with_label("a\"").inc_by(2);
+		counters.with_label("b").inc();
+		registry.histogram_vec("call_seconds", "Call time", "method", &[1.0])
+			.with_label("a")
+			.observe(Duration::from_millis(500));
+
+		assert_eq!(registry.render(), "\
+# HELP calls_total Calls
+# TYPE calls_total counter
+calls_total{method=\"a\\\"\"} 2
+calls_total{method=\"b\"} 2
+# HELP call_seconds Call time
+# TYPE call_seconds histogram
+call_seconds_bucket{method=\"a\",le=\"1\"} 1
+call_seconds_bucket{method=\"a\",le=\"+Inf\"} 1
+call_seconds_sum{method=\"a\"} 0.5
+call_seconds_count{method=\"a\"} 1
+");
+	}
+
+	#[test]
+	fn registering_twice_returns_the_same_metric() {
+		let registry = Registry::new();
+		registry.counter("test_total", "A counter").inc();
+		registry.counter("test_total", "A counter").inc();
+
+		assert_eq!(registry.counter("test_total", "A counter").get(), 2);
+		assert_eq!(registry.render().matches("# TYPE").count(), 1);
+	}
+}
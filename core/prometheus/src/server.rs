@@ -0,0 +1,160 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A minimal HTTP server that exports the metrics of the global registry.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::{debug, info};
+
+/// How long we wait for a client to send its whole request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long we wait for a client to receive the response.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The maximum length of the request line and of each header, in bytes.
+const MAX_LINE_LENGTH: u64 = 8 * 1024;
+
+/// The maximum number of headers of a request.
+const MAX_HEADERS: usize = 100;
+
+/// A running metrics server.
+///
+/// The server runs on its own thread for the lifetime of the process.
+pub struct Server {
+	address: SocketAddr,
+}
+
+impl Server {
+	/// The address the server is listening on.
+	pub fn address(&self) -> SocketAddr {
+		self.address
+	}
+}
+
+/// Start serving the metrics of the global registry on `http://<address>/metrics`.
+pub fn start_server(address: &SocketAddr) -> io::Result<Server> {
+	let listener = TcpListener::bind(address)?;
+	let address = listener.local_addr()?;
+
+	thread::Builder::new()
+		.name("prometheus".into())
+		.spawn(move || for stream in listener.incoming() {
+			let result = stream.and_then(handle);
+			if let Err(err) = result {
+				debug!(target: "prometheus", "Failed to serve metrics request: {}", err);
+			}
+		})?;
+
+	info!("Prometheus metrics served on http://{}/metrics", address);
+	Ok(Server { address })
+}
+
+fn handle(mut stream: TcpStream) -> io::Result<()> {
+	stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+	// The metrics are served by a single thread, a client sending its request slowly must not
+	// hold it for longer than the timeout.
+	let deadline = Instant::now() + READ_TIMEOUT;
+	let mut reader = BufReader::new(stream.try_clone()?);
+	let request_line = read_line(&mut reader, deadline)?;
+
+	// Skip the headers, we don't need any of them.
+	let mut headers = 0;
+	while read_line(&mut reader, deadline)?.len() > 2 {
+		headers += 1;
+		if headers > MAX_HEADERS {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "Too many headers"));
+		}
+	}
+
+	let mut parts = request_line.split_whitespace();
+	let (status, body) = match (parts.next(), parts.next()) {
+		(Some("GET"), Some("/metrics")) => ("200 OK", crate::global().render()),
+		_ => ("404 Not Found", String::new()),
+	};
+
+	write!(
+		stream,
+		"HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+		status,
+		body.len(),
+		body,
+	)?;
+	stream.flush()
+}
+
+/// Read a line of the request, which must be at most `MAX_LINE_LENGTH` long and be received
+/// before `deadline`.
+fn read_line(reader: &mut BufReader<TcpStream>, deadline: Instant) -> io::Result<String> {
+	let now = Instant::now();
+	if now >= deadline {
+		return Err(io::Error::new(io::ErrorKind::TimedOut, "Request not received in time"));
+	}
+	reader.get_ref().set_read_timeout(Some(deadline - now))?;
+
+	let mut line = String::new();
+	reader.by_ref().take(MAX_LINE_LENGTH).read_line(&mut line)?;
+	if !line.ends_with('\n') {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, "Request line too long or truncated"));
+	}
+	Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Send `request` and return the response, empty if the server dropped the connection.
+	fn send(address: SocketAddr, request: &[u8]) -> String {
+		let mut stream = TcpStream::connect(address).unwrap();
+		let mut response = String::new();
+		// The server may close the connection before receiving the whole request.
+		let _ = stream.write_all(request).and_then(|_| stream.read_to_string(&mut response));
+		response
+	}
+
+	fn get(address: SocketAddr, path: &str) -> String {
+		send(address, format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes())
+	}
+
+	#[test]
+	fn serves_metrics() {
+		crate::counter("substrate_test_server_total", "Counter of the server test").inc();
+		let server = start_server(&"127.0.0.1:0".parse().unwrap()).unwrap();
+
+		let response = get(server.address(), "/metrics");
+		assert!(response.starts_with("HTTP/1.1 200 OK"));
+		assert!(response.contains("substrate_test_server_total 1"));
+
+		assert!(get(server.address(), "/").starts_with("HTTP/1.1 404 Not Found"));
+	}
+
+	#[test]
+	fn oversized_requests_are_dropped() {
+		let server = start_server(&"127.0.0.1:0".parse().unwrap()).unwrap();
+
+		let long_line = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE_LENGTH as usize));
+		assert_eq!(send(server.address(), long_line.as_bytes()), "");
+		let many_headers = format!("GET /metrics HTTP/1.1\r\n{}\r\n", "Foo: bar\r\n".repeat(MAX_HEADERS + 1));
+		assert_eq!(send(server.address(), many_headers.as_bytes()), "");
+
+		assert!(get(server.address(), "/metrics").starts_with("HTTP/1.1 200 OK"));
+	}
+}
@@ -21,6 +21,7 @@
 pub use substrate_rpc as apis;
 
 use std::io;
+use jsonrpc_core as rpc;
 use log::error;
 use sr_primitives::{traits::{Block as BlockT, NumberFor}, generic::SignedBlock};
 
@@ -31,7 +32,10 @@ const MAX_PAYLOAD: usize = 15 * 1024 * 1024;
 const WS_MAX_CONNECTIONS: usize = 100;
 
 pub type Metadata = apis::metadata::Metadata;
-pub type RpcHandler = pubsub::PubSubHandler<Metadata>;
+pub type RpcMiddleware = apis::middleware::RpcMiddleware;
+pub type RpcHandler = pubsub::PubSubHandler<Metadata, RpcMiddleware>;
+/// Additional RPC methods, served next to the standard APIs.
+pub type RpcExtension = rpc::MetaIoHandler<Metadata>;
 
 pub use self::inner::*;
 
@@ -41,6 +45,8 @@ pub fn rpc_handler<Block: BlockT, ExHash, S, C, A, Y>(
 	chain: C,
 	author: A,
 	system: Y,
+	extension: RpcExtension,
+	middleware: RpcMiddleware,
 ) -> RpcHandler where
 	Block: BlockT + 'static,
 	ExHash: Send + Sync + 'static + sr_primitives::Serialize + sr_primitives::DeserializeOwned,
@@ -49,30 +55,59 @@ pub fn rpc_handler<Block: BlockT, ExHash, S, C, A, Y>(
 	A: apis::author::AuthorApi<ExHash, Block::Hash, Metadata=Metadata>,
 	Y: apis::system::SystemApi<Block::Hash, NumberFor<Block>>,
 {
-	let mut io = pubsub::PubSubHandler::default();
+	let mut io = pubsub::PubSubHandler::new(rpc::MetaIoHandler::with_middleware(middleware));
 	io.extend_with(state.to_delegate());
 	io.extend_with(chain.to_delegate());
 	io.extend_with(author.to_delegate());
 	io.extend_with(system.to_delegate());
+	io.extend_with(extension);
 	io
 }
 
 #[cfg(not(target_os = "unknown"))]
 mod inner {
 	use super::*;
+	use std::sync::Arc;
 
 	pub type HttpServer = http::Server;
 	pub type WsServer = ws::Server;
 
 	/// Start HTTP server listening on given address.
 	///
+	/// Requests are authenticated if the server only listens on a loopback address, or if they
+	/// carry `auth_token` as bearer token in their `Authorization` header.
+	///
+	/// The server doesn't see the address of its peers, so unless `trust_proxy` is set all
+	/// requests share one rate limit. With `trust_proxy` the server is expected to be reached
+	/// through a reverse proxy that sets the `X-Forwarded-For` header: requests are rate limited
+	/// per forwarded address, and forwarded requests are never authenticated by the loopback
+	/// address of the server.
+	///
 	/// **Note**: Only available if `not(target_os = "unknown")`.
 	pub fn start_http(
 		addr: &std::net::SocketAddr,
 		cors: Option<&Vec<String>>,
+		auth_token: Option<&String>,
+		trust_proxy: bool,
 		io: RpcHandler,
 	) -> io::Result<http::Server> {
-		http::ServerBuilder::new(io)
+		let local = addr.ip().is_loopback();
+		let auth_header = auth_token.map(|token| format!("Bearer {}", token));
+		let rate_limiter = Arc::new(apis::middleware::RateLimiter::default());
+		let rate_limiters = Arc::new(apis::middleware::RateLimiters::default());
+		http::ServerBuilder::with_meta_extractor(io, move |request: &http::hyper::Request<http::hyper::Body>| {
+			let authorized = auth_header.as_ref().map_or(false, |expected| {
+				request.headers().get(http::hyper::header::AUTHORIZATION)
+					.map_or(false, |header| header.as_bytes() == expected.as_bytes())
+			});
+			let forwarded_for = if trust_proxy { forwarded_for(request) } else { None };
+			Metadata::default()
+				.authenticated((local && forwarded_for.is_none()) || authorized)
+				.with_rate_limiter(match forwarded_for {
+					Some(address) => rate_limiters.get(address),
+					None => rate_limiter.clone(),
+				})
+		})
 			.threads(4)
 			.health_api(("/health", "system_health"))
 			.allowed_hosts(hosts_filtering(cors.is_some()))
@@ -88,14 +123,25 @@ mod inner {
 
 	/// Start WS server listening on given address.
 	///
+	/// Connections are authenticated if the server only listens on a loopback address, or if
+	/// they request the `bearer.<auth_token>` subprotocol (browsers can't set headers on
+	/// WebSocket connections).
+	///
 	/// **Note**: Only available if `not(target_os = "unknown")`.
 	pub fn start_ws(
 		addr: &std::net::SocketAddr,
 		max_connections: Option<usize>,
 		cors: Option<&Vec<String>>,
+		auth_token: Option<&String>,
 		io: RpcHandler,
 	) -> io::Result<ws::Server> {
-		ws::ServerBuilder::with_meta_extractor(io, |context: &ws::RequestContext| Metadata::new(context.sender()))
+		let local = addr.ip().is_loopback();
+		let auth_protocol = auth_token.map(|token| format!("bearer.{}", token));
+		ws::ServerBuilder::with_meta_extractor(io, move |context: &ws::RequestContext| {
+			let authorized = auth_protocol.as_ref()
+				.map_or(false, |expected| context.protocols.iter().any(|protocol| protocol == expected));
+			Metadata::new(context.sender()).authenticated(local || authorized)
+		})
 			.max_payload(MAX_PAYLOAD)
 			.max_connections(max_connections.unwrap_or(WS_MAX_CONNECTIONS))
 			.allowed_origins(map_cors(cors))
@@ -111,6 +157,16 @@ mod inner {
 			})
 	}
 
+	/// The address of the client as added by the reverse proxy to the `X-Forwarded-For` header.
+	///
+	/// Only the last address is taken, the ones before are given by the client.
+	fn forwarded_for(request: &http::hyper::Request<http::hyper::Body>) -> Option<std::net::IpAddr> {
+		request.headers().get_all("x-forwarded-for").iter().last()
+			.and_then(|header| header.to_str().ok())
+			.and_then(|addresses| addresses.rsplit(',').next())
+			.and_then(|address| address.trim().parse().ok())
+	}
+
 	fn map_cors<T: for<'a> From<&'a str>>(
 		cors: Option<&Vec<String>>
 	) -> http::DomainsValidation<T> {
@@ -37,6 +37,14 @@ pub enum Error {
 	/// Incorrect extrinsic format.
 	#[display(fmt="Invalid extrinsic format: {}", _0)]
 	BadFormat(codec::Error),
+	/// Keystore error.
+	Keystore(keystore::Error),
+	/// The node has no keystore.
+	#[display(fmt="The node has no keystore")]
+	KeystoreUnavailable,
+	/// The runtime does not describe its session keys.
+	#[display(fmt="The runtime does not support the `SessionKeys` api")]
+	SessionKeysUnsupported,
 }
 
 impl std::error::Error for Error {
@@ -45,6 +53,7 @@ impl std::error::Error for Error {
 			Error::Client(ref err) => Some(err),
 			Error::Pool(ref err) => Some(err),
 			Error::Verification(ref err) => Some(&**err),
+			Error::Keystore(ref err) => Some(err),
 			_ => None,
 		}
 	}
@@ -56,6 +65,8 @@ const BASE_ERROR: i64 = 1000;
 const BAD_FORMAT: i64 = BASE_ERROR + 1;
 /// Error during transaction verification in runtime.
 const VERIFICATION_ERROR: i64 = BASE_ERROR + 2;
+/// Session keys can't be generated.
+const SESSION_KEYS_UNAVAILABLE: i64 = BASE_ERROR + 3;
 
 /// Pool rejected the transaction as invalid
 const POOL_INVALID_TX: i64 = BASE_ERROR + 10;
@@ -122,6 +133,14 @@ impl From<Error> for rpc::Error {
 				message: "Immediately Dropped" .into(),
 				data: Some("The transaction couldn't enter the pool because of the limit".into()),
 			},
+			e @ Error::KeystoreUnavailable |
+			e @ Error::SessionKeysUnsupported |
+			e @ Error::Keystore(keystore::Error::UnsupportedKeyType(_)) |
+			e @ Error::Keystore(keystore::Error::GenerationUnsupported) => rpc::Error {
+				code: rpc::ErrorCode::ServerError(SESSION_KEYS_UNAVAILABLE),
+				message: format!("Session keys can't be generated: {}", e),
+				data: None,
+			},
 			e => errors::internal(e),
 		}
 	}
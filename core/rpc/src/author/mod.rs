@@ -18,21 +18,25 @@
 
 pub mod error;
 pub mod hash;
+pub mod session_keys;
+pub mod status;
 
 #[cfg(test)]
 mod tests;
 
 use std::sync::Arc;
 
-use client::{self, Client};
+use client::{self, Client, runtime_api::SessionKeys};
 use crate::rpc::futures::{Sink, Stream, Future};
 use crate::subscriptions::Subscriptions;
 use jsonrpc_derive::rpc;
 use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId};
 use log::warn;
 use codec::{Encode, Decode};
-use primitives::{Bytes, Blake2Hasher, H256};
-use sr_primitives::{generic, traits};
+use keystore::Keystore;
+use primitives::{Bytes, Blake2Hasher, H256, crypto::KeyTypeId};
+use state_machine::{ExecutionStrategy, NeverOffchainExt};
+use sr_primitives::{generic, traits, transaction_validity::TransactionSource};
 use self::error::Result;
 use transaction_pool::{
 	txpool::{
@@ -61,6 +65,21 @@ pub trait AuthorApi<Hash, BlockHash> {
 	#[rpc(name = "author_pendingExtrinsics")]
 	fn pending_extrinsics(&self) -> Result<Vec<Bytes>>;
 
+	/// Returns all extrinsics waiting for their requirements (e.g. a preceding nonce) to be satisfied.
+	#[rpc(name = "author_futureExtrinsics")]
+	fn future_extrinsics(&self) -> Result<Vec<Bytes>>;
+
+	/// Returns the number of transactions and their total size in both queues of the pool.
+	#[rpc(name = "author_poolStatus")]
+	fn pool_status(&self) -> Result<status::PoolStatus>;
+
+	/// Generate new session keys in the keystore of the node.
+	///
+	/// `owner` is the encoded account that will set the keys with `session::set_keys`. Returns
+	/// the keys and the proof that `owner` owns them.
+	#[rpc(name = "author_rotateKeys")]
+	fn rotate_keys(&self, owner: Bytes) -> Result<session_keys::RotatedKeys>;
+
 	/// Remove given extrinsic from the pool and temporarily ban it to prevent reimporting.
 	#[rpc(name = "author_removeExtrinsic")]
 	fn remove_extrinsic(&self, bytes_or_hash: Vec<hash::ExtrinsicOrHash<Hash>>) -> Result<Vec<Hash>>;
@@ -82,6 +101,8 @@ pub struct Author<B, E, P, RA> where P: PoolChainApi + Sync + Send + 'static {
 	pool: Arc<Pool<P>>,
 	/// Subscriptions manager
 	subscriptions: Subscriptions,
+	/// The keystore of the node, used to generate session keys.
+	keystore: Option<Arc<dyn Keystore>>,
 }
 
 impl<B, E, P, RA> Author<B, E, P, RA> where P: PoolChainApi + Sync + Send + 'static {
@@ -90,11 +111,13 @@ impl<B, E, P, RA> Author<B, E, P, RA> where P: PoolChainApi + Sync + Send + 'sta
 		client: Arc<Client<B, E, <P as PoolChainApi>::Block, RA>>,
 		pool: Arc<Pool<P>>,
 		subscriptions: Subscriptions,
+		keystore: Option<Arc<dyn Keystore>>,
 	) -> Self {
 		Author {
 			client,
 			pool,
 			subscriptions,
+			keystore,
 		}
 	}
 }
@@ -113,7 +136,7 @@ impl<B, E, P, RA> AuthorApi<ExHash<P>, BlockHash<P>> for Author<B, E, P, RA> whe
 		let xt = Decode::decode(&mut &ext[..])?;
 		let best_block_hash = self.client.info().chain.best_hash;
 		self.pool
-			.submit_one(&generic::BlockId::hash(best_block_hash), xt)
+			.submit_one(&generic::BlockId::hash(best_block_hash), TransactionSource::Local, xt)
 			.map_err(|e| e.into_pool_error()
 				.map(Into::into)
 				.unwrap_or_else(|e| error::Error::Verification(Box::new(e)).into())
@@ -124,6 +147,35 @@ impl<B, E, P, RA> AuthorApi<ExHash<P>, BlockHash<P>> for Author<B, E, P, RA> whe
 		Ok(self.pool.ready().map(|tx| tx.data.encode().into()).collect())
 	}
 
+	fn future_extrinsics(&self) -> Result<Vec<Bytes>> {
+		Ok(self.pool.futures().into_iter().map(|tx| tx.data.encode().into()).collect())
+	}
+
+	fn pool_status(&self) -> Result<status::PoolStatus> {
+		Ok(self.pool.status().into())
+	}
+
+	fn rotate_keys(&self, owner: Bytes) -> Result<session_keys::RotatedKeys> {
+		let keystore = self.keystore.as_ref().ok_or(error::Error::KeystoreUnavailable)?;
+		let best_block = generic::BlockId::hash(self.client.info().chain.best_hash);
+
+		let version = self.client.runtime_version_at(&best_block)?;
+		if !version.has_api::<dyn SessionKeys<P::Block>>() {
+			return Err(error::Error::SessionKeysUnsupported);
+		}
+		let key_types = self.client.executor().call(
+			&best_block,
+			"SessionKeys_session_key_types",
+			&[],
+			ExecutionStrategy::NativeElseWasm,
+			NeverOffchainExt::new(),
+		)?;
+		let key_types = Vec::<KeyTypeId>::decode(&mut &key_types[..])
+			.map_err(|_| error::Error::SessionKeysUnsupported)?;
+
+		session_keys::rotate_keys(&**keystore, &key_types, &owner)
+	}
+
 	fn remove_extrinsic(&self, bytes_or_hash: Vec<hash::ExtrinsicOrHash<ExHash<P>>>) -> Result<Vec<ExHash<P>>> {
 		let hashes = bytes_or_hash.into_iter()
 			.map(|x| match x {
@@ -148,7 +200,7 @@ impl<B, E, P, RA> AuthorApi<ExHash<P>, BlockHash<P>> for Author<B, E, P, RA> whe
 			let best_block_hash = self.client.info().chain.best_hash;
 			let dxt = <<P as PoolChainApi>::Block as traits::Block>::Extrinsic::decode(&mut &xt[..])?;
 			self.pool
-				.submit_and_watch(&generic::BlockId::hash(best_block_hash), dxt)
+				.submit_and_watch(&generic::BlockId::hash(best_block_hash), TransactionSource::Local, dxt)
 				.map_err(|e| e.into_pool_error()
 					.map(Into::into)
 					.unwrap_or_else(|e| error::Error::Verification(Box::new(e)).into())
@@ -0,0 +1,56 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Session key generation for author RPC module.
+
+use codec::Encode;
+use keystore::Keystore;
+use primitives::{Bytes, H512, crypto::KeyTypeId};
+use serde::{Serialize, Deserialize};
+use sr_primitives::traits::session_keys_ownership_payload;
+
+use super::error::Result;
+
+/// Session keys generated by `author_rotateKeys`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RotatedKeys {
+	/// The concatenated public keys, to be passed as `keys` to `session::set_keys`.
+	pub keys: Bytes,
+	/// The proof of ownership, to be passed as `proof` to `session::set_keys`.
+	pub proof: Bytes,
+}
+
+/// Generate a new key in `store` for each of the given key types and prove that `owner` owns them.
+pub fn rotate_keys(
+	store: &dyn Keystore,
+	key_types: &[KeyTypeId],
+	owner: &[u8],
+) -> Result<RotatedKeys> {
+	let payload = session_keys_ownership_payload(owner);
+	let mut keys = Vec::new();
+	let mut signatures = Vec::new();
+
+	for &key_type in key_types {
+		let public = store.generate_by_type(key_type)?;
+		let signature = store.sign_by_type(key_type, &public, &payload)?;
+		// All supported key types use 64 byte signatures.
+		signatures.push(H512::from_slice(&signature));
+		keys.extend(public);
+	}
+
+	Ok(RotatedKeys { keys: keys.into(), proof: signatures.encode().into() })
+}
@@ -19,6 +19,7 @@ use super::*;
 use std::sync::Arc;
 use assert_matches::assert_matches;
 use codec::Encode;
+use keystore::memory::MemoryStore;
 use transaction_pool::{
 	txpool::Pool,
 	ChainApi,
@@ -45,6 +46,7 @@ fn submit_transaction_should_not_cause_error() {
 		client: client.clone(),
 		pool: Arc::new(Pool::new(Default::default(), ChainApi::new(client))),
 		subscriptions: Subscriptions::new(Arc::new(runtime.executor())),
+		keystore: None,
 	};
 	let xt = uxt(AccountKeyring::Alice, 1).encode();
 	let h: H256 = blake2_256(&xt).into();
@@ -66,6 +68,7 @@ fn submit_rich_transaction_should_not_cause_error() {
 		client: client.clone(),
 		pool: Arc::new(Pool::new(Default::default(), ChainApi::new(client.clone()))),
 		subscriptions: Subscriptions::new(Arc::new(runtime.executor())),
+		keystore: None,
 	};
 	let xt = uxt(AccountKeyring::Alice, 0).encode();
 	let h: H256 = blake2_256(&xt).into();
@@ -89,6 +92,7 @@ fn should_watch_extrinsic() {
 		client,
 		pool: pool.clone(),
 		subscriptions: Subscriptions::new(Arc::new(runtime.executor())),
+		keystore: None,
 	};
 	let (subscriber, id_rx, data) = ::jsonrpc_pubsub::typed::Subscriber::new_test("test");
 
@@ -129,6 +133,7 @@ fn should_return_pending_extrinsics() {
 		client,
 		pool: pool.clone(),
 		subscriptions: Subscriptions::new(Arc::new(runtime.executor())),
+		keystore: None,
 	};
 	let ex = uxt(AccountKeyring::Alice, 0);
 	AuthorApi::submit_extrinsic(&p, ex.encode().into()).unwrap();
@@ -138,6 +143,32 @@ fn should_return_pending_extrinsics() {
 	);
 }
 
+#[test]
+fn should_return_future_extrinsics_and_pool_status() {
+	let runtime = runtime::Runtime::new().unwrap();
+	let client = Arc::new(test_client::new());
+	let pool = Arc::new(Pool::new(Default::default(), ChainApi::new(client.clone())));
+	let p = Author {
+		client,
+		pool: pool.clone(),
+		subscriptions: Subscriptions::new(Arc::new(runtime.executor())),
+		keystore: None,
+	};
+	let ready = uxt(AccountKeyring::Alice, 0);
+	AuthorApi::submit_extrinsic(&p, ready.encode().into()).unwrap();
+	let future = uxt(AccountKeyring::Bob, 5);
+	AuthorApi::submit_extrinsic(&p, future.encode().into()).unwrap();
+
+	assert_matches!(
+		p.future_extrinsics(),
+		Ok(ref expected) if *expected == vec![Bytes(future.encode())]
+	);
+	assert_matches!(
+		p.pool_status(),
+		Ok(status::PoolStatus { ready: 1, future: 1, .. })
+	);
+}
+
 #[test]
 fn should_remove_extrinsics() {
 	let runtime = runtime::Runtime::new().unwrap();
@@ -147,6 +178,7 @@ fn should_remove_extrinsics() {
 		client,
 		pool: pool.clone(),
 		subscriptions: Subscriptions::new(Arc::new(runtime.executor())),
+		keystore: None,
 	};
 	let ex1 = uxt(AccountKeyring::Alice, 0);
 	p.submit_extrinsic(ex1.encode().into()).unwrap();
@@ -165,3 +197,45 @@ fn should_remove_extrinsics() {
 
  	assert_eq!(removed.len(), 3);
 }
+
+#[test]
+fn rotate_keys_requires_session_keys_api() {
+	let runtime = runtime::Runtime::new().unwrap();
+	let client = Arc::new(test_client::new());
+	let p = Author {
+		client: client.clone(),
+		pool: Arc::new(Pool::new(Default::default(), ChainApi::new(client))),
+		subscriptions: Subscriptions::new(Arc::new(runtime.executor())),
+		keystore: Some(Arc::new(MemoryStore::new())),
+	};
+
+	assert_matches!(
+		AuthorApi::rotate_keys(&p, vec![1u8; 32].into()),
+		Err(error::Error::SessionKeysUnsupported)
+	);
+}
+
+#[test]
+fn rotated_keys_are_proven_by_owner() {
+	use primitives::{ed25519, sr25519, Pair, crypto::key_types};
+	use sr_primitives::traits::session_keys_ownership_payload;
+
+	let keystore = MemoryStore::new();
+	let owner = vec![1u8; 32];
+
+	let rotated = session_keys::rotate_keys(
+		&keystore,
+		&[key_types::ED25519, key_types::SR25519],
+		&owner,
+	).unwrap();
+
+	assert_eq!(rotated.keys.len(), 64);
+	let signatures = Vec::<primitives::H512>::decode(&mut &rotated.proof[..]).unwrap();
+	let payload = session_keys_ownership_payload(&owner);
+	assert!(ed25519::Pair::verify_weak(signatures[0].as_bytes(), &payload, &rotated.keys[..32]));
+	assert!(sr25519::Pair::verify_weak(signatures[1].as_bytes(), &payload, &rotated.keys[32..]));
+	assert!(!ed25519::Pair::verify_weak(signatures[0].as_bytes(), &session_keys_ownership_payload(&[2; 32]), &rotated.keys[..32]));
+
+	assert_eq!(keystore.public_keys_by_type(key_types::ED25519).unwrap().len(), 1);
+	assert_eq!(keystore.public_keys_by_type(key_types::SR25519).unwrap().len(), 1);
+}
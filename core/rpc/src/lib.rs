@@ -27,6 +27,7 @@ pub use subscriptions::Subscriptions;
 pub mod author;
 pub mod chain;
 pub mod metadata;
+pub mod middleware;
 pub mod state;
 pub mod system;
 
@@ -18,6 +18,7 @@
 use std::sync::Arc;
 
 use jsonrpc_pubsub::{Session, PubSubMetadata};
+use crate::middleware::RateLimiter;
 use crate::rpc::futures::sync::mpsc;
 
 /// RPC Metadata.
@@ -28,6 +29,10 @@ use crate::rpc::futures::sync::mpsc;
 #[derive(Default, Clone)]
 pub struct Metadata {
 	session: Option<Arc<Session>>,
+	/// Whether restricted methods may be called.
+	authenticated: bool,
+	/// Rate limit state of the connection, shared by all of its requests.
+	rate_limiter: Arc<RateLimiter>,
 }
 
 impl crate::rpc::Metadata for Metadata {}
@@ -42,9 +47,34 @@ impl Metadata {
 	pub fn new(transport: mpsc::Sender<String>) -> Self {
 		Metadata {
 			session: Some(Arc::new(Session::new(transport))),
+			authenticated: false,
+			rate_limiter: Default::default(),
 		}
 	}
 
+	/// Mark the connection as authenticated, i.e. allowed to call restricted methods.
+	pub fn authenticated(mut self, authenticated: bool) -> Self {
+		self.authenticated = authenticated;
+		self
+	}
+
+	/// Share the given rate limit state, e.g. between all requests of a transport without
+	/// persistent connections.
+	pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+		self.rate_limiter = rate_limiter;
+		self
+	}
+
+	/// Whether the connection may call restricted methods.
+	pub fn is_authenticated(&self) -> bool {
+		self.authenticated
+	}
+
+	/// The rate limit state of the connection.
+	pub fn rate_limiter(&self) -> &RateLimiter {
+		&self.rate_limiter
+	}
+
 	/// Create new `Metadata` for tests.
 	#[cfg(test)]
 	pub fn new_test() -> (mpsc::Receiver<String>, Self) {
@@ -0,0 +1,373 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC middleware.
+//!
+//! Every call goes through [`RpcMiddleware`], which counts and times calls per method, enforces
+//! the per-connection rate limit and the maximum response size, and rejects restricted and unsafe
+//! methods on connections that are not authenticated.
+
+use std::{collections::HashMap, net::IpAddr, sync::Arc, time::{Duration, Instant}};
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use rpc::futures::{Future, future::{self, Either}};
+
+use crate::metadata::Metadata;
+use crate::rpc;
+
+/// Methods that are expensive to serve, and are restricted if `expensive` is given as one of the
+/// restricted methods.
+pub const EXPENSIVE_METHODS: &[&str] = &[
+	"state_getKeys",
+	"state_getChildKeys",
+	"state_queryStorage",
+	"state_traceBlock",
+];
+
+/// Methods that change the node itself, and are rejected on connections that are not
+/// authenticated unless [`Limits::allow_unsafe`] is set.
+pub const UNSAFE_METHODS: &[&str] = &[
+	"author_rotateKeys",
+	"system_addLogFilter",
+	"system_resetLogFilter",
+	"system_addReservedPeer",
+	"system_removeReservedPeer",
+	"system_addBootNode",
+];
+
+/// Label of calls to methods that don't exist, so that they don't add a label value each.
+const UNKNOWN_METHOD: &str = "<unknown>";
+
+lazy_static! {
+	static ref CALLS: Arc<prometheus::CounterVec> = prometheus::counter_vec(
+		"substrate_rpc_calls_total",
+		"Number of RPC calls per method",
+		"method",
+	);
+	static ref CALL_TIME: Arc<prometheus::HistogramVec> = prometheus::duration_histogram_vec(
+		"substrate_rpc_call_time_seconds",
+		"Time taken to answer an RPC call per method",
+		"method",
+	);
+	static ref REJECTED_CALLS: Arc<prometheus::CounterVec> = prometheus::counter_vec(
+		"substrate_rpc_rejected_calls_total",
+		"Number of RPC calls rejected by the middleware per reason",
+		"reason",
+	);
+}
+
+/// Limits enforced by the RPC middleware.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Limits {
+	/// Maximum number of calls per second and connection. Unlimited if `None`.
+	pub calls_per_second: Option<u32>,
+	/// Maximum size of a serialized response in bytes. Unlimited if `None`.
+	pub max_response_size: Option<usize>,
+	/// Methods that are rejected on connections that are not authenticated. `expensive` stands
+	/// for all [`EXPENSIVE_METHODS`].
+	pub restricted_methods: Vec<String>,
+	/// Whether [`UNSAFE_METHODS`] may be called on connections that are not authenticated.
+	pub allow_unsafe: bool,
+}
+
+impl Limits {
+	fn is_restricted(&self, method: &str) -> bool {
+		let is_unsafe = UNSAFE_METHODS.contains(&method);
+		(is_unsafe && !self.allow_unsafe) ||
+			self.restricted_methods.iter().any(|restricted| match restricted.as_str() {
+				"expensive" => EXPENSIVE_METHODS.contains(&method),
+				"unsafe" => is_unsafe,
+				restricted => restricted == method,
+			})
+	}
+}
+
+/// Rate limit state of a connection.
+///
+/// Implemented as a token bucket that holds up to a second worth of calls, so short bursts are
+/// allowed as long as the average rate stays below the limit.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+	/// Available calls and the time they were last updated. `None` if no call was made yet.
+	bucket: Mutex<Option<(f64, Instant)>>,
+}
+
+impl RateLimiter {
+	/// Take a call from the bucket, returns `false` if the limit is exceeded.
+	fn try_acquire(&self, calls_per_second: u32) -> bool {
+		let now = Instant::now();
+		let rate = f64::from(calls_per_second);
+		let mut bucket = self.bucket.lock();
+		let available = match *bucket {
+			Some((available, updated)) => {
+				let elapsed = now.duration_since(updated);
+				let elapsed = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) / 1e9;
+				(available + elapsed * rate).min(rate)
+			},
+			None => rate,
+		};
+
+		let acquired = available >= 1.0;
+		*bucket = Some((if acquired { available - 1.0 } else { available }, now));
+		acquired
+	}
+
+	/// Whether no call was made for a second, so the bucket is full again.
+	fn is_idle(&self, now: Instant) -> bool {
+		self.bucket.lock().map_or(true, |(_, updated)| now.duration_since(updated) >= Duration::from_secs(1))
+	}
+}
+
+/// Rate limit state per remote address, for transports without persistent connections.
+#[derive(Debug, Default)]
+pub struct RateLimiters {
+	limiters: Mutex<HashMap<IpAddr, Arc<RateLimiter>>>,
+}
+
+impl RateLimiters {
+	/// The rate limit state of `address`.
+	pub fn get(&self, address: IpAddr) -> Arc<RateLimiter> {
+		let mut limiters = self.limiters.lock();
+		if !limiters.contains_key(&address) {
+			// Forgetting idle addresses doesn't change their limit, and bounds the memory to the
+			// addresses that made a call in the last second.
+			let now = Instant::now();
+			limiters.retain(|_, limiter| !limiter.is_idle(now));
+		}
+		limiters.entry(address).or_default().clone()
+	}
+}
+
+/// Reasons for the middleware to reject a call.
+#[derive(Debug, derive_more::Display)]
+pub enum Error {
+	/// The connection made too many calls.
+	#[display(fmt = "Rate limit of {} calls per second exceeded", _0)]
+	RateLimited(u32),
+	/// The method may only be called on authenticated connections.
+	#[display(fmt = "Method {} is restricted to authenticated connections", _0)]
+	Unauthorized(String),
+	/// The response is too large.
+	#[display(fmt = "Response of {} bytes exceeds the limit of {} bytes", _0, _1)]
+	ResponseTooLarge(usize, usize),
+}
+
+impl std::error::Error for Error {}
+
+impl Error {
+	fn reason(&self) -> &'static str {
+		match self {
+			Error::RateLimited(_) => "rate_limited",
+			Error::Unauthorized(_) => "unauthorized",
+			Error::ResponseTooLarge(..) => "response_too_large",
+		}
+	}
+}
+
+/// Base code for all middleware errors.
+const BASE_ERROR: i64 = 5000;
+
+impl From<Error> for rpc::Error {
+	fn from(e: Error) -> Self {
+		let code = match e {
+			Error::RateLimited(_) => BASE_ERROR + 1,
+			Error::Unauthorized(_) => BASE_ERROR + 2,
+			Error::ResponseTooLarge(..) => BASE_ERROR + 3,
+		};
+		rpc::Error {
+			code: rpc::ErrorCode::ServerError(code),
+			message: format!("{}", e),
+			data: None,
+		}
+	}
+}
+
+type FutureResponse = Box<dyn Future<Item = Option<rpc::Response>, Error = ()> + Send>;
+type FutureOutput = Box<dyn Future<Item = Option<rpc::Output>, Error = ()> + Send>;
+
+/// Middleware that records metrics of and enforces [`Limits`] on all RPC calls.
+#[derive(Debug, Clone, Default)]
+pub struct RpcMiddleware {
+	limits: Arc<Limits>,
+}
+
+impl RpcMiddleware {
+	/// Create a middleware enforcing the given limits.
+	pub fn new(limits: Limits) -> Self {
+		RpcMiddleware { limits: Arc::new(limits) }
+	}
+
+	/// Check whether the call may be made at all.
+	fn check(&self, method: &str, meta: &Metadata) -> Result<(), Error> {
+		if let Some(calls_per_second) = self.limits.calls_per_second {
+			if !meta.rate_limiter().try_acquire(calls_per_second) {
+				return Err(Error::RateLimited(calls_per_second));
+			}
+		}
+
+		if !meta.is_authenticated() && self.limits.is_restricted(method) {
+			return Err(Error::Unauthorized(method.into()));
+		}
+
+		Ok(())
+	}
+
+	/// Replace a successful output that exceeds the maximum response size with an error.
+	fn limit_size(&self, output: rpc::Output) -> rpc::Output {
+		let max_size = match self.limits.max_response_size {
+			Some(max_size) => max_size,
+			None => return output,
+		};
+
+		match output {
+			rpc::Output::Success(success) => {
+				let size = serde_json::to_string(&success.result).map(|s| s.len()).unwrap_or(0);
+				if size <= max_size {
+					return rpc::Output::Success(success);
+				}
+
+				let error = Error::ResponseTooLarge(size, max_size);
+				REJECTED_CALLS.with_label(error.reason()).inc();
+				rpc::Output::from(Err(error.into()), success.id, success.jsonrpc)
+			},
+			failure => failure,
+		}
+	}
+}
+
+impl rpc::Middleware<Metadata> for RpcMiddleware {
+	type Future = FutureResponse;
+	type CallFuture = FutureOutput;
+
+	fn on_call<F, X>(&self, call: rpc::Call, meta: Metadata, next: F) -> Either<Self::CallFuture, X> where
+		F: FnOnce(rpc::Call, Metadata) -> X + Send,
+		X: Future<Item = Option<rpc::Output>, Error = ()> + Send + 'static,
+	{
+		let method = match call {
+			rpc::Call::MethodCall(ref call) => call.method.clone(),
+			rpc::Call::Notification(ref notification) => notification.method.clone(),
+			rpc::Call::Invalid { .. } => return Either::B(next(call, meta)),
+		};
+
+		if let Err(error) = self.check(&method, &meta) {
+			REJECTED_CALLS.with_label(error.reason()).inc();
+			let output = match call {
+				rpc::Call::MethodCall(call) => Some(rpc::Output::from(Err(error.into()), call.id, call.jsonrpc)),
+				_ => None,
+			};
+			return Either::A(Box::new(future::ok(output)));
+		}
+
+		let middleware = self.clone();
+		let started = Instant::now();
+		Either::A(Box::new(next(call, meta).map(move |output| {
+			let elapsed = started.elapsed();
+			let label = match output {
+				Some(rpc::Output::Failure(ref failure))
+					if failure.error.code == rpc::ErrorCode::MethodNotFound => UNKNOWN_METHOD,
+				_ => method.as_str(),
+			};
+			CALLS.with_label(label).inc();
+			CALL_TIME.with_label(label).observe(elapsed);
+
+			output.map(|output| middleware.limit_size(output))
+		})))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn handler(limits: Limits) -> rpc::MetaIoHandler<Metadata, RpcMiddleware> {
+		let mut io = rpc::MetaIoHandler::with_middleware(RpcMiddleware::new(limits));
+		io.add_method("test_small", |_| Ok(rpc::Value::String("ok".into())));
+		io.add_method("test_large", |_| Ok(rpc::Value::String("x".repeat(100))));
+		io
+	}
+
+	fn call(io: &rpc::MetaIoHandler<Metadata, RpcMiddleware>, meta: &Metadata, method: &str) -> String {
+		let request = format!(r#"{{"jsonrpc":"2.0","method":"{}","params":[],"id":1}}"#, method);
+		io.handle_request(&request, meta.clone()).wait().unwrap().unwrap()
+	}
+
+	fn error_code(response: &str) -> Option<i64> {
+		let response: serde_json::Value = serde_json::from_str(response).unwrap();
+		response["error"]["code"].as_i64()
+	}
+
+	#[test]
+	fn should_rate_limit_per_connection() {
+		let io = handler(Limits { calls_per_second: Some(2), ..Default::default() });
+		let first = Metadata::default();
+		let second = Metadata::default();
+
+		assert_eq!(error_code(&call(&io, &first, "test_small")), None);
+		assert_eq!(error_code(&call(&io, &first, "test_small")), None);
+		assert_eq!(error_code(&call(&io, &first, "test_small")), Some(BASE_ERROR + 1));
+		assert_eq!(error_code(&call(&io, &second, "test_small")), None);
+	}
+
+	#[test]
+	fn should_reject_restricted_methods_on_unauthenticated_connections() {
+		let io = handler(Limits { restricted_methods: vec!["test_small".into()], ..Default::default() });
+
+		assert_eq!(error_code(&call(&io, &Metadata::default(), "test_small")), Some(BASE_ERROR + 2));
+		assert_eq!(error_code(&call(&io, &Metadata::default().authenticated(true), "test_small")), None);
+		assert_eq!(error_code(&call(&io, &Metadata::default(), "test_large")), None);
+	}
+
+	#[test]
+	fn should_expand_expensive_methods() {
+		let limits = Limits { restricted_methods: vec!["expensive".into()], ..Default::default() };
+
+		assert!(limits.is_restricted("state_queryStorage"));
+		assert!(!limits.is_restricted("state_getStorage"));
+	}
+
+	#[test]
+	fn should_restrict_unsafe_methods_by_default() {
+		assert!(Limits::default().is_restricted("author_rotateKeys"));
+		assert!(Limits::default().is_restricted("system_addLogFilter"));
+		assert!(!Limits::default().is_restricted("state_queryStorage"));
+
+		let allowed = Limits { allow_unsafe: true, ..Default::default() };
+		assert!(!allowed.is_restricted("author_rotateKeys"));
+
+		let restricted = Limits { allow_unsafe: true, restricted_methods: vec!["unsafe".into()], ..Default::default() };
+		assert!(restricted.is_restricted("author_rotateKeys"));
+	}
+
+	#[test]
+	fn should_share_rate_limit_per_address() {
+		let limiters = RateLimiters::default();
+		let first: IpAddr = "10.0.0.1".parse().unwrap();
+		let second: IpAddr = "10.0.0.2".parse().unwrap();
+
+		assert!(limiters.get(first).try_acquire(1));
+		assert!(!limiters.get(first).try_acquire(1));
+		assert!(limiters.get(second).try_acquire(1));
+	}
+
+	#[test]
+	fn should_limit_response_size() {
+		let io = handler(Limits { max_response_size: Some(50), ..Default::default() });
+
+		assert_eq!(error_code(&call(&io, &Metadata::default(), "test_small")), None);
+		assert_eq!(error_code(&call(&io, &Metadata::default(), "test_large")), Some(BASE_ERROR + 3));
+	}
+}
@@ -60,6 +60,11 @@ impl From<Error> for rpc::Error {
 				message: format!("{}", e),
 				data: None,
 			},
+			Error::Client(client::error::Error::Execution(ref err)) => rpc::Error {
+				code: rpc::ErrorCode::ServerError(BASE_ERROR + 2),
+				message: "Runtime call failed".into(),
+				data: Some(format!("{}", err).into()),
+			},
 			e => errors::internal(e),
 		}
 	}
@@ -29,16 +29,19 @@ use std::{
 use futures03::{future, StreamExt as _, TryStreamExt as _};
 
 use client::{self, Client, CallExecutor, BlockchainEvents, runtime_api::Metadata};
+use client::light::fetcher::{Fetcher, RemoteChangesRequest};
+use codec::{Encode, Decode};
 use crate::rpc::Result as RpcResult;
 use crate::rpc::futures::{stream, Future, Sink, Stream};
 use crate::subscriptions::Subscriptions;
 use jsonrpc_derive::rpc;
 use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId};
 use log::{warn, trace};
+use network::OnDemand;
 use primitives::hexdisplay::HexDisplay;
-use primitives::storage::{self, StorageKey, StorageData, StorageChangeSet};
-use primitives::{H256, Blake2Hasher, Bytes};
-use sr_primitives::generic::BlockId;
+use primitives::storage::{self, StorageKey, StorageData, StorageChangeSet, well_known_keys};
+use primitives::{H256, Blake2Hasher, Bytes, ChangesTrieConfiguration, tracing};
+use sr_primitives::generic::{BlockId, DigestItem};
 use sr_primitives::traits::{
 	Block as BlockT, Header, ProvideRuntimeApi, NumberFor,
 	SaturatedConversion
@@ -131,6 +134,13 @@ pub trait StateApi<Hash> {
 		hash: Option<Hash>
 	) -> Result<Vec<StorageChangeSet<Hash>>>;
 
+	/// Re-execute a block on top of its parent and return the runtime tracing spans.
+	///
+	/// Spans are given in the order they were entered. Every dispatched call has a span of its
+	/// own, so the spans of depth 0 usually map to the extrinsics of the block.
+	#[rpc(name = "state_traceBlock")]
+	fn trace_block(&self, block: Hash) -> Result<Vec<tracing::Span>>;
+
 	/// New runtime version subscription
 	#[pubsub(
 		subscription = "state_runtimeVersion",
@@ -168,6 +178,8 @@ pub struct State<B, E, Block: BlockT, RA> {
 	client: Arc<Client<B, E, Block, RA>>,
 	/// Current subscriptions.
 	subscriptions: Subscriptions,
+	/// Fetcher of the light client, used to query key changes from full nodes.
+	fetcher: Option<Arc<OnDemand<Block>>>,
 }
 
 /// Ranges to query in state_queryStorage.
@@ -194,9 +206,16 @@ impl<B, E, Block: BlockT, RA> State<B, E, Block, RA> where
 		Self {
 			client,
 			subscriptions,
+			fetcher: None,
 		}
 	}
 
+	/// Query key changes through the light client `fetcher`, instead of the local changes tries.
+	pub fn with_fetcher(mut self, fetcher: Arc<OnDemand<Block>>) -> Self {
+		self.fetcher = Some(fetcher);
+		self
+	}
+
 	/// Splits the `query_storage` block range into 'filtered' and 'unfiltered' subranges.
 	/// Blocks that contain changes within filtered subrange could be filtered using changes tries.
 	/// Blocks that contain changes within unfiltered subrange must be filtered manually.
@@ -238,8 +257,8 @@ impl<B, E, Block: BlockT, RA> State<B, E, Block, RA> where
 					blocks
 				};
 				// check if we can filter blocks-with-changes from some (sub)range using changes tries
-				let changes_trie_range = self.client.max_key_changes_range(from_number, BlockId::Hash(to.hash()))?;
-				let filtered_range_begin = changes_trie_range.map(|(begin, _)| (begin - from_number).saturated_into::<usize>());
+				let filtered_range_begin = self.max_key_changes_range_begin(from_number, to.hash())?
+					.map(|begin| (begin - from_number).saturated_into::<usize>());
 				let (unfiltered_range, filtered_range) = split_range(blocks.len(), filtered_range_begin);
 				Ok(QueryStorageRange {
 					hashes: blocks,
@@ -254,6 +273,64 @@ impl<B, E, Block: BlockT, RA> State<B, E, Block, RA> where
 		}
 	}
 
+	/// Returns the first block of the longest range within [from; to] that key changes can be
+	/// queried for, or `None` if changes tries are not supported.
+	///
+	/// Light clients don't know which changes tries are pruned by full nodes, so they query the
+	/// whole range.
+	fn max_key_changes_range_begin(
+		&self,
+		from: NumberFor<Block>,
+		to: Block::Hash,
+	) -> Result<Option<NumberFor<Block>>> {
+		if self.fetcher.is_none() {
+			return Ok(self.client.max_key_changes_range(from, BlockId::Hash(to))?.map(|(begin, _)| begin));
+		}
+		Ok(self.changes_trie_config(to)?.map(|_| from))
+	}
+
+	/// Reads the changes trie configuration from the state at `block`.
+	fn changes_trie_config(&self, block: Block::Hash) -> Result<Option<ChangesTrieConfiguration>> {
+		let key = StorageKey(well_known_keys::CHANGES_TRIE_CONFIG.to_vec());
+		Ok(self.client.storage(&BlockId::Hash(block), &key)?
+			.and_then(|config| Decode::decode(&mut &config.0[..]).ok()))
+	}
+
+	/// Returns the (block, extrinsic) pairs where `key` has been changed within the `filtered`
+	/// subrange of `range`, in descending order.
+	fn key_changes(
+		&self,
+		range: &QueryStorageRange<Block>,
+		filtered: &Range<usize>,
+		key: &StorageKey,
+	) -> Result<Vec<(NumberFor<Block>, u32)>> {
+		let first = (range.first_number + filtered.start.saturated_into(), range.hashes[filtered.start]);
+		let last = (range.first_number + (filtered.end - 1).saturated_into(), range.hashes[filtered.end - 1]);
+		let fetcher = match self.fetcher {
+			Some(ref fetcher) => fetcher,
+			None => return Ok(self.client.key_changes(first.0, BlockId::Hash(last.1), key)?),
+		};
+
+		let changes_trie_config = self.changes_trie_config(last.1)?
+			.ok_or(client::error::Error::ChangesTriesNotSupported)?;
+		let tries_roots = range.hashes[filtered.clone()].iter()
+			.map(|hash| self.client.header(&BlockId::Hash(*hash)).map(|header| header
+				.and_then(|header| header.digest().log(DigestItem::as_changes_trie_root).cloned())
+				.unwrap_or_default()
+			))
+			.collect::<client::error::Result<_>>()?;
+		let changes = futures03::executor::block_on(fetcher.remote_changes(RemoteChangesRequest {
+			changes_trie_config,
+			first_block: first,
+			last_block: last,
+			max_block: last,
+			tries_roots: (first.0, first.1, tries_roots),
+			key: key.0.clone(),
+			retry_count: None,
+		}))?;
+		Ok(changes)
+	}
+
 	/// Iterates through range.unfiltered_range and check each block for changes of keys' values.
 	fn query_storage_unfiltered(
 		&self,
@@ -294,18 +371,15 @@ impl<B, E, Block: BlockT, RA> State<B, E, Block, RA> where
 		last_values: &HashMap<StorageKey, Option<StorageData>>,
 		changes: &mut Vec<StorageChangeSet<Block::Hash>>,
 	) -> Result<()> {
-		let (begin, end) = match range.filtered_range {
-			Some(ref filtered_range) => (
-				range.first_number + filtered_range.start.saturated_into(),
-				BlockId::Hash(range.hashes[filtered_range.end - 1].clone())
-			),
+		let filtered_range = match range.filtered_range {
+			Some(ref filtered_range) => filtered_range,
 			None => return Ok(()),
 		};
 		let mut changes_map: BTreeMap<NumberFor<Block>, StorageChangeSet<Block::Hash>> = BTreeMap::new();
 		for key in keys {
 			let mut last_block = None;
 			let mut last_value = last_values.get(key).cloned().unwrap_or_default();
-			for (block, _) in self.client.key_changes(begin, end, key)?.into_iter().rev() {
+			for (block, _) in self.key_changes(range, filtered_range, key)?.into_iter().rev() {
 				if last_block == Some(block) {
 					continue;
 				}
@@ -437,6 +511,27 @@ impl<B, E, Block, RA> StateApi<Block::Hash> for State<B, E, Block, RA> where
 		self.client.runtime_api().metadata(&BlockId::Hash(block)).map(Into::into).map_err(Into::into)
 	}
 
+	fn trace_block(&self, block: Block::Hash) -> Result<Vec<tracing::Span>> {
+		trace!(target: "rpc", "Tracing block {:?}", block);
+		let (mut header, extrinsics) = self.client.block(&BlockId::Hash(block))?
+			.ok_or_else(|| client::error::Error::UnknownBlock(format!("{}", block)))?
+			.block
+			.deconstruct();
+		// seals are added after execution, so the runtime doesn't expect them.
+		while header.digest().logs().last().map_or(false, |log| log.as_seal().is_some()) {
+			header.digest_mut().pop();
+		}
+		let parent = BlockId::Hash(*header.parent_hash());
+		let block = Block::new(header, extrinsics).encode();
+
+		let (result, spans) = tracing::collect(|| self.client.executor().call(
+			&parent,
+			"Core_execute_block", &block, ExecutionStrategy::NativeElseWasm, state_machine::NeverOffchainExt::new(),
+		));
+		result?;
+		Ok(spans)
+	}
+
 	fn query_storage(
 		&self,
 		keys: Vec<StorageKey>,
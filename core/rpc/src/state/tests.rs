@@ -90,6 +90,36 @@ fn should_call_contract() {
 	)
 }
 
+#[test]
+fn should_trace_block() {
+	let core = tokio::runtime::Runtime::new().unwrap();
+	let client = Arc::new(test_client::new());
+	let api = State::new(client.clone(), Subscriptions::new(Arc::new(core.executor())));
+
+	let mut builder = client.new_block(Default::default()).unwrap();
+	builder.push_transfer(runtime::Transfer {
+		from: AccountKeyring::Alice.into(),
+		to: AccountKeyring::Ferdie.into(),
+		amount: 42,
+		nonce: 0,
+	}).unwrap();
+	let block = builder.bake().unwrap();
+	let hash = block.header.hash();
+	client.import(BlockOrigin::Own, block).unwrap();
+
+	let spans = api.trace_block(hash).unwrap();
+	assert_eq!(spans.len(), 1);
+	assert_eq!(spans[0].name, "execute_transaction");
+	assert_eq!(spans[0].depth, 0);
+	assert!(spans[0].storage_reads > 0);
+	assert!(spans[0].storage_writes > 0);
+
+	assert_matches!(
+		api.trace_block(H256::from([1u8; 32])),
+		Err(Error::Client(client::error::Error::UnknownBlock(_)))
+	);
+}
+
 #[test]
 fn should_notify_about_storage_changes() {
 	let mut core = tokio::runtime::Runtime::new().unwrap();
@@ -260,7 +290,7 @@ fn should_return_runtime_version() {
 		\"specVersion\":1,\"implVersion\":1,\"apis\":[[\"0xdf6acb689907609b\",2],\
 		[\"0x37e397fc7c91f5e4\",1],[\"0xd2bc9897eed08f15\",1],[\"0x40fe3ad401f8959a\",3],\
 		[\"0xc6e9a76309f39b09\",1],[\"0xdd718d5cc53262d4\",1],[\"0xcbca25e39f142387\",1],\
-		[\"0xf78b278be53f454c\",1]]}";
+		[\"0xf78b278be53f454c\",1]],\"transactionVersion\":1}";
 	assert_eq!(
 		serde_json::to_string(&api.runtime_version(None.into()).unwrap()).unwrap(),
 		result,
@@ -16,7 +16,8 @@
 
 //! Substrate system API helpers.
 
-use std::fmt;
+use std::{fmt, collections::BTreeMap};
+use primitives::Bytes;
 use serde::{Serialize, Deserialize};
 use serde_json::{Value, map::Map};
 
@@ -50,6 +51,23 @@ pub struct Health {
 	pub should_have_peers: bool,
 }
 
+/// The state a light client needs to start syncing from the latest finalized block instead of
+/// the genesis block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LightSyncState {
+	/// The SCALE-encoded header of the latest finalized block.
+	pub finalized_block_header: Bytes,
+	/// The SCALE-encoded GRANDPA authority set, as stored by the finality gadget.
+	pub grandpa_authority_set: Option<Bytes>,
+	/// The SCALE-encoded BABE epoch changes, as stored by the consensus engine.
+	pub babe_epoch_changes: Option<Bytes>,
+	/// The values of the blockchain cache at the latest finalized block, by cache key
+	/// (e.g. `epch` for the BABE epoch).
+	#[serde(default)]
+	pub cache: BTreeMap<String, Bytes>,
+}
+
 /// Network Peer information
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -23,16 +23,20 @@ pub mod helpers;
 mod tests;
 
 use crate::helpers::Receiver;
+use futures::Future;
 use futures03::{channel::{mpsc, oneshot}, compat::Compat};
 use jsonrpc_derive::rpc;
 use network;
 use sr_primitives::traits::{self, Header as HeaderT};
 
 use self::error::Result;
-pub use self::helpers::{Properties, SystemInfo, Health, PeerInfo};
+pub use self::helpers::{Properties, SystemInfo, Health, PeerInfo, LightSyncState};
 
 pub use self::gen_client::Client as SystemClient;
 
+/// Future that resolves to the result of a request answered by the service.
+pub type FutureResult<T> = Box<dyn Future<Item = T, Error = jsonrpc_core::Error> + Send>;
+
 /// Substrate system RPC API
 #[rpc]
 pub trait SystemApi<Hash, Number> {
@@ -70,6 +74,39 @@ pub trait SystemApi<Hash, Number> {
 	// TODO: make this stable and move structs https://github.com/paritytech/substrate/issues/1890
 	#[rpc(name = "system_networkState", returns = "network::NetworkState")]
 	fn system_network_state(&self) -> Receiver<network::NetworkState>;
+
+	/// Returns the state a light client needs to sync from the latest finalized block.
+	///
+	/// The result can be embedded as the `lightSyncState` section of the chain spec.
+	#[rpc(name = "system_lightSyncState", returns = "LightSyncState")]
+	fn system_light_sync_state(&self) -> Receiver<LightSyncState>;
+
+	/// Adds a reserved peer, given as multiaddr ending with `/p2p/<PeerId>`.
+	///
+	/// The peer stays reserved across restarts of the node.
+	#[rpc(name = "system_addReservedPeer", returns = "()")]
+	fn system_add_reserved_peer(&self, peer: String) -> FutureResult<()>;
+
+	/// Removes the reserved peer with the given `PeerId`.
+	#[rpc(name = "system_removeReservedPeer", returns = "()")]
+	fn system_remove_reserved_peer(&self, peer_id: String) -> FutureResult<()>;
+
+	/// Adds a boot node, given as multiaddr ending with `/p2p/<PeerId>`.
+	///
+	/// The boot node is kept across restarts of the node.
+	#[rpc(name = "system_addBootNode", returns = "()")]
+	fn system_add_boot_node(&self, peer: String) -> FutureResult<()>;
+
+	/// Adds the supplied directives to the current log filter.
+	///
+	/// The syntax is identical to the CLI `<target>=<level>`, e.g. `sync=debug,afg=trace`.
+	/// Directives added this way take precedence over the ones given on startup.
+	#[rpc(name = "system_addLogFilter")]
+	fn system_add_log_filter(&self, directives: String) -> Result<()>;
+
+	/// Resets the log filter to the one the node was started with.
+	#[rpc(name = "system_resetLogFilter")]
+	fn system_reset_log_filter(&self) -> Result<()>;
 }
 
 /// System API implementation
@@ -86,6 +123,14 @@ pub enum Request<B: traits::Block> {
 	Peers(oneshot::Sender<Vec<PeerInfo<B::Hash, <B::Header as HeaderT>::Number>>>),
 	/// Must return the state of the network.
 	NetworkState(oneshot::Sender<network::NetworkState>),
+	/// Must return the state a light client needs to sync from the latest finalized block.
+	LightSyncState(oneshot::Sender<LightSyncState>),
+	/// Must add the given reserved peer and return an error if it is invalid.
+	AddReservedPeer(String, oneshot::Sender<std::result::Result<(), String>>),
+	/// Must remove the reserved peer with the given `PeerId` and return an error if it is invalid.
+	RemoveReservedPeer(String, oneshot::Sender<std::result::Result<(), String>>),
+	/// Must add the given boot node and return an error if it is invalid.
+	AddBootNode(String, oneshot::Sender<std::result::Result<(), String>>),
 }
 
 impl<B: traits::Block> System<B> {
@@ -138,4 +183,42 @@ impl<B: traits::Block> SystemApi<B::Hash, <B::Header as HeaderT>::Number> for Sy
 		let _ = self.send_back.unbounded_send(Request::NetworkState(tx));
 		Receiver(Compat::new(rx))
 	}
+
+	fn system_light_sync_state(&self) -> Receiver<LightSyncState> {
+		let (tx, rx) = oneshot::channel();
+		let _ = self.send_back.unbounded_send(Request::LightSyncState(tx));
+		Receiver(Compat::new(rx))
+	}
+
+	fn system_add_reserved_peer(&self, peer: String) -> FutureResult<()> {
+		let (tx, rx) = oneshot::channel();
+		let _ = self.send_back.unbounded_send(Request::AddReservedPeer(peer, tx));
+		peer_result(rx)
+	}
+
+	fn system_remove_reserved_peer(&self, peer_id: String) -> FutureResult<()> {
+		let (tx, rx) = oneshot::channel();
+		let _ = self.send_back.unbounded_send(Request::RemoveReservedPeer(peer_id, tx));
+		peer_result(rx)
+	}
+
+	fn system_add_boot_node(&self, peer: String) -> FutureResult<()> {
+		let (tx, rx) = oneshot::channel();
+		let _ = self.send_back.unbounded_send(Request::AddBootNode(peer, tx));
+		peer_result(rx)
+	}
+
+	fn system_add_log_filter(&self, directives: String) -> Result<()> {
+		substrate_logger::add_filter(&directives).map_err(error::Error::InvalidLogFilter)
+	}
+
+	fn system_reset_log_filter(&self) -> Result<()> {
+		substrate_logger::reset_filter();
+		Ok(())
+	}
+}
+
+/// Turns the answer to a peer request into an RPC result.
+fn peer_result(rx: oneshot::Receiver<std::result::Result<(), String>>) -> FutureResult<()> {
+	Box::new(Receiver(Compat::new(rx)).and_then(|result| result.map_err(|e| error::Error::InvalidPeer(e).into())))
 }
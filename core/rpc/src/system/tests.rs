@@ -80,6 +80,24 @@ fn api<T: Into<Option<Status>>>(sync: T) -> System<Block> {
 						peerset: serde_json::Value::Null,
 					});
 				}
+				Request::LightSyncState(sender) => {
+					let _ = sender.send(LightSyncState {
+						finalized_block_header: vec![1, 2, 3].into(),
+						grandpa_authority_set: Some(vec![4, 5].into()),
+						babe_epoch_changes: None,
+						cache: vec![("epch".to_string(), vec![6].into())].into_iter().collect(),
+					});
+				}
+				Request::AddReservedPeer(peer, sender) | Request::AddBootNode(peer, sender) => {
+					let _ = sender.send(network::config::parse_str_addr(&peer)
+						.map(|_| ())
+						.map_err(|e| format!("{:?}", e)));
+				}
+				Request::RemoveReservedPeer(peer_id, sender) => {
+					let _ = sender.send(peer_id.parse::<PeerId>()
+						.map(|_| ())
+						.map_err(|e| format!("{:?}", e)));
+				}
 			};
 
 			future::ready(())
@@ -220,3 +238,40 @@ fn system_network_state() {
 		}
 	);
 }
+
+#[test]
+fn system_add_log_filter_rejects_invalid_directives() {
+	assert_matches!(
+		api(None).system_add_log_filter("sync=loud".into()),
+		Err(error::Error::InvalidLogFilter(_))
+	);
+	assert_matches!(api(None).system_reset_log_filter(), Ok(()));
+}
+
+#[test]
+fn system_light_sync_state_works() {
+	let sync_state = wait_receiver(api(None).system_light_sync_state());
+
+	assert_eq!(
+		serde_json::to_value(&sync_state).unwrap(),
+		serde_json::json!({
+			"finalizedBlockHeader": "0x010203",
+			"grandpaAuthoritySet": "0x0405",
+			"babeEpochChanges": null,
+			"cache": { "epch": "0x06" },
+		})
+	);
+}
+
+#[test]
+fn system_reserved_peers_work() {
+	let peer_id = PeerId::random();
+	let peer = format!("/ip4/198.51.100.19/tcp/30333/p2p/{}", peer_id.to_base58());
+	let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+
+	assert_eq!(runtime.block_on(api(None).system_add_reserved_peer(peer.clone())), Ok(()));
+	assert_eq!(runtime.block_on(api(None).system_add_boot_node(peer)), Ok(()));
+	assert_eq!(runtime.block_on(api(None).system_remove_reserved_peer(peer_id.to_base58())), Ok(()));
+	assert!(runtime.block_on(api(None).system_add_reserved_peer("/ip4/198.51.100.19".into())).is_err());
+	assert!(runtime.block_on(api(None).system_remove_reserved_peer("invalid".into())).is_err());
+}
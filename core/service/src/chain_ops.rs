@@ -16,22 +16,34 @@
 
 //! Chain utilities.
 
-use std::{self, io::{Read, Write, Seek}};
+use std::{self, collections::{BTreeMap, BTreeSet, HashMap, HashSet}, io::{Read, Write, Seek}, time::Instant};
 use futures::prelude::*;
 use futures03::TryFutureExt as _;
 use log::{info, warn};
 
+use primitives::{Blake2Hasher, blake2_128, blake2_256, twox_64, twox_128, twox_256};
+use primitives::storage::{StorageKey, StorageData, well_known_keys};
+use sr_primitives::weights::Weight;
 use sr_primitives::generic::{SignedBlock, BlockId};
 use sr_primitives::traits::{SaturatedConversion, Zero, One, Block, Header, NumberFor};
 use consensus_common::import_queue::{ImportQueue, IncomingBlock, Link, BlockImportError, BlockImportResult};
 use network::message;
 
+use benchmarking::{ComponentRange, ComponentValues};
+use client::{CallExecutor, ExecutionStrategy, NeverOffchainExt};
+use state_machine::{CodeExecutor, OverlayedChanges, InMemoryChangesTrieStorage, backend::InMemory};
 use consensus_common::BlockOrigin;
-use crate::components::{self, Components, ServiceFactory, FactoryFullConfiguration, FactoryBlockNumber, RuntimeGenesis};
+use crate::components::{
+	self, Components, ComponentClient, FullComponents, ServiceFactory, FactoryFullConfiguration,
+	FactoryBlockNumber, RuntimeGenesis, ChainSpecExtension,
+};
+use srml_metadata::{
+	DecodeDifferent, RuntimeMetadata, RuntimeMetadataPrefixed, StorageEntryType, StorageHasher,
+};
 use crate::new_client;
 use codec::{Decode, Encode, IoReader};
 use crate::error;
-use crate::chain_spec::ChainSpec;
+use crate::chain_spec::{ChainSpec, StorageOverride};
 
 /// Export a range of blocks to a binary stream.
 pub fn export_blocks<F, E, W>(
@@ -50,12 +62,20 @@ pub fn export_blocks<F, E, W>(
 	let client = new_client::<F>(&config)?;
 	let mut block = from;
 
+	let best_number = client.info().chain.best_number;
 	let last = match to {
 		Some(v) if v.is_zero() => One::one(),
 		Some(v) => v,
-		None => client.info().chain.best_number,
+		None => best_number,
 	};
 
+	// The binary format announces the number of blocks up front, so blocks that we don't
+	// have must not be promised to the importing side.
+	if last > best_number {
+		warn!("Requested block #{} is not yet known, exporting up to #{}", last, best_number);
+	}
+	let last = std::cmp::min(last, best_number);
+
 	if last < block {
 		return Err("Invalid block range specified".into());
 	}
@@ -193,7 +213,7 @@ pub fn import_blocks<F, E, R>(
 			}
 		}
 
-		block_count = b;
+		block_count = b + 1;
 		if b % 1000 == 0 && b != 0 {
 			info!("#{} blocks were added to the queue", b);
 		}
@@ -221,10 +241,12 @@ pub fn import_blocks<F, E, R>(
 			info!(
 				"#{} blocks were imported (#{} left)",
 				link.imported_blocks,
-				count - link.imported_blocks
+				block_count.saturating_sub(link.imported_blocks)
 			);
 		}
-		if link.imported_blocks >= count {
+		// Only wait for the blocks that were actually handed over to the queue, a truncated
+		// input would otherwise keep us waiting forever.
+		if link.imported_blocks >= block_count {
 			info!("Imported {} blocks. Best: #{}", block_count, client.info().chain.best_number);
 			Ok(Async::Ready(()))
 		} else {
@@ -252,9 +274,832 @@ pub fn revert_chain<F>(
 	Ok(())
 }
 
+/// Fork off the chain: build a raw chain spec whose genesis storage, child tries included, is the
+/// state of the chain at block `at`, or at the best block if `None`, with `overrides` applied.
+///
+/// The bootnodes of the chain are removed, the nodes of the fork must not connect to them.
+pub fn fork_off<F>(
+	config: FactoryFullConfiguration<F>,
+	at: Option<FactoryBlockNumber<F>>,
+	overrides: HashMap<StorageKey, StorageOverride>,
+) -> error::Result<String>
+	where F: ServiceFactory,
+{
+	let client = new_client::<F>(&config)?;
+	let at = match at {
+		Some(number) => BlockId::Number(number),
+		None => BlockId::Hash(client.info().chain.best_hash),
+	};
+	info!("Forking off the chain at {}", at);
+
+	let state = read_state::<F>(&client, &at)?;
+	info!(
+		"Forked off {} storage entries and {} child tries",
+		state.get(&None).map_or(0, HashMap::len),
+		state.keys().filter(|child| child.is_some()).count(),
+	);
+	fork_spec(config.chain_spec, state, overrides)
+}
+
+/// Build the raw chain spec of a fork of the chain of `spec` whose genesis is `state`.
+fn fork_spec<G, Ext>(
+	mut spec: ChainSpec<G, Ext>,
+	mut state: State,
+	overrides: HashMap<StorageKey, StorageOverride>,
+) -> error::Result<String>
+	where G: RuntimeGenesis, Ext: ChainSpecExtension,
+{
+	let storage = state.remove(&None).unwrap_or_default();
+	let children = state.into_iter().filter_map(|(child, values)| Some((child?, values))).collect();
+	spec.set_genesis_storage(storage, children);
+	spec.clear_boot_nodes();
+	spec.apply_storage_overrides(overrides)?;
+	build_spec(spec, true)
+}
+
+/// The whole state of a block: the values of the top-level storage under `None` and of each child
+/// trie under its key.
+type State = HashMap<Option<Vec<u8>>, HashMap<Vec<u8>, Vec<u8>>>;
+
+fn read_state<F: ServiceFactory>(
+	client: &ComponentClient<FullComponents<F>>,
+	at: &BlockId<F::Block>,
+) -> error::Result<State> {
+	let mut storage = State::new();
+	for key in client.storage_keys(at, &StorageKey(Vec::new()))? {
+		if well_known_keys::is_child_storage_key(&key.0) {
+			let child = storage.entry(Some(key.0.clone())).or_default();
+			for child_key in client.child_storage_keys(at, &key, &StorageKey(Vec::new()))? {
+				if let Some(value) = client.child_storage(at, &key, &child_key)? {
+					child.insert(child_key.0, value.0);
+				}
+			}
+		} else if let Some(value) = client.storage(at, &key)? {
+			storage.entry(None).or_default().insert(key.0, value.0);
+		}
+	}
+	Ok(storage)
+}
+
+/// A change made to the storage by a runtime upgrade, or between two blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageChange {
+	/// The key of the child trie, `None` for the top-level storage.
+	pub child: Option<StorageKey>,
+	/// The changed key.
+	pub key: StorageKey,
+	/// The value before the upgrade.
+	pub before: Option<StorageData>,
+	/// The value after the upgrade.
+	pub after: Option<StorageData>,
+}
+
+/// The outcome of running a runtime upgrade over the state of the chain.
+#[derive(Debug, Clone)]
+pub struct TryRuntimeResult {
+	/// The changes made by the upgrade, sorted by child trie and key. They are relative to the
+	/// state of the block initialized by the current runtime, so the changes every block makes,
+	/// like the block number, are left out.
+	pub changes: Vec<StorageChange>,
+	/// The execution time of the upgrade in nanoseconds.
+	pub nanos: u128,
+	/// The execution time of the initialization of the block by the current runtime in
+	/// nanoseconds.
+	pub baseline_nanos: u128,
+	/// The weight registered by the new runtime while initializing the block, `None` if it
+	/// doesn't keep track of the weight of its blocks in `System AllExtrinsicsWeight`.
+	pub weight: Option<Weight>,
+}
+
+/// Try a runtime upgrade against the state of the chain at block `at`, or at the best block if
+/// `None`.
+///
+/// The state is copied into memory and the block following `at` is initialized twice: once by
+/// the current runtime, as a baseline, and once after replacing `:code` by `code`, which runs the
+/// migrations of the modules of the new runtime. Nothing is written to the database. A panic of
+/// either runtime is returned as an error.
+pub fn try_runtime<F>(
+	config: FactoryFullConfiguration<F>,
+	at: Option<FactoryBlockNumber<F>>,
+	code: Vec<u8>,
+) -> error::Result<TryRuntimeResult>
+	where F: ServiceFactory,
+{
+	let client = new_client::<F>(&config)?;
+	let at = match at {
+		Some(number) => BlockId::Number(number),
+		None => BlockId::Hash(client.info().chain.best_hash),
+	};
+	let parent = client.header(&at)?.ok_or_else(|| format!("Unknown block {}", at))?;
+	info!("Loading the state at {}", at);
+
+	let state = read_state::<F>(&client, &at)?;
+	info!("Loaded {} storage entries", state.values().map(HashMap::len).sum::<usize>());
+
+	let header = <<F::Block as Block>::Header as Header>::new(
+		*parent.number() + One::one(),
+		Default::default(),
+		Default::default(),
+		parent.hash(),
+		Default::default(),
+	);
+	upgrade::<F::Block, _>(&crate::new_executor::<F>(&config), state, &header, code)
+}
+
+/// Initialize the block `header` on top of `state` with the current runtime and with `code`, and
+/// compare both outcomes.
+fn upgrade<B: Block, E: CodeExecutor<Blake2Hasher>>(
+	executor: &E,
+	state: State,
+	header: &B::Header,
+	code: Vec<u8>,
+) -> error::Result<TryRuntimeResult> {
+	let (baseline, baseline_nanos) = initialize_block::<B, _>(executor, state.clone(), header)
+		.map_err(|e| format!("Initializing the block with the current runtime failed: {}", e))?;
+	let mut upgraded = state;
+	upgraded.entry(None).or_default().insert(well_known_keys::CODE.to_vec(), code);
+	let (upgraded, nanos) = initialize_block::<B, _>(executor, upgraded, header)
+		.map_err(|e| format!("Runtime upgrade failed: {}", e))?;
+
+	let value = |state: &State, child: &Option<Vec<u8>>, key: &[u8]| {
+		state.get(child).and_then(|values| values.get(key)).cloned()
+	};
+	let mut keys: Vec<_> = baseline.iter().chain(upgraded.iter())
+		.flat_map(|(child, values)| values.keys().map(move |key| (child.clone(), key.clone())))
+		.collect();
+	keys.sort();
+	keys.dedup();
+	let changes = keys.into_iter().filter_map(|(child, key)| {
+		let (before, after) = (value(&baseline, &child, &key), value(&upgraded, &child, &key));
+		if before == after {
+			return None;
+		}
+		Some(StorageChange {
+			child: child.map(StorageKey),
+			key: StorageKey(key),
+			before: before.map(StorageData),
+			after: after.map(StorageData),
+		})
+	}).collect();
+
+	let weight = value(&upgraded, &None, &twox_128(b"System AllExtrinsicsWeight"))
+		.and_then(|weight| Weight::decode(&mut &weight[..]).ok());
+
+	Ok(TryRuntimeResult { changes, nanos, baseline_nanos, weight })
+}
+
+/// Initialize the block `header` on top of `state` with the runtime in its `:code`.
+///
+/// Returns the state of the initialized block and the execution time in nanoseconds.
+fn initialize_block<B: Block, E: CodeExecutor<Blake2Hasher>>(
+	executor: &E,
+	mut state: State,
+	header: &B::Header,
+) -> Result<(State, u128), String> {
+	let backend = InMemory::<Blake2Hasher>::from(state.clone());
+	let mut overlay = OverlayedChanges::default();
+	let start = Instant::now();
+	state_machine::new(
+		&backend,
+		None::<&InMemoryChangesTrieStorage<Blake2Hasher, NumberFor<B>>>,
+		NeverOffchainExt::new(),
+		&mut overlay,
+		executor,
+		"Core_initialize_block",
+		&header.encode(),
+	)
+		.execute(ExecutionStrategy::AlwaysWasm)
+		.map_err(|e| e.to_string())?;
+	let nanos = start.elapsed().as_nanos();
+
+	overlay.commit_prospective();
+	let (mut top, children) = overlay.into_committed();
+	let mut apply = |child: Option<Vec<u8>>, changes: &mut dyn Iterator<Item = (Vec<u8>, Option<Vec<u8>>)>| {
+		let values = state.entry(child).or_default();
+		for (key, value) in changes {
+			match value {
+				Some(value) => values.insert(key, value),
+				None => values.remove(&key),
+			};
+		}
+	};
+	apply(None, &mut top);
+	for (child, mut values) in children {
+		apply(Some(child), &mut values);
+	}
+	Ok((state, nanos))
+}
+
+/// A storage item declared by a runtime module, as described by the metadata of the runtime.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StorageItem {
+	/// The storage prefix of the module, e.g. `Balances`.
+	pub module: String,
+	/// The name of the item, e.g. `FreeBalance`.
+	pub name: String,
+	/// The type of the keys of a map, e.g. `T::AccountId`, `None` for storage values.
+	pub key_type: Option<String>,
+	/// The type of the values, e.g. `T::Balance`.
+	pub value_type: String,
+}
+
+/// The storage diff between two blocks, grouped by storage item.
+#[derive(Debug, Clone, Default)]
+pub struct StateDiff {
+	/// The changes of each storage item, sorted by module and item. The changes of keys that
+	/// can't be attributed to an item, such as the entries of child tries or of hashed maps whose
+	/// keys appear nowhere else in the state, are under `None`.
+	pub items: BTreeMap<Option<StorageItem>, Vec<StorageChange>>,
+}
+
+/// Compute the storage diff between the blocks `from` and `to`, or the best block if `None`.
+///
+/// Keys are attributed to the storage items found in the metadata of the runtimes of both
+/// blocks. The hashes of storage values and of the heads of linked maps are derived from the
+/// metadata, the entries of linked maps are found by following their links and the entries of
+/// `Twox64Concat` maps keep their key next to its hash. The keys found this way are then tried as
+/// the keys of the maps whose entries are only stored under a hash, such as the `Blake2_256` map
+/// of the balance of each account.
+pub fn state_diff<F>(
+	config: FactoryFullConfiguration<F>,
+	from: FactoryBlockNumber<F>,
+	to: Option<FactoryBlockNumber<F>>,
+) -> error::Result<StateDiff>
+	where F: ServiceFactory,
+{
+	let client = new_client::<F>(&config)?;
+	let from = BlockId::Number(from);
+	let to = match to {
+		Some(number) => BlockId::Number(number),
+		None => BlockId::Hash(client.info().chain.best_hash),
+	};
+	info!("Comparing the state at {} with the state at {}", from, to);
+
+	let mut items = runtime_storage_items::<F>(&client, &to)?;
+	for layout in runtime_storage_items::<F>(&client, &from)? {
+		if !items.iter().any(|known| known.item == layout.item) {
+			items.push(layout);
+		}
+	}
+
+	let before = read_state::<F>(&client, &from)?;
+	let after = read_state::<F>(&client, &to)?;
+	Ok(diff(&items, &before, &after))
+}
+
+/// Group the changes between the states `before` and `after` by the storage items owning them.
+fn diff(items: &[ItemLayout], before: &State, after: &State) -> StateDiff {
+	let top = |state: &State| attribute(items, state.get(&None).unwrap_or(&HashMap::new()));
+	let mut owners = top(after);
+	for (key, owner) in top(before) {
+		owners.entry(key).or_insert(owner);
+	}
+
+	let mut keys: Vec<_> = before.iter().chain(after.iter())
+		.flat_map(|(child, values)| values.keys().map(move |key| (child.clone(), key.clone())))
+		.collect();
+	keys.sort();
+	keys.dedup();
+
+	let mut diff = StateDiff::default();
+	for (child, key) in keys {
+		let value = |state: &State| state.get(&child).and_then(|values| values.get(&key)).cloned();
+		let (before, after) = (value(before), value(after));
+		if before == after {
+			continue;
+		}
+		let item = match child {
+			Some(_) => None,
+			None => owners.get(&key).map(|owner| items[*owner].item.clone()),
+		};
+		diff.items.entry(item).or_default().push(StorageChange {
+			child: child.map(StorageKey),
+			key: StorageKey(key),
+			before: before.map(StorageData),
+			after: after.map(StorageData),
+		});
+	}
+	diff
+}
+
+/// How the keys of a storage item are derived from its prefix, `{module} {name}`.
+#[derive(Debug, Clone, PartialEq)]
+enum ItemKeys {
+	/// A storage value, stored under the `twox_128` hash of the prefix.
+	Value,
+	/// A map, whose entries are stored under the hash of the prefix followed by the encoded key.
+	/// The head of a linked map is stored under the hash of `head of ` followed by the prefix.
+	Map { hasher: StorageHasher, linked: bool },
+	/// A double map, whose entries are stored under the hash of the prefix followed by the
+	/// encoded first key, followed by the hash of the second key.
+	DoubleMap { hasher: StorageHasher },
+}
+
+/// A storage item and the layout of its keys.
+#[derive(Debug, Clone)]
+struct ItemLayout {
+	item: StorageItem,
+	prefix: Vec<u8>,
+	keys: ItemKeys,
+}
+
+/// The storage items declared in the metadata of the runtime at block `at`.
+fn runtime_storage_items<F: ServiceFactory>(
+	client: &ComponentClient<FullComponents<F>>,
+	at: &BlockId<F::Block>,
+) -> error::Result<Vec<ItemLayout>> {
+	let metadata = client.executor().call(
+		at,
+		"Metadata_metadata",
+		&[],
+		ExecutionStrategy::NativeElseWasm,
+		NeverOffchainExt::new(),
+	)?;
+	let metadata = Vec::<u8>::decode(&mut &metadata[..])
+		.map_err(|e| format!("Error decoding the metadata at {}: {}", at, e.what()))?;
+	Ok(storage_items(&metadata).map_err(|e| format!("{} at {}", e, at))?)
+}
+
+/// The storage items declared in the encoded `metadata` of a runtime.
+fn storage_items(metadata: &[u8]) -> Result<Vec<ItemLayout>, String> {
+	let metadata = RuntimeMetadataPrefixed::decode(&mut &metadata[..])
+		.map_err(|e| format!("Error decoding the metadata: {}", e.what()))?;
+	let modules = match metadata.1 {
+		RuntimeMetadata::V7(metadata) => decoded(metadata.modules),
+		_ => None,
+	}.ok_or_else(|| "Unsupported metadata version".to_string())?;
+
+	let mut items = Vec::new();
+	for storage in modules.into_iter().filter_map(|module| module.storage.and_then(decoded)) {
+		let module = decoded(storage.prefix).unwrap_or_default();
+		for entry in decoded(storage.entries).unwrap_or_default() {
+			let name = decoded(entry.name).unwrap_or_default();
+			let prefix = format!("{} {}", module, name).into_bytes();
+			let (key_type, value_type, keys) = match entry.ty {
+				StorageEntryType::Plain(value) => (None, value, ItemKeys::Value),
+				StorageEntryType::Map { hasher, key, value, is_linked } =>
+					(Some(key), value, ItemKeys::Map { hasher, linked: is_linked }),
+				StorageEntryType::DoubleMap { hasher, key1, value, .. } =>
+					(Some(key1), value, ItemKeys::DoubleMap { hasher }),
+			};
+			let item = StorageItem {
+				module: module.clone(),
+				name,
+				key_type: key_type.and_then(decoded),
+				value_type: decoded(value_type).unwrap_or_default(),
+			};
+			items.push(ItemLayout { item, prefix, keys });
+		}
+	}
+	Ok(items)
+}
+
+/// Attribute the keys of the top-level storage `values` to the `items` owning them, by index.
+///
+/// The keys of maps hashed with anything but `Twox64Concat` can't be recovered from their hash,
+/// so every key found in a linked map or next to its hash is tried as a key of those maps.
+fn attribute(items: &[ItemLayout], values: &HashMap<Vec<u8>, Vec<u8>>) -> HashMap<Vec<u8>, usize> {
+	let mut owners = HashMap::new();
+	let mut candidates = BTreeSet::new();
+	for (index, layout) in items.iter().enumerate() {
+		let prefix = &layout.prefix[..];
+		match &layout.keys {
+			ItemKeys::Value => {
+				owners.insert(twox_128(prefix).to_vec(), index);
+			},
+			ItemKeys::Map { hasher, linked } => {
+				if *hasher == StorageHasher::Twox64Concat {
+					for key in values.keys() {
+						if let Some(encoded) = concat_key(key, prefix) {
+							owners.insert(key.clone(), index);
+							candidates.insert(encoded.to_vec());
+						}
+					}
+				}
+				if *linked {
+					let head = hash(hasher, &[&b"head of "[..], prefix].concat());
+					let mut next = values.get(&head).cloned();
+					let mut visited = HashSet::new();
+					owners.insert(head, index);
+					while let Some(key) = next.take() {
+						let entry = hash(hasher, &[prefix, &key[..]].concat());
+						let value = match values.get(&entry) {
+							Some(value) if visited.insert(entry.clone()) => value,
+							_ => break,
+						};
+						owners.insert(entry, index);
+						// The entry ends with the link to the next one, `Some(key)`, which is
+						// encoded as `1` followed by the key. Only keys of the same length are
+						// followed, a wrong guess doesn't match any entry.
+						next = value.len().checked_sub(key.len() + 1)
+							.filter(|at| value[*at] == 1)
+							.map(|at| value[at + 1..].to_vec());
+						candidates.insert(key);
+					}
+				}
+			},
+			ItemKeys::DoubleMap { hasher } => if *hasher == StorageHasher::Twox64Concat {
+				for key in values.keys() {
+					if key.len() > 8 && key[8..].starts_with(prefix) {
+						owners.insert(key.clone(), index);
+					}
+				}
+			},
+		}
+	}
+
+	let mut hashed = HashMap::new();
+	let mut hashed_prefixes = HashMap::new();
+	for (index, layout) in items.iter().enumerate() {
+		let (hasher, target) = match &layout.keys {
+			ItemKeys::Map { hasher, .. } => (hasher, &mut hashed),
+			ItemKeys::DoubleMap { hasher } => (hasher, &mut hashed_prefixes),
+			ItemKeys::Value => continue,
+		};
+		if *hasher == StorageHasher::Twox64Concat {
+			continue;
+		}
+		for candidate in &candidates {
+			target.insert(hash(hasher, &[&layout.prefix[..], &candidate[..]].concat()), index);
+		}
+	}
+	for key in values.keys() {
+		if owners.contains_key(key) {
+			continue;
+		}
+		let owner = hashed.get(key).or_else(|| {
+			// The first key of a double map is hashed into 16 or 32 bytes.
+			[16, 32].iter()
+				.filter(|len| key.len() > **len)
+				.find_map(|len| hashed_prefixes.get(&key[..*len]))
+		});
+		if let Some(owner) = owner {
+			owners.insert(key.clone(), *owner);
+		}
+	}
+	owners
+}
+
+/// The encoded key of `key` if it is an entry of the `Twox64Concat` map with `prefix`.
+fn concat_key<'a>(key: &'a [u8], prefix: &[u8]) -> Option<&'a [u8]> {
+	if key.len() <= 8 || !key[8..].starts_with(prefix) || twox_64(&key[8..])[..] != key[..8] {
+		return None;
+	}
+	Some(&key[8 + prefix.len()..])
+}
+
+/// The decoded value of a piece of metadata, which is never in its encodable form once decoded.
+fn decoded<B, O>(value: DecodeDifferent<B, O>) -> Option<O> {
+	match value {
+		DecodeDifferent::Decoded(value) => Some(value),
+		DecodeDifferent::Encode(_) => None,
+	}
+}
+
+/// Hash `data` with the hasher of a storage map.
+fn hash(hasher: &StorageHasher, data: &[u8]) -> Vec<u8> {
+	match hasher {
+		StorageHasher::Blake2_128 => blake2_128(data).to_vec(),
+		StorageHasher::Blake2_256 => blake2_256(data).to_vec(),
+		StorageHasher::Twox128 => twox_128(data).to_vec(),
+		StorageHasher::Twox256 => twox_256(data).to_vec(),
+		StorageHasher::Twox64Concat => twox_64(data).iter().chain(data).cloned().collect(),
+	}
+}
+
+/// The measured execution time of a benchmark at a single point of its components.
+#[derive(Debug, Clone)]
+pub struct BenchmarkResult {
+	/// The values of the components.
+	pub components: ComponentValues,
+	/// The execution time of the extrinsic in nanoseconds.
+	pub nanos: u128,
+}
+
+/// Run the benchmark of `extrinsic` in `module` on top of the best block.
+///
+/// Each component is stepped through its range in `steps` points while all other components are
+/// kept at their highest value. Every point is measured `repeat` times. Returns `None` if the
+/// runtime has no such benchmark.
+pub fn benchmark<F>(
+	config: FactoryFullConfiguration<F>,
+	module: &str,
+	extrinsic: &str,
+	steps: u32,
+	repeat: u32,
+	strategy: ExecutionStrategy,
+) -> error::Result<Option<(Vec<ComponentRange>, Vec<BenchmarkResult>)>>
+	where F: ServiceFactory,
+{
+	let client = new_client::<F>(&config)?;
+	let at = BlockId::Hash(client.info().chain.best_hash);
+	let executor = client.executor();
+	let module = module.as_bytes().to_vec();
+	let extrinsic = extrinsic.as_bytes().to_vec();
+
+	let call = |method: &str, data: Vec<u8>| -> error::Result<Vec<u8>> {
+		Ok(executor.call(&at, method, &data, strategy, NeverOffchainExt::new())?)
+	};
+	let timed = |method: &str, components: &ComponentValues| -> error::Result<u128> {
+		let start = Instant::now();
+		let result = call(method, (&module, &extrinsic, components).encode())?;
+		let elapsed = start.elapsed().as_nanos();
+		match Result::<(), Vec<u8>>::decode(&mut &result[..]) {
+			Ok(Ok(())) => Ok(elapsed),
+			Ok(Err(e)) => Err(format!("Benchmark failed: {}", String::from_utf8_lossy(&e)).into()),
+			Err(e) => Err(format!("Invalid benchmark result: {}", e.what()).into()),
+		}
+	};
+
+	let ranges = call("Benchmark_benchmark_components", (&module, &extrinsic).encode())?;
+	let ranges = match Option::<Vec<ComponentRange>>::decode(&mut &ranges[..])
+		.map_err(|e| format!("Invalid benchmark components: {}", e.what()))?
+	{
+		Some(ranges) => ranges,
+		None => return Ok(None),
+	};
+
+	if let Some(range) = ranges.iter().find(|range| range.low > range.high) {
+		return Err(format!(
+			"Invalid range {}..={} of component {}",
+			range.low,
+			range.high,
+			String::from_utf8_lossy(&range.name),
+		).into());
+	}
+
+	let steps = steps.max(1);
+	let mut results = Vec::new();
+	for (index, range) in ranges.iter().enumerate() {
+		for step in 0..steps {
+			let value = component_value(range, step, steps);
+			let components: ComponentValues = ranges.iter().enumerate()
+				.map(|(i, r)| (r.name.clone(), if i == index { value } else { r.high }))
+				.collect();
+
+			for _ in 0..repeat {
+				let setup = timed("Benchmark_benchmark_setup", &components)?;
+				let dispatch = timed("Benchmark_benchmark_dispatch", &components)?;
+				results.push(BenchmarkResult {
+					components: components.clone(),
+					nanos: dispatch.saturating_sub(setup),
+				});
+			}
+		}
+	}
+
+	Ok(Some((ranges, results)))
+}
+
+/// The value of a component at `step` of `steps` points spread evenly over its range.
+///
+/// The range must not be empty and `step` must be lower than `steps`.
+fn component_value(range: &ComponentRange, step: u32, steps: u32) -> u32 {
+	let span = u64::from(range.high - range.low);
+	let offset = span * u64::from(step) / u64::from(steps.saturating_sub(1).max(1));
+	// `step < steps` so the offset is at most the span.
+	range.low + offset as u32
+}
+
 /// Build a chain spec json
-pub fn build_spec<G>(spec: ChainSpec<G>, raw: bool) -> error::Result<String>
-	where G: RuntimeGenesis,
+pub fn build_spec<G, Ext>(spec: ChainSpec<G, Ext>, raw: bool) -> error::Result<String>
+	where G: RuntimeGenesis, Ext: ChainSpecExtension,
 {
 	Ok(spec.to_json(raw)?)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use srml_metadata::{
+		META_RESERVED, ModuleMetadata, RuntimeMetadataV7, StorageEntryMetadata, StorageEntryModifier,
+		StorageMetadata,
+	};
+
+	fn range(low: u32, high: u32) -> ComponentRange {
+		ComponentRange { name: b"n".to_vec(), low, high }
+	}
+
+	fn entry(name: &'static str, ty: StorageEntryType) -> StorageEntryMetadata {
+		StorageEntryMetadata {
+			name: DecodeDifferent::Encode(name),
+			modifier: StorageEntryModifier::Optional,
+			ty,
+			default: DecodeDifferent::Decoded(Vec::new()),
+			documentation: DecodeDifferent::Decoded(Vec::new()),
+		}
+	}
+
+	fn map(hasher: StorageHasher, is_linked: bool) -> StorageEntryType {
+		StorageEntryType::Map {
+			hasher,
+			key: DecodeDifferent::Encode("u32"),
+			value: DecodeDifferent::Encode("u64"),
+			is_linked,
+		}
+	}
+
+	/// The encoded metadata of a runtime with a single `Test` module.
+	fn metadata() -> Vec<u8> {
+		let storage = StorageMetadata {
+			prefix: DecodeDifferent::Encode("Test"),
+			entries: DecodeDifferent::Decoded(vec![
+				entry("Total", StorageEntryType::Plain(DecodeDifferent::Encode("u64"))),
+				entry("Linked", map(StorageHasher::Blake2_256, true)),
+				entry("Concat", map(StorageHasher::Twox64Concat, false)),
+				entry("Hashed", map(StorageHasher::Twox128, false)),
+				entry("Double", StorageEntryType::DoubleMap {
+					hasher: StorageHasher::Blake2_128,
+					key1: DecodeDifferent::Encode("u32"),
+					key2: DecodeDifferent::Encode("u32"),
+					value: DecodeDifferent::Encode("u64"),
+					key2_hasher: StorageHasher::Twox128,
+				}),
+			]),
+		};
+		let module = ModuleMetadata {
+			name: DecodeDifferent::Encode("Test"),
+			storage: Some(DecodeDifferent::Decoded(storage)),
+			calls: None,
+			event: None,
+			constants: DecodeDifferent::Decoded(Vec::new()),
+		};
+		RuntimeMetadataPrefixed(META_RESERVED, RuntimeMetadata::V7(RuntimeMetadataV7 {
+			modules: DecodeDifferent::Decoded(vec![module]),
+		})).encode()
+	}
+
+	/// The key of the entry `key` of the map `name` of the `Test` module.
+	fn key(name: &str, hasher: StorageHasher, key: u32) -> Vec<u8> {
+		hash(&hasher, &[format!("Test {}", name).as_bytes(), &key.encode()[..]].concat())
+	}
+
+	/// A state with two entries in `Linked`, one in `Concat` and `Double`, and three in `Hashed`,
+	/// the last of which isn't a key of any other map.
+	fn state() -> HashMap<Vec<u8>, Vec<u8>> {
+		let mut state = HashMap::new();
+		state.insert(twox_128(b"Test Total").to_vec(), 10u64.encode());
+		state.insert(blake2_256(b"head of Test Linked").to_vec(), 1u32.encode());
+		state.insert(key("Linked", StorageHasher::Blake2_256, 1), (5u64, (None::<u32>, Some(2u32))).encode());
+		state.insert(key("Linked", StorageHasher::Blake2_256, 2), (6u64, (Some(1u32), None::<u32>)).encode());
+		state.insert(key("Concat", StorageHasher::Twox64Concat, 3), 7u64.encode());
+		for k in 2..5 {
+			state.insert(key("Hashed", StorageHasher::Twox128, k), 8u64.encode());
+		}
+		let double = [key("Double", StorageHasher::Blake2_128, 1), twox_128(&9u32.encode()).to_vec()].concat();
+		state.insert(double, 9u64.encode());
+		state.insert(b"unknown".to_vec(), Vec::new());
+		state
+	}
+
+	#[test]
+	fn forks_keep_child_tries_and_drop_the_bootnodes() {
+		use sr_primitives::BuildStorage;
+
+		let spec = ChainSpec::<node_runtime::GenesisConfig>::from_genesis(
+			"Test",
+			"test",
+			|| unreachable!("the genesis of the fork is its state"),
+			vec!["/ip4/127.0.0.1/tcp/30333/p2p/QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV".into()],
+			None,
+			None,
+			None,
+			None,
+			None,
+		);
+		let child = b":child_storage:default:test".to_vec();
+		let mut state = State::new();
+		state.entry(None).or_default().insert(b"a".to_vec(), b"b".to_vec());
+		state.entry(None).or_default().insert(b"c".to_vec(), b"d".to_vec());
+		state.entry(Some(child.clone())).or_default().insert(b"e".to_vec(), b"f".to_vec());
+		let mut overrides = HashMap::new();
+		overrides.insert(StorageKey(b"c".to_vec()), StorageOverride::Value(None));
+
+		let json = fork_spec(spec, state, overrides).unwrap();
+		let fork = ChainSpec::<node_runtime::GenesisConfig>::from_json_bytes(json.into_bytes()).unwrap();
+
+		assert!(fork.boot_nodes().is_empty());
+		let (storage, children) = (&fork).build_storage().unwrap();
+		assert_eq!(storage, vec![(b"a".to_vec(), b"b".to_vec())].into_iter().collect());
+		assert_eq!(children[&child], vec![(b"e".to_vec(), b"f".to_vec())].into_iter().collect());
+	}
+
+	#[test]
+	fn runtime_upgrades_are_compared_with_the_current_runtime() {
+		use substrate_test_runtime_client::{LocalExecutor, runtime::{Block as TestBlock, WASM_BINARY}};
+
+		let executor = substrate_executor::NativeExecutor::<LocalExecutor>::new(None);
+		let header = <TestBlock as Block>::Header::new(
+			1,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		);
+		let mut state = State::new();
+		state.entry(None).or_default().insert(well_known_keys::CODE.to_vec(), WASM_BINARY.to_vec());
+
+		let (initialized, _) = initialize_block::<TestBlock, _>(&executor, state.clone(), &header).unwrap();
+		assert!(initialized[&None].len() > state[&None].len());
+
+		// The changes every block makes aren't part of the upgrade.
+		let result = upgrade::<TestBlock, _>(&executor, state.clone(), &header, WASM_BINARY.to_vec()).unwrap();
+		assert_eq!(result.changes, Vec::new());
+		assert_eq!(result.weight, None);
+
+		assert!(upgrade::<TestBlock, _>(&executor, state, &header, vec![1, 2, 3]).is_err());
+	}
+
+	#[test]
+	fn storage_items_are_decoded_from_the_metadata() {
+		let items = storage_items(&metadata()).unwrap();
+
+		let names: Vec<_> = items.iter().map(|layout| layout.item.name.as_str()).collect();
+		assert_eq!(names, vec!["Total", "Linked", "Concat", "Hashed", "Double"]);
+		assert_eq!(items[0].item.key_type, None);
+		assert_eq!(items[0].keys, ItemKeys::Value);
+		assert_eq!(items[1].item, StorageItem {
+			module: "Test".into(),
+			name: "Linked".into(),
+			key_type: Some("u32".into()),
+			value_type: "u64".into(),
+		});
+		assert_eq!(items[1].prefix, b"Test Linked".to_vec());
+		assert_eq!(items[1].keys, ItemKeys::Map { hasher: StorageHasher::Blake2_256, linked: true });
+		assert_eq!(items[4].keys, ItemKeys::DoubleMap { hasher: StorageHasher::Blake2_128 });
+		assert!(storage_items(&[1, 2, 3]).is_err());
+	}
+
+	#[test]
+	fn storage_items_are_decoded_from_the_metadata_of_a_runtime() {
+		let items = storage_items(&node_runtime::Runtime::metadata().encode()).unwrap();
+
+		let free_balance = items.iter().find(|layout| layout.prefix == b"Balances FreeBalance".to_vec()).unwrap();
+		assert_eq!(free_balance.item.key_type, Some("T::AccountId".into()));
+		assert_eq!(free_balance.keys, ItemKeys::Map { hasher: StorageHasher::Blake2_256, linked: false });
+		let validators = items.iter().find(|layout| layout.prefix == b"Staking Validators".to_vec()).unwrap();
+		assert_eq!(validators.keys, ItemKeys::Map { hasher: StorageHasher::Blake2_256, linked: true });
+	}
+
+	#[test]
+	fn hashed_map_keys_are_attributed_through_the_keys_of_other_maps() {
+		let items = storage_items(&metadata()).unwrap();
+		let state = state();
+		let owners = attribute(&items, &state);
+		let owner = |key: &[u8]| owners.get(key).map(|owner| items[*owner].item.name.as_str());
+
+		assert_eq!(owner(&twox_128(b"Test Total")), Some("Total"));
+		assert_eq!(owner(&blake2_256(b"head of Test Linked")), Some("Linked"));
+		assert_eq!(owner(&key("Linked", StorageHasher::Blake2_256, 1)), Some("Linked"));
+		assert_eq!(owner(&key("Linked", StorageHasher::Blake2_256, 2)), Some("Linked"));
+		assert_eq!(owner(&key("Concat", StorageHasher::Twox64Concat, 3)), Some("Concat"));
+		// 2 is a key of `Linked`, 3 a key of `Concat`, 4 is a key of no other map.
+		assert_eq!(owner(&key("Hashed", StorageHasher::Twox128, 2)), Some("Hashed"));
+		assert_eq!(owner(&key("Hashed", StorageHasher::Twox128, 3)), Some("Hashed"));
+		assert_eq!(owner(&key("Hashed", StorageHasher::Twox128, 4)), None);
+		let double = [key("Double", StorageHasher::Blake2_128, 1), twox_128(&9u32.encode()).to_vec()].concat();
+		assert_eq!(owner(&double), Some("Double"));
+		assert_eq!(owner(b"unknown"), None);
+	}
+
+	#[test]
+	fn changes_are_grouped_by_storage_item() {
+		let items = storage_items(&metadata()).unwrap();
+		let mut before = State::new();
+		before.insert(None, state());
+		let mut after = before.clone();
+		let top = after.get_mut(&None).unwrap();
+		top.remove(&key("Linked", StorageHasher::Blake2_256, 2));
+		top.insert(key("Hashed", StorageHasher::Twox128, 3), 11u64.encode());
+		top.insert(key("Hashed", StorageHasher::Twox128, 4), 12u64.encode());
+		after.entry(Some(b":child_storage:default:test".to_vec())).or_default().insert(b"a".to_vec(), vec![1]);
+
+		let diff = diff(&items, &before, &after);
+
+		let changed: Vec<_> = diff.items.iter()
+			.map(|(item, changes)| (item.as_ref().map(|item| item.name.as_str()), changes.len()))
+			.collect();
+		assert_eq!(changed, vec![(None, 2), (Some("Hashed"), 1), (Some("Linked"), 1)]);
+		let removed = &diff.items.iter().find(|(item, _)| item.as_ref().map_or(false, |item| item.name == "Linked")).unwrap().1[0];
+		assert_eq!(removed.before, Some(StorageData((6u64, (Some(1u32), None::<u32>)).encode())));
+		assert_eq!(removed.after, None);
+	}
+
+	#[test]
+	fn component_values_are_spread_over_the_range() {
+		let values = |range, steps| (0..steps).map(|step| component_value(&range, step, steps)).collect::<Vec<_>>();
+
+		assert_eq!(values(range(0, 100), 5), vec![0, 25, 50, 75, 100]);
+		assert_eq!(values(range(10, 10), 3), vec![10, 10, 10]);
+		assert_eq!(values(range(7, 100), 1), vec![7]);
+	}
+
+	#[test]
+	fn component_values_do_not_overflow() {
+		let values = (0..10).map(|step| component_value(&range(0, u32::max_value()), step, 10)).collect::<Vec<_>>();
+
+		assert_eq!(values[0], 0);
+		assert_eq!(values[9], u32::max_value());
+		assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+	}
+}
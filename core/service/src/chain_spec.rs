@@ -19,19 +19,23 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fs::File;
+use std::io::Read;
+use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::path::PathBuf;
-use serde::{Serialize, Deserialize};
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
 use primitives::storage::{StorageKey, StorageData};
 use sr_primitives::{BuildStorage, StorageOverlay, ChildrenStorageOverlay};
 use serde_json as json;
 use crate::components::RuntimeGenesis;
 use network::Multiaddr;
 use tel::TelemetryEndpoints;
+use rpc::apis::system::LightSyncState;
 
 enum GenesisSource<G> {
 	File(PathBuf),
 	Binary(Cow<'static, [u8]>),
 	Factory(fn() -> G),
+	Storage(StorageOverlay, ChildrenStorageOverlay),
 }
 
 impl<G: RuntimeGenesis> Clone for GenesisSource<G> {
@@ -40,6 +44,8 @@ impl<G: RuntimeGenesis> Clone for GenesisSource<G> {
 			GenesisSource::File(ref path) => GenesisSource::File(path.clone()),
 			GenesisSource::Binary(ref d) => GenesisSource::Binary(d.clone()),
 			GenesisSource::Factory(f) => GenesisSource::Factory(f),
+			GenesisSource::Storage(ref storage, ref children) =>
+				GenesisSource::Storage(storage.clone(), children.clone()),
 		}
 	}
 }
@@ -54,25 +60,25 @@ impl<G: RuntimeGenesis> GenesisSource<G> {
 		match self {
 			GenesisSource::File(path) => {
 				let file = File::open(path).map_err(|e| format!("Error opening spec file: {}", e))?;
-				let genesis: GenesisContainer<G> =
-					json::from_reader(file).map_err(|e| format!("Error parsing spec file: {}", e))?;
+				let genesis: GenesisContainer<G> = parse_json(file, "spec file")?;
 				Ok(genesis.genesis)
 			},
 			GenesisSource::Binary(buf) => {
-				let genesis: GenesisContainer<G> =
-					json::from_reader(buf.as_ref()).map_err(|e| format!("Error parsing embedded file: {}", e))?;
+				let genesis: GenesisContainer<G> = parse_json(buf.as_ref(), "embedded file")?;
 				Ok(genesis.genesis)
 			},
 			GenesisSource::Factory(f) => Ok(Genesis::Runtime(f())),
+			GenesisSource::Storage(storage, children) =>
+				Ok(Genesis::Raw(RawGenesis::from_storage((storage.clone(), children.clone())))),
 		}
 	}
 }
 
-impl<'a, G: RuntimeGenesis> BuildStorage for &'a ChainSpec<G> {
+impl<'a, G: RuntimeGenesis, E> BuildStorage for &'a ChainSpec<G, E> {
 	fn build_storage(self) -> Result<(StorageOverlay, ChildrenStorageOverlay), String> {
 		match self.genesis.resolve()? {
-			Genesis::Runtime(gc) => gc.build_storage(),
-			Genesis::Raw(map) => Ok((map.into_iter().map(|(k, v)| (k.0, v.0)).collect(), Default::default())),
+			Genesis::Runtime(gc) => build_genesis_storage(gc),
+			Genesis::Raw(raw) => Ok(raw.into_storage()),
 		}
 	}
 	fn assimilate_storage(self, _: &mut StorageOverlay, _: &mut ChildrenStorageOverlay) -> Result<(), String> {
@@ -80,17 +86,87 @@ impl<'a, G: RuntimeGenesis> BuildStorage for &'a ChainSpec<G> {
 	}
 }
 
+/// Parse `what` from the json in `reader`, reporting the path of the value that failed to parse.
+fn parse_json<T: DeserializeOwned>(reader: impl Read, what: &str) -> Result<T, String> {
+	let mut deserializer = json::Deserializer::from_reader(reader);
+	let value = serde_path_to_error::deserialize(&mut deserializer)
+		.map_err(|e| format!("Error parsing {} at `{}`: {}", what, e.path(), e.inner()))?;
+	deserializer.end().map_err(|e| format!("Error parsing {}: {}", what, e))?;
+	Ok(value)
+}
+
+/// Build the storage of `genesis`.
+///
+/// The genesis builders of the modules panic when they are given an invalid config, these panics
+/// are returned as errors.
+fn build_genesis_storage<G: RuntimeGenesis>(
+	genesis: G,
+) -> Result<(StorageOverlay, ChildrenStorageOverlay), String> {
+	// Substrate uses custom panic hook that terminates process on panic. Disable termination for
+	// building the genesis storage.
+	let _guard = panic_handler::AbortGuard::force_unwind();
+	catch_unwind(AssertUnwindSafe(|| genesis.build_storage()))
+		.map_err(|panic| {
+			let message = panic.downcast_ref::<&str>().map(|s| s.to_string())
+				.or_else(|| panic.downcast_ref::<String>().cloned())
+				.unwrap_or_else(|| "unknown error".into());
+			format!("Invalid genesis config: {}", message)
+		})?
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
 enum Genesis<G> {
 	Runtime(G),
-	Raw(HashMap<StorageKey, StorageData>),
+	Raw(RawGenesis),
+}
+
+/// The raw storage of the genesis block.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawGenesis {
+	/// The top-level storage and the storage of each child trie, by the key of the trie.
+	WithChildren {
+		top: HashMap<StorageKey, StorageData>,
+		children: HashMap<StorageKey, HashMap<StorageKey, StorageData>>,
+	},
+	/// The top-level storage of a genesis without child tries, the only form known to older
+	/// versions.
+	Top(HashMap<StorageKey, StorageData>),
+}
+
+impl RawGenesis {
+	fn from_storage((storage, children): (StorageOverlay, ChildrenStorageOverlay)) -> Self {
+		fn raw(storage: StorageOverlay) -> HashMap<StorageKey, StorageData> {
+			storage.into_iter().map(|(k, v)| (StorageKey(k), StorageData(v))).collect()
+		}
+
+		if children.is_empty() {
+			return RawGenesis::Top(raw(storage));
+		}
+		RawGenesis::WithChildren {
+			top: raw(storage),
+			children: children.into_iter().map(|(k, v)| (StorageKey(k), raw(v))).collect(),
+		}
+	}
+
+	fn into_storage(self) -> (StorageOverlay, ChildrenStorageOverlay) {
+		fn storage(raw: HashMap<StorageKey, StorageData>) -> StorageOverlay {
+			raw.into_iter().map(|(k, v)| (k.0, v.0)).collect()
+		}
+
+		match self {
+			RawGenesis::WithChildren { top, children } =>
+				(storage(top), children.into_iter().map(|(k, v)| (k.0, storage(v))).collect()),
+			RawGenesis::Top(top) => (storage(top), Default::default()),
+		}
+	}
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
-struct ChainSpecFile {
+struct ChainSpecFile<E> {
 	pub name: String,
 	pub id: String,
 	pub boot_nodes: Vec<String>,
@@ -98,18 +174,49 @@ struct ChainSpecFile {
 	pub protocol_id: Option<String>,
 	pub consensus_engine: Option<String>,
 	pub properties: Option<Properties>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub default_heap_pages: Option<u64>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub max_memory_pages: Option<u32>,
+	#[serde(default, skip_serializing_if = "HashMap::is_empty")]
+	pub code_substitutes: HashMap<String, StorageData>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub light_sync_state: Option<LightSyncState>,
+	#[serde(flatten)]
+	pub extensions: E,
+}
+
+/// An override of the genesis storage, keyed by a storage key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum StorageOverride {
+	/// The new value of the key, `None` to remove the key, or the whole child trie if the key is
+	/// the key of a child trie.
+	Value(Option<StorageData>),
+	/// The overrides of the entries of the child trie with the key. The child trie is created if
+	/// it doesn't exist, and removed once it is empty.
+	Child(HashMap<StorageKey, Option<StorageData>>),
 }
 
 /// Arbitrary properties defined in chain spec as a JSON object
+///
+/// Well-known properties used by wallets and the CLI are `tokenSymbol`, `tokenDecimals` and
+/// `ss58Format`, the prefix used for SS58 addresses on the chain.
 pub type Properties = json::map::Map<String, json::Value>;
 
+/// A chain spec without any extensions.
+pub type NoExtension = Option<()>;
+
 /// A configuration of a chain. Can be used to build a genesis block.
-pub struct ChainSpec<G: RuntimeGenesis> {
-	spec: ChainSpecFile,
+///
+/// `E` holds the node-specific extensions of the chain spec. Its fields are flattened into the
+/// top level of the JSON file, next to the standard fields.
+pub struct ChainSpec<G: RuntimeGenesis, E = NoExtension> {
+	spec: ChainSpecFile<E>,
 	genesis: GenesisSource<G>,
 }
 
-impl<G: RuntimeGenesis> Clone for ChainSpec<G> {
+impl<G: RuntimeGenesis, E: Clone> Clone for ChainSpec<G, E> {
 	fn clone(&self) -> Self {
 		ChainSpec {
 			spec: self.spec.clone(),
@@ -118,7 +225,7 @@ impl<G: RuntimeGenesis> Clone for ChainSpec<G> {
 	}
 }
 
-impl<G: RuntimeGenesis> ChainSpec<G> {
+impl<G: RuntimeGenesis, E> ChainSpec<G, E> {
 	/// A list of bootnode addresses.
 	pub fn boot_nodes(&self) -> &[String] {
 		&self.spec.boot_nodes
@@ -155,29 +262,108 @@ impl<G: RuntimeGenesis> ChainSpec<G> {
 		self.spec.properties.as_ref().unwrap_or(&json::map::Map::new()).clone()
 	}
 
+	/// The default number of 64KB pages to allocate for Wasm execution, if the chain specifies
+	/// one.
+	pub fn default_heap_pages(&self) -> Option<u64> {
+		self.spec.default_heap_pages
+	}
+
+	/// The maximum number of 64KB pages of memory a Wasm runtime instance may have, if the
+	/// chain specifies one.
+	pub fn max_memory_pages(&self) -> Option<u32> {
+		self.spec.max_memory_pages
+	}
+
+	/// Runtime code to use instead of the on-chain one when executing on top of the state of
+	/// the blocks with the given numbers.
+	pub fn code_substitutes(&self) -> Result<HashMap<u64, Vec<u8>>, String> {
+		self.spec.code_substitutes.iter()
+			.map(|(number, code)| number.parse::<u64>()
+				.map(|number| (number, code.0.clone()))
+				.map_err(|e| format!("Invalid block number {} in code substitutes: {}", number, e))
+			)
+			.collect()
+	}
+
+	/// The state light clients can start syncing from instead of the genesis block, as returned
+	/// by the `system_lightSyncState` RPC.
+	pub fn light_sync_state(&self) -> Option<&LightSyncState> {
+		self.spec.light_sync_state.as_ref()
+	}
+
+	/// Embed the state light clients can start syncing from.
+	pub fn set_light_sync_state(&mut self, light_sync_state: LightSyncState) {
+		self.spec.light_sync_state = Some(light_sync_state);
+	}
+
+	/// Node-specific extensions of the chain spec.
+	pub fn extensions(&self) -> &E {
+		&self.spec.extensions
+	}
+
+	/// Mutable access to the node-specific extensions of the chain spec.
+	pub fn extensions_mut(&mut self) -> &mut E {
+		&mut self.spec.extensions
+	}
+
 	/// Add a bootnode to the list.
 	pub fn add_boot_node(&mut self, addr: Multiaddr) {
 		self.spec.boot_nodes.push(addr.to_string())
 	}
 
-	/// Parse json content into a `ChainSpec`
-	pub fn from_json_bytes(json: impl Into<Cow<'static, [u8]>>) -> Result<Self, String> {
-		let json = json.into();
-		let spec = json::from_slice(json.as_ref()).map_err(|e| format!("Error parsing spec file: {}", e))?;
-		Ok(ChainSpec {
-			spec,
-			genesis: GenesisSource::Binary(json),
-		})
+	/// Remove all the bootnodes.
+	pub fn clear_boot_nodes(&mut self) {
+		self.spec.boot_nodes.clear()
 	}
 
-	/// Parse json file into a `ChainSpec`
-	pub fn from_json_file(path: PathBuf) -> Result<Self, String> {
-		let file = File::open(&path).map_err(|e| format!("Error opening spec file: {}", e))?;
-		let spec = json::from_reader(file).map_err(|e| format!("Error parsing spec file: {}", e))?;
-		Ok(ChainSpec {
-			spec,
-			genesis: GenesisSource::File(path),
-		})
+	/// Replace the genesis of the chain with the storage built from `genesis`.
+	pub fn set_genesis(&mut self, genesis: G) -> Result<(), String> {
+		let (storage, children) = build_genesis_storage(genesis)?;
+		self.genesis = GenesisSource::Storage(storage, children);
+		Ok(())
+	}
+
+	/// Replace the genesis of the chain with the given raw storage and child tries.
+	pub fn set_genesis_storage(&mut self, storage: StorageOverlay, children: ChildrenStorageOverlay) {
+		self.genesis = GenesisSource::Storage(storage, children);
+	}
+
+	/// Apply the given storage overrides to the genesis storage.
+	///
+	/// This turns the genesis into its raw form.
+	pub fn apply_storage_overrides(
+		&mut self,
+		overrides: HashMap<StorageKey, StorageOverride>,
+	) -> Result<(), String> {
+		let (mut storage, mut children) = match self.genesis.resolve()? {
+			Genesis::Runtime(gc) => build_genesis_storage(gc)?,
+			Genesis::Raw(raw) => raw.into_storage(),
+		};
+		fn apply(storage: &mut StorageOverlay, key: StorageKey, value: Option<StorageData>) {
+			match value {
+				Some(value) => { storage.insert(key.0, value.0); },
+				None => { storage.remove(&key.0); },
+			}
+		}
+		for (key, value) in overrides {
+			match value {
+				StorageOverride::Value(None) if children.contains_key(&key.0) => {
+					children.remove(&key.0);
+				},
+				StorageOverride::Value(value) => apply(&mut storage, key, value),
+				StorageOverride::Child(overrides) => {
+					let child = children.entry(key.0.clone()).or_default();
+					for (key, value) in overrides {
+						apply(child, key, value);
+					}
+					if child.is_empty() {
+						children.remove(&key.0);
+					}
+				},
+			}
+		}
+		self.genesis = GenesisSource::Storage(storage, children);
+		Ok(())
 	}
 
 	/// Create hardcoded spec.
@@ -190,6 +376,7 @@ impl<G: RuntimeGenesis> ChainSpec<G> {
 		protocol_id: Option<&str>,
 		consensus_engine: Option<&str>,
 		properties: Option<Properties>,
+		extensions: E,
 	) -> Self
 	{
 		let spec = ChainSpecFile {
@@ -200,30 +387,51 @@ impl<G: RuntimeGenesis> ChainSpec<G> {
 			protocol_id: protocol_id.map(str::to_owned),
 			consensus_engine: consensus_engine.map(str::to_owned),
 			properties,
+			default_heap_pages: None,
+			max_memory_pages: None,
+			code_substitutes: HashMap::new(),
+			light_sync_state: None,
+			extensions,
 		};
 		ChainSpec {
 			spec,
 			genesis: GenesisSource::Factory(constructor),
 		}
 	}
+}
+
+impl<G: RuntimeGenesis, E: Serialize + DeserializeOwned> ChainSpec<G, E> {
+	/// Parse json content into a `ChainSpec`
+	pub fn from_json_bytes(json: impl Into<Cow<'static, [u8]>>) -> Result<Self, String> {
+		let json = json.into();
+		let spec = parse_json(json.as_ref(), "spec file")?;
+		Ok(ChainSpec {
+			spec,
+			genesis: GenesisSource::Binary(json),
+		})
+	}
+
+	/// Parse json file into a `ChainSpec`
+	pub fn from_json_file(path: PathBuf) -> Result<Self, String> {
+		let file = File::open(&path).map_err(|e| format!("Error opening spec file: {}", e))?;
+		let spec = parse_json(file, "spec file")?;
+		Ok(ChainSpec {
+			spec,
+			genesis: GenesisSource::File(path),
+		})
+	}
 
 	/// Dump to json string.
 	pub fn to_json(self, raw: bool) -> Result<String, String> {
 		#[derive(Serialize, Deserialize)]
-		struct Container<G> {
+		struct Container<G, E> {
 			#[serde(flatten)]
-			spec: ChainSpecFile,
+			spec: ChainSpecFile<E>,
 			genesis: Genesis<G>,
 
 		};
 		let genesis = match (raw, self.genesis.resolve()?) {
-			(true, Genesis::Runtime(g)) => {
-				let storage = g.build_storage()?.0.into_iter()
-					.map(|(k, v)| (StorageKey(k), StorageData(v)))
-					.collect();
-
-				Genesis::Raw(storage)
-			},
+			(true, Genesis::Runtime(g)) => Genesis::Raw(RawGenesis::from_storage(build_genesis_storage(g)?)),
 			(_, genesis) => genesis,
 		};
 		let spec = Container {
@@ -233,3 +441,170 @@ impl<G: RuntimeGenesis> ChainSpec<G> {
 		json::to_string_pretty(&spec).map_err(|e| format!("Error generating spec json: {}", e))
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Debug, Serialize, Deserialize)]
+	struct Genesis(HashMap<String, String>);
+
+	impl BuildStorage for Genesis {
+		fn assimilate_storage(
+			self,
+			storage: &mut StorageOverlay,
+			_child_storage: &mut ChildrenStorageOverlay,
+		) -> Result<(), String> {
+			storage.extend(
+				self.0.into_iter().map(|(a, b)| (a.into_bytes(), b.into_bytes()))
+			);
+			Ok(())
+		}
+	}
+
+	#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+	#[serde(rename_all = "camelCase")]
+	struct Extensions {
+		fork_blocks: Vec<u64>,
+	}
+
+	const SPEC: &[u8] = br#"{
+		"name": "Test",
+		"id": "test",
+		"bootNodes": [],
+		"telemetryEndpoints": null,
+		"protocolId": null,
+		"consensusEngine": null,
+		"properties": null,
+		"forkBlocks": [5, 10],
+		"genesis": { "runtime": {} }
+	}"#;
+
+	#[test]
+	fn should_deserialize_extensions() {
+		let spec = ChainSpec::<Genesis, Extensions>::from_json_bytes(SPEC).unwrap();
+
+		assert_eq!(spec.extensions(), &Extensions { fork_blocks: vec![5, 10] });
+	}
+
+	#[test]
+	fn should_serialize_extensions() {
+		let spec = ChainSpec::<Genesis, Extensions>::from_json_bytes(SPEC).unwrap();
+		let json = spec.to_json(false).unwrap();
+		let spec = ChainSpec::<Genesis, Extensions>::from_json_bytes(json.into_bytes()).unwrap();
+
+		assert_eq!(spec.extensions(), &Extensions { fork_blocks: vec![5, 10] });
+	}
+
+	#[test]
+	fn should_ignore_extensions_by_default() {
+		let spec = ChainSpec::<Genesis>::from_json_bytes(SPEC).unwrap();
+
+		assert_eq!(spec.name(), "Test");
+	}
+
+	#[test]
+	fn should_report_path_of_invalid_value() {
+		let spec = String::from_utf8(SPEC.to_vec()).unwrap().replace(r#""bootNodes": []"#, r#""bootNodes": [1]"#);
+		let err = ChainSpec::<Genesis>::from_json_bytes(spec.into_bytes()).err().unwrap();
+
+		assert!(err.starts_with("Error parsing spec file at `bootNodes[0]`"), "{}", err);
+	}
+
+	#[test]
+	fn raw_genesis_keeps_child_tries() {
+		let mut spec = ChainSpec::<Genesis>::from_json_bytes(SPEC).unwrap();
+		let mut children = ChildrenStorageOverlay::new();
+		children.insert(b":child_storage:default:test".to_vec(), vec![(b"a".to_vec(), b"b".to_vec())].into_iter().collect());
+		let storage: StorageOverlay = vec![(b"c".to_vec(), b"d".to_vec())].into_iter().collect();
+		spec.set_genesis_storage(storage.clone(), children.clone());
+
+		let json = spec.to_json(true).unwrap();
+		let spec = ChainSpec::<Genesis>::from_json_bytes(json.into_bytes()).unwrap();
+
+		assert_eq!((&spec).build_storage().unwrap(), (storage, children));
+	}
+
+	#[test]
+	fn raw_genesis_without_child_tries_is_a_map_of_the_storage() {
+		let spec = String::from_utf8(SPEC.to_vec()).unwrap()
+			.replace(r#""runtime": {}"#, r#""raw": { "0x0c": "0x0d" }"#);
+		let spec = ChainSpec::<Genesis>::from_json_bytes(spec.into_bytes()).unwrap();
+
+		let storage = (&spec).build_storage().unwrap();
+		assert_eq!(storage.0, vec![(vec![0x0c], vec![0x0d])].into_iter().collect());
+		assert!(storage.1.is_empty());
+		let json: json::Value = json::from_str(&spec.to_json(true).unwrap()).unwrap();
+		assert_eq!(json["genesis"]["raw"], json::json!({ "0x0c": "0x0d" }));
+	}
+
+	#[derive(Debug, Serialize, Deserialize)]
+	struct ChildGenesis;
+
+	impl BuildStorage for ChildGenesis {
+		fn assimilate_storage(
+			self,
+			storage: &mut StorageOverlay,
+			child_storage: &mut ChildrenStorageOverlay,
+		) -> Result<(), String> {
+			storage.insert(vec![1], vec![1]);
+			storage.insert(vec![2], vec![2]);
+			let child = child_storage.entry(b":child_storage:default:a".to_vec()).or_default();
+			child.insert(vec![3], vec![3]);
+			child.insert(vec![4], vec![4]);
+			child_storage.entry(b":child_storage:default:b".to_vec()).or_default().insert(vec![5], vec![5]);
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn storage_overrides_apply_to_child_tries() {
+		let mut spec = ChainSpec::<ChildGenesis>::from_genesis(
+			"Test", "test", || ChildGenesis, vec![], None, None, None, None, None,
+		);
+		let overrides = json::from_str(r#"{
+			"0x01": "0x0a",
+			"0x02": null,
+			"0x3a6368696c645f73746f726167653a64656661756c743a61": { "0x03": "0x0c", "0x04": null, "0x06": "0x06" },
+			"0x3a6368696c645f73746f726167653a64656661756c743a62": null,
+			"0x3a6368696c645f73746f726167653a64656661756c743a63": { "0x07": null }
+		}"#).unwrap();
+		spec.apply_storage_overrides(overrides).unwrap();
+
+		let json = spec.to_json(true).unwrap();
+		let (storage, children) = (&ChainSpec::<ChildGenesis>::from_json_bytes(json.into_bytes()).unwrap())
+			.build_storage()
+			.unwrap();
+		assert_eq!(storage, vec![(vec![1], vec![10])].into_iter().collect());
+		assert_eq!(children.len(), 1);
+		assert_eq!(
+			children[&b":child_storage:default:a".to_vec()],
+			vec![(vec![3], vec![12]), (vec![6], vec![6])].into_iter().collect(),
+		);
+	}
+
+	#[derive(Debug, Serialize, Deserialize)]
+	struct InvalidGenesis;
+
+	impl BuildStorage for InvalidGenesis {
+		fn assimilate_storage(
+			self,
+			_storage: &mut StorageOverlay,
+			_child_storage: &mut ChildrenStorageOverlay,
+		) -> Result<(), String> {
+			panic!("Empty validator set in genesis block!")
+		}
+	}
+
+	#[test]
+	fn should_report_panics_of_genesis_builders() {
+		let spec = ChainSpec::<InvalidGenesis>::from_genesis(
+			"Test", "test", || InvalidGenesis, vec![], None, None, None, None, None,
+		);
+
+		assert_eq!(
+			(&spec).build_storage().err(),
+			Some("Invalid genesis config: Empty validator set in genesis block!".into()),
+		);
+	}
+}
@@ -27,10 +27,12 @@ use network::{self, OnDemand, FinalityProofProvider, NetworkStateInfo, config::B
 use substrate_executor::{NativeExecutor, NativeExecutionDispatch};
 use transaction_pool::txpool::{self, Options as TransactionPoolOptions, Pool as TransactionPool};
 use sr_primitives::{
-	BuildStorage, traits::{Block as BlockT, Header as HeaderT, ProvideRuntimeApi}, generic::BlockId
+	BuildStorage, traits::{Block as BlockT, Header as HeaderT, ProvideRuntimeApi}, generic::BlockId,
+	transaction_validity::TransactionSource,
 };
 use crate::config::Configuration;
 use primitives::{Blake2Hasher, H256, Pair};
+use keystore::Keystore;
 use rpc::{self, apis::system::SystemInfo};
 use futures::{prelude::*, future::Executor};
 use futures03::channel::mpsc;
@@ -96,7 +98,7 @@ pub type FullClient<F> = Client<FullBackend<F>, FullExecutor<F>, <F as ServiceFa
 pub type LightClient<F> = Client<LightBackend<F>, LightExecutor<F>, <F as ServiceFactory>::Block, <F as ServiceFactory>::RuntimeApi>;
 
 /// `ChainSpec` specialization for a factory.
-pub type FactoryChainSpec<F> = ChainSpec<<F as ServiceFactory>::Genesis>;
+pub type FactoryChainSpec<F> = ChainSpec<<F as ServiceFactory>::Genesis, <F as ServiceFactory>::ChainSpecExtension>;
 
 /// `Genesis` specialization for a factory.
 pub type FactoryGenesis<F> = <F as ServiceFactory>::Genesis;
@@ -111,7 +113,11 @@ pub type FactoryExtrinsic<F> = <<F as ServiceFactory>::Block as BlockT>::Extrins
 pub type FactoryBlockNumber<F> = <<FactoryBlock<F> as BlockT>::Header as HeaderT>::Number;
 
 /// Full `Configuration` type for a factory.
-pub type FactoryFullConfiguration<F> = Configuration<<F as ServiceFactory>::Configuration, FactoryGenesis<F>>;
+pub type FactoryFullConfiguration<F> = Configuration<
+	<F as ServiceFactory>::Configuration,
+	FactoryGenesis<F>,
+	<F as ServiceFactory>::ChainSpecExtension,
+>;
 
 /// Client type for `Components`.
 pub type ComponentClient<C> = Client<
@@ -152,6 +158,10 @@ pub type PoolApi<C> = <C as Components>::TransactionPoolApi;
 pub trait RuntimeGenesis: Serialize + DeserializeOwned + BuildStorage {}
 impl<T: Serialize + DeserializeOwned + BuildStorage> RuntimeGenesis for T {}
 
+/// A set of traits for the node-side extensions of the chain spec.
+pub trait ChainSpecExtension: Serialize + DeserializeOwned + Clone + Send + Sync + 'static {}
+impl<T: Serialize + DeserializeOwned + Clone + Send + Sync + 'static> ChainSpecExtension for T {}
+
 /// Something that can start the RPC service.
 pub trait StartRPC<C: Components> {
 	fn start_rpc(
@@ -160,6 +170,10 @@ pub trait StartRPC<C: Components> {
 		system_info: SystemInfo,
 		task_executor: TaskExecutor,
 		transaction_pool: Arc<TransactionPool<C::TransactionPoolApi>>,
+		keystore: Option<Arc<dyn Keystore>>,
+		on_demand: Option<Arc<OnDemand<ComponentBlock<C>>>>,
+		rpc_extension: rpc::RpcExtension,
+		middleware: rpc::RpcMiddleware,
 	) -> rpc::RpcHandler;
 }
 
@@ -173,17 +187,32 @@ impl<C: Components> StartRPC<Self> for C where
 		rpc_system_info: SystemInfo,
 		task_executor: TaskExecutor,
 		transaction_pool: Arc<TransactionPool<C::TransactionPoolApi>>,
+		keystore: Option<Arc<dyn Keystore>>,
+		on_demand: Option<Arc<OnDemand<ComponentBlock<C>>>>,
+		rpc_extension: rpc::RpcExtension,
+		middleware: rpc::RpcMiddleware,
 	) -> rpc::RpcHandler {
 		let subscriptions = rpc::apis::Subscriptions::new(task_executor.clone());
 		let chain = rpc::apis::chain::Chain::new(client.clone(), subscriptions.clone());
 		let state = rpc::apis::state::State::new(client.clone(), subscriptions.clone());
-		let author = rpc::apis::author::Author::new(client, transaction_pool, subscriptions);
+		let state = match on_demand {
+			Some(on_demand) => state.with_fetcher(on_demand),
+			None => state,
+		};
+		let author = rpc::apis::author::Author::new(
+			client,
+			transaction_pool,
+			subscriptions,
+			keystore,
+		);
 		let system = rpc::apis::system::System::new(rpc_system_info, system_send_back);
 		rpc::rpc_handler::<ComponentBlock<C>, ComponentExHash<C>, _, _, _, _>(
 			state,
 			chain,
 			author,
 			system,
+			rpc_extension,
+			middleware,
 		)
 	}
 }
@@ -239,6 +268,7 @@ impl<C: Components> MaintainTransactionPool<Self> for C where
 pub trait OffchainWorker<C: Components> {
 	fn offchain_workers(
 		number: &FactoryBlockNumber<C::Factory>,
+		hash: &<ComponentBlock<C> as BlockT>::Hash,
 		offchain: &offchain::OffchainWorkers<
 			ComponentClient<C>,
 			ComponentOffchainStorage<C>,
@@ -256,6 +286,7 @@ impl<C: Components> OffchainWorker<Self> for C where
 {
 	fn offchain_workers(
 		number: &FactoryBlockNumber<C::Factory>,
+		hash: &<ComponentBlock<C> as BlockT>::Hash,
 		offchain: &offchain::OffchainWorkers<
 			ComponentClient<C>,
 			ComponentOffchainStorage<C>,
@@ -265,7 +296,7 @@ impl<C: Components> OffchainWorker<Self> for C where
 		pool: &Arc<TransactionPool<C::TransactionPoolApi>>,
 		network_state: &Arc<dyn NetworkStateInfo + Send + Sync>,
 	) -> error::Result<Box<dyn Future<Item = (), Error = ()> + Send>> {
-		Ok(Box::new(offchain.on_block_imported(number, pool, network_state.clone())))
+		Ok(Box::new(offchain.on_block_imported(number, hash, pool, network_state.clone())))
 	}
 }
 
@@ -310,6 +341,8 @@ pub trait ServiceFactory: 'static + Sized {
 	type LightTransactionPoolApi: txpool::ChainApi<Hash = <Self::Block as BlockT>::Hash, Block = Self::Block> + 'static;
 	/// Genesis configuration for the runtime.
 	type Genesis: RuntimeGenesis;
+	/// Node-side extensions of the chain spec.
+	type ChainSpecExtension: ChainSpecExtension;
 	/// Other configuration for service members.
 	type Configuration: Default;
 	/// Extended full service type.
@@ -340,6 +373,12 @@ pub trait ServiceFactory: 'static + Sized {
 		client: Arc<FullClient<Self>>
 	) -> Result<Option<Arc<dyn FinalityProofProvider<Self::Block>>>, error::Error>;
 
+	/// Build the additional RPC methods of the full node.
+	fn build_rpc_extension(
+		client: Arc<FullClient<Self>>,
+		transaction_pool: Arc<TransactionPool<Self::FullTransactionPoolApi>>,
+	) -> Result<rpc::RpcExtension, error::Error>;
+
 	/// Build the Fork Choice algorithm for full client
 	fn build_select_chain(
 		config: &mut FactoryFullConfiguration<Self>,
@@ -442,6 +481,12 @@ pub trait Components: Sized + 'static {
 		config: &mut FactoryFullConfiguration<Self::Factory>,
 		client: Arc<ComponentClient<Self>>
 	) -> Result<Option<Self::SelectChain>, error::Error>;
+
+	/// Additional RPC methods, served next to the standard APIs.
+	fn build_rpc_extension(
+		client: Arc<ComponentClient<Self>>,
+		transaction_pool: Arc<TransactionPool<Self::TransactionPoolApi>>,
+	) -> Result<rpc::RpcExtension, error::Error>;
 }
 
 /// A struct that implement `Components` for the full client.
@@ -509,14 +554,17 @@ impl<Factory: ServiceFactory> Components for FullComponents<Factory> {
 			state_cache_size: config.state_cache_size,
 			state_cache_child_ratio:
 				config.state_cache_child_ratio.map(|v| (v, 100)),
+			trie_node_cache_size: config.trie_node_cache_size,
 			path: config.database_path.clone(),
 			pruning: config.pruning.clone(),
+			prune_headers: config.prune_headers,
 		};
 		Ok((Arc::new(client_db::new_client(
 			db_settings,
 			executor,
 			&config.chain_spec,
 			config.execution_strategies.clone(),
+			config.chain_spec.code_substitutes()?,
 		)?), None))
 	}
 
@@ -550,6 +598,13 @@ impl<Factory: ServiceFactory> Components for FullComponents<Factory> {
 	) -> Result<Option<Arc<dyn FinalityProofProvider<<Self::Factory as ServiceFactory>::Block>>>, error::Error> {
 		Factory::build_finality_proof_provider(client)
 	}
+
+	fn build_rpc_extension(
+		client: Arc<ComponentClient<Self>>,
+		transaction_pool: Arc<TransactionPool<Self::TransactionPoolApi>>,
+	) -> Result<rpc::RpcExtension, error::Error> {
+		Factory::build_rpc_extension(client, transaction_pool)
+	}
 }
 
 /// A struct that implement `Components` for the light client.
@@ -612,15 +667,20 @@ impl<Factory: ServiceFactory> Components for LightComponents<Factory> {
 			state_cache_size: config.state_cache_size,
 			state_cache_child_ratio:
 				config.state_cache_child_ratio.map(|v| (v, 100)),
+			trie_node_cache_size: config.trie_node_cache_size,
 			path: config.database_path.clone(),
 			pruning: config.pruning.clone(),
+			prune_headers: false,
 		};
 		let db_storage = client_db::light::LightStorage::new(db_settings)?;
 		let light_blockchain = client::light::new_light_blockchain(db_storage);
 		let fetch_checker = Arc::new(client::light::new_fetch_checker(light_blockchain.clone(), executor.clone()));
 		let fetcher = Arc::new(network::OnDemand::new(fetch_checker));
-		let client_backend = client::light::new_light_backend(light_blockchain, fetcher.clone());
+		let client_backend = client::light::new_light_backend(light_blockchain.clone(), fetcher.clone());
 		let client = client::light::new_light(client_backend, fetcher.clone(), &config.chain_spec, executor)?;
+		if let Some(sync_state) = config.chain_spec.light_sync_state() {
+			crate::import_light_sync_state::<FactoryBlock<Factory>, _>(light_blockchain.storage(), sync_state)?;
+		}
 		Ok((Arc::new(client), Some(fetcher)))
 	}
 
@@ -650,6 +710,13 @@ impl<Factory: ServiceFactory> Components for LightComponents<Factory> {
 	) -> Result<Option<Self::SelectChain>, error::Error> {
 		Ok(None)
 	}
+
+	fn build_rpc_extension(
+		_client: Arc<ComponentClient<Self>>,
+		_transaction_pool: Arc<TransactionPool<Self::TransactionPoolApi>>,
+	) -> Result<rpc::RpcExtension, error::Error> {
+		Ok(Default::default())
+	}
 }
 
 #[cfg(test)]
@@ -672,7 +739,7 @@ mod tests {
 		let best = longest_chain.best_chain().unwrap();
 
 		// store the transaction in the pool
-		pool.submit_one(&BlockId::hash(best.hash()), transaction.clone()).unwrap();
+		pool.submit_one(&BlockId::hash(best.hash()), TransactionSource::External, transaction.clone()).unwrap();
 
 		// import the block
 		let mut builder = client.new_block(Default::default()).unwrap();
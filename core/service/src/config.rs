@@ -19,10 +19,12 @@
 pub use client::ExecutionStrategies;
 pub use client_db::PruningMode;
 pub use network::config::{ExtTransport, NetworkConfiguration, Roles};
+pub use substrate_executor::WasmExecutionMethod;
+pub use rpc::apis::middleware::Limits as RpcLimits;
 
 use std::{path::PathBuf, net::SocketAddr};
 use transaction_pool;
-use crate::chain_spec::ChainSpec;
+use crate::chain_spec::{ChainSpec, NoExtension};
 use primitives::crypto::Protected;
 use sr_primitives::BuildStorage;
 use serde::{Serialize, de::DeserializeOwned};
@@ -30,8 +32,10 @@ use target_info::Target;
 use tel::TelemetryEndpoints;
 
 /// Service configuration.
+///
+/// `E` holds the node-side extensions of the chain spec.
 #[derive(Clone)]
-pub struct Configuration<C, G: Serialize + DeserializeOwned + BuildStorage> {
+pub struct Configuration<C, G: Serialize + DeserializeOwned + BuildStorage, E = NoExtension> {
 	/// Implementation name
 	pub impl_name: &'static str,
 	/// Implementation version
@@ -46,6 +50,8 @@ pub struct Configuration<C, G: Serialize + DeserializeOwned + BuildStorage> {
 	pub network: NetworkConfiguration,
 	/// Path to key files.
 	pub keystore_path: Option<PathBuf>,
+	/// URI of an external signer holding the authority keys. Replaces the local keystore.
+	pub keystore_uri: Option<String>,
 	/// Path to the database.
 	pub database_path: PathBuf,
 	/// Cache Size for internal database in MiB
@@ -54,12 +60,16 @@ pub struct Configuration<C, G: Serialize + DeserializeOwned + BuildStorage> {
 	pub state_cache_size: usize,
 	/// Size in percent of cache size dedicated to child tries
 	pub state_cache_child_ratio: Option<usize>,
+	/// Size of the trie node cache in Bytes
+	pub trie_node_cache_size: usize,
 	/// Pruning settings.
 	pub pruning: PruningMode,
+	/// Prune the headers of finalized blocks once they are replaced by a CHT.
+	pub prune_headers: bool,
 	/// Additional key seeds.
 	pub keys: Vec<String>,
 	/// Chain configuration.
-	pub chain_spec: ChainSpec<G>,
+	pub chain_spec: ChainSpec<G, E>,
 	/// Custom configuration.
 	pub custom: C,
 	/// Node name.
@@ -74,6 +84,16 @@ pub struct Configuration<C, G: Serialize + DeserializeOwned + BuildStorage> {
 	pub rpc_ws_max_connections: Option<usize>,
 	/// CORS settings for HTTP & WS servers. `None` if all origins are allowed.
 	pub rpc_cors: Option<Vec<String>>,
+	/// Limits enforced on RPC calls.
+	pub rpc_limits: RpcLimits,
+	/// Token that authenticates HTTP RPC requests as bearer token and WS connections as
+	/// `bearer.<TOKEN>` subprotocol. `None` if only local servers are authenticated.
+	pub rpc_auth_token: Option<String>,
+	/// Whether the HTTP RPC server is reached through a reverse proxy that sets the
+	/// `X-Forwarded-For` header.
+	pub rpc_trust_proxy: bool,
+	/// Prometheus metrics endpoint binding address. `None` if disabled.
+	pub prometheus_endpoint: Option<SocketAddr>,
 	/// Telemetry service URL. `None` if disabled.
 	pub telemetry_endpoints: Option<TelemetryEndpoints>,
 	/// External WASM transport for the telemetry. If `Some`, when connection to a telemetry
@@ -81,8 +101,16 @@ pub struct Configuration<C, G: Serialize + DeserializeOwned + BuildStorage> {
 	pub telemetry_external_transport: Option<ExtTransport>,
 	/// The default number of 64KB pages to allocate for Wasm execution
 	pub default_heap_pages: Option<u64>,
+	/// The maximum number of 64KB pages of memory a Wasm runtime instance may have.
+	pub max_memory_pages: Option<u32>,
+	/// The method used to execute the runtime Wasm code.
+	pub wasm_method: WasmExecutionMethod,
 	/// Should offchain workers be executed.
 	pub offchain_worker: bool,
+	/// Should offchain workers be executed only at new best blocks.
+	pub offchain_worker_best_only: bool,
+	/// Maximum number of offchain workers running at the same time, `None` if unlimited.
+	pub offchain_worker_max_concurrency: Option<usize>,
 	/// Enable authoring even when offline.
 	pub force_authoring: bool,
 	/// Disable GRANDPA when running in validator mode
@@ -94,9 +122,9 @@ pub struct Configuration<C, G: Serialize + DeserializeOwned + BuildStorage> {
 	pub password: Protected<String>,
 }
 
-impl<C: Default, G: Serialize + DeserializeOwned + BuildStorage> Configuration<C, G> {
+impl<C: Default, G: Serialize + DeserializeOwned + BuildStorage, E> Configuration<C, G, E> {
 	/// Create default config for given chain spec.
-	pub fn default_with_spec(chain_spec: ChainSpec<G>) -> Self {
+	pub fn default_with_spec(chain_spec: ChainSpec<G, E>) -> Self {
 		let mut configuration = Configuration {
 			impl_name: "parity-substrate",
 			impl_version: "0.0.0",
@@ -107,22 +135,33 @@ impl<C: Default, G: Serialize + DeserializeOwned + BuildStorage> Configuration<C
 			transaction_pool: Default::default(),
 			network: Default::default(),
 			keystore_path: Default::default(),
+			keystore_uri: Default::default(),
 			database_path: Default::default(),
 			database_cache_size: Default::default(),
 			state_cache_size: Default::default(),
 			state_cache_child_ratio: Default::default(),
+			trie_node_cache_size: Default::default(),
 			keys: Default::default(),
 			custom: Default::default(),
 			pruning: PruningMode::default(),
+			prune_headers: false,
 			execution_strategies: Default::default(),
 			rpc_http: None,
 			rpc_ws: None,
 			rpc_ws_max_connections: None,
 			rpc_cors: Some(vec![]),
+			rpc_limits: Default::default(),
+			rpc_auth_token: None,
+			rpc_trust_proxy: false,
+			prometheus_endpoint: None,
 			telemetry_endpoints: None,
 			telemetry_external_transport: None,
 			default_heap_pages: None,
+			max_memory_pages: None,
+			wasm_method: Default::default(),
 			offchain_worker: Default::default(),
+			offchain_worker_best_only: false,
+			offchain_worker_max_concurrency: None,
 			force_authoring: false,
 			disable_grandpa: false,
 			grandpa_voter: false,
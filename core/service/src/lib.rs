@@ -24,6 +24,8 @@ mod chain_spec;
 pub mod config;
 pub mod chain_ops;
 pub mod error;
+mod metrics;
+mod peers;
 
 use std::io;
 use std::marker::PhantomData;
@@ -37,20 +39,23 @@ use client::{BlockchainEvents, backend::Backend, runtime_api::BlockT};
 use exit_future::Signal;
 use futures::prelude::*;
 use futures03::stream::{StreamExt as _, TryStreamExt as _};
-use keystore::Store as Keystore;
+use keystore::{Store as Keystore, remote::RemoteSigner};
 use network::{NetworkState, NetworkStateInfo};
 use log::{log, info, warn, debug, error, Level};
 use codec::{Encode, Decode};
 use primitives::{Pair, ed25519, sr25519, crypto};
 use sr_primitives::generic::BlockId;
+use sr_primitives::transaction_validity::TransactionSource;
 use sr_primitives::traits::{Header, NumberFor, SaturatedConversion, Zero};
 use substrate_executor::NativeExecutor;
 use sysinfo::{get_current_pid, ProcessExt, System, SystemExt};
 use tel::{telemetry, SUBSTRATE_INFO};
 
 pub use self::error::Error;
-pub use config::{Configuration, Roles, PruningMode};
-pub use chain_spec::{ChainSpec, Properties};
+pub use config::{Configuration, Roles, PruningMode, RpcLimits, WasmExecutionMethod};
+pub use chain_spec::{ChainSpec, Properties, NoExtension, StorageOverride};
+pub use rpc::apis::system::LightSyncState;
+pub use rpc::RpcExtension;
 pub use transaction_pool::txpool::{
 	self, Pool as TransactionPool, Options as TransactionPoolOptions, ChainApi, IntoPoolError
 };
@@ -61,7 +66,7 @@ pub use components::{
 	LightExecutor, Components, PoolApi, ComponentClient, ComponentOffchainStorage,
 	ComponentBlock, FullClient, LightClient, FullComponents, LightComponents,
 	CodeExecutor, NetworkService, FactoryChainSpec, FactoryBlock,
-	FactoryFullConfiguration, RuntimeGenesis, FactoryGenesis,
+	FactoryFullConfiguration, RuntimeGenesis, ChainSpecExtension, FactoryGenesis,
 	ComponentExHash, ComponentExtrinsic, FactoryExtrinsic,
 	ComponentConsensusPair, ComponentFinalityPair,
 };
@@ -75,6 +80,12 @@ pub use futures::future::Executor;
 
 const DEFAULT_PROTOCOL_ID: &str = "sup";
 
+/// Interval at which transactions in the pool are re-validated against the best block.
+const TRANSACTION_POOL_REVALIDATION_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Maximal number of pool transactions re-validated at every interval.
+const TRANSACTION_POOL_REVALIDATION_BATCH: usize = 512;
+
 /// Substrate service.
 pub struct Service<Components: components::Components> {
 	client: Arc<ComponentClient<Components>>,
@@ -101,6 +112,7 @@ pub struct Service<Components: components::Components> {
 	rpc_handlers: rpc::RpcHandler,
 	_rpc: Box<dyn std::any::Any + Send + Sync>,
 	_telemetry: Option<tel::Telemetry>,
+	_prometheus: Option<Box<dyn std::any::Any + Send + Sync>>,
 	_telemetry_on_connect_sinks: Arc<Mutex<Vec<mpsc::UnboundedSender<()>>>>,
 	_offchain_workers: Option<Arc<offchain::OffchainWorkers<
 		ComponentClient<Components>,
@@ -110,11 +122,23 @@ pub struct Service<Components: components::Components> {
 	>>,
 }
 
+/// Creates the runtime executor, falling back to the execution settings of the chain spec
+/// where `config` does not override them.
+fn new_executor<Factory: components::ServiceFactory>(
+	config: &FactoryFullConfiguration<Factory>,
+) -> CodeExecutor<Factory> {
+	NativeExecutor::new_with_wasm_method(
+		config.wasm_method,
+		config.default_heap_pages.or(config.chain_spec.default_heap_pages()),
+	)
+	.with_max_memory_pages(config.max_memory_pages.or(config.chain_spec.max_memory_pages()))
+}
+
 /// Creates bare client without any networking.
 pub fn new_client<Factory: components::ServiceFactory>(config: &FactoryFullConfiguration<Factory>)
 	-> Result<Arc<ComponentClient<components::FullComponents<Factory>>>, error::Error>
 {
-	let executor = NativeExecutor::new(config.default_heap_pages);
+	let executor = new_executor::<Factory>(config);
 	let (client, _) = components::FullComponents::<Factory>::build_client(
 		config,
 		executor,
@@ -170,10 +194,18 @@ impl<Components: components::Components> Service<Components> {
 			mpsc::unbounded::<Box<dyn Future<Item = (), Error = ()> + Send>>();
 
 		// Create client
-		let executor = NativeExecutor::new(config.default_heap_pages);
+		let executor = new_executor::<Components::Factory>(&config);
 
-		let mut keystore = if let Some(keystore_path) = config.keystore_path.as_ref() {
-			match Keystore::open(keystore_path.clone()) {
+		let remote_signer = match config.keystore_uri.as_ref() {
+			Some(uri) => Some(Arc::new(RemoteSigner::new(uri)?)),
+			None => None,
+		};
+
+		let mut keystore = if remote_signer.is_some() {
+			// All keys are held by the external signer.
+			None
+		} else if let Some(keystore_path) = config.keystore_path.as_ref() {
+			match Keystore::open(keystore_path.clone(), config.password.clone()) {
 				Ok(ks) => Some(ks),
 				Err(err) => {
 					error!("Failed to initialize keystore: {}", err);
@@ -196,14 +228,33 @@ impl<Components: components::Components> Service<Components> {
 			}
 
 			public_key = match keystore.contents::<ed25519::Public>()?.get(0) {
-				Some(public_key) => public_key.to_string(),
+				Some(public_key) => {
+					// Authorities can't sign without their key, so fail early on a wrong password.
+					if config.roles == Roles::AUTHORITY {
+						keystore.load::<ed25519::Pair>(public_key)?;
+					}
+					public_key.to_string()
+				},
 				None => {
-					let key: ed25519::Pair = keystore.generate(&config.password.as_ref())?;
+					let key: ed25519::Pair = keystore.generate()?;
 					let public_key = key.public();
 					info!("Generated a new keypair: {:?}", public_key);
 					public_key.to_string()
 				}
 			}
+		} else if let Some(remote_signer) = remote_signer.as_ref() {
+			// The signer may be started after the node, it's only needed once something is signed.
+			public_key = match remote_signer.contents::<ed25519::Public>() {
+				Ok(keys) => match keys.get(0) {
+					Some(public_key) => public_key.to_string(),
+					None => "<no-remote-key>".into(),
+				},
+				Err(err) => {
+					warn!("External signer is unreachable: {}", err);
+					"<unreachable-remote-key>".into()
+				},
+			};
+			info!("Using external signer with key: {}", public_key);
 		} else {
 			public_key = format!("<disabled-keystore>");
 		}
@@ -249,13 +300,18 @@ impl<Components: components::Components> Service<Components> {
 			network::config::ProtocolId::from(protocol_id_full)
 		};
 
+		let persisted_peers = peers::PersistedPeers::load(config.network.net_config_path.as_ref());
+		let mut network_config = config.network.clone();
+		network_config.reserved_nodes.extend(persisted_peers.reserved_nodes().iter().cloned());
+		network_config.boot_nodes.extend(persisted_peers.boot_nodes().iter().cloned());
+
 		let network_params = network::config::Params {
 			roles: config.roles,
-			network_config: config.network.clone(),
+			network_config,
 			chain: client.clone(),
 			finality_proof_provider,
 			finality_proof_request_builder,
-			on_demand,
+			on_demand: on_demand.clone(),
 			transaction_pool: transaction_pool_adapter.clone() as _,
 			import_queue,
 			protocol_id,
@@ -267,23 +323,34 @@ impl<Components: components::Components> Service<Components> {
 		let network = network_mut.service().clone();
 		let network_status_sinks = Arc::new(Mutex::new(Vec::new()));
 
+		let keystore = keystore.map(Arc::new);
+		// Components that only need to sign go through the `Keystore` trait, so they work with
+		// both the local keystore and the external signer.
+		let signing_keystore: Option<Arc<dyn keystore::Keystore>> = match remote_signer {
+			Some(remote_signer) => Some(remote_signer as _),
+			None => keystore.clone().map(|keystore| keystore as _),
+		};
 		let keystore_authority_key = AuthorityKeyProvider {
 			_marker: PhantomData,
 			roles: config.roles,
-			password: config.password.clone(),
-			keystore: keystore.map(Arc::new),
+			keystore: keystore.clone(),
+			signing_keystore: signing_keystore.clone(),
 		};
 
 		#[allow(deprecated)]
 		let offchain_storage = client.backend().offchain_storage();
 		let offchain_workers = match (config.offchain_worker, offchain_storage) {
 			(true, Some(db)) => {
-				Some(Arc::new(offchain::OffchainWorkers::new(
+				let workers = offchain::OffchainWorkers::new(
 					client.clone(),
 					db,
 					keystore_authority_key.clone(),
 					config.password.clone(),
-				)))
+				);
+				Some(Arc::new(match config.offchain_worker_max_concurrency {
+					Some(max) => workers.with_max_concurrency(max),
+					None => workers,
+				}))
 			},
 			(true, None) => {
 				log::warn!("Offchain workers disabled, due to lack of offchain storage support in backend.");
@@ -297,6 +364,7 @@ impl<Components: components::Components> Service<Components> {
 			let txpool = Arc::downgrade(&transaction_pool);
 			let wclient = Arc::downgrade(&client);
 			let offchain = offchain_workers.as_ref().map(Arc::downgrade);
+			let offchain_best_only = config.offchain_worker_best_only;
 			let to_spawn_tx_ = to_spawn_tx.clone();
 			let network_state_info: Arc<dyn NetworkStateInfo + Send + Sync> = network.clone();
 
@@ -313,9 +381,13 @@ impl<Components: components::Components> Service<Components> {
 						).map_err(|e| warn!("Pool error processing new block: {:?}", e))?;
 					}
 
-					if let (Some(txpool), Some(offchain)) = (txpool.upgrade(), offchain.as_ref().and_then(|o| o.upgrade())) {
+					let offchain = offchain.as_ref()
+						.filter(|_| notification.is_new_best || !offchain_best_only)
+						.and_then(|o| o.upgrade());
+					if let (Some(txpool), Some(offchain)) = (txpool.upgrade(), offchain) {
 						let future = Components::RuntimeServices::offchain_workers(
 							&number,
+							&notification.hash,
 							&offchain,
 							&txpool,
 							&network_state_info,
@@ -330,6 +402,60 @@ impl<Components: components::Components> Service<Components> {
 			let _ = to_spawn_tx.unbounded_send(Box::new(events));
 		}
 
+		if let Some(offchain) = offchain_workers.as_ref().map(Arc::downgrade) {
+			// finality notifications
+			let events = client.finality_notification_stream()
+				.map(|v| Ok::<_, ()>(v)).compat()
+				.for_each(move |notification| {
+					if let Some(offchain) = offchain.upgrade() {
+						offchain.on_block_finalized(notification.header.number());
+					}
+					Ok(())
+				})
+				.select(exit.clone())
+				.then(|_| Ok(()));
+			let _ = to_spawn_tx.unbounded_send(Box::new(events));
+		}
+
+		{
+			// periodic revalidation of the pool
+			//
+			// Validating transactions calls into the runtime, which would block the tasks of the
+			// executor, so the timer only wakes up a dedicated thread. Ticks that arrive while a
+			// revalidation is still running are dropped.
+			let txpool = Arc::downgrade(&transaction_pool);
+			let wclient = Arc::downgrade(&client);
+			let (revalidate_tx, revalidate_rx) = std::sync::mpsc::sync_channel::<()>(1);
+
+			std::thread::Builder::new()
+				.name("txpool-revalidation".into())
+				.spawn(move || for () in revalidate_rx {
+					let (txpool, client) = match (txpool.upgrade(), wclient.upgrade()) {
+						(Some(txpool), Some(client)) => (txpool, client),
+						_ => break,
+					};
+					if txpool.status().is_empty() {
+						continue
+					}
+
+					let best = BlockId::hash(client.info().chain.best_hash);
+					if let Err(e) = txpool.revalidate(&best, TRANSACTION_POOL_REVALIDATION_BATCH) {
+						warn!("Pool error revalidating transactions: {:?}", e);
+					}
+				})?;
+
+			// the thread stops once the timer, and with it `revalidate_tx`, is dropped on exit
+			let revalidation = tokio_timer::Interval::new_interval(TRANSACTION_POOL_REVALIDATION_INTERVAL)
+				.map_err(|e| warn!("Transaction pool revalidation timer error: {:?}", e))
+				.for_each(move |_| {
+					let _ = revalidate_tx.try_send(());
+					Ok(())
+				})
+				.select(exit.clone())
+				.then(|_| Ok(()));
+			let _ = to_spawn_tx.unbounded_send(Box::new(revalidation));
+		}
+
 		{
 			// extrinsic notifications
 			let network = Arc::downgrade(&network);
@@ -366,6 +492,9 @@ impl<Components: components::Components> Service<Components> {
 			let num_peers = net_status.num_connected_peers;
 			let txpool_status = transaction_pool_.status();
 			let finalized_number: u64 = info.chain.finalized_number.saturated_into::<u64>();
+			let sync_target = net_status.best_seen_block
+				.map(|number| number.saturated_into::<u64>())
+				.unwrap_or(best_number);
 			let bandwidth_download = net_status.average_download_per_sec;
 			let bandwidth_upload = net_status.average_upload_per_sec;
 
@@ -385,6 +514,14 @@ impl<Components: components::Components> Service<Components> {
 				} else { (0.0, 0) }
 			} else { (0.0, 0) };
 
+			metrics::BEST_BLOCK_HEIGHT.set(best_number as i64);
+			metrics::FINALIZED_BLOCK_HEIGHT.set(finalized_number as i64);
+			metrics::SYNC_TARGET.set(sync_target as i64);
+			metrics::PEERS.set(num_peers as i64);
+			metrics::TRANSACTION_POOL_READY.set(txpool_status.ready as i64);
+			metrics::TRANSACTION_POOL_FUTURE.set(txpool_status.future as i64);
+			metrics::STATE_CACHE_BYTES.set(used_state_cache_size as i64);
+
 			telemetry!(
 				SUBSTRATE_INFO;
 				"system.interval";
@@ -408,6 +545,11 @@ impl<Components: components::Components> Service<Components> {
 
 		// RPC
 		let (system_rpc_tx, system_rpc_rx) = futures03::channel::mpsc::unbounded();
+		let middleware = rpc::RpcMiddleware::new(config.rpc_limits.clone());
+		let rpc_extension = Components::build_rpc_extension(
+			client.clone(),
+			transaction_pool.clone(),
+		)?;
 		let gen_handler = || {
 			let system_info = rpc::apis::system::SystemInfo {
 				chain_name: config.chain_spec.name().into(),
@@ -421,16 +563,22 @@ impl<Components: components::Components> Service<Components> {
 				system_info.clone(),
 				Arc::new(SpawnTaskHandle { sender: to_spawn_tx.clone() }),
 				transaction_pool.clone(),
+				signing_keystore.clone(),
+				on_demand.clone(),
+				rpc_extension.clone(),
+				middleware.clone(),
 			)
 		};
 		let rpc_handlers = gen_handler();
 		let rpc = start_rpc_servers::<Components::Factory, _>(&config, gen_handler)?;
+		let prometheus = start_prometheus_server(config.prometheus_endpoint)?;
 
 		let _ = to_spawn_tx.unbounded_send(Box::new(build_network_future::<Components, _, _>(
 			network_mut,
 			client.clone(),
 			network_status_sinks.clone(),
 			system_rpc_rx,
+			persisted_peers,
 			has_bootnodes
 		)
 			.map_err(|_| ())
@@ -497,6 +645,7 @@ impl<Components: components::Components> Service<Components> {
 			rpc_handlers,
 			_rpc: rpc,
 			_telemetry: telemetry,
+			_prometheus: prometheus,
 			_offchain_workers: offchain_workers,
 			_telemetry_on_connect_sinks: telemetry_connection_sinks.clone(),
 		})
@@ -516,6 +665,15 @@ impl<Components: components::Components> Service<Components> {
 		self.keystore.fg_authority_key(&BlockId::Number(Zero::zero()))
 	}
 
+	/// give the keystore the consensus engines sign with, if we are an authority
+	pub fn authority_keystore(&self) -> Option<Arc<dyn keystore::Keystore>> {
+		if self.keystore.roles != Roles::AUTHORITY {
+			return None
+		}
+
+		self.keystore.signing_keystore.clone()
+	}
+
 	/// return a shared instance of Telemetry (if enabled)
 	pub fn telemetry(&self) -> Option<tel::Telemetry> {
 		self._telemetry.as_ref().map(|t| t.clone())
@@ -587,6 +745,8 @@ impl<Components> Future for Service<Components> where Components: components::Co
 
 	fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
 		while let Ok(Async::Ready(Some(task_to_spawn))) = self.to_spawn_rx.poll() {
+			let task_to_spawn: Box<dyn Future<Item = (), Error = ()> + Send> =
+				Box::new(metrics::CountedTask::new(task_to_spawn));
 			let executor = tokio_executor::DefaultExecutor::current();
 			if let Err(err) = executor.execute(task_to_spawn) {
 				debug!(
@@ -624,6 +784,93 @@ impl<Components> Executor<Box<dyn Future<Item = (), Error = ()> + Send>>
 	}
 }
 
+/// The aux storage key of the GRANDPA authority set, see the `finality-grandpa` aux schema.
+const GRANDPA_AUTHORITY_SET_KEY: &[u8] = b"grandpa_voters";
+/// The aux storage key of the BABE epoch changes, see the `consensus-babe` aux schema.
+const BABE_EPOCH_CHANGES_KEY: &[u8] = b"babe_epoch_changes";
+/// The keys of the blockchain cache exported in the light sync state.
+const CACHE_KEYS: [consensus_common::well_known_cache_keys::Id; 2] = [
+	consensus_common::well_known_cache_keys::AUTHORITIES,
+	consensus_common::well_known_cache_keys::EPOCH,
+];
+
+/// Builds the state a light client needs to sync from the latest finalized block.
+fn light_sync_state<Components: components::Components>(
+	client: &ComponentClient<Components>,
+) -> error::Result<LightSyncState> {
+	use client::{backend::AuxStore, blockchain::ProvideCache};
+
+	let finalized_hash = client.info().chain.finalized_hash;
+	let header = client.header(&BlockId::hash(finalized_hash))?
+		.ok_or_else(|| error::Error::Other(format!("Unknown finalized block {}", finalized_hash)))?;
+	let cache = client.cache()
+		.map(|cache| CACHE_KEYS.iter()
+			.filter_map(|key| cache.get_at(key, &BlockId::hash(finalized_hash))
+				.map(|value| (String::from_utf8_lossy(key).into_owned(), value.into())))
+			.collect())
+		.unwrap_or_default();
+	Ok(LightSyncState {
+		finalized_block_header: header.encode().into(),
+		grandpa_authority_set: client.get_aux(GRANDPA_AUTHORITY_SET_KEY)?.map(Into::into),
+		babe_epoch_changes: client.get_aux(BABE_EPOCH_CHANGES_KEY)?.map(Into::into),
+		cache,
+	})
+}
+
+/// Stores the light sync state embedded in the chain spec in the storage of a light client,
+/// which then syncs from its finalized block instead of the genesis block.
+///
+/// Does nothing if the light client has already finalized that block.
+pub(crate) fn import_light_sync_state<Block, S>(
+	storage: &S,
+	sync_state: &LightSyncState,
+) -> error::Result<()> where
+	Block: BlockT,
+	S: client::light::blockchain::Storage<Block>,
+{
+	let header = <Block::Header as Decode>::decode(&mut &sync_state.finalized_block_header[..])
+		.map_err(|e| error::Error::Other(format!("Invalid light sync state header: {}", e.what())))?;
+	if *header.number() <= storage.info().finalized_number {
+		return Ok(());
+	}
+
+	let cache = sync_state.cache.iter()
+		.map(|(key, value)| {
+			let mut id = consensus_common::well_known_cache_keys::Id::default();
+			if key.len() != id.len() {
+				return Err(error::Error::Other(format!("Invalid light sync state cache key {}", key)));
+			}
+			id.copy_from_slice(key.as_bytes());
+			Ok((id, value.to_vec()))
+		})
+		.collect::<error::Result<_>>()?;
+
+	let mut aux_ops = Vec::new();
+	if let Some(ref authority_set) = sync_state.grandpa_authority_set {
+		aux_ops.push((
+			GRANDPA_AUTHORITY_SET_KEY.to_vec(),
+			Some(light_grandpa_authority_set(authority_set)?),
+		));
+	}
+	if let Some(ref epoch_changes) = sync_state.babe_epoch_changes {
+		aux_ops.push((BABE_EPOCH_CHANGES_KEY.to_vec(), Some(epoch_changes.to_vec())));
+	}
+
+	info!("Starting to sync from the light sync state at #{} ({})", header.number(), header.hash());
+	storage.import_checkpoint(header, cache, aux_ops).map_err(Into::into)
+}
+
+/// Converts the GRANDPA authority set stored by full nodes, which starts with the current
+/// authorities and the set id, to the one stored by the light client import, which holds them
+/// in the opposite order.
+///
+/// The pending changes are dropped: light clients learn about the changes from finality proofs.
+fn light_grandpa_authority_set(authority_set: &[u8]) -> error::Result<Vec<u8>> {
+	let (authorities, set_id) = <(Vec<(ed25519::Public, u64)>, u64)>::decode(&mut &authority_set[..])
+		.map_err(|e| error::Error::Other(format!("Invalid GRANDPA authority set: {}", e.what())))?;
+	Ok((set_id, authorities).encode())
+}
+
 /// Builds a never-ending future that continuously polls the network.
 ///
 /// The `status_sink` contain a list of senders to send a periodic network status to.
@@ -636,6 +883,7 @@ fn build_network_future<
 	client: Arc<ComponentClient<Components>>,
 	status_sinks: Arc<Mutex<Vec<mpsc::UnboundedSender<(NetworkStatus<ComponentBlock<Components>>, NetworkState)>>>>,
 	rpc_rx: futures03::channel::mpsc::UnboundedReceiver<rpc::apis::system::Request<ComponentBlock<Components>>>,
+	mut persisted_peers: peers::PersistedPeers,
 	should_have_peers: bool,
 ) -> impl Future<Item = (), Error = ()> {
 	// Compatibility shim while we're transitionning to stable Futures.
@@ -692,6 +940,35 @@ fn build_network_future<
 				rpc::apis::system::Request::NetworkState(sender) => {
 					let _ = sender.send(network.network_state());
 				}
+				rpc::apis::system::Request::LightSyncState(sender) => {
+					match light_sync_state::<Components>(&client) {
+						Ok(sync_state) => { let _ = sender.send(sync_state); },
+						Err(e) => warn!(target: "service", "Failed to build the light sync state: {:?}", e),
+					}
+				}
+				rpc::apis::system::Request::AddReservedPeer(peer, sender) => {
+					let result = network.service().add_reserved_peer(peer.clone());
+					if result.is_ok() {
+						persisted_peers.add_reserved_node(peer);
+					}
+					let _ = sender.send(result);
+				}
+				rpc::apis::system::Request::RemoveReservedPeer(peer_id, sender) => {
+					let result = peer_id.parse::<network::PeerId>()
+						.map(|peer_id| {
+							network.service().remove_reserved_peer(peer_id.clone());
+							persisted_peers.remove_reserved_node(&peer_id);
+						})
+						.map_err(|e| format!("{:?}", e));
+					let _ = sender.send(result);
+				}
+				rpc::apis::system::Request::AddBootNode(peer, sender) => {
+					let result = network.service().add_boot_node(peer.clone());
+					if result.is_ok() {
+						persisted_peers.add_boot_node(peer);
+					}
+					let _ = sender.send(result);
+				}
 			};
 		}
 
@@ -786,7 +1063,13 @@ fn start_rpc_servers<F: ServiceFactory, H: FnMut() -> rpc::RpcHandler>(
 	Ok(Box::new((
 		maybe_start_server(
 			config.rpc_http,
-			|address| rpc::start_http(address, config.rpc_cors.as_ref(), gen_handler()),
+			|address| rpc::start_http(
+				address,
+				config.rpc_cors.as_ref(),
+				config.rpc_auth_token.as_ref(),
+				config.rpc_trust_proxy,
+				gen_handler(),
+			),
 		)?,
 		maybe_start_server(
 			config.rpc_ws,
@@ -794,6 +1077,7 @@ fn start_rpc_servers<F: ServiceFactory, H: FnMut() -> rpc::RpcHandler>(
 				address,
 				config.rpc_ws_max_connections,
 				config.rpc_cors.as_ref(),
+				config.rpc_auth_token.as_ref(),
 				gen_handler(),
 			),
 		)?.map(Mutex::new),
@@ -809,6 +1093,25 @@ fn start_rpc_servers<F: ServiceFactory, H: FnMut() -> rpc::RpcHandler>(
 	Ok(Box::new(()))
 }
 
+/// Starts the Prometheus metrics server if enabled, and returns an opaque object that keeps it alive.
+#[cfg(not(target_os = "unknown"))]
+fn start_prometheus_server(
+	address: Option<SocketAddr>,
+) -> Result<Option<Box<dyn std::any::Any + Send + Sync>>, error::Error> {
+	match address {
+		Some(address) => Ok(Some(Box::new(prometheus::start_server(&address)?))),
+		None => Ok(None),
+	}
+}
+
+/// Starts the Prometheus metrics server if enabled, and returns an opaque object that keeps it alive.
+#[cfg(target_os = "unknown")]
+fn start_prometheus_server(
+	_: Option<SocketAddr>,
+) -> Result<Option<Box<dyn std::any::Any + Send + Sync>>, error::Error> {
+	Ok(None)
+}
+
 /// An RPC session. Used to perform in-memory RPC queries (ie. RPC queries that don't go through
 /// the HTTP or WebSockets server).
 pub struct RpcSession {
@@ -821,10 +1124,11 @@ impl RpcSession {
 	/// The `sender` is stored inside the `RpcSession` and is used to communicate spontaneous JSON
 	/// messages.
 	///
-	/// The `RpcSession` must be kept alive in order to receive messages on the sender.
+	/// The `RpcSession` must be kept alive in order to receive messages on the sender. Being
+	/// in-process, the session is authenticated.
 	pub fn new(sender: mpsc::Sender<String>) -> RpcSession {
 		RpcSession {
-			metadata: rpc::Metadata::new(sender),
+			metadata: rpc::Metadata::new(sender).authenticated(true),
 		}
 	}
 }
@@ -871,36 +1175,28 @@ impl<C: Components> network::TransactionPool<ComponentExHash<C>, ComponentBlock<
 	}
 
 	fn import(&self, transaction: &ComponentExtrinsic<C>) -> Option<ComponentExHash<C>> {
+		self.submit(transaction).map_err(|e| debug!("{}", e)).ok()
+	}
+
+	fn submit(&self, transaction: &ComponentExtrinsic<C>) -> Result<ComponentExHash<C>, String> {
 		if !self.imports_external_transactions {
-			debug!("Transaction rejected");
-			return None;
+			return Err("Transaction rejected: the node doesn't import external transactions".into());
 		}
 
 		let encoded = transaction.encode();
-		match Decode::decode(&mut &encoded[..]) {
-			Ok(uxt) => {
-				let best_block_id = self.best_block_id()?;
-				match self.pool.submit_one(&best_block_id, uxt) {
-					Ok(hash) => Some(hash),
-					Err(e) => match e.into_pool_error() {
-						Ok(txpool::error::Error::AlreadyImported(hash)) => {
-							hash.downcast::<ComponentExHash<C>>().ok()
-								.map(|x| x.as_ref().clone())
-						},
-						Ok(e) => {
-							debug!("Error adding transaction to the pool: {:?}", e);
-							None
-						},
-						Err(e) => {
-							debug!("Error converting pool error: {:?}", e);
-							None
-						},
-					}
-				}
-			}
-			Err(e) => {
-				debug!("Error decoding transaction {}", e);
-				None
+		let uxt = Decode::decode(&mut &encoded[..])
+			.map_err(|e| format!("Error decoding transaction {}", e))?;
+		let best_block_id = self.best_block_id()
+			.ok_or_else(|| "Error adding transaction to the pool: unknown best block".to_string())?;
+		match self.pool.submit_one(&best_block_id, TransactionSource::External, uxt) {
+			Ok(hash) => Ok(hash),
+			Err(e) => match e.into_pool_error() {
+				Ok(txpool::error::Error::AlreadyImported(hash)) => hash
+					.downcast::<ComponentExHash<C>>()
+					.map(|x| x.as_ref().clone())
+					.map_err(|_| "Error converting the hash of an imported transaction".into()),
+				Ok(e) => Err(format!("Error adding transaction to the pool: {:?}", e)),
+				Err(e) => Err(format!("Error converting pool error: {:?}", e)),
 			}
 		}
 	}
@@ -908,6 +1204,11 @@ impl<C: Components> network::TransactionPool<ComponentExHash<C>, ComponentBlock<
 	fn on_broadcasted(&self, propagations: HashMap<ComponentExHash<C>, Vec<String>>) {
 		self.pool.on_broadcasted(propagations)
 	}
+
+	fn on_rejected(&self, hash: &ComponentExHash<C>, reason: String) {
+		debug!("Transaction {:?} rejected by the network: {}", hash, reason);
+		self.pool.remove_invalid(&[hash.clone()]);
+	}
 }
 
 #[derive(Clone)]
@@ -916,7 +1217,32 @@ pub struct AuthorityKeyProvider<Block, ConsensusPair, FinalityPair> {
 	_marker: PhantomData<(Block, ConsensusPair, FinalityPair)>,
 	roles: Roles,
 	keystore: Option<Arc<Keystore>>,
-	password: crypto::Protected<String>,
+	signing_keystore: Option<Arc<dyn keystore::Keystore>>,
+}
+
+impl<Block, ConsensusPair, FinalityPair> AuthorityKeyProvider<Block, ConsensusPair, FinalityPair> {
+	/// Returns the first key of type `P`, signing through the `Keystore` trait.
+	fn external_key<P: Pair>(&self) -> Option<offchain::ExternalKey> {
+		if self.roles != Roles::AUTHORITY {
+			return None
+		}
+
+		let keystore = self.signing_keystore.as_ref()?.clone();
+		let public = match keystore.public_keys::<P::Public>() {
+			Ok(keys) => keys.into_iter().next()?,
+			Err(err) => {
+				warn!("Failed to get authority key from keystore: {}", err);
+				return None
+			},
+		};
+
+		Some(offchain::ExternalKey {
+			public: crypto::Public::to_raw_vec(&public),
+			sign: Box::new(move |data| keystore.sign::<P>(&public, data).map_err(|err| {
+				warn!("Failed to sign with authority key: {}", err);
+			})),
+		})
+	}
 }
 
 impl<Block, ConsensusPair, FinalityPair>
@@ -943,13 +1269,16 @@ where
 		let loaded_key = keystore
 			.contents()
 			.map(|keys| keys.get(0)
-				 .map(|k| keystore.load(k, self.password.as_ref()))
+				 .map(|k| keystore.load(k))
 			);
 
-		if let Ok(Some(Ok(key))) = loaded_key {
-			Some(key)
-		} else {
-			None
+		match loaded_key {
+			Ok(Some(Ok(key))) => Some(key),
+			Ok(Some(Err(err))) => {
+				warn!("Failed to load authority key: {}", err);
+				None
+			},
+			_ => None,
 		}
 	}
 
@@ -966,15 +1295,45 @@ where
 		let loaded_key = keystore
 			.contents()
 			.map(|keys| keys.get(0)
-				 .map(|k| keystore.load(k, self.password.as_ref()))
+				 .map(|k| keystore.load(k))
 			);
 
-		if let Ok(Some(Ok(key))) = loaded_key {
-			Some(key)
-		} else {
-			None
+		match loaded_key {
+			Ok(Some(Ok(key))) => Some(key),
+			Ok(Some(Err(err))) => {
+				warn!("Failed to load authority key: {}", err);
+				None
+			},
+			_ => None,
 		}
 	}
+
+	fn external_authority_key(&self, _at: &BlockId<Block>) -> Option<offchain::ExternalKey> {
+		self.external_key::<ConsensusPair>()
+	}
+
+	fn external_fg_authority_key(&self, _at: &BlockId<Block>) -> Option<offchain::ExternalKey> {
+		self.external_key::<FinalityPair>()
+	}
+
+	fn keystore_public_keys(&self, key_type: crypto::KeyTypeId) -> Result<Vec<Vec<u8>>, ()> {
+		let keystore = self.signing_keystore.as_ref().ok_or(())?;
+		keystore.public_keys_by_type(key_type).map_err(|err| {
+			warn!("Failed to get keys of type {} from keystore: {}", key_type, err);
+		})
+	}
+
+	fn keystore_sign(
+		&self,
+		key_type: crypto::KeyTypeId,
+		public: &[u8],
+		data: &[u8],
+	) -> Result<Vec<u8>, ()> {
+		let keystore = self.signing_keystore.as_ref().ok_or(())?;
+		keystore.sign_by_type(key_type, public, data).map_err(|err| {
+			warn!("Failed to sign with key of type {}: {}", key_type, err);
+		})
+	}
 }
 
 /// Constructs a service factory with the given name that implements the `ServiceFactory` trait.
@@ -982,13 +1341,15 @@ where
 /// by `{}` blocks. These blocks are required and used to initialize the given parameter.
 /// In these block it is required to write a closure that takes the same number of arguments,
 /// the corresponding function in the `ServiceFactory` trait provides.
+/// `ChainSpecExtension`, following `Configuration`, is optional and defaults to `NoExtension`.
+/// The last parameter, `RpcExtension`, is optional and defaults to no additional RPC methods.
 ///
 /// # Example
 ///
 /// ```
 /// # use substrate_service::{
 /// # 	construct_service_factory, Service, FullBackend, FullExecutor, LightBackend, LightExecutor,
-/// # 	FullComponents, LightComponents, FactoryFullConfiguration, FullClient
+/// # 	FullComponents, LightComponents, FactoryFullConfiguration, FullClient, RpcExtension,
 /// # };
 /// # use transaction_pool::{self, txpool::{Pool as TransactionPool}};
 /// # use network::{config::DummyFinalityProofRequestBuilder, construct_simple_protocol};
@@ -1061,11 +1422,16 @@ where
 /// 		FinalityProofProvider = { |client: Arc<FullClient<Self>>| {
 /// 				Ok(Some(Arc::new(grandpa::FinalityProofProvider::new(client.clone(), client)) as _))
 /// 			}},
+/// 		RpcExtension = { |client: Arc<FullClient<Self>>, pool: Arc<TransactionPool<FullChainApi<Self>>>| {
+/// 				Ok(RpcExtension::default())
+/// 			}},
 /// 	}
 /// }
 /// ```
 #[macro_export]
 macro_rules! construct_service_factory {
+	(@extension) => { $crate::NoExtension };
+	(@extension $extension:ty) => { $extension };
 	(
 		$(#[$attr:meta])*
 		struct $name:ident {
@@ -1079,6 +1445,7 @@ macro_rules! construct_service_factory {
 			LightTransactionPoolApi = $light_transaction:ty { $( $light_transaction_init:tt )* },
 			Genesis = $genesis:ty,
 			Configuration = $config:ty,
+			$( ChainSpecExtension = $extension:ty, )?
 			FullService = $full_service:ty { $( $full_service_init:tt )* },
 			AuthoritySetup = { $( $authority_setup:tt )* },
 			LightService = $light_service:ty { $( $light_service_init:tt )* },
@@ -1089,6 +1456,7 @@ macro_rules! construct_service_factory {
 			SelectChain = $select_chain:ty
 				{ $( $select_chain_init:tt )* },
 			FinalityProofProvider = { $( $finality_proof_provider_init:tt )* },
+			$( RpcExtension = { $( $rpc_extension_init:tt )* }, )?
 		}
 	) => {
 		$( #[$attr] )*
@@ -1106,6 +1474,7 @@ macro_rules! construct_service_factory {
 			type LightTransactionPoolApi = $light_transaction;
 			type Genesis = $genesis;
 			type Configuration = $config;
+			type ChainSpecExtension = $crate::construct_service_factory!(@extension $( $extension )?);
 			type FullService = $full_service;
 			type LightService = $light_service;
 			type FullImportQueue = $full_import_queue;
@@ -1162,6 +1531,20 @@ macro_rules! construct_service_factory {
 				( $( $finality_proof_provider_init )* ) (client)
 			}
 
+			fn build_rpc_extension(
+				client: Arc<$crate::FullClient<Self>>,
+				transaction_pool: Arc<$crate::TransactionPool<Self::FullTransactionPoolApi>>,
+			) -> Result<$crate::RpcExtension, $crate::Error> {
+				let build = |
+					_: Arc<$crate::FullClient<Self>>,
+					_: Arc<$crate::TransactionPool<Self::FullTransactionPoolApi>>,
+				| -> Result<$crate::RpcExtension, $crate::Error> {
+					Ok(Default::default())
+				};
+				$( let build = $( $rpc_extension_init )*; )?
+				build(client, transaction_pool)
+			}
+
 			fn new_light(
 				config: $crate::FactoryFullConfiguration<Self>
 			) -> $crate::Result<Self::LightService, $crate::Error>
@@ -1204,8 +1587,8 @@ mod tests {
 			from: AccountKeyring::Alice.into(),
 			to: Default::default(),
 		}.into_signed_tx();
-		pool.submit_one(&BlockId::hash(best.hash()), transaction.clone()).unwrap();
-		pool.submit_one(&BlockId::hash(best.hash()), Extrinsic::IncludeData(vec![1])).unwrap();
+		pool.submit_one(&BlockId::hash(best.hash()), TransactionSource::External, transaction.clone()).unwrap();
+		pool.submit_one(&BlockId::hash(best.hash()), TransactionSource::External, Extrinsic::IncludeData(vec![1])).unwrap();
 		assert_eq!(pool.status().ready, 2);
 
 		// when
@@ -1217,4 +1600,40 @@ mod tests {
 		// this should not panic
 		let _ = transactions[0].1.transfer();
 	}
+
+	#[test]
+	fn light_sync_state_is_imported_once() {
+		use client::{backend::{AuxStore, NewBlockState}, blockchain::HeaderBackend, in_mem};
+		use substrate_test_runtime_client::runtime::{Block, Header as RuntimeHeader};
+
+		// given
+		let storage = in_mem::Blockchain::<Block>::new();
+		let genesis = RuntimeHeader::new(0, Default::default(), Default::default(), Default::default(), Default::default());
+		storage.insert(genesis.hash(), genesis, None, None, NewBlockState::Final).unwrap();
+
+		let header = RuntimeHeader::new(10, Default::default(), Default::default(), [1; 32].into(), Default::default());
+		let authorities = vec![(ed25519::Public::from_raw([1; 32]), 1u64)];
+		let mut authority_set = (authorities.clone(), 5u64).encode();
+		authority_set.extend(vec![0, 0]);
+		let sync_state = LightSyncState {
+			finalized_block_header: header.encode().into(),
+			grandpa_authority_set: Some(authority_set.into()),
+			babe_epoch_changes: Some(vec![1, 2, 3].into()),
+			cache: vec![("epch".to_string(), vec![4].into())].into_iter().collect(),
+		};
+
+		// when
+		import_light_sync_state(&storage, &sync_state).unwrap();
+
+		// then
+		assert_eq!(storage.info().finalized_hash, header.hash());
+		assert_eq!(storage.info().best_hash, header.hash());
+		assert_eq!(storage.get_aux(GRANDPA_AUTHORITY_SET_KEY).unwrap(), Some((5u64, authorities).encode()));
+		assert_eq!(storage.get_aux(BABE_EPOCH_CHANGES_KEY).unwrap(), Some(vec![1, 2, 3]));
+
+		// the state is not imported again once its block is finalized
+		storage.insert_aux(&[(BABE_EPOCH_CHANGES_KEY, &[4][..])], &[]).unwrap();
+		import_light_sync_state(&storage, &sync_state).unwrap();
+		assert_eq!(storage.get_aux(BABE_EPOCH_CHANGES_KEY).unwrap(), Some(vec![4]));
+	}
 }
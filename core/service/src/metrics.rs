@@ -0,0 +1,73 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Prometheus metrics of the service.
+
+use std::sync::Arc;
+
+use futures::prelude::*;
+use lazy_static::lazy_static;
+use prometheus::{Counter, Gauge};
+
+lazy_static! {
+	pub static ref BEST_BLOCK_HEIGHT: Arc<Gauge> =
+		prometheus::gauge("substrate_block_height_best", "Height of the best block");
+	pub static ref FINALIZED_BLOCK_HEIGHT: Arc<Gauge> =
+		prometheus::gauge("substrate_block_height_finalized", "Height of the last finalized block");
+	pub static ref SYNC_TARGET: Arc<Gauge> =
+		prometheus::gauge("substrate_sync_target", "Highest block number seen on the network");
+	pub static ref PEERS: Arc<Gauge> =
+		prometheus::gauge("substrate_peers", "Number of connected peers");
+	pub static ref TRANSACTION_POOL_READY: Arc<Gauge> =
+		prometheus::gauge("substrate_transaction_pool_ready", "Number of ready transactions in the pool");
+	pub static ref TRANSACTION_POOL_FUTURE: Arc<Gauge> =
+		prometheus::gauge("substrate_transaction_pool_future", "Number of future transactions in the pool");
+	pub static ref STATE_CACHE_BYTES: Arc<Gauge> =
+		prometheus::gauge("substrate_state_cache_bytes", "Memory used by the state cache");
+	pub static ref TASKS_SPAWNED: Arc<Counter> =
+		prometheus::counter("substrate_tasks_spawned_total", "Number of spawned background tasks");
+	pub static ref TASKS_ACTIVE: Arc<Gauge> =
+		prometheus::gauge("substrate_tasks_active", "Number of running background tasks");
+}
+
+/// A background task that is counted as active until it is dropped.
+pub struct CountedTask<F> {
+	inner: F,
+}
+
+impl<F> CountedTask<F> {
+	/// Start counting `inner` as an active task.
+	pub fn new(inner: F) -> Self {
+		TASKS_SPAWNED.inc();
+		TASKS_ACTIVE.inc();
+		CountedTask { inner }
+	}
+}
+
+impl<F: Future> Future for CountedTask<F> {
+	type Item = F::Item;
+	type Error = F::Error;
+
+	fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+		self.inner.poll()
+	}
+}
+
+impl<F> Drop for CountedTask<F> {
+	fn drop(&mut self) {
+		TASKS_ACTIVE.dec();
+	}
+}
@@ -0,0 +1,132 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Peers added by the node operator through the RPC, kept across restarts of the node.
+
+use std::{fs::{self, File}, path::{Path, PathBuf}};
+
+use log::warn;
+use network::{PeerId, config::parse_str_addr};
+use serde::{Serialize, Deserialize};
+
+/// Name of the file the peers are stored in, inside the network config directory.
+const PEERS_FILE: &str = "peers.json";
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Peers {
+	reserved_nodes: Vec<String>,
+	boot_nodes: Vec<String>,
+}
+
+/// Peers added by the node operator, stored in the network config directory.
+pub struct PersistedPeers {
+	path: Option<PathBuf>,
+	peers: Peers,
+}
+
+impl PersistedPeers {
+	/// Load the peers stored in the network config directory `net_config_path`.
+	///
+	/// Nothing is stored if no directory is given.
+	pub fn load(net_config_path: Option<&String>) -> Self {
+		let path = net_config_path.map(|dir| Path::new(dir).join(PEERS_FILE));
+		let peers = path.as_ref()
+			.filter(|path| path.exists())
+			.and_then(|path| File::open(path)
+				.map_err(|e| e.to_string())
+				.and_then(|file| serde_json::from_reader(file).map_err(|e| e.to_string()))
+				.map_err(|e| warn!("Failed to load peers from {}: {}", path.display(), e))
+				.ok()
+			)
+			.unwrap_or_default();
+		PersistedPeers { path, peers }
+	}
+
+	/// The addresses of the reserved peers.
+	pub fn reserved_nodes(&self) -> &[String] {
+		&self.peers.reserved_nodes
+	}
+
+	/// The addresses of the boot nodes.
+	pub fn boot_nodes(&self) -> &[String] {
+		&self.peers.boot_nodes
+	}
+
+	/// Store the address of a reserved peer.
+	pub fn add_reserved_node(&mut self, addr: String) {
+		if !self.peers.reserved_nodes.contains(&addr) {
+			self.peers.reserved_nodes.push(addr);
+			self.save();
+		}
+	}
+
+	/// Remove all addresses of the reserved peer `peer_id`.
+	pub fn remove_reserved_node(&mut self, peer_id: &PeerId) {
+		let len = self.peers.reserved_nodes.len();
+		self.peers.reserved_nodes.retain(|addr| parse_str_addr(addr)
+			.map_or(true, |(id, _)| &id != peer_id)
+		);
+		if self.peers.reserved_nodes.len() != len {
+			self.save();
+		}
+	}
+
+	/// Store the address of a boot node.
+	pub fn add_boot_node(&mut self, addr: String) {
+		if !self.peers.boot_nodes.contains(&addr) {
+			self.peers.boot_nodes.push(addr);
+			self.save();
+		}
+	}
+
+	fn save(&self) {
+		if let Some(path) = &self.path {
+			let result = path.parent().map_or(Ok(()), fs::create_dir_all)
+				.and_then(|_| File::create(path))
+				.map_err(|e| e.to_string())
+				.and_then(|file| serde_json::to_writer_pretty(file, &self.peers).map_err(|e| e.to_string()));
+			if let Err(e) = result {
+				warn!("Failed to store peers in {}: {}", path.display(), e);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const PEER: &str = "/ip4/127.0.0.1/tcp/30333/p2p/QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV";
+
+	#[test]
+	fn peers_are_kept_across_loads() {
+		let dir = tempdir::TempDir::new("peers").unwrap();
+		let dir = dir.path().to_string_lossy().into_owned();
+
+		let mut peers = PersistedPeers::load(Some(&dir));
+		peers.add_reserved_node(PEER.into());
+		peers.add_reserved_node(PEER.into());
+		peers.add_boot_node(PEER.into());
+
+		let mut peers = PersistedPeers::load(Some(&dir));
+		assert_eq!(peers.reserved_nodes(), &[PEER.to_string()]);
+		assert_eq!(peers.boot_nodes(), &[PEER.to_string()]);
+
+		peers.remove_reserved_node(&parse_str_addr(PEER).unwrap().0);
+		assert!(PersistedPeers::load(Some(&dir)).reserved_nodes().is_empty());
+	}
+}
@@ -15,8 +15,14 @@
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Service integration test utils.
+//!
+//! `TestNet` runs authorities, full nodes and light nodes of a service factory inside the test
+//! process, each listening on its own localhost port, so that tests can connect them, drive
+//! block production and wait for the nodes to sync or finalize. `connectivity`, `sync`,
+//! `consensus` and `export_import` are the standard scenarios every service is expected to pass.
 
 use std::iter;
+use std::io::Cursor;
 use std::sync::{Arc, Mutex, MutexGuard};
 use std::net::Ipv4Addr;
 use std::time::Duration;
@@ -37,12 +43,14 @@ use service::{
 use network::{multiaddr, Multiaddr};
 use network::config::{NetworkConfiguration, TransportConfig, NodeKeyConfig, Secret, NonReservedPeerMode};
 use sr_primitives::generic::BlockId;
+use sr_primitives::transaction_validity::TransactionSource;
 use consensus::{BlockImportParams, BlockImport};
 
 /// Maximum duration of single wait call.
 const MAX_WAIT_TIME: Duration = Duration::from_secs(60 * 3);
 
-struct TestNet<F: ServiceFactory> {
+/// A network of nodes running inside the test process.
+pub struct TestNet<F: ServiceFactory> {
 	runtime: Runtime,
 	authority_nodes: Vec<(usize, SyncService<F::FullService>, Multiaddr)>,
 	full_nodes: Vec<(usize, SyncService<F::FullService>, Multiaddr)>,
@@ -56,6 +64,7 @@ struct TestNet<F: ServiceFactory> {
 pub struct SyncService<T>(Arc<Mutex<T>>);
 
 impl<T> SyncService<T> {
+	/// Lock the service.
 	pub fn get(&self) -> MutexGuard<T> {
 		self.0.lock().unwrap()
 	}
@@ -83,6 +92,93 @@ impl<T: Future<Item=(), Error=()>> Future for SyncService<T> {
 }
 
 impl<F: ServiceFactory> TestNet<F> {
+	/// The authority nodes with their index and address.
+	pub fn authority_nodes(&self) -> &[(usize, SyncService<F::FullService>, Multiaddr)] {
+		&self.authority_nodes
+	}
+
+	/// The full nodes with their index and address.
+	pub fn full_nodes(&self) -> &[(usize, SyncService<F::FullService>, Multiaddr)] {
+		&self.full_nodes
+	}
+
+	/// The light nodes with their index and address.
+	pub fn light_nodes(&self) -> &[(usize, SyncService<F::LightService>, Multiaddr)] {
+		&self.light_nodes
+	}
+
+	/// Connect all nodes to the first authority, or to the first full node if there are no
+	/// authorities, as a reserved peer.
+	pub fn connect_to_first(&self) {
+		let first = self.authority_nodes.first().or_else(|| self.full_nodes.first())
+			.expect("the network has at least one full node; qed");
+		let first_address = first.2.to_string();
+		for (index, service, _) in self.authority_nodes.iter().chain(self.full_nodes.iter()) {
+			if *index != first.0 {
+				service.get().network().add_reserved_peer(first_address.clone())
+					.expect("Error adding reserved peer");
+			}
+		}
+		for (_, service, _) in self.light_nodes.iter() {
+			service.get().network().add_reserved_peer(first_address.clone())
+				.expect("Error adding reserved peer");
+		}
+	}
+
+	/// Wait until the best block of all nodes is at least `number`.
+	pub fn wait_for_best(&mut self, number: u32) {
+		self.run_until_all_nodes(
+			move |_index, service| service.get().client().info().chain.best_number >= number.into(),
+			move |_index, service| service.get().client().info().chain.best_number >= number.into(),
+		);
+	}
+
+	/// Wait until the last finalized block of all full nodes and authorities is at least
+	/// `number`, and the best block of all light nodes is at least `number`.
+	pub fn wait_for_finalized(&mut self, number: u32) {
+		self.run_until_all_nodes(
+			move |_index, service|
+				service.get().client().info().chain.finalized_number >= number.into(),
+			move |_index, service| service.get().client().info().chain.best_number >= number.into(),
+		);
+	}
+
+	/// Assert that all full nodes and authorities agree on the hash of block `number`.
+	pub fn assert_same_block(&self, number: u32) {
+		let mut hashes = self.authority_nodes.iter().chain(self.full_nodes.iter())
+			.map(|(index, service, _)| (*index, service.get().client().block_hash(number.into())
+				.expect("Error reading block hash")));
+		if let Some((first, expected)) = hashes.next() {
+			for (index, hash) in hashes {
+				assert_eq!(hash, expected, "Node {} and node {} disagree on block #{}", index, first, number);
+			}
+		}
+	}
+
+	/// Run the network until the predicates hold for all authorities, full nodes and light
+	/// nodes.
+	pub fn run_until_all_nodes<FP, LP>(
+		&mut self,
+		full_predicate: FP,
+		light_predicate: LP,
+	)
+		where
+			FP: Send + Fn(usize, &SyncService<F::FullService>) -> bool + 'static,
+			LP: Send + Fn(usize, &SyncService<F::LightService>) -> bool + 'static,
+	{
+		let authority_nodes = self.authority_nodes.clone();
+		let full_nodes = self.full_nodes.clone();
+		let light_nodes = self.light_nodes.clone();
+		self.run_until(move || {
+			authority_nodes.iter().chain(full_nodes.iter())
+				.all(|&(ref id, ref service, _)| full_predicate(*id, service))
+			&& light_nodes.iter().all(|&(ref id, ref service, _)| light_predicate(*id, service))
+		});
+	}
+
+	/// Run the network until the predicates hold for all full nodes and light nodes.
+	///
+	/// Authorities are not checked.
 	pub fn run_until_all_full<FP, LP>(
 		&mut self,
 		full_predicate: FP,
@@ -94,27 +190,19 @@ impl<F: ServiceFactory> TestNet<F> {
 	{
 		let full_nodes = self.full_nodes.clone();
 		let light_nodes = self.light_nodes.clone();
+		self.run_until(move || {
+			full_nodes.iter().all(|&(ref id, ref service, _)| full_predicate(*id, service))
+			&& light_nodes.iter().all(|&(ref id, ref service, _)| light_predicate(*id, service))
+		});
+	}
+
+	/// Run the network until `ready` returns `true`, checking it every 100 ms.
+	///
+	/// Panics after `MAX_WAIT_TIME`.
+	pub fn run_until<R: Send + Fn() -> bool + 'static>(&mut self, ready: R) {
 		let interval = Interval::new_interval(Duration::from_millis(100))
 			.map_err(|_| ())
-			.for_each(move |_| {
-				let full_ready = full_nodes.iter().all(|&(ref id, ref service, _)|
-					full_predicate(*id, service)
-				);
-
-				if !full_ready {
-					return Ok(());
-				}
-
-				let light_ready = light_nodes.iter().all(|&(ref id, ref service, _)|
-					light_predicate(*id, service)
-				);
-
-				if !light_ready {
-					Ok(())
-				} else {
-					Err(())
-				}
-			})
+			.for_each(move |_| if ready() { Err(()) } else { Ok(()) })
 			.timeout(MAX_WAIT_TIME);
 
 		match self.runtime.block_on(interval) {
@@ -174,11 +262,14 @@ fn node_config<F: ServiceFactory> (
 		transaction_pool: Default::default(),
 		network: network_config,
 		keystore_path: Some(root.join("key")),
+		keystore_uri: None,
 		database_path: root.join("db"),
 		database_cache_size: None,
 		state_cache_size: 16777216,
 		state_cache_child_ratio: None,
+		trie_node_cache_size: 16777216,
 		pruning: Default::default(),
+		prune_headers: false,
 		keys: keys,
 		chain_spec: (*spec).clone(),
 		custom: Default::default(),
@@ -188,10 +279,18 @@ fn node_config<F: ServiceFactory> (
 		rpc_ws: None,
 		rpc_ws_max_connections: None,
 		rpc_cors: None,
+		rpc_limits: Default::default(),
+		rpc_auth_token: None,
+		rpc_trust_proxy: false,
+		prometheus_endpoint: None,
 		telemetry_endpoints: None,
 		telemetry_external_transport: None,
 		default_heap_pages: None,
+		max_memory_pages: None,
+		wasm_method: Default::default(),
 		offchain_worker: false,
+		offchain_worker_best_only: false,
+		offchain_worker_max_concurrency: None,
 		force_authoring: false,
 		disable_grandpa: false,
 		grandpa_voter: false,
@@ -203,7 +302,12 @@ impl<F: ServiceFactory> TestNet<F> where
 	F::FullService: Future<Item=(), Error=()>,
 	F::LightService: Future<Item=(), Error=()>
 {
-	fn new(
+	/// Start a network of `full` full nodes, `light` light nodes and one authority for each key
+	/// seed of `authorities`.
+	///
+	/// Node `i` listens on `127.0.0.1:{base_port + i}`. The nodes are not connected to each
+	/// other.
+	pub fn new(
 		temp: &TempDir,
 		spec: FactoryChainSpec<F>,
 		full: usize,
@@ -227,7 +331,8 @@ impl<F: ServiceFactory> TestNet<F> where
 		net
 	}
 
-	fn insert_nodes(&mut self, temp: &TempDir, full: usize, light: usize, authorities: Vec<String>) {
+	/// Start more nodes, numbered after the existing ones.
+	pub fn insert_nodes(&mut self, temp: &TempDir, full: usize, light: usize, authorities: Vec<String>) {
 		let mut nodes = self.nodes;
 		let base_port = self.base_port;
 		let spec = &self.chain_spec;
@@ -402,13 +507,59 @@ pub fn sync<F, B, E>(spec: FactoryChainSpec<F>, mut block_factory: B, mut extrin
 	let first_service = network.full_nodes[0].1.clone();
 	let best_block = BlockId::number(first_service.get().client().info().chain.best_number);
 	let extrinsic = extrinsic_factory(&first_service);
-	first_service.get().transaction_pool().submit_one(&best_block, extrinsic).unwrap();
+	first_service.get().transaction_pool().submit_one(&best_block, TransactionSource::External, extrinsic).unwrap();
 	network.run_until_all_full(
 		|_index, service| service.get().transaction_pool().ready().count() == 1,
 		|_index, _service| true,
 	);
 }
 
+/// Export the blocks built by `block_factory` from a node and import them into another one.
+pub fn export_import<F, B>(spec: FactoryChainSpec<F>, mut block_factory: B) where
+	F: ServiceFactory,
+	F::FullService: Future<Item=(), Error=()>,
+	B: FnMut(&SyncService<F::FullService>) -> BlockImportParams<F::Block>,
+{
+	const NUM_BLOCKS: u32 = 16;
+	let _ = env_logger::try_init();
+	let temp = TempDir::new("substrate-export-import-test").expect("Error creating test dir");
+	let config = |index| node_config::<F>(index, &spec, Roles::FULL, None, 30800, &temp);
+
+	info!("Generating blocks");
+	let best_hash = {
+		let service = SyncService::from(F::new_full(config(0)).expect("Error creating test node service"));
+		let mut client = service.get().client();
+		for _ in 0 .. NUM_BLOCKS {
+			let import_data = block_factory(&service);
+			client.import_block(import_data, HashMap::new()).expect("Error importing test block");
+		}
+		let best_hash = service.get().client().info().chain.best_hash;
+		best_hash
+	};
+
+	info!("Exporting blocks");
+	let mut blocks = Vec::new();
+	service::chain_ops::export_blocks::<F, _, _>(
+		config(0),
+		futures::future::empty(),
+		&mut blocks,
+		1.into(),
+		// Past the best block, which the export is clamped to.
+		Some((NUM_BLOCKS * 2).into()),
+		false,
+	).expect("Error exporting blocks");
+
+	info!("Importing blocks");
+	service::chain_ops::import_blocks::<F, _, _>(config(1), futures::future::empty(), Cursor::new(blocks))
+		.expect("Error starting the import of blocks")
+		.wait()
+		.expect("Error importing blocks");
+
+	let info = service::new_client::<F>(&config(1)).expect("Error opening the client").info().chain;
+	assert_eq!(info.best_number, NUM_BLOCKS.into());
+	assert_eq!(info.best_hash, best_hash);
+}
+
 pub fn consensus<F>(spec: FactoryChainSpec<F>, authorities: Vec<String>) where
 	F: ServiceFactory,
 	F::FullService: Future<Item=(), Error=()>,
@@ -428,35 +579,34 @@ pub fn consensus<F>(spec: FactoryChainSpec<F>, authorities: Vec<String>) where
 	);
 
 	info!("Checking consensus");
-	let first_address = network.authority_nodes[0].2.clone();
-	for (_, service, _) in network.full_nodes.iter() {
-		service.get().network().add_reserved_peer(first_address.to_string()).expect("Error adding reserved peer");
-	}
-	for (_, service, _) in network.light_nodes.iter() {
-		service.get().network().add_reserved_peer(first_address.to_string()).expect("Error adding reserved peer");
-	}
-	for (_, service, _) in network.authority_nodes.iter().skip(1) {
-		service.get().network().add_reserved_peer(first_address.to_string()).expect("Error adding reserved peer");
-	}
-	network.run_until_all_full(
-		|_index, service|
-			service.get().client().info().chain.finalized_number >= (NUM_BLOCKS as u32 / 2).into(),
-		|_index, service|
-			service.get().client().info().chain.best_number >= (NUM_BLOCKS as u32 / 2).into(),
-	);
+	network.connect_to_first();
+	network.wait_for_finalized(NUM_BLOCKS as u32 / 2);
 
 	info!("Adding more peers");
 	network.insert_nodes(&temp, NUM_FULL_NODES / 2, NUM_LIGHT_NODES / 2, vec![]);
-	for (_, service, _) in network.full_nodes.iter() {
-		service.get().network().add_reserved_peer(first_address.to_string()).expect("Error adding reserved peer");
-	}
-	for (_, service, _) in network.light_nodes.iter() {
-		service.get().network().add_reserved_peer(first_address.to_string()).expect("Error adding reserved peer");
-	}
-	network.run_until_all_full(
-		|_index, service|
-			service.get().client().info().chain.finalized_number >= (NUM_BLOCKS as u32).into(),
-		|_index, service|
-			service.get().client().info().chain.best_number >= (NUM_BLOCKS as u32).into(),
-	);
+	network.connect_to_first();
+	network.wait_for_finalized(NUM_BLOCKS as u32);
+	network.assert_same_block(NUM_BLOCKS as u32);
+}
+
+/// Start a full node of `spec` and return its response to the JSON-RPC `request`.
+pub fn rpc_query<F>(spec: FactoryChainSpec<F>, request: &str) -> Option<String> where
+	F: ServiceFactory,
+	F::FullService: Future<Item=(), Error=()>,
+	F::LightService: Future<Item=(), Error=()>,
+{
+	let temp = TempDir::new("substrate-rpc-test").expect("Error creating test dir");
+	let response = {
+		let network = TestNet::<F>::new(&temp, spec, 1, 0, vec![], 30700);
+		let (sender, _receiver) = futures::sync::mpsc::channel(0);
+		let session = service::RpcSession::new(sender);
+		let response = network.full_nodes[0].1.get()
+			.rpc_query(&session, request)
+			.wait()
+			.expect("RPC queries can't fail");
+		network.runtime.shutdown_now().wait().expect("Error shutting down runtime");
+		response
+	};
+	temp.close().expect("Error removing temp dir");
+	response
 }
@@ -33,6 +33,9 @@ use rstd::vec::Vec;
 pub use codec;
 
 pub use primitives::Blake2Hasher;
+pub use primitives::batch_verifier::SignatureKind;
+pub use primitives::sr25519::VrfSignature;
+pub use primitives::crypto::KeyTypeId;
 use primitives::offchain::{
 	Timestamp,
 	HttpRequestId, HttpRequestStatus, HttpError,
@@ -142,6 +145,16 @@ export_api! {
 		/// Clear the storage entries with a key that starts with the given prefix.
 		fn clear_prefix(prefix: &[u8]);
 
+		/// Write `value` under `key` to the offchain index.
+		///
+		/// The offchain index is written to the node's offchain database when the block is
+		/// imported, where offchain workers and RPCs can read it. It is not part of the state and
+		/// can't be read during block execution.
+		fn offchain_index_set(key: &[u8], value: &[u8]);
+
+		/// Remove `key` from the offchain index.
+		fn offchain_index_clear(key: &[u8]);
+
 		/// "Commit" all existing operations and compute the resultant storage root.
 		fn storage_root() -> [u8; 32];
 
@@ -195,6 +208,15 @@ export_api! {
 			T: Printable,
 			T: Sized
 		;
+
+		/// Enter a tracing span called `name`, nested in the span that is currently open.
+		///
+		/// The node records the wall time and the storage accesses of the span while it traces
+		/// the execution, e.g. for the `state_traceBlock` RPC. Otherwise this does nothing.
+		fn trace_enter_span(name: &str);
+
+		/// Exit the most recently entered tracing span.
+		fn trace_exit_span();
 	}
 }
 
@@ -206,6 +228,34 @@ export_api! {
 		/// Verify an sr25519 signature.
 		fn sr25519_verify<P: AsRef<[u8]>>(sig: &[u8; 64], msg: &[u8], pubkey: P) -> bool;
 
+		/// Verify the sr25519 VRF signature of `msg` in the given `context` by `pubkey`.
+		///
+		/// Returns the randomness derived from the VRF output if the signature is valid.
+		fn sr25519_vrf_verify<P: AsRef<[u8]>>(
+			context: &[u8],
+			msg: &[u8],
+			signature: &VrfSignature,
+			pubkey: P,
+		) -> Option<[u8; 32]>;
+
+		/// Start a batch of signature verifications.
+		///
+		/// Until the batch is finished, signatures passed to `batch_verify` are verified in the
+		/// background. Any batch that was started before and not finished is discarded.
+		fn start_batch_verify();
+
+		/// Verify a signature of the given kind as part of the current batch.
+		///
+		/// Within a batch the signature is queued and `true` is returned; whether it is valid
+		/// is reported by `finish_batch_verify`. Without a batch the signature is verified
+		/// right away.
+		fn batch_verify<P: AsRef<[u8]>>(kind: SignatureKind, sig: &[u8; 64], msg: &[u8], pubkey: P) -> bool;
+
+		/// Finish the current batch and return whether all signatures queued in it are valid.
+		///
+		/// Panics if no batch was started.
+		fn finish_batch_verify() -> bool;
+
 		/// Verify and recover a SECP256k1 ECDSA signature.
 		/// - `sig` is passed in RSV format. V should be either 0/1 or 27/28.
 		/// - returns `Err` if the signature is bad, otherwise the 64-byte pubkey (doesn't include the 0x04 prefix).
@@ -281,6 +331,22 @@ export_api! {
 		/// lengths are incorrect.
 		fn verify(key: CryptoKey, msg: &[u8], signature: &[u8]) -> Result<bool, ()>;
 
+		/// Sign `msg` in the given `context` with the VRF of the given sr25519 key.
+		///
+		/// Returns an error if `key` is not available or does not exist, or is not a local
+		/// sr25519 key.
+		fn vrf_sign(key: CryptoKey, context: &[u8], msg: &[u8]) -> Result<VrfSignature, ()>;
+
+		/// Returns the raw public keys of all keys of the given type held by the node's keystore.
+		///
+		/// Returns an error if no keystore is available.
+		fn keystore_public_keys(key_type: KeyTypeId) -> Result<Vec<Vec<u8>>, ()>;
+
+		/// Sign `data` with the key `public` of the given type held by the node's keystore.
+		///
+		/// Returns an error if no keystore is available or it doesn't hold the key.
+		fn keystore_sign(key_type: KeyTypeId, public: &[u8], data: &[u8]) -> Result<Vec<u8>, ()>;
+
 		/// Returns current UNIX timestamp (in millis)
 		fn timestamp() -> Timestamp;
 
@@ -28,7 +28,7 @@ pub use substrate_state_machine::{
 };
 
 use environmental::environmental;
-use primitives::{offchain, hexdisplay::HexDisplay, H256};
+use primitives::{offchain, hexdisplay::HexDisplay, H256, batch_verifier::BatchVerifier};
 use trie::{TrieConfiguration, trie_types::Layout};
 
 #[cfg(feature = "std")]
@@ -146,6 +146,18 @@ impl StorageApi for () {
 		);
 	}
 
+	fn offchain_index_set(key: &[u8], value: &[u8]) {
+		ext::with(|ext|
+			ext.set_offchain_index(key, Some(value))
+		);
+	}
+
+	fn offchain_index_clear(key: &[u8]) {
+		ext::with(|ext|
+			ext.set_offchain_index(key, None)
+		);
+	}
+
 	fn storage_root() -> [u8; 32] {
 		ext::with(|ext|
 			ext.storage_root()
@@ -205,6 +217,39 @@ impl OtherApi for () {
 	fn print<T: Printable + Sized>(value: T) {
 		value.print()
 	}
+
+	fn trace_enter_span(name: &str) {
+		primitives::tracing::enter_span(name)
+	}
+
+	fn trace_exit_span() {
+		primitives::tracing::exit_span()
+	}
+}
+
+thread_local! {
+	/// The signature verification batch of the runtime executing on this thread.
+	static BATCH_VERIFIER: std::cell::RefCell<Option<BatchVerifier>> = std::cell::RefCell::new(None);
+}
+
+/// Drops the signature verification batch of this thread when created and when dropped.
+///
+/// An execution that panics between `start_batch_verify` and `finish_batch_verify` would
+/// otherwise leave its batch behind, and the next execution on the thread would have its
+/// signatures queued into it instead of being checked.
+struct BatchVerifierGuard;
+
+impl BatchVerifierGuard {
+	fn new() -> Self {
+		BATCH_VERIFIER.with(|batch| *batch.borrow_mut() = None);
+		BatchVerifierGuard
+	}
+}
+
+impl Drop for BatchVerifierGuard {
+	fn drop(&mut self) {
+		BATCH_VERIFIER.with(|batch| *batch.borrow_mut() = None);
+	}
 }
 
 impl CryptoApi for () {
@@ -216,6 +261,43 @@ impl CryptoApi for () {
 		sr25519::Pair::verify_weak(sig, msg, pubkey)
 	}
 
+	fn sr25519_vrf_verify<P: AsRef<[u8]>>(
+		context: &[u8],
+		msg: &[u8],
+		signature: &VrfSignature,
+		pubkey: P,
+	) -> Option<[u8; 32]> {
+		sr25519::vrf_verify(pubkey.as_ref(), context, msg, signature)
+	}
+
+	fn start_batch_verify() {
+		BATCH_VERIFIER.with(|batch| *batch.borrow_mut() = Some(BatchVerifier::new()));
+	}
+
+	fn batch_verify<P: AsRef<[u8]>>(kind: SignatureKind, sig: &[u8; 64], msg: &[u8], pubkey: P) -> bool {
+		let pubkey = pubkey.as_ref();
+		BATCH_VERIFIER.with(|batch| match (batch.borrow_mut().as_mut(), pubkey.len()) {
+			(Some(batch), 32) => {
+				let mut public = [0u8; 32];
+				public.copy_from_slice(pubkey);
+				batch.push(kind, *sig, msg.to_vec(), public);
+				true
+			},
+			_ => match kind {
+				SignatureKind::Ed25519 => ed25519::Pair::verify_weak(sig, msg, pubkey),
+				SignatureKind::Sr25519 => sr25519::Pair::verify_weak(sig, msg, pubkey),
+				SignatureKind::Sr25519OrEd25519 => sr25519::Pair::verify_weak(sig, msg, pubkey)
+					|| ed25519::Pair::verify_weak(sig, msg, pubkey),
+			},
+		})
+	}
+
+	fn finish_batch_verify() -> bool {
+		BATCH_VERIFIER.with(|batch| batch.borrow_mut().take())
+			.expect("finish_batch_verify called without a batch being started")
+			.verify()
+	}
+
 	fn secp256k1_ecdsa_recover(sig: &[u8; 65], msg: &[u8; 32]) -> Result<[u8; 64], EcdsaVerifyError> {
 		let rs = secp256k1::Signature::parse_slice(&sig[0..64])
 			.map_err(|_| EcdsaVerifyError::BadRS)?;
@@ -325,6 +407,28 @@ impl OffchainApi for () {
 		}, "verify can be called only in the offchain worker context")
 	}
 
+	fn vrf_sign(
+		key: offchain::CryptoKey,
+		context: &[u8],
+		msg: &[u8],
+	) -> Result<VrfSignature, ()> {
+		with_offchain(|ext| {
+			ext.vrf_sign(key, context, msg)
+		}, "vrf_sign can be called only in the offchain worker context")
+	}
+
+	fn keystore_public_keys(key_type: KeyTypeId) -> Result<Vec<Vec<u8>>, ()> {
+		with_offchain(|ext| {
+			ext.keystore_public_keys(key_type)
+		}, "keystore_public_keys can be called only in the offchain worker context")
+	}
+
+	fn keystore_sign(key_type: KeyTypeId, public: &[u8], data: &[u8]) -> Result<Vec<u8>, ()> {
+		with_offchain(|ext| {
+			ext.keystore_sign(key_type, public, data)
+		}, "keystore_sign can be called only in the offchain worker context")
+	}
+
 	fn timestamp() -> offchain::Timestamp {
 		with_offchain(|ext| {
 			ext.timestamp()
@@ -430,6 +534,7 @@ impl Api for () {}
 /// externalities `ext`. Forwards the value that the closure returns.
 // NOTE: need a concrete hasher here due to limitations of the `environmental!` macro, otherwise a type param would have been fine I think.
 pub fn with_externalities<R, F: FnOnce() -> R>(ext: &mut dyn Externalities<Blake2Hasher>, f: F) -> R {
+	let _batch = BatchVerifierGuard::new();
 	ext::using(ext, f)
 }
 
@@ -445,7 +550,10 @@ pub fn with_storage<R, F: FnOnce() -> R>(storage: &mut StorageOverlay, f: F) ->
 	let mut alt_storage = Default::default();
 	rstd::mem::swap(&mut alt_storage, storage);
 	let mut ext = BasicExternalities::new(alt_storage);
-	let r = ext::using(&mut ext, f);
+	let r = {
+		let _batch = BatchVerifierGuard::new();
+		ext::using(&mut ext, f)
+	};
 	*storage = ext.into_storages().0;
 	r
 }
@@ -464,7 +572,10 @@ pub fn with_storage_and_children<R, F: FnOnce() -> R>(
 	rstd::mem::swap(&mut alt_children_storage, children_storage);
 
 	let mut ext = BasicExternalities::new_with_children(alt_storage, alt_children_storage);
-	let r = ext::using(&mut ext, f);
+	let r = {
+		let _batch = BatchVerifierGuard::new();
+		ext::using(&mut ext, f)
+	};
 
 	let storage_tuple = ext.into_storages();
 	*storage = storage_tuple.0;
@@ -551,4 +662,23 @@ mod std_tests {
 			assert!(storage(b":abc").is_none());
 		});
 	}
+
+	#[test]
+	fn batch_of_a_panicking_execution_is_dropped() {
+		let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+		let bad_signature = pair.sign(b"other message");
+		let mut t = BasicExternalities::default();
+
+		let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+			with_externalities(&mut t, || {
+				start_batch_verify();
+				panic!("execution failed in the middle of a batch");
+			})
+		}));
+		assert!(result.is_err());
+
+		with_externalities(&mut t, || {
+			assert!(!batch_verify(SignatureKind::Ed25519, &bad_signature.0, b"message", pair.public()));
+		});
+	}
 }
@@ -212,6 +212,10 @@ pub mod ext {
 		fn ext_exists_storage(key_data: *const u8, key_len: u32) -> u32;
 		/// Remove storage entries which key starts with given prefix.
 		fn ext_clear_prefix(prefix_data: *const u8, prefix_len: u32);
+		/// Set value for key in the offchain index.
+		fn ext_offchain_index_set(key_data: *const u8, key_len: u32, value_data: *const u8, value_len: u32);
+		/// Remove key and value from the offchain index.
+		fn ext_offchain_index_clear(key_data: *const u8, key_len: u32);
 		/// Gets the value of the given key from storage.
 		///
 		/// The host allocates the memory for storing the value.
@@ -333,6 +337,11 @@ pub mod ext {
 		/// The current relay chain identifier.
 		fn ext_chain_id() -> u64;
 
+		/// Enter a tracing span, given its utf8 encoded name.
+		fn ext_trace_enter_span(name_data: *const u8, name_len: u32);
+		/// Exit the most recently entered tracing span.
+		fn ext_trace_exit_span();
+
 		/// Calculate a blake2_256 merkle trie root.
 		fn ext_blake2_256_enumerated_trie_root(
 			values_data: *const u8,
@@ -366,6 +375,30 @@ pub mod ext {
 			sig_data: *const u8,
 			pubkey_data: *const u8
 		) -> u32;
+		/// Note: ext_sr25519_vrf_verify returns 0 and writes the 32 bytes of randomness derived
+		/// from the VRF output to `randomness_out` if the signature is correct, nonzero otherwise.
+		fn ext_sr25519_vrf_verify(
+			context_data: *const u8,
+			context_len: u32,
+			msg_data: *const u8,
+			msg_len: u32,
+			sig_data: *const u8,
+			pubkey_data: *const u8,
+			randomness_out: *mut u8
+		) -> u32;
+		/// Start a batch of signature verifications, discarding any unfinished one.
+		fn ext_start_batch_verify();
+		/// Note: ext_batch_verify returns 0 if the signature was queued or is correct, nonzero otherwise.
+		fn ext_batch_verify(
+			kind: u32,
+			msg_data: *const u8,
+			msg_len: u32,
+			sig_data: *const u8,
+			pubkey_data: *const u8
+		) -> u32;
+		/// Note: ext_finish_batch_verify returns 0 if all signatures of the batch are correct,
+		/// nonzero otherwise.
+		fn ext_finish_batch_verify() -> u32;
 		/// Note: ext_secp256k1_ecdsa_recover returns 0 if the signature is correct, nonzero otherwise.
 		fn ext_secp256k1_ecdsa_recover(
 			msg_data: *const u8,
@@ -486,6 +519,48 @@ pub mod ext {
 			signature_len: u32
 		) -> u32;
 
+		/// Sign with the VRF of a local sr25519 key.
+		///
+		/// Writes the encoded 96 bytes VRF signature to `sig_out` and returns:
+		/// - `0` in case the message was signed
+		/// - `u32::max_value` if the key is invalid.
+		fn ext_vrf_sign(
+			key: u64,
+			context_data: *const u8,
+			context_len: u32,
+			msg_data: *const u8,
+			msg_len: u32,
+			sig_out: *mut u8
+		) -> u32;
+
+		/// Get the raw public keys of all keys of the given type held by the keystore.
+		///
+		/// The encoded `Result<Vec<Vec<u8>>, ()>`.
+		/// `written_out` contains the length of the message.
+		///
+		/// The ownership of the returned buffer is transferred to the runtime
+		/// code and the runtime is responsible for freeing it. This is always
+		/// a properly allocated pointer (which cannot be NULL), hence the
+		/// runtime code can always rely on it.
+		fn ext_keystore_public_keys(key_type: u32, written_out: *mut u32) -> *mut u8;
+
+		/// Sign a piece of data with the key `public` of the given type held by the keystore.
+		///
+		/// # Returns
+		///
+		/// - `0` in case the key is not available,
+		/// `sig_data_len` is set to `u32::max_value`
+		/// - Otherwise, pointer to the signature in memory,
+		///	`sig_data_len` contains the length of the signature.
+		fn ext_keystore_sign(
+			key_type: u32,
+			public: *const u8,
+			public_len: u32,
+			data: *const u8,
+			data_len: u32,
+			sig_data_len: *mut u32
+		) -> *mut u8;
+
 		/// Returns current UNIX timestamp (milliseconds)
 		fn ext_timestamp() -> u64;
 
@@ -743,6 +818,26 @@ impl StorageApi for () {
 		}
 	}
 
+	fn offchain_index_set(key: &[u8], value: &[u8]) {
+		unsafe {
+			ext_offchain_index_set.get()(
+				key.as_ptr(),
+				key.len() as u32,
+				value.as_ptr(),
+				value.len() as u32
+			);
+		}
+	}
+
+	fn offchain_index_clear(key: &[u8]) {
+		unsafe {
+			ext_offchain_index_clear.get()(
+				key.as_ptr(),
+				key.len() as u32
+			);
+		}
+	}
+
 	fn kill_child_storage(storage_key: &[u8]) {
 		unsafe {
 			ext_kill_child_storage.get()(
@@ -818,6 +913,17 @@ impl OtherApi for () {
 		value.print()
 	}
 
+	fn trace_enter_span(name: &str) {
+		unsafe {
+			ext_trace_enter_span.get()(name.as_ptr(), name.len() as u32);
+		}
+	}
+
+	fn trace_exit_span() {
+		unsafe {
+			ext_trace_exit_span.get()();
+		}
+	}
 }
 
 impl HashingApi for () {
@@ -883,6 +989,56 @@ impl CryptoApi for () {
 		}
 	}
 
+	fn sr25519_vrf_verify<P: AsRef<[u8]>>(
+		context: &[u8],
+		msg: &[u8],
+		signature: &VrfSignature,
+		pubkey: P,
+	) -> Option<[u8; 32]> {
+		let signature = codec::Encode::encode(signature);
+		let mut randomness = [0u8; 32];
+		let result = unsafe {
+			ext_sr25519_vrf_verify.get()(
+				context.as_ptr(),
+				context.len() as u32,
+				msg.as_ptr(),
+				msg.len() as u32,
+				signature.as_ptr(),
+				pubkey.as_ref().as_ptr(),
+				randomness.as_mut_ptr(),
+			)
+		};
+		if result == 0 {
+			Some(randomness)
+		} else {
+			None
+		}
+	}
+
+	fn start_batch_verify() {
+		unsafe {
+			ext_start_batch_verify.get()();
+		}
+	}
+
+	fn batch_verify<P: AsRef<[u8]>>(kind: SignatureKind, sig: &[u8; 64], msg: &[u8], pubkey: P) -> bool {
+		unsafe {
+			ext_batch_verify.get()(
+				kind.into(),
+				msg.as_ptr(),
+				msg.len() as u32,
+				sig.as_ptr(),
+				pubkey.as_ref().as_ptr(),
+			) == 0
+		}
+	}
+
+	fn finish_batch_verify() -> bool {
+		unsafe {
+			ext_finish_batch_verify.get()() == 0
+		}
+	}
+
 	fn secp256k1_ecdsa_recover(sig: &[u8; 65], msg: &[u8; 32]) -> Result<[u8; 64], EcdsaVerifyError> {
 		let mut pubkey = [0u8; 64];
 		match unsafe {
@@ -1023,6 +1179,62 @@ impl OffchainApi for () {
 		}
 	}
 
+	fn vrf_sign(
+		key: offchain::CryptoKey,
+		context: &[u8],
+		msg: &[u8],
+	) -> Result<VrfSignature, ()> {
+		let mut signature = [0u8; 96];
+		let val = unsafe {
+			ext_vrf_sign.get()(
+				key.into(),
+				context.as_ptr(),
+				context.len() as u32,
+				msg.as_ptr(),
+				msg.len() as u32,
+				signature.as_mut_ptr(),
+			)
+		};
+
+		match val {
+			0 => codec::Decode::decode(&mut &signature[..]).map_err(|_| ()),
+			_ => Err(()),
+		}
+	}
+
+	fn keystore_public_keys(key_type: KeyTypeId) -> Result<Vec<Vec<u8>>, ()> {
+		let mut len = 0u32;
+		let raw_result = unsafe {
+			let ptr = ext_keystore_public_keys.get()(
+				key_type,
+				&mut len,
+			);
+
+			from_raw_parts(ptr, len)
+		};
+
+		match raw_result {
+			Some(raw_result) => codec::Decode::decode(&mut &*raw_result).unwrap_or(Err(())),
+			None => Err(())
+		}
+	}
+
+	fn keystore_sign(key_type: KeyTypeId, public: &[u8], data: &[u8]) -> Result<Vec<u8>, ()> {
+		let mut len = 0_u32;
+		unsafe {
+			let ptr = ext_keystore_sign.get()(
+				key_type,
+				public.as_ptr(),
+				public.len() as u32,
+				data.as_ptr(),
+				data.len() as u32,
+				&mut len
+			);
+
+			from_raw_parts(ptr, len).ok_or(())
+		}
+	}
+
 	fn timestamp() -> offchain::Timestamp {
 		offchain::Timestamp::from_unix_millis(unsafe {
 			ext_timestamp.get()()
@@ -0,0 +1,163 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Generators of arbitrary runtime primitives, for property-based tests with `quickcheck`.
+//!
+//! Implements `Arbitrary` for the generic types of this crate, so that properties can be checked
+//! against eras, digests, headers and extrinsics of any shape. Hashes are filled with random
+//! bytes; use `arbitrary_hash` for the hash types of other crates, which can't implement
+//! `Arbitrary` here.
+//!
+//! Only available with the `arbitrary` feature.
+
+use std::fmt::Debug;
+
+use quickcheck::{Arbitrary, Gen, QuickCheck};
+use codec::{Encode, Decode};
+use crate::generic::{Era, Digest, DigestItem, Header, UncheckedExtrinsic};
+use crate::testing::UintAuthorityId;
+use crate::traits::{Hash as HashT, SignedExtension};
+use crate::ConsensusEngineId;
+
+/// A hash, or any other fixed size byte array, filled with random bytes.
+pub fn arbitrary_hash<G: Gen, H: Default + AsMut<[u8]>>(g: &mut G) -> H {
+	let mut hash = H::default();
+	g.fill_bytes(hash.as_mut());
+	hash
+}
+
+/// Check that any value of `T` is decoded back from its encoding, and nothing more.
+///
+/// Panics with the first counterexample found.
+pub fn check_encode_roundtrip<T: Arbitrary + Encode + Decode + PartialEq + Debug>() {
+	fn roundtrip<T: Encode + Decode + PartialEq>(value: T) -> bool {
+		let encoded = value.encode();
+		let mut input = &encoded[..];
+		T::decode(&mut input).ok() == Some(value) && input.is_empty()
+	}
+
+	QuickCheck::new().quickcheck(roundtrip::<T> as fn(T) -> bool);
+}
+
+fn arbitrary_engine_id<G: Gen>(g: &mut G) -> ConsensusEngineId {
+	arbitrary_hash(g)
+}
+
+impl Arbitrary for Era {
+	fn arbitrary<G: Gen>(g: &mut G) -> Self {
+		if bool::arbitrary(g) {
+			Era::immortal()
+		} else {
+			Era::mortal(u64::arbitrary(g), u64::arbitrary(g))
+		}
+	}
+}
+
+impl<Hash: Arbitrary> Arbitrary for DigestItem<Hash> {
+	fn arbitrary<G: Gen>(g: &mut G) -> Self {
+		match u8::arbitrary(g) % 5 {
+			0 => DigestItem::ChangesTrieRoot(Hash::arbitrary(g)),
+			1 => DigestItem::PreRuntime(arbitrary_engine_id(g), Vec::arbitrary(g)),
+			2 => DigestItem::Consensus(arbitrary_engine_id(g), Vec::arbitrary(g)),
+			3 => DigestItem::Seal(arbitrary_engine_id(g), Vec::arbitrary(g)),
+			_ => DigestItem::Other(Vec::arbitrary(g)),
+		}
+	}
+}
+
+impl<Hash: Arbitrary + Encode + Decode> Arbitrary for Digest<Hash> {
+	fn arbitrary<G: Gen>(g: &mut G) -> Self {
+		Digest { logs: Vec::arbitrary(g) }
+	}
+
+	fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+		Box::new(self.logs.shrink().map(|logs| Digest { logs }))
+	}
+}
+
+impl<Number, Hash> Arbitrary for Header<Number, Hash> where
+	Number: Arbitrary + Copy + Into<u128>,
+	Hash: HashT,
+{
+	fn arbitrary<G: Gen>(g: &mut G) -> Self {
+		let logs = (0..u8::arbitrary(g) % 4)
+			.map(|_| match u8::arbitrary(g) % 4 {
+				0 => DigestItem::ChangesTrieRoot(arbitrary_hash(g)),
+				1 => DigestItem::PreRuntime(arbitrary_engine_id(g), Vec::arbitrary(g)),
+				2 => DigestItem::Seal(arbitrary_engine_id(g), Vec::arbitrary(g)),
+				_ => DigestItem::Other(Vec::arbitrary(g)),
+			})
+			.collect();
+		Header {
+			parent_hash: arbitrary_hash(g),
+			number: Number::arbitrary(g),
+			state_root: arbitrary_hash(g),
+			extrinsics_root: arbitrary_hash(g),
+			digest: Digest { logs },
+		}
+	}
+}
+
+impl<Address, Call, Signature, Extra> Arbitrary for UncheckedExtrinsic<Address, Call, Signature, Extra> where
+	Address: Arbitrary,
+	Call: Arbitrary,
+	Signature: Arbitrary,
+	Extra: SignedExtension + Arbitrary,
+{
+	fn arbitrary<G: Gen>(g: &mut G) -> Self {
+		UncheckedExtrinsic {
+			signature: Option::arbitrary(g),
+			function: Call::arbitrary(g),
+		}
+	}
+
+	fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+		let signature = self.signature.clone();
+		Box::new(
+			self.function.shrink().map(move |function| UncheckedExtrinsic {
+				signature: signature.clone(),
+				function,
+			})
+		)
+	}
+}
+
+impl Arbitrary for UintAuthorityId {
+	fn arbitrary<G: Gen>(g: &mut G) -> Self {
+		UintAuthorityId(u64::arbitrary(g))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use primitives::H256;
+	use crate::traits::BlakeTwo256;
+
+	#[test]
+	fn generic_types_roundtrip() {
+		check_encode_roundtrip::<Era>();
+		check_encode_roundtrip::<Header<u64, BlakeTwo256>>();
+		check_encode_roundtrip::<UncheckedExtrinsic<u64, Vec<u8>, primitives::sr25519::Signature, ()>>();
+		check_encode_roundtrip::<Digest<UintAuthorityId>>();
+	}
+
+	#[test]
+	fn hashes_are_filled() {
+		let hash: H256 = arbitrary_hash(&mut quickcheck::StdThreadGen::new(32));
+		assert_ne!(hash, H256::zero());
+	}
+}
@@ -23,7 +23,7 @@ use crate::traits::{
 	ValidateUnsigned
 };
 use crate::weights::{GetDispatchInfo, DispatchInfo};
-use crate::transaction_validity::TransactionValidity;
+use crate::transaction_validity::{TransactionValidity, TransactionSource};
 
 /// Definition of something that the external world might want to say; its
 /// existence implies that it has been checked and is good, particularly with
@@ -56,7 +56,12 @@ where
 		self.signed.as_ref().map(|x| &x.0)
 	}
 
+	fn call(&self) -> &Self::Call {
+		&self.function
+	}
+
 	fn validate<U: ValidateUnsigned<Call=Self::Call>>(&self,
+		source: TransactionSource,
 		info: DispatchInfo,
 		len: usize,
 	) -> TransactionValidity {
@@ -64,7 +69,7 @@ where
 			Extra::validate(extra, id, info, len).into()
 		} else {
 			match Extra::validate_unsigned(info, len) {
-				Ok(extra) => match U::validate_unsigned(&self.function) {
+				Ok(extra) => match U::validate_unsigned(source, &self.function) {
 					TransactionValidity::Valid(v) =>
 						TransactionValidity::Valid(v.combine_with(extra)),
 					x => x,
@@ -78,14 +83,16 @@ where
 		info: DispatchInfo,
 		len: usize,
 	) -> Result<DispatchResult, DispatchError> {
-		let maybe_who = if let Some((id, extra)) = self.signed {
-			Extra::pre_dispatch(extra, &id, info, len)?;
-			Some(id)
+		let (maybe_who, pre) = if let Some((id, extra)) = self.signed {
+			let pre = Extra::pre_dispatch(extra, &id, info, len)?;
+			(Some(id), pre)
 		} else {
-			Extra::pre_dispatch_unsigned(info, len)?;
-			None
+			let pre = Extra::pre_dispatch_unsigned(info, len)?;
+			(None, pre)
 		};
-		Ok(self.function.dispatch(Origin::from(maybe_who)))
+		let (res, post_info) = self.function.dispatch_with_post_info(Origin::from(maybe_who));
+		Extra::post_dispatch(pre, info, post_info, len);
+		Ok(res)
 	}
 }
 
@@ -27,6 +27,12 @@ pub type Period = u64;
 /// Era phase
 pub type Phase = u64;
 
+/// The shortest period a mortal era can have.
+const MIN_PERIOD: Period = 4;
+
+/// The longest period a mortal era can be encoded with.
+const MAX_PERIOD: Period = 1 << 16;
+
 /// An era to describe the longevity of a transaction.
 #[derive(PartialEq, Eq, Clone, Copy)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
@@ -56,10 +62,22 @@ impl Era {
 	/// Create a new era based on a period (which should be a power of two between 4 and 65536 inclusive)
 	/// and a block number on which it should start (or, for long periods, be shortly after the start).
 	pub fn mortal(period: u64, current: u64) -> Self {
+		Self::mortal_with_max_period(period, current, MAX_PERIOD)
+	}
+
+	/// Create a new era like `mortal`, but never longer than `max_period`.
+	///
+	/// `max_period` is rounded down to a power of two and should usually be the number of block
+	/// hashes the chain retains (`BlockHashCount`), since a transaction can only be checked for as
+	/// long as the hash of its birth block is known. The resulting period is still at least
+	/// 4 and at most 65536 blocks.
+	pub fn mortal_with_max_period(period: u64, current: u64, max_period: u64) -> Self {
+		let max_period = 1u64 << (63 - max_period.max(1).leading_zeros());
 		let period = period.checked_next_power_of_two()
-			.unwrap_or(1 << 16)
-			.max(4)
-			.min(1 << 16);
+			.unwrap_or(MAX_PERIOD)
+			.min(max_period)
+			.max(MIN_PERIOD)
+			.min(MAX_PERIOD);
 		let phase = current % period;
 		let quantize_factor = (period >> 12).max(1);
 		let quantized_phase = phase / quantize_factor * quantize_factor;
@@ -80,6 +98,14 @@ impl Era {
 		}
 	}
 
+	/// The period of this era, or `None` if it is immortal.
+	pub fn period(&self) -> Option<Period> {
+		match self {
+			Era::Immortal => None,
+			Era::Mortal(period, _) => Some(*period),
+		}
+	}
+
 	/// Get the block number of the start of the era whose properties this object
 	/// describes that `current` belongs to.
 	pub fn birth(self, current: u64) -> u64 {
@@ -123,7 +149,7 @@ impl Decode for Era {
 			let period = 2 << (encoded % (1 << 4));
 			let quantize_factor = (period >> 12).max(1);
 			let phase = (encoded >> 4) * quantize_factor;
-			if period >= 4 && phase < period {
+			if period >= MIN_PERIOD && phase < period {
 				Ok(Era::Mortal(period, phase))
 			} else {
 				Err("Invalid period and phase".into())
@@ -185,6 +211,18 @@ mod tests {
 		assert_eq!(Era::mortal(1000000, 1000001), Era::Mortal(65536, 1000001 % 65536 / 4 * 4));
 	}
 
+	#[test]
+	fn max_period_clamps_era_initialization() {
+		assert_eq!(Era::mortal_with_max_period(64, 42, 2400), Era::Mortal(64, 42));
+		// rounded down to the largest power of two not exceeding the maximum.
+		assert_eq!(Era::mortal_with_max_period(4096, 42, 2400), Era::Mortal(2048, 42));
+		assert_eq!(Era::mortal_with_max_period(4096, 42, 1024).period(), Some(1024));
+		// never shorter than the minimum or longer than what can be encoded.
+		assert_eq!(Era::mortal_with_max_period(64, 42, 0).period(), Some(MIN_PERIOD));
+		assert_eq!(Era::mortal_with_max_period(1 << 20, 42, u64::max_value()).period(), Some(MAX_PERIOD));
+		assert_eq!(Era::immortal().period(), None);
+	}
+
 	#[test]
 	fn mortal_birth_death_works() {
 		let e = Era::mortal(4, 6);
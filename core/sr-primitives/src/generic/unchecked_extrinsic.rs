@@ -21,8 +21,9 @@ use std::fmt;
 
 use rstd::prelude::*;
 use runtime_io::blake2_256;
-use crate::codec::{Decode, Encode, Input, Error};
+use crate::codec::{Compact, Decode, Encode, Input, Error};
 use crate::traits::{self, Member, MaybeDisplay, SignedExtension, Checkable, Extrinsic};
+use crate::weights::{GetDispatchInfo, DispatchInfo};
 use super::CheckedExtrinsic;
 
 const TRANSACTION_VERSION: u8 = 3;
@@ -81,6 +82,17 @@ impl<Address, Call, Signature, Extra: SignedExtension> Extrinsic
 	}
 }
 
+impl<Address, Call, Signature, Extra> GetDispatchInfo
+	for UncheckedExtrinsic<Address, Call, Signature, Extra>
+where
+	Call: GetDispatchInfo,
+	Extra: SignedExtension,
+{
+	fn get_dispatch_info(&self) -> DispatchInfo {
+		self.function.get_dispatch_info()
+	}
+}
+
 impl<Address, AccountId, Call, Signature, Extra, Lookup>
 	Checkable<Lookup>
 for
@@ -103,9 +115,9 @@ where
 				let signed = lookup.lookup(signed)?;
 				if !raw_payload.using_encoded(|payload| {
 					if payload.len() > 256 {
-						signature.verify(&blake2_256(payload)[..], &signed)
+						signature.batch_verify(&blake2_256(payload)[..], &signed)
 					} else {
-						signature.verify(payload, &signed)
+						signature.batch_verify(payload, &signed)
 					}
 				}) {
 					return Err(crate::BAD_SIGNATURE)
@@ -135,8 +147,9 @@ where
 		// This is a little more complicated than usual since the binary format must be compatible
 		// with substrate's generic `Vec<u8>` type. Basically this just means accepting that there
 		// will be a prefix of vector length (we don't need
-		// to use this).
-		let _length_do_not_remove_me_see_above: Vec<()> = Decode::decode(input)?;
+		// to use this). It is decoded as a compact length rather than a `Vec<()>`, which would
+		// spin over every element of a bogus length.
+		let _length_do_not_remove_me_see_above: Compact<u32> = Decode::decode(input)?;
 
 		let version = input.read_byte()?;
 
@@ -235,6 +248,7 @@ mod tests {
 	impl SignedExtension for TestExtra {
 		type AccountId = u64;
 		type AdditionalSigned = ();
+		type Pre = ();
 		fn additional_signed(&self) -> rstd::result::Result<(), &'static str> { Ok(()) }
 	}
 
@@ -316,4 +330,11 @@ mod tests {
 		let as_vec: Vec<u8> = Decode::decode(&mut encoded.as_slice()).unwrap();
 		assert_eq!(as_vec.encode(), encoded);
 	}
+
+	#[test]
+	fn bogus_length_prefix_is_rejected() {
+		// The largest compact `u32` followed by nothing.
+		let encoded = [0b11u8, 0xff, 0xff, 0xff, 0xff];
+		assert!(Ex::decode(&mut &encoded[..]).is_err());
+	}
 }
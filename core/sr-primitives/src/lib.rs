@@ -35,7 +35,7 @@ pub use paste;
 pub use runtime_io::{StorageOverlay, ChildrenStorageOverlay};
 
 use rstd::{prelude::*, ops, convert::TryInto};
-use primitives::{crypto, ed25519, sr25519, hash::{H256, H512}};
+use primitives::{crypto, ed25519, sr25519, ecdsa, hash::{H160, H256, H512}};
 use codec::{Encode, Decode};
 
 #[cfg(feature = "std")]
@@ -47,6 +47,8 @@ use traits::{SaturatedConversion, UniqueSaturatedInto, Saturating, Bounded, Chec
 
 pub mod generic;
 pub mod transaction_validity;
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
 
 /// Re-export these since they're only "kind of" generic.
 pub use generic::{DigestItem, Digest};
@@ -57,6 +59,15 @@ pub use primitives::crypto::{key_types, KeyTypeId};
 /// A message indicating an invalid signature in extrinsic.
 pub const BAD_SIGNATURE: &str = "bad signature in extrinsic";
 
+/// A message indicating that the hash of a mortal transaction's birth block is no longer known.
+///
+/// The transaction was either signed against a block that has since been pruned from the block
+/// hash retention window or its era has already ended.
+pub const ANCIENT_BIRTH_BLOCK: &str = "transaction birth block ancient";
+
+/// A message indicating that a mortal transaction's era is longer than the chain is able to check.
+pub const ERA_PERIOD_TOO_LONG: &str = "transaction era period exceeds block hash retention";
+
 /// Full block error message.
 ///
 /// This allows modules to indicate that given transaction is potentially valid
@@ -133,6 +144,25 @@ pub trait BuildModuleGenesisStorage<T, I>: Sized {
 	) -> Result<(), String>;
 }
 
+/// Build the genesis storage of the module `name` with `build`.
+///
+/// Used by `impl_outer_config!` to tell which module an invalid genesis config belongs to, the
+/// panics of the genesis builder are returned as errors.
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub fn build_module_genesis_storage(
+	name: &str,
+	build: impl FnOnce() -> Result<(), String>,
+) -> Result<(), String> {
+	std::panic::catch_unwind(std::panic::AssertUnwindSafe(build))
+		.unwrap_or_else(|panic| Err(
+			panic.downcast_ref::<&str>().map(|s| s.to_string())
+				.or_else(|| panic.downcast_ref::<String>().cloned())
+				.unwrap_or_else(|| "unknown error".into())
+		))
+		.map_err(|e| format!("{}: {}", name, e))
+}
+
 #[cfg(feature = "std")]
 impl BuildStorage for StorageOverlay {
 	fn build_storage(self) -> Result<(StorageOverlay, ChildrenStorageOverlay), String> {
@@ -566,6 +596,8 @@ pub enum MultiSignature {
 	Ed25519(ed25519::Signature),
 	/// An Sr25519 signature.
 	Sr25519(sr25519::Signature),
+	/// An ECDSA (secp256k1) signature.
+	Ecdsa(ecdsa::Signature),
 }
 
 impl From<ed25519::Signature> for MultiSignature {
@@ -580,6 +612,12 @@ impl From<sr25519::Signature> for MultiSignature {
 	}
 }
 
+impl From<ecdsa::Signature> for MultiSignature {
+	fn from(x: ecdsa::Signature) -> Self {
+		MultiSignature::Ecdsa(x)
+	}
+}
+
 impl Default for MultiSignature {
 	fn default() -> Self {
 		MultiSignature::Ed25519(Default::default())
@@ -594,6 +632,8 @@ pub enum MultiSigner {
 	Ed25519(ed25519::Public),
 	/// An Sr25519 identity.
 	Sr25519(sr25519::Public),
+	/// An ECDSA (secp256k1) identity.
+	Ecdsa(ecdsa::Public),
 }
 
 impl Default for MultiSigner {
@@ -615,6 +655,28 @@ impl AsRef<[u8]> for MultiSigner {
 		match *self {
 			MultiSigner::Ed25519(ref who) => who.as_ref(),
 			MultiSigner::Sr25519(ref who) => who.as_ref(),
+			MultiSigner::Ecdsa(ref who) => who.as_ref(),
+		}
+	}
+}
+
+impl MultiSigner {
+	/// A 256-bit identifier of this signer, derived by hashing its public key with Blake2-256.
+	///
+	/// Unlike the signer itself this has the same size for every key type.
+	pub fn to_account_hash(&self) -> H256 {
+		runtime_io::blake2_256(self.as_ref()).into()
+	}
+
+	/// The Ethereum address of an ECDSA signer: the last 20 bytes of the Keccak-256 hash of its
+	/// uncompressed public key. Returns `None` for other key types.
+	pub fn to_eth_address(&self) -> Option<H160> {
+		match *self {
+			MultiSigner::Ecdsa(ref who) => {
+				let hash = runtime_io::keccak_256(who.as_ref());
+				Some(H160::from_slice(&hash[12..]))
+			}
+			_ => None,
 		}
 	}
 }
@@ -631,12 +693,19 @@ impl From<sr25519::Public> for MultiSigner {
 	}
 }
 
+impl From<ecdsa::Public> for MultiSigner {
+	fn from(x: ecdsa::Public) -> Self {
+		MultiSigner::Ecdsa(x)
+	}
+}
+
  #[cfg(feature = "std")]
 impl std::fmt::Display for MultiSigner {
 	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
 		match *self {
 			MultiSigner::Ed25519(ref who) => write!(fmt, "ed25519: {}", who),
 			MultiSigner::Sr25519(ref who) => write!(fmt, "sr25519: {}", who),
+			MultiSigner::Ecdsa(ref who) => write!(fmt, "ecdsa: {}", who),
 		}
 	}
 }
@@ -647,6 +716,16 @@ impl Verify for MultiSignature {
 		match (self, signer) {
 			(MultiSignature::Ed25519(ref sig), &MultiSigner::Ed25519(ref who)) => sig.verify(msg, who),
 			(MultiSignature::Sr25519(ref sig), &MultiSigner::Sr25519(ref who)) => sig.verify(msg, who),
+			(MultiSignature::Ecdsa(ref sig), &MultiSigner::Ecdsa(ref who)) => sig.verify(msg, who),
+			_ => false,
+		}
+	}
+
+	fn batch_verify<L: Lazy<[u8]>>(&self, msg: L, signer: &Self::Signer) -> bool {
+		match (self, signer) {
+			(MultiSignature::Ed25519(ref sig), &MultiSigner::Ed25519(ref who)) => sig.batch_verify(msg, who),
+			(MultiSignature::Sr25519(ref sig), &MultiSigner::Sr25519(ref who)) => sig.batch_verify(msg, who),
+			(MultiSignature::Ecdsa(ref sig), &MultiSigner::Ecdsa(ref who)) => sig.verify(msg, who),
 			_ => false,
 		}
 	}
@@ -663,6 +742,15 @@ impl Verify for AnySignature {
 		runtime_io::sr25519_verify(self.0.as_fixed_bytes(), msg.get(), &signer.0) ||
 			runtime_io::ed25519_verify(self.0.as_fixed_bytes(), msg.get(), &signer.0)
 	}
+
+	fn batch_verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &sr25519::Public) -> bool {
+		runtime_io::batch_verify(
+			runtime_io::SignatureKind::Sr25519OrEd25519,
+			self.0.as_fixed_bytes(),
+			msg.get(),
+			&signer.0,
+		)
+	}
 }
 
 impl From<sr25519::Signature> for AnySignature {
@@ -709,6 +797,12 @@ pub enum ApplyError {
 	Future = 2,
 	/// Sending account had too low a balance.
 	CantPay = 3,
+	/// The call is not allowed by the runtime's base call filter.
+	CallFiltered = 4,
+	/// The hash of the transaction's birth block has been pruned.
+	AncientBirthBlock = 5,
+	/// The transaction's era period exceeds the block hash retention window.
+	EraPeriodTooLong = 6,
 	/// Block is full, no more extrinsics can be applied.
 	FullBlock = 255,
 }
@@ -842,10 +936,13 @@ macro_rules! impl_outer_config {
 		$top:ident;
 		$children:ident;
 	) => {
-		$crate::BuildModuleGenesisStorage::<$runtime, $module::$instance>::build_module_genesis_storage(
-			$extra,
-			$top,
-			$children,
+		$crate::build_module_genesis_storage(
+			concat!(stringify!($module), "_", stringify!($instance)),
+			|| $crate::BuildModuleGenesisStorage::<$runtime, $module::$instance>::build_module_genesis_storage(
+				$extra,
+				$top,
+				$children,
+			),
 		)?;
 	};
 	(@CALL_FN
@@ -856,10 +953,13 @@ macro_rules! impl_outer_config {
 		$top:ident;
 		$children:ident;
 	) => {
-		$crate::BuildModuleGenesisStorage::<$runtime, $module::__InherentHiddenInstance>::build_module_genesis_storage(
-			$extra,
-			$top,
-			$children,
+		$crate::build_module_genesis_storage(
+			stringify!($module),
+			|| $crate::BuildModuleGenesisStorage::<$runtime, $module::__InherentHiddenInstance>::build_module_genesis_storage(
+				$extra,
+				$top,
+				$children,
+			),
 		)?;
 	}
 }
@@ -1009,4 +1109,43 @@ mod tests {
 			((Into::<U256>::into(std::u128::MAX) * 999_999u32) / 1_000_000u32).as_u128()
 		);
 	}
+
+	#[test]
+	fn multi_signature_should_verify_ecdsa() {
+		use primitives::crypto::Pair;
+		use super::{MultiSignature, MultiSigner, traits::Verify};
+
+		let pair = primitives::ecdsa::Pair::from_seed(&[1u8; 32]);
+		let msg = &b"Hello"[..];
+		let signature: MultiSignature = pair.sign(msg).into();
+		let signer: MultiSigner = pair.public().into();
+		assert!(signature.verify(msg, &signer));
+		assert!(!signature.verify(&b"World"[..], &signer));
+
+		let other: MultiSigner = primitives::sr25519::Pair::from_seed(&[1u8; 32]).public().into();
+		assert!(!signature.verify(msg, &other));
+
+		assert!(signer.to_eth_address().is_some());
+		assert!(other.to_eth_address().is_none());
+		assert_ne!(signer.to_account_hash(), other.to_account_hash());
+	}
+
+	#[test]
+	fn module_genesis_errors_and_panics_name_the_module() {
+		use super::build_module_genesis_storage;
+
+		assert_eq!(build_module_genesis_storage("balances", || Ok(())), Ok(()));
+		assert_eq!(
+			build_module_genesis_storage("balances", || Err("Invalid balance".into())),
+			Err("balances: Invalid balance".into()),
+		);
+		assert_eq!(
+			build_module_genesis_storage("session", || panic!("Empty validator set in genesis block!")),
+			Err("session: Empty validator set in genesis block!".into()),
+		);
+		assert_eq!(
+			build_module_genesis_storage("session", || panic!("duplicate validator {}", 4)),
+			Err("session: duplicate validator 4".into()),
+		);
+	}
 }
@@ -28,7 +28,7 @@ use crate::weights::{GetDispatchInfo, DispatchInfo};
 pub use primitives::H256;
 use primitives::U256;
 use primitives::ed25519::{Public as AuthorityId};
-use crate::transaction_validity::TransactionValidity;
+use crate::transaction_validity::{TransactionValidity, TransactionSource};
 
 /// Authority Id
 #[derive(Default, PartialEq, Eq, Clone, Encode, Decode, Debug)]
@@ -257,8 +257,11 @@ impl<Origin, Call, Extra> Applyable for TestXt<Call, Extra> where
 
 	fn sender(&self) -> Option<&u64> { self.0.as_ref().map(|x| &x.0) }
 
+	fn call(&self) -> &Call { &self.1 }
+
 	/// Checks to see if this is a valid *transaction*. It returns information on it if so.
 	fn validate<U: ValidateUnsigned<Call=Self::Call>>(&self,
+		_source: TransactionSource,
 		_info: DispatchInfo,
 		_len: usize,
 	) -> TransactionValidity {
@@ -271,14 +274,16 @@ impl<Origin, Call, Extra> Applyable for TestXt<Call, Extra> where
 		info: DispatchInfo,
 		len: usize,
 	) -> Result<DispatchResult, DispatchError> {
-		let maybe_who = if let Some((who, extra)) = self.0 {
-			Extra::pre_dispatch(extra, &who, info, len)?;
-			Some(who)
+		let (maybe_who, pre) = if let Some((who, extra)) = self.0 {
+			let pre = Extra::pre_dispatch(extra, &who, info, len)?;
+			(Some(who), pre)
 		} else {
-			Extra::pre_dispatch_unsigned(info, len)?;
-			None
+			let pre = Extra::pre_dispatch_unsigned(info, len)?;
+			(None, pre)
 		};
-		Ok(self.1.dispatch(maybe_who.into()))
+		let (res, post_info) = self.1.dispatch_with_post_info(maybe_who.into());
+		Extra::post_dispatch(pre, info, post_info, len);
+		Ok(res)
 	}
 }
 
@@ -23,9 +23,9 @@ use runtime_io;
 #[cfg(feature = "std")] use serde::{Serialize, Deserialize, de::DeserializeOwned};
 use primitives::{self, Hasher, Blake2Hasher};
 use crate::codec::{Codec, Encode, Decode, HasCompact};
-use crate::transaction_validity::{ValidTransaction, TransactionValidity};
+use crate::transaction_validity::{ValidTransaction, TransactionValidity, TransactionSource};
 use crate::generic::{Digest, DigestItem};
-use crate::weights::DispatchInfo;
+use crate::weights::{DispatchInfo, PostDispatchInfo};
 pub use primitives::crypto::TypedKey;
 pub use integer_sqrt::IntegerSquareRoot;
 pub use num_traits::{
@@ -55,6 +55,15 @@ pub trait Verify {
 	type Signer;
 	/// Verify a signature. Return `true` if signature is valid for the value.
 	fn verify<L: Lazy<[u8]>>(&self, msg: L, signer: &Self::Signer) -> bool;
+
+	/// Verify a signature as part of the current signature verification batch, if any.
+	///
+	/// Within a batch this may return `true` for an invalid signature, which then makes the
+	/// whole batch fail. See `runtime_io::start_batch_verify`. By default the signature is
+	/// verified right away.
+	fn batch_verify<L: Lazy<[u8]>>(&self, msg: L, signer: &Self::Signer) -> bool {
+		self.verify(msg, signer)
+	}
 }
 
 impl Verify for primitives::ed25519::Signature {
@@ -62,6 +71,10 @@ impl Verify for primitives::ed25519::Signature {
 	fn verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &Self::Signer) -> bool {
 		runtime_io::ed25519_verify(self.as_ref(), msg.get(), signer)
 	}
+
+	fn batch_verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &Self::Signer) -> bool {
+		runtime_io::batch_verify(runtime_io::SignatureKind::Ed25519, self.as_ref(), msg.get(), signer)
+	}
 }
 
 impl Verify for primitives::sr25519::Signature {
@@ -69,6 +82,20 @@ impl Verify for primitives::sr25519::Signature {
 	fn verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &Self::Signer) -> bool {
 		runtime_io::sr25519_verify(self.as_ref(), msg.get(), signer)
 	}
+
+	fn batch_verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &Self::Signer) -> bool {
+		runtime_io::batch_verify(runtime_io::SignatureKind::Sr25519, self.as_ref(), msg.get(), signer)
+	}
+}
+
+impl Verify for primitives::ecdsa::Signature {
+	type Signer = primitives::ecdsa::Public;
+	fn verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &Self::Signer) -> bool {
+		match runtime_io::secp256k1_ecdsa_recover(self.as_ref(), &runtime_io::blake2_256(msg.get())) {
+			Ok(pubkey) => &pubkey[..] == &signer.0[..],
+			_ => false,
+		}
+	}
 }
 
 /// Some sort of check on the origin is performed by this object.
@@ -784,6 +811,33 @@ impl From<DispatchError> for i8 {
 /// or an error message.
 pub type DispatchResult = result::Result<(), &'static str>;
 
+/// Result of a module function call which also reports information that is only known after the
+/// dispatch, e.g. the weight it actually consumed.
+pub type DispatchResultWithPostInfo = result::Result<PostDispatchInfo, &'static str>;
+
+/// Anything that a module function can return: splits it into the plain `DispatchResult` and the
+/// `PostDispatchInfo` of the call.
+pub trait IntoDispatchResultWithPostInfo {
+	/// Split `self` into its `DispatchResult` and `PostDispatchInfo`.
+	fn split_post_info(self) -> (DispatchResult, PostDispatchInfo);
+}
+
+impl IntoDispatchResultWithPostInfo for DispatchResult {
+	fn split_post_info(self) -> (DispatchResult, PostDispatchInfo) {
+		(self, Default::default())
+	}
+}
+
+impl IntoDispatchResultWithPostInfo for DispatchResultWithPostInfo {
+	fn split_post_info(self) -> (DispatchResult, PostDispatchInfo) {
+		match self {
+			Ok(post_info) => (Ok(()), post_info),
+			// an erroneous call is charged its pre-declared weight.
+			Err(e) => (Err(e), Default::default()),
+		}
+	}
+}
+
 /// A lazy call (module function and argument values) that can be executed via its `dispatch`
 /// method.
 pub trait Dispatchable {
@@ -795,6 +849,13 @@ pub trait Dispatchable {
 	type Trait;
 	/// Actually dispatch this call and result the result of it.
 	fn dispatch(self, origin: Self::Origin) -> DispatchResult;
+	/// Dispatch this call and return the result along with the information that is only known
+	/// after the dispatch. By default, nothing beyond the pre-declared weight is known.
+	fn dispatch_with_post_info(self, origin: Self::Origin) -> (DispatchResult, PostDispatchInfo)
+		where Self: Sized
+	{
+		(self.dispatch(origin), Default::default())
+	}
 }
 
 /// Means by which a transaction may be extended. This type embodies both the data and the logic
@@ -809,6 +870,10 @@ pub trait SignedExtension:
 	/// from the transaction using the `additional_signed` function.
 	type AdditionalSigned: Encode;
 
+	/// The type that encodes information that can be passed from `pre_dispatch` to
+	/// `post_dispatch`.
+	type Pre: Default;
+
 	/// Construct any additional data that should be in the signed payload of the transaction. Can
 	/// also perform any pre-signature-verification checks and return an error if needed.
 	fn additional_signed(&self) -> Result<Self::AdditionalSigned, &'static str>;
@@ -827,7 +892,9 @@ pub trait SignedExtension:
 		who: &Self::AccountId,
 		info: DispatchInfo,
 		len: usize,
-	) -> Result<(), DispatchError> { self.validate(who, info, len).map(|_| ()) }
+	) -> Result<Self::Pre, DispatchError> {
+		self.validate(who, info, len).map(|_| Default::default())
+	}
 
 	/// Validate an unsigned transaction for the transaction queue. Normally the default
 	/// implementation is fine since `ValidateUnsigned` is a better way of recognising and
@@ -841,7 +908,19 @@ pub trait SignedExtension:
 	fn pre_dispatch_unsigned(
 		info: DispatchInfo,
 		len: usize,
-	) -> Result<(), DispatchError> { Self::validate_unsigned(info, len).map(|_| ()) }
+	) -> Result<Self::Pre, DispatchError> {
+		Self::validate_unsigned(info, len).map(|_| Default::default())
+	}
+
+	/// Do any post-flight stuff for a transaction, signed or unsigned. `pre` is the value returned
+	/// by the corresponding `pre_dispatch` and `post_info` what the dispatched call reported about
+	/// itself, e.g. its actual weight.
+	fn post_dispatch(
+		_pre: Self::Pre,
+		_info: DispatchInfo,
+		_post_info: PostDispatchInfo,
+		_len: usize,
+	) { }
 }
 
 macro_rules! tuple_impl_indexed {
@@ -855,6 +934,7 @@ macro_rules! tuple_impl_indexed {
 		> SignedExtension for ($($direct),+,) {
 			type AccountId = AccountId;
 			type AdditionalSigned = ($($direct::AdditionalSigned,)+);
+			type Pre = ($($direct::Pre,)+);
 			fn additional_signed(&self) -> Result<Self::AdditionalSigned, &'static str> {
 				Ok(( $(self.$index.additional_signed()?,)+ ))
 			}
@@ -872,9 +952,8 @@ macro_rules! tuple_impl_indexed {
 				who: &Self::AccountId,
 				info: DispatchInfo,
 				len: usize,
-			) -> Result<(), DispatchError> {
-				$(self.$index.pre_dispatch(who, info, len)?;)+
-				Ok(())
+			) -> Result<Self::Pre, DispatchError> {
+				Ok(($(self.$index.pre_dispatch(who, info, len)?,)+))
 			}
 			fn validate_unsigned(
 				info: DispatchInfo,
@@ -886,9 +965,16 @@ macro_rules! tuple_impl_indexed {
 			fn pre_dispatch_unsigned(
 				info: DispatchInfo,
 				len: usize,
-			) -> Result<(), DispatchError> {
-				$($direct::pre_dispatch_unsigned(info, len)?;)+
-				Ok(())
+			) -> Result<Self::Pre, DispatchError> {
+				Ok(($($direct::pre_dispatch_unsigned(info, len)?,)+))
+			}
+			fn post_dispatch(
+				pre: Self::Pre,
+				info: DispatchInfo,
+				post_info: PostDispatchInfo,
+				len: usize,
+			) {
+				$($direct::post_dispatch(pre.$index, info, post_info, len);)+
 			}
 		}
 
@@ -915,6 +1001,7 @@ tuple_impl_indexed!(A, B, C, D, E, F, G, H, I, J, ; 0, 1, 2, 3, 4, 5, 6, 7, 8, 9
 impl SignedExtension for () {
 	type AccountId = u64;
 	type AdditionalSigned = ();
+	type Pre = ();
 	fn additional_signed(&self) -> rstd::result::Result<(), &'static str> { Ok(()) }
 }
 
@@ -934,8 +1021,12 @@ pub trait Applyable: Sized + Send + Sync {
 	/// Returns a reference to the sender if any.
 	fn sender(&self) -> Option<&Self::AccountId>;
 
+	/// Returns a reference to the call that this piece of information dispatches.
+	fn call(&self) -> &Self::Call;
+
 	/// Checks to see if this is a valid *transaction*. It returns information on it if so.
 	fn validate<V: ValidateUnsigned<Call=Self::Call>>(&self,
+		source: TransactionSource,
 		info: DispatchInfo,
 		len: usize,
 	) -> TransactionValidity;
@@ -1014,8 +1105,11 @@ pub trait ValidateUnsigned {
 	/// This doesn't execute any side-effects; it merely checks
 	/// whether the transaction would panic if it were included or not.
 	///
+	/// The `source` describes where the transaction came from, which allows
+	/// to e.g. accept some calls only if they were submitted by the local node.
+	///
 	/// Changes made to storage should be discarded by caller.
-	fn validate_unsigned(call: &Self::Call) -> TransactionValidity;
+	fn validate_unsigned(source: TransactionSource, call: &Self::Call) -> TransactionValidity;
 }
 
 /// Opaque datatype that may be destructured into a series of raw byte slices (which represent
@@ -1032,8 +1126,47 @@ pub trait OpaqueKeys: Clone {
 	fn get<T: Decode>(&self, i: super::KeyTypeId) -> Option<T> {
 		T::decode(&mut self.get_raw(i)).ok()
 	}
-	/// Verify a proof of ownership for the keys.
-	fn ownership_proof_is_valid(&self, _proof: &[u8]) -> bool { true }
+	/// Verify a proof that `owner` (the encoded account that sets the keys) owns the keys.
+	fn ownership_proof_is_valid(&self, _owner: &[u8], _proof: &[u8]) -> bool { true }
+}
+
+/// Returns the message that is signed with each session key to prove that `owner` (the encoded
+/// account that sets the keys) owns it.
+pub fn session_keys_ownership_payload(owner: &[u8]) -> Vec<u8> {
+	let mut payload = b"substrate:session_keys_ownership".to_vec();
+	payload.extend_from_slice(owner);
+	payload
+}
+
+/// Verify the ownership proof for `keys`.
+///
+/// The proof is the encoded list of the signatures of `session_keys_ownership_payload(owner)`
+/// with each key, in the order of `OpaqueKeys::key_ids`. Only ed25519 and sr25519 keys are
+/// supported.
+pub fn verify_session_keys_ownership<K: OpaqueKeys>(keys: &K, owner: &[u8], proof: &[u8]) -> bool {
+	let signatures = match Vec::<primitives::H512>::decode(&mut &proof[..]) {
+		Ok(signatures) => signatures,
+		Err(_) => return false,
+	};
+	let payload = session_keys_ownership_payload(owner);
+
+	let mut signatures = signatures.into_iter();
+	let all_valid = K::key_ids().into_iter().all(|id| {
+		let signature = match signatures.next() {
+			Some(signature) => signature,
+			None => return false,
+		};
+		let public = keys.get_raw(id);
+		match id {
+			crate::key_types::ED25519 =>
+				runtime_io::ed25519_verify(signature.as_fixed_bytes(), &payload, public),
+			crate::key_types::SR25519 =>
+				runtime_io::sr25519_verify(signature.as_fixed_bytes(), &payload, public),
+			_ => false,
+		}
+	});
+
+	all_valid && signatures.next().is_none()
 }
 
 /// Input that adds infinite number of zero after wrapped input.
@@ -1112,8 +1245,49 @@ impl<T: Encode + Decode + Default, Id: Encode + Decode + TypeId> AccountIdConver
 
 #[cfg(test)]
 mod tests {
-	use super::AccountIdConversion;
+	use super::{AccountIdConversion, SignedExtension, DispatchError};
 	use crate::codec::{Encode, Decode, Input};
+	use crate::transaction_validity::ValidTransaction;
+	use crate::weights::DispatchInfo;
+
+	#[derive(Encode, Decode, Clone, Eq, PartialEq, Debug)]
+	struct PriorityExtension(u64);
+	impl SignedExtension for PriorityExtension {
+		type AccountId = u64;
+		type AdditionalSigned = u64;
+		type Pre = ();
+		fn additional_signed(&self) -> Result<u64, &'static str> { Ok(self.0) }
+		fn validate(
+			&self,
+			who: &u64,
+			_info: DispatchInfo,
+			_len: usize,
+		) -> Result<ValidTransaction, DispatchError> {
+			Ok(ValidTransaction {
+				priority: self.0,
+				provides: vec![(who, self.0).encode()],
+				longevity: 64 / self.0,
+				..Default::default()
+			})
+		}
+	}
+
+	#[derive(Encode, Decode, Clone, Eq, PartialEq, Debug)]
+	struct RejectingExtension;
+	impl SignedExtension for RejectingExtension {
+		type AccountId = u64;
+		type AdditionalSigned = ();
+		type Pre = ();
+		fn additional_signed(&self) -> Result<(), &'static str> { Ok(()) }
+		fn validate(
+			&self,
+			_who: &u64,
+			_info: DispatchInfo,
+			_len: usize,
+		) -> Result<ValidTransaction, DispatchError> {
+			Err(DispatchError::Payment)
+		}
+	}
 
 	#[derive(Encode, Decode, Default, PartialEq, Debug)]
 	struct U32Value(u32);
@@ -1163,6 +1337,31 @@ mod tests {
 		assert!(r.is_none());
 	}
 
+	#[test]
+	fn signed_extension_tuple_should_combine_validity() {
+		let extra = (PriorityExtension(2), PriorityExtension(4));
+		assert_eq!(extra.additional_signed(), Ok((2, 4)));
+
+		let valid = extra.validate(&1, Default::default(), 0).unwrap();
+		assert_eq!(valid.priority, 6);
+		assert_eq!(valid.provides, vec![(1u64, 2u64).encode(), (1u64, 4u64).encode()]);
+		assert_eq!(valid.longevity, 16);
+		assert!(valid.propagate);
+	}
+
+	#[test]
+	fn signed_extension_tuple_should_fail_if_any_fails() {
+		let extra = (PriorityExtension(2), RejectingExtension);
+		match extra.validate(&1, Default::default(), 0) {
+			Err(DispatchError::Payment) => {},
+			r => panic!("Expected payment error, got: {:?}", r),
+		}
+		match extra.pre_dispatch(&1, Default::default(), 0) {
+			Err(DispatchError::Payment) => {},
+			r => panic!("Expected payment error, got: {:?}", r),
+		}
+	}
+
 	#[test]
 	fn trailing_zero_should_work() {
 		let mut t = super::TrailingZeroInput(&[1, 2, 3]);
@@ -1178,6 +1377,47 @@ mod tests {
 		assert_eq!(t.remaining_len(), Ok(None));
 		assert_eq!(buffer, [0, 0]);
 	}
+
+	mod session_keys {
+		use primitives::{ed25519, sr25519, Pair, H512};
+		use crate::key_types;
+
+		crate::impl_opaque_keys! {
+			pub struct Keys {
+				#[id(key_types::ED25519)]
+				pub ed25519: ed25519::Public,
+				#[id(key_types::SR25519)]
+				pub sr25519: sr25519::Public,
+			}
+		}
+
+		#[test]
+		fn session_keys_ownership_proof_should_work() {
+			use crate::traits::{OpaqueKeys, session_keys_ownership_payload};
+			use crate::codec::Encode;
+
+			let ed = ed25519::Pair::from_seed(&[1; 32]);
+			let sr = sr25519::Pair::from_seed(&[2; 32]);
+			let keys = Keys { ed25519: ed.public(), sr25519: sr.public() };
+			let sign = |owner: &[u8]| {
+				let payload = session_keys_ownership_payload(owner);
+				vec![
+					H512::from_slice(ed.sign(&payload).as_ref()),
+					H512::from_slice(sr.sign(&payload).as_ref()),
+				].encode()
+			};
+
+			assert!(keys.ownership_proof_is_valid(&[1; 32], &sign(&[1; 32])));
+			assert!(!keys.ownership_proof_is_valid(&[2; 32], &sign(&[1; 32])));
+			assert!(!keys.ownership_proof_is_valid(&[1; 32], &[]));
+
+			// An additional signature makes the proof invalid.
+			let mut too_long = vec![H512::default(); 3];
+			too_long[0] = H512::from_slice(ed.sign(&session_keys_ownership_payload(&[1; 32])).as_ref());
+			too_long[1] = H512::from_slice(sr.sign(&session_keys_ownership_payload(&[1; 32])).as_ref());
+			assert!(!keys.ownership_proof_is_valid(&[1; 32], &too_long.encode()));
+		}
+	}
 }
 
 /// Calls a given macro a number of times with a set of fixed params and an incrementing numeral.
@@ -1256,6 +1496,10 @@ macro_rules! impl_opaque_keys {
 					_ => &[],
 				}
 			}
+
+			fn ownership_proof_is_valid(&self, owner: &[u8], proof: &[u8]) -> bool {
+				$crate::traits::verify_session_keys_ownership(self, owner, proof)
+			}
 		}
 	};
 }
@@ -30,6 +30,23 @@ pub type TransactionLongevity = u64;
 /// Tag for a transaction. No two transactions with the same tag should be placed on-chain.
 pub type TransactionTag = Vec<u8>;
 
+/// The source of the transaction.
+///
+/// Depending on the source we might apply different validation schemes,
+/// e.g. some transactions may only be accepted if they were produced by the local node.
+#[derive(Copy, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum TransactionSource {
+	/// Transaction is already included in a block.
+	///
+	/// This means that we are re-validating transactions from a retracted or pruned block.
+	InBlock,
+	/// Transaction is coming from a local source, i.e. RPC or an offchain worker.
+	Local,
+	/// Transaction has been received externally, i.e. gossiped by other peers.
+	External,
+}
+
 /// Information on a transaction's validity and, if valid, on how it relates to other transactions.
 #[derive(Clone, PartialEq, Eq, Encode)]
 #[cfg_attr(feature = "std", derive(Debug))]
@@ -25,6 +25,8 @@
 
 use crate::{Fixed64, traits::Saturating};
 use crate::codec::{Encode, Decode};
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
 
 pub use crate::transaction_validity::TransactionPriority;
 use crate::traits::Bounded;
@@ -34,8 +36,9 @@ pub type Weight = u32;
 
 /// A generalized group of dispatch types. This is only distinguishing normal, user-triggered transactions
 /// (`Normal`) and anything beyond which serves a higher purpose to the system (`Operational`).
-#[cfg_attr(feature = "std", derive(Debug))]
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode)]
 pub enum DispatchClass {
 	/// A normal dispatch.
 	Normal,
@@ -84,6 +87,43 @@ impl DispatchInfo {
 	}
 }
 
+/// Weight information that is only available post dispatch.
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct PostDispatchInfo {
+	/// Actual weight consumed by a call or `None` which stands for the worst case static weight.
+	pub actual_weight: Option<Weight>,
+}
+
+impl PostDispatchInfo {
+	/// Calculate how much (if any) weight was not used by the `Dispatchable`.
+	pub fn calc_unspent(&self, info: &DispatchInfo) -> Weight {
+		info.weight.saturating_sub(self.calc_actual_weight(info))
+	}
+
+	/// Calculate how much weight was actually spent by the `Dispatchable`.
+	///
+	/// A call can never consume more than its pre-declared weight.
+	pub fn calc_actual_weight(&self, info: &DispatchInfo) -> Weight {
+		match self.actual_weight {
+			Some(actual_weight) => actual_weight.min(info.weight),
+			None => info.weight,
+		}
+	}
+}
+
+impl From<Option<Weight>> for PostDispatchInfo {
+	fn from(actual_weight: Option<Weight>) -> Self {
+		Self { actual_weight }
+	}
+}
+
+impl From<()> for PostDispatchInfo {
+	fn from(_: ()) -> Self {
+		Self { actual_weight: None }
+	}
+}
+
 /// A `Dispatchable` function (aka transaction) that can carry some static information along with it, using the
 /// `#[weight]` attribute.
 pub trait GetDispatchInfo {
@@ -239,4 +279,15 @@ mod tests {
 		fm = WeightMultiplier::from_rational(1, 1);
 		test_set.clone().into_iter().for_each(|i| { assert_eq!(fm.apply_to(i), i * 2); });
 	}
+	#[test]
+	fn post_dispatch_info_calculates_unspent_weight() {
+		let info = DispatchInfo { weight: 100, ..Default::default() };
+
+		assert_eq!(PostDispatchInfo::default().calc_unspent(&info), 0);
+		assert_eq!(PostDispatchInfo::from(Some(60)).calc_unspent(&info), 40);
+		assert_eq!(PostDispatchInfo::from(Some(60)).calc_actual_weight(&info), 60);
+		// can never spend more than declared.
+		assert_eq!(PostDispatchInfo::from(Some(160)).calc_unspent(&info), 0);
+		assert_eq!(PostDispatchInfo::from(Some(160)).calc_actual_weight(&info), 100);
+	}
 }
@@ -29,7 +29,7 @@ use sr_primitives::traits::RuntimeApiInfo;
 
 use codec::Encode;
 #[cfg(feature = "std")]
-use codec::Decode;
+use codec::{Decode, Input};
 use sr_primitives::RuntimeString;
 pub use sr_primitives::create_runtime_str;
 
@@ -62,8 +62,8 @@ macro_rules! create_apis_vec {
 /// This triplet have different semantics and mis-interpretation could cause problems.
 /// In particular: bug fixes should result in an increment of `spec_version` and possibly `authoring_version`,
 /// absolutely not `impl_version` since they change the semantics of the runtime.
-#[derive(Clone, PartialEq, Eq, Encode)]
-#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize, Decode))]
+#[derive(Clone, PartialEq, Eq, Encode, Default)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
 #[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
 pub struct RuntimeVersion {
 	/// Identifies the different Substrate runtimes. There'll be at least polkadot and node.
@@ -98,6 +98,42 @@ pub struct RuntimeVersion {
 	/// List of supported API "features" along with their versions.
 	#[cfg_attr(feature = "std", serde(serialize_with = "apis_serialize::serialize"))]
 	pub apis: ApisVec,
+
+	/// All existing dispatches are fully compatible when this number doesn't change. If this
+	/// number changes, then `spec_version` must change, also.
+	///
+	/// This number must change when an existing dispatchable (module ID & call ID) is changed,
+	/// either through an alteration in its user-level semantics, a parameter added/removed/changed,
+	/// a dispatchable being removed, a module being removed, or a dispatchable/module changing its
+	/// index.
+	///
+	/// It need *not* change when a new module is added or when a dispatchable is added. Unlike
+	/// `spec_version`, it is part of the signed payload of transactions, so that transactions
+	/// signed offline stay valid across runtime upgrades that keep the existing calls intact.
+	#[cfg_attr(feature = "std", serde(default = "legacy_transaction_version"))]
+	pub transaction_version: u32,
+}
+
+/// The `transaction_version` of runtimes that predate it.
+#[cfg(feature = "std")]
+fn legacy_transaction_version() -> u32 {
+	1
+}
+
+#[cfg(feature = "std")]
+impl Decode for RuntimeVersion {
+	fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+		Ok(RuntimeVersion {
+			spec_name: Decode::decode(input)?,
+			impl_name: Decode::decode(input)?,
+			authoring_version: Decode::decode(input)?,
+			spec_version: Decode::decode(input)?,
+			impl_version: Decode::decode(input)?,
+			apis: Decode::decode(input)?,
+			// runtimes that predate `transaction_version` don't encode it.
+			transaction_version: Decode::decode(input).unwrap_or_else(|_| legacy_transaction_version()),
+		})
+	}
 }
 
 #[cfg(feature = "std")]
@@ -188,3 +224,42 @@ mod apis_serialize {
 		bytes::serialize(*apis, ser)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn version() -> RuntimeVersion {
+		RuntimeVersion {
+			spec_name: create_runtime_str!("test"),
+			impl_name: create_runtime_str!("test"),
+			authoring_version: 1,
+			spec_version: 7,
+			impl_version: 7,
+			apis: create_apis_vec!([([1; 8], 2)]),
+			transaction_version: 3,
+		}
+	}
+
+	#[test]
+	fn transaction_version_is_encoded() {
+		let encoded = version().encode();
+		assert_eq!(RuntimeVersion::decode(&mut &encoded[..]).unwrap(), version());
+
+		let json = serde_json::to_string(&version()).unwrap();
+		assert_eq!(serde_json::from_str::<RuntimeVersion>(&json).unwrap(), version());
+	}
+
+	#[test]
+	fn versions_without_transaction_version_are_read_as_version_one() {
+		let legacy = RuntimeVersion { transaction_version: 1, ..version() };
+
+		let mut encoded = version().encode();
+		encoded.truncate(encoded.len() - 4);
+		assert_eq!(RuntimeVersion::decode(&mut &encoded[..]).unwrap(), legacy);
+
+		let mut json = serde_json::to_value(&version()).unwrap();
+		json.as_object_mut().unwrap().remove("transactionVersion");
+		assert_eq!(serde_json::from_value::<RuntimeVersion>(json).unwrap(), legacy);
+	}
+}
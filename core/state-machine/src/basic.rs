@@ -117,6 +117,10 @@ impl<H: Hasher> Externalities<H> for BasicExternalities where H::Out: Ord {
 		}
 	}
 
+	fn set_offchain_index(&mut self, _key: &[u8], _value: Option<&[u8]>) {
+		warn!("Call to set offchain index on basic externalities, which don't keep one.");
+	}
+
 	fn place_child_storage(
 		&mut self,
 		storage_key: ChildStorageKey<H>,
@@ -22,7 +22,7 @@ use crate::backend::Backend;
 use crate::changes_trie::{Storage as ChangesTrieStorage, build_changes_trie};
 use crate::{Externalities, OverlayedChanges, ChildStorageKey};
 use hash_db::Hasher;
-use primitives::offchain;
+use primitives::{offchain, tracing};
 use primitives::storage::well_known_keys::is_child_storage_key;
 use trie::{MemoryDB, default_child_trie_root};
 use trie::trie_types::Layout;
@@ -175,12 +175,14 @@ where
 {
 	fn storage(&self, key: &[u8]) -> Option<Vec<u8>> {
 		let _guard = panic_handler::AbortGuard::force_abort();
+		tracing::note_storage_read();
 		self.overlay.storage(key).map(|x| x.map(|x| x.to_vec())).unwrap_or_else(||
 			self.backend.storage(key).expect(EXT_NOT_ALLOWED_TO_FAIL))
 	}
 
 	fn storage_hash(&self, key: &[u8]) -> Option<H::Out> {
 		let _guard = panic_handler::AbortGuard::force_abort();
+		tracing::note_storage_read();
 		self.overlay.storage(key).map(|x| x.map(|x| H::hash(x))).unwrap_or_else(||
 			self.backend.storage_hash(key).expect(EXT_NOT_ALLOWED_TO_FAIL))
 	}
@@ -197,12 +199,14 @@ where
 
 	fn child_storage(&self, storage_key: ChildStorageKey<H>, key: &[u8]) -> Option<Vec<u8>> {
 		let _guard = panic_handler::AbortGuard::force_abort();
+		tracing::note_storage_read();
 		self.overlay.child_storage(storage_key.as_ref(), key).map(|x| x.map(|x| x.to_vec())).unwrap_or_else(||
 			self.backend.child_storage(storage_key.as_ref(), key).expect(EXT_NOT_ALLOWED_TO_FAIL))
 	}
 
 	fn exists_storage(&self, key: &[u8]) -> bool {
 		let _guard = panic_handler::AbortGuard::force_abort();
+		tracing::note_storage_read();
 		match self.overlay.storage(key) {
 			Some(x) => x.is_some(),
 			_ => self.backend.exists_storage(key).expect(EXT_NOT_ALLOWED_TO_FAIL),
@@ -211,6 +215,7 @@ where
 
 	fn exists_child_storage(&self, storage_key: ChildStorageKey<H>, key: &[u8]) -> bool {
 		let _guard = panic_handler::AbortGuard::force_abort();
+		tracing::note_storage_read();
 
 		match self.overlay.child_storage(storage_key.as_ref(), key) {
 			Some(x) => x.is_some(),
@@ -220,6 +225,7 @@ where
 
 	fn place_storage(&mut self, key: Vec<u8>, value: Option<Vec<u8>>) {
 		let _guard = panic_handler::AbortGuard::force_abort();
+		tracing::note_storage_write();
 		if is_child_storage_key(&key) {
 			warn!(target: "trie", "Refuse to directly set child storage key");
 			return;
@@ -229,8 +235,14 @@ where
 		self.overlay.set_storage(key, value);
 	}
 
+	fn set_offchain_index(&mut self, key: &[u8], value: Option<&[u8]>) {
+		let _guard = panic_handler::AbortGuard::force_abort();
+		self.overlay.set_offchain_index(key.to_vec(), value.map(<[u8]>::to_vec));
+	}
+
 	fn place_child_storage(&mut self, storage_key: ChildStorageKey<H>, key: Vec<u8>, value: Option<Vec<u8>>) {
 		let _guard = panic_handler::AbortGuard::force_abort();
+		tracing::note_storage_write();
 
 		self.mark_dirty();
 		self.overlay.set_child_storage(storage_key.into_owned(), key, value);
@@ -238,6 +250,7 @@ where
 
 	fn kill_child_storage(&mut self, storage_key: ChildStorageKey<H>) {
 		let _guard = panic_handler::AbortGuard::force_abort();
+		tracing::note_storage_write();
 
 		self.mark_dirty();
 		self.overlay.clear_child_storage(storage_key.as_ref());
@@ -248,6 +261,7 @@ where
 
 	fn clear_prefix(&mut self, prefix: &[u8]) {
 		let _guard = panic_handler::AbortGuard::force_abort();
+		tracing::note_storage_write();
 		if is_child_storage_key(prefix) {
 			warn!(target: "trie", "Refuse to directly clear prefix that is part of child storage key");
 			return;
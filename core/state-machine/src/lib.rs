@@ -135,10 +135,23 @@ pub enum ExecutionError {
 	UnableToGenerateProof,
 	/// Invalid execution proof.
 	InvalidProof,
+	/// The native and the wasm runtime returned different results for the same call.
+	ConsensusMismatch {
+		/// The result of the wasm execution.
+		wasm: String,
+		/// The result of the native execution.
+		native: String,
+	},
 }
 
 impl fmt::Display for ExecutionError {
-	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "Externalities Error") }
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ExecutionError::ConsensusMismatch { wasm, native } =>
+				write!(f, "Consensus error between wasm {} and native {}", wasm, native),
+			_ => write!(f, "Externalities Error"),
+		}
+	}
 }
 
 type CallResult<R, E> = Result<NativeOrEncoded<R>, E>;
@@ -207,6 +220,12 @@ pub trait Externalities<H: Hasher> {
 	/// Set or clear a child storage entry. Return whether the operation succeeds.
 	fn place_child_storage(&mut self, storage_key: ChildStorageKey<H>, key: Vec<u8>, value: Option<Vec<u8>>);
 
+	/// Set or clear an entry of the offchain index.
+	///
+	/// The offchain index is written to the node's offchain database when the block is imported.
+	/// It is not part of the state and can't be read back during block execution.
+	fn set_offchain_index(&mut self, key: &[u8], value: Option<&[u8]>);
+
 	/// Get the identity of the chain.
 	fn chain_id(&self) -> u64;
 
@@ -294,6 +313,31 @@ impl offchain::Externalities for NeverOffchainExt {
 		unreachable!()
 	}
 
+	fn vrf_sign(
+		&mut self,
+		_key: offchain::CryptoKey,
+		_context: &[u8],
+		_message: &[u8],
+	) -> Result<primitives::sr25519::VrfSignature, ()> {
+		unreachable!()
+	}
+
+	fn keystore_public_keys(
+		&mut self,
+		_key_type: primitives::crypto::KeyTypeId,
+	) -> Result<Vec<Vec<u8>>, ()> {
+		unreachable!()
+	}
+
+	fn keystore_sign(
+		&mut self,
+		_key_type: primitives::crypto::KeyTypeId,
+		_public: &[u8],
+		_data: &[u8],
+	) -> Result<Vec<u8>, ()> {
+		unreachable!()
+	}
+
 	fn timestamp(&mut self) -> offchain::Timestamp {
 		unreachable!()
 	}
@@ -406,6 +450,9 @@ pub enum ExecutionStrategy {
 	Both,
 	/// First native, then if that fails or is not possible, wasm.
 	NativeElseWasm,
+	/// Run with both the wasm and the native variant (if compatible). Fail the call with
+	/// `ExecutionError::ConsensusMismatch` on any discrepancy.
+	BothOrError,
 }
 
 type DefaultHandler<R, E> = fn(
@@ -424,6 +471,8 @@ pub enum ExecutionManager<F> {
 	Both(F),
 	/// First native, then if that fails or is not possible, wasm.
 	NativeElseWasm,
+	/// Run with both the wasm and the native variant (if compatible). Fail the call on any discrepancy.
+	BothOrError,
 }
 
 impl<'a, F> From<&'a ExecutionManager<F>> for ExecutionStrategy {
@@ -433,6 +482,7 @@ impl<'a, F> From<&'a ExecutionManager<F>> for ExecutionStrategy {
 			ExecutionManager::AlwaysWasm => ExecutionStrategy::AlwaysWasm,
 			ExecutionManager::NativeElseWasm => ExecutionStrategy::NativeElseWasm,
 			ExecutionManager::Both(_) => ExecutionStrategy::Both,
+			ExecutionManager::BothOrError => ExecutionStrategy::BothOrError,
 		}
 	}
 }
@@ -452,6 +502,7 @@ impl ExecutionStrategy {
 				);
 				wasm_result
 			}),
+			ExecutionStrategy::BothOrError => ExecutionManager::BothOrError,
 		}
 	}
 }
@@ -677,6 +728,26 @@ impl<'a, H, N, B, T, O, Exec> StateMachine<'a, H, N, B, T, O, Exec> where
 				ExecutionManager::Both(on_consensus_failure) => {
 					self.execute_call_with_both_strategy(compute_tx, native_call.take(), orig_prospective, on_consensus_failure)
 				},
+				ExecutionManager::BothOrError => {
+					let mut mismatch = None;
+					let res = self.execute_call_with_both_strategy(
+						compute_tx,
+						native_call.take(),
+						orig_prospective,
+						|wasm_result, native_result| {
+							mismatch = Some(ExecutionError::ConsensusMismatch {
+								wasm: format!("{:?}", wasm_result),
+								native: format!("{:?}", native_result),
+							});
+							wasm_result
+						},
+					);
+					if let Some(mismatch) = mismatch {
+						warn!("{}", mismatch);
+						return Err(Box::new(mismatch));
+					}
+					res
+				},
 				ExecutionManager::NativeElseWasm => {
 					self.execute_call_with_native_else_wasm_strategy(compute_tx, native_call.take(), orig_prospective)
 				},
@@ -1103,6 +1174,32 @@ mod tests {
 		assert!(consensus_failed);
 	}
 
+	#[test]
+	fn dual_execution_strategy_reports_consensus_failure_as_error() {
+		let execute = |fallback_succeeds| new(
+			&trie_backend::tests::test_trie(),
+			Some(&InMemoryChangesTrieStorage::<Blake2Hasher, u64>::new()),
+			NeverOffchainExt::new(),
+			&mut Default::default(),
+			&DummyCodeExecutor {
+				change_changes_trie_config: false,
+				native_available: true,
+				native_succeeds: true,
+				fallback_succeeds,
+			},
+			"test",
+			&[],
+		).execute_using_consensus_failure_handler::<DefaultHandler<NeverNativeValue, u8>, NeverNativeValue, fn() -> _>(
+			ExecutionManager::BothOrError,
+			true,
+			None,
+		).map(|(result, _, _)| result.into_encoded());
+
+		assert_eq!(execute(true).unwrap(), vec![66]);
+		let err = execute(false).unwrap_err();
+		assert!(format!("{}", err).starts_with("Consensus error between wasm"));
+	}
+
 	#[test]
 	fn prove_execution_and_proof_check_works() {
 		let executor = DummyCodeExecutor {
@@ -56,6 +56,9 @@ pub struct OverlayedChangeSet {
 	pub top: HashMap<Vec<u8>, OverlayedValue>,
 	/// Child storage changes.
 	pub children: HashMap<Vec<u8>, (Option<BTreeSet<u32>>, HashMap<Vec<u8>, Option<Vec<u8>>>)>,
+	/// Offchain index changes. These are written to the offchain database when the block is
+	/// imported and are not part of the state.
+	pub offchain: HashMap<Vec<u8>, Option<Vec<u8>>>,
 }
 
 #[cfg(test)]
@@ -64,6 +67,7 @@ impl FromIterator<(Vec<u8>, OverlayedValue)> for OverlayedChangeSet {
 		Self {
 			top: iter.into_iter().collect(),
 			children: Default::default(),
+			offchain: Default::default(),
 		}
 	}
 }
@@ -71,13 +75,14 @@ impl FromIterator<(Vec<u8>, OverlayedValue)> for OverlayedChangeSet {
 impl OverlayedChangeSet {
 	/// Whether the change set is empty.
 	pub fn is_empty(&self) -> bool {
-		self.top.is_empty() && self.children.is_empty()
+		self.top.is_empty() && self.children.is_empty() && self.offchain.is_empty()
 	}
 
 	/// Clear the change set.
 	pub fn clear(&mut self) {
 		self.top.clear();
 		self.children.clear();
+		self.offchain.clear();
 	}
 }
 
@@ -159,6 +164,21 @@ impl OverlayedChanges {
 		}
 	}
 
+	/// Inserts the given key-value pair into the prospective offchain index change set.
+	///
+	/// `None` can be used to delete a value specified by the given key.
+	pub(crate) fn set_offchain_index(&mut self, key: Vec<u8>, val: Option<Vec<u8>>) {
+		self.prospective.offchain.insert(key, val);
+	}
+
+	/// Returns the committed offchain index changes.
+	///
+	/// `None` values are deletions.
+	pub fn offchain_index_changes(&self) -> impl Iterator<Item=(&[u8], Option<&[u8]>)> {
+		self.committed.offchain.iter()
+			.map(|(k, v)| (k.as_slice(), v.as_ref().map(AsRef::as_ref)))
+	}
+
 	/// Clear child storage of given storage key.
 	///
 	/// NOTE that this doesn't take place immediately but written into the prospective
@@ -248,6 +268,7 @@ impl OverlayedChanges {
 						.extend(prospective_extrinsics);
 				}
 			}
+			self.committed.offchain.extend(self.prospective.offchain.drain());
 		}
 	}
 
@@ -343,6 +364,37 @@ mod tests {
 		assert!(overlayed.storage(&key).unwrap().is_none());
 	}
 
+	#[test]
+	fn overlayed_offchain_index_works() {
+		let mut overlayed = OverlayedChanges::default();
+
+		let changes = |overlayed: &OverlayedChanges| overlayed.offchain_index_changes()
+			.map(|(k, v)| (k.to_vec(), v.map(<[u8]>::to_vec)))
+			.collect::<HashMap<_, _>>();
+
+		overlayed.set_offchain_index(vec![1], Some(vec![10]));
+		overlayed.set_offchain_index(vec![2], Some(vec![20]));
+		assert!(changes(&overlayed).is_empty());
+
+		overlayed.commit_prospective();
+		assert_eq!(changes(&overlayed), vec![
+			(vec![1], Some(vec![10])),
+			(vec![2], Some(vec![20])),
+		].into_iter().collect());
+
+		overlayed.set_offchain_index(vec![1], Some(vec![11]));
+		overlayed.discard_prospective();
+		overlayed.set_offchain_index(vec![2], None);
+		overlayed.commit_prospective();
+		assert_eq!(changes(&overlayed), vec![
+			(vec![1], Some(vec![10])),
+			(vec![2], None),
+		].into_iter().collect());
+
+		// the offchain index isn't part of the state
+		assert!(overlayed.storage(&[1]).is_none());
+	}
+
 	#[test]
 	fn overlayed_storage_root_works() {
 		let initial: HashMap<_, _> = vec![
@@ -92,11 +92,24 @@ impl<H: Hasher, N: ChangesTrieBlockNumber> TestExternalities<H, N> {
 		self.backend = self.backend.update(vec![(None, k, Some(v))]);
 	}
 
+	/// Insert key/value into the child trie `storage_key` of the backend.
+	pub fn insert_child(&mut self, storage_key: Vec<u8>, k: Vec<u8>, v: Vec<u8>) {
+		assert!(is_child_storage_key(&storage_key));
+		self.backend = self.backend.update(vec![(Some(storage_key), k, Some(v))]);
+	}
+
 	/// Set offchain externaltiies.
 	pub fn set_offchain_externalities(&mut self, offchain: impl offchain::Externalities + 'static) {
 		self.offchain = Some(Box::new(offchain));
 	}
 
+	/// Returns the pending offchain index changes, `None` values are deletions.
+	pub fn offchain_index(&self) -> HashMap<Vec<u8>, Option<Vec<u8>>> {
+		self.overlay.committed.offchain.clone().into_iter()
+			.chain(self.overlay.prospective.offchain.clone().into_iter())
+			.collect()
+	}
+
 	/// Get mutable reference to changes trie storage.
 	pub fn changes_trie_storage(&mut self) -> &mut ChangesTrieInMemoryStorage<H, N> {
 		&mut self.changes_trie_storage
@@ -198,6 +211,10 @@ impl<H, N> Externalities<H> for TestExternalities<H, N>
 		self.overlay.set_child_storage(storage_key.into_owned(), key, value);
 	}
 
+	fn set_offchain_index(&mut self, key: &[u8], value: Option<&[u8]>) {
+		self.overlay.set_offchain_index(key.to_vec(), value.map(<[u8]>::to_vec));
+	}
+
 	fn kill_child_storage(&mut self, storage_key: ChildStorageKey<H>) {
 		let backend = &self.backend;
 		let overlay = &mut self.overlay;
@@ -290,4 +307,16 @@ mod tests {
 
 		assert_eq!(&ext.storage(CODE).unwrap(), &code);
 	}
+
+	#[test]
+	fn insert_and_retrieve_child_storage() {
+		let mut ext = TestExternalities::<Blake2Hasher, u64>::default();
+		let storage_key = b":child_storage:default:sub".to_vec();
+
+		ext.insert_child(storage_key.clone(), b"doe".to_vec(), b"reindeer".to_vec());
+
+		let child = ChildStorageKey::from_slice(&storage_key).unwrap();
+		assert_eq!(ext.child_storage(child, b"doe"), Some(b"reindeer".to_vec()));
+		assert_ne!(ext.child_storage_root(ChildStorageKey::from_slice(&storage_key).unwrap()), Vec::<u8>::new());
+	}
 }
@@ -91,6 +91,9 @@ pub struct TelemetryConfig {
 /// List of telemetry servers we want to talk to. Contains the URL of the server, and the
 /// maximum verbosity level.
 ///
+/// Each server only receives the messages up to its own verbosity level. Messages are buffered
+/// while a server is unreachable, and we reconnect with an exponential backoff.
+///
 /// The URL string can be either a URL or a multiaddress.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TelemetryEndpoints(Vec<(String, u8)>);
@@ -145,11 +148,15 @@ struct TelemetryDrain {
 /// Please be careful to not call this function twice in the same program. The `slog` crate
 /// doesn't provide any way of knowing whether a global logger has already been registered.
 pub fn init_telemetry(config: TelemetryConfig) -> Telemetry {
-	// Build the list of telemetry endpoints.
-	let mut endpoints = Vec::new();
+	// Build the list of telemetry endpoints. An endpoint given more than once gets a single
+	// connection, with the highest of the verbosity levels.
+	let mut endpoints: Vec<(Multiaddr, u8)> = Vec::new();
 	for &(ref url, verbosity) in &config.endpoints.0 {
 		match url_to_multiaddr(url) {
-			Ok(addr) => endpoints.push((addr, verbosity)),
+			Ok(addr) => match endpoints.iter_mut().find(|(a, _)| *a == addr) {
+				Some((_, v)) => *v = (*v).max(verbosity),
+				None => endpoints.push((addr, verbosity)),
+			},
 			Err(err) => warn!(target: "telemetry", "Invalid telemetry URL {}: {}", url, err),
 		}
 	}
@@ -19,9 +19,9 @@
 //! # Usage
 //!
 //! - Create a `TelemetryWorker` with `TelemetryWorker::new`.
-//! - Send messages to the telemetry with `TelemetryWorker::send_message`. Messages will only be
-//!   sent to the appropriate targets. Messages may be ignored if the target happens to be
-//!   temporarily unreachable.
+//! - Send messages to the telemetry with `TelemetryWorker::log`. Messages will only be
+//!   sent to the appropriate targets. Messages are buffered while a target is unreachable, and
+//!   the oldest ones are dropped if it stays unreachable for too long.
 //! - You must appropriately poll the worker with `TelemetryWorker::poll`. Polling will/may produce
 //!   events indicating what happened since the latest polling.
 //!
@@ -173,8 +173,7 @@ impl TelemetryWorker {
 				continue;
 			}
 
-			// `send_message` returns an error if we're not connected, which we silently ignore.
-			let _ = node.send_message(serialized.clone());
+			node.send_message(serialized.clone());
 		}
 
 		Ok(())
@@ -25,8 +25,15 @@ use log::{trace, debug, warn, error};
 use rand::Rng as _;
 use std::{collections::VecDeque, fmt, mem, pin::Pin, task::Context, task::Poll, time::Duration};
 
-/// Maximum number of pending telemetry messages.
-const MAX_PENDING: usize = 10;
+/// Maximum number of pending telemetry messages. Messages are kept while we are disconnected,
+/// and the oldest ones are dropped once the queue is full.
+const MAX_PENDING: usize = 256;
+
+/// Delay before the first reconnection attempt. It doubles with every failed attempt.
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Upper bound of the delay between two reconnection attempts.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(5 * 60);
 
 /// Handler for a single telemetry node.
 pub struct Node<TTrans: Transport> {
@@ -36,6 +43,12 @@ pub struct Node<TTrans: Transport> {
 	socket: NodeSocket<TTrans>,
 	/// Transport used to establish new connections.
 	transport: TTrans,
+	/// Queue of packets to send. Kept across reconnections.
+	pending: VecDeque<BytesMut>,
+	/// Number of messages dropped because the queue was full since we last connected.
+	dropped: usize,
+	/// Number of connection attempts that failed since we were last connected.
+	failed_attempts: u32,
 }
 
 enum NodeSocket<TTrans: Transport> {
@@ -54,8 +67,6 @@ enum NodeSocket<TTrans: Transport> {
 struct NodeSocketConnected<TTrans: Transport> {
 	/// Where to send data.
 	sink: TTrans::Output,
-	/// Queue of packets to send.
-	pending: VecDeque<BytesMut>,
 	/// If true, we need to flush the sink.
 	need_flush: bool,
 }
@@ -76,6 +87,9 @@ impl<TTrans: Transport> Node<TTrans> {
 			addr,
 			socket: NodeSocket::ReconnectNow,
 			transport,
+			pending: VecDeque::new(),
+			dropped: 0,
+			failed_attempts: 0,
 		}
 	}
 
@@ -88,64 +102,69 @@ impl<TTrans: Transport> Node<TTrans> {
 impl<TTrans: Transport, TSinkErr> Node<TTrans>
 where TTrans: Clone + Unpin, TTrans::Dial: Unpin,
 	TTrans::Output: Sink<BytesMut, Error = TSinkErr> + Stream + Unpin, TSinkErr: fmt::Debug {
-	/// Sends a WebSocket frame to the node. Returns an error if we are not connected to the node.
+	/// Queues a WebSocket frame for the node.
+	///
+	/// Messages are kept while we are not connected and sent once the connection is
+	/// (re-)established. If the queue is full, the oldest message is dropped.
 	///
 	/// After calling this method, you should call `poll` in order for it to be properly processed.
-	pub fn send_message(&mut self, payload: Vec<u8>) -> Result<(), ()> {
-		if let NodeSocket::Connected(NodeSocketConnected { pending, .. }) = &mut self.socket {
-			if pending.len() <= MAX_PENDING {
-				trace!(target: "telemetry", "Adding log entry to queue for {:?}", self.addr);
-				pending.push_back(payload.into());
-				Ok(())
-			} else {
-				warn!(target: "telemetry", "Rejected log entry because queue is full for {:?}",
-					self.addr);
-				Err(())
-			}
-		} else {
-			Err(())
+	pub fn send_message(&mut self, payload: Vec<u8>) {
+		if self.pending.len() >= MAX_PENDING {
+			trace!(target: "telemetry", "Dropping oldest log entry because queue is full for {:?}",
+				self.addr);
+			self.pending.pop_front();
+			self.dropped += 1;
 		}
+		trace!(target: "telemetry", "Adding log entry to queue for {:?}", self.addr);
+		self.pending.push_back(payload.into());
 	}
 
 	/// Polls the node for updates. Must be performed regularly.
 	pub fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<NodeEvent<TSinkErr>> {
-		let mut socket = mem::replace(&mut self.socket, NodeSocket::Poisoned);
-		self.socket = loop {
+		let this = &mut *self;
+		let mut socket = mem::replace(&mut this.socket, NodeSocket::Poisoned);
+		this.socket = loop {
 			match socket {
 				NodeSocket::Connected(mut conn) =>
-					match NodeSocketConnected::poll(Pin::new(&mut conn), cx, &self.addr) {
+					match NodeSocketConnected::poll(Pin::new(&mut conn), cx, &mut this.pending, &this.addr) {
 						Poll::Ready(Ok(v)) => match v {}
 						Poll::Pending => break NodeSocket::Connected(conn),
 						Poll::Ready(Err(err)) => {
-							warn!(target: "telemetry", "Disconnected from {}: {:?}", self.addr, err);
-							let timeout = gen_rand_reconnect_delay();
-							self.socket = NodeSocket::WaitingReconnect(timeout);
+							warn!(target: "telemetry", "Disconnected from {}: {:?}", this.addr, err);
+							let timeout = reconnect_delay(this.failed_attempts);
+							this.socket = NodeSocket::WaitingReconnect(timeout);
 							return Poll::Ready(NodeEvent::Disconnected(err))
 						}
 					}
 				NodeSocket::Dialing(mut s) => match Future::poll(Pin::new(&mut s), cx) {
 					Poll::Ready(Ok(sink)) => {
-						debug!(target: "telemetry", "Connected to {}", self.addr);
-						let conn = NodeSocketConnected { sink, pending: VecDeque::new(), need_flush: false };
-						self.socket = NodeSocket::Connected(conn);
+						debug!(target: "telemetry", "Connected to {}", this.addr);
+						if this.dropped != 0 {
+							warn!(target: "telemetry", "Dropped {} log entries for {} while disconnected",
+								this.dropped, this.addr);
+							this.dropped = 0;
+						}
+						this.failed_attempts = 0;
+						let conn = NodeSocketConnected { sink, need_flush: false };
+						this.socket = NodeSocket::Connected(conn);
 						return Poll::Ready(NodeEvent::Connected)
 					},
 					Poll::Pending => break NodeSocket::Dialing(s),
 					Poll::Ready(Err(err)) => {
-						warn!(target: "telemetry", "Error while dialing {}: {:?}", self.addr, err);
-						let timeout = gen_rand_reconnect_delay();
-						socket = NodeSocket::WaitingReconnect(timeout);
+						warn!(target: "telemetry", "Error while dialing {}: {:?}", this.addr, err);
+						socket = NodeSocket::WaitingReconnect(reconnect_delay(this.failed_attempts));
+						this.failed_attempts = this.failed_attempts.saturating_add(1);
 					}
 				}
-				NodeSocket::ReconnectNow => match self.transport.clone().dial(self.addr.clone()) {
+				NodeSocket::ReconnectNow => match this.transport.clone().dial(this.addr.clone()) {
 					Ok(d) => {
-						debug!(target: "telemetry", "Started dialing {}", self.addr);
+						debug!(target: "telemetry", "Started dialing {}", this.addr);
 						socket = NodeSocket::Dialing(d.compat());
 					}
 					Err(err) => {
-						warn!(target: "telemetry", "Error while dialing {}: {:?}", self.addr, err);
-						let timeout = gen_rand_reconnect_delay();
-						socket = NodeSocket::WaitingReconnect(timeout);
+						warn!(target: "telemetry", "Error while dialing {}: {:?}", this.addr, err);
+						socket = NodeSocket::WaitingReconnect(reconnect_delay(this.failed_attempts));
+						this.failed_attempts = this.failed_attempts.saturating_add(1);
 					}
 				}
 				NodeSocket::WaitingReconnect(mut s) =>
@@ -155,7 +174,7 @@ where TTrans: Clone + Unpin, TTrans::Dial: Unpin,
 						break NodeSocket::WaitingReconnect(s)
 					}
 				NodeSocket::Poisoned => {
-					error!(target: "telemetry", "Poisoned connection with {}", self.addr);
+					error!(target: "telemetry", "Poisoned connection with {}", this.addr);
 					break NodeSocket::Poisoned
 				}
 			}
@@ -165,13 +184,21 @@ where TTrans: Clone + Unpin, TTrans::Dial: Unpin,
 	}
 }
 
-/// Generates a `Delay` object with a random timeout.
+/// Generates a `Delay` object with a random timeout, after `failed_attempts` consecutive
+/// connection attempts have failed.
 ///
-/// If there are general connection issues, not all endpoints should be synchronized in their
-/// re-connection time.
-fn gen_rand_reconnect_delay() -> Delay {
-	let random_delay = rand::thread_rng().gen_range(5, 10);
-	Delay::new(Duration::from_secs(random_delay))
+/// The timeout grows exponentially with the number of failed attempts, up to
+/// `MAX_RECONNECT_DELAY`. If there are general connection issues, not all endpoints should be
+/// synchronized in their re-connection time, hence the randomness.
+fn reconnect_delay(failed_attempts: u32) -> Delay {
+	Delay::new(reconnect_delay_duration(failed_attempts, &mut rand::thread_rng()))
+}
+
+fn reconnect_delay_duration(failed_attempts: u32, rng: &mut impl rand::Rng) -> Duration {
+	let base = INITIAL_RECONNECT_DELAY.as_secs()
+		.saturating_mul(1 << failed_attempts.min(16))
+		.min(MAX_RECONNECT_DELAY.as_secs() / 2);
+	Duration::from_secs(rng.gen_range(base, base * 2))
 }
 
 impl<TTrans: Transport, TSinkErr> NodeSocketConnected<TTrans>
@@ -182,12 +209,13 @@ where TTrans::Output: Sink<BytesMut, Error = TSinkErr> + Stream + Unpin {
 	fn poll(
 		mut self: Pin<&mut Self>,
 		cx: &mut Context,
+		pending: &mut VecDeque<BytesMut>,
 		my_addr: &Multiaddr
 	) -> Poll<Result<futures::never::Never, TSinkErr>> {
 		loop {
-			if let Some(item) = self.pending.pop_front() {
+			if let Some(item) = pending.pop_front() {
 				if let Poll::Pending = Sink::poll_ready(Pin::new(&mut self.sink), cx) {
-					self.pending.push_front(item);
+					pending.push_front(item);
 					return Poll::Pending
 				}
 
@@ -238,3 +266,23 @@ impl<TTrans: Transport> fmt::Debug for Node<TTrans> {
 			.finish()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn reconnect_delay_backs_off_up_to_the_maximum() {
+		let mut rng = rand::thread_rng();
+		for _ in 0..100 {
+			let first = reconnect_delay_duration(0, &mut rng);
+			assert!(first >= INITIAL_RECONNECT_DELAY && first < INITIAL_RECONNECT_DELAY * 2);
+
+			let third = reconnect_delay_duration(2, &mut rng);
+			assert!(third >= INITIAL_RECONNECT_DELAY * 4 && third < INITIAL_RECONNECT_DELAY * 8);
+
+			let late = reconnect_delay_duration(u32::max_value(), &mut rng);
+			assert!(late >= MAX_RECONNECT_DELAY / 2 && late < MAX_RECONNECT_DELAY);
+		}
+	}
+}
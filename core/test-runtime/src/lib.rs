@@ -36,7 +36,7 @@ use substrate_client::{
 };
 use sr_primitives::{
 	ApplyResult, create_runtime_str, Perbill,
-	transaction_validity::{TransactionValidity, ValidTransaction},
+	transaction_validity::{TransactionValidity, ValidTransaction, TransactionSource},
 	traits::{
 		BlindCheckable, BlakeTwo256, Block as BlockT, Extrinsic as ExtrinsicT,
 		GetNodeBlockType, GetRuntimeBlockType, Verify, IdentityLookup
@@ -67,6 +67,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_version: 1,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
+	transaction_version: 1,
 };
 
 fn version() -> RuntimeVersion {
@@ -100,6 +101,15 @@ impl Transfer {
 			.expect("Creates keyring from public key.").sign(&self.encode()).into();
 		Extrinsic::Transfer(self, signature)
 	}
+
+	/// Convert into a signed extrinsic which is rejected with `FullBlock` unless it is the first
+	/// extrinsic of its block.
+	#[cfg(feature = "std")]
+	pub fn into_resources_exhausting_tx(self) -> Extrinsic {
+		let signature = keyring::AccountKeyring::from_public(&self.from)
+			.expect("Creates keyring from public key.").sign(&self.encode()).into();
+		Extrinsic::ExhaustResourcesUnlessFirst(self, signature)
+	}
 }
 
 /// Extrinsic for test-runtime.
@@ -110,6 +120,7 @@ pub enum Extrinsic {
 	Transfer(Transfer, AccountSignature),
 	IncludeData(Vec<u8>),
 	StorageChange(Vec<u8>, Option<Vec<u8>>),
+	ExhaustResourcesUnlessFirst(Transfer, AccountSignature),
 }
 
 #[cfg(feature = "std")]
@@ -134,6 +145,13 @@ impl BlindCheckable for Extrinsic {
 			},
 			Extrinsic::IncludeData(_) => Err(sr_primitives::BAD_SIGNATURE),
 			Extrinsic::StorageChange(key, value) => Ok(Extrinsic::StorageChange(key, value)),
+			Extrinsic::ExhaustResourcesUnlessFirst(transfer, signature) => {
+				if sr_primitives::verify_encoded_lazy(&signature, &transfer, &transfer.from) {
+					Ok(Extrinsic::ExhaustResourcesUnlessFirst(transfer, signature))
+				} else {
+					Err(sr_primitives::BAD_SIGNATURE)
+				}
+			},
 		}
 	}
 }
@@ -158,6 +176,7 @@ impl Extrinsic {
 	pub fn transfer(&self) -> &Transfer {
 		match self {
 			Extrinsic::Transfer(ref transfer, _) => transfer,
+			Extrinsic::ExhaustResourcesUnlessFirst(ref transfer, _) => transfer,
 			_ => panic!("cannot convert to transfer ref"),
 		}
 	}
@@ -336,6 +355,7 @@ parameter_types! {
 	pub const MaximumBlockWeight: u32 = 4 * 1024 * 1024;
 	pub const MaximumBlockLength: u32 = 4 * 1024 * 1024;
 	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+	pub const Version: RuntimeVersion = VERSION;
 }
 
 impl srml_system::Trait for Runtime {
@@ -353,6 +373,9 @@ impl srml_system::Trait for Runtime {
 	type MaximumBlockWeight = MaximumBlockWeight;
 	type MaximumBlockLength = MaximumBlockLength;
 	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = Version;
+	type Call = ();
+	type BaseCallFilter = ();
 }
 
 impl srml_timestamp::Trait for Runtime {
@@ -445,7 +468,10 @@ cfg_if! {
 			}
 
 			impl client_api::TaggedTransactionQueue<Block> for Runtime {
-				fn validate_transaction(utx: <Block as BlockT>::Extrinsic) -> TransactionValidity {
+				fn validate_transaction(
+					_source: TransactionSource,
+					utx: <Block as BlockT>::Extrinsic,
+				) -> TransactionValidity {
 					if let Extrinsic::IncludeData(data) = utx {
 						return TransactionValidity::Valid(ValidTransaction {
 							priority: data.len() as u64,
@@ -605,7 +631,10 @@ cfg_if! {
 			}
 
 			impl client_api::TaggedTransactionQueue<Block> for Runtime {
-				fn validate_transaction(utx: <Block as BlockT>::Extrinsic) -> TransactionValidity {
+				fn validate_transaction(
+					_source: TransactionSource,
+					utx: <Block as BlockT>::Extrinsic,
+				) -> TransactionValidity {
 					if let Extrinsic::IncludeData(data) = utx {
 						return TransactionValidity::Valid(ValidTransaction{
 							priority: data.len() as u64,
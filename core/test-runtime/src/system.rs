@@ -107,7 +107,9 @@ fn execute_block_with_state_root_handler(
 	// execute transactions
 	block.extrinsics.iter().enumerate().for_each(|(i, e)| {
 		storage::unhashed::put(well_known_keys::EXTRINSIC_INDEX, &(i as u32));
+		runtime_io::trace_enter_span("execute_transaction");
 		execute_transaction_backend(e).unwrap_or_else(|_| panic!("Invalid transaction"));
+		runtime_io::trace_exit_span();
 		storage::unhashed::kill(well_known_keys::EXTRINSIC_INDEX);
 	});
 
@@ -244,6 +246,13 @@ fn execute_transaction_backend(utx: &Extrinsic) -> ApplyResult {
 		Extrinsic::AuthoritiesChange(ref new_auth) => execute_new_authorities_backend(new_auth),
 		Extrinsic::IncludeData(_) => Ok(ApplyOutcome::Success),
 		Extrinsic::StorageChange(key, value) => execute_storage_change(key, value.as_ref().map(|v| &**v)),
+		Extrinsic::ExhaustResourcesUnlessFirst(ref transfer, _) => {
+			let extrinsic_index: u32 = storage::unhashed::get(well_known_keys::EXTRINSIC_INDEX).unwrap();
+			if extrinsic_index != 0 {
+				return Err(ApplyError::FullBlock);
+			}
+			execute_transfer_backend(transfer)
+		},
 	}
 }
 
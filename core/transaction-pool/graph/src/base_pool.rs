@@ -33,6 +33,7 @@ use sr_primitives::transaction_validity::{
 	TransactionTag as Tag,
 	TransactionLongevity as Longevity,
 	TransactionPriority as Priority,
+	TransactionSource as Source,
 };
 
 use crate::error;
@@ -57,6 +58,8 @@ pub enum Imported<Hash, Ex> {
 	Future {
 		/// Hash of transaction that was successfully imported.
 		hash: Hash,
+		/// Transactions removed from the Future pool (replaced).
+		removed: Vec<Arc<Transaction<Hash, Ex>>>,
 	}
 }
 
@@ -102,6 +105,8 @@ pub struct Transaction<Hash, Extrinsic> {
 	pub provides: Vec<Tag>,
 	/// Should that transaction be propagated.
 	pub propagate: bool,
+	/// Source of the transaction.
+	pub source: Source,
 }
 
 impl<Hash, Extrinsic> Transaction<Hash, Extrinsic> {
@@ -133,6 +138,7 @@ impl<Hash, Extrinsic> fmt::Debug for Transaction<Hash, Extrinsic> where
 		write!(fmt, "valid_till: {:?}, ", &self.valid_till)?;
 		write!(fmt, "bytes: {:?}, ", &self.bytes)?;
 		write!(fmt, "propagate: {:?}, ", &self.propagate)?;
+		write!(fmt, "source: {:?}, ", &self.source)?;
 		write!(fmt, "requires: [")?;
 		print_tags(fmt, &self.requires)?;
 		write!(fmt, "], provides: [")?;
@@ -207,8 +213,9 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: ::std::fmt::Debug> BasePool<Hash
 		// If all tags are not satisfied import to future.
 		if !tx.is_ready() {
 			let hash = tx.transaction.hash.clone();
+			let removed = self.future.replace_previous(&tx.transaction)?;
 			self.future.import(tx);
-			return Ok(Imported::Future { hash });
+			return Ok(Imported::Future { hash, removed });
 		}
 
 		self.import_to_ready(tx)
@@ -304,6 +311,37 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: ::std::fmt::Debug> BasePool<Hash
 			.collect()
 	}
 
+	/// Returns the number of ready and future transactions given transaction (transitively) depends on.
+	///
+	/// For account-nonce based runtimes that's the number of pending transactions
+	/// of the same sender. The search stops as soon as `limit` dependencies are found,
+	/// so it looks at no more than `limit` transactions whatever the size of the pool.
+	pub fn dependencies_count(&self, tx: &Transaction<Hash, Ex>, limit: usize) -> usize {
+		let ready = self.ready.provided_tags();
+		let mut visited = HashSet::new();
+		let mut to_visit = tx.requires.clone();
+
+		while let Some(tag) = to_visit.pop() {
+			if visited.len() >= limit {
+				break;
+			}
+			if let Some(hash) = ready.get(&tag) {
+				if visited.insert(hash.clone()) {
+					let requires = self.ready.by_hash(&[hash.clone()]).pop().and_then(|tx| tx).map(|tx| tx.requires.clone());
+					to_visit.extend(requires.into_iter().flatten());
+				}
+				continue;
+			}
+			for tx in self.future.providers(&tag) {
+				if visited.insert(tx.hash.clone()) {
+					to_visit.extend(tx.requires.iter().cloned());
+				}
+			}
+		}
+
+		visited.len().min(limit)
+	}
+
 	/// Makes sure that the transactions in the queues stay within provided limits.
 	///
 	/// Removes and returns worst transactions from the queues and all transactions that depend on them.
@@ -484,6 +522,7 @@ mod tests {
 			requires: vec![],
 			provides: vec![vec![1]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 
 		// then
@@ -506,6 +545,7 @@ mod tests {
 			requires: vec![],
 			provides: vec![vec![1]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		pool.import(Transaction {
 			data: vec![1u8],
@@ -516,6 +556,7 @@ mod tests {
 			requires: vec![],
 			provides: vec![vec![1]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap_err();
 
 		// then
@@ -539,6 +580,7 @@ mod tests {
 			requires: vec![vec![0]],
 			provides: vec![vec![1]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		assert_eq!(pool.ready().count(), 0);
 		assert_eq!(pool.ready.len(), 0);
@@ -551,6 +593,7 @@ mod tests {
 			requires: vec![],
 			provides: vec![vec![0]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 
 		// then
@@ -558,6 +601,61 @@ mod tests {
 		assert_eq!(pool.ready.len(), 2);
 	}
 
+	#[test]
+	fn should_replace_future_transaction_with_higher_priority() {
+		// given
+		let mut pool = pool();
+		pool.import(Transaction {
+			data: vec![1u8],
+			bytes: 1,
+			hash: 1,
+			priority: 5u64,
+			valid_till: 64u64,
+			requires: vec![vec![0]],
+			provides: vec![vec![1]],
+			propagate: true,
+			source: Source::External,
+		}).unwrap();
+
+		// when
+		let err = pool.import(Transaction {
+			data: vec![2u8],
+			bytes: 1,
+			hash: 2,
+			priority: 5u64,
+			valid_till: 64u64,
+			requires: vec![vec![0]],
+			provides: vec![vec![1]],
+			propagate: true,
+			source: Source::External,
+		}).unwrap_err();
+		let res = pool.import(Transaction {
+			data: vec![3u8],
+			bytes: 1,
+			hash: 3,
+			priority: 6u64,
+			valid_till: 64u64,
+			requires: vec![vec![0]],
+			provides: vec![vec![1]],
+			propagate: true,
+			source: Source::External,
+		}).unwrap();
+
+		// then
+		match err {
+			error::Error::TooLowPriority { old: 5, new: 5 } => {},
+			e => panic!("Expected TooLowPriority error, got: {:?}", e),
+		}
+		if let Imported::Future { hash, removed } = res {
+			assert_eq!(hash, 3);
+			assert_eq!(removed.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![1]);
+		} else {
+			panic!("Expected transaction to be imported to future: {:?}", res);
+		}
+		assert_eq!(pool.future.len(), 1);
+		assert!(pool.future.contains(&3));
+	}
+
 	#[test]
 	fn should_promote_a_subgraph() {
 		// given
@@ -573,6 +671,7 @@ mod tests {
 			requires: vec![vec![0]],
 			provides: vec![vec![1]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		pool.import(Transaction {
 			data: vec![3u8],
@@ -583,6 +682,7 @@ mod tests {
 			requires: vec![vec![2]],
 			provides: vec![],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		pool.import(Transaction {
 			data: vec![2u8],
@@ -593,6 +693,7 @@ mod tests {
 			requires: vec![vec![1]],
 			provides: vec![vec![3], vec![2]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		pool.import(Transaction {
 			data: vec![4u8],
@@ -603,6 +704,7 @@ mod tests {
 			requires: vec![vec![3], vec![4]],
 			provides: vec![],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		assert_eq!(pool.ready().count(), 0);
 		assert_eq!(pool.ready.len(), 0);
@@ -616,6 +718,7 @@ mod tests {
 			requires: vec![],
 			provides: vec![vec![0], vec![4]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 
 		// then
@@ -648,6 +751,7 @@ mod tests {
 			requires: vec![vec![0]],
 			provides: vec![vec![1]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		pool.import(Transaction {
 			data: vec![3u8],
@@ -658,6 +762,7 @@ mod tests {
 			requires: vec![vec![1]],
 			provides: vec![vec![2]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		assert_eq!(pool.ready().count(), 0);
 		assert_eq!(pool.ready.len(), 0);
@@ -672,6 +777,7 @@ mod tests {
 			requires: vec![vec![2]],
 			provides: vec![vec![0]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 
 		// then
@@ -692,6 +798,7 @@ mod tests {
 			requires: vec![],
 			provides: vec![vec![0]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		let mut it = pool.ready().into_iter().map(|tx| tx.data[0]);
 		assert_eq!(it.next(), Some(4));
@@ -720,6 +827,7 @@ mod tests {
 			requires: vec![vec![0]],
 			provides: vec![vec![1]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		pool.import(Transaction {
 			data: vec![3u8],
@@ -730,6 +838,7 @@ mod tests {
 			requires: vec![vec![1]],
 			provides: vec![vec![2]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		assert_eq!(pool.ready().count(), 0);
 		assert_eq!(pool.ready.len(), 0);
@@ -744,6 +853,7 @@ mod tests {
 			requires: vec![vec![2]],
 			provides: vec![vec![0]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 
 		// then
@@ -764,6 +874,7 @@ mod tests {
 			requires: vec![],
 			provides: vec![vec![0]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap_err();
 		let mut it = pool.ready().into_iter().map(|tx| tx.data[0]);
 		assert_eq!(it.next(), None);
@@ -788,6 +899,7 @@ mod tests {
 			requires: vec![],
 			provides: vec![vec![0], vec![4]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		pool.import(Transaction {
 			data: vec![1u8],
@@ -798,6 +910,7 @@ mod tests {
 			requires: vec![vec![0]],
 			provides: vec![vec![1]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		pool.import(Transaction {
 			data: vec![3u8],
@@ -808,6 +921,7 @@ mod tests {
 			requires: vec![vec![2]],
 			provides: vec![],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		pool.import(Transaction {
 			data: vec![2u8],
@@ -818,6 +932,7 @@ mod tests {
 			requires: vec![vec![1]],
 			provides: vec![vec![3], vec![2]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		pool.import(Transaction {
 			data: vec![4u8],
@@ -828,6 +943,7 @@ mod tests {
 			requires: vec![vec![3], vec![4]],
 			provides: vec![],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		// future
 		pool.import(Transaction {
@@ -839,6 +955,7 @@ mod tests {
 			requires: vec![vec![11]],
 			provides: vec![],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		assert_eq!(pool.ready().count(), 5);
 		assert_eq!(pool.future.len(), 1);
@@ -865,6 +982,7 @@ mod tests {
 			requires: vec![vec![0]],
 			provides: vec![vec![100]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		// ready
 		pool.import(Transaction {
@@ -876,6 +994,7 @@ mod tests {
 			requires: vec![],
 			provides: vec![vec![1]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		pool.import(Transaction {
 			data: vec![2u8],
@@ -886,6 +1005,7 @@ mod tests {
 			requires: vec![vec![2]],
 			provides: vec![vec![3]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		pool.import(Transaction {
 			data: vec![3u8],
@@ -896,6 +1016,7 @@ mod tests {
 			requires: vec![vec![1]],
 			provides: vec![vec![2]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 		pool.import(Transaction {
 			data: vec![4u8],
@@ -906,6 +1027,7 @@ mod tests {
 			requires: vec![vec![3], vec![2]],
 			provides: vec![vec![4]],
 			propagate: true,
+			source: Source::External,
 		}).unwrap();
 
 		assert_eq!(pool.ready().count(), 4);
@@ -941,6 +1063,7 @@ mod tests {
 				requires: vec![vec![3], vec![2]],
 				provides: vec![vec![4]],
 				propagate: true,
+				source: Source::External,
 			}),
 			"Transaction { \
 hash: 4, priority: 1000, valid_till: 64, bytes: 1, propagate: true, \
@@ -959,6 +1082,7 @@ requires: [03,02], provides: [04], data: [4]}".to_owned()
 				requires: vec![vec![3], vec![2]],
 				provides: vec![vec![4]],
 				propagate: true,
+				source: Source::External,
 		}.is_propagateable(), true);
 
 		assert_eq!(Transaction {
@@ -970,6 +1094,46 @@ requires: [03,02], provides: [04], data: [4]}".to_owned()
 				requires: vec![vec![3], vec![2]],
 				provides: vec![vec![4]],
 				propagate: false,
+				source: Source::External,
 		}.is_propagateable(), false);
 	}
+
+	#[test]
+	fn should_count_dependencies_through_both_queues() {
+		// given
+		let mut pool = pool();
+		let tx = |hash: u64, requires: Vec<Tag>, provides: Vec<Tag>| Transaction {
+			data: vec![hash as u8],
+			bytes: 1,
+			hash,
+			priority: 5u64,
+			valid_till: 64u64,
+			requires,
+			provides,
+			propagate: true,
+			source: Source::External,
+		};
+		pool.import(tx(3, vec![vec![2]], vec![vec![3]])).unwrap();
+		pool.import(tx(4, vec![vec![3]], vec![vec![4]])).unwrap();
+		let new = tx(5, vec![vec![4]], vec![vec![5]]);
+
+		// then
+		assert_eq!(pool.futures().count(), 2);
+		assert_eq!(pool.dependencies_count(&new, 8), 2);
+		assert_eq!(pool.dependencies_count(&new, 1), 1);
+
+		// when the futures become ready
+		pool.import(tx(1, vec![], vec![vec![2]])).unwrap();
+
+		// then
+		assert_eq!(pool.ready().count(), 3);
+		assert_eq!(pool.future.providers(&vec![3]).count(), 0);
+		assert_eq!(pool.dependencies_count(&new, 8), 3);
+
+		// when they are removed
+		pool.remove_invalid(&[1]);
+
+		// then
+		assert_eq!(pool.dependencies_count(&new, 8), 0);
+	}
 }
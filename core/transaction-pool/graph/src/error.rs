@@ -47,6 +47,9 @@ pub enum Error {
 	/// Deps cycle etected and we couldn't import transaction.
 	#[display(fmt="Cycle Detected")]
 	CycleDetected,
+	/// Transaction depends on too many other transactions in the pool.
+	#[display(fmt="Too many pending dependencies (limit: {})", _0)]
+	TooManyDependencies(usize),
 	/// Transaction was dropped immediately after it got inserted.
 	#[display(fmt="Transaction couldn't enter the pool because of the limit.")]
 	ImmediatelyDropped,
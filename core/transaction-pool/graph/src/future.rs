@@ -28,6 +28,7 @@ use sr_primitives::transaction_validity::{
 };
 
 use crate::base_pool::Transaction;
+use crate::error;
 
 /// Transaction with partially satisfied dependencies.
 pub struct WaitingTransaction<Hash, Ex> {
@@ -115,6 +116,8 @@ pub struct FutureTransactions<Hash: hash::Hash + Eq, Ex> {
 	wanted_tags: HashMap<Tag, HashSet<Hash>>,
 	/// Transactions waiting for a particular other transaction
 	waiting: HashMap<Hash, WaitingTransaction<Hash, Ex>>,
+	/// Transactions providing a particular tag
+	provided_tags: HashMap<Tag, HashSet<Hash>>,
 }
 
 impl<Hash: hash::Hash + Eq, Ex> Default for FutureTransactions<Hash, Ex> {
@@ -122,6 +125,7 @@ impl<Hash: hash::Hash + Eq, Ex> Default for FutureTransactions<Hash, Ex> {
 		FutureTransactions {
 			wanted_tags: Default::default(),
 			waiting: Default::default(),
+			provided_tags: Default::default(),
 		}
 	}
 }
@@ -133,6 +137,13 @@ every hash from `wanted_tags` is always present in `waiting`;
 qed
 #";
 
+const PROVIDED_PROOF: &str = r"#
+In import we always insert to `waiting` if we push to `provided_tags`;
+when removing from `waiting` we always clear `provided_tags`;
+every hash from `provided_tags` is always present in `waiting`;
+qed
+#";
+
 impl<Hash: hash::Hash + Eq + Clone, Ex> FutureTransactions<Hash, Ex> {
 	/// Import transaction to Future queue.
 	///
@@ -150,15 +161,68 @@ impl<Hash: hash::Hash + Eq + Clone, Ex> FutureTransactions<Hash, Ex> {
 			entry.insert(tx.transaction.hash.clone());
 		}
 
+		// Index the tags it provides
+		for tag in &tx.transaction.provides {
+			let entry = self.provided_tags.entry(tag.clone()).or_insert_with(HashSet::new);
+			entry.insert(tx.transaction.hash.clone());
+		}
+
 		// Add the transaction to a by-hash waiting map
 		self.waiting.insert(tx.transaction.hash.clone(), tx);
 	}
 
+	/// Removes transactions that provide the same tags as given one.
+	///
+	/// Transactions providing the same tag (e.g. the same sender and nonce) can't
+	/// both end up in a block, so the new transaction replaces the old ones,
+	/// but only if its priority is strictly higher than the sum of their priorities.
+	///
+	/// Returns a list of removed (replaced) transactions.
+	pub fn replace_previous(
+		&mut self,
+		tx: &Transaction<Hash, Ex>,
+	) -> error::Result<Vec<Arc<Transaction<Hash, Ex>>>> {
+		let replace_hashes = tx.provides
+			.iter()
+			.filter_map(|tag| self.provided_tags.get(tag))
+			.flatten()
+			.cloned()
+			.collect::<HashSet<_>>()
+			.into_iter()
+			.collect::<Vec<_>>();
+
+		// early exit if we are not replacing anything.
+		if replace_hashes.is_empty() {
+			return Ok(vec![]);
+		}
+
+		let old_priority = replace_hashes
+			.iter()
+			.filter_map(|hash| self.waiting.get(hash))
+			.fold(0u64, |total, waiting| total.saturating_add(waiting.transaction.priority));
+
+		// bail - the transaction has too low priority to replace the old ones
+		if old_priority >= tx.priority {
+			return Err(error::Error::TooLowPriority { old: old_priority, new: tx.priority })
+		}
+
+		Ok(self.remove(&replace_hashes))
+	}
+
 	/// Returns true if given hash is part of the queue.
 	pub fn contains(&self, hash: &Hash) -> bool {
 		self.waiting.contains_key(hash)
 	}
 
+	/// Returns the transactions providing given tag.
+	pub fn providers(&self, tag: &Tag) -> impl Iterator<Item=&Transaction<Hash, Ex>> {
+		self.provided_tags
+			.get(tag)
+			.into_iter()
+			.flatten()
+			.map(move |hash| &*self.waiting.get(hash).expect(PROVIDED_PROOF).transaction)
+	}
+
 	/// Returns a list of known transactions
 	pub fn by_hash(&self, hashes: &[Hash]) -> Vec<Option<Arc<Transaction<Hash, Ex>>>> {
 		hashes.iter().map(|h| self.waiting.get(h).map(|x| x.transaction.clone())).collect()
@@ -182,6 +246,7 @@ impl<Hash: hash::Hash + Eq + Clone, Ex> FutureTransactions<Hash, Ex> {
 
 					if is_ready {
 						let tx = self.waiting.remove(&hash).expect(WAITING_PROOF);
+						self.unindex_provided(&tx.transaction);
 						became_ready.push(tx);
 					}
 				}
@@ -208,6 +273,7 @@ impl<Hash: hash::Hash + Eq + Clone, Ex> FutureTransactions<Hash, Ex> {
 						self.wanted_tags.remove(&tag);
 					}
 				}
+				self.unindex_provided(&waiting_tx.transaction);
 				// add to result
 				removed.push(waiting_tx.transaction)
 			}
@@ -215,6 +281,19 @@ impl<Hash: hash::Hash + Eq + Clone, Ex> FutureTransactions<Hash, Ex> {
 		removed
 	}
 
+	/// Removes the tags provided by a transaction leaving the queue from the index.
+	fn unindex_provided(&mut self, tx: &Transaction<Hash, Ex>) {
+		for tag in &tx.provides {
+			let remove = if let Some(providers) = self.provided_tags.get_mut(tag) {
+				providers.remove(&tx.hash);
+				providers.is_empty()
+			} else { false };
+			if remove {
+				self.provided_tags.remove(tag);
+			}
+		}
+	}
+
 	/// Fold a list of future transactions to compute a single value.
 	pub fn fold<R, F: FnMut(Option<R>, &WaitingTransaction<Hash, Ex>) -> Option<R>>(&mut self, f: F) -> Option<R> {
 		self.waiting
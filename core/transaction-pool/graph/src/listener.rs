@@ -19,25 +19,38 @@ use std::{
 	collections::HashMap,
 	hash,
 };
+use futures::sync::mpsc;
 use serde::Serialize;
 use crate::watcher;
 use sr_primitives::traits;
 use log::warn;
 
+/// Stream of status updates of all transactions in the pool.
+pub type TransactionEvents<H, H2> = mpsc::UnboundedReceiver<(H, watcher::Status<H, H2>)>;
+
 /// Extrinsic pool default listener.
 pub struct Listener<H: hash::Hash + Eq, H2> {
-	watchers: HashMap<H, watcher::Sender<H, H2>>
+	watchers: HashMap<H, watcher::Sender<H, H2>>,
+	event_sinks: Vec<mpsc::UnboundedSender<(H, watcher::Status<H, H2>)>>,
 }
 
 impl<H: hash::Hash + Eq, H2> Default for Listener<H, H2> {
 	fn default() -> Self {
 		Listener {
 			watchers: Default::default(),
+			event_sinks: Default::default(),
 		}
 	}
 }
 
 impl<H: hash::Hash + traits::Member + Serialize, H2: Clone> Listener<H, H2> {
+	fn notify(&mut self, hash: &H, status: watcher::Status<H, H2>) {
+		if self.event_sinks.is_empty() {
+			return;
+		}
+		self.event_sinks.retain(|sink| sink.unbounded_send((hash.clone(), status.clone())).is_ok());
+	}
+
 	fn fire<F>(&mut self, hash: &H, fun: F) where F: FnOnce(&mut watcher::Sender<H, H2>) {
 		let clean = if let Some(h) = self.watchers.get_mut(hash) {
 			fun(h);
@@ -51,6 +64,13 @@ impl<H: hash::Hash + traits::Member + Serialize, H2: Clone> Listener<H, H2> {
 		}
 	}
 
+	/// Returns a stream of status updates of all transactions in the pool.
+	pub fn event_stream(&mut self) -> TransactionEvents<H, H2> {
+		let (sink, stream) = mpsc::unbounded();
+		self.event_sinks.push(sink);
+		stream
+	}
+
 	/// Creates a new watcher for given verified extrinsic.
 	///
 	/// The watcher can be used to subscribe to lifecycle events of that extrinsic.
@@ -61,24 +81,32 @@ impl<H: hash::Hash + traits::Member + Serialize, H2: Clone> Listener<H, H2> {
 
 	/// Notify the listeners about extrinsic broadcast.
 	pub fn broadcasted(&mut self, hash: &H, peers: Vec<String>) {
+		self.notify(hash, watcher::Status::Broadcast(peers.clone()));
 		self.fire(hash, |watcher| watcher.broadcast(peers));
 	}
 
 	/// New transaction was added to the ready pool or promoted from the future pool.
 	pub fn ready(&mut self, tx: &H, old: Option<&H>) {
+		self.notify(tx, watcher::Status::Ready);
 		self.fire(tx, |watcher| watcher.ready());
 		if let Some(old) = old {
+			self.notify(old, watcher::Status::Usurped(tx.clone()));
 			self.fire(old, |watcher| watcher.usurped(tx.clone()));
 		}
 	}
 
 	/// New transaction was added to the future pool.
 	pub fn future(&mut self, tx: &H) {
+		self.notify(tx, watcher::Status::Future);
 		self.fire(tx, |watcher| watcher.future());
 	}
 
 	/// Transaction was dropped from the pool because of the limit.
 	pub fn dropped(&mut self, tx: &H, by: Option<&H>) {
+		self.notify(tx, match by {
+			Some(t) => watcher::Status::Usurped(t.clone()),
+			None => watcher::Status::Dropped,
+		});
 		self.fire(tx, |watcher| match by {
 			Some(t) => watcher.usurped(t.clone()),
 			None => watcher.dropped(),
@@ -88,11 +116,13 @@ impl<H: hash::Hash + traits::Member + Serialize, H2: Clone> Listener<H, H2> {
 	/// Transaction was removed as invalid.
 	pub fn invalid(&mut self, tx: &H) {
 		warn!(target: "transaction-pool", "Extrinsic invalid: {:?}", tx);
+		self.notify(tx, watcher::Status::Invalid);
 		self.fire(tx, |watcher| watcher.invalid());
 	}
 
 	/// Transaction was pruned from the pool.
 	pub fn pruned(&mut self, header_hash: H2, tx: &H) {
+		self.notify(tx, watcher::Status::Finalized(header_hash.clone()));
 		self.fire(tx, |watcher| watcher.finalized(header_hash))
 	}
 }
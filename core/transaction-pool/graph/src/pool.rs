@@ -34,10 +34,11 @@ use parking_lot::{Mutex, RwLock};
 use sr_primitives::{
 	generic::BlockId,
 	traits::{self, SaturatedConversion},
-	transaction_validity::{TransactionValidity, TransactionTag as Tag},
+	transaction_validity::{TransactionValidity, TransactionTag as Tag, TransactionSource},
 };
 
 pub use crate::base_pool::Limit;
+pub use crate::listener::TransactionEvents;
 
 /// Modification notification event stream type;
 pub type EventStream = mpsc::UnboundedReceiver<()>;
@@ -63,7 +64,12 @@ pub trait ChainApi: Send + Sync {
 	type Error: From<error::Error> + error::IntoPoolError;
 
 	/// Verify extrinsic at given block.
-	fn validate_transaction(&self, at: &BlockId<Self::Block>, uxt: ExtrinsicFor<Self>) -> Result<TransactionValidity, Self::Error>;
+	fn validate_transaction(
+		&self,
+		at: &BlockId<Self::Block>,
+		source: TransactionSource,
+		uxt: ExtrinsicFor<Self>,
+	) -> Result<TransactionValidity, Self::Error>;
 
 	/// Returns a block number given the block id.
 	fn block_id_to_number(&self, at: &BlockId<Self::Block>) -> Result<Option<NumberFor<Self>>, Self::Error>;
@@ -82,6 +88,12 @@ pub struct Options {
 	pub ready: Limit,
 	/// Future queue limits.
 	pub future: Limit,
+	/// Maximal number of ready and future transactions a single transaction may depend on,
+	/// `0` means unlimited.
+	///
+	/// For account-nonce based runtimes this limits the number of pending
+	/// transactions of a single sender.
+	pub per_sender: usize,
 }
 
 impl Default for Options {
@@ -95,6 +107,7 @@ impl Default for Options {
 				count: 128,
 				total_bytes: 1 * 1024 * 1024,
 			},
+			per_sender: 64,
 		}
 	}
 }
@@ -110,11 +123,18 @@ pub struct Pool<B: ChainApi> {
 	>>,
 	import_notification_sinks: Mutex<Vec<mpsc::UnboundedSender<()>>>,
 	rotator: PoolRotator<ExHash<B>>,
+	/// Where the next revalidation starts, in the ready then future transactions.
+	revalidation_cursor: Mutex<usize>,
 }
 
 impl<B: ChainApi> Pool<B> {
-	/// Imports a bunch of unverified extrinsics to the pool
-	pub fn submit_at<T>(&self, at: &BlockId<B::Block>, xts: T) -> Result<Vec<Result<ExHash<B>, B::Error>>, B::Error> where
+	/// Imports a bunch of unverified extrinsics coming from given source to the pool
+	pub fn submit_at<T>(
+		&self,
+		at: &BlockId<B::Block>,
+		source: TransactionSource,
+		xts: T,
+	) -> Result<Vec<Result<ExHash<B>, B::Error>>, B::Error> where
 		T: IntoIterator<Item=ExtrinsicFor<B>>
 	{
 		let block_number = self.api.block_id_to_number(at)?
@@ -128,7 +148,7 @@ impl<B: ChainApi> Pool<B> {
 					return Err(error::Error::TemporarilyBanned.into())
 				}
 
-				match self.api.validate_transaction(at, xt.clone())? {
+				match self.api.validate_transaction(at, source, xt.clone())? {
 					TransactionValidity::Valid(validity) => {
 						Ok(base::Transaction {
 							data: xt,
@@ -139,6 +159,7 @@ impl<B: ChainApi> Pool<B> {
 							requires: validity.requires,
 							provides: validity.provides,
 							propagate: validity.propagate,
+							source,
 							valid_till: block_number
 								.saturated_into::<u64>()
 								.saturating_add(validity.longevity),
@@ -154,10 +175,20 @@ impl<B: ChainApi> Pool<B> {
 				}
 			})
 			.map(|tx| {
-				let imported = self.pool.write().import(tx?)?;
+				let tx = tx?;
+				let per_sender = self.options.per_sender;
+				let imported = {
+					// count and import under the same lock, so that concurrent submissions
+					// can't exceed the limit
+					let mut pool = self.pool.write();
+					if per_sender != 0 && pool.dependencies_count(&tx, per_sender) >= per_sender {
+						return Err(error::Error::TooManyDependencies(per_sender).into());
+					}
+					pool.import(tx)?
+				};
 
 				if let base::Imported::Ready { .. } = imported {
-					self.import_notification_sinks.lock().retain(|sink| sink.unbounded_send(()).is_ok());
+					self.notify_ready_imported();
 				}
 
 				let mut listener = self.listener.write();
@@ -205,15 +236,25 @@ impl<B: ChainApi> Pool<B> {
 	}
 
 	/// Imports one unverified extrinsic to the pool
-	pub fn submit_one(&self, at: &BlockId<B::Block>, xt: ExtrinsicFor<B>) -> Result<ExHash<B>, B::Error> {
-		Ok(self.submit_at(at, ::std::iter::once(xt))?.pop().expect("One extrinsic passed; one result returned; qed")?)
+	pub fn submit_one(
+		&self,
+		at: &BlockId<B::Block>,
+		source: TransactionSource,
+		xt: ExtrinsicFor<B>,
+	) -> Result<ExHash<B>, B::Error> {
+		Ok(self.submit_at(at, source, ::std::iter::once(xt))?.pop().expect("One extrinsic passed; one result returned; qed")?)
 	}
 
 	/// Import a single extrinsic and starts to watch their progress in the pool.
-	pub fn submit_and_watch(&self, at: &BlockId<B::Block>, xt: ExtrinsicFor<B>) -> Result<Watcher<ExHash<B>, BlockHash<B>>, B::Error> {
+	pub fn submit_and_watch(
+		&self,
+		at: &BlockId<B::Block>,
+		source: TransactionSource,
+		xt: ExtrinsicFor<B>,
+	) -> Result<Watcher<ExHash<B>, BlockHash<B>>, B::Error> {
 		let hash = self.api.hash_and_length(&xt).0;
 		let watcher = self.listener.write().create_watcher(hash);
-		self.submit_one(at, xt)?;
+		self.submit_one(at, source, xt)?;
 		Ok(watcher)
 	}
 
@@ -241,7 +282,7 @@ impl<B: ChainApi> Pool<B> {
 					// if it's not found in the pool query the runtime at parent block
 					// to get validity info and tags that the extrinsic provides.
 					None => {
-						let validity = self.api.validate_transaction(parent, extrinsic.clone());
+						let validity = self.api.validate_transaction(parent, TransactionSource::InBlock, extrinsic.clone());
 						match validity {
 							Ok(TransactionValidity::Valid(mut validity)) => {
 								tags.append(&mut validity.provides);
@@ -293,6 +334,12 @@ impl<B: ChainApi> Pool<B> {
 			for promoted in &status.promoted {
 				fire_events(&mut *listener, promoted);
 			}
+			if status.promoted.iter().any(|imported| match imported {
+				base::Imported::Ready { .. } => true,
+				base::Imported::Future { .. } => false,
+			}) {
+				self.notify_ready_imported();
+			}
 			for f in &status.failed {
 				listener.dropped(f, None);
 			}
@@ -305,7 +352,11 @@ impl<B: ChainApi> Pool<B> {
 		// try to re-submit pruned transactions since some of them might be still valid.
 		// note that `known_imported_hashes` will be rejected here due to temporary ban.
 		let hashes = status.pruned.iter().map(|tx| tx.hash.clone()).collect::<Vec<_>>();
-		let results = self.submit_at(at, status.pruned.into_iter().map(|tx| tx.data.clone()))?;
+		let results = self.submit_at(
+			at,
+			TransactionSource::InBlock,
+			status.pruned.into_iter().map(|tx| tx.data.clone()),
+		)?;
 
 		// Collect the hashes of transactions that now became invalid (meaning that they are successfully pruned).
 		let hashes = results.into_iter().enumerate().filter_map(|(idx, r)| match r.map_err(error::IntoPoolError::into_pool_error) {
@@ -329,9 +380,50 @@ impl<B: ChainApi> Pool<B> {
 		Ok(())
 	}
 
+	/// Re-validates transactions in the pool against the state at given block.
+	///
+	/// At most `max` transactions are checked, ready ones (ordered by priority) first.
+	/// Each call starts where the previous one stopped and wraps around, so all the
+	/// transactions are eventually checked even when there are more than `max` of them.
+	/// Transactions that are no longer valid (e.g. their nonce was used or the sender
+	/// can't pay the fees anymore) are removed from the pool and temporarily banned.
+	pub fn revalidate(&self, at: &BlockId<B::Block>, max: usize) -> Result<(), B::Error> {
+		let to_revalidate = {
+			let pool = self.pool.read();
+			let mut all = pool.ready()
+				.map(|tx| (tx.hash.clone(), tx.source, tx.data.clone()))
+				.chain(pool.futures().map(|tx| (tx.hash.clone(), tx.source, tx.data.clone())))
+				.collect::<Vec<_>>();
+			let mut cursor = self.revalidation_cursor.lock();
+			if *cursor >= all.len() {
+				*cursor = 0;
+			}
+			all.rotate_left(*cursor);
+			all.truncate(max);
+			*cursor += all.len();
+			all
+		};
+
+		let mut invalid = Vec::new();
+		for (hash, source, xt) in to_revalidate {
+			match self.api.validate_transaction(at, source, xt)? {
+				TransactionValidity::Invalid(_) => invalid.push(hash),
+				TransactionValidity::Valid(_) | TransactionValidity::Unknown(_) => {},
+			}
+		}
+
+		if !invalid.is_empty() {
+			debug!(target: "txpool", "Revalidation found {} invalid transactions", invalid.len());
+			self.remove_invalid(&invalid);
+		}
+
+		Ok(())
+	}
+
 	/// Removes stale transactions from the pool.
 	///
 	/// Stale transactions are transaction beyond their longevity period.
+	/// They are temporarily banned and reported as `Dropped` to their watchers.
 	/// Note this function does not remove transactions that are already included in the chain.
 	/// See `prune_tags` if you want this.
 	pub fn clear_stale(&self, at: &BlockId<B::Block>) -> Result<(), B::Error> {
@@ -356,8 +448,16 @@ impl<B: ChainApi> Pool<B> {
 			hashes
 		};
 		// removing old transactions
-		self.remove_invalid(&to_remove);
-		self.remove_invalid(&futures_to_remove);
+		let stale = self.pool.write().remove_invalid(
+			&to_remove.into_iter().chain(futures_to_remove).collect::<Vec<_>>()
+		);
+		{
+			let mut listener = self.listener.write();
+			for tx in &stale {
+				debug!(target: "txpool", "[{:?}] Dropping stale transaction (valid till {})", tx.hash, tx.valid_till);
+				listener.dropped(&tx.hash, None);
+			}
+		}
 		// clear banned transactions timeouts
 		self.rotator.clear_timeouts(&now);
 
@@ -373,16 +473,32 @@ impl<B: ChainApi> Pool<B> {
 			pool: Default::default(),
 			import_notification_sinks: Default::default(),
 			rotator: Default::default(),
+			revalidation_cursor: Default::default(),
 		}
 	}
 
 	/// Return an event stream of transactions imported to the pool.
+	///
+	/// A notification is sent every time new transactions become ready, so block
+	/// authors may wait on this stream instead of polling the pool.
 	pub fn import_notification_stream(&self) -> EventStream {
 		let (sink, stream) = mpsc::unbounded();
 		self.import_notification_sinks.lock().push(sink);
 		stream
 	}
 
+	/// Return a stream of status updates of all transactions in the pool.
+	///
+	/// Unlike `submit_and_watch` the stream is not bound to a single transaction
+	/// and reports every transaction entering, moving within or leaving the pool.
+	pub fn transaction_events(&self) -> TransactionEvents<ExHash<B>, BlockHash<B>> {
+		self.listener.write().event_stream()
+	}
+
+	fn notify_ready_imported(&self) {
+		self.import_notification_sinks.lock().retain(|sink| sink.unbounded_send(()).is_ok());
+	}
+
 	/// Invoked when extrinsics are broadcasted.
 	pub fn on_broadcasted(&self, propagated: HashMap<ExHash<B>, Vec<String>>) {
 		let mut listener = self.listener.write();
@@ -412,6 +528,13 @@ impl<B: ChainApi> Pool<B> {
 		self.pool.read().ready()
 	}
 
+	/// Returns future transactions (the ones that have some requirements not satisfied yet).
+	pub fn futures(&self) -> Vec<TransactionFor<B>> {
+		let pool = self.pool.read();
+		let hashes = pool.futures().map(|tx| tx.hash.clone()).collect::<Vec<_>>();
+		pool.by_hash(&hashes).into_iter().filter_map(|tx| tx).collect()
+	}
+
 	/// Returns pool status.
 	pub fn status(&self) -> base::Status {
 		self.pool.read().status()
@@ -443,8 +566,11 @@ fn fire_events<H, H2, Ex>(
 				listener.ready(p, None);
 			}
 		},
-		base::Imported::Future { ref hash } => {
-			listener.future(hash)
+		base::Imported::Future { ref hash, ref removed } => {
+			listener.future(hash);
+			for r in removed {
+				listener.dropped(&r.hash, Some(hash));
+			}
 		},
 	}
 }
@@ -462,6 +588,7 @@ mod tests {
 	#[derive(Debug, Default)]
 	struct TestApi {
 		delay: Mutex<Option<std::sync::mpsc::Receiver<()>>>,
+		validated: Mutex<Vec<u64>>,
 	}
 
 	impl ChainApi for TestApi {
@@ -470,10 +597,16 @@ mod tests {
 		type Error = error::Error;
 
 		/// Verify extrinsic at given block.
-		fn validate_transaction(&self, at: &BlockId<Self::Block>, uxt: ExtrinsicFor<Self>) -> Result<TransactionValidity, Self::Error> {
+		fn validate_transaction(
+			&self,
+			at: &BlockId<Self::Block>,
+			_source: TransactionSource,
+			uxt: ExtrinsicFor<Self>,
+		) -> Result<TransactionValidity, Self::Error> {
 
 			let block_number = self.block_id_to_number(at)?.unwrap();
 			let nonce = uxt.transfer().nonce;
+			self.validated.lock().push(nonce);
 
 			// This is used to control the test flow.
 			if nonce > 0 {
@@ -539,7 +672,7 @@ mod tests {
 		let pool = pool();
 
 		// when
-		let hash = pool.submit_one(&BlockId::Number(0), uxt(Transfer {
+		let hash = pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
 			from: AccountId::from_h256(H256::from_low_u64_be(1)),
 			to: AccountId::from_h256(H256::from_low_u64_be(2)),
 			amount: 5,
@@ -550,6 +683,23 @@ mod tests {
 		assert_eq!(pool.ready().map(|v| v.hash).collect::<Vec<_>>(), vec![hash]);
 	}
 
+	#[test]
+	fn should_remember_transaction_source() {
+		// given
+		let pool = pool();
+
+		// when
+		pool.submit_one(&BlockId::Number(0), TransactionSource::Local, uxt(Transfer {
+			from: AccountId::from_h256(H256::from_low_u64_be(1)),
+			to: AccountId::from_h256(H256::from_low_u64_be(2)),
+			amount: 5,
+			nonce: 0,
+		})).unwrap();
+
+		// then
+		assert_eq!(pool.ready().map(|v| v.source).collect::<Vec<_>>(), vec![TransactionSource::Local]);
+	}
+
 	#[test]
 	fn should_reject_if_temporarily_banned() {
 		// given
@@ -563,7 +713,7 @@ mod tests {
 
 		// when
 		pool.rotator.ban(&time::Instant::now(), vec![pool.hash_of(&uxt)]);
-		let res = pool.submit_one(&BlockId::Number(0), uxt);
+		let res = pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt);
 		assert_eq!(pool.status().ready, 0);
 		assert_eq!(pool.status().future, 0);
 
@@ -579,20 +729,20 @@ mod tests {
 			let stream = pool.import_notification_stream();
 
 			// when
-			let _hash = pool.submit_one(&BlockId::Number(0), uxt(Transfer {
+			let _hash = pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
 				from: AccountId::from_h256(H256::from_low_u64_be(1)),
 				to: AccountId::from_h256(H256::from_low_u64_be(2)),
 				amount: 5,
 				nonce: 0,
 			})).unwrap();
-			let _hash = pool.submit_one(&BlockId::Number(0), uxt(Transfer {
+			let _hash = pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
 				from: AccountId::from_h256(H256::from_low_u64_be(1)),
 				to: AccountId::from_h256(H256::from_low_u64_be(2)),
 				amount: 5,
 				nonce: 1,
 			})).unwrap();
 			// future doesn't count
-			let _hash = pool.submit_one(&BlockId::Number(0), uxt(Transfer {
+			let _hash = pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
 				from: AccountId::from_h256(H256::from_low_u64_be(1)),
 				to: AccountId::from_h256(H256::from_low_u64_be(2)),
 				amount: 5,
@@ -615,19 +765,19 @@ mod tests {
 	fn should_clear_stale_transactions() {
 		// given
 		let pool = pool();
-		let hash1 = pool.submit_one(&BlockId::Number(0), uxt(Transfer {
+		let hash1 = pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
 			from: AccountId::from_h256(H256::from_low_u64_be(1)),
 			to: AccountId::from_h256(H256::from_low_u64_be(2)),
 			amount: 5,
 			nonce: 0,
 		})).unwrap();
-		let hash2 = pool.submit_one(&BlockId::Number(0), uxt(Transfer {
+		let hash2 = pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
 			from: AccountId::from_h256(H256::from_low_u64_be(1)),
 			to: AccountId::from_h256(H256::from_low_u64_be(2)),
 			amount: 5,
 			nonce: 1,
 		})).unwrap();
-		let hash3 = pool.submit_one(&BlockId::Number(0), uxt(Transfer {
+		let hash3 = pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
 			from: AccountId::from_h256(H256::from_low_u64_be(1)),
 			to: AccountId::from_h256(H256::from_low_u64_be(2)),
 			amount: 5,
@@ -647,11 +797,69 @@ mod tests {
 		assert!(pool.rotator.is_banned(&hash3));
 	}
 
+	#[test]
+	fn should_revalidate_transactions() {
+		// given
+		let pool = pool();
+		let hash1 = pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
+			from: AccountId::from_h256(H256::from_low_u64_be(1)),
+			to: AccountId::from_h256(H256::from_low_u64_be(2)),
+			amount: 5,
+			nonce: 0,
+		})).unwrap();
+		let hash2 = pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
+			from: AccountId::from_h256(H256::from_low_u64_be(1)),
+			to: AccountId::from_h256(H256::from_low_u64_be(2)),
+			amount: 5,
+			nonce: 2,
+		})).unwrap();
+		assert_eq!(pool.status().ready, 1);
+		assert_eq!(pool.status().future, 1);
+
+		// when
+		pool.revalidate(&BlockId::Number(1), 16).unwrap();
+
+		// then
+		assert_eq!(pool.status().ready, 0);
+		assert_eq!(pool.status().future, 1);
+		assert!(pool.rotator.is_banned(&hash1));
+		assert!(!pool.rotator.is_banned(&hash2));
+	}
+
+	#[test]
+	fn should_revalidate_all_transactions_across_calls() {
+		// given
+		let pool = pool();
+		for nonce in 0..3 {
+			pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
+				from: AccountId::from_h256(H256::from_low_u64_be(1)),
+				to: AccountId::from_h256(H256::from_low_u64_be(2)),
+				amount: 5,
+				nonce,
+			})).unwrap();
+		}
+		assert_eq!(pool.status().ready, 3);
+		let revalidate = || {
+			pool.api.validated.lock().clear();
+			pool.revalidate(&BlockId::Number(0), 2).unwrap();
+			pool.api.validated.lock().clone()
+		};
+
+		// when
+		let first = revalidate();
+		let second = revalidate();
+
+		// then
+		assert_eq!(first, vec![0, 1]);
+		assert_eq!(second, vec![2, 0]);
+		assert_eq!(pool.status().ready, 3);
+	}
+
 	#[test]
 	fn should_ban_mined_transactions() {
 		// given
 		let pool = pool();
-		let hash1 = pool.submit_one(&BlockId::Number(0), uxt(Transfer {
+		let hash1 = pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
 			from: AccountId::from_h256(H256::from_low_u64_be(1)),
 			to: AccountId::from_h256(H256::from_low_u64_be(2)),
 			amount: 5,
@@ -675,9 +883,10 @@ mod tests {
 		let pool = Pool::new(Options {
 			ready: limit.clone(),
 			future: limit.clone(),
+			..Default::default()
 		}, TestApi::default());
 
-		let hash1 = pool.submit_one(&BlockId::Number(0), uxt(Transfer {
+		let hash1 = pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
 			from: AccountId::from_h256(H256::from_low_u64_be(1)),
 			to: AccountId::from_h256(H256::from_low_u64_be(2)),
 			amount: 5,
@@ -686,7 +895,7 @@ mod tests {
 		assert_eq!(pool.status().future, 1);
 
 		// when
-		let hash2 = pool.submit_one(&BlockId::Number(0), uxt(Transfer {
+		let hash2 = pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
 			from: AccountId::from_h256(H256::from_low_u64_be(2)),
 			to: AccountId::from_h256(H256::from_low_u64_be(2)),
 			amount: 5,
@@ -699,6 +908,77 @@ mod tests {
 		assert!(!pool.rotator.is_banned(&hash2));
 	}
 
+	#[test]
+	fn should_limit_dependencies_per_sender() {
+		// given
+		let pool = Pool::new(Options {
+			per_sender: 2,
+			..Default::default()
+		}, TestApi::default());
+		let transfer = |nonce| uxt(Transfer {
+			from: AccountId::from_h256(H256::from_low_u64_be(1)),
+			to: AccountId::from_h256(H256::from_low_u64_be(2)),
+			amount: 5,
+			nonce,
+		});
+		pool.submit_one(&BlockId::Number(0), TransactionSource::External, transfer(0)).unwrap();
+		pool.submit_one(&BlockId::Number(0), TransactionSource::External, transfer(1)).unwrap();
+
+		// when
+		let err = pool.submit_one(&BlockId::Number(0), TransactionSource::External, transfer(2)).unwrap_err();
+
+		// then
+		assert_matches!(err, error::Error::TooManyDependencies(2));
+		assert_eq!(pool.status().ready, 2);
+	}
+
+	#[test]
+	fn should_count_future_dependencies_per_sender() {
+		// given
+		let pool = Pool::new(Options {
+			per_sender: 2,
+			..Default::default()
+		}, TestApi::default());
+		let transfer = |nonce| uxt(Transfer {
+			from: AccountId::from_h256(H256::from_low_u64_be(1)),
+			to: AccountId::from_h256(H256::from_low_u64_be(2)),
+			amount: 5,
+			nonce,
+		});
+		pool.submit_one(&BlockId::Number(0), transfer(1)).unwrap();
+		pool.submit_one(&BlockId::Number(0), transfer(2)).unwrap();
+
+		// when
+		let err = pool.submit_one(&BlockId::Number(0), transfer(3)).unwrap_err();
+
+		// then
+		assert_matches!(err, error::Error::TooManyDependencies(2));
+		assert_eq!(pool.status().future, 2);
+	}
+
+	#[test]
+	fn should_not_limit_dependencies_per_sender_if_limit_is_zero() {
+		// given
+		let pool = Pool::new(Options {
+			per_sender: 0,
+			..Default::default()
+		}, TestApi::default());
+		let transfer = |nonce| uxt(Transfer {
+			from: AccountId::from_h256(H256::from_low_u64_be(1)),
+			to: AccountId::from_h256(H256::from_low_u64_be(2)),
+			amount: 5,
+			nonce,
+		});
+
+		// when
+		for nonce in 0..4 {
+			pool.submit_one(&BlockId::Number(0), transfer(nonce)).unwrap();
+		}
+
+		// then
+		assert_eq!(pool.status().ready, 4);
+	}
+
 	#[test]
 	fn should_error_if_reject_immediately() {
 		// given
@@ -709,10 +989,11 @@ mod tests {
 		let pool = Pool::new(Options {
 			ready: limit.clone(),
 			future: limit.clone(),
+			..Default::default()
 		}, TestApi::default());
 
 		// when
-		pool.submit_one(&BlockId::Number(0), uxt(Transfer {
+		pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
 			from: AccountId::from_h256(H256::from_low_u64_be(1)),
 			to: AccountId::from_h256(H256::from_low_u64_be(2)),
 			amount: 5,
@@ -732,7 +1013,7 @@ mod tests {
 		fn should_trigger_ready_and_finalized() {
 			// given
 			let pool = pool();
-			let watcher = pool.submit_and_watch(&BlockId::Number(0), uxt(Transfer {
+			let watcher = pool.submit_and_watch(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
 				from: AccountId::from_h256(H256::from_low_u64_be(1)),
 				to: AccountId::from_h256(H256::from_low_u64_be(2)),
 				amount: 5,
@@ -757,7 +1038,7 @@ mod tests {
 		fn should_trigger_ready_and_finalized_when_pruning_via_hash() {
 			// given
 			let pool = pool();
-			let watcher = pool.submit_and_watch(&BlockId::Number(0), uxt(Transfer {
+			let watcher = pool.submit_and_watch(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
 				from: AccountId::from_h256(H256::from_low_u64_be(1)),
 				to: AccountId::from_h256(H256::from_low_u64_be(2)),
 				amount: 5,
@@ -782,7 +1063,7 @@ mod tests {
 		fn should_trigger_future_and_ready_after_promoted() {
 			// given
 			let pool = pool();
-			let watcher = pool.submit_and_watch(&BlockId::Number(0), uxt(Transfer {
+			let watcher = pool.submit_and_watch(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
 				from: AccountId::from_h256(H256::from_low_u64_be(1)),
 				to: AccountId::from_h256(H256::from_low_u64_be(2)),
 				amount: 5,
@@ -792,7 +1073,7 @@ mod tests {
 			assert_eq!(pool.status().future, 1);
 
 			// when
-			pool.submit_one(&BlockId::Number(0), uxt(Transfer {
+			pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
 				from: AccountId::from_h256(H256::from_low_u64_be(1)),
 				to: AccountId::from_h256(H256::from_low_u64_be(2)),
 				amount: 5,
@@ -816,7 +1097,7 @@ mod tests {
 				amount: 5,
 				nonce: 0,
 			});
-			let watcher = pool.submit_and_watch(&BlockId::Number(0), uxt).unwrap();
+			let watcher = pool.submit_and_watch(&BlockId::Number(0), TransactionSource::External, uxt).unwrap();
 			assert_eq!(pool.status().ready, 1);
 
 			// when
@@ -840,7 +1121,7 @@ mod tests {
 				amount: 5,
 				nonce: 0,
 			});
-			let watcher = pool.submit_and_watch(&BlockId::Number(0), uxt).unwrap();
+			let watcher = pool.submit_and_watch(&BlockId::Number(0), TransactionSource::External, uxt).unwrap();
 			assert_eq!(pool.status().ready, 1);
 
 			// when
@@ -866,6 +1147,7 @@ mod tests {
 			let pool = Pool::new(Options {
 				ready: limit.clone(),
 				future: limit.clone(),
+				..Default::default()
 			}, TestApi::default());
 
 			let xt = uxt(Transfer {
@@ -874,7 +1156,7 @@ mod tests {
 				amount: 5,
 				nonce: 0,
 			});
-			let watcher = pool.submit_and_watch(&BlockId::Number(0), xt).unwrap();
+			let watcher = pool.submit_and_watch(&BlockId::Number(0), TransactionSource::External, xt).unwrap();
 			assert_eq!(pool.status().ready, 1);
 
 			// when
@@ -884,7 +1166,7 @@ mod tests {
 				amount: 4,
 				nonce: 1,
 			});
-			pool.submit_one(&BlockId::Number(1), xt).unwrap();
+			pool.submit_one(&BlockId::Number(1), TransactionSource::External, xt).unwrap();
 			assert_eq!(pool.status().ready, 1);
 
 			// then
@@ -893,6 +1175,58 @@ mod tests {
 			assert_eq!(stream.next(), Some(Ok(watcher::Status::Dropped)));
 		}
 
+		#[test]
+		fn should_trigger_dropped_for_stale_transactions() {
+			// given
+			let pool = pool();
+			let xt = uxt(Transfer {
+				from: AccountId::from_h256(H256::from_low_u64_be(1)),
+				to: AccountId::from_h256(H256::from_low_u64_be(2)),
+				amount: 5,
+				nonce: 0,
+			});
+			let watcher = pool.submit_and_watch(&BlockId::Number(0), TransactionSource::External, xt).unwrap();
+			assert_eq!(pool.status().ready, 1);
+
+			// when
+			pool.clear_stale(&BlockId::Number(5)).unwrap();
+
+			// then
+			assert_eq!(pool.status().ready, 0);
+			let mut stream = watcher.into_stream().wait();
+			assert_eq!(stream.next(), Some(Ok(watcher::Status::Ready)));
+			assert_eq!(stream.next(), Some(Ok(watcher::Status::Dropped)));
+		}
+
+		#[test]
+		fn should_report_events_of_all_transactions() {
+			// given
+			let pool = pool();
+			let events = pool.transaction_events();
+
+			// when
+			let hash1 = pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
+				from: AccountId::from_h256(H256::from_low_u64_be(1)),
+				to: AccountId::from_h256(H256::from_low_u64_be(2)),
+				amount: 5,
+				nonce: 1,
+			})).unwrap();
+			let hash0 = pool.submit_one(&BlockId::Number(0), TransactionSource::External, uxt(Transfer {
+				from: AccountId::from_h256(H256::from_low_u64_be(1)),
+				to: AccountId::from_h256(H256::from_low_u64_be(2)),
+				amount: 5,
+				nonce: 0,
+			})).unwrap();
+			pool.remove_invalid(&[hash1]);
+
+			// then
+			let mut stream = events.wait();
+			assert_eq!(stream.next(), Some(Ok((hash1, watcher::Status::Future))));
+			assert_eq!(stream.next(), Some(Ok((hash0, watcher::Status::Ready))));
+			assert_eq!(stream.next(), Some(Ok((hash1, watcher::Status::Ready))));
+			assert_eq!(stream.next(), Some(Ok((hash1, watcher::Status::Invalid))));
+		}
+
 		#[test]
 		fn should_handle_pruning_in_the_middle_of_import() {
 			let _ = env_logger::try_init();
@@ -914,7 +1248,7 @@ mod tests {
 			// This transaction should go to future, since we use `nonce: 1`
 			let pool2 = pool.clone();
 			std::thread::spawn(move || {
-				pool2.submit_one(&BlockId::Number(0), xt).unwrap();
+				pool2.submit_one(&BlockId::Number(0), TransactionSource::External, xt).unwrap();
 				ready.send(()).unwrap();
 			});
 
@@ -928,7 +1262,7 @@ mod tests {
 			});
 			// The tag the above transaction provides (TestApi is using just nonce as u8)
 			let provides = vec![0_u8];
-			pool.submit_one(&BlockId::Number(0), xt).unwrap();
+			pool.submit_one(&BlockId::Number(0), TransactionSource::External, xt).unwrap();
 			assert_eq!(pool.status().ready, 1);
 
 			// Now block import happens before the second transaction is able to finish verification.
@@ -99,6 +99,7 @@ impl<Hash: hash::Hash + Eq + Clone> PoolRotator<Hash> {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use sr_primitives::transaction_validity::TransactionSource as Source;
 
 	type Hash = u64;
 	type Ex = ();
@@ -121,6 +122,7 @@ mod tests {
 			requires: vec![],
 			provides: vec![],
 			propagate: true,
+			source: Source::External,
 		};
 
 		(hash, tx)
@@ -187,6 +189,7 @@ mod tests {
 				requires: vec![],
 				provides: vec![],
 				propagate: true,
+				source: Source::External,
 			}
 		}
 
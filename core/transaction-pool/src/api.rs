@@ -20,7 +20,7 @@ use std::{
 	sync::Arc,
 	marker::PhantomData,
 };
-use client::{runtime_api::TaggedTransactionQueue, blockchain::HeaderBackend};
+use client::{runtime_api::{TaggedTransactionQueue, ApiExt}, blockchain::HeaderBackend};
 use codec::Encode;
 use txpool;
 use primitives::{
@@ -31,7 +31,7 @@ use primitives::{
 use sr_primitives::{
 	generic::BlockId,
 	traits,
-	transaction_validity::TransactionValidity,
+	transaction_validity::{TransactionValidity, TransactionSource},
 };
 
 use crate::error;
@@ -63,8 +63,23 @@ impl<T, Block> txpool::ChainApi for ChainApi<T, Block> where
 	type Hash = H256;
 	type Error = error::Error;
 
-	fn validate_transaction(&self, at: &BlockId<Self::Block>, uxt: txpool::ExtrinsicFor<Self>) -> error::Result<TransactionValidity> {
-		Ok(self.client.runtime_api().validate_transaction(at, uxt)?)
+	fn validate_transaction(
+		&self,
+		at: &BlockId<Self::Block>,
+		source: TransactionSource,
+		uxt: txpool::ExtrinsicFor<Self>,
+	) -> error::Result<TransactionValidity> {
+		let runtime_api = self.client.runtime_api();
+		let has_source = runtime_api
+			.has_api_with::<dyn TaggedTransactionQueue<Block>, _>(at, |v| v >= 2)?;
+
+		if has_source {
+			Ok(runtime_api.validate_transaction(at, source, uxt)?)
+		} else {
+			#[allow(deprecated)] // old runtimes don't know about the transaction source
+			let validity = runtime_api.validate_transaction_before_version_2(at, uxt)?;
+			Ok(validity)
+		}
 	}
 
 	fn block_id_to_number(&self, at: &BlockId<Self::Block>) -> error::Result<Option<txpool::NumberFor<Self>>> {
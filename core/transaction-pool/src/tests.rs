@@ -23,7 +23,7 @@ use test_client::{runtime::{AccountId, Block, Hash, Index, Extrinsic, Transfer},
 use sr_primitives::{
 	generic::{self, BlockId},
 	traits::{Hash as HashT, BlakeTwo256},
-	transaction_validity::{TransactionValidity, ValidTransaction},
+	transaction_validity::{TransactionValidity, ValidTransaction, TransactionSource},
 };
 
 struct TestApi;
@@ -39,7 +39,12 @@ impl txpool::ChainApi for TestApi {
 	type Hash = Hash;
 	type Error = error::Error;
 
-	fn validate_transaction(&self, at: &BlockId<Self::Block>, uxt: txpool::ExtrinsicFor<Self>) -> error::Result<TransactionValidity> {
+	fn validate_transaction(
+		&self,
+		at: &BlockId<Self::Block>,
+		_source: TransactionSource,
+		uxt: txpool::ExtrinsicFor<Self>,
+	) -> error::Result<TransactionValidity> {
 		let expected = index(at);
 		let requires = if expected == uxt.transfer().nonce {
 			vec![]
@@ -105,7 +110,7 @@ fn pool() -> Pool<TestApi> {
 fn submission_should_work() {
 	let pool = pool();
 	assert_eq!(209, index(&BlockId::number(0)));
-	pool.submit_one(&BlockId::number(0), uxt(Alice, 209)).unwrap();
+	pool.submit_one(&BlockId::number(0), TransactionSource::External, uxt(Alice, 209)).unwrap();
 
 	let pending: Vec<_> = pool.ready().map(|a| a.data.transfer().nonce).collect();
 	assert_eq!(pending, vec![209]);
@@ -114,8 +119,8 @@ fn submission_should_work() {
 #[test]
 fn multiple_submission_should_work() {
 	let pool = pool();
-	pool.submit_one(&BlockId::number(0), uxt(Alice, 209)).unwrap();
-	pool.submit_one(&BlockId::number(0), uxt(Alice, 210)).unwrap();
+	pool.submit_one(&BlockId::number(0), TransactionSource::External, uxt(Alice, 209)).unwrap();
+	pool.submit_one(&BlockId::number(0), TransactionSource::External, uxt(Alice, 210)).unwrap();
 
 	let pending: Vec<_> = pool.ready().map(|a| a.data.transfer().nonce).collect();
 	assert_eq!(pending, vec![209, 210]);
@@ -124,7 +129,7 @@ fn multiple_submission_should_work() {
 #[test]
 fn early_nonce_should_be_culled() {
 	let pool = pool();
-	pool.submit_one(&BlockId::number(0), uxt(Alice, 208)).unwrap();
+	pool.submit_one(&BlockId::number(0), TransactionSource::External, uxt(Alice, 208)).unwrap();
 
 	let pending: Vec<_> = pool.ready().map(|a| a.data.transfer().nonce).collect();
 	assert_eq!(pending, Vec::<Index>::new());
@@ -134,11 +139,11 @@ fn early_nonce_should_be_culled() {
 fn late_nonce_should_be_queued() {
 	let pool = pool();
 
-	pool.submit_one(&BlockId::number(0), uxt(Alice, 210)).unwrap();
+	pool.submit_one(&BlockId::number(0), TransactionSource::External, uxt(Alice, 210)).unwrap();
 	let pending: Vec<_> = pool.ready().map(|a| a.data.transfer().nonce).collect();
 	assert_eq!(pending, Vec::<Index>::new());
 
-	pool.submit_one(&BlockId::number(0), uxt(Alice, 209)).unwrap();
+	pool.submit_one(&BlockId::number(0), TransactionSource::External, uxt(Alice, 209)).unwrap();
 	let pending: Vec<_> = pool.ready().map(|a| a.data.transfer().nonce).collect();
 	assert_eq!(pending, vec![209, 210]);
 }
@@ -146,8 +151,8 @@ fn late_nonce_should_be_queued() {
 #[test]
 fn prune_tags_should_work() {
 	let pool = pool();
-	pool.submit_one(&BlockId::number(0), uxt(Alice, 209)).unwrap();
-	pool.submit_one(&BlockId::number(0), uxt(Alice, 210)).unwrap();
+	pool.submit_one(&BlockId::number(0), TransactionSource::External, uxt(Alice, 209)).unwrap();
+	pool.submit_one(&BlockId::number(0), TransactionSource::External, uxt(Alice, 210)).unwrap();
 
 	let pending: Vec<_> = pool.ready().map(|a| a.data.transfer().nonce).collect();
 	assert_eq!(pending, vec![209, 210]);
@@ -162,14 +167,14 @@ fn prune_tags_should_work() {
 fn should_ban_invalid_transactions() {
 	let pool = pool();
 	let uxt = uxt(Alice, 209);
-	let hash = pool.submit_one(&BlockId::number(0), uxt.clone()).unwrap();
+	let hash = pool.submit_one(&BlockId::number(0), TransactionSource::External, uxt.clone()).unwrap();
 	pool.remove_invalid(&[hash]);
-	pool.submit_one(&BlockId::number(0), uxt.clone()).unwrap_err();
+	pool.submit_one(&BlockId::number(0), TransactionSource::External, uxt.clone()).unwrap_err();
 
 	// when
 	let pending: Vec<_> = pool.ready().map(|a| a.data.transfer().nonce).collect();
 	assert_eq!(pending, Vec::<Index>::new());
 
 	// then
-	pool.submit_one(&BlockId::number(0), uxt.clone()).unwrap_err();
+	pool.submit_one(&BlockId::number(0), TransactionSource::External, uxt.clone()).unwrap_err();
 }
@@ -226,7 +226,8 @@ fn check_provide_dummy_wasm_binary() -> bool {
 fn provide_dummy_wasm_binary(file_path: &Path) {
 	fs::write(
 		file_path,
-		"pub const WASM_BINARY: &[u8] = &[]; pub const WASM_BINARY_BLOATY: &[u8] = &[];"
+		"pub const WASM_BINARY: &[u8] = &[]; pub const WASM_BINARY_BLOATY: &[u8] = &[]; \
+		 pub const WASM_BINARY_HASH: [u8; 32] = [0; 32];"
 	).expect("Writing dummy WASM binary should not fail");
 }
 
@@ -51,7 +51,9 @@
 //! ```
 //!
 //! This will include the generated WASM binary as two constants `WASM_BINARY` and `WASM_BINARY_BLOATY`.
-//! The former is a compact WASM binary and the latter is not compacted.
+//! The former is a compact WASM binary and the latter is not compacted. Additionally, the constant
+//! `WASM_BINARY_HASH` contains the blake2-256 hash of `WASM_BINARY`, which is the hash of the runtime
+//! code as it is stored on chain.
 //!
 //! ## Environment variables
 //!
@@ -66,11 +68,26 @@
 //!                          needs to change. As WASM builder instructs `cargo` to watch for file changes
 //!                          this environment variable should only be required in certain circumstances.
 //! - `WASM_BUILD_RUSTFLAGS` - Extend `RUSTFLAGS` given to `cargo build` while building the WASM binary.
+//! - `WASM_BUILD_TOOLCHAIN` - The toolchain that should be used to build the WASM binaries, e.g.
+//!                            `nightly-2019-08-01`. The toolchain is invoked through `rustup`. By default
+//!                            the latest installed nightly is used.
+//! - `WASM_BUILD_DETERMINISTIC` - Build the WASM binaries deterministically. Local paths are stripped
+//!                                from the binary, incremental compilation is disabled and the code is
+//!                                generated in a single codegen unit. Together with a pinned
+//!                                `WASM_BUILD_TOOLCHAIN` and the `Cargo.lock` of the project, this makes
+//!                                it possible for anyone to rebuild a WASM binary and compare its hash.
 //!
 //! Each project can be skipped individually by using the environment variable `SKIP_PROJECT_NAME_WASM_BUILD`.
 //! Where `PROJECT_NAME` needs to be replaced by the name of the cargo project, e.g. `node-runtime` will
 //! be `NODE_RUNTIME`.
 //!
+//! ## Verifying a WASM binary
+//!
+//! The hash of every compact WASM binary that is built is printed as part of the build output. When
+//! `WASM_BUILD_DETERMINISTIC` is set, it is also emitted as a cargo warning, so that it shows up in
+//! a normal `cargo build`. A runtime upgrade can be verified by building the published source with
+//! the same toolchain in deterministic mode and comparing the hash with the one of the proposed code.
+//!
 //! ## Prerequisites:
 //!
 //! WASM builder requires the following prerequisities for building the WASM binary:
@@ -96,6 +113,12 @@ const WASM_BUILD_TYPE_ENV: &str = "WASM_BUILD_TYPE";
 /// Environment variable to extend the `RUSTFLAGS` variable given to the WASM build.
 const WASM_BUILD_RUSTFLAGS_ENV: &str = "WASM_BUILD_RUSTFLAGS";
 
+/// Environment variable to set the toolchain used to compile the WASM binary.
+const WASM_BUILD_TOOLCHAIN_ENV: &str = "WASM_BUILD_TOOLCHAIN";
+
+/// Environment variable that enables the deterministic build of the WASM binary.
+const WASM_BUILD_DETERMINISTIC_ENV: &str = "WASM_BUILD_DETERMINISTIC";
+
 /// Build the currently built project as WASM binary.
 ///
 /// The current project is determined by using the `CARGO_MANIFEST_DIR` environment variable.
@@ -124,6 +147,16 @@ pub fn build_project(file_name: &str, cargo_manifest: &str) {
 	}
 
 	let (wasm_binary, bloaty) = wasm_project::create_and_compile(&cargo_manifest);
+	let wasm_binary_hash = wasm_binary.hash();
+
+	println!("WASM binary `{}` has hash: 0x{}", wasm_binary.wasm_binary_path(), to_hex(&wasm_binary_hash));
+	if wasm_project::is_deterministic_build() {
+		build_helper::warning!(
+			"Deterministic WASM binary `{}` has hash: 0x{}",
+			wasm_binary.wasm_binary_path(),
+			to_hex(&wasm_binary_hash),
+		);
+	}
 
 	create_out_file(
 		file_name,
@@ -131,13 +164,20 @@ pub fn build_project(file_name: &str, cargo_manifest: &str) {
 			r#"
 				pub const WASM_BINARY: &[u8] = include_bytes!("{wasm_binary}");
 				pub const WASM_BINARY_BLOATY: &[u8] = include_bytes!("{wasm_binary_bloaty}");
+				pub const WASM_BINARY_HASH: [u8; 32] = {wasm_binary_hash:?};
 			"#,
 			wasm_binary = wasm_binary.wasm_binary_path(),
 			wasm_binary_bloaty = bloaty.wasm_binary_bloaty_path(),
+			wasm_binary_hash = wasm_binary_hash,
 		),
 	);
 }
 
+/// Hex encode the given bytes.
+fn to_hex(bytes: &[u8]) -> String {
+	bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
 /// Checks if the build of the WASM binary should be skipped.
 fn check_skip_build() -> bool {
 	env::var(SKIP_BUILD_ENV).is_ok()
@@ -151,7 +191,15 @@ fn create_out_file(file_name: &str, content: String) {
 }
 
 /// Get a cargo command that compiles with nightly
+///
+/// If `WASM_BUILD_TOOLCHAIN` is set, the given toolchain is always used.
 fn get_nightly_cargo() -> CargoCommand {
+	if let Ok(toolchain) = env::var(WASM_BUILD_TOOLCHAIN_ENV) {
+		let mut rustup_run_toolchain = CargoCommand::new("rustup");
+		rustup_run_toolchain.args(&["run", &toolchain, "cargo"]);
+		return rustup_run_toolchain;
+	}
+
 	let default_cargo = CargoCommand::new("cargo");
 	let mut rustup_run_nightly = CargoCommand::new("rustup");
 	rustup_run_nightly.args(&["run", "nightly", "cargo"]);
@@ -44,6 +44,14 @@ impl WasmBinary {
 	pub fn wasm_binary_path(&self) -> String {
 		self.0.display().to_string().replace('\\', "/")
 	}
+
+	/// Returns the blake2-256 hash of the wasm binary.
+	pub fn hash(&self) -> [u8; 32] {
+		let code = fs::read(&self.0).expect("Compact WASM binary exists after compilation; qed");
+		let mut hash = [0u8; 32];
+		hash.copy_from_slice(blake2_rfc::blake2b::blake2b(32, &[], &code).as_bytes());
+		hash
+	}
 }
 
 /// A lock for the WASM workspace.
@@ -85,7 +93,7 @@ pub fn create_and_compile(cargo_manifest: &Path) -> (WasmBinary, WasmBinaryBloat
 	let project = create_project(cargo_manifest, &wasm_workspace);
 	create_wasm_workspace_project(&wasm_workspace);
 
-	build_project(&project);
+	build_project(&project, cargo_manifest, &wasm_workspace);
 	let (wasm_binary, bloaty) = compact_wasm_file(&project, cargo_manifest, &wasm_workspace);
 
 	generate_rerun_if_changed_instructions(cargo_manifest, &project, &wasm_workspace);
@@ -256,13 +264,52 @@ fn is_release_build() -> bool {
 	}
 }
 
+/// Returns if the WASM binary should be built deterministically.
+pub fn is_deterministic_build() -> bool {
+	env::var(crate::WASM_BUILD_DETERMINISTIC_ENV).is_ok()
+}
+
+/// Returns the `RUSTFLAGS` that make the build independent of the machine it is executed on.
+///
+/// All local paths that can end up in the WASM binary are remapped to fixed prefixes.
+fn deterministic_rustflags(cargo_manifest: &Path, wasm_workspace: &Path) -> String {
+	let crate_path = cargo_manifest.parent().expect("Parent path exists; qed");
+	let project_root = find_cargo_lock(cargo_manifest)
+		.and_then(|lock| lock.parent().map(Path::to_path_buf))
+		.unwrap_or_else(|| crate_path.to_path_buf());
+	let home = env::var("HOME").map(PathBuf::from).ok();
+	let cargo_home = env::var("CARGO_HOME").map(PathBuf::from).ok()
+		.or_else(|| home.as_ref().map(|h| h.join(".cargo")));
+	let rustup_home = env::var("RUSTUP_HOME").map(PathBuf::from).ok()
+		.or_else(|| home.as_ref().map(|h| h.join(".rustup")));
+
+	// `rustc` prefers the mapping given last, so more specific paths need to come later.
+	let remaps = rustup_home.map(|p| (p, "/rustup"))
+		.into_iter()
+		.chain(cargo_home.map(|p| (p, "/cargo")))
+		.chain(Some((project_root, "/project")))
+		.chain(Some((wasm_workspace.to_path_buf(), "/wbuild")));
+
+	remaps
+		.map(|(from, to)| format!("--remap-path-prefix={}={} ", from.display(), to))
+		.chain(Some("-C codegen-units=1".into()))
+		.collect()
+}
+
 /// Build the project to create the WASM binary.
-fn build_project(project: &Path) {
+fn build_project(project: &Path, cargo_manifest: &Path, wasm_workspace: &Path) {
 	let manifest_path = project.join("Cargo.toml");
 	let mut build_cmd = crate::get_nightly_cargo().command();
 
+	let deterministic_rustflags = if is_deterministic_build() {
+		deterministic_rustflags(cargo_manifest, wasm_workspace)
+	} else {
+		String::new()
+	};
+
 	let rustflags = format!(
-		"-C link-arg=--export-table {}",
+		"-C link-arg=--export-table {} {}",
+		deterministic_rustflags,
 		env::var(crate::WASM_BUILD_RUSTFLAGS_ENV).unwrap_or_default(),
 	);
 
@@ -272,6 +319,10 @@ fn build_project(project: &Path) {
 		// We don't want to call ourselves recursively
 		.env(crate::SKIP_BUILD_ENV, "");
 
+	if is_deterministic_build() {
+		build_cmd.env("CARGO_INCREMENTAL", "0");
+	}
+
 	if is_release_build() {
 		build_cmd.arg("--release");
 	};
@@ -349,4 +400,6 @@ fn generate_rerun_if_changed_instructions(
 	println!("cargo:rerun-if-env-changed={}", crate::SKIP_BUILD_ENV);
 	println!("cargo:rerun-if-env-changed={}", crate::WASM_BUILD_TYPE_ENV);
 	println!("cargo:rerun-if-env-changed={}", crate::WASM_BUILD_RUSTFLAGS_ENV);
+	println!("cargo:rerun-if-env-changed={}", crate::WASM_BUILD_TOOLCHAIN_ENV);
+	println!("cargo:rerun-if-env-changed={}", crate::WASM_BUILD_DETERMINISTIC_ENV);
 }
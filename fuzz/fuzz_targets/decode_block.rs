@@ -0,0 +1,29 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Decodes arbitrary bytes as a header and as a block of the node runtime, and as an opaque
+//! block, as received by the import queue.
+
+#![no_main]
+
+use codec::Decode;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+	let _ = node_runtime::Header::decode(&mut &data[..]);
+	let _ = node_runtime::Block::decode(&mut &data[..]);
+	let _ = node_primitives::Block::decode(&mut &data[..]);
+});
@@ -9,8 +9,11 @@
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
 use rstd::prelude::*;
-use primitives::{ed25519, sr25519, OpaqueMetadata};
-use sr_primitives::{ApplyResult, transaction_validity::TransactionValidity, generic, create_runtime_str};
+use primitives::{ed25519, OpaqueMetadata};
+use sr_primitives::{
+	ApplyResult, transaction_validity::{TransactionValidity, TransactionSource}, generic, create_runtime_str,
+	MultiSignature,
+};
 use sr_primitives::traits::{NumberFor, BlakeTwo256, Block as BlockT, StaticLookup, Verify, ConvertInto};
 use sr_primitives::weights::Weight;
 use client::{
@@ -18,6 +21,7 @@ use client::{
 	runtime_api, impl_runtime_apis
 };
 use version::RuntimeVersion;
+use support::traits::Contains;
 #[cfg(feature = "std")]
 use version::NativeVersion;
 
@@ -35,11 +39,12 @@ pub type AuraSignature = ed25519::Signature;
 /// The Ed25519 pub key of an session that belongs to an Aura authority of the chain.
 pub type AuraId = ed25519::Public;
 
-/// Alias to pubkey that identifies an account on the chain.
+/// Alias to pubkey that identifies an account on the chain. Accounts may be keyed by sr25519,
+/// ed25519 or ECDSA (secp256k1) public keys.
 pub type AccountId = <AccountSignature as Verify>::Signer;
 
-/// The type used by authorities to prove their ID.
-pub type AccountSignature = sr25519::Signature;
+/// The type used by accounts to sign their transactions.
+pub type AccountSignature = MultiSignature;
 
 /// A hash of some data used by the chain.
 pub type Hash = primitives::H256;
@@ -80,9 +85,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("node-template"),
 	impl_name: create_runtime_str!("node-template"),
 	authoring_version: 3,
-	spec_version: 4,
-	impl_version: 4,
+	spec_version: 6,
+	impl_version: 6,
 	apis: RUNTIME_API_VERSIONS,
+	transaction_version: 1,
 };
 
 /// The version infromation used to identify this runtime when compiled natively.
@@ -98,9 +104,19 @@ parameter_types! {
 	pub const BlockHashCount: BlockNumber = 250;
 	pub const MaximumBlockWeight: Weight = 1_000_000;
 	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+	pub const Version: RuntimeVersion = VERSION;
 	pub const MaximumBlockLength: u32 = 5 * 1024 * 1024;
 }
 
+/// Filter for the calls that may be dispatched through extrinsics at all.
+pub struct BaseFilter;
+impl Contains<Call> for BaseFilter {
+	fn contains(_call: &Call) -> bool {
+		// all calls are allowed for now.
+		true
+	}
+}
+
 impl system::Trait for Runtime {
 	/// The identifier used to distinguish between accounts.
 	type AccountId = AccountId;
@@ -130,6 +146,9 @@ impl system::Trait for Runtime {
 	type MaximumBlockLength = MaximumBlockLength;
 	/// Portion of the block weight that is available to all normal transactions.
 	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = Version;
+	type Call = Call;
+	type BaseCallFilter = BaseFilter;
 }
 
 impl aura::Trait for Runtime {
@@ -163,8 +182,6 @@ parameter_types! {
 	pub const ExistentialDeposit: u128 = 500;
 	pub const TransferFee: u128 = 0;
 	pub const CreationFee: u128 = 0;
-	pub const TransactionBaseFee: u128 = 0;
-	pub const TransactionByteFee: u128 = 1;
 }
 
 impl balances::Trait for Runtime {
@@ -177,12 +194,21 @@ impl balances::Trait for Runtime {
 	/// The ubiquitous event type.
 	type Event = Event;
 
-	type TransactionPayment = ();
 	type DustRemoval = ();
 	type TransferPayment = ();
 	type ExistentialDeposit = ExistentialDeposit;
 	type TransferFee = TransferFee;
 	type CreationFee = CreationFee;
+}
+
+parameter_types! {
+	pub const TransactionBaseFee: u128 = 0;
+	pub const TransactionByteFee: u128 = 1;
+}
+
+impl transaction_payment::Trait for Runtime {
+	type Currency = balances::Module<Runtime>;
+	type OnTransactionPayment = ();
 	type TransactionBaseFee = TransactionBaseFee;
 	type TransactionByteFee = TransactionByteFee;
 	type WeightToFee = ConvertInto;
@@ -210,6 +236,7 @@ construct_runtime!(
 		Aura: aura::{Module, Config<T>, Inherent(Timestamp)},
 		Indices: indices::{default, Config<T>},
 		Balances: balances,
+		TransactionPayment: transaction_payment::{Module},
 		Sudo: sudo,
 		// Used for the module template in `./template.rs`
 		TemplateModule: template::{Module, Call, Storage, Event<T>},
@@ -227,7 +254,12 @@ pub type Block = generic::Block<Header, UncheckedExtrinsic>;
 /// BlockId type as expected by this runtime.
 pub type BlockId = generic::BlockId<Block>;
 /// The SignedExtension to the basic transaction logic.
-pub type SignedExtra = (system::CheckNonce<Runtime>, system::CheckWeight<Runtime>, balances::TakeFees<Runtime>);
+pub type SignedExtra = (
+	system::CheckTxVersion<Runtime>,
+	system::CheckNonce<Runtime>,
+	system::CheckWeight<Runtime>,
+	transaction_payment::ChargeTransactionPayment<Runtime>
+);
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, AccountSignature, SignedExtra>;
 /// Extrinsic type that has already been checked.
@@ -280,8 +312,11 @@ impl_runtime_apis! {
 	}
 
 	impl runtime_api::TaggedTransactionQueue<Block> for Runtime {
-		fn validate_transaction(tx: <Block as BlockT>::Extrinsic) -> TransactionValidity {
-			Executive::validate_transaction(tx)
+		fn validate_transaction(
+			source: TransactionSource,
+			tx: <Block as BlockT>::Extrinsic,
+		) -> TransactionValidity {
+			Executive::validate_transaction(source, tx)
 		}
 	}
 
@@ -106,6 +106,9 @@ mod tests {
 		type MaximumBlockWeight = MaximumBlockWeight;
 		type MaximumBlockLength = MaximumBlockLength;
 		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+		type Call = ();
+		type BaseCallFilter = ();
 	}
 	impl Trait for Test {
 		type Event = ();
@@ -32,6 +32,7 @@ fn account_key(s: &str) -> AccountId {
 	sr25519::Pair::from_string(&format!("//{}", s), None)
 		.expect("static values are valid; qed")
 		.public()
+		.into()
 }
 
 impl Alternative {
@@ -52,6 +53,7 @@ impl Alternative {
 				None,
 				None,
 				None,
+				None,
 				None
 			),
 			Alternative::LocalTestnet => ChainSpec::from_genesis(
@@ -74,6 +76,7 @@ impl Alternative {
 				None,
 				None,
 				None,
+				None,
 				None
 			),
 		})
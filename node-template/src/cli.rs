@@ -6,7 +6,7 @@ pub use substrate_cli::{VersionInfo, IntoExit, error};
 use substrate_cli::{informant, parse_and_execute, NoCustom};
 use substrate_service::{ServiceFactory, Roles as ServiceRoles};
 use crate::chain_spec;
-use std::ops::Deref;
+use std::{ops::Deref, time::Duration};
 use log::info;
 
 /// Parse command line arguments into service configuration.
@@ -17,28 +17,31 @@ pub fn run<I, T, E>(args: I, exit: E, version: VersionInfo) -> error::Result<()>
 {
 	parse_and_execute::<service::Factory, NoCustom, NoCustom, _, _, _, _, _>(
 		load_spec, &version, "substrate-node", args, exit,
-	 	|exit, _cli_args, _custom_args, config| {
+	 	|exit, cli_args, _custom_args, config| {
 			info!("{}", version.name);
 			info!("  version {}", config.full_version());
 			info!("  by {}, 2017, 2018", version.author);
 			info!("Chain specification: {}", config.chain_spec.name());
 			info!("Node name: {}", config.name);
 			info!("Roles: {:?}", config.roles);
-			let runtime = Runtime::new().map_err(|e| format!("{:?}", e))?;
+			let shutdown_timeout = Duration::from_secs(cli_args.shutdown_timeout);
+			let runtime = Runtime::new()?;
 			match config.roles {
 				ServiceRoles::LIGHT => run_until_exit(
 					runtime,
-				 	service::Factory::new_light(config).map_err(|e| format!("{:?}", e))?,
-					exit
+				 	service::Factory::new_light(config)?,
+					exit,
+					shutdown_timeout,
 				),
 				_ => run_until_exit(
 					runtime,
-					service::Factory::new_full(config).map_err(|e| format!("{:?}", e))?,
-					exit
+					service::Factory::new_full(config)?,
+					exit,
+					shutdown_timeout,
 				),
-			}.map_err(|e| format!("{:?}", e))
+			}
 		}
-	).map_err(Into::into).map(|_| ())
+	).map(|_| ())
 }
 
 fn load_spec(id: &str) -> Result<Option<chain_spec::ChainSpec>, String> {
@@ -52,6 +55,7 @@ fn run_until_exit<T, C, E>(
 	mut runtime: Runtime,
 	service: T,
 	e: E,
+	shutdown_timeout: Duration,
 ) -> error::Result<()>
 	where
 		T: Deref<Target=substrate_service::Service<C>> + Future<Item = (), Error = ()> + Send + 'static,
@@ -70,7 +74,7 @@ fn run_until_exit<T, C, E>(
 	let _ = runtime.block_on(service.select(e.into_exit()));
 	exit_send.fire();
 
-	Ok(())
+	substrate_cli::shutdown_runtime(runtime, shutdown_timeout)
 }
 
 // handles ctrl-c
@@ -22,6 +22,6 @@ fn main() {
 
 	if let Err(e) = cli::run(::std::env::args(), cli::Exit, version) {
 		eprintln!("Error starting the node: {}\n\n{:?}", e, e);
-		std::process::exit(1)
+		std::process::exit(e.exit_code())
 	}
 }
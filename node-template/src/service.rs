@@ -15,7 +15,7 @@ use basic_authorship::ProposerFactory;
 use consensus::{import_queue, start_aura, AuraImportQueue, SlotDuration};
 use futures::prelude::*;
 use substrate_client::{self as client, LongestChain};
-use primitives::{ed25519::Pair, Pair as PairT};
+use primitives::ed25519::Pair;
 use inherents::InherentDataProviders;
 use network::{config::DummyFinalityProofRequestBuilder, construct_simple_protocol};
 use substrate_executor::native_executor_instance;
@@ -68,8 +68,8 @@ construct_service_factory! {
 			},
 		AuthoritySetup = {
 			|service: Self::FullService| {
-				if let Some(key) = service.authority_key() {
-					info!("Using authority key {}", key.public());
+				if let Some(keystore) = service.authority_keystore() {
+					info!("Authoring blocks with the Aura keys of the keystore");
 					let proposer = ProposerFactory {
 						client: service.client(),
 						transaction_pool: service.transaction_pool(),
@@ -77,9 +77,9 @@ construct_service_factory! {
 					let client = service.client();
 					let select_chain = service.select_chain()
 						.ok_or_else(|| ServiceError::SelectChainRequired)?;
-					let aura = start_aura(
+					let aura = start_aura::<_, _, _, _, _, Pair, _, _, _>(
 						SlotDuration::get_or_compute(&*client)?,
-						Arc::new(key),
+						keystore,
 						client.clone(),
 						select_chain,
 						client,
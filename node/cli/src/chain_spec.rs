@@ -165,6 +165,7 @@ fn staging_testnet_config_genesis() -> GenesisConfig {
 			gossip_at: 0,
 			last_new_era_start: 0,
 		}),
+		maintenance: Some(Default::default()),
 		grandpa: Some(GrandpaConfig {
 			authorities: initial_authorities.iter().map(|x| (x.3.clone(), 1)).collect(),
 		}),
@@ -183,6 +184,7 @@ pub fn staging_testnet_config() -> ChainSpec {
 		None,
 		None,
 		None,
+		None,
 	)
 }
 
@@ -244,7 +246,7 @@ pub fn testnet_genesis(
 	const ENDOWMENT: Balance = 10_000_000 * DOLLARS;
 	const STASH: Balance = 100 * DOLLARS;
 
-	let desired_seats = (endowed_accounts.len() / 2 - initial_authorities.len()) as u32;
+	let desired_seats = (endowed_accounts.len() / 2).saturating_sub(initial_authorities.len()) as u32;
 
 	GenesisConfig {
 		system: Some(SystemConfig {
@@ -308,6 +310,7 @@ pub fn testnet_genesis(
 			gossip_at: 0,
 			last_new_era_start: 0,
 		}),
+		maintenance: Some(Default::default()),
 		grandpa: Some(GrandpaConfig {
 			authorities: initial_authorities.iter().map(|x| (x.3.clone(), 1)).collect(),
 		}),
@@ -325,9 +328,45 @@ fn development_config_genesis() -> GenesisConfig {
 	)
 }
 
+/// Development genesis (single validator Alice) with the given accounts endowed with
+/// `endowment` each. The accounts are given by their seeds, e.g. `Alice` or `Bob//stash`.
+///
+/// The stash and controller accounts of Alice are always endowed, as they are needed to bond.
+pub fn development_genesis(endowed_seeds: &[String], endowment: Option<Balance>) -> GenesisConfig {
+	let authority = get_authority_keys_from_seed("Alice");
+	let endowed_accounts = if endowed_seeds.is_empty() {
+		None
+	} else {
+		let mut accounts = vec![authority.0.clone(), authority.1.clone()];
+		for account in endowed_seeds.iter().map(|seed| get_account_id_from_seed(seed)) {
+			if !accounts.contains(&account) {
+				accounts.push(account);
+			}
+		}
+		Some(accounts)
+	};
+
+	let mut genesis = testnet_genesis(
+		vec![
+			authority,
+		],
+		get_account_id_from_seed("Alice"),
+		endowed_accounts,
+		true,
+	);
+
+	if let (Some(endowment), Some(balances)) = (endowment, genesis.balances.as_mut()) {
+		for (_, balance) in balances.balances.iter_mut() {
+			*balance = endowment;
+		}
+	}
+
+	genesis
+}
+
 /// Development config (single validator Alice)
 pub fn development_config() -> ChainSpec {
-	ChainSpec::from_genesis("Development", "dev", development_config_genesis, vec![], None, None, None, None)
+	ChainSpec::from_genesis("Development", "dev", development_config_genesis, vec![], None, None, None, None, None)
 }
 
 fn local_testnet_genesis() -> GenesisConfig {
@@ -344,7 +383,7 @@ fn local_testnet_genesis() -> GenesisConfig {
 
 /// Local testnet config (multivalidator Alice + Bob)
 pub fn local_testnet_config() -> ChainSpec {
-	ChainSpec::from_genesis("Local Testnet", "local_testnet", local_testnet_genesis, vec![], None, None, None, None)
+	ChainSpec::from_genesis("Local Testnet", "local_testnet", local_testnet_genesis, vec![], None, None, None, None, None)
 }
 
 #[cfg(test)]
@@ -375,12 +414,13 @@ pub(crate) mod tests {
 			None,
 			None,
 			None,
+			None,
 		)
 	}
 
 	/// Local testnet config (multivalidator Alice + Bob)
 	pub fn integration_test_config_with_two_authorities() -> ChainSpec {
-		ChainSpec::from_genesis("Integration Test", "test", local_testnet_genesis, vec![], None, None, None, None)
+		ChainSpec::from_genesis("Integration Test", "test", local_testnet_genesis, vec![], None, None, None, None, None)
 	}
 
 	#[test]
@@ -388,4 +428,23 @@ pub(crate) mod tests {
 	fn test_connectivity() {
 		service_test::connectivity::<Factory>(integration_test_config_with_two_authorities());
 	}
+
+	#[test]
+	fn validators_without_session_keys_are_rejected() {
+		use sr_primitives::BuildStorage;
+
+		let spec = ChainSpec::from_genesis("Integration Test", "test", || {
+			let mut genesis = local_testnet_genesis();
+			// Bob stays a validator, without session keys.
+			genesis.session.as_mut().unwrap().keys.pop();
+			genesis
+		}, vec![], None, None, None, None, None);
+		let err = (&spec).build_storage().err().unwrap();
+
+		assert!(
+			err.starts_with("Invalid genesis config: session: genesis config contains no session keys"),
+			"{}",
+			err,
+		);
+	}
 }
@@ -53,11 +53,16 @@ type Number = <<node_primitives::Block as BlockT>::Header as HeaderT>::Number;
 impl<Number> FactoryState<Number> {
 	fn build_extra(index: node_primitives::Index, phase: u64) -> node_runtime::SignedExtra {
 		(
+			system::CheckTxVersion::new(),
 			system::CheckGenesis::new(),
-			system::CheckEra::from(Era::mortal(256, phase)),
+			system::CheckEra::from(Era::mortal_with_max_period(
+				256,
+				phase,
+				node_runtime::BlockHashCount::get().into(),
+			)),
 			system::CheckNonce::from(index),
 			system::CheckWeight::new(),
-			balances::TakeFees::from(0)
+			transaction_payment::ChargeTransactionPayment::from(0)
 		)
 	}
 }
@@ -148,7 +153,14 @@ impl RuntimeAdapter for FactoryState<Number> {
 					(*amount).into()
 				)
 			)
-		}, key, (genesis_hash.clone(), prior_block_hash.clone(), (), (), ()))
+		}, key, (
+			node_runtime::VERSION.transaction_version,
+			genesis_hash.clone(),
+			prior_block_hash.clone(),
+			(),
+			(),
+			(),
+		))
 	}
 
 	fn inherent_extrinsics(&self) -> InherentData {
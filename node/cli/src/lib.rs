@@ -27,8 +27,9 @@ mod factory_impl;
 use tokio::prelude::Future;
 use tokio::runtime::{Builder as RuntimeBuilder, Runtime};
 pub use cli::{VersionInfo, IntoExit, NoCustom, SharedParams, ExecutionStrategyParam};
-use substrate_service::{ServiceFactory, Roles as ServiceRoles};
-use std::ops::Deref;
+use substrate_service::{ServiceFactory, Roles as ServiceRoles, FactoryFullConfiguration};
+use node_primitives::Balance;
+use std::{ops::Deref, time::Duration};
 use log::info;
 use structopt::{StructOpt, clap::App};
 use cli::{AugmentClap, GetLogFilter};
@@ -123,6 +124,51 @@ impl AugmentClap for FactoryCmd {
 	}
 }
 
+/// Custom parameters of the run command to tailor the development chain. They are only
+/// considered together with `--dev`.
+#[derive(Debug, StructOpt, Clone)]
+pub struct DevParams {
+	/// Seeds of the well-known accounts to endow on the development chain, e.g. `Alice,Bob//stash`.
+	#[structopt(long = "dev-endowed-accounts", value_name = "SEEDS", raw(use_delimiter = "true"))]
+	pub dev_endowed_accounts: Vec<String>,
+
+	/// The balance of each endowed account on the development chain.
+	#[structopt(long = "dev-endowment", value_name = "BALANCE")]
+	pub dev_endowment: Option<Balance>,
+
+	/// Finalize every new best block of the development chain instantly instead of running GRANDPA.
+	#[structopt(long = "dev-instant-finality")]
+	pub dev_instant_finality: bool,
+}
+
+impl AugmentClap for DevParams {
+	fn augment_clap<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+		DevParams::augment_clap(app)
+	}
+}
+
+impl DevParams {
+	fn is_customized(&self) -> bool {
+		!self.dev_endowed_accounts.is_empty() || self.dev_endowment.is_some() || self.dev_instant_finality
+	}
+
+	/// Apply the parameters to the configuration of a development chain.
+	fn apply(&self, config: &mut FactoryFullConfiguration<service::Factory>) -> Result<(), String> {
+		if !self.dev_endowed_accounts.is_empty() || self.dev_endowment.is_some() {
+			config.chain_spec.set_genesis(
+				chain_spec::development_genesis(&self.dev_endowed_accounts, self.dev_endowment)
+			)?;
+		}
+
+		if self.dev_instant_finality {
+			config.disable_grandpa = true;
+			config.custom.instant_finality = true;
+		}
+
+		Ok(())
+	}
+}
+
 /// Get a chain config from a spec setting.
 impl ChainSpec {
 	pub(crate) fn load(self) -> Result<chain_spec::ChainSpec, String> {
@@ -158,29 +204,39 @@ pub fn run<I, T, E>(args: I, exit: E, version: cli::VersionInfo) -> error::Resul
 	T: Into<std::ffi::OsString> + Clone,
 	E: IntoExit,
 {
-	let ret = cli::parse_and_execute::<service::Factory, CustomSubcommands, NoCustom, _, _, _, _, _>(
+	let ret = cli::parse_and_execute::<service::Factory, CustomSubcommands, DevParams, _, _, _, _, _>(
 		load_spec, &version, "substrate-node", args, exit,
-		|exit, _cli_args, _custom_args, config| {
+		|exit, cli_args, dev_params, mut config| {
+			if dev_params.is_customized() {
+				if ChainSpec::from(config.chain_spec.id()) != Some(ChainSpec::Development) {
+					return Err(error::Error::Input(
+						"The `--dev-*` parameters can only be used with the development chain".into()
+					));
+				}
+				dev_params.apply(&mut config)?;
+			}
 			info!("{}", version.name);
 			info!("  version {}", config.full_version());
 			info!("  by Parity Technologies, 2017-2019");
 			info!("Chain specification: {}", config.chain_spec.name());
 			info!("Node name: {}", config.name);
 			info!("Roles: {:?}", config.roles);
-			let runtime = RuntimeBuilder::new().name_prefix("main-tokio-").build()
-				.map_err(|e| format!("{:?}", e))?;
+			let shutdown_timeout = Duration::from_secs(cli_args.shutdown_timeout);
+			let runtime = RuntimeBuilder::new().name_prefix("main-tokio-").build()?;
 			match config.roles {
 				ServiceRoles::LIGHT => run_until_exit(
 					runtime,
-					service::Factory::new_light(config).map_err(|e| format!("{:?}", e))?,
-					exit
+					service::Factory::new_light(config)?,
+					exit,
+					shutdown_timeout,
 				),
 				_ => run_until_exit(
 					runtime,
-					service::Factory::new_full(config).map_err(|e| format!("{:?}", e))?,
-					exit
+					service::Factory::new_full(config)?,
+					exit,
+					shutdown_timeout,
 				),
-			}.map_err(|e| format!("{:?}", e))
+			}
 		}
 	);
 
@@ -223,6 +279,7 @@ fn run_until_exit<T, C, E>(
 	mut runtime: Runtime,
 	service: T,
 	e: E,
+	shutdown_timeout: Duration,
 ) -> error::Result<()>
 	where
 		T: Deref<Target=substrate_service::Service<C>> + Future<Item = (), Error = ()> + Send + 'static,
@@ -241,8 +298,6 @@ fn run_until_exit<T, C, E>(
 	let _ = runtime.block_on(service.select(e.into_exit()));
 	exit_send.fire();
 
-	// TODO [andre]: timeout this future #1318
-	let _ = runtime.shutdown_on_idle().wait();
-
-	Ok(())
+	info!("Shutting down, waiting for remaining tasks to finish");
+	cli::shutdown_runtime(runtime, shutdown_timeout)
 }
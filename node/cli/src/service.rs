@@ -23,31 +23,44 @@ use std::time::Duration;
 
 use babe::{import_queue, start_babe, BabeImportQueue, Config};
 use babe_primitives::AuthorityPair as BabePair;
-use client::{self, LongestChain};
-use grandpa::{self, FinalityProofProvider as GrandpaFinalityProofProvider};
+use client::{self, LongestChain, BlockchainEvents, backend::Backend};
+use grandpa::{self, FinalityProofProvider as GrandpaFinalityProofProvider, rpc::GrandpaApi};
 use node_executor;
-use primitives::Pair;
 use grandpa_primitives::AuthorityPair as GrandpaPair;
 use futures::prelude::*;
-use node_primitives::Block;
-use node_runtime::{GenesisConfig, RuntimeApi};
+use node_primitives::{Block, AccountId, Balance, Hash};
+use node_runtime::{GenesisConfig, RuntimeApi, Call, UncheckedExtrinsic};
+use contracts_rpc::{Contracts, ContractsApi};
+use evm_rpc::{Eth, EthApi, ConvertTransaction};
 use substrate_service::{
 	FactoryFullConfiguration, LightComponents, FullComponents, FullBackend,
-	FullClient, LightClient, LightBackend, FullExecutor, LightExecutor,
+	FullClient, LightClient, LightBackend, FullExecutor, LightExecutor, RpcExtension,
 	error::{Error as ServiceError},
 };
 use transaction_pool::{self, txpool::{Pool as TransactionPool}};
 use inherents::InherentDataProviders;
 use network::construct_simple_protocol;
 use substrate_service::construct_service_factory;
-use log::info;
+use log::{info, warn};
+use sr_primitives::{generic::BlockId, OpaqueExtrinsic};
 use substrate_service::TelemetryOnConnect;
+use codec::Encode;
 
 construct_simple_protocol! {
 	/// Demo protocol attachment for substrate.
 	pub struct NodeProtocol where Block = Block { }
 }
 
+/// Converts Ethereum transactions into unsigned `evm::transact` extrinsics.
+pub struct TransactionConverter;
+
+impl ConvertTransaction<OpaqueExtrinsic> for TransactionConverter {
+	fn convert_transaction(&self, transaction: evm::Transaction) -> OpaqueExtrinsic {
+		let extrinsic = UncheckedExtrinsic::new_unsigned(Call::EVM(evm::Call::transact(transaction)));
+		OpaqueExtrinsic(extrinsic.encode())
+	}
+}
+
 type BabeBlockImportForService<F> = babe::BabeBlockImport<
 	FullBackend<F>,
 	FullExecutor<F>,
@@ -73,6 +86,8 @@ pub struct NodeConfig<F: substrate_service::ServiceFactory> {
 	)>,
 	/// Tasks that were created by previous setup steps and should be spawned.
 	pub tasks_to_spawn: Option<Vec<Box<dyn Future<Item = (), Error = ()> + Send>>>,
+	/// Finalize every new best block right away instead of running GRANDPA.
+	pub instant_finality: bool,
 	inherent_data_providers: InherentDataProviders,
 }
 
@@ -82,6 +97,7 @@ impl<F> Default for NodeConfig<F> where F: substrate_service::ServiceFactory {
 			import_setup: None,
 			inherent_data_providers: InherentDataProviders::new(),
 			tasks_to_spawn: None,
+			instant_finality: false,
 		}
 	}
 }
@@ -119,8 +135,11 @@ construct_service_factory! {
 					}
 				}
 
-				if let Some(babe_key) = service.authority_key() {
-					info!("Using BABE key {}", babe_key.public());
+				// The keystore of an authority, either the local one or the external signer.
+				let keystore = service.authority_keystore();
+
+				if let Some(keystore) = keystore.clone() {
+					info!("Authoring blocks with the BABE keys of the keystore");
 
 					let proposer = substrate_basic_authorship::ProposerFactory {
 						client: service.client(),
@@ -133,7 +152,7 @@ construct_service_factory! {
 
 					let babe_config = babe::BabeParams {
 						config: Config::get_or_compute(&*client)?,
-						local_key: Arc::new(babe_key),
+						keystore,
 						client,
 						select_chain,
 						block_import,
@@ -149,21 +168,38 @@ construct_service_factory! {
 					service.spawn_task(Box::new(select));
 				}
 
-				let grandpa_key = if service.config.disable_grandpa {
+				if service.config.custom.instant_finality {
+					info!("Finalizing every new best block instantly");
+
+					let client = service.client();
+					let finalize = client.import_notification_stream()
+						.filter(|notification| notification.is_new_best)
+						.for_each(move |notification| {
+							if let Err(e) = client.finalize_block(BlockId::Hash(notification.hash), None, true) {
+								warn!("Failed to finalize block {}: {:?}", notification.hash, e);
+							}
+							Ok(())
+						});
+					service.spawn_task(Box::new(finalize.select(service.on_exit()).then(|_| Ok(()))));
+
+					return Ok(service);
+				}
+
+				let grandpa_keystore = if service.config.disable_grandpa {
 					None
 				} else {
-					service.fg_authority_key()
+					keystore
 				};
 
 				let config = grandpa::Config {
-					local_key: grandpa_key.map(Arc::new),
+					keystore: grandpa_keystore,
 					// FIXME #1578 make this available through chainspec
 					gossip_duration: Duration::from_millis(333),
 					justification_period: 4096,
 					name: Some(service.config.name.clone())
 				};
 
-				match config.local_key {
+				match config.keystore {
 					None if !service.config.grandpa_voter => {
 						service.spawn_task(Box::new(grandpa::run_grandpa_observer(
 							config,
@@ -172,7 +208,7 @@ construct_service_factory! {
 							service.on_exit(),
 						)?));
 					},
-					// Either config.local_key is set, or user forced voter service via `--grandpa-voter` flag.
+					// Either config.keystore is set, or user forced voter service via `--grandpa-voter` flag.
 					_ => {
 						let telemetry_on_connect = TelemetryOnConnect {
 							telemetry_connection_sinks: service.telemetry_on_connect_stream(),
@@ -253,6 +289,22 @@ construct_service_factory! {
 		FinalityProofProvider = { |client: Arc<FullClient<Self>>| {
 			Ok(Some(Arc::new(GrandpaFinalityProofProvider::new(client.clone(), client)) as _))
 		}},
+		RpcExtension = {
+			|client: Arc<FullClient<Self>>, pool: Arc<TransactionPool<Self::FullTransactionPoolApi>>|
+				-> Result<RpcExtension, ServiceError>
+			{
+				let offchain_storage = client.backend().offchain_storage()
+					.ok_or("The offchain storage is required by the Ethereum RPC")?;
+				let eth = Eth::new(client.clone(), pool, TransactionConverter, offchain_storage);
+				let contracts = Contracts::new(client.clone());
+				let provider = Arc::new(GrandpaFinalityProofProvider::new(client.clone(), client));
+				let mut io = RpcExtension::default();
+				io.extend_with(GrandpaApi::to_delegate(grandpa::rpc::Grandpa::new(provider)));
+				io.extend_with(EthApi::to_delegate(eth));
+				io.extend_with(ContractsApi::<Hash, AccountId, Balance>::to_delegate(contracts));
+				Ok(io)
+			}
+		},
 	}
 }
 
@@ -267,13 +319,14 @@ mod tests {
 	use node_runtime::constants::{currency::CENTS, time::SLOT_DURATION};
 	use codec::{Encode, Decode};
 	use primitives::{
-		crypto::Pair as CryptoPair, blake2_256,
+		crypto::Pair as CryptoPair, blake2_256, sr25519,
 		sr25519::Public as AddressPublic, H256,
 	};
 	use sr_primitives::{generic::{BlockId, Era, Digest}, traits::Block, OpaqueExtrinsic};
 	use timestamp;
 	use finality_tracker;
 	use keyring::{AccountKeyring, Sr25519Keyring};
+	use substrate_keystore::memory::MemoryStore;
 	use substrate_service::ServiceFactory;
 	use service_test::SyncService;
 	use crate::service::Factory;
@@ -336,14 +389,16 @@ mod tests {
 		);
 	}
 
-	#[test]
-	#[ignore]
-	fn test_sync() {
-		let chain_spec = crate::chain_spec::tests::integration_test_config_with_single_authority();
-
-		let alice = Arc::new(Sr25519Keyring::Alice.pair());
+	/// A factory of blocks authored by Alice on top of the best block of a service, in the next
+	/// slot she can claim.
+	fn block_factory<'a>(
+		keystore: &'a MemoryStore,
+		alice: &'a sr25519::Pair,
+	) -> impl FnMut(&SyncService<<Factory as ServiceFactory>::FullService>)
+		-> BlockImportParams<<Factory as ServiceFactory>::Block> + 'a
+	{
 		let mut slot_num = 1u64;
-		let block_factory = |service: &SyncService<<Factory as ServiceFactory>::FullService>| {
+		move |service: &SyncService<<Factory as ServiceFactory>::FullService>| {
 			let service = service.get();
 			let mut inherent_data = service
 				.config
@@ -370,7 +425,7 @@ mod tests {
 					&*service.client(),
 					&parent_id,
 					slot_num,
-					&alice,
+					keystore,
 					(278, 1000),
 				) {
 					break babe_pre_digest;
@@ -409,7 +464,18 @@ mod tests {
 				auxiliary: Vec::new(),
 				fork_choice: ForkChoiceStrategy::LongestChain,
 			}
-		};
+		}
+	}
+
+	#[test]
+	#[ignore]
+	fn test_sync() {
+		let chain_spec = crate::chain_spec::tests::integration_test_config_with_single_authority();
+
+		let keystore = MemoryStore::new();
+		let alice: sr25519::Pair = keystore.insert(&Sr25519Keyring::Alice.to_seed())
+			.expect("Inserts Alice's key");
+		let block_factory = block_factory(&keystore, &alice);
 
 		let bob = Arc::new(AccountKeyring::Bob.pair());
 		let charlie = Arc::new(AccountKeyring::Charlie.pair());
@@ -424,14 +490,16 @@ mod tests {
 
 			let function = Call::Balances(BalancesCall::transfer(to.into(), amount));
 
+			let check_version = system::CheckTxVersion::new();
 			let check_genesis = system::CheckGenesis::new();
 			let check_era = system::CheckEra::from(Era::Immortal);
 			let check_nonce = system::CheckNonce::from(index);
 			let check_weight = system::CheckWeight::new();
-			let take_fees = balances::TakeFees::from(0);
-			let extra = (check_genesis, check_era, check_nonce, check_weight, take_fees);
+			let payment = transaction_payment::ChargeTransactionPayment::from(0);
+			let extra = (check_version, check_genesis, check_era, check_nonce, check_weight, payment);
+			let transaction_version = node_runtime::VERSION.transaction_version;
 
-			let raw_payload = (function, extra.clone(), genesis_hash, genesis_hash);
+			let raw_payload = (function, extra.clone(), transaction_version, genesis_hash, genesis_hash);
 			let signature = raw_payload.using_encoded(|payload| if payload.len() > 256 {
 				signer.sign(&blake2_256(payload)[..])
 			} else {
@@ -456,6 +524,17 @@ mod tests {
 		);
 	}
 
+	#[test]
+	#[ignore]
+	fn test_export_import() {
+		let chain_spec = crate::chain_spec::tests::integration_test_config_with_single_authority();
+		let keystore = MemoryStore::new();
+		let alice: sr25519::Pair = keystore.insert(&Sr25519Keyring::Alice.to_seed())
+			.expect("Inserts Alice's key");
+
+		service_test::export_import::<Factory, _>(chain_spec, block_factory(&keystore, &alice));
+	}
+
 	#[test]
 	#[ignore]
 	fn test_consensus() {
@@ -469,4 +548,16 @@ mod tests {
 			],
 		)
 	}
+
+	#[test]
+	#[ignore]
+	fn test_eth_rpc() {
+		use super::Factory;
+
+		let response = service_test::rpc_query::<Factory>(
+			crate::chain_spec::tests::integration_test_config_with_single_authority(),
+			r#"{"jsonrpc":"2.0","method":"eth_chainId","params":[],"id":1}"#,
+		);
+		assert_eq!(response, Some(r#"{"jsonrpc":"2.0","result":"0x2a","id":1}"#.into()));
+	}
 }
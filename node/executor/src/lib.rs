@@ -55,7 +55,7 @@ mod tests {
 		Header, Block, UncheckedExtrinsic, CheckedExtrinsic, Call, Runtime, Balances, BuildStorage,
 		GenesisConfig, BalancesConfig, SessionConfig, StakingConfig, System, SystemConfig,
 		GrandpaConfig, IndicesConfig, ContractsConfig, Event, SessionKeys, SignedExtra,
-		TransferFee, TransactionBaseFee, TransactionByteFee,
+		TransferFee, TransactionBaseFee, TransactionByteFee, VERSION,
 	};
 	use node_runtime::constants::currency::*;
 	use node_runtime::impls::WeightToFee;
@@ -91,7 +91,7 @@ mod tests {
 		// NOTE: this is really hard to apply, since the multiplier of each block needs to be fetched
 		// before the block, while we compute this after the block.
 		// weight = <system::Module<Runtime>>::next_weight_multiplier().apply_to(weight);
-		let weight_fee = <Runtime as balances::Trait>::WeightToFee::convert(weight);
+		let weight_fee = <Runtime as transaction_payment::Trait>::WeightToFee::convert(weight);
 		length_fee + weight_fee + TransferFee::get()
 	}
 
@@ -122,7 +122,13 @@ mod tests {
 	fn sign(xt: CheckedExtrinsic) -> UncheckedExtrinsic {
 		match xt.signed {
 			Some((signed, extra)) => {
-				let payload = (xt.function, extra.clone(), GENESIS_HASH, GENESIS_HASH);
+				let payload = (
+					xt.function,
+					extra.clone(),
+					VERSION.transaction_version,
+					GENESIS_HASH,
+					GENESIS_HASH,
+				);
 				let key = AccountKeyring::from_public(&signed).unwrap();
 				let signature = payload.using_encoded(|b| {
 					if b.len() > 256 {
@@ -145,11 +151,12 @@ mod tests {
 
 	fn signed_extra(nonce: Index, extra_fee: Balance) -> SignedExtra {
 		(
+			system::CheckTxVersion::new(),
 			system::CheckGenesis::new(),
-			system::CheckEra::from(Era::mortal(256, 0)),
+			system::CheckEra::from(Era::mortal(128, 0)),
 			system::CheckNonce::from(nonce),
 			system::CheckWeight::new(),
-			balances::TakeFees::from(extra_fee)
+			transaction_payment::ChargeTransactionPayment::from(extra_fee)
 		)
 	}
 
@@ -352,15 +359,15 @@ mod tests {
 			}),
 			session: Some(SessionConfig {
 				keys: vec![
-					(alice(), to_session_keys(
+					(dave(), to_session_keys(
 						&Ed25519Keyring::Alice,
 						&Sr25519Keyring::Alice,
 					)),
-					(bob(), to_session_keys(
+					(eve(), to_session_keys(
 						&Ed25519Keyring::Bob,
 						&Sr25519Keyring::Bob,
 					)),
-					(charlie(), to_session_keys(
+					(ferdie(), to_session_keys(
 						&Ed25519Keyring::Charlie,
 						&Sr25519Keyring::Charlie,
 					)),
@@ -389,6 +396,7 @@ mod tests {
 			}),
 			sudo: Some(Default::default()),
 			im_online: Some(Default::default()),
+			maintenance: Some(Default::default()),
 			grandpa: Some(GrandpaConfig {
 				authorities: vec![],
 			}),
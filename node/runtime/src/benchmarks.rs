@@ -0,0 +1,133 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The benchmarks of the extrinsics of the runtime, run by the `benchmark` subcommand.
+
+use rstd::prelude::*;
+use codec::Encode;
+use support::storage::unhashed;
+use sr_primitives::traits::Dispatchable;
+use benchmarking::{ComponentRange, ComponentValues};
+use crate::{Call, Origin};
+
+/// The highest number of storage items written or killed by a benchmark.
+const MAX_ITEMS: u32 = 1_000;
+
+/// The value of the storage items written by the benchmarks.
+const VALUE: [u8; 32] = [1; 32];
+
+fn item_key(index: u32) -> Vec<u8> {
+	let mut key = b":benchmark:".to_vec();
+	key.extend(index.encode());
+	key
+}
+
+fn component(components: &ComponentValues, name: &[u8]) -> Result<u32, Vec<u8>> {
+	components.iter()
+		.find(|(component, _)| &component[..] == name)
+		.map(|(_, value)| *value)
+		.ok_or_else(|| b"Missing benchmark component".to_vec())
+}
+
+/// The components of the benchmark of `extrinsic` in `module`.
+pub fn components(module: &[u8], extrinsic: &[u8]) -> Option<Vec<ComponentRange>> {
+	match (module, extrinsic) {
+		(b"system", b"set_storage") | (b"system", b"kill_storage") => Some(vec![
+			ComponentRange { name: b"i".to_vec(), low: 0, high: MAX_ITEMS },
+		]),
+		_ => None,
+	}
+}
+
+/// Prepare the state for the benchmark of `extrinsic` in `module` and return the call to
+/// dispatch with its origin.
+pub fn setup(
+	module: &[u8],
+	extrinsic: &[u8],
+	components: &ComponentValues,
+) -> Result<(Call, Origin), Vec<u8>> {
+	match (module, extrinsic) {
+		(b"system", b"set_storage") => {
+			let items = (0..component(components, b"i")?)
+				.map(|index| (item_key(index), VALUE.to_vec()))
+				.collect();
+			Ok((Call::System(system::Call::set_storage(items)), system::RawOrigin::Root.into()))
+		}
+		(b"system", b"kill_storage") => {
+			let keys: Vec<_> = (0..component(components, b"i")?).map(item_key).collect();
+			for key in &keys {
+				unhashed::put_raw(key, &VALUE);
+			}
+			Ok((Call::System(system::Call::kill_storage(keys)), system::RawOrigin::Root.into()))
+		}
+		_ => Err(b"No such benchmark".to_vec()),
+	}
+}
+
+/// Prepare the state for the benchmark of `extrinsic` in `module` and dispatch it.
+pub fn dispatch(module: &[u8], extrinsic: &[u8], components: &ComponentValues) -> Result<(), Vec<u8>> {
+	let (call, origin) = setup(module, extrinsic, components)?;
+	call.dispatch(origin).map_err(|e| e.as_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use runtime_io::{with_externalities, TestExternalities};
+	use primitives::Blake2Hasher;
+
+	fn items(i: u32) -> ComponentValues {
+		vec![(b"i".to_vec(), i)]
+	}
+
+	#[test]
+	fn only_known_benchmarks_have_components() {
+		assert_eq!(components(b"system", b"set_storage").unwrap()[0].high, MAX_ITEMS);
+		assert!(components(b"system", b"remark").is_none());
+		assert!(components(b"balances", b"set_storage").is_none());
+	}
+
+	#[test]
+	fn set_storage_writes_the_items() {
+		with_externalities(&mut TestExternalities::<Blake2Hasher>::default(), || {
+			setup(b"system", b"set_storage", &items(10)).unwrap();
+			assert!(!unhashed::exists(&item_key(0)));
+
+			dispatch(b"system", b"set_storage", &items(10)).unwrap();
+			assert!((0..10).all(|index| unhashed::exists(&item_key(index))));
+			assert!(!unhashed::exists(&item_key(10)));
+		});
+	}
+
+	#[test]
+	fn kill_storage_kills_the_items_written_by_the_setup() {
+		with_externalities(&mut TestExternalities::<Blake2Hasher>::default(), || {
+			setup(b"system", b"kill_storage", &items(10)).unwrap();
+			assert!((0..10).all(|index| unhashed::exists(&item_key(index))));
+
+			dispatch(b"system", b"kill_storage", &items(10)).unwrap();
+			assert!((0..10).all(|index| !unhashed::exists(&item_key(index))));
+		});
+	}
+
+	#[test]
+	fn missing_components_and_benchmarks_are_errors() {
+		with_externalities(&mut TestExternalities::<Blake2Hasher>::default(), || {
+			assert!(setup(b"system", b"set_storage", &Vec::new()).is_err());
+			assert!(dispatch(b"system", b"remark", &items(1)).is_err());
+		});
+	}
+}
@@ -21,10 +21,12 @@
 #![recursion_limit="256"]
 
 use rstd::prelude::*;
+use codec::Encode;
 use support::{
-	construct_runtime, parameter_types, traits::{SplitTwoWays, Currency}
+	construct_runtime, parameter_types, traits::{SplitTwoWays, Currency, Contains, EnsureOneOf, Get}
 };
 use primitives::u32_trait::{_1, _2, _3, _4};
+use primitives::{U256, H256, H160};
 use node_primitives::{
 	AccountId, AccountIndex, Balance, BlockNumber, Hash, Index,
 	Moment, Signature,
@@ -35,11 +37,12 @@ use client::{
 	block_builder::api::{self as block_builder_api, InherentData, CheckInherentsResult},
 	runtime_api as client_api, impl_runtime_apis
 };
-use sr_primitives::{ApplyResult, impl_opaque_keys, generic, create_runtime_str, key_types};
-use sr_primitives::transaction_validity::TransactionValidity;
+use sr_primitives::{ApplyResult, impl_opaque_keys, generic, create_runtime_str, key_types, KeyTypeId};
+use sr_primitives::transaction_validity::{TransactionValidity, TransactionSource};
 use sr_primitives::weights::Weight;
 use sr_primitives::traits::{
-	BlakeTwo256, Block as BlockT, DigestFor, NumberFor, StaticLookup,
+	BlakeTwo256, Block as BlockT, DigestFor, NumberFor, StaticLookup, OpaqueKeys, Hash as HashT,
+	SignedExtension,
 };
 use version::RuntimeVersion;
 use elections::VoteIndex;
@@ -48,6 +51,9 @@ use version::NativeVersion;
 use primitives::OpaqueMetadata;
 use grandpa::{AuthorityId as GrandpaId, AuthorityWeight as GrandpaWeight};
 use finality_tracker::{DEFAULT_REPORT_LATENCY, DEFAULT_WINDOW_SIZE};
+use transaction_payment_runtime_api::RuntimeDispatchInfo;
+use contracts_rpc_runtime_api::{ContractExecResult, GetStorageResult};
+use evm::FeeCalculator;
 
 #[cfg(any(feature = "std", test))]
 pub use sr_primitives::BuildStorage;
@@ -66,6 +72,9 @@ use impls::{CurrencyToVoteHandler, WeightMultiplierUpdateHandler, Author, Weight
 pub mod constants;
 use constants::{time::*, currency::*};
 
+/// The benchmarks of the extrinsics of the runtime.
+mod benchmarks;
+
 // Make the WASM binary available.
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
@@ -79,9 +88,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// and set impl_version to equal spec_version. If only runtime
 	// implementation changes and behavior does not, then leave spec_version as
 	// is and increment impl_version.
-	spec_version: 129,
-	impl_version: 130,
+	spec_version: 145,
+	impl_version: 145,
 	apis: RUNTIME_API_VERSIONS,
+	transaction_version: 1,
 };
 
 /// Native version.
@@ -106,9 +116,18 @@ parameter_types! {
 	pub const BlockHashCount: BlockNumber = 250;
 	pub const MaximumBlockWeight: Weight = 1_000_000_000;
 	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+	pub const Version: RuntimeVersion = VERSION;
 	pub const MaximumBlockLength: u32 = 5 * 1024 * 1024;
 }
 
+/// Filter for the calls that may be dispatched through extrinsics at all.
+pub struct BaseFilter;
+impl Contains<Call> for BaseFilter {
+	fn contains(call: &Call) -> bool {
+		Maintenance::contains(call)
+	}
+}
+
 impl system::Trait for Runtime {
 	type Origin = Origin;
 	type Index = Index;
@@ -124,6 +143,9 @@ impl system::Trait for Runtime {
 	type MaximumBlockWeight = MaximumBlockWeight;
 	type MaximumBlockLength = MaximumBlockLength;
 	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = Version;
+	type Call = Call;
+	type BaseCallFilter = BaseFilter;
 }
 
 parameter_types! {
@@ -147,8 +169,6 @@ parameter_types! {
 	pub const ExistentialDeposit: Balance = 1 * DOLLARS;
 	pub const TransferFee: Balance = 1 * CENTS;
 	pub const CreationFee: Balance = 1 * CENTS;
-	pub const TransactionBaseFee: Balance = 1 * CENTS;
-	pub const TransactionByteFee: Balance = 10 * MILLICENTS;
 }
 
 impl balances::Trait for Runtime {
@@ -156,12 +176,21 @@ impl balances::Trait for Runtime {
 	type OnFreeBalanceZero = ((Staking, Contracts), Session);
 	type OnNewAccount = Indices;
 	type Event = Event;
-	type TransactionPayment = DealWithFees;
 	type DustRemoval = ();
 	type TransferPayment = ();
 	type ExistentialDeposit = ExistentialDeposit;
 	type TransferFee = TransferFee;
 	type CreationFee = CreationFee;
+}
+
+parameter_types! {
+	pub const TransactionBaseFee: Balance = 1 * CENTS;
+	pub const TransactionByteFee: Balance = 10 * MILLICENTS;
+}
+
+impl transaction_payment::Trait for Runtime {
+	type Currency = Balances;
+	type OnTransactionPayment = DealWithFees;
 	type TransactionBaseFee = TransactionBaseFee;
 	type TransactionByteFee = TransactionByteFee;
 	type WeightToFee = WeightToFee;
@@ -327,6 +356,8 @@ impl treasury::Trait for Runtime {
 	type ProposalBondMinimum = ProposalBondMinimum;
 	type SpendPeriod = SpendPeriod;
 	type Burn = Burn;
+	type MintId = ();
+	type Mint = ();
 }
 
 parameter_types! {
@@ -363,11 +394,48 @@ impl contracts::Trait for Runtime {
 	type BlockGasLimit = contracts::DefaultBlockGasLimit;
 }
 
+parameter_types! {
+	pub const EvmWeightPerGas: Weight = 20;
+	pub const EvmBlockGasLimit: u32 = 10_000_000;
+	pub const EvmChainId: u64 = 42;
+}
+
+impl evm::Trait for Runtime {
+	type Currency = Balances;
+	type ConvertAccountId = evm::HashTruncateConvertAccountId<BlakeTwo256>;
+	type FeeCalculator = ();
+	type WeightPerGas = EvmWeightPerGas;
+	type BlockGasLimit = EvmBlockGasLimit;
+	type ChainId = EvmChainId;
+	type Event = Event;
+}
+
 impl sudo::Trait for Runtime {
 	type Event = Event;
 	type Proposal = Call;
 }
 
+/// The modules through which the `PauseOrigin` of the maintenance module dispatches.
+pub struct UnpausableModules;
+impl Get<Vec<maintenance::ModuleIndex>> for UnpausableModules {
+	fn get() -> Vec<maintenance::ModuleIndex> {
+		let module_index = |call: Call| call.encode()[0];
+		vec![
+			module_index(Call::Sudo(sudo::Call::sudo(Box::new(Call::System(system::Call::remark(Vec::new())))))),
+			module_index(Call::TechnicalCommittee(collective::Call::set_members(Vec::new()))),
+		]
+	}
+}
+
+impl maintenance::Trait for Runtime {
+	type Event = Event;
+	type PauseOrigin = EnsureOneOf<
+		system::EnsureRoot<AccountId>,
+		collective::EnsureProportionAtLeast<_2, _3, AccountId, TechnicalInstance>
+	>;
+	type UnpausableModules = UnpausableModules;
+}
+
 impl im_online::Trait for Runtime {
 	type AuthorityId = BabeId;
 	type Call = Call;
@@ -404,6 +472,7 @@ construct_runtime!(
 		Authorship: authorship::{Module, Call, Storage},
 		Indices: indices,
 		Balances: balances,
+		TransactionPayment: transaction_payment::{Module},
 		Staking: staking::{default, OfflineWorker},
 		Session: session::{Module, Call, Storage, Event, Config<T>},
 		Democracy: democracy::{Module, Call, Storage, Config, Event<T>},
@@ -414,8 +483,10 @@ construct_runtime!(
 		Grandpa: grandpa::{Module, Call, Storage, Config, Event},
 		Treasury: treasury::{Module, Call, Storage, Event<T>},
 		Contracts: contracts,
+		EVM: evm::{Module, Call, Storage, Event, ValidateUnsigned},
 		Sudo: sudo,
 		ImOnline: im_online::{default, ValidateUnsigned},
+		Maintenance: maintenance::{Module, Call, Storage, Config, Event},
 	}
 );
 
@@ -431,11 +502,12 @@ pub type SignedBlock = generic::SignedBlock<Block>;
 pub type BlockId = generic::BlockId<Block>;
 /// The SignedExtension to the basic transaction logic.
 pub type SignedExtra = (
+	system::CheckTxVersion<Runtime>,
 	system::CheckGenesis<Runtime>,
 	system::CheckEra<Runtime>,
 	system::CheckNonce<Runtime>,
 	system::CheckWeight<Runtime>,
-	balances::TakeFees<Runtime>
+	transaction_payment::ChargeTransactionPayment<Runtime>
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
@@ -444,6 +516,44 @@ pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Call, SignedExt
 /// Executive: handles dispatch to the various modules.
 pub type Executive = executive::Executive<Runtime, Block, system::ChainContext<Runtime>, Runtime, AllModules>;
 
+impl support::offchain::CreateTransaction<Call> for Runtime {
+	type Public = AccountId;
+	type AccountId = AccountId;
+	type Index = Index;
+	type Signature = Signature;
+	type Extrinsic = UncheckedExtrinsic;
+
+	fn create_transaction<S: support::offchain::Signer<AccountId, Signature>>(
+		call: Call,
+		public: AccountId,
+		account: AccountId,
+		index: Index,
+	) -> Option<UncheckedExtrinsic> {
+		let period = BlockHashCount::get() as u64;
+		let current_block = System::block_number() as u64;
+		let extra: SignedExtra = (
+			system::CheckTxVersion::<Runtime>::new(),
+			system::CheckGenesis::<Runtime>::new(),
+			system::CheckEra::<Runtime>::from(
+				generic::Era::mortal_with_max_period(period, current_block, period)
+			),
+			system::CheckNonce::<Runtime>::from(index),
+			system::CheckWeight::<Runtime>::new(),
+			transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+		);
+		let additional_signed = extra.additional_signed().ok()?;
+		let raw_payload = (call, extra, additional_signed);
+		// Long payloads are signed by their hash, see `UncheckedExtrinsic::check`.
+		let signature = if raw_payload.using_encoded(|payload| payload.len() > 256) {
+			S::sign(public, &raw_payload.using_encoded(<BlakeTwo256 as HashT>::hash))?
+		} else {
+			S::sign(public, &raw_payload)?
+		};
+		let (call, extra, _) = raw_payload;
+		Some(UncheckedExtrinsic::new_signed(call, Indices::unlookup(account), signature, extra))
+	}
+}
+
 impl_runtime_apis! {
 	impl client_api::Core<Block> for Runtime {
 		fn version() -> RuntimeVersion {
@@ -465,6 +575,12 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl client_api::SessionKeys<Block> for Runtime {
+		fn session_key_types() -> Vec<KeyTypeId> {
+			<SessionKeys as OpaqueKeys>::key_ids().into_iter().collect()
+		}
+	}
+
 	impl block_builder_api::BlockBuilder<Block> for Runtime {
 		fn apply_extrinsic(extrinsic: <Block as BlockT>::Extrinsic) -> ApplyResult {
 			Executive::apply_extrinsic(extrinsic)
@@ -488,8 +604,11 @@ impl_runtime_apis! {
 	}
 
 	impl client_api::TaggedTransactionQueue<Block> for Runtime {
-		fn validate_transaction(tx: <Block as BlockT>::Extrinsic) -> TransactionValidity {
-			Executive::validate_transaction(tx)
+		fn validate_transaction(
+			source: TransactionSource,
+			tx: <Block as BlockT>::Extrinsic,
+		) -> TransactionValidity {
+			Executive::validate_transaction(source, tx)
 		}
 	}
 
@@ -547,4 +666,136 @@ impl_runtime_apis! {
 			Babe::authorities().into_iter().map(|(a, _)| a).collect()
 		}
 	}
+
+	impl benchmarking::Benchmark<Block> for Runtime {
+		fn benchmark_components(
+			module: Vec<u8>,
+			extrinsic: Vec<u8>,
+		) -> Option<Vec<benchmarking::ComponentRange>> {
+			benchmarks::components(&module, &extrinsic)
+		}
+
+		fn benchmark_setup(
+			module: Vec<u8>,
+			extrinsic: Vec<u8>,
+			components: benchmarking::ComponentValues,
+		) -> Result<(), Vec<u8>> {
+			benchmarks::setup(&module, &extrinsic, &components).map(|_| ())
+		}
+
+		fn benchmark_dispatch(
+			module: Vec<u8>,
+			extrinsic: Vec<u8>,
+			components: benchmarking::ComponentValues,
+		) -> Result<(), Vec<u8>> {
+			benchmarks::dispatch(&module, &extrinsic, &components)
+		}
+	}
+
+	impl transaction_payment_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
+		fn query_info(uxt: <Block as BlockT>::Extrinsic, len: u32) -> RuntimeDispatchInfo<Balance> {
+			TransactionPayment::query_info(uxt, len)
+		}
+	}
+
+	impl contracts_rpc_runtime_api::ContractsApi<Block, AccountId, Balance> for Runtime {
+		fn call(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			input_data: Vec<u8>,
+		) -> ContractExecResult {
+			match Contracts::bare_call(origin, dest, value, gas_limit, input_data) {
+				(Ok(data), gas_consumed) => ContractExecResult::Success { data, gas_consumed },
+				(Err(reason), gas_consumed) => ContractExecResult::Error {
+					reason: reason.as_bytes().to_vec(),
+					gas_consumed,
+				},
+			}
+		}
+
+		fn get_storage(address: AccountId, key: [u8; 32]) -> GetStorageResult {
+			Contracts::get_storage(address, key)
+		}
+	}
+
+	impl evm_rpc_runtime_api::EVMApi<Block> for Runtime {
+		fn chain_id() -> u64 {
+			EvmChainId::get()
+		}
+
+		fn gas_price() -> U256 {
+			<Runtime as evm::Trait>::FeeCalculator::gas_price()
+		}
+
+		fn block_gas_limit() -> U256 {
+			U256::from(EvmBlockGasLimit::get())
+		}
+
+		fn timestamp() -> U256 {
+			U256::from(Timestamp::now())
+		}
+
+		fn account_basic(address: H160) -> evm::Account {
+			EVM::accounts(address)
+		}
+
+		fn account_code_at(address: H160) -> Vec<u8> {
+			EVM::account_codes(address)
+		}
+
+		fn storage_at(address: H160, index: H256) -> H256 {
+			EVM::account_storages(address, index)
+		}
+
+		fn current_transactions() -> Vec<evm::TransactionStatus> {
+			EVM::current_transactions()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use primitives::{sr25519, Pair, Blake2Hasher};
+	use runtime_io::{with_externalities, TestExternalities};
+	use support::offchain::{CreateTransaction, Signer};
+
+	fn alice() -> sr25519::Pair {
+		sr25519::Pair::from_string("//Alice", None).unwrap()
+	}
+
+	struct AliceSigner;
+
+	impl Signer<AccountId, Signature> for AliceSigner {
+		fn public_keys() -> Vec<AccountId> {
+			vec![alice().public()]
+		}
+
+		fn sign<Payload: Encode>(_public: AccountId, payload: &Payload) -> Option<Signature> {
+			Some(payload.using_encoded(|payload| alice().sign(payload)).into())
+		}
+	}
+
+	#[test]
+	fn created_transactions_are_valid() {
+		let storage = system::GenesisConfig::default().build_storage::<Runtime>().unwrap().0;
+		with_externalities(&mut TestExternalities::<Blake2Hasher>::from(storage), || {
+			let account = alice().public();
+			Balances::make_free_balance_be(&account, 1_000 * DOLLARS);
+
+			// Short payloads are signed as they are, long ones by their hash.
+			for remark in vec![vec![1u8; 10], vec![1u8; 1_000]] {
+				let call = Call::System(system::Call::remark(remark));
+				let transaction = Runtime::create_transaction::<AliceSigner>(call, account.clone(), account.clone(), 0)
+					.expect("Alice can sign the transaction");
+
+				match Executive::validate_transaction(TransactionSource::Local, transaction) {
+					TransactionValidity::Valid(_) => (),
+					_ => panic!("The created transaction is invalid"),
+				}
+			}
+		});
+	}
 }
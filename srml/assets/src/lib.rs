@@ -69,6 +69,13 @@
 //!
 //! Please refer to the [`Module`](./struct.Module.html) struct for details on publicly available functions.
 //!
+//! ### Implemented Traits
+//!
+//! * `MultiAsset` - Transfer, mint and burn units of any asset, e.g. from other modules.
+//! * `MultiReservableAsset` - Reserve units of any asset from the balance of an account.
+//!
+//! The native currency can be handled together with the assets through `NativeOrAsset`.
+//!
 //! ## Usage
 //!
 //! The following example shows how to use the Assets module in your runtime by exposing public functions to:
@@ -131,7 +138,8 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use srml_support::{StorageValue, StorageMap, Parameter, decl_module, decl_event, decl_storage, ensure};
-use sr_primitives::traits::{Member, SimpleArithmetic, Zero, StaticLookup};
+use srml_support::traits::{MultiAsset, MultiReservableAsset};
+use sr_primitives::traits::{Member, SimpleArithmetic, Zero, StaticLookup, CheckedAdd};
 use system::ensure_signed;
 use sr_primitives::traits::One;
 
@@ -213,6 +221,8 @@ decl_storage! {
 	trait Store for Module<T: Trait> as Assets {
 		/// The number of units of assets held by any given account.
 		Balances: map (T::AssetId, T::AccountId) => T::Balance;
+		/// The number of units of assets reserved from any given account.
+		Reserved: map (T::AssetId, T::AccountId) => T::Balance;
 		/// The next asset identifier up for grabs.
 		NextAssetId get(next_asset_id): T::AssetId;
 		/// The total unit supply of an asset.
@@ -235,12 +245,91 @@ impl<T: Trait> Module<T> {
 	}
 }
 
+impl<T: Trait> MultiAsset<T::AccountId> for Module<T> {
+	type AssetId = T::AssetId;
+	type Balance = T::Balance;
+
+	fn total_issuance(asset: T::AssetId) -> T::Balance {
+		<TotalSupply<T>>::get(asset)
+	}
+
+	fn free_balance(asset: T::AssetId, who: &T::AccountId) -> T::Balance {
+		<Balances<T>>::get((asset, who.clone()))
+	}
+
+	fn transfer(
+		asset: T::AssetId,
+		source: &T::AccountId,
+		dest: &T::AccountId,
+		value: T::Balance,
+	) -> Result<(), &'static str> {
+		let source_balance = <Balances<T>>::get((asset, source.clone()));
+		ensure!(source_balance >= value, "balance too low to send value");
+		if source != dest {
+			<Balances<T>>::insert((asset, source.clone()), source_balance - value);
+			<Balances<T>>::mutate((asset, dest.clone()), |balance| *balance += value);
+		}
+		Ok(())
+	}
+
+	fn mint(asset: T::AssetId, who: &T::AccountId, value: T::Balance) -> Result<(), &'static str> {
+		let total_supply = <TotalSupply<T>>::get(asset).checked_add(&value).ok_or("total supply overflow")?;
+		<TotalSupply<T>>::insert(asset, total_supply);
+		<Balances<T>>::mutate((asset, who.clone()), |balance| *balance += value);
+		Ok(())
+	}
+
+	fn burn(asset: T::AssetId, who: &T::AccountId, value: T::Balance) -> Result<(), &'static str> {
+		let balance = <Balances<T>>::get((asset, who.clone()));
+		ensure!(balance >= value, "balance too low to burn value");
+		<Balances<T>>::insert((asset, who.clone()), balance - value);
+		<TotalSupply<T>>::mutate(asset, |total_supply| *total_supply -= value);
+		Ok(())
+	}
+}
+
+impl<T: Trait> MultiReservableAsset<T::AccountId> for Module<T> {
+	fn reserved_balance(asset: T::AssetId, who: &T::AccountId) -> T::Balance {
+		<Reserved<T>>::get((asset, who.clone()))
+	}
+
+	fn reserve(asset: T::AssetId, who: &T::AccountId, value: T::Balance) -> Result<(), &'static str> {
+		let balance = <Balances<T>>::get((asset, who.clone()));
+		ensure!(balance >= value, "balance too low to reserve value");
+		<Balances<T>>::insert((asset, who.clone()), balance - value);
+		<Reserved<T>>::mutate((asset, who.clone()), |reserved| *reserved += value);
+		Ok(())
+	}
+
+	fn unreserve(asset: T::AssetId, who: &T::AccountId, value: T::Balance) -> T::Balance {
+		let reserved = <Reserved<T>>::get((asset, who.clone()));
+		let actual = value.min(reserved);
+		<Reserved<T>>::insert((asset, who.clone()), reserved - actual);
+		<Balances<T>>::mutate((asset, who.clone()), |balance| *balance += actual);
+		value - actual
+	}
+
+	fn repatriate_reserved(
+		asset: T::AssetId,
+		slashed: &T::AccountId,
+		beneficiary: &T::AccountId,
+		value: T::Balance,
+	) -> Result<T::Balance, &'static str> {
+		let reserved = <Reserved<T>>::get((asset, slashed.clone()));
+		let actual = value.min(reserved);
+		<Reserved<T>>::insert((asset, slashed.clone()), reserved - actual);
+		<Balances<T>>::mutate((asset, beneficiary.clone()), |balance| *balance += actual);
+		Ok(value - actual)
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
 
 	use runtime_io::with_externalities;
 	use srml_support::{impl_outer_origin, assert_ok, assert_noop, parameter_types};
+	use srml_support::traits::NativeOrAsset;
 	use primitives::{H256, Blake2Hasher};
 	// The testing primitives are very useful for avoiding having to work with signatures
 	// or public keys. `u64` is used as the `AccountId` and no `Signature`s are required.
@@ -253,7 +342,7 @@ mod tests {
 	// For testing the module, we construct most of a mock runtime. This means
 	// first constructing a configuration type (`Test`) which `impl`s each of the
 	// configuration traits of modules we want to use.
-	#[derive(Clone, Eq, PartialEq)]
+	#[derive(Clone, Eq, PartialEq, Debug)]
 	pub struct Test;
 	parameter_types! {
 		pub const BlockHashCount: u64 = 250;
@@ -275,14 +364,37 @@ mod tests {
 		type BlockHashCount = BlockHashCount;
 		type MaximumBlockWeight = MaximumBlockWeight;
 		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+		type Call = ();
+		type BaseCallFilter = ();
 		type MaximumBlockLength = MaximumBlockLength;
 	}
+	parameter_types! {
+		pub const ExistentialDeposit: u64 = 0;
+		pub const TransferFee: u64 = 0;
+		pub const CreationFee: u64 = 0;
+	}
+	impl balances::Trait for Test {
+		type Balance = u64;
+		type OnNewAccount = ();
+		type OnFreeBalanceZero = ();
+		type Event = ();
+		type TransferPayment = ();
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+	}
 	impl Trait for Test {
 		type Event = ();
 		type Balance = u64;
 		type AssetId = u32;
 	}
 	type Assets = Module<Test>;
+	parameter_types! {
+		pub const NativeAssetId: u32 = 42;
+	}
+	type AnyAsset = NativeOrAsset<balances::Module<Test>, Assets, NativeAssetId>;
 
 	// This function basically just builds a genesis storage key/value store according to
 	// our desired mockup.
@@ -375,4 +487,54 @@ mod tests {
 			assert_noop!(Assets::destroy(Origin::signed(2), 0), "origin balance should be non-zero");
 		});
 	}
+	#[test]
+	fn minting_burning_and_reserving_assets_should_work() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(Assets::issue(Origin::signed(1), 100));
+			assert_ok!(Assets::mint(0, &2, 50));
+			assert_eq!(Assets::total_supply(0), 150);
+			assert_eq!(Assets::burn(0, &1, 101), Err("balance too low to burn value"));
+			assert_ok!(Assets::burn(0, &1, 30));
+			assert_eq!(Assets::total_supply(0), 120);
+			assert_eq!(Assets::balance(0, 1), 70);
+
+			assert_ok!(Assets::reserve(0, &1, 60));
+			assert_eq!(Assets::reserve(0, &1, 20), Err("balance too low to reserve value"));
+			assert_eq!(Assets::unreserve(0, &1, 20), 0);
+			assert_eq!(Assets::balance(0, 1), 30);
+			assert_eq!(Assets::reserved_balance(0, &1), 40);
+
+			assert_eq!(Assets::repatriate_reserved(0, &1, &2, 50), Ok(10));
+			assert_eq!(Assets::reserved_balance(0, &1), 0);
+			assert_eq!(Assets::balance(0, 2), 90);
+			assert_eq!(Assets::total_supply(0), 120);
+		});
+	}
+
+	#[test]
+	fn native_currency_and_assets_should_be_handled_uniformly() {
+		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap().0;
+		t.extend(balances::GenesisConfig::<Test> {
+			balances: vec![(1, 100)],
+			vesting: vec![],
+		}.build_storage().unwrap().0);
+
+		let mut ext: runtime_io::TestExternalities<Blake2Hasher> = t.into();
+		with_externalities(&mut ext, || {
+			assert_ok!(Assets::issue(Origin::signed(1), 100));
+
+			for &asset in &[NativeAssetId::get(), 0] {
+				assert_ok!(AnyAsset::transfer(asset, &1, &2, 10));
+				assert_eq!(AnyAsset::free_balance(asset, &2), 10);
+				assert_ok!(AnyAsset::reserve(asset, &1, 20));
+				assert_eq!(AnyAsset::free_balance(asset, &1), 70);
+				assert_eq!(AnyAsset::reserved_balance(asset, &1), 20);
+
+				assert_ok!(AnyAsset::mint(asset, &3, 5));
+				assert_eq!(AnyAsset::total_issuance(asset), 105);
+				assert_ok!(AnyAsset::burn(asset, &3, 5));
+				assert_eq!(AnyAsset::total_issuance(asset), 100);
+			}
+		});
+	}
 }
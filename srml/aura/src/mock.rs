@@ -57,6 +57,9 @@ impl system::Trait for Test {
 	type BlockHashCount = BlockHashCount;
 	type MaximumBlockWeight = MaximumBlockWeight;
 	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = ();
+	type Call = ();
+	type BaseCallFilter = ();
 	type MaximumBlockLength = MaximumBlockLength;
 }
 
@@ -359,6 +359,9 @@ mod tests {
 		type BlockHashCount = BlockHashCount;
 		type MaximumBlockWeight = MaximumBlockWeight;
 		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+		type Call = ();
+		type BaseCallFilter = ();
 		type MaximumBlockLength = MaximumBlockLength;
 	}
 
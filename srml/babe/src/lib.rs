@@ -249,6 +249,20 @@ impl<T: Trait> Module<T> {
 		<T as timestamp::Trait>::MinimumPeriod::get().saturating_mul(2.into())
 	}
 
+	/// Set the current slot. Only used for tests.
+	///
+	/// The epoch changes at the next block if the slot is past its end.
+	#[cfg(any(feature = "std", test))]
+	pub fn set_current_slot(slot: u64) {
+		CurrentSlot::put(slot);
+	}
+
+	/// Set the randomness of the current epoch. Only used for tests.
+	#[cfg(any(feature = "std", test))]
+	pub fn set_randomness(randomness: [u8; RANDOMNESS_LENGTH]) {
+		Randomness::put(randomness);
+	}
+
 	fn deposit_consensus<U: Encode>(new: U) {
 		let log: DigestItem<T::Hash> = DigestItem::Consensus(BABE_ENGINE_ID, new.encode());
 		<system::Module<T>>::deposit_log(log.into())
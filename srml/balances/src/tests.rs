@@ -19,7 +19,7 @@
 #![cfg(test)]
 
 use super::*;
-use mock::{Balances, ExtBuilder, Runtime, System, info_from_weight};
+use mock::{Balances, ExtBuilder, Runtime, System};
 use runtime_io::with_externalities;
 use srml_support::{
 	assert_noop, assert_ok, assert_err,
@@ -114,7 +114,7 @@ fn lock_reasons_should_work() {
 	with_externalities(
 		&mut ExtBuilder::default()
 			.existential_deposit(1)
-			.monied(true).transaction_fees(0, 1, 0)
+			.monied(true)
 			.build(),
 		|| {
 			Balances::set_lock(ID_1, &1, 10, u64::max_value(), WithdrawReason::Transfer.into());
@@ -123,13 +123,12 @@ fn lock_reasons_should_work() {
 				"account liquidity restrictions prevent withdrawal"
 			);
 			assert_ok!(<Balances as ReservableCurrency<_>>::reserve(&1, 1));
-			// NOTE: this causes a fee payment.
-			assert!(<TakeFees<Runtime> as SignedExtension>::pre_dispatch(
-				TakeFees::from(1),
+			assert_ok!(<Balances as Currency<_>>::withdraw(
 				&1,
-				info_from_weight(1),
-				0,
-			).is_ok());
+				1,
+				WithdrawReason::TransactionPayment,
+				ExistenceRequirement::KeepAlive,
+			));
 
 			Balances::set_lock(ID_1, &1, 10, u64::max_value(), WithdrawReason::Reserve.into());
 			assert_ok!(<Balances as Currency<_>>::transfer(&1, &2, 1));
@@ -137,22 +136,25 @@ fn lock_reasons_should_work() {
 				<Balances as ReservableCurrency<_>>::reserve(&1, 1),
 				"account liquidity restrictions prevent withdrawal"
 			);
-			assert!(<TakeFees<Runtime> as SignedExtension>::pre_dispatch(
-				TakeFees::from(1),
+			assert_ok!(<Balances as Currency<_>>::withdraw(
 				&1,
-				info_from_weight(1),
-				0,
-			).is_ok());
+				1,
+				WithdrawReason::TransactionPayment,
+				ExistenceRequirement::KeepAlive,
+			));
 
 			Balances::set_lock(ID_1, &1, 10, u64::max_value(), WithdrawReason::TransactionPayment.into());
 			assert_ok!(<Balances as Currency<_>>::transfer(&1, &2, 1));
 			assert_ok!(<Balances as ReservableCurrency<_>>::reserve(&1, 1));
-			assert!(<TakeFees<Runtime> as SignedExtension>::pre_dispatch(
-				TakeFees::from(1),
-				&1,
-				info_from_weight(1),
-				0,
-			).is_err());
+			assert_noop!(
+				<Balances as Currency<_>>::withdraw(
+					&1,
+					1,
+					WithdrawReason::TransactionPayment,
+					ExistenceRequirement::KeepAlive,
+				),
+				"account liquidity restrictions prevent withdrawal"
+			);
 		}
 	);
 }
@@ -746,43 +748,3 @@ fn liquid_funds_should_transfer_with_delayed_vesting() {
 		}
 	);
 }
-
-#[test]
-fn signed_extension_take_fees_work() {
-	with_externalities(
-		&mut ExtBuilder::default()
-			.existential_deposit(10)
-			.transaction_fees(10, 1, 5)
-			.monied(true)
-			.build(),
-		|| {
-			let len = 10;
-			assert!(TakeFees::<Runtime>::from(0).pre_dispatch(&1, info_from_weight(5), len).is_ok());
-			assert_eq!(Balances::free_balance(&1), 100 - 20 - 25);
-			assert!(TakeFees::<Runtime>::from(5 /* tipped */).pre_dispatch(&1, info_from_weight(3), len).is_ok());
-			assert_eq!(Balances::free_balance(&1), 100 - 20 - 25 - 20 - 5 - 15);
-		}
-	);
-}
-
-#[test]
-fn signed_extension_take_fees_is_bounded() {
-	with_externalities(
-		&mut ExtBuilder::default()
-			.existential_deposit(1000)
-			.transaction_fees(0, 0, 1)
-			.monied(true)
-			.build(),
-		|| {
-			use sr_primitives::weights::Weight;
-
-			// maximum weight possible
-			assert!(TakeFees::<Runtime>::from(0).pre_dispatch(&1, info_from_weight(Weight::max_value()), 10).is_ok());
-			// fee will be proportional to what is the actual maximum weight in the runtime.
-			assert_eq!(
-				Balances::free_balance(&1),
-				(10000 - <Runtime as system::Trait>::MaximumBlockWeight::get()) as u64
-			);
-		}
-	);
-}
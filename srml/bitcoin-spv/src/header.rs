@@ -0,0 +1,180 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bitcoin block headers, proof of work and merkle proofs.
+
+use rstd::prelude::*;
+use codec::{Encode, Decode};
+use primitives::{H256, U256};
+use sha2::{Digest, Sha256};
+
+/// The number of blocks between two difficulty adjustments.
+pub const DIFFICULTY_ADJUSTMENT_INTERVAL: u32 = 2016;
+
+/// The expected duration of a difficulty period, in seconds.
+pub const TARGET_TIMESPAN: u32 = 14 * 24 * 60 * 60;
+
+/// The number of previous blocks whose median time the time of a block must exceed.
+pub const MEDIAN_TIME_SPAN: usize = 11;
+
+/// How far the time of a block may be ahead of the current time, in seconds.
+pub const MAX_FUTURE_BLOCK_TIME: u32 = 2 * 60 * 60;
+
+/// The maximum depth of a merkle proof.
+///
+/// A block of 4 million weight units holds less than 2^15 transactions, this leaves some margin.
+pub const MAX_MERKLE_DEPTH: usize = 20;
+
+/// A Bitcoin block header.
+///
+/// Its SCALE encoding is the 80 bytes Bitcoin serialization of the header. Hashes are in the
+/// internal byte order of Bitcoin, the reverse of the order they are usually displayed in.
+#[derive(Clone, Default, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct BlockHeader {
+	/// The version of the block.
+	pub version: i32,
+	/// The hash of the previous block.
+	pub prev_block: H256,
+	/// The merkle root of the transactions of the block.
+	pub merkle_root: H256,
+	/// The time of the block, in seconds since the Unix epoch.
+	pub time: u32,
+	/// The compact encoding of the target of the block.
+	pub bits: u32,
+	/// The nonce of the proof of work.
+	pub nonce: u32,
+}
+
+impl BlockHeader {
+	/// The hash of the header, which identifies the block.
+	pub fn hash(&self) -> H256 {
+		sha256d(&self.encode())
+	}
+
+	/// Whether the hash of the header doesn't exceed its target.
+	pub fn has_valid_proof_of_work(&self) -> bool {
+		match bits_to_target(self.bits) {
+			Some(target) => U256::from_little_endian(self.hash().as_bytes()) <= target,
+			None => false,
+		}
+	}
+}
+
+/// The proof that a transaction is in a block.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct MerkleProof {
+	/// The hash of the block.
+	pub block_hash: H256,
+	/// The position of the transaction in the block.
+	pub index: u32,
+	/// The hashes of the siblings of the path from the transaction to the merkle root, from the
+	/// transaction.
+	pub hashes: Vec<H256>,
+}
+
+impl MerkleProof {
+	/// The merkle root proven for the transaction `tx_id`, `None` if the proof is deeper than
+	/// `MAX_MERKLE_DEPTH` or the index doesn't fit in it.
+	pub fn merkle_root(&self, tx_id: &H256) -> Option<H256> {
+		if self.hashes.len() > MAX_MERKLE_DEPTH || (self.index >> self.hashes.len()) != 0 {
+			return None;
+		}
+
+		let mut hash = *tx_id;
+		for (depth, sibling) in self.hashes.iter().enumerate() {
+			let mut pair = Vec::with_capacity(64);
+			if (self.index >> depth) & 1 == 1 {
+				pair.extend_from_slice(sibling.as_bytes());
+				pair.extend_from_slice(hash.as_bytes());
+			} else {
+				pair.extend_from_slice(hash.as_bytes());
+				pair.extend_from_slice(sibling.as_bytes());
+			}
+			hash = sha256d(&pair);
+		}
+		Some(hash)
+	}
+}
+
+/// The double SHA-256 hash of `data`.
+pub fn sha256d(data: &[u8]) -> H256 {
+	let first = Sha256::digest(data);
+	H256::from_slice(Sha256::digest(first.as_slice()).as_slice())
+}
+
+/// The target encoded by `bits`, `None` if it is negative or overflows.
+pub fn bits_to_target(bits: u32) -> Option<U256> {
+	let size = bits >> 24;
+	let mantissa = bits & 0x007f_ffff;
+	if bits & 0x0080_0000 != 0 && mantissa != 0 {
+		return None;
+	}
+
+	if size <= 3 {
+		Some(U256::from(mantissa >> (8 * (3 - size))))
+	} else if mantissa == 0 {
+		Some(U256::zero())
+	} else {
+		let shift = 8 * (size - 3);
+		let bits_needed = 32 - mantissa.leading_zeros();
+		if bits_needed + shift > 256 {
+			return None;
+		}
+		Some(U256::from(mantissa) << shift as usize)
+	}
+}
+
+/// The compact encoding of `target`.
+pub fn target_to_bits(target: U256) -> u32 {
+	let mut size = (target.bits() as u32 + 7) / 8;
+	let mut compact = if size <= 3 {
+		target.low_u32() << (8 * (3 - size))
+	} else {
+		(target >> (8 * (size - 3)) as usize).low_u32()
+	};
+	if compact & 0x0080_0000 != 0 {
+		compact >>= 8;
+		size += 1;
+	}
+	compact | size << 24
+}
+
+/// The expected number of hashes needed to find a block with `target`.
+pub fn work(target: U256) -> U256 {
+	if target == U256::max_value() {
+		return U256::one();
+	}
+	(!target / (target + U256::one())) + U256::one()
+}
+
+/// The target of the difficulty period following one of target `target` that lasted `timespan`
+/// seconds, not easier than `limit`.
+pub fn retarget(target: U256, timespan: u32, limit: U256) -> U256 {
+	let timespan = timespan.max(TARGET_TIMESPAN / 4).min(TARGET_TIMESPAN * 4);
+
+	// `target * timespan / TARGET_TIMESPAN`, without overflowing for the easiest targets.
+	let expected = U256::from(TARGET_TIMESPAN);
+	let (quotient, remainder) = (target / expected, target % expected);
+	let (scaled, mul_overflow) = quotient.overflowing_mul(U256::from(timespan));
+	let (target, add_overflow) = scaled.overflowing_add(remainder * U256::from(timespan) / expected);
+	if mul_overflow || add_overflow {
+		return limit;
+	}
+
+	target.min(limit)
+}
@@ -0,0 +1,254 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Bitcoin SPV Module
+//!
+//! The Bitcoin SPV module tracks the Bitcoin header chain and verifies that transactions are
+//! included in its blocks, as a simplified payment verification client.
+//!
+//! - [`bitcoin_spv::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! The module tracks the headers descending from a trusted header, given to `initialize`, which
+//! must start a difficulty period. Anyone can then import the children of the known headers,
+//! whose proof of work and difficulty are checked: the target is retargeted every
+//! `DIFFICULTY_ADJUSTMENT_INTERVAL` blocks from the duration of the previous period, and can't be
+//! easier than `PowLimit`. The time of a header must exceed the median time of the
+//! `MEDIAN_TIME_SPAN` previous blocks, and can't be more than `MAX_FUTURE_BLOCK_TIME` ahead of
+//! the current time.
+//!
+//! The main chain is the chain of known headers with the most work. Other modules verify that a
+//! transaction is in one of its blocks, with enough confirmations, through `VerifyTransaction`.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `initialize` - Start tracking the Bitcoin chain from a trusted header.
+//! - `import_header` - Import a Bitcoin header.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+mod header;
+#[cfg(test)]
+mod tests;
+
+pub use crate::header::{
+	BlockHeader, MerkleProof, DIFFICULTY_ADJUSTMENT_INTERVAL, TARGET_TIMESPAN, MEDIAN_TIME_SPAN,
+	MAX_FUTURE_BLOCK_TIME, MAX_MERKLE_DEPTH, sha256d, bits_to_target, target_to_bits, work, retarget,
+};
+
+use rstd::{prelude::*, convert::TryInto};
+use codec::{Encode, Decode};
+use primitives::{H256, U256};
+use srml_support::{StorageValue, StorageMap, decl_module, decl_storage, decl_event, ensure};
+use srml_support::dispatch::Result;
+use srml_support::traits::{Get, Time};
+use system::{ensure_root, ensure_signed};
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event> + Into<<Self as system::Trait>::Event>;
+
+	/// The compact encoding of the easiest target allowed, `0x1d00ffff` on the main network.
+	type PowLimit: Get<u32>;
+
+	/// The current time, in milliseconds since the Unix epoch.
+	type Time: Time;
+}
+
+/// A known Bitcoin header.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct StoredHeader {
+	/// The header.
+	pub header: BlockHeader,
+	/// The height of the block.
+	pub height: u32,
+	/// The total work of the chain up to the block, from the initial header.
+	pub chain_work: U256,
+	/// The time of the first block of the difficulty period of the block.
+	pub period_start_time: u32,
+}
+
+/// Verification of Bitcoin transactions.
+pub trait VerifyTransaction {
+	/// Check that the transaction `tx_id` is in a block of the main chain, as proven by `proof`,
+	/// with at least `confirmations` blocks from this block to the best block.
+	fn verify_transaction(tx_id: &H256, proof: &MerkleProof, confirmations: u32) -> Result;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as BitcoinSpv {
+		/// The known headers, in the main chain or not.
+		pub Headers get(header): map H256 => Option<StoredHeader>;
+		/// The hash of the block of each height of the main chain.
+		pub MainChain get(main_chain_hash): map u32 => Option<H256>;
+		/// The hash of the best block, `None` before the module is initialized.
+		pub BestBlock get(best_block): Option<H256>;
+	}
+}
+
+decl_event!(
+	pub enum Event {
+		/// A header was imported at the given height.
+		HeaderImported(H256, u32),
+		/// The main chain changed to the one ending with the given block.
+		BestBlockChanged(H256),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		/// Start tracking the Bitcoin chain from the trusted `header` at `height`, which must be
+		/// the first block of a difficulty period. Can only be called once.
+		fn initialize(origin, header: BlockHeader, height: u32) -> Result {
+			ensure_root(origin)?;
+			ensure!(BestBlock::get().is_none(), "Module is already initialized");
+			ensure!(
+				height % DIFFICULTY_ADJUSTMENT_INTERVAL == 0,
+				"Initial header must start a difficulty period"
+			);
+			let target = bits_to_target(header.bits).ok_or("Invalid difficulty")?;
+
+			let hash = header.hash();
+			let period_start_time = header.time;
+			Headers::insert(hash, StoredHeader { header, height, chain_work: work(target), period_start_time });
+			MainChain::insert(height, hash);
+			BestBlock::put(hash);
+
+			Ok(())
+		}
+
+		/// Import `header`, whose parent must be known.
+		fn import_header(origin, header: BlockHeader) -> Result {
+			ensure_signed(origin)?;
+			let best = Self::best_block().and_then(|hash| Headers::get(hash)).ok_or("Module is not initialized")?;
+
+			let hash = header.hash();
+			ensure!(!Headers::exists(hash), "Header is already imported");
+			let parent = Headers::get(header.prev_block).ok_or("Parent header is not imported")?;
+			let height = parent.height + 1;
+
+			ensure!(header.bits == Self::next_bits(&parent)?, "Unexpected difficulty");
+			ensure!(header.has_valid_proof_of_work(), "Insufficient proof of work");
+			ensure!(header.time > Self::median_time_past(&parent), "Block time is too early");
+			let now = TryInto::<u64>::try_into(T::Time::now()).unwrap_or(u64::max_value()) / 1000;
+			ensure!(
+				u64::from(header.time) <= now.saturating_add(MAX_FUTURE_BLOCK_TIME.into()),
+				"Block time is too far in the future"
+			);
+
+			let target = bits_to_target(header.bits).ok_or("Invalid difficulty")?;
+			let period_start_time = if height % DIFFICULTY_ADJUSTMENT_INTERVAL == 0 {
+				header.time
+			} else {
+				parent.period_start_time
+			};
+			let stored = StoredHeader {
+				header,
+				height,
+				chain_work: parent.chain_work.saturating_add(work(target)),
+				period_start_time,
+			};
+			let is_best = stored.chain_work > best.chain_work;
+			Headers::insert(hash, stored);
+			Self::deposit_event(Event::HeaderImported(hash, height));
+
+			if is_best {
+				Self::set_best_block(hash, height, best.height);
+			}
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// The bits of the child of `parent`.
+	fn next_bits(parent: &StoredHeader) -> rstd::result::Result<u32, &'static str> {
+		if (parent.height + 1) % DIFFICULTY_ADJUSTMENT_INTERVAL != 0 {
+			return Ok(parent.header.bits);
+		}
+
+		let limit = bits_to_target(T::PowLimit::get()).ok_or("Invalid proof of work limit")?;
+		let target = bits_to_target(parent.header.bits).ok_or("Invalid difficulty")?;
+		let timespan = parent.header.time.saturating_sub(parent.period_start_time);
+		Ok(target_to_bits(retarget(target, timespan, limit)))
+	}
+
+	/// The median time of `parent` and its `MEDIAN_TIME_SPAN - 1` previous blocks, or of as many
+	/// of them as are known.
+	fn median_time_past(parent: &StoredHeader) -> u32 {
+		let mut times = Vec::with_capacity(MEDIAN_TIME_SPAN);
+		times.push(parent.header.time);
+		let mut ancestor = parent.header.prev_block;
+		while times.len() < MEDIAN_TIME_SPAN {
+			match Headers::get(ancestor) {
+				Some(stored) => {
+					times.push(stored.header.time);
+					ancestor = stored.header.prev_block;
+				},
+				None => break,
+			}
+		}
+
+		times.sort_unstable();
+		times[times.len() / 2]
+	}
+
+	/// Make the chain ending with the block `hash` at `height` the main chain, replacing the one
+	/// ending at `previous_height`.
+	fn set_best_block(hash: H256, height: u32, previous_height: u32) {
+		for stale in height + 1..=previous_height {
+			MainChain::remove(stale);
+		}
+
+		// Replace the blocks of the previous main chain down to the common ancestor.
+		let (mut ancestor, mut ancestor_height) = (hash, height);
+		while MainChain::get(ancestor_height) != Some(ancestor) {
+			MainChain::insert(ancestor_height, ancestor);
+			match Headers::get(ancestor) {
+				Some(stored) if ancestor_height > 0 => {
+					ancestor = stored.header.prev_block;
+					ancestor_height -= 1;
+				},
+				_ => break,
+			}
+		}
+
+		BestBlock::put(hash);
+		Self::deposit_event(Event::BestBlockChanged(hash));
+	}
+}
+
+impl<T: Trait> VerifyTransaction for Module<T> {
+	fn verify_transaction(tx_id: &H256, proof: &MerkleProof, confirmations: u32) -> Result {
+		let stored = Headers::get(proof.block_hash).ok_or("Block is not imported")?;
+		ensure!(MainChain::get(stored.height) == Some(proof.block_hash), "Block is not in the main chain");
+		let best = Self::best_block().and_then(|hash| Headers::get(hash)).ok_or("Module is not initialized")?;
+		ensure!(best.height - stored.height + 1 >= confirmations, "Not enough confirmations");
+
+		let merkle_root = proof.merkle_root(tx_id).ok_or("Invalid merkle proof")?;
+		ensure!(merkle_root == stored.header.merkle_root, "Transaction is not in the block");
+
+		Ok(())
+	}
+}
@@ -0,0 +1,249 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use super::*;
+
+use std::cell::RefCell;
+use primitives::Blake2Hasher;
+use runtime_io::with_externalities;
+use srml_support::{assert_noop, assert_ok, impl_test_runtime, parameter_types};
+
+impl_test_runtime! {
+	pub struct Test;
+}
+parameter_types! {
+	pub const PowLimit: u32 = 0x207f_ffff;
+}
+thread_local! {
+	static NOW: RefCell<u64> = RefCell::new(1_000_000_000);
+}
+pub struct Now;
+impl Time for Now {
+	type Moment = u64;
+	fn now() -> u64 {
+		NOW.with(|now| *now.borrow())
+	}
+}
+impl Trait for Test {
+	type Event = ();
+	type PowLimit = PowLimit;
+	type Time = Now;
+}
+type Spv = Module<Test>;
+
+fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+	system::GenesisConfig::default().build_storage::<Test>().unwrap().0.into()
+}
+
+/// A child of `parent` with valid proof of work for `bits`.
+fn mine(parent: &BlockHeader, merkle_root: H256, time: u32, bits: u32) -> BlockHeader {
+	let mut header = BlockHeader {
+		version: 1,
+		prev_block: parent.hash(),
+		merkle_root,
+		time,
+		bits,
+		nonce: 0,
+	};
+	while !header.has_valid_proof_of_work() {
+		header.nonce += 1;
+	}
+	header
+}
+
+/// The initial header and `len` descendants, ten minutes apart, imported in the main chain.
+fn initialize(len: usize) -> Vec<BlockHeader> {
+	let genesis = mine(&BlockHeader::default(), H256::zero(), 0, PowLimit::get());
+	assert_ok!(Spv::initialize(Origin::ROOT, genesis.clone(), 0));
+
+	let mut headers = vec![genesis];
+	for _ in 0..len {
+		let parent = headers.last().unwrap();
+		let header = mine(parent, H256::zero(), parent.time + 600, parent.bits);
+		assert_ok!(Spv::import_header(Origin::signed(1), header.clone()));
+		headers.push(header);
+	}
+	headers
+}
+
+fn hash_pair(left: &H256, right: &H256) -> H256 {
+	sha256d(&[left.as_bytes(), right.as_bytes()].concat())
+}
+
+#[test]
+fn compact_targets_are_decoded() {
+	assert_eq!(bits_to_target(0x1d00_ffff), Some(U256::from(0xffff) << 208));
+	assert_eq!(bits_to_target(0x0300_1234), Some(U256::from(0x1234)));
+	assert_eq!(bits_to_target(0x0412_3456), Some(U256::from(0x1234_5600)));
+	assert_eq!(bits_to_target(0x0492_3456), None);
+	assert_eq!(bits_to_target(0xff12_3456), None);
+
+	assert_eq!(target_to_bits(U256::from(0xffff) << 208), 0x1d00_ffff);
+	assert_eq!(target_to_bits(U256::from(0x80)), 0x0200_8000);
+	assert_eq!(target_to_bits(U256::from(0x1234_5600)), 0x0412_3456);
+}
+
+#[test]
+fn headers_are_imported_on_top_of_known_parents() {
+	with_externalities(&mut new_test_ext(), || {
+		let headers = initialize(1);
+		let header = mine(&headers[1], H256::zero(), 1200, PowLimit::get());
+
+		assert_noop!(
+			Spv::import_header(Origin::signed(1), mine(&header, H256::zero(), 1800, PowLimit::get())),
+			"Parent header is not imported"
+		);
+		assert_noop!(
+			Spv::import_header(Origin::signed(1), mine(&headers[1], H256::zero(), 1200, 0x2000_ffff)),
+			"Unexpected difficulty"
+		);
+
+		let mut invalid = header.clone();
+		while invalid.has_valid_proof_of_work() {
+			invalid.nonce += 1;
+		}
+		assert_noop!(Spv::import_header(Origin::signed(1), invalid), "Insufficient proof of work");
+
+		assert_ok!(Spv::import_header(Origin::signed(1), header.clone()));
+		assert_noop!(Spv::import_header(Origin::signed(1), header.clone()), "Header is already imported");
+		assert_eq!(Spv::best_block(), Some(header.hash()));
+		assert_eq!(Spv::main_chain_hash(2), Some(header.hash()));
+	});
+}
+
+#[test]
+fn the_chain_with_the_most_work_is_the_main_chain() {
+	with_externalities(&mut new_test_ext(), || {
+		let headers = initialize(2);
+
+		let fork = mine(&headers[1], H256::repeat_byte(1), 1300, PowLimit::get());
+		assert_ok!(Spv::import_header(Origin::signed(1), fork.clone()));
+		assert_eq!(Spv::best_block(), Some(headers[2].hash()));
+
+		let fork_child = mine(&fork, H256::zero(), 1900, PowLimit::get());
+		assert_ok!(Spv::import_header(Origin::signed(1), fork_child.clone()));
+		assert_eq!(Spv::best_block(), Some(fork_child.hash()));
+		assert_eq!(Spv::main_chain_hash(1), Some(headers[1].hash()));
+		assert_eq!(Spv::main_chain_hash(2), Some(fork.hash()));
+		assert_eq!(Spv::main_chain_hash(3), Some(fork_child.hash()));
+	});
+}
+
+#[test]
+fn difficulty_is_retargeted_every_period() {
+	with_externalities(&mut new_test_ext(), || {
+		let genesis = mine(&BlockHeader::default(), H256::zero(), 0, PowLimit::get());
+		assert_ok!(Spv::initialize(Origin::ROOT, genesis.clone(), 0));
+
+		// The period lasts less than a quarter of the expected time.
+		let mut parent = genesis;
+		for _ in 1..DIFFICULTY_ADJUSTMENT_INTERVAL {
+			let header = mine(&parent, H256::zero(), parent.time + 150, parent.bits);
+			assert_ok!(Spv::import_header(Origin::signed(1), header.clone()));
+			parent = header;
+		}
+
+		assert_noop!(
+			Spv::import_header(Origin::signed(1), mine(&parent, H256::zero(), parent.time + 150, parent.bits)),
+			"Unexpected difficulty"
+		);
+		let header = mine(&parent, H256::zero(), parent.time + 150, 0x201f_ffff);
+		assert_ok!(Spv::import_header(Origin::signed(1), header.clone()));
+		assert_eq!(Spv::main_chain_hash(DIFFICULTY_ADJUSTMENT_INTERVAL), Some(header.hash()));
+	});
+}
+
+#[test]
+fn transactions_are_verified_with_merkle_proofs() {
+	with_externalities(&mut new_test_ext(), || {
+		let headers = initialize(1);
+		let tx_ids = (0..4).map(H256::repeat_byte).collect::<Vec<_>>();
+		let (left, right) = (hash_pair(&tx_ids[0], &tx_ids[1]), hash_pair(&tx_ids[2], &tx_ids[3]));
+		let block = mine(&headers[1], hash_pair(&left, &right), 1200, PowLimit::get());
+		let proof = MerkleProof { block_hash: block.hash(), index: 2, hashes: vec![tx_ids[3], left] };
+
+		assert_noop!(Spv::verify_transaction(&tx_ids[2], &proof, 1), "Block is not imported");
+		assert_ok!(Spv::import_header(Origin::signed(1), block.clone()));
+
+		assert_ok!(Spv::verify_transaction(&tx_ids[2], &proof, 1));
+		assert_noop!(Spv::verify_transaction(&tx_ids[3], &proof, 1), "Transaction is not in the block");
+		assert_noop!(
+			Spv::verify_transaction(&tx_ids[2], &MerkleProof { index: 4, ..proof.clone() }, 1),
+			"Invalid merkle proof"
+		);
+		assert_noop!(Spv::verify_transaction(&tx_ids[2], &proof, 2), "Not enough confirmations");
+
+		assert_ok!(Spv::import_header(Origin::signed(1), mine(&block, H256::zero(), 1800, PowLimit::get())));
+		assert_ok!(Spv::verify_transaction(&tx_ids[2], &proof, 2));
+	});
+}
+
+#[test]
+fn transactions_must_be_in_the_main_chain() {
+	with_externalities(&mut new_test_ext(), || {
+		let headers = initialize(2);
+		let tx_id = H256::repeat_byte(1);
+		let block = mine(&headers[1], tx_id, 1300, PowLimit::get());
+		assert_ok!(Spv::import_header(Origin::signed(1), block.clone()));
+
+		let proof = MerkleProof { block_hash: block.hash(), index: 0, hashes: vec![] };
+		assert_noop!(Spv::verify_transaction(&tx_id, &proof, 1), "Block is not in the main chain");
+	});
+}
+
+#[test]
+fn block_times_must_exceed_the_median_time_past() {
+	with_externalities(&mut new_test_ext(), || {
+		// The median of the times of the last 11 blocks, from 600 to 6600, is 3600.
+		let headers = initialize(11);
+		let parent = headers.last().unwrap();
+
+		assert_noop!(
+			Spv::import_header(Origin::signed(1), mine(parent, H256::zero(), 3600, parent.bits)),
+			"Block time is too early"
+		);
+		// Earlier than its parent, but still after the median.
+		assert_ok!(Spv::import_header(Origin::signed(1), mine(parent, H256::zero(), 3601, parent.bits)));
+	});
+}
+
+#[test]
+fn block_times_must_not_be_too_far_in_the_future() {
+	with_externalities(&mut new_test_ext(), || {
+		let headers = initialize(1);
+		NOW.with(|now| *now.borrow_mut() = 10_000_000);
+		let latest = 10_000 + MAX_FUTURE_BLOCK_TIME;
+
+		assert_noop!(
+			Spv::import_header(Origin::signed(1), mine(&headers[1], H256::zero(), latest + 1, PowLimit::get())),
+			"Block time is too far in the future"
+		);
+		assert_ok!(Spv::import_header(Origin::signed(1), mine(&headers[1], H256::zero(), latest, PowLimit::get())));
+	});
+}
+
+#[test]
+fn merkle_proofs_are_not_deeper_than_the_limit() {
+	let tx_id = H256::repeat_byte(1);
+	let proof = |depth| MerkleProof { block_hash: H256::zero(), index: 0, hashes: vec![tx_id; depth] };
+	let root = (0..MAX_MERKLE_DEPTH).fold(tx_id, |hash, _| hash_pair(&hash, &tx_id));
+
+	assert_eq!(proof(MAX_MERKLE_DEPTH).merkle_root(&tx_id), Some(root));
+	assert_eq!(proof(MAX_MERKLE_DEPTH + 1).merkle_root(&tx_id), None);
+	assert_eq!(MerkleProof { index: 1 << 31, ..proof(MAX_MERKLE_DEPTH) }.merkle_root(&tx_id), None);
+}
@@ -0,0 +1,355 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Bridge Messages Module
+//!
+//! The Bridge Messages module passes messages between two chains running this module, which
+//! track the finality of each other with the bridge module.
+//!
+//! - [`bridge_messages::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! Messages are sent in lanes: each lane is a queue of messages numbered by consecutive nonces,
+//! delivered in order. The sender of a message pays a fee for it, made of `MessageBaseFee` and
+//! `MessageByteFee` for each byte of its payload, which is handed to `OnMessageFee`.
+//!
+//! Relayers deliver the messages with a proof of the storage of the sending chain at one of its
+//! finalized headers, known from `BridgedHeaders`. The payload of each message is decoded as a
+//! `Call` and dispatched with the origin given by `ConvertOrigin` for the lane and the sender. A
+//! message whose payload can't be decoded, or whose call fails, is still delivered.
+//!
+//! The relayer declares the weight of the calls it delivers, up to `MaxDispatchWeight`, and pays
+//! for it in the weight of the delivery. A delivery whose calls need more weight than declared is
+//! rejected.
+//!
+//! Relayers then prove the delivery to the sending chain with a proof of the storage of the
+//! receiving chain, which lets it remove the delivered messages from the queue, at most
+//! `MAX_MESSAGES_PER_DELIVERY` at once.
+//!
+//! Both chains must use the same account type and declare the module under the same name.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `send_message` - Send a message in a lane.
+//! - `receive_messages` - Deliver messages sent by the bridged chain.
+//! - `confirm_delivery` - Remove the messages delivered to the bridged chain.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+mod proof;
+#[cfg(test)]
+mod tests;
+
+pub use crate::proof::StorageProofChecker;
+
+use rstd::{prelude::*, marker::PhantomData};
+use codec::{Encode, Decode};
+use srml_support::{
+	StorageMap, Parameter, Dispatchable, Blake2_256, decl_module, decl_storage, decl_event, ensure,
+};
+use srml_support::dispatch::Result;
+use srml_support::storage::keys;
+use srml_support::traits::{Currency, ExistenceRequirement, Get, OnUnbalanced, WithdrawReason};
+use sr_primitives::traits::{Convert, Header as HeaderT, Saturating};
+use sr_primitives::weights::{
+	Weight, WeighData, ClassifyDispatch, DispatchClass, GetDispatchInfo, SimpleDispatchInfo,
+};
+use system::ensure_signed;
+use bridge::FinalizedHeaders;
+
+/// The name of the module in `decl_storage!`, which must be the same on both chains.
+const MODULE_NAME: &str = "BridgeMessages";
+
+/// The maximum number of messages delivered, or confirmed as delivered, at once.
+pub const MAX_MESSAGES_PER_DELIVERY: MessageNonce = 128;
+
+/// The weight of `receive_messages` besides its messages.
+const RECEIVE_BASE_WEIGHT: Weight = 50_000;
+/// The weight of reading and decoding a delivered message, besides its call.
+const MESSAGE_WEIGHT: Weight = 20_000;
+/// The weight of `confirm_delivery`, removing up to `MAX_MESSAGES_PER_DELIVERY` messages.
+const CONFIRM_WEIGHT: Weight = 50_000 + 10_000 * MAX_MESSAGES_PER_DELIVERY as Weight;
+
+/// The identifier of a lane.
+pub type LaneId = [u8; 4];
+
+/// The number of a message in its lane, starting at 1.
+pub type MessageNonce = u64;
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
+type BridgedHash<T> = <<T as Trait>::BridgedHeader as HeaderT>::Hash;
+
+/// A message waiting for its delivery.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Message<AccountId> {
+	/// The sender of the message.
+	pub sender: AccountId,
+	/// The encoded call dispatched by the receiving chain.
+	pub payload: Vec<u8>,
+}
+
+/// The state of an outbound lane.
+#[derive(Clone, Default, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct OutboundLaneState {
+	/// The nonce of the last sent message.
+	pub latest_sent: MessageNonce,
+	/// The nonce of the last message known to be delivered.
+	pub latest_delivered: MessageNonce,
+}
+
+/// A proof of the messages `begin..=end` of `lane`, in the state of the bridged header
+/// `header_hash`.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct MessagesProof<Hash> {
+	/// The hash of a finalized header of the bridged chain.
+	pub header_hash: Hash,
+	/// The lane of the messages.
+	pub lane: LaneId,
+	/// The nonce of the first message.
+	pub begin: MessageNonce,
+	/// The nonce of the last message.
+	pub end: MessageNonce,
+	/// The trie nodes proving the messages.
+	pub storage_proof: Vec<Vec<u8>>,
+}
+
+/// A proof of the last message of `lane` delivered to the bridged chain, in the state of the
+/// bridged header `header_hash`.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct DeliveryProof<Hash> {
+	/// The hash of a finalized header of the bridged chain.
+	pub header_hash: Hash,
+	/// The lane of the messages.
+	pub lane: LaneId,
+	/// The trie nodes proving the nonce of the last delivered message.
+	pub storage_proof: Vec<Vec<u8>>,
+}
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The currency the fees of the messages are paid in.
+	type Currency: Currency<Self::AccountId>;
+
+	/// The fee of every message.
+	type MessageBaseFee: Get<BalanceOf<Self>>;
+
+	/// The fee of each byte of the payload of a message.
+	type MessageByteFee: Get<BalanceOf<Self>>;
+
+	/// Handler for the fees of the messages.
+	type OnMessageFee: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+	/// The header of the bridged chain.
+	type BridgedHeader: HeaderT;
+
+	/// The finalized headers of the bridged chain, usually the bridge module.
+	type BridgedHeaders: FinalizedHeaders<Self::BridgedHeader>;
+
+	/// The calls carried by the messages.
+	type Call: Parameter + Dispatchable<Origin=<Self as system::Trait>::Origin> + GetDispatchInfo;
+
+	/// The maximum weight of the calls of a delivery.
+	type MaxDispatchWeight: Get<Weight>;
+
+	/// The origin of the calls of the messages, from their lane and sender.
+	type ConvertOrigin: Convert<(LaneId, Self::AccountId), <Self as system::Trait>::Origin>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as BridgeMessages {
+		/// The state of each outbound lane.
+		pub OutboundLanes get(outbound_lane): map LaneId => OutboundLaneState;
+		/// The messages sent and not known to be delivered yet.
+		pub OutboundMessages get(outbound_message): map (LaneId, MessageNonce) => Option<Message<T::AccountId>>;
+		/// The nonce of the last message delivered in each inbound lane.
+		pub InboundLanes get(inbound_lane): map LaneId => MessageNonce;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		Balance = BalanceOf<T>,
+	{
+		/// A message was sent by the account in the lane, with the given nonce and fee.
+		MessageSent(LaneId, MessageNonce, AccountId, Balance),
+		/// A message of the bridged chain was dispatched, successfully or not.
+		MessageDispatched(LaneId, MessageNonce, bool),
+		/// The messages of the lane were delivered up to the given nonce.
+		MessagesDelivered(LaneId, MessageNonce),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		/// The maximum weight of the calls of a delivery.
+		const MaxDispatchWeight: Weight = T::MaxDispatchWeight::get();
+
+		fn deposit_event() = default;
+
+		/// Send a message carrying the encoded call `payload` in `lane`.
+		///
+		/// The sender pays the fee of the message.
+		fn send_message(origin, lane: LaneId, payload: Vec<u8>) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			let fee = Self::message_fee(payload.len());
+			let imbalance = T::Currency::withdraw(
+				&sender,
+				fee,
+				WithdrawReason::Fee,
+				ExistenceRequirement::KeepAlive,
+			)?;
+			T::OnMessageFee::on_unbalanced(imbalance);
+
+			let nonce = OutboundLanes::mutate(lane, |state| {
+				state.latest_sent += 1;
+				state.latest_sent
+			});
+			<OutboundMessages<T>>::insert((lane, nonce), Message { sender: sender.clone(), payload });
+			Self::deposit_event(RawEvent::MessageSent(lane, nonce, sender, fee));
+
+			Ok(())
+		}
+
+		/// Deliver the messages of the bridged chain proven by `proof`, which must follow the last
+		/// delivered message of their lane.
+		///
+		/// `dispatch_weight` is the total weight of the calls of the messages, charged with the
+		/// delivery. The delivery fails if the calls need more.
+		#[weight = DeliveryWeight::<T>::default()]
+		fn receive_messages(
+			origin,
+			proof: MessagesProof<BridgedHash<T>>,
+			dispatch_weight: Weight
+		) -> Result {
+			ensure_signed(origin)?;
+			let lane = proof.lane;
+			ensure!(proof.begin == Self::inbound_lane(lane) + 1, "Unexpected message nonce");
+			ensure!(proof.end >= proof.begin, "No messages to deliver");
+			ensure!(proof.end - proof.begin < MAX_MESSAGES_PER_DELIVERY, "Too many messages to deliver");
+			ensure!(dispatch_weight <= T::MaxDispatchWeight::get(), "Dispatch weight is too high");
+
+			let checker = Self::proof_checker(&proof.header_hash, proof.storage_proof)?;
+			let mut messages = Vec::new();
+			for nonce in proof.begin..=proof.end {
+				let key = keys::map_key::<Blake2_256, _>(MODULE_NAME, "OutboundMessages", None, &(lane, nonce));
+				let message = checker.read_value(&key)?
+					.and_then(|value| Message::<T::AccountId>::decode(&mut &value[..]).ok())
+					.ok_or("Message is not in the proof")?;
+				let call = T::Call::decode(&mut &message.payload[..]).ok();
+				messages.push((nonce, message.sender, call));
+			}
+
+			let needed = messages.iter()
+				.filter_map(|(_, _, call)| call.as_ref())
+				.fold(0, |weight: Weight, call| weight.saturating_add(call.get_dispatch_info().weight));
+			ensure!(needed <= dispatch_weight, "Messages need more weight than declared");
+
+			InboundLanes::insert(lane, proof.end);
+			for (nonce, sender, call) in messages {
+				let origin = T::ConvertOrigin::convert((lane, sender));
+				let dispatched = call.map(|call| call.dispatch(origin).is_ok()).unwrap_or(false);
+				Self::deposit_event(RawEvent::MessageDispatched(lane, nonce, dispatched));
+			}
+
+			Ok(())
+		}
+
+		/// Remove the messages delivered to the bridged chain, as proven by `proof`.
+		///
+		/// At most `MAX_MESSAGES_PER_DELIVERY` messages are removed. The following ones are
+		/// removed by confirming the delivery again.
+		#[weight = SimpleDispatchInfo::FixedNormal(CONFIRM_WEIGHT)]
+		fn confirm_delivery(origin, proof: DeliveryProof<BridgedHash<T>>) -> Result {
+			ensure_signed(origin)?;
+			let lane = proof.lane;
+
+			let checker = Self::proof_checker(&proof.header_hash, proof.storage_proof)?;
+			let key = keys::map_key::<Blake2_256, _>(MODULE_NAME, "InboundLanes", None, &lane);
+			let delivered = match checker.read_value(&key)? {
+				Some(value) => MessageNonce::decode(&mut &value[..]).map_err(|_| "Invalid delivered nonce")?,
+				None => 0,
+			};
+
+			let mut state = Self::outbound_lane(lane);
+			ensure!(delivered > state.latest_delivered, "Messages are already known to be delivered");
+			ensure!(delivered <= state.latest_sent, "Delivered messages were never sent");
+
+			let delivered = delivered.min(state.latest_delivered + MAX_MESSAGES_PER_DELIVERY);
+			for nonce in state.latest_delivered + 1..=delivered {
+				<OutboundMessages<T>>::remove((lane, nonce));
+			}
+			state.latest_delivered = delivered;
+			OutboundLanes::insert(lane, state);
+			Self::deposit_event(RawEvent::MessagesDelivered(lane, delivered));
+
+			Ok(())
+		}
+	}
+}
+
+/// Weight of `receive_messages`: a step per message, and the weight declared for their calls.
+pub struct DeliveryWeight<T>(PhantomData<T>);
+
+impl<T> Default for DeliveryWeight<T> {
+	fn default() -> Self {
+		DeliveryWeight(PhantomData)
+	}
+}
+
+impl<T: Trait> WeighData<(&MessagesProof<BridgedHash<T>>, &Weight)> for DeliveryWeight<T> {
+	fn weigh_data(&self, (proof, dispatch_weight): (&MessagesProof<BridgedHash<T>>, &Weight)) -> Weight {
+		let messages = proof.end.saturating_sub(proof.begin).saturating_add(1).min(MAX_MESSAGES_PER_DELIVERY);
+		RECEIVE_BASE_WEIGHT
+			.saturating_add(MESSAGE_WEIGHT.saturating_mul(messages as Weight))
+			.saturating_add(*dispatch_weight)
+	}
+}
+
+impl<T, A> ClassifyDispatch<A> for DeliveryWeight<T> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// The fee of a message whose payload is `len` bytes long.
+	pub fn message_fee(len: usize) -> BalanceOf<T> {
+		let len = BalanceOf::<T>::from(len as u32);
+		T::MessageBaseFee::get().saturating_add(T::MessageByteFee::get().saturating_mul(len))
+	}
+
+	fn proof_checker(header_hash: &BridgedHash<T>, proof: Vec<Vec<u8>>)
+		-> rstd::result::Result<StorageProofChecker<T::BridgedHeader>, &'static str>
+	{
+		let header = T::BridgedHeaders::finalized_header(header_hash)
+			.ok_or("Header is not finalized")?;
+		Ok(StorageProofChecker::new(&header, proof))
+	}
+}
@@ -0,0 +1,56 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Reading the storage of the bridged chain from storage proofs.
+
+use rstd::prelude::*;
+use substrate_trie::{MemoryDB, Trie, HashDBT, EMPTY_PREFIX};
+use substrate_trie::trie_types::TrieDB;
+use sr_primitives::traits::{Hash as HashT, Header as HeaderT};
+
+type HasherOf<Header> = <<Header as HeaderT>::Hashing as HashT>::Hasher;
+
+/// Values of the state of a bridged header, proven by the trie nodes leading to them.
+pub struct StorageProofChecker<Header: HeaderT> {
+	root: Header::Hash,
+	db: MemoryDB<HasherOf<Header>>,
+}
+
+impl<Header: HeaderT> StorageProofChecker<Header> {
+	/// Check values of the state of `header` with the trie nodes `proof`.
+	pub fn new(header: &Header, proof: Vec<Vec<u8>>) -> Self {
+		let mut db = MemoryDB::default();
+		for node in proof {
+			HashDBT::insert(&mut db, EMPTY_PREFIX, &node[..]);
+		}
+
+		StorageProofChecker {
+			root: *header.state_root(),
+			db,
+		}
+	}
+
+	/// The value at `key`, `None` if the proof shows that there is none.
+	///
+	/// Fails if the proof doesn't contain the nodes leading to the key.
+	pub fn read_value(&self, key: &[u8]) -> Result<Option<Vec<u8>>, &'static str> {
+		let trie = TrieDB::<HasherOf<Header>>::new(&self.db, &self.root)
+			.map_err(|_| "Storage proof doesn't contain the state root")?;
+		trie.get(key)
+			.map(|value| value.map(|value| value.to_vec()))
+			.map_err(|_| "Storage proof doesn't contain the value")
+	}
+}
@@ -0,0 +1,309 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use super::*;
+
+use std::cell::RefCell;
+use primitives::{Blake2Hasher, H256};
+use runtime_io::with_externalities;
+use sr_primitives::testing::{Digest, Header};
+use srml_support::{assert_noop, assert_ok, impl_test_runtime, parameter_types};
+use srml_support::storage::unhashed;
+use substrate_trie::{MemoryDB, TrieMut};
+use substrate_trie::trie_types::TrieDBMut;
+
+impl_test_runtime! {
+	pub struct Test;
+	balances {
+		ExistentialDeposit = 0;
+		TransferFee = 0;
+		CreationFee = 0;
+	}
+}
+parameter_types! {
+	pub const MessageBaseFee: u64 = 10;
+	pub const MessageByteFee: u64 = 1;
+	pub const MaxDispatchWeight: u32 = 2_000_000;
+}
+
+thread_local! {
+	static FINALIZED_HEADERS: RefCell<Vec<Header>> = RefCell::new(Vec::new());
+}
+
+/// The finalized headers of the bridged chain, added by `prove`.
+pub struct TestHeaders;
+impl FinalizedHeaders<Header> for TestHeaders {
+	fn best_finalized_header() -> Option<Header> {
+		FINALIZED_HEADERS.with(|headers| headers.borrow().last().cloned())
+	}
+
+	fn finalized_header(hash: &H256) -> Option<Header> {
+		FINALIZED_HEADERS.with(|headers| headers.borrow().iter().find(|h| h.hash() == *hash).cloned())
+	}
+}
+
+/// Dispatches the messages as signed by their sender.
+pub struct SenderOrigin;
+impl Convert<(LaneId, u64), Origin> for SenderOrigin {
+	fn convert((_, sender): (LaneId, u64)) -> Origin {
+		Origin::signed(sender)
+	}
+}
+
+impl Trait for Test {
+	type Event = ();
+	type Currency = balances::Module<Test>;
+	type MessageBaseFee = MessageBaseFee;
+	type MessageByteFee = MessageByteFee;
+	type OnMessageFee = ();
+	type BridgedHeader = Header;
+	type BridgedHeaders = TestHeaders;
+	type Call = balances::Call<Test>;
+	type ConvertOrigin = SenderOrigin;
+	type MaxDispatchWeight = MaxDispatchWeight;
+}
+type Balances = balances::Module<Test>;
+type Messages = Module<Test>;
+
+const LANE: LaneId = *b"lane";
+
+fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap().0;
+	t.extend(balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100)],
+		vesting: vec![],
+	}.build_storage().unwrap().0);
+	t.into()
+}
+
+/// The weight of the transfers of `transfer`.
+const TRANSFER_WEIGHT: u32 = 1_000_000;
+
+fn transfer(dest: u64, value: u64) -> Vec<u8> {
+	balances::Call::<Test>::transfer(dest, value).encode()
+}
+
+/// The storage of the current chain at `keys`, with the finalized bridged header `number` whose
+/// state they are in and the proof of their values.
+fn prove(number: u64, keys: &[Vec<u8>]) -> (H256, Vec<Vec<u8>>) {
+	let pairs = keys.iter()
+		.filter_map(|key| unhashed::get_raw(key).map(|value| (key.clone(), value)))
+		.collect::<Vec<_>>();
+
+	let mut db = MemoryDB::<Blake2Hasher>::default();
+	let mut root = H256::default();
+	{
+		let mut trie = TrieDBMut::<Blake2Hasher>::new(&mut db, &mut root);
+		for (key, value) in pairs {
+			trie.insert(&key, &value).unwrap();
+		}
+	}
+
+	let header = Header::new(number, H256::zero(), root, H256::zero(), Digest::default());
+	let hash = header.hash();
+	FINALIZED_HEADERS.with(|headers| headers.borrow_mut().push(header));
+	(hash, db.drain().into_iter().map(|(_, (node, _))| node).collect())
+}
+
+fn message_key(nonce: MessageNonce) -> Vec<u8> {
+	keys::map_key::<Blake2_256, _>(MODULE_NAME, "OutboundMessages", None, &(LANE, nonce))
+}
+
+fn prove_messages(number: u64, begin: MessageNonce, end: MessageNonce) -> MessagesProof<H256> {
+	let keys = (begin..=end).map(message_key).collect::<Vec<_>>();
+	let (header_hash, storage_proof) = prove(number, &keys);
+	MessagesProof { header_hash, lane: LANE, begin, end, storage_proof }
+}
+
+fn prove_delivery(number: u64) -> DeliveryProof<H256> {
+	let key = keys::map_key::<Blake2_256, _>(MODULE_NAME, "InboundLanes", None, &LANE);
+	let (header_hash, storage_proof) = prove(number, &[key]);
+	DeliveryProof { header_hash, lane: LANE, storage_proof }
+}
+
+#[test]
+fn sent_messages_are_queued_and_pay_fees() {
+	with_externalities(&mut new_test_ext(), || {
+		let payload = transfer(2, 30);
+		let fee = 10 + payload.len() as u64;
+		assert_eq!(Messages::message_fee(payload.len()), fee);
+
+		assert_ok!(Messages::send_message(Origin::signed(1), LANE, payload.clone()));
+		assert_ok!(Messages::send_message(Origin::signed(1), LANE, payload.clone()));
+
+		assert_eq!(Balances::free_balance(&1), 100 - 2 * fee);
+		assert_eq!(Messages::outbound_lane(LANE), OutboundLaneState { latest_sent: 2, latest_delivered: 0 });
+		assert_eq!(Messages::outbound_message((LANE, 2)), Some(Message { sender: 1, payload }));
+		assert_noop!(
+			Messages::send_message(Origin::signed(1), LANE, vec![0; 100]),
+			"too few free funds in account"
+		);
+	});
+}
+
+#[test]
+fn received_messages_are_dispatched_in_order() {
+	let mut sending = new_test_ext();
+	let mut receiving = new_test_ext();
+
+	let (first, second) = with_externalities(&mut sending, || {
+		assert_ok!(Messages::send_message(Origin::signed(1), LANE, transfer(2, 30)));
+		assert_ok!(Messages::send_message(Origin::signed(1), LANE, transfer(2, 1_000)));
+		assert_ok!(Messages::send_message(Origin::signed(1), LANE, vec![0xff]));
+		(prove_messages(1, 1, 1), prove_messages(2, 2, 3))
+	});
+
+	with_externalities(&mut receiving, || {
+		assert_noop!(
+			Messages::receive_messages(Origin::signed(3), second.clone(), 2 * TRANSFER_WEIGHT),
+			"Unexpected message nonce"
+		);
+		assert_ok!(Messages::receive_messages(Origin::signed(3), first.clone(), 2 * TRANSFER_WEIGHT));
+		assert_eq!(Balances::free_balance(&2), 130);
+		assert_noop!(
+			Messages::receive_messages(Origin::signed(3), first, 2 * TRANSFER_WEIGHT),
+			"Unexpected message nonce"
+		);
+
+		// Messages whose call fails or can't be decoded are still delivered.
+		assert_ok!(Messages::receive_messages(Origin::signed(3), second, 2 * TRANSFER_WEIGHT));
+		assert_eq!(Balances::free_balance(&1), 70);
+		assert_eq!(Messages::inbound_lane(LANE), 3);
+	});
+}
+
+#[test]
+fn received_messages_must_be_proven() {
+	let mut sending = new_test_ext();
+	let mut receiving = new_test_ext();
+
+	let (proof, missing) = with_externalities(&mut sending, || {
+		assert_ok!(Messages::send_message(Origin::signed(1), LANE, transfer(2, 30)));
+		(prove_messages(1, 1, 1), prove_messages(2, 1, 2))
+	});
+
+	with_externalities(&mut receiving, || {
+		let mut unfinalized = proof.clone();
+		unfinalized.header_hash = H256::repeat_byte(1);
+		assert_noop!(
+			Messages::receive_messages(Origin::signed(3), unfinalized, 2 * TRANSFER_WEIGHT),
+			"Header is not finalized"
+		);
+
+		let mut incomplete = proof.clone();
+		incomplete.storage_proof.clear();
+		assert_noop!(
+			Messages::receive_messages(Origin::signed(3), incomplete, 2 * TRANSFER_WEIGHT),
+			"Storage proof doesn't contain the state root"
+		);
+
+		assert_noop!(
+			Messages::receive_messages(Origin::signed(3), missing, 2 * TRANSFER_WEIGHT),
+			"Message is not in the proof"
+		);
+		assert_ok!(Messages::receive_messages(Origin::signed(3), proof, 2 * TRANSFER_WEIGHT));
+	});
+}
+
+#[test]
+fn delivered_messages_are_removed() {
+	let mut sending = new_test_ext();
+	let mut receiving = new_test_ext();
+
+	let proof = with_externalities(&mut sending, || {
+		for _ in 0..3 {
+			assert_ok!(Messages::send_message(Origin::signed(1), LANE, transfer(2, 1)));
+		}
+		prove_messages(1, 1, 2)
+	});
+	let delivery = with_externalities(&mut receiving, || {
+		assert_ok!(Messages::receive_messages(Origin::signed(3), proof, 2 * TRANSFER_WEIGHT));
+		prove_delivery(2)
+	});
+
+	with_externalities(&mut sending, || {
+		assert_ok!(Messages::confirm_delivery(Origin::signed(3), delivery.clone()));
+		assert_eq!(Messages::outbound_lane(LANE), OutboundLaneState { latest_sent: 3, latest_delivered: 2 });
+		assert_eq!(Messages::outbound_message((LANE, 2)), None);
+		assert!(Messages::outbound_message((LANE, 3)).is_some());
+		assert_noop!(
+			Messages::confirm_delivery(Origin::signed(3), delivery),
+			"Messages are already known to be delivered"
+		);
+	});
+}
+
+#[test]
+fn received_messages_must_have_enough_weight() {
+	let mut sending = new_test_ext();
+	let mut receiving = new_test_ext();
+
+	let proof = with_externalities(&mut sending, || {
+		assert_ok!(Messages::send_message(Origin::signed(1), LANE, transfer(2, 30)));
+		assert_ok!(Messages::send_message(Origin::signed(1), LANE, transfer(2, 30)));
+		prove_messages(1, 1, 2)
+	});
+
+	with_externalities(&mut receiving, || {
+		assert_noop!(
+			Messages::receive_messages(Origin::signed(3), proof.clone(), 3 * TRANSFER_WEIGHT),
+			"Dispatch weight is too high"
+		);
+		assert_noop!(
+			Messages::receive_messages(Origin::signed(3), proof.clone(), TRANSFER_WEIGHT),
+			"Messages need more weight than declared"
+		);
+
+		let weight = |dispatch_weight| DeliveryWeight::<Test>::default().weigh_data((&proof, &dispatch_weight));
+		assert_eq!(weight(2 * TRANSFER_WEIGHT) - weight(0), 2 * TRANSFER_WEIGHT);
+
+		assert_ok!(Messages::receive_messages(Origin::signed(3), proof, 2 * TRANSFER_WEIGHT));
+		assert_eq!(Balances::free_balance(&2), 160);
+	});
+}
+
+#[test]
+fn delivery_confirmations_remove_a_bounded_number_of_messages() {
+	let mut sending = new_test_ext();
+	let mut receiving = new_test_ext();
+
+	let count = MAX_MESSAGES_PER_DELIVERY + 2;
+	let (first, second) = with_externalities(&mut sending, || {
+		Balances::make_free_balance_be(&1, 10_000);
+		for _ in 0..count {
+			assert_ok!(Messages::send_message(Origin::signed(1), LANE, vec![0xff]));
+		}
+		let first = prove_messages(1, 1, MAX_MESSAGES_PER_DELIVERY);
+		(first, prove_messages(2, MAX_MESSAGES_PER_DELIVERY + 1, count))
+	});
+	let delivery = with_externalities(&mut receiving, || {
+		assert_ok!(Messages::receive_messages(Origin::signed(3), first, 0));
+		assert_ok!(Messages::receive_messages(Origin::signed(3), second, 0));
+		prove_delivery(3)
+	});
+
+	with_externalities(&mut sending, || {
+		assert_ok!(Messages::confirm_delivery(Origin::signed(3), delivery.clone()));
+		assert_eq!(Messages::outbound_lane(LANE).latest_delivered, MAX_MESSAGES_PER_DELIVERY);
+		assert!(Messages::outbound_message((LANE, count)).is_some());
+
+		assert_ok!(Messages::confirm_delivery(Origin::signed(3), delivery));
+		assert_eq!(Messages::outbound_lane(LANE).latest_delivered, count);
+		assert_eq!(Messages::outbound_message((LANE, count)), None);
+	});
+}
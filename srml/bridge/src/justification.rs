@@ -0,0 +1,144 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Verification of GRANDPA justifications.
+//!
+//! The types mirror the encoding of the justifications produced by the GRANDPA voters of the
+//! bridged chain, which can't be verified with the `finality-grandpa` crate in the runtime.
+
+use rstd::prelude::*;
+use codec::{Encode, Decode};
+use sr_primitives::traits::{Header as HeaderT, Verify};
+use fg_primitives::{AuthorityId, AuthoritySignature, AuthorityWeight};
+
+/// A vote for the finality of the block `target_hash`, and of its ancestors.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Precommit<Hash, Number> {
+	/// The hash of the voted block.
+	pub target_hash: Hash,
+	/// The number of the voted block.
+	pub target_number: Number,
+}
+
+/// A precommit signed by an authority.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct SignedPrecommit<Hash, Number> {
+	/// The precommit.
+	pub precommit: Precommit<Hash, Number>,
+	/// The signature of the precommit, for its round and authority set.
+	pub signature: AuthoritySignature,
+	/// The authority that signed the precommit.
+	pub id: AuthorityId,
+}
+
+/// The precommits finalizing the block `target_hash`.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Commit<Hash, Number> {
+	/// The hash of the finalized block.
+	pub target_hash: Hash,
+	/// The number of the finalized block.
+	pub target_number: Number,
+	/// The precommits for the block or its descendants.
+	pub precommits: Vec<SignedPrecommit<Hash, Number>>,
+}
+
+/// A GRANDPA justification of the finality of a block.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct GrandpaJustification<Header: HeaderT> {
+	/// The round of the commit.
+	pub round: u64,
+	/// The commit.
+	pub commit: Commit<Header::Hash, Header::Number>,
+	/// The headers between the finalized block and the targets of the precommits.
+	pub votes_ancestries: Vec<Header>,
+}
+
+/// The message signed by the authorities, as encoded by the GRANDPA voters.
+#[derive(Encode)]
+pub(crate) enum Message<Hash, Number> {
+	#[codec(index = "1")]
+	Precommit(Precommit<Hash, Number>),
+}
+
+/// Check that `justification` proves the finality of the block `hash` with number `number`,
+/// for the authority set `set_id` made of `authorities`.
+///
+/// The justification needs valid precommits from more than two thirds of the weight of the
+/// authorities, for the block or its descendants with their ancestry in `votes_ancestries`.
+/// Each authority may only sign one precommit.
+pub fn verify_justification<Header: HeaderT>(
+	hash: Header::Hash,
+	number: Header::Number,
+	set_id: u64,
+	authorities: &[(AuthorityId, AuthorityWeight)],
+	justification: &GrandpaJustification<Header>,
+) -> Result<(), &'static str> {
+	let commit = &justification.commit;
+	if commit.target_hash != hash || commit.target_number != number {
+		return Err("Justification is for another header");
+	}
+
+	let mut voters: Vec<(AuthorityId, AuthorityWeight)> = Vec::new();
+	let mut visited = Vec::new();
+	for signed in &commit.precommits {
+		if voters.iter().any(|(id, _)| *id == signed.id) {
+			return Err("Justification contains duplicate precommits");
+		}
+		let weight = authorities.iter()
+			.find(|(id, _)| *id == signed.id)
+			.map(|(_, weight)| *weight)
+			.ok_or("Precommit signed by an unknown authority")?;
+
+		let message = Message::Precommit(signed.precommit.clone());
+		let payload = (message, justification.round, set_id).encode();
+		if !signed.signature.verify(&payload[..], &signed.id) {
+			return Err("Invalid precommit signature");
+		}
+
+		let mut current = signed.precommit.target_hash;
+		while current != commit.target_hash {
+			let header = justification.votes_ancestries.iter()
+				.find(|header| header.hash() == current)
+				.ok_or("Precommit target is not a descendant of the justified header")?;
+			if *header.number() <= commit.target_number {
+				return Err("Precommit target is not a descendant of the justified header");
+			}
+			if !visited.contains(&current) {
+				visited.push(current);
+			}
+			current = *header.parent_hash();
+		}
+
+		voters.push((signed.id.clone(), weight));
+	}
+
+	if justification.votes_ancestries.len() != visited.len() {
+		return Err("Justification contains unused or duplicate headers");
+	}
+
+	let total = authorities.iter().fold(0, |total: AuthorityWeight, (_, weight)| total.saturating_add(*weight));
+	let voted = voters.iter().fold(0, |voted: AuthorityWeight, (_, weight)| voted.saturating_add(*weight));
+	let faulty = total.saturating_sub(1) / 3;
+	if voted == 0 || voted < total - faulty {
+		return Err("Not enough precommits to justify the header");
+	}
+
+	Ok(())
+}
@@ -0,0 +1,358 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Bridge Module
+//!
+//! The Bridge module is an on-chain light client of another Substrate chain finalized by
+//! GRANDPA.
+//!
+//! - [`bridge::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! The module tracks the headers of the bridged chain from a trusted finalized header and the
+//! GRANDPA authority set finalizing its successors, given to `initialize`. Anyone can then import
+//! the descendants of the best finalized header, and finalize them with the GRANDPA justifications
+//! of the bridged chain. Other modules query the finalized headers through `FinalizedHeaders`,
+//! for instance to check storage proofs against their state root.
+//!
+//! Importing a header reserves `HeaderDeposit` from its submitter. The deposit is returned when
+//! the header is finalized. The imported headers of the numbers being finalized that aren't
+//! finalized themselves are pruned, and their deposit is slashed.
+//!
+//! ### Authority Set Changes
+//!
+//! The standard authority set changes scheduled in the digests of the imported headers are
+//! enacted when the header at the end of their delay is finalized. That header must be finalized
+//! by its own justification, the following headers being finalized by the new authority set. A
+//! change can't be scheduled while another one is pending. Forced changes aren't supported.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `initialize` - Start tracking the bridged chain from a finalized header.
+//! - `import_header` - Import a header of the bridged chain.
+//! - `finalize_header` - Finalize an imported header with a GRANDPA justification.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+mod justification;
+#[cfg(test)]
+mod tests;
+
+pub use crate::justification::{
+	GrandpaJustification, Commit, Precommit, SignedPrecommit, verify_justification,
+};
+
+use rstd::{prelude::*, marker::PhantomData};
+use codec::{Encode, Decode};
+use srml_support::{
+	StorageValue, StorageMap, Parameter, decl_module, decl_storage, decl_event, ensure,
+};
+use srml_support::dispatch::Result;
+use srml_support::traits::{Currency, ReservableCurrency, OnUnbalanced, Get};
+use sr_primitives::generic::OpaqueDigestItemId;
+use sr_primitives::traits::{Header as HeaderT, One, Zero, Saturating, SaturatedConversion};
+use sr_primitives::weights::{Weight, WeighData, ClassifyDispatch, DispatchClass, SimpleDispatchInfo};
+use system::{ensure_root, ensure_signed};
+use fg_primitives::{ConsensusLog, ScheduledChange, GRANDPA_ENGINE_ID};
+pub use fg_primitives::{AuthorityId, AuthorityWeight};
+
+type BridgedHash<T> = <<T as Trait>::BridgedHeader as HeaderT>::Hash;
+type BridgedNumber<T> = <<T as Trait>::BridgedHeader as HeaderT>::Number;
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
+
+/// The weight of `import_header`.
+const IMPORT_WEIGHT: Weight = 50_000;
+/// The weight of `finalize_header` besides the precommits and headers it checks.
+const FINALIZE_BASE_WEIGHT: Weight = 50_000;
+/// The weight of checking the signature of a precommit.
+const PRECOMMIT_WEIGHT: Weight = 100_000;
+/// The weight of walking through a header of a justification or of the finalized route.
+const HEADER_WEIGHT: Weight = 10_000;
+
+pub trait Trait: system::Trait {
+	/// The header of the bridged chain.
+	type BridgedHeader: HeaderT + Parameter;
+
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The currency the header deposits are reserved in.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The deposit reserved from the submitter of a header until it is finalized or pruned.
+	type HeaderDeposit: Get<BalanceOf<Self>>;
+
+	/// Handler for the deposits of the pruned headers.
+	type Slashed: OnUnbalanced<NegativeImbalanceOf<Self>>;
+}
+
+/// An authority set change of the bridged chain, waiting for its enactment.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct PendingChange<N> {
+	/// The next authority set.
+	pub next_authorities: Vec<(AuthorityId, AuthorityWeight)>,
+	/// The number of the header enacting the change once finalized.
+	pub enacted_at: N,
+}
+
+/// Access to the finalized headers of a bridged chain.
+pub trait FinalizedHeaders<Header: HeaderT> {
+	/// The best finalized header, `None` before the bridge is initialized.
+	fn best_finalized_header() -> Option<Header>;
+
+	/// The header with hash `hash`, if it is finalized.
+	fn finalized_header(hash: &Header::Hash) -> Option<Header>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Bridge {
+		/// The imported headers of the bridged chain, finalized or not.
+		pub Headers get(header): map BridgedHash<T> => Option<T::BridgedHeader>;
+		/// The hash of the finalized header of each number.
+		pub FinalizedHashes get(finalized_hash): map BridgedNumber<T> => Option<BridgedHash<T>>;
+		/// The hash of the best finalized header, `None` before the bridge is initialized.
+		pub BestFinalized get(best_finalized): Option<BridgedHash<T>>;
+		/// The authority set finalizing the headers after the best finalized header.
+		pub Authorities get(authorities): Vec<(AuthorityId, AuthorityWeight)>;
+		/// The id of the current authority set.
+		pub SetId get(set_id): u64;
+		/// The authority set change scheduled by a finalized header and not enacted yet.
+		pub NextChange get(next_change): Option<PendingChange<BridgedNumber<T>>>;
+		/// The authority set changes scheduled by the imported headers that aren't finalized.
+		ScheduledChanges: map BridgedHash<T> => Option<ScheduledChange<BridgedNumber<T>>>;
+		/// The hashes of the imported headers that aren't finalized, by number.
+		ImportedHashes: map BridgedNumber<T> => Vec<BridgedHash<T>>;
+		/// The submitter of each imported header that isn't finalized, and its deposit.
+		HeaderDeposits: map BridgedHash<T> => Option<(T::AccountId, BalanceOf<T>)>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where Hash = BridgedHash<T> {
+		/// A header of the bridged chain was imported.
+		HeaderImported(Hash),
+		/// A header of the bridged chain was finalized, with its ancestors.
+		HeaderFinalized(Hash),
+		/// An imported header of the bridged chain that wasn't finalized was pruned.
+		HeaderPruned(Hash),
+		/// The authority set of the bridged chain changed to the given set id.
+		AuthoritySetChanged(u64),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		/// The deposit reserved from the submitter of a header until it is finalized or pruned.
+		const HeaderDeposit: BalanceOf<T> = T::HeaderDeposit::get();
+
+		fn deposit_event() = default;
+
+		/// Start tracking the bridged chain from the finalized header `header`.
+		///
+		/// The descendants of `header` are finalized by the authority set `set_id` made of
+		/// `authorities`. Can only be called once.
+		fn initialize(
+			origin,
+			header: T::BridgedHeader,
+			authorities: Vec<(AuthorityId, AuthorityWeight)>,
+			set_id: u64
+		) -> Result {
+			ensure_root(origin)?;
+			ensure!(<BestFinalized<T>>::get().is_none(), "Bridge is already initialized");
+
+			let hash = header.hash();
+			<FinalizedHashes<T>>::insert(header.number(), hash);
+			<Headers<T>>::insert(hash, header);
+			<BestFinalized<T>>::put(hash);
+			Authorities::put(authorities);
+			SetId::put(set_id);
+
+			Ok(())
+		}
+
+		/// Import `header`, whose parent must be imported and not older than the best finalized
+		/// header.
+		///
+		/// `HeaderDeposit` is reserved from the sender until the header is finalized or pruned.
+		#[weight = SimpleDispatchInfo::FixedNormal(IMPORT_WEIGHT)]
+		fn import_header(origin, header: T::BridgedHeader) -> Result {
+			let who = ensure_signed(origin)?;
+			let best_finalized = Self::best_finalized_header().ok_or("Bridge is not initialized")?;
+
+			let hash = header.hash();
+			ensure!(!<Headers<T>>::exists(hash), "Header is already imported");
+			ensure!(*header.number() > *best_finalized.number(), "Header is older than the best finalized header");
+			let parent = <Headers<T>>::get(header.parent_hash()).ok_or("Parent header is not imported")?;
+			ensure!(*header.number() == *parent.number() + One::one(), "Header number doesn't follow its parent");
+
+			let deposit = T::HeaderDeposit::get();
+			T::Currency::reserve(&who, deposit)?;
+			<HeaderDeposits<T>>::insert(hash, (who, deposit));
+			<ImportedHashes<T>>::mutate(header.number(), |hashes| hashes.push(hash));
+			if let Some(change) = Self::scheduled_change(&header) {
+				<ScheduledChanges<T>>::insert(hash, change);
+			}
+			<Headers<T>>::insert(hash, header);
+			Self::deposit_event(RawEvent::HeaderImported(hash));
+
+			Ok(())
+		}
+
+		/// Finalize the imported header justified by `justification`, with its ancestors.
+		///
+		/// The deposits of the finalized headers are returned, and the other imported headers
+		/// with their numbers are pruned.
+		#[weight = FinalizeWeight::<T>::default()]
+		fn finalize_header(origin, justification: GrandpaJustification<T::BridgedHeader>) -> Result {
+			ensure_signed(origin)?;
+			let best_finalized = Self::best_finalized_header().ok_or("Bridge is not initialized")?;
+
+			let hash = justification.commit.target_hash;
+			let header = <Headers<T>>::get(hash).ok_or("Header is not imported")?;
+			let number = *header.number();
+			ensure!(number > *best_finalized.number(), "Header is already finalized");
+			verify_justification(hash, number, SetId::get(), &Authorities::get(), &justification)?;
+
+			// The headers finalized with `header`, from the oldest.
+			let mut route = vec![(number, hash)];
+			let mut parent = *header.parent_hash();
+			while parent != best_finalized.hash() {
+				let ancestor = <Headers<T>>::get(parent)
+					.filter(|ancestor| ancestor.number() > best_finalized.number())
+					.ok_or("Header is not a descendant of the best finalized header")?;
+				route.push((*ancestor.number(), parent));
+				parent = *ancestor.parent_hash();
+			}
+			route.reverse();
+
+			let mut next_change = Self::next_change();
+			for (number, hash) in &route {
+				if let Some(change) = <ScheduledChanges<T>>::get(hash) {
+					ensure!(next_change.is_none(), "Authority set change scheduled while another one is pending");
+					next_change = Some(PendingChange {
+						next_authorities: change.next_authorities,
+						enacted_at: *number + change.delay,
+					});
+				}
+			}
+			if let Some(change) = &next_change {
+				ensure!(
+					change.enacted_at >= number,
+					"Header enacting the authority set change must be finalized first"
+				);
+			}
+
+			for (number, hash) in route {
+				<FinalizedHashes<T>>::insert(number, hash);
+				<ScheduledChanges<T>>::remove(hash);
+				if let Some((who, deposit)) = <HeaderDeposits<T>>::take(hash) {
+					T::Currency::unreserve(&who, deposit);
+				}
+				for pruned in <ImportedHashes<T>>::take(number).into_iter().filter(|pruned| *pruned != hash) {
+					Self::prune_header(pruned);
+				}
+			}
+			<BestFinalized<T>>::put(hash);
+			Self::deposit_event(RawEvent::HeaderFinalized(hash));
+
+			match next_change {
+				Some(change) if change.enacted_at == number => {
+					let set_id = SetId::get() + 1;
+					Authorities::put(change.next_authorities);
+					SetId::put(set_id);
+					<NextChange<T>>::kill();
+					Self::deposit_event(RawEvent::AuthoritySetChanged(set_id));
+				},
+				Some(change) => <NextChange<T>>::put(change),
+				None => (),
+			}
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Remove the imported header `hash`, which can't be finalized anymore, and slash its deposit.
+	fn prune_header(hash: BridgedHash<T>) {
+		<Headers<T>>::remove(hash);
+		<ScheduledChanges<T>>::remove(hash);
+		if let Some((who, deposit)) = <HeaderDeposits<T>>::take(hash) {
+			let imbalance = T::Currency::slash_reserved(&who, deposit).0;
+			T::Slashed::on_unbalanced(imbalance);
+		}
+		Self::deposit_event(RawEvent::HeaderPruned(hash));
+	}
+
+	/// The standard authority set change scheduled in the digest of `header`, if any.
+	fn scheduled_change(header: &T::BridgedHeader) -> Option<ScheduledChange<BridgedNumber<T>>> {
+		let id = OpaqueDigestItemId::Consensus(&GRANDPA_ENGINE_ID);
+		header.digest()
+			.convert_first(|log| log.try_to::<ConsensusLog<BridgedNumber<T>>>(id))
+			.and_then(|log| log.try_into_change())
+	}
+}
+
+/// Weight of `finalize_header`: a signature check per precommit, and a step per header of the
+/// votes ancestries and of the finalized route.
+pub struct FinalizeWeight<T>(PhantomData<T>);
+
+impl<T> Default for FinalizeWeight<T> {
+	fn default() -> Self {
+		FinalizeWeight(PhantomData)
+	}
+}
+
+impl<T: Trait> WeighData<(&GrandpaJustification<T::BridgedHeader>,)> for FinalizeWeight<T> {
+	fn weigh_data(&self, (justification,): (&GrandpaJustification<T::BridgedHeader>,)) -> Weight {
+		let best_finalized = Module::<T>::best_finalized_header()
+			.map(|header| *header.number())
+			.unwrap_or_else(Zero::zero);
+		let route: Weight = justification.commit.target_number
+			.saturating_sub(best_finalized)
+			.saturated_into();
+		let precommits: Weight = justification.commit.precommits.len().saturated_into();
+		let ancestries: Weight = justification.votes_ancestries.len().saturated_into();
+
+		FINALIZE_BASE_WEIGHT
+			.saturating_add(PRECOMMIT_WEIGHT.saturating_mul(precommits))
+			.saturating_add(HEADER_WEIGHT.saturating_mul(ancestries.saturating_add(route)))
+	}
+}
+
+impl<T, A> ClassifyDispatch<A> for FinalizeWeight<T> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+impl<T: Trait> FinalizedHeaders<T::BridgedHeader> for Module<T> {
+	fn best_finalized_header() -> Option<T::BridgedHeader> {
+		<BestFinalized<T>>::get().and_then(|hash| <Headers<T>>::get(hash))
+	}
+
+	fn finalized_header(hash: &BridgedHash<T>) -> Option<T::BridgedHeader> {
+		<Headers<T>>::get(hash)
+			.filter(|header| <FinalizedHashes<T>>::get(header.number()).as_ref() == Some(hash))
+	}
+}
@@ -0,0 +1,271 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use super::*;
+
+use keyring::Ed25519Keyring as Keyring;
+use primitives::{Blake2Hasher, H256};
+use runtime_io::with_externalities;
+use sr_primitives::testing::{Digest, DigestItem, Header};
+use srml_support::{assert_noop, assert_ok, impl_test_runtime, parameter_types};
+use crate::justification::Message;
+
+impl_test_runtime! {
+	pub struct Test;
+	balances {
+		ExistentialDeposit = 0;
+		TransferFee = 0;
+		CreationFee = 0;
+	}
+}
+parameter_types! {
+	pub const HeaderDeposit: u64 = 10;
+}
+impl Trait for Test {
+	type BridgedHeader = Header;
+	type Event = ();
+	type Currency = balances::Module<Test>;
+	type HeaderDeposit = HeaderDeposit;
+	type Slashed = ();
+}
+type Balances = balances::Module<Test>;
+type Bridge = Module<Test>;
+
+fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap().0;
+	t.extend(balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 15)],
+		vesting: vec![],
+	}.build_storage().unwrap().0);
+	t.into()
+}
+
+fn authorities(keys: &[Keyring]) -> Vec<(AuthorityId, AuthorityWeight)> {
+	keys.iter().map(|key| ((*key).into(), 1)).collect()
+}
+
+fn child(parent: &Header, digest: Digest) -> Header {
+	Header::new(parent.number + 1, H256::zero(), H256::zero(), parent.hash(), digest)
+}
+
+/// The bridged chain from its genesis, tracked with the four first keys as authorities.
+fn initialize(len: usize) -> Vec<Header> {
+	let mut headers = vec![Header::new(0, H256::zero(), H256::zero(), H256::zero(), Digest::default())];
+	for _ in 1..len {
+		headers.push(child(headers.last().unwrap(), Digest::default()));
+	}
+
+	let keys = [Keyring::Alice, Keyring::Bob, Keyring::Charlie, Keyring::Dave];
+	assert_ok!(Bridge::initialize(Origin::ROOT, headers[0].clone(), authorities(&keys), 0));
+	headers
+}
+
+/// A justification of `header` signed by `keys`, whose precommits are for `target`.
+fn justify(header: &Header, target: &Header, ancestries: &[Header], set_id: u64, keys: &[Keyring])
+	-> GrandpaJustification<Header>
+{
+	let precommit = Precommit { target_hash: target.hash(), target_number: target.number };
+	let payload = (Message::Precommit(precommit.clone()), 1u64, set_id).encode();
+	GrandpaJustification {
+		round: 1,
+		commit: Commit {
+			target_hash: header.hash(),
+			target_number: header.number,
+			precommits: keys.iter().map(|key| SignedPrecommit {
+				precommit: precommit.clone(),
+				signature: key.sign(&payload),
+				id: (*key).into(),
+			}).collect(),
+		},
+		votes_ancestries: ancestries.to_vec(),
+	}
+}
+
+#[test]
+fn headers_are_imported_on_top_of_imported_parents() {
+	with_externalities(&mut new_test_ext(), || {
+		let headers = initialize(1);
+		let header = child(&headers[0], Digest::default());
+
+		assert_noop!(
+			Bridge::import_header(Origin::signed(1), child(&header, Digest::default())),
+			"Parent header is not imported"
+		);
+		assert_ok!(Bridge::import_header(Origin::signed(1), header.clone()));
+		assert_noop!(Bridge::import_header(Origin::signed(1), header.clone()), "Header is already imported");
+		assert_eq!(Bridge::header(header.hash()), Some(header.clone()));
+		assert_eq!(Bridge::finalized_header(&header.hash()), None);
+	});
+}
+
+#[test]
+fn justified_headers_are_finalized_with_their_ancestors() {
+	with_externalities(&mut new_test_ext(), || {
+		let headers = initialize(4);
+		for header in &headers[1..] {
+			assert_ok!(Bridge::import_header(Origin::signed(1), header.clone()));
+		}
+		let keys = [Keyring::Alice, Keyring::Bob, Keyring::Charlie];
+
+		// Precommits for a descendant also finalize the header.
+		let justification = justify(&headers[2], &headers[3], &headers[3..], 0, &keys);
+		assert_ok!(Bridge::finalize_header(Origin::signed(1), justification));
+
+		assert_eq!(Bridge::best_finalized_header(), Some(headers[2].clone()));
+		assert_eq!(Bridge::finalized_header(&headers[1].hash()), Some(headers[1].clone()));
+		assert_eq!(Bridge::finalized_header(&headers[3].hash()), None);
+	});
+}
+
+#[test]
+fn invalid_justifications_are_rejected() {
+	with_externalities(&mut new_test_ext(), || {
+		let headers = initialize(3);
+		for header in &headers[1..] {
+			assert_ok!(Bridge::import_header(Origin::signed(1), header.clone()));
+		}
+		let keys = [Keyring::Alice, Keyring::Bob, Keyring::Charlie];
+
+		assert_noop!(
+			Bridge::finalize_header(Origin::signed(1), justify(&headers[1], &headers[1], &[], 0, &keys[..2])),
+			"Not enough precommits to justify the header"
+		);
+		assert_noop!(
+			Bridge::finalize_header(Origin::signed(1), justify(&headers[1], &headers[1], &[], 1, &keys)),
+			"Invalid precommit signature"
+		);
+		assert_noop!(
+			Bridge::finalize_header(Origin::signed(1), justify(&headers[1], &headers[1], &[], 0, &[Keyring::Eve])),
+			"Precommit signed by an unknown authority"
+		);
+		assert_noop!(
+			Bridge::finalize_header(Origin::signed(1), justify(&headers[1], &headers[2], &[], 0, &keys)),
+			"Precommit target is not a descendant of the justified header"
+		);
+		assert_noop!(
+			Bridge::finalize_header(Origin::signed(1), justify(&headers[1], &headers[1], &headers[2..], 0, &keys)),
+			"Justification contains unused or duplicate headers"
+		);
+
+		// An authority can't make up for the missing precommits by signing twice.
+		let duplicated = [Keyring::Alice, Keyring::Bob, Keyring::Bob];
+		assert_noop!(
+			Bridge::finalize_header(Origin::signed(1), justify(&headers[1], &headers[1], &[], 0, &duplicated)),
+			"Justification contains duplicate precommits"
+		);
+	});
+}
+
+#[test]
+fn scheduled_authority_set_changes_are_enacted() {
+	with_externalities(&mut new_test_ext(), || {
+		let headers = initialize(1);
+		let change = ScheduledChange { next_authorities: authorities(&[Keyring::Eve]), delay: 1 };
+		let mut digest = Digest::default();
+		digest.push(DigestItem::Consensus(GRANDPA_ENGINE_ID, ConsensusLog::ScheduledChange(change).encode()));
+		let signal = child(&headers[0], digest);
+		let enacting = child(&signal, Digest::default());
+		let next = child(&enacting, Digest::default());
+		for header in &[&signal, &enacting, &next] {
+			assert_ok!(Bridge::import_header(Origin::signed(1), (*header).clone()));
+		}
+		let keys = [Keyring::Alice, Keyring::Bob, Keyring::Charlie];
+
+		assert_noop!(
+			Bridge::finalize_header(Origin::signed(1), justify(&next, &next, &[], 0, &keys)),
+			"Header enacting the authority set change must be finalized first"
+		);
+
+		assert_ok!(Bridge::finalize_header(Origin::signed(1), justify(&signal, &signal, &[], 0, &keys)));
+		assert_eq!(Bridge::next_change().map(|change| change.enacted_at), Some(2));
+		assert_ok!(Bridge::finalize_header(Origin::signed(1), justify(&enacting, &enacting, &[], 0, &keys)));
+		assert_eq!(Bridge::set_id(), 1);
+		assert_eq!(Bridge::authorities(), authorities(&[Keyring::Eve]));
+		assert_eq!(Bridge::next_change(), None);
+
+		assert_noop!(
+			Bridge::finalize_header(Origin::signed(1), justify(&next, &next, &[], 1, &keys)),
+			"Precommit signed by an unknown authority"
+		);
+		assert_ok!(Bridge::finalize_header(Origin::signed(1), justify(&next, &next, &[], 1, &[Keyring::Eve])));
+		assert_eq!(Bridge::best_finalized_header(), Some(next));
+	});
+}
+
+#[test]
+fn imported_headers_reserve_a_deposit() {
+	with_externalities(&mut new_test_ext(), || {
+		let headers = initialize(1);
+		let header = child(&headers[0], Digest::default());
+		let fork = Header::new(1, H256::repeat_byte(1), H256::zero(), headers[0].hash(), Digest::default());
+
+		assert_ok!(Bridge::import_header(Origin::signed(2), header.clone()));
+		assert_eq!(Balances::reserved_balance(2), 10);
+		assert_noop!(Bridge::import_header(Origin::signed(2), fork), "not enough free funds");
+	});
+}
+
+#[test]
+fn finalization_returns_deposits_and_prunes_forks() {
+	with_externalities(&mut new_test_ext(), || {
+		let headers = initialize(3);
+		for header in &headers[1..] {
+			assert_ok!(Bridge::import_header(Origin::signed(1), header.clone()));
+		}
+		let fork = Header::new(1, H256::repeat_byte(1), H256::zero(), headers[0].hash(), Digest::default());
+		let fork_child = child(&fork, Digest::default());
+		assert_ok!(Bridge::import_header(Origin::signed(2), fork.clone()));
+		assert_eq!(Balances::reserved_balance(1), 20);
+		assert_eq!(Balances::reserved_balance(2), 10);
+
+		let keys = [Keyring::Alice, Keyring::Bob, Keyring::Charlie];
+		assert_ok!(Bridge::finalize_header(Origin::signed(1), justify(&headers[1], &headers[1], &[], 0, &keys)));
+
+		assert_eq!(Balances::reserved_balance(1), 10);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::free_balance(2), 5);
+		assert_eq!(Bridge::header(fork.hash()), None);
+		assert_noop!(
+			Bridge::import_header(Origin::signed(1), fork_child),
+			"Parent header is not imported"
+		);
+
+		assert_ok!(Bridge::finalize_header(Origin::signed(1), justify(&headers[2], &headers[2], &[], 0, &keys)));
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::free_balance(1), 100);
+	});
+}
+
+#[test]
+fn finalization_weighs_precommits_and_headers() {
+	with_externalities(&mut new_test_ext(), || {
+		let headers = initialize(4);
+		let keys = [Keyring::Alice, Keyring::Bob, Keyring::Charlie];
+		let weight = |justification: &GrandpaJustification<Header>| {
+			FinalizeWeight::<Test>::default().weigh_data((justification,))
+		};
+
+		let light = justify(&headers[1], &headers[1], &[], 0, &keys[..1]);
+		let more_precommits = justify(&headers[1], &headers[1], &[], 0, &keys);
+		let longer_route = justify(&headers[2], &headers[2], &[], 0, &keys);
+		let more_ancestries = justify(&headers[2], &headers[3], &headers[3..], 0, &keys);
+		assert!(weight(&light) < weight(&more_precommits));
+		assert!(weight(&more_precommits) < weight(&longer_route));
+		assert!(weight(&longer_route) < weight(&more_ancestries));
+	});
+}
@@ -424,6 +424,9 @@ mod tests {
 		type MaximumBlockWeight = MaximumBlockWeight;
 		type MaximumBlockLength = MaximumBlockLength;
 		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+		type Call = ();
+		type BaseCallFilter = ();
 	}
 	impl Trait<Instance1> for Test {
 		type Origin = Origin;
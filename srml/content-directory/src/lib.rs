@@ -0,0 +1,547 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Content Directory Module
+//!
+//! The Content Directory module lists channels and their content, described by off-chain data.
+//!
+//! - [`content_directory::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! Channels are created by their owner, who adds content to them. Channels and content only hold
+//! the hash of their metadata, which is stored off chain.
+//!
+//! Curation is done by curator groups, created by root. Root assigns a curator group to a channel,
+//! whose curators can then censor the channel or its content and feature its content. Censored
+//! channels can't get new content, and censored content can't be featured. At most
+//! `MaxFeaturedContents` contents are featured at a time.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `create_curator_group` - Create a curator group.
+//! - `set_curators` - Set the curators of a curator group.
+//! - `remove_curator_group` - Remove a curator group.
+//! - `set_channel_curator_group` - Assign a curator group to a channel.
+//! - `create_channel` - Create a channel.
+//! - `update_channel` - Update the metadata of a channel.
+//! - `transfer_channel` - Transfer a channel to a new owner.
+//! - `add_content` - Add content to a channel.
+//! - `update_content` - Update the metadata of content.
+//! - `remove_content` - Remove content.
+//! - `censor_channel` - Censor or uncensor a channel.
+//! - `censor_content` - Censor or uncensor content.
+//! - `set_featured` - Feature or unfeature content.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod tests;
+
+use rstd::prelude::*;
+use rstd::marker::PhantomData;
+use codec::{Encode, Decode};
+use srml_support::{StorageValue, StorageMap, Parameter, decl_module, decl_storage, decl_event, ensure};
+use srml_support::dispatch::Result;
+use srml_support::traits::Get;
+use sr_primitives::traits::{SimpleArithmetic, StaticLookup, One, SaturatedConversion};
+use sr_primitives::weights::{Weight, WeighData, ClassifyDispatch, DispatchClass, SimpleDispatchInfo};
+use system::{ensure_root, ensure_signed};
+
+/// The weight of the calls which create or update a single channel or content.
+const UPDATE_WEIGHT: Weight = 50_000;
+/// The weight of the calls on a curator group besides the curators they look up.
+const CURATOR_GROUP_WEIGHT: Weight = 50_000;
+/// The weight of looking up a curator.
+const CURATOR_WEIGHT: Weight = 10_000;
+/// The weight of walking through a featured content.
+const FEATURED_CONTENT_WEIGHT: Weight = 1_000;
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The identifier of a curator group.
+	type CuratorGroupId: Parameter + SimpleArithmetic + Default + Copy;
+
+	/// The identifier of a channel.
+	type ChannelId: Parameter + SimpleArithmetic + Default + Copy;
+
+	/// The identifier of content.
+	type ContentId: Parameter + SimpleArithmetic + Default + Copy;
+
+	/// The maximum number of featured contents.
+	type MaxFeaturedContents: Get<u32>;
+}
+
+/// A channel.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Channel<AccountId, Hash, CuratorGroupId> {
+	/// The owner of the channel.
+	pub owner: AccountId,
+	/// The hash of the metadata of the channel.
+	pub metadata: Hash,
+	/// The curator group curating the channel.
+	pub curator_group: Option<CuratorGroupId>,
+	/// Whether the channel is censored.
+	pub censored: bool,
+	/// The number of contents of the channel.
+	pub contents: u32,
+}
+
+/// Content of a channel.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Content<ChannelId, Hash> {
+	/// The channel of the content.
+	pub channel: ChannelId,
+	/// The hash of the metadata of the content.
+	pub metadata: Hash,
+	/// Whether the content is censored.
+	pub censored: bool,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as ContentDirectory {
+		/// The identifier of the next curator group.
+		pub NextCuratorGroupId get(next_curator_group_id): T::CuratorGroupId;
+		/// The curators of each curator group.
+		pub CuratorGroups get(curator_group): map T::CuratorGroupId => Option<Vec<T::AccountId>>;
+
+		/// The identifier of the next channel.
+		pub NextChannelId get(next_channel_id): T::ChannelId;
+		/// The channels.
+		pub Channels get(channel): map T::ChannelId => Option<Channel<T::AccountId, T::Hash, T::CuratorGroupId>>;
+
+		/// The identifier of the next content.
+		pub NextContentId get(next_content_id): T::ContentId;
+		/// The contents.
+		pub Contents get(content): map T::ContentId => Option<Content<T::ChannelId, T::Hash>>;
+
+		/// The featured contents, in the order they were featured.
+		pub FeaturedContents get(featured_contents): Vec<T::ContentId>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as Trait>::CuratorGroupId,
+		<T as Trait>::ChannelId,
+		<T as Trait>::ContentId,
+	{
+		/// A curator group was created.
+		CuratorGroupCreated(CuratorGroupId),
+		/// The curators of a curator group changed.
+		CuratorsChanged(CuratorGroupId),
+		/// A curator group was removed.
+		CuratorGroupRemoved(CuratorGroupId),
+		/// The curator group of a channel changed.
+		ChannelCuratorGroupChanged(ChannelId, Option<CuratorGroupId>),
+		/// A channel was created by the owner.
+		ChannelCreated(ChannelId, AccountId),
+		/// The metadata of a channel changed.
+		ChannelUpdated(ChannelId),
+		/// A channel was transferred to the new owner.
+		ChannelTransferred(ChannelId, AccountId),
+		/// Content was added to a channel.
+		ContentAdded(ContentId, ChannelId),
+		/// The metadata of content changed.
+		ContentUpdated(ContentId),
+		/// Content was removed.
+		ContentRemoved(ContentId),
+		/// A channel was censored (`true`) or uncensored (`false`).
+		ChannelCensored(ChannelId, bool),
+		/// Content was censored (`true`) or uncensored (`false`).
+		ContentCensored(ContentId, bool),
+		/// Content was featured (`true`) or unfeatured (`false`).
+		ContentFeatured(ContentId, bool),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		/// The maximum number of featured contents.
+		const MaxFeaturedContents: u32 = T::MaxFeaturedContents::get();
+
+		fn deposit_event() = default;
+
+		/// Create a curator group of `curators`.
+		#[weight = CuratorsWeight::<T>::default()]
+		fn create_curator_group(origin, curators: Vec<<T::Lookup as StaticLookup>::Source>) -> Result {
+			ensure_root(origin)?;
+			let curators = Self::lookup_all(curators)?;
+
+			let id = Self::next_curator_group_id();
+			<NextCuratorGroupId<T>>::mutate(|id| *id += One::one());
+			<CuratorGroups<T>>::insert(id, curators);
+			Self::deposit_event(RawEvent::CuratorGroupCreated(id));
+
+			Ok(())
+		}
+
+		/// Set the curators of the curator group `group` to `curators`.
+		#[weight = CuratorsWeight::<T>::default()]
+		fn set_curators(
+			origin,
+			#[compact] group: T::CuratorGroupId,
+			curators: Vec<<T::Lookup as StaticLookup>::Source>
+		) -> Result {
+			ensure_root(origin)?;
+			ensure!(<CuratorGroups<T>>::exists(group), "Unknown curator group");
+			let curators = Self::lookup_all(curators)?;
+
+			<CuratorGroups<T>>::insert(group, curators);
+			Self::deposit_event(RawEvent::CuratorsChanged(group));
+
+			Ok(())
+		}
+
+		/// Remove the curator group `group`.
+		///
+		/// The channels it curated can't be curated until they are assigned another group.
+		#[weight = SimpleDispatchInfo::FixedOperational(CURATOR_GROUP_WEIGHT)]
+		fn remove_curator_group(origin, #[compact] group: T::CuratorGroupId) -> Result {
+			ensure_root(origin)?;
+			ensure!(<CuratorGroups<T>>::exists(group), "Unknown curator group");
+
+			<CuratorGroups<T>>::remove(group);
+			Self::deposit_event(RawEvent::CuratorGroupRemoved(group));
+
+			Ok(())
+		}
+
+		/// Make `group` the curator group of the channel `channel_id`, or leave the channel
+		/// uncurated.
+		#[weight = SimpleDispatchInfo::FixedOperational(UPDATE_WEIGHT)]
+		fn set_channel_curator_group(
+			origin,
+			#[compact] channel_id: T::ChannelId,
+			group: Option<T::CuratorGroupId>
+		) -> Result {
+			ensure_root(origin)?;
+			let mut channel = Self::channel(channel_id).ok_or("Unknown channel")?;
+			if let Some(group) = group {
+				ensure!(<CuratorGroups<T>>::exists(group), "Unknown curator group");
+			}
+
+			channel.curator_group = group;
+			<Channels<T>>::insert(channel_id, channel);
+			Self::deposit_event(RawEvent::ChannelCuratorGroupChanged(channel_id, group));
+
+			Ok(())
+		}
+
+		/// Create a channel owned by the sender, with the hash of its metadata.
+		#[weight = SimpleDispatchInfo::FixedNormal(UPDATE_WEIGHT)]
+		fn create_channel(origin, metadata: T::Hash) -> Result {
+			let owner = ensure_signed(origin)?;
+
+			let id = Self::next_channel_id();
+			<NextChannelId<T>>::mutate(|id| *id += One::one());
+			<Channels<T>>::insert(id, Channel {
+				owner: owner.clone(),
+				metadata,
+				curator_group: None,
+				censored: false,
+				contents: 0,
+			});
+			Self::deposit_event(RawEvent::ChannelCreated(id, owner));
+
+			Ok(())
+		}
+
+		/// Set the hash of the metadata of the channel `channel_id` of the sender.
+		#[weight = SimpleDispatchInfo::FixedNormal(UPDATE_WEIGHT)]
+		fn update_channel(origin, #[compact] channel_id: T::ChannelId, metadata: T::Hash) -> Result {
+			let who = ensure_signed(origin)?;
+			let mut channel = Self::owned_channel(&who, channel_id)?;
+
+			channel.metadata = metadata;
+			<Channels<T>>::insert(channel_id, channel);
+			Self::deposit_event(RawEvent::ChannelUpdated(channel_id));
+
+			Ok(())
+		}
+
+		/// Transfer the channel `channel_id` of the sender to `new_owner`.
+		#[weight = SimpleDispatchInfo::FixedNormal(UPDATE_WEIGHT)]
+		fn transfer_channel(
+			origin,
+			#[compact] channel_id: T::ChannelId,
+			new_owner: <T::Lookup as StaticLookup>::Source
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let new_owner = T::Lookup::lookup(new_owner)?;
+			let mut channel = Self::owned_channel(&who, channel_id)?;
+
+			channel.owner = new_owner.clone();
+			<Channels<T>>::insert(channel_id, channel);
+			Self::deposit_event(RawEvent::ChannelTransferred(channel_id, new_owner));
+
+			Ok(())
+		}
+
+		/// Add content to the channel `channel_id` of the sender, with the hash of its metadata.
+		#[weight = SimpleDispatchInfo::FixedNormal(UPDATE_WEIGHT)]
+		fn add_content(origin, #[compact] channel_id: T::ChannelId, metadata: T::Hash) -> Result {
+			let who = ensure_signed(origin)?;
+			let mut channel = Self::owned_channel(&who, channel_id)?;
+			ensure!(!channel.censored, "Channel is censored");
+
+			channel.contents = channel.contents.checked_add(1).ok_or("Too many contents")?;
+			let id = Self::next_content_id();
+			<NextContentId<T>>::mutate(|id| *id += One::one());
+			<Channels<T>>::insert(channel_id, channel);
+			<Contents<T>>::insert(id, Content { channel: channel_id, metadata, censored: false });
+			Self::deposit_event(RawEvent::ContentAdded(id, channel_id));
+
+			Ok(())
+		}
+
+		/// Set the hash of the metadata of the content `content_id` of a channel of the sender.
+		#[weight = SimpleDispatchInfo::FixedNormal(UPDATE_WEIGHT)]
+		fn update_content(origin, #[compact] content_id: T::ContentId, metadata: T::Hash) -> Result {
+			let who = ensure_signed(origin)?;
+			let mut content = Self::content(content_id).ok_or("Unknown content")?;
+			Self::owned_channel(&who, content.channel)?;
+
+			content.metadata = metadata;
+			<Contents<T>>::insert(content_id, content);
+			Self::deposit_event(RawEvent::ContentUpdated(content_id));
+
+			Ok(())
+		}
+
+		/// Remove the content `content_id` of a channel of the sender.
+		#[weight = CurationWeight::<T>::default()]
+		fn remove_content(origin, #[compact] content_id: T::ContentId) -> Result {
+			let who = ensure_signed(origin)?;
+			let content = Self::content(content_id).ok_or("Unknown content")?;
+			let mut channel = Self::owned_channel(&who, content.channel)?;
+
+			channel.contents -= 1;
+			<Channels<T>>::insert(content.channel, channel);
+			<Contents<T>>::remove(content_id);
+			Self::unfeature(content_id);
+			Self::deposit_event(RawEvent::ContentRemoved(content_id));
+
+			Ok(())
+		}
+
+		/// Censor the channel `channel_id`, or uncensor it if `censored` is `false`.
+		///
+		/// The sender must be a curator of `group`, the curator group of the channel.
+		#[weight = CurationWeight::<T>::default()]
+		fn censor_channel(
+			origin,
+			#[compact] group: T::CuratorGroupId,
+			#[compact] channel_id: T::ChannelId,
+			censored: bool
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let mut channel = Self::channel(channel_id).ok_or("Unknown channel")?;
+			Self::ensure_curator(&who, group, &channel)?;
+
+			channel.censored = censored;
+			<Channels<T>>::insert(channel_id, channel);
+			Self::deposit_event(RawEvent::ChannelCensored(channel_id, censored));
+
+			Ok(())
+		}
+
+		/// Censor the content `content_id`, or uncensor it if `censored` is `false`. Censored
+		/// content is unfeatured.
+		///
+		/// The sender must be a curator of `group`, the curator group of the channel of the
+		/// content.
+		#[weight = CurationWeight::<T>::default()]
+		fn censor_content(
+			origin,
+			#[compact] group: T::CuratorGroupId,
+			#[compact] content_id: T::ContentId,
+			censored: bool
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let mut content = Self::content(content_id).ok_or("Unknown content")?;
+			let channel = Self::channel(content.channel).ok_or("Unknown channel")?;
+			Self::ensure_curator(&who, group, &channel)?;
+
+			content.censored = censored;
+			<Contents<T>>::insert(content_id, content);
+			if censored {
+				Self::unfeature(content_id);
+			}
+			Self::deposit_event(RawEvent::ContentCensored(content_id, censored));
+
+			Ok(())
+		}
+
+		/// Feature the content `content_id`, or unfeature it if `featured` is `false`.
+		///
+		/// The sender must be a curator of `group`, the curator group of the channel of the
+		/// content.
+		#[weight = CurationWeight::<T>::default()]
+		fn set_featured(
+			origin,
+			#[compact] group: T::CuratorGroupId,
+			#[compact] content_id: T::ContentId,
+			featured: bool
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let content = Self::content(content_id).ok_or("Unknown content")?;
+			let channel = Self::channel(content.channel).ok_or("Unknown channel")?;
+			Self::ensure_curator(&who, group, &channel)?;
+
+			if !featured {
+				Self::unfeature(content_id);
+				return Ok(());
+			}
+
+			ensure!(!content.censored && !channel.censored, "Content is censored");
+			let mut featured_contents = Self::featured_contents();
+			ensure!(!featured_contents.contains(&content_id), "Content is already featured");
+			ensure!(
+				featured_contents.len() < T::MaxFeaturedContents::get() as usize,
+				"Too many featured contents"
+			);
+
+			featured_contents.push(content_id);
+			<FeaturedContents<T>>::put(featured_contents);
+			Self::deposit_event(RawEvent::ContentFeatured(content_id, true));
+
+			Ok(())
+		}
+	}
+}
+
+/// Weight of `create_curator_group` and `set_curators`: a lookup per curator.
+pub struct CuratorsWeight<T>(PhantomData<T>);
+
+impl<T> Default for CuratorsWeight<T> {
+	fn default() -> Self {
+		CuratorsWeight(PhantomData)
+	}
+}
+
+impl<T: Trait> WeighData<(&Vec<<T::Lookup as StaticLookup>::Source>,)> for CuratorsWeight<T> {
+	fn weigh_data(&self, (curators,): (&Vec<<T::Lookup as StaticLookup>::Source>,)) -> Weight {
+		let curators: Weight = curators.len().saturated_into();
+		CURATOR_GROUP_WEIGHT.saturating_add(CURATOR_WEIGHT.saturating_mul(curators))
+	}
+}
+
+impl<T: Trait> WeighData<(&T::CuratorGroupId, &Vec<<T::Lookup as StaticLookup>::Source>)> for CuratorsWeight<T> {
+	fn weigh_data(
+		&self,
+		(_, curators): (&T::CuratorGroupId, &Vec<<T::Lookup as StaticLookup>::Source>),
+	) -> Weight {
+		self.weigh_data((curators,))
+	}
+}
+
+impl<T, A> ClassifyDispatch<A> for CuratorsWeight<T> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		DispatchClass::Operational
+	}
+}
+
+/// Weight of the calls which may unfeature content, walking through the featured contents, and
+/// of the calls of curators, walking through the curators of their group.
+pub struct CurationWeight<T>(PhantomData<T>);
+
+impl<T> Default for CurationWeight<T> {
+	fn default() -> Self {
+		CurationWeight(PhantomData)
+	}
+}
+
+impl<T: Trait> CurationWeight<T> {
+	/// The weight of the call besides the curators of its group.
+	fn base() -> Weight {
+		UPDATE_WEIGHT.saturating_add(FEATURED_CONTENT_WEIGHT.saturating_mul(T::MaxFeaturedContents::get()))
+	}
+}
+
+impl<T: Trait> WeighData<(&T::ContentId,)> for CurationWeight<T> {
+	fn weigh_data(&self, _: (&T::ContentId,)) -> Weight {
+		Self::base()
+	}
+}
+
+impl<T: Trait, I, B> WeighData<(&T::CuratorGroupId, I, B)> for CurationWeight<T> {
+	fn weigh_data(&self, (group, _, _): (&T::CuratorGroupId, I, B)) -> Weight {
+		let curators: Weight = Module::<T>::curator_group(group)
+			.map_or(0, |curators| curators.len())
+			.saturated_into();
+		Self::base().saturating_add(CURATOR_WEIGHT.saturating_mul(curators))
+	}
+}
+
+impl<T, A> ClassifyDispatch<A> for CurationWeight<T> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+impl<T: Trait> Module<T> {
+	fn lookup_all(
+		sources: Vec<<T::Lookup as StaticLookup>::Source>,
+	) -> rstd::result::Result<Vec<T::AccountId>, &'static str> {
+		let mut accounts = sources.into_iter()
+			.map(T::Lookup::lookup)
+			.collect::<rstd::result::Result<Vec<_>, _>>()?;
+		accounts.sort();
+		accounts.dedup();
+		Ok(accounts)
+	}
+
+	/// The channel `channel_id`, if it is owned by `who`.
+	fn owned_channel(
+		who: &T::AccountId,
+		channel_id: T::ChannelId,
+	) -> rstd::result::Result<Channel<T::AccountId, T::Hash, T::CuratorGroupId>, &'static str> {
+		let channel = Self::channel(channel_id).ok_or("Unknown channel")?;
+		ensure!(channel.owner == *who, "Not the owner of the channel");
+		Ok(channel)
+	}
+
+	/// Check that `who` is a curator of `group`, the curator group of `channel`.
+	fn ensure_curator(
+		who: &T::AccountId,
+		group: T::CuratorGroupId,
+		channel: &Channel<T::AccountId, T::Hash, T::CuratorGroupId>,
+	) -> Result {
+		let curators = Self::curator_group(group).ok_or("Unknown curator group")?;
+		ensure!(curators.contains(who), "Not a curator of the group");
+		ensure!(channel.curator_group == Some(group), "Channel is not curated by the group");
+		Ok(())
+	}
+
+	fn unfeature(content_id: T::ContentId) {
+		let mut featured_contents = Self::featured_contents();
+		let len = featured_contents.len();
+		featured_contents.retain(|id| *id != content_id);
+		if featured_contents.len() != len {
+			<FeaturedContents<T>>::put(featured_contents);
+			Self::deposit_event(RawEvent::ContentFeatured(content_id, false));
+		}
+	}
+}
@@ -0,0 +1,158 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use super::*;
+
+use primitives::{Blake2Hasher, H256};
+use runtime_io::with_externalities;
+use sr_primitives::weights::GetDispatchInfo;
+use srml_support::{assert_noop, assert_ok, impl_test_runtime, parameter_types};
+
+impl_test_runtime! {
+	pub struct Test;
+}
+parameter_types! {
+	pub const MaxFeaturedContents: u32 = 2;
+}
+impl Trait for Test {
+	type Event = ();
+	type CuratorGroupId = u32;
+	type ChannelId = u64;
+	type ContentId = u64;
+	type MaxFeaturedContents = MaxFeaturedContents;
+}
+type Directory = Module<Test>;
+
+fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+	system::GenesisConfig::default().build_storage::<Test>().unwrap().0.into()
+}
+
+/// The channel 0 of the account 1 with the contents 0 to `contents - 1`, curated by the curator
+/// group 0 of the accounts 10 and 11.
+fn new_curated_channel(contents: u64) {
+	assert_ok!(Directory::create_curator_group(Origin::ROOT, vec![11, 10, 11]));
+	assert_ok!(Directory::create_channel(Origin::signed(1), H256::repeat_byte(1)));
+	assert_ok!(Directory::set_channel_curator_group(Origin::ROOT, 0, Some(0)));
+	for content in 0..contents {
+		assert_ok!(Directory::add_content(Origin::signed(1), 0, H256::from_low_u64_be(content)));
+	}
+}
+
+#[test]
+fn channels_are_managed_by_their_owner() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Directory::create_channel(Origin::signed(1), H256::repeat_byte(1)));
+		assert_noop!(Directory::update_channel(Origin::signed(2), 0, H256::repeat_byte(2)), "Not the owner of the channel");
+		assert_noop!(Directory::update_channel(Origin::signed(1), 1, H256::repeat_byte(2)), "Unknown channel");
+		assert_ok!(Directory::update_channel(Origin::signed(1), 0, H256::repeat_byte(2)));
+		assert_eq!(Directory::channel(0).unwrap().metadata, H256::repeat_byte(2));
+
+		assert_ok!(Directory::transfer_channel(Origin::signed(1), 0, 2));
+		assert_noop!(Directory::add_content(Origin::signed(1), 0, H256::zero()), "Not the owner of the channel");
+		assert_ok!(Directory::add_content(Origin::signed(2), 0, H256::zero()));
+		assert_eq!(Directory::content(0), Some(Content { channel: 0, metadata: H256::zero(), censored: false }));
+		assert_eq!(Directory::channel(0).unwrap().contents, 1);
+
+		assert_ok!(Directory::update_content(Origin::signed(2), 0, H256::repeat_byte(3)));
+		assert_eq!(Directory::content(0).unwrap().metadata, H256::repeat_byte(3));
+		assert_ok!(Directory::remove_content(Origin::signed(2), 0));
+		assert_eq!(Directory::content(0), None);
+		assert_eq!(Directory::channel(0).unwrap().contents, 0);
+	});
+}
+
+#[test]
+fn curator_groups_are_managed_by_root() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_noop!(
+			Directory::create_curator_group(Origin::signed(1), vec![10]),
+			"bad origin: expected to be a root origin"
+		);
+		assert_ok!(Directory::create_curator_group(Origin::ROOT, vec![11, 10, 11]));
+		assert_eq!(Directory::curator_group(0), Some(vec![10, 11]));
+
+		assert_noop!(Directory::set_curators(Origin::ROOT, 1, vec![12]), "Unknown curator group");
+		assert_ok!(Directory::set_curators(Origin::ROOT, 0, vec![12]));
+		assert_eq!(Directory::curator_group(0), Some(vec![12]));
+
+		assert_ok!(Directory::create_channel(Origin::signed(1), H256::zero()));
+		assert_noop!(Directory::set_channel_curator_group(Origin::ROOT, 0, Some(1)), "Unknown curator group");
+		assert_ok!(Directory::set_channel_curator_group(Origin::ROOT, 0, Some(0)));
+		assert_ok!(Directory::censor_channel(Origin::signed(12), 0, 0, true));
+
+		assert_ok!(Directory::remove_curator_group(Origin::ROOT, 0));
+		assert_noop!(Directory::censor_channel(Origin::signed(12), 0, 0, false), "Unknown curator group");
+	});
+}
+
+#[test]
+fn curators_censor_their_channels() {
+	with_externalities(&mut new_test_ext(), || {
+		new_curated_channel(1);
+		assert_ok!(Directory::create_curator_group(Origin::ROOT, vec![12]));
+
+		assert_noop!(Directory::censor_channel(Origin::signed(1), 0, 0, true), "Not a curator of the group");
+		assert_noop!(Directory::censor_channel(Origin::signed(12), 1, 0, true), "Channel is not curated by the group");
+		assert_ok!(Directory::censor_channel(Origin::signed(10), 0, 0, true));
+		assert_noop!(Directory::add_content(Origin::signed(1), 0, H256::zero()), "Channel is censored");
+		assert_ok!(Directory::censor_channel(Origin::signed(11), 0, 0, false));
+		assert_ok!(Directory::add_content(Origin::signed(1), 0, H256::zero()));
+
+		assert_ok!(Directory::censor_content(Origin::signed(10), 0, 0, true));
+		assert!(Directory::content(0).unwrap().censored);
+		assert_noop!(Directory::set_featured(Origin::signed(10), 0, 0, true), "Content is censored");
+	});
+}
+
+#[test]
+fn curators_feature_content() {
+	with_externalities(&mut new_test_ext(), || {
+		new_curated_channel(3);
+
+		assert_noop!(Directory::set_featured(Origin::signed(1), 0, 0, true), "Not a curator of the group");
+		assert_ok!(Directory::set_featured(Origin::signed(10), 0, 2, true));
+		assert_noop!(Directory::set_featured(Origin::signed(10), 0, 2, true), "Content is already featured");
+		assert_ok!(Directory::set_featured(Origin::signed(11), 0, 0, true));
+		assert_noop!(Directory::set_featured(Origin::signed(10), 0, 1, true), "Too many featured contents");
+		assert_eq!(Directory::featured_contents(), vec![2, 0]);
+
+		// Censored and removed content is unfeatured.
+		assert_ok!(Directory::censor_content(Origin::signed(10), 0, 2, true));
+		assert_eq!(Directory::featured_contents(), vec![0]);
+		assert_ok!(Directory::set_featured(Origin::signed(10), 0, 1, true));
+		assert_ok!(Directory::remove_content(Origin::signed(1), 0));
+		assert_eq!(Directory::featured_contents(), vec![1]);
+
+		assert_ok!(Directory::set_featured(Origin::signed(10), 0, 1, false));
+		assert_eq!(Directory::featured_contents(), Vec::<u64>::new());
+	});
+}
+
+#[test]
+fn curation_is_weighed_by_the_curators() {
+	with_externalities(&mut new_test_ext(), || {
+		new_curated_channel(0);
+		let weight = |call: Call<Test>| call.get_dispatch_info().weight;
+
+		assert!(weight(Call::create_curator_group(vec![10])) < weight(Call::create_curator_group(vec![10, 11])));
+		assert!(weight(Call::remove_content(0)) < weight(Call::set_featured(0, 0, true)));
+		let two_curators = weight(Call::set_featured(0, 0, true));
+		assert_ok!(Directory::set_curators(Origin::ROOT, 0, vec![10, 11, 12]));
+		assert!(two_curators < weight(Call::set_featured(0, 0, true)));
+	});
+}
@@ -0,0 +1,65 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API definition for the RPC of the contracts module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+use rstd::prelude::*;
+use client::decl_runtime_apis;
+use codec::{Encode, Decode, Codec};
+
+pub use contracts::{Gas, GetStorageError, GetStorageResult};
+
+/// The result of a contract call executed by the runtime API.
+#[derive(Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum ContractExecResult {
+	/// The call succeeded.
+	Success {
+		/// The output of the contract.
+		data: Vec<u8>,
+		/// The gas consumed by the call.
+		gas_consumed: Gas,
+	},
+	/// The call failed.
+	Error {
+		/// The reason the call failed.
+		reason: Vec<u8>,
+		/// The gas consumed by the call.
+		gas_consumed: Gas,
+	},
+}
+
+decl_runtime_apis! {
+	/// The api to execute contract calls and read contract storage for the contracts RPC.
+	pub trait ContractsApi<AccountId: Codec, Balance: Codec> {
+		/// Call the contract at `dest` as `origin`, without submitting a transaction.
+		///
+		/// The changes made by the call are discarded.
+		fn call(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: Gas,
+			input_data: Vec<u8>,
+		) -> ContractExecResult;
+
+		/// The value at `key` in the storage of the contract at `address`.
+		fn get_storage(address: AccountId, key: [u8; 32]) -> GetStorageResult;
+	}
+}
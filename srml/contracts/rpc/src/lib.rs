@@ -0,0 +1,232 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC API of the contracts module.
+//!
+//! Lets contract tooling read from contracts without submitting transactions: `contracts_call`
+//! executes a call on top of a block and returns the output of the contract and the gas consumed,
+//! discarding the changes it made, and `contracts_getStorage` reads raw values of the storage of a
+//! contract.
+
+#![warn(missing_docs)]
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use client::{Client, CallExecutor, backend::Backend, runtime_api::ConstructRuntimeApi};
+use codec::Codec;
+use contracts_rpc_runtime_api::{ContractExecResult, GetStorageError};
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use primitives::{Blake2Hasher, Bytes, H256};
+use serde::{Serialize, Deserialize};
+use sr_primitives::generic::BlockId;
+use sr_primitives::traits::{Block as BlockT, ProvideRuntimeApi};
+
+pub use contracts_rpc_runtime_api::ContractsApi as ContractsRuntimeApi;
+pub use self::gen_client::Client as ContractsClient;
+
+/// Base code for all contracts RPC errors.
+const BASE_ERROR: i64 = 8000;
+
+/// The maximum gas a call executed by `contracts_call` can consume.
+const GAS_LIMIT: u64 = 5 * 1_000_000;
+
+/// A call to a contract, executed by `contracts_call`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CallRequest<AccountId, Balance> {
+	/// The account making the call, which pays for the gas.
+	pub origin: AccountId,
+	/// The contract called.
+	pub dest: AccountId,
+	/// The value transferred to the contract.
+	pub value: Balance,
+	/// The maximum gas the call can consume, at most 5 000 000.
+	pub gas_limit: u64,
+	/// The input of the contract.
+	pub input_data: Bytes,
+}
+
+/// The result of a call executed by `contracts_call`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcContractExecResult {
+	/// The call succeeded.
+	#[serde(rename_all = "camelCase")]
+	Success {
+		/// The output of the contract.
+		data: Bytes,
+		/// The gas consumed by the call.
+		gas_consumed: u64,
+	},
+	/// The call failed.
+	#[serde(rename_all = "camelCase")]
+	Error {
+		/// The reason the call failed.
+		reason: String,
+		/// The gas consumed by the call.
+		gas_consumed: u64,
+	},
+}
+
+impl From<ContractExecResult> for RpcContractExecResult {
+	fn from(result: ContractExecResult) -> Self {
+		match result {
+			ContractExecResult::Success { data, gas_consumed } => RpcContractExecResult::Success {
+				data: data.into(),
+				gas_consumed,
+			},
+			ContractExecResult::Error { reason, gas_consumed } => RpcContractExecResult::Error {
+				reason: String::from_utf8_lossy(&reason).into_owned(),
+				gas_consumed,
+			},
+		}
+	}
+}
+
+/// Contracts RPC API.
+#[rpc]
+pub trait ContractsApi<BlockHash, AccountId, Balance> {
+	/// Execute `call_request` on top of the block `at`, the best block by default.
+	///
+	/// The changes made by the call are discarded.
+	#[rpc(name = "contracts_call")]
+	fn call(
+		&self,
+		call_request: CallRequest<AccountId, Balance>,
+		at: Option<BlockHash>,
+	) -> Result<RpcContractExecResult>;
+
+	/// The value at `key` in the storage of the contract at `address`, at the block `at`, the
+	/// best block by default.
+	#[rpc(name = "contracts_getStorage")]
+	fn get_storage(&self, address: AccountId, key: H256, at: Option<BlockHash>) -> Result<Option<Bytes>>;
+}
+
+/// Implementation of the contracts RPC API on top of a client.
+pub struct Contracts<B, E, Block: BlockT, RA> {
+	client: Arc<Client<B, E, Block, RA>>,
+}
+
+impl<B, E, Block: BlockT, RA> Contracts<B, E, Block, RA> {
+	/// Create new contracts RPC handler.
+	pub fn new(client: Arc<Client<B, E, Block, RA>>) -> Self {
+		Contracts { client }
+	}
+}
+
+impl<B, E, Block, RA, AccountId, Balance> ContractsApi<<Block as BlockT>::Hash, AccountId, Balance>
+	for Contracts<B, E, Block, RA>
+where
+	B: Backend<Block, Blake2Hasher> + Send + Sync + 'static,
+	E: CallExecutor<Block, Blake2Hasher> + Clone + Send + Sync + 'static,
+	Block: BlockT<Hash=H256>,
+	RA: ConstructRuntimeApi<Block, Client<B, E, Block, RA>> + Send + Sync + 'static,
+	<Client<B, E, Block, RA> as ProvideRuntimeApi>::Api: ContractsRuntimeApi<Block, AccountId, Balance>,
+	AccountId: Codec,
+	Balance: Codec,
+{
+	fn call(
+		&self,
+		call_request: CallRequest<AccountId, Balance>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<RpcContractExecResult> {
+		let at = BlockId::Hash(at.unwrap_or_else(|| self.client.info().chain.best_hash));
+		let CallRequest { origin, dest, value, gas_limit, input_data } = call_request;
+		if gas_limit > GAS_LIMIT {
+			return Err(gas_limit_error(gas_limit));
+		}
+
+		self.client.runtime_api().call(&at, origin, dest, value, gas_limit, input_data.to_vec())
+			.map(Into::into)
+			.map_err(internal_error)
+	}
+
+	fn get_storage(
+		&self,
+		address: AccountId,
+		key: H256,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<Bytes>> {
+		let at = BlockId::Hash(at.unwrap_or_else(|| self.client.info().chain.best_hash));
+
+		self.client.runtime_api().get_storage(&at, address, key.to_fixed_bytes())
+			.map_err(internal_error)?
+			.map(|value| value.map(Into::into))
+			.map_err(storage_error)
+	}
+}
+
+fn internal_error<T: Debug>(e: T) -> Error {
+	Error {
+		code: ErrorCode::ServerError(BASE_ERROR + 1),
+		message: format!("Failed to query the chain: {:?}", e),
+		data: None,
+	}
+}
+
+fn gas_limit_error(gas_limit: u64) -> Error {
+	Error {
+		code: ErrorCode::InvalidParams,
+		message: format!("Gas limit {} is over the maximum of {}", gas_limit, GAS_LIMIT),
+		data: None,
+	}
+}
+
+fn storage_error(e: GetStorageError) -> Error {
+	let (code, message) = match e {
+		GetStorageError::ContractDoesntExist => (BASE_ERROR + 2, "The contract doesn't exist"),
+		GetStorageError::IsTombstone => (BASE_ERROR + 3, "The contract was evicted and is a tombstone"),
+	};
+	Error {
+		code: ErrorCode::ServerError(code),
+		message: message.into(),
+		data: None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn call_request_should_deserialize() {
+		let request: CallRequest<String, u64> = serde_json::from_str(r#"{
+			"origin": "alice",
+			"dest": "contract",
+			"value": 10,
+			"gasLimit": 1000,
+			"inputData": "0x8c97db39"
+		}"#).unwrap();
+
+		assert_eq!(request.gas_limit, 1000);
+		assert_eq!(request.input_data, Bytes(vec![0x8c, 0x97, 0xdb, 0x39]));
+	}
+
+	#[test]
+	fn exec_result_should_serialize() {
+		let result: RpcContractExecResult = ContractExecResult::Success {
+			data: vec![1, 2],
+			gas_consumed: 5000,
+		}.into();
+
+		assert_eq!(
+			serde_json::to_string(&result).unwrap(),
+			r#"{"success":{"data":"0x0102","gasConsumed":5000}}"#
+		);
+	}
+}
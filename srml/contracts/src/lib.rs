@@ -116,6 +116,23 @@ use timestamp;
 pub type CodeHash<T> = <T as system::Trait>::Hash;
 pub type TrieId = Vec<u8>;
 
+/// The result of a call executed by `bare_call`: the output of the contract, or the reason the
+/// call failed.
+pub type ExecResult = rstd::result::Result<Vec<u8>, &'static str>;
+
+/// The reason the storage of a contract couldn't be read by `get_storage`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum GetStorageError {
+	/// The account is not a contract.
+	ContractDoesntExist,
+	/// The contract was evicted and only its tombstone remains.
+	IsTombstone,
+}
+
+/// The result of reading the storage of a contract with `get_storage`.
+pub type GetStorageResult = rstd::result::Result<Option<Vec<u8>>, GetStorageError>;
+
 /// A function that generates an `AccountId` for a contract upon instantiation.
 pub trait ContractAddressFor<CodeHash, AccountId> {
 	fn contract_address_for(code_hash: &CodeHash, data: &[u8], origin: &AccountId) -> AccountId;
@@ -628,6 +645,39 @@ decl_module! {
 }
 
 impl<T: Trait> Module<T> {
+	/// Make a call to `dest` as `origin`, like the `call` dispatchable, returning the output of the
+	/// contract and the gas consumed.
+	///
+	/// `origin` pays for the gas, and the changes are committed to the storage as for a
+	/// transaction. This is meant to be executed on a state that is thrown away afterwards, e.g.
+	/// by a runtime API, to read from contracts without submitting transactions.
+	pub fn bare_call(
+		origin: T::AccountId,
+		dest: T::AccountId,
+		value: BalanceOf<T>,
+		gas_limit: Gas,
+		input_data: Vec<u8>,
+	) -> (ExecResult, Gas) {
+		let mut output_data = Vec::new();
+		let mut gas_consumed = 0;
+		let result = Self::execute_wasm(origin, gas_limit, |ctx, gas_meter| {
+			let result = ctx.call(dest, value, gas_meter, &input_data, exec::EmptyOutputBuf::new())
+				.map(|receipt| output_data = receipt.output_data);
+			gas_consumed = gas_limit - gas_meter.gas_left();
+			result
+		});
+		(result.map(|()| output_data), gas_consumed)
+	}
+
+	/// The value at `key` in the storage of the contract at `address`.
+	pub fn get_storage(address: T::AccountId, key: [u8; 32]) -> GetStorageResult {
+		let contract_info = <ContractInfoOf<T>>::get(&address)
+			.ok_or(GetStorageError::ContractDoesntExist)?
+			.get_alive()
+			.ok_or(GetStorageError::IsTombstone)?;
+		Ok(AccountDb::<T>::get_storage(&DirectAccountDb, &address, Some(&contract_info.trie_id), &key))
+	}
+
 	fn execute_wasm(
 		origin: T::AccountId,
 		gas_limit: Gas,
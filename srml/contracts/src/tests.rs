@@ -22,7 +22,8 @@
 use crate::account_db::{AccountDb, DirectAccountDb, OverlayAccountDb};
 use crate::{
 	BalanceOf, ComputeDispatchFee, ContractAddressFor, ContractInfo, ContractInfoOf, GenesisConfig,
-	Module, RawAliveContractInfo, RawEvent, Trait, TrieId, TrieIdFromParentCounter, TrieIdGenerator,
+	GetStorageError, Module, RawAliveContractInfo, RawEvent, Trait, TrieId, TrieIdFromParentCounter,
+	TrieIdGenerator,
 };
 use assert_matches::assert_matches;
 use hex_literal::*;
@@ -98,8 +99,6 @@ parameter_types! {
 	pub const BlockHashCount: u64 = 250;
 	pub const MaximumBlockWeight: u32 = 1024;
 	pub const MaximumBlockLength: u32 = 2 * 1024;
-	pub const BalancesTransactionBaseFee: u64 = 0;
-	pub const BalancesTransactionByteFee: u64 = 0;
 	pub const AvailableBlockRatio: Perbill = Perbill::one();
 }
 impl system::Trait for Test {
@@ -116,6 +115,9 @@ impl system::Trait for Test {
 	type BlockHashCount = BlockHashCount;
 	type MaximumBlockWeight = MaximumBlockWeight;
 	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = ();
+	type Call = ();
+	type BaseCallFilter = ();
 	type MaximumBlockLength = MaximumBlockLength;
 }
 impl balances::Trait for Test {
@@ -123,15 +125,11 @@ impl balances::Trait for Test {
 	type OnFreeBalanceZero = Contract;
 	type OnNewAccount = ();
 	type Event = MetaEvent;
-	type TransactionPayment = ();
 	type DustRemoval = ();
 	type TransferPayment = ();
 	type ExistentialDeposit = ExistentialDeposit;
 	type TransferFee = TransferFee;
 	type CreationFee = CreationFee;
-	type TransactionBaseFee = BalancesTransactionBaseFee;
-	type TransactionByteFee = BalancesTransactionByteFee;
-	type WeightToFee = ();
 }
 parameter_types! {
 	pub const MinimumPeriod: u64 = 1;
@@ -1565,3 +1563,45 @@ fn storage_max_value_limit() {
 		}
 	);
 }
+
+#[test]
+fn bare_call_returns_output_and_gas_consumed() {
+	let (wasm, code_hash) = compile_module::<Test>(CODE_RETURN_FROM_START_FN).unwrap();
+
+	with_externalities(
+		&mut ExtBuilder::default().existential_deposit(50).build(),
+		|| {
+			Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_ok!(Contract::put_code(Origin::signed(ALICE), 100_000, wasm));
+			assert_ok!(Contract::create(Origin::signed(ALICE), 100, 100_000, code_hash.into(), vec![]));
+
+			let (result, gas_consumed) = Contract::bare_call(ALICE, BOB, 0, 100_000, vec![]);
+			assert_eq!(result, Ok(vec![1, 2, 3, 4]));
+			assert!(gas_consumed > 0 && gas_consumed < 100_000);
+
+			let (result, _) = Contract::bare_call(ALICE, BOB, 0, 0, vec![]);
+			assert_eq!(result, Err("not enough gas to pay base call fee"));
+		}
+	);
+}
+
+#[test]
+fn get_storage_reads_contract_storage() {
+	let (wasm, code_hash) = compile_module::<Test>(CODE_STORAGE_SIZE).unwrap();
+
+	with_externalities(
+		&mut ExtBuilder::default().existential_deposit(50).build(),
+		|| {
+			Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_ok!(Contract::put_code(Origin::signed(ALICE), 100_000, wasm));
+			assert_ok!(Contract::create(Origin::signed(ALICE), 30_000, 100_000, code_hash.into(), vec![]));
+			assert_ok!(Contract::call(Origin::signed(ALICE), BOB, 0, 100_000, Encode::encode(&4u32)));
+
+			let mut key = [0u8; 32];
+			key[0] = 1;
+			assert_eq!(Contract::get_storage(BOB, key), Ok(Some(vec![1, 0, 0, 0])));
+			assert_eq!(Contract::get_storage(BOB, [0; 32]), Ok(None));
+			assert_eq!(Contract::get_storage(CHARLIE, key), Err(GetStorageError::ContractDoesntExist));
+		}
+	);
+}
@@ -0,0 +1,364 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # DEX Module
+//!
+//! The DEX module exchanges assets through constant product liquidity pools.
+//!
+//! - [`dex::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! A pool holds reserves of two assets of `Assets`, in the account of the module. Liquidity
+//! providers deposit both assets in proportion of the reserves and receive liquidity tokens,
+//! which are tracked by the module and give back their share of the reserves when removed. The
+//! first provider sets the price and receives the square root of the product of its deposits,
+//! less `MINIMUM_LIQUIDITY` tokens which are burnt: the pool can't be drained, and the price of a
+//! token can't be inflated to make the deposits of the other providers round down to nothing.
+//!
+//! Swaps keep the product of the reserves constant, after `SwapFee` is taken from the amount
+//! sent, which stays in the pool for the liquidity providers.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `create_pool` - Create a pool of two assets.
+//! - `add_liquidity` - Deposit assets in a pool for liquidity tokens.
+//! - `remove_liquidity` - Withdraw assets from a pool for liquidity tokens.
+//! - `swap` - Exchange an asset for the other asset of a pool.
+//! - `transfer_liquidity` - Transfer liquidity tokens.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod tests;
+
+use rstd::convert::{TryFrom, TryInto};
+use codec::{Encode, Decode};
+use primitives::U256;
+use sr_primitives::{Permill, ModuleId};
+use sr_primitives::traits::{
+	AccountIdConversion, SimpleArithmetic, StaticLookup, CheckedAdd, CheckedMul, IntegerSquareRoot,
+	One, Zero,
+};
+use srml_support::{StorageValue, StorageMap, Parameter, decl_module, decl_storage, decl_event, ensure};
+use srml_support::dispatch::Result;
+use srml_support::traits::{Get, MultiAsset};
+use system::ensure_signed;
+
+const MODULE_ID: ModuleId = ModuleId(*b"py/dexpl");
+
+/// The amount of the liquidity tokens of the first deposit in a pool which are burnt.
+pub const MINIMUM_LIQUIDITY: u32 = 1000;
+
+type AssetIdOf<T> = <<T as Trait>::Assets as MultiAsset<<T as system::Trait>::AccountId>>::AssetId;
+type BalanceOf<T> = <<T as Trait>::Assets as MultiAsset<<T as system::Trait>::AccountId>>::Balance;
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The assets exchanged in the pools.
+	type Assets: MultiAsset<Self::AccountId>;
+
+	/// The identifier of a pool.
+	type PoolId: Parameter + SimpleArithmetic + Default + Copy;
+
+	/// The fraction of the amount sent to a swap kept by the pool.
+	type SwapFee: Get<Permill>;
+}
+
+/// A liquidity pool.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Pool<AssetId, Balance> {
+	/// The first asset of the pool.
+	pub asset_a: AssetId,
+	/// The second asset of the pool.
+	pub asset_b: AssetId,
+	/// The reserve of the first asset.
+	pub reserve_a: Balance,
+	/// The reserve of the second asset.
+	pub reserve_b: Balance,
+	/// The total amount of liquidity tokens of the pool.
+	pub total_liquidity: Balance,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Dex {
+		/// The identifier of the next pool.
+		pub NextPoolId get(next_pool_id): T::PoolId;
+		/// The pools.
+		pub Pools get(pool): map T::PoolId => Option<Pool<AssetIdOf<T>, BalanceOf<T>>>;
+		/// The pool of each pair of assets, in both orders.
+		pub PoolIds get(pool_id): map (AssetIdOf<T>, AssetIdOf<T>) => Option<T::PoolId>;
+		/// The liquidity tokens of each pool of each account.
+		pub Liquidity get(liquidity): map (T::PoolId, T::AccountId) => BalanceOf<T>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as Trait>::PoolId,
+		AssetId = AssetIdOf<T>,
+		Balance = BalanceOf<T>,
+	{
+		/// A pool of the two assets was created by the account.
+		PoolCreated(PoolId, AssetId, AssetId, AccountId),
+		/// The account deposited the amounts of the assets in a pool for the liquidity tokens.
+		LiquidityAdded(PoolId, AccountId, Balance, Balance, Balance),
+		/// The account withdrew the amounts of the assets from a pool for the liquidity tokens.
+		LiquidityRemoved(PoolId, AccountId, Balance, Balance, Balance),
+		/// The account swapped the amount of the first asset for the amount of the second one.
+		Swapped(PoolId, AccountId, AssetId, Balance, AssetId, Balance),
+		/// Liquidity tokens of a pool were transferred from an account to another.
+		LiquidityTransferred(PoolId, AccountId, AccountId, Balance),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		/// The fraction of the amount sent to a swap kept by the pool.
+		const SwapFee: Permill = T::SwapFee::get();
+
+		fn deposit_event() = default;
+
+		/// Create an empty pool of `asset_a` and `asset_b`.
+		fn create_pool(origin, asset_a: AssetIdOf<T>, asset_b: AssetIdOf<T>) -> Result {
+			let who = ensure_signed(origin)?;
+			ensure!(asset_a != asset_b, "Assets must be different");
+			ensure!(!<PoolIds<T>>::exists((asset_a, asset_b)), "Pool already exists");
+
+			let id = Self::next_pool_id();
+			<NextPoolId<T>>::mutate(|id| *id += One::one());
+			<Pools<T>>::insert(id, Pool {
+				asset_a,
+				asset_b,
+				reserve_a: Zero::zero(),
+				reserve_b: Zero::zero(),
+				total_liquidity: Zero::zero(),
+			});
+			<PoolIds<T>>::insert((asset_a, asset_b), id);
+			<PoolIds<T>>::insert((asset_b, asset_a), id);
+			Self::deposit_event(RawEvent::PoolCreated(id, asset_a, asset_b, who));
+
+			Ok(())
+		}
+
+		/// Deposit `amount_a` of the first asset of the pool `pool_id` and the amount of the
+		/// second one at the price of the pool, which can't exceed `max_amount_b`.
+		///
+		/// The first deposit in a pool sets its price, with `max_amount_b` of the second asset.
+		fn add_liquidity(
+			origin,
+			#[compact] pool_id: T::PoolId,
+			#[compact] amount_a: BalanceOf<T>,
+			#[compact] max_amount_b: BalanceOf<T>
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let mut pool = Self::pool(pool_id).ok_or("Unknown pool")?;
+
+			let (amount_b, liquidity) = if pool.total_liquidity.is_zero() {
+				let product = amount_a.checked_mul(&max_amount_b).ok_or("Overflow")?;
+				let liquidity = product.integer_sqrt();
+				let minimum_liquidity = BalanceOf::<T>::from(MINIMUM_LIQUIDITY);
+				ensure!(liquidity > minimum_liquidity, "Insufficient liquidity minted");
+				// The burnt tokens stay in the total liquidity, without belonging to anyone.
+				pool.total_liquidity = minimum_liquidity;
+				(max_amount_b, liquidity - minimum_liquidity)
+			} else {
+				// Round the deposit of the second asset up, in favor of the pool.
+				let amount_b = Self::mul_div_up(amount_a, pool.reserve_b, pool.reserve_a)?;
+				(amount_b, Self::mul_div(amount_a, pool.total_liquidity, pool.reserve_a)?)
+			};
+			ensure!(amount_b <= max_amount_b, "Maximum amount exceeded");
+			ensure!(!liquidity.is_zero(), "Insufficient liquidity minted");
+			ensure!(T::Assets::free_balance(pool.asset_a, &who) >= amount_a, "Insufficient balance");
+			ensure!(T::Assets::free_balance(pool.asset_b, &who) >= amount_b, "Insufficient balance");
+
+			pool.reserve_a = pool.reserve_a.checked_add(&amount_a).ok_or("Overflow")?;
+			pool.reserve_b = pool.reserve_b.checked_add(&amount_b).ok_or("Overflow")?;
+			pool.total_liquidity = pool.total_liquidity.checked_add(&liquidity).ok_or("Overflow")?;
+
+			let account = Self::account_id();
+			T::Assets::transfer(pool.asset_a, &who, &account, amount_a)?;
+			T::Assets::transfer(pool.asset_b, &who, &account, amount_b)?;
+			<Pools<T>>::insert(pool_id, pool);
+			<Liquidity<T>>::mutate((pool_id, who.clone()), |balance| *balance += liquidity);
+			Self::deposit_event(RawEvent::LiquidityAdded(pool_id, who, amount_a, amount_b, liquidity));
+
+			Ok(())
+		}
+
+		/// Withdraw the share of the reserves of the pool `pool_id` of `liquidity` tokens of the
+		/// sender, of at least `min_amount_a` and `min_amount_b` of the assets.
+		fn remove_liquidity(
+			origin,
+			#[compact] pool_id: T::PoolId,
+			#[compact] liquidity: BalanceOf<T>,
+			#[compact] min_amount_a: BalanceOf<T>,
+			#[compact] min_amount_b: BalanceOf<T>
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let mut pool = Self::pool(pool_id).ok_or("Unknown pool")?;
+			let balance = Self::liquidity((pool_id, who.clone()));
+			ensure!(!liquidity.is_zero() && liquidity <= balance, "Insufficient liquidity");
+
+			let amount_a = Self::mul_div(liquidity, pool.reserve_a, pool.total_liquidity)?;
+			let amount_b = Self::mul_div(liquidity, pool.reserve_b, pool.total_liquidity)?;
+			ensure!(amount_a >= min_amount_a && amount_b >= min_amount_b, "Minimum amount not reached");
+
+			pool.reserve_a -= amount_a;
+			pool.reserve_b -= amount_b;
+			pool.total_liquidity -= liquidity;
+
+			let account = Self::account_id();
+			T::Assets::transfer(pool.asset_a, &account, &who, amount_a)?;
+			T::Assets::transfer(pool.asset_b, &account, &who, amount_b)?;
+			<Pools<T>>::insert(pool_id, pool);
+			<Liquidity<T>>::insert((pool_id, who.clone()), balance - liquidity);
+			Self::deposit_event(RawEvent::LiquidityRemoved(pool_id, who, amount_a, amount_b, liquidity));
+
+			Ok(())
+		}
+
+		/// Exchange `amount_in` of `asset_in` for at least `min_amount_out` of the other asset of
+		/// the pool `pool_id`.
+		fn swap(
+			origin,
+			#[compact] pool_id: T::PoolId,
+			asset_in: AssetIdOf<T>,
+			#[compact] amount_in: BalanceOf<T>,
+			#[compact] min_amount_out: BalanceOf<T>
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let mut pool = Self::pool(pool_id).ok_or("Unknown pool")?;
+
+			let amount_out = Self::amount_out(&pool, asset_in, amount_in)?;
+			ensure!(!amount_out.is_zero() && amount_out >= min_amount_out, "Minimum amount not reached");
+			ensure!(T::Assets::free_balance(asset_in, &who) >= amount_in, "Insufficient balance");
+
+			let asset_out = if asset_in == pool.asset_a {
+				pool.reserve_a = pool.reserve_a.checked_add(&amount_in).ok_or("Overflow")?;
+				pool.reserve_b -= amount_out;
+				pool.asset_b
+			} else {
+				pool.reserve_b = pool.reserve_b.checked_add(&amount_in).ok_or("Overflow")?;
+				pool.reserve_a -= amount_out;
+				pool.asset_a
+			};
+
+			let account = Self::account_id();
+			T::Assets::transfer(asset_in, &who, &account, amount_in)?;
+			T::Assets::transfer(asset_out, &account, &who, amount_out)?;
+			<Pools<T>>::insert(pool_id, pool);
+			Self::deposit_event(RawEvent::Swapped(pool_id, who, asset_in, amount_in, asset_out, amount_out));
+
+			Ok(())
+		}
+
+		/// Transfer `amount` liquidity tokens of the pool `pool_id` of the sender to `dest`.
+		fn transfer_liquidity(
+			origin,
+			#[compact] pool_id: T::PoolId,
+			dest: <T::Lookup as StaticLookup>::Source,
+			#[compact] amount: BalanceOf<T>
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+			ensure!(<Pools<T>>::exists(pool_id), "Unknown pool");
+
+			let balance = Self::liquidity((pool_id, who.clone()));
+			ensure!(amount <= balance, "Insufficient liquidity");
+			if who != dest {
+				let dest_balance = Self::liquidity((pool_id, dest.clone()));
+				let dest_balance = dest_balance.checked_add(&amount).ok_or("Overflow")?;
+				<Liquidity<T>>::insert((pool_id, who.clone()), balance - amount);
+				<Liquidity<T>>::insert((pool_id, dest.clone()), dest_balance);
+			}
+			Self::deposit_event(RawEvent::LiquidityTransferred(pool_id, who, dest, amount));
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// The account holding the reserves of all the pools, derived from the identifier of the
+	/// module.
+	pub fn account_id() -> T::AccountId {
+		MODULE_ID.into_account()
+	}
+
+	/// The amount of the other asset of the pool `pool_id` a swap of `amount_in` of `asset_in`
+	/// would give.
+	pub fn quote(pool_id: T::PoolId, asset_in: AssetIdOf<T>, amount_in: BalanceOf<T>) -> Option<BalanceOf<T>> {
+		Self::pool(pool_id).and_then(|pool| Self::amount_out(&pool, asset_in, amount_in).ok())
+	}
+
+	fn amount_out(
+		pool: &Pool<AssetIdOf<T>, BalanceOf<T>>,
+		asset_in: AssetIdOf<T>,
+		amount_in: BalanceOf<T>,
+	) -> rstd::result::Result<BalanceOf<T>, &'static str> {
+		let (reserve_in, reserve_out) = if asset_in == pool.asset_a {
+			(pool.reserve_a, pool.reserve_b)
+		} else if asset_in == pool.asset_b {
+			(pool.reserve_b, pool.reserve_a)
+		} else {
+			return Err("Asset is not in the pool");
+		};
+		ensure!(!reserve_in.is_zero() && !reserve_out.is_zero(), "Pool has no liquidity");
+
+		let amount_in = amount_in - T::SwapFee::get() * amount_in;
+		let reserve_in = reserve_in.checked_add(&amount_in).ok_or("Overflow")?;
+		Self::mul_div(amount_in, reserve_out, reserve_in)
+	}
+
+	/// `a * b / c`, rounded down. The product doesn't overflow.
+	fn mul_div(
+		a: BalanceOf<T>,
+		b: BalanceOf<T>,
+		c: BalanceOf<T>,
+	) -> rstd::result::Result<BalanceOf<T>, &'static str> {
+		ensure!(!c.is_zero(), "Pool has no liquidity");
+		Self::from_u256(Self::to_u256(a)? * Self::to_u256(b)? / Self::to_u256(c)?)
+	}
+
+	/// `a * b / c`, rounded up. The product doesn't overflow.
+	fn mul_div_up(
+		a: BalanceOf<T>,
+		b: BalanceOf<T>,
+		c: BalanceOf<T>,
+	) -> rstd::result::Result<BalanceOf<T>, &'static str> {
+		ensure!(!c.is_zero(), "Pool has no liquidity");
+		let (product, c) = (Self::to_u256(a)? * Self::to_u256(b)?, Self::to_u256(c)?);
+		let quotient = product / c;
+		Self::from_u256(if (product % c).is_zero() { quotient } else { quotient + U256::one() })
+	}
+
+	fn to_u256(balance: BalanceOf<T>) -> rstd::result::Result<U256, &'static str> {
+		TryInto::<u128>::try_into(balance).map(U256::from).map_err(|_| "Overflow")
+	}
+
+	fn from_u256(value: U256) -> rstd::result::Result<BalanceOf<T>, &'static str> {
+		ensure!(value <= U256::from(u128::max_value()), "Overflow");
+		BalanceOf::<T>::try_from(value.as_u128()).map_err(|_| "Overflow")
+	}
+}
@@ -0,0 +1,211 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use super::*;
+
+use primitives::Blake2Hasher;
+use runtime_io::with_externalities;
+use srml_support::{assert_noop, assert_ok, impl_test_runtime, parameter_types};
+
+mod dex {
+	// Re-export needed for `impl_outer_event!`.
+	pub use super::super::*;
+}
+
+impl_test_runtime! {
+	pub struct Test;
+	events {
+		dex<T>,
+	}
+}
+impl assets::Trait for Test {
+	type Event = ();
+	type Balance = u64;
+	type AssetId = u32;
+}
+parameter_types! {
+	pub const SwapFee: Permill = Permill::from_percent(1);
+}
+impl Trait for Test {
+	type Event = TestEvent;
+	type Assets = Assets;
+	type PoolId = u32;
+	type SwapFee = SwapFee;
+}
+type System = system::Module<Test>;
+type Assets = assets::Module<Test>;
+type Dex = Module<Test>;
+
+fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+	let mut ext: runtime_io::TestExternalities<Blake2Hasher> =
+		system::GenesisConfig::default().build_storage::<Test>().unwrap().0.into();
+	with_externalities(&mut ext, || {
+		for who in &[1, 2] {
+			for asset in 0..3 {
+				assert_ok!(Assets::mint(asset, who, 10_000));
+			}
+		}
+	});
+	ext
+}
+
+/// A pool of the assets 0 and 1 with 1000 and 4000 of them from the account 1.
+fn last_event() -> TestEvent {
+	System::events().pop().expect("an event was deposited").event
+}
+
+fn new_pool() {
+	assert_ok!(Dex::create_pool(Origin::signed(1), 0, 1));
+	assert_ok!(Dex::add_liquidity(Origin::signed(1), 0, 1000, 4000));
+}
+
+#[test]
+fn pools_are_created_once_per_pair() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_noop!(Dex::create_pool(Origin::signed(1), 0, 0), "Assets must be different");
+		assert_ok!(Dex::create_pool(Origin::signed(1), 0, 1));
+		assert_eq!(last_event(), TestEvent::dex(RawEvent::PoolCreated(0, 0, 1, 1)));
+		assert_noop!(Dex::create_pool(Origin::signed(2), 1, 0), "Pool already exists");
+		assert_ok!(Dex::create_pool(Origin::signed(2), 1, 2));
+		assert_eq!(last_event(), TestEvent::dex(RawEvent::PoolCreated(1, 1, 2, 2)));
+
+		assert_eq!(Dex::pool_id((1, 0)), Some(0));
+		assert_eq!(Dex::pool_id((2, 1)), Some(1));
+		assert_eq!(Dex::next_pool_id(), 2);
+	});
+}
+
+#[test]
+fn liquidity_is_added_at_the_price_of_the_pool() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_noop!(Dex::add_liquidity(Origin::signed(1), 0, 1000, 4000), "Unknown pool");
+		new_pool();
+		// The first deposit mints 2000 tokens, of which `MINIMUM_LIQUIDITY` are burnt.
+		assert_eq!(Dex::liquidity((0, 1)), 1000);
+		assert_eq!(Dex::pool(0).unwrap().total_liquidity, 2000);
+		assert_eq!(Assets::free_balance(1, &Dex::account_id()), 4000);
+
+		assert_noop!(Dex::add_liquidity(Origin::signed(2), 0, 101, 403), "Maximum amount exceeded");
+		assert_noop!(Dex::add_liquidity(Origin::signed(2), 0, 3000, 20_000), "Insufficient balance");
+		assert_ok!(Dex::add_liquidity(Origin::signed(2), 0, 101, 405));
+		assert_eq!(last_event(), TestEvent::dex(RawEvent::LiquidityAdded(0, 2, 101, 404, 202)));
+
+		assert_eq!(Assets::free_balance(0, &2), 10_000 - 101);
+		assert_eq!(Assets::free_balance(1, &2), 10_000 - 404);
+		assert_eq!(Dex::liquidity((0, 2)), 202);
+		assert_eq!(Dex::pool(0), Some(Pool {
+			asset_a: 0,
+			asset_b: 1,
+			reserve_a: 1101,
+			reserve_b: 4404,
+			total_liquidity: 2202,
+		}));
+	});
+}
+
+#[test]
+fn swaps_keep_the_product_of_the_reserves() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Dex::create_pool(Origin::signed(1), 0, 1));
+		assert_noop!(Dex::swap(Origin::signed(2), 0, 0, 100, 0), "Pool has no liquidity");
+		assert_ok!(Dex::add_liquidity(Origin::signed(1), 0, 1000, 4000));
+
+		// 99 are swapped after the fee: 99 * 4000 / (1000 + 99).
+		assert_eq!(Dex::quote(0, 0, 100), Some(360));
+		assert_noop!(Dex::swap(Origin::signed(2), 0, 0, 100, 361), "Minimum amount not reached");
+		assert_noop!(Dex::swap(Origin::signed(2), 0, 2, 100, 0), "Asset is not in the pool");
+		assert_ok!(Dex::swap(Origin::signed(2), 0, 0, 100, 360));
+		assert_eq!(last_event(), TestEvent::dex(RawEvent::Swapped(0, 2, 0, 100, 1, 360)));
+
+		assert_eq!(Assets::free_balance(0, &2), 10_000 - 100);
+		assert_eq!(Assets::free_balance(1, &2), 10_000 + 360);
+		let pool = Dex::pool(0).unwrap();
+		assert_eq!((pool.reserve_a, pool.reserve_b), (1100, 3640));
+
+		// Swaps work both ways: 396 * 1100 / (3640 + 396).
+		assert_ok!(Dex::swap(Origin::signed(2), 0, 1, 400, 0));
+		assert_eq!(last_event(), TestEvent::dex(RawEvent::Swapped(0, 2, 1, 400, 0, 107)));
+		assert_eq!(Assets::free_balance(0, &2), 10_000 - 100 + 107);
+	});
+}
+
+#[test]
+fn liquidity_is_removed_for_a_share_of_the_reserves() {
+	with_externalities(&mut new_test_ext(), || {
+		new_pool();
+		assert_ok!(Dex::swap(Origin::signed(2), 0, 0, 100, 0));
+
+		assert_noop!(Dex::remove_liquidity(Origin::signed(2), 0, 1, 0, 0), "Insufficient liquidity");
+		assert_noop!(Dex::remove_liquidity(Origin::signed(1), 0, 500, 276, 0), "Minimum amount not reached");
+
+		// A quarter of the reserves of 1100 and 3640.
+		assert_ok!(Dex::remove_liquidity(Origin::signed(1), 0, 500, 275, 910));
+		assert_eq!(last_event(), TestEvent::dex(RawEvent::LiquidityRemoved(0, 1, 275, 910, 500)));
+		assert_eq!(Assets::free_balance(0, &1), 10_000 - 1000 + 275);
+		assert_eq!(Assets::free_balance(1, &1), 10_000 - 4000 + 910);
+		assert_eq!(Dex::liquidity((0, 1)), 500);
+		let pool = Dex::pool(0).unwrap();
+		assert_eq!((pool.reserve_a, pool.reserve_b, pool.total_liquidity), (825, 2730, 1500));
+	});
+}
+
+#[test]
+fn liquidity_tokens_are_transferable() {
+	with_externalities(&mut new_test_ext(), || {
+		new_pool();
+		assert_noop!(Dex::transfer_liquidity(Origin::signed(1), 0, 2, 1001), "Insufficient liquidity");
+		assert_ok!(Dex::transfer_liquidity(Origin::signed(1), 0, 2, 500));
+		assert_eq!(last_event(), TestEvent::dex(RawEvent::LiquidityTransferred(0, 1, 2, 500)));
+		assert_eq!(Dex::liquidity((0, 1)), 500);
+		assert_eq!(Dex::liquidity((0, 2)), 500);
+
+		assert_ok!(Dex::remove_liquidity(Origin::signed(2), 0, 500, 250, 1000));
+		assert_eq!(Assets::free_balance(0, &2), 10_000 + 250);
+	});
+}
+
+#[test]
+fn first_deposits_must_exceed_the_minimum_liquidity() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Dex::create_pool(Origin::signed(1), 0, 1));
+		assert_noop!(Dex::add_liquidity(Origin::signed(1), 0, 100, 10_000), "Insufficient liquidity minted");
+		assert_ok!(Dex::add_liquidity(Origin::signed(1), 0, 101, 10_000));
+		assert_eq!(Dex::liquidity((0, 1)), 4);
+
+		// The burnt tokens keep their share of the reserves.
+		assert_ok!(Dex::remove_liquidity(Origin::signed(1), 0, 4, 0, 0));
+		let pool = Dex::pool(0).unwrap();
+		assert_eq!((pool.reserve_a, pool.reserve_b, pool.total_liquidity), (101, 9961, 1000));
+	});
+}
+
+#[test]
+fn large_amounts_do_not_overflow() {
+	with_externalities(&mut new_test_ext(), || {
+		// The total supply of the assets is 20_000 already.
+		assert_ok!(Assets::mint(0, &3, u64::max_value() - 20_000));
+		assert_ok!(Assets::mint(1, &3, u64::max_value() - 20_000));
+		assert_ok!(Dex::create_pool(Origin::signed(3), 0, 1));
+		assert_ok!(Dex::add_liquidity(Origin::signed(3), 0, 1 << 32, 1 << 30));
+
+		// Deposits at the price of the pool multiply reserves of 2^32 and 2^30 by 2^31.
+		assert_ok!(Dex::add_liquidity(Origin::signed(3), 0, 1 << 63, 1 << 61));
+		let pool = Dex::pool(0).unwrap();
+		assert_eq!((pool.reserve_a, pool.reserve_b), ((1 << 32) + (1 << 63), (1 << 30) + (1 << 61)));
+	});
+}
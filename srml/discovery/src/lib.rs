@@ -0,0 +1,245 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Discovery Module
+//!
+//! The Discovery module lets providers publish where clients can reach them.
+//!
+//! - [`discovery::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! The role accounts of `Providers`, e.g. the active storage providers, publish their endpoint:
+//! an IPNS identity resolving to their service information, or the hash of a URL. Endpoints
+//! expire after a lifetime, `DefaultLifetime` unless given, so that providers keep confirming
+//! they are reachable. Root sets the default lifetime, within `MinLifetime` and `MaxLifetime`.
+//!
+//! Anyone can remove the endpoints that expired, or whose account isn't a provider anymore.
+//! The events carry the endpoints, so that indexers can follow them without reading the storage.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `set_endpoint` - Publish the endpoint of the sender.
+//! - `unset_endpoint` - Remove the endpoint of the sender.
+//! - `remove_stale_endpoint` - Remove an expired endpoint.
+//! - `set_default_lifetime` - Set the default lifetime of endpoints.
+//!
+//! ### Public Functions
+//!
+//! - `endpoint_of` - The endpoint of an account, unless expired.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod tests;
+
+use rstd::prelude::*;
+use rstd::marker::PhantomData;
+use codec::{Encode, Decode};
+use srml_support::{StorageValue, StorageMap, decl_module, decl_storage, decl_event, ensure};
+use srml_support::dispatch::Result;
+use srml_support::traits::{Contains, Get};
+use sr_primitives::traits::SaturatedConversion;
+use sr_primitives::weights::{Weight, WeighData, ClassifyDispatch, DispatchClass, SimpleDispatchInfo};
+use system::{ensure_signed, ensure_root};
+
+/// The weight of the calls besides the bytes of the endpoint they write.
+const BASE_WEIGHT: Weight = 100_000;
+/// The weight of writing a byte of an endpoint.
+const BYTE_WEIGHT: Weight = 100;
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The role accounts that can publish an endpoint.
+	type Providers: Contains<Self::AccountId>;
+
+	/// The minimum lifetime of an endpoint.
+	type MinLifetime: Get<Self::BlockNumber>;
+
+	/// The maximum lifetime of an endpoint.
+	type MaxLifetime: Get<Self::BlockNumber>;
+
+	/// The maximum length of an IPNS identity.
+	type MaxIpnsIdLength: Get<u32>;
+}
+
+/// Where a provider can be reached.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum Endpoint<Hash> {
+	/// An IPNS identity, resolving to the service information of the provider.
+	Ipns(Vec<u8>),
+	/// The hash of a URL, published off chain.
+	UrlHash(Hash),
+}
+
+/// A published endpoint.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct EndpointInfo<BlockNumber, Hash> {
+	/// The endpoint.
+	pub endpoint: Endpoint<Hash>,
+	/// The block from which the endpoint is expired.
+	pub expires_at: BlockNumber,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Discovery {
+		/// The endpoints of the providers, possibly expired.
+		pub Endpoints get(endpoint_info): map T::AccountId => Option<EndpointInfo<T::BlockNumber, T::Hash>>;
+		/// The lifetime of the endpoints published without one.
+		pub DefaultLifetime get(default_lifetime) config(): T::BlockNumber;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as system::Trait>::BlockNumber,
+		<T as system::Trait>::Hash,
+	{
+		/// The account published the endpoint, expiring at the block.
+		EndpointSet(AccountId, Endpoint<Hash>, BlockNumber),
+		/// The account removed its endpoint.
+		EndpointUnset(AccountId),
+		/// The stale endpoint of the account was removed.
+		EndpointRemoved(AccountId),
+		/// The default lifetime of endpoints was set.
+		DefaultLifetimeSet(BlockNumber),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		/// The minimum lifetime of an endpoint.
+		const MinLifetime: T::BlockNumber = T::MinLifetime::get();
+
+		/// The maximum lifetime of an endpoint.
+		const MaxLifetime: T::BlockNumber = T::MaxLifetime::get();
+
+		/// The maximum length of an IPNS identity.
+		const MaxIpnsIdLength: u32 = T::MaxIpnsIdLength::get();
+
+		fn deposit_event() = default;
+
+		/// Publish `endpoint` as the endpoint of the sender for `lifetime` blocks, or
+		/// `DefaultLifetime` if `None`, replacing the previous one.
+		///
+		/// The sender must be a provider.
+		#[weight = EndpointWeight::<T>::default()]
+		fn set_endpoint(origin, endpoint: Endpoint<T::Hash>, lifetime: Option<T::BlockNumber>) -> Result {
+			let who = ensure_signed(origin)?;
+			ensure!(T::Providers::contains(&who), "Not a provider");
+			if let Endpoint::Ipns(ref id) = endpoint {
+				ensure!(id.len() <= T::MaxIpnsIdLength::get() as usize, "IPNS identity is too long");
+			}
+			let lifetime = lifetime.unwrap_or_else(Self::default_lifetime);
+			Self::ensure_valid_lifetime(lifetime)?;
+
+			let expires_at = <system::Module<T>>::block_number() + lifetime;
+			<Endpoints<T>>::insert(&who, EndpointInfo { endpoint: endpoint.clone(), expires_at });
+			Self::deposit_event(RawEvent::EndpointSet(who, endpoint, expires_at));
+
+			Ok(())
+		}
+
+		/// Remove the endpoint of the sender.
+		#[weight = SimpleDispatchInfo::FixedNormal(BASE_WEIGHT)]
+		fn unset_endpoint(origin) -> Result {
+			let who = ensure_signed(origin)?;
+			ensure!(<Endpoints<T>>::exists(&who), "No endpoint");
+
+			<Endpoints<T>>::remove(&who);
+			Self::deposit_event(RawEvent::EndpointUnset(who));
+
+			Ok(())
+		}
+
+		/// Remove the endpoint of `who`, which must be expired or not belong to a provider
+		/// anymore.
+		#[weight = SimpleDispatchInfo::FixedNormal(BASE_WEIGHT)]
+		fn remove_stale_endpoint(origin, who: T::AccountId) -> Result {
+			ensure_signed(origin)?;
+			let info = Self::endpoint_info(&who).ok_or("No endpoint")?;
+			ensure!(
+				info.expires_at <= <system::Module<T>>::block_number() || !T::Providers::contains(&who),
+				"Endpoint is not stale"
+			);
+
+			<Endpoints<T>>::remove(&who);
+			Self::deposit_event(RawEvent::EndpointRemoved(who));
+
+			Ok(())
+		}
+
+		/// Set the lifetime of the endpoints published without one to `lifetime`.
+		///
+		/// The origin must be root.
+		#[weight = SimpleDispatchInfo::FixedOperational(BASE_WEIGHT)]
+		fn set_default_lifetime(origin, lifetime: T::BlockNumber) -> Result {
+			ensure_root(origin)?;
+			Self::ensure_valid_lifetime(lifetime)?;
+
+			<DefaultLifetime<T>>::put(lifetime);
+			Self::deposit_event(RawEvent::DefaultLifetimeSet(lifetime));
+
+			Ok(())
+		}
+	}
+}
+
+/// Weight of `set_endpoint`: a step per byte of the endpoint.
+pub struct EndpointWeight<T>(PhantomData<T>);
+
+impl<T> Default for EndpointWeight<T> {
+	fn default() -> Self {
+		EndpointWeight(PhantomData)
+	}
+}
+
+impl<T: Trait> WeighData<(&Endpoint<T::Hash>, &Option<T::BlockNumber>)> for EndpointWeight<T> {
+	fn weigh_data(&self, (endpoint, _): (&Endpoint<T::Hash>, &Option<T::BlockNumber>)) -> Weight {
+		let bytes: Weight = endpoint.using_encoded(|encoded| encoded.len()).saturated_into();
+		BASE_WEIGHT.saturating_add(BYTE_WEIGHT.saturating_mul(bytes))
+	}
+}
+
+impl<T, A> ClassifyDispatch<A> for EndpointWeight<T> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// The endpoint of `who`, unless it expired or `who` isn't a provider anymore.
+	pub fn endpoint_of(who: &T::AccountId) -> Option<Endpoint<T::Hash>> {
+		Self::endpoint_info(who)
+			.filter(|info| info.expires_at > <system::Module<T>>::block_number())
+			.filter(|_| T::Providers::contains(who))
+			.map(|info| info.endpoint)
+	}
+
+	fn ensure_valid_lifetime(lifetime: T::BlockNumber) -> Result {
+		ensure!(lifetime >= T::MinLifetime::get(), "Lifetime is too short");
+		ensure!(lifetime <= T::MaxLifetime::get(), "Lifetime is too long");
+		Ok(())
+	}
+}
@@ -0,0 +1,153 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use super::*;
+
+use std::cell::RefCell;
+use primitives::{Blake2Hasher, H256};
+use runtime_io::with_externalities;
+use sr_primitives::weights::GetDispatchInfo;
+use srml_support::{assert_noop, assert_ok, impl_test_runtime, parameter_types};
+
+mod discovery {
+	// Re-export needed for `impl_outer_event!`.
+	pub use super::super::*;
+}
+
+impl_test_runtime! {
+	pub struct Test;
+	events {
+		discovery<T>,
+	}
+}
+
+thread_local! {
+	static PROVIDERS: RefCell<Vec<u64>> = RefCell::new(vec![1, 2]);
+}
+
+pub struct Providers;
+impl Contains<u64> for Providers {
+	fn contains(who: &u64) -> bool {
+		PROVIDERS.with(|providers| providers.borrow().contains(who))
+	}
+}
+
+parameter_types! {
+	pub const MinLifetime: u64 = 5;
+	pub const MaxLifetime: u64 = 100;
+	pub const MaxIpnsIdLength: u32 = 8;
+}
+impl Trait for Test {
+	type Event = TestEvent;
+	type Providers = Providers;
+	type MinLifetime = MinLifetime;
+	type MaxLifetime = MaxLifetime;
+	type MaxIpnsIdLength = MaxIpnsIdLength;
+}
+type System = system::Module<Test>;
+type Discovery = Module<Test>;
+
+fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap().0;
+	t.extend(GenesisConfig::<Test> {
+		default_lifetime: 10,
+	}.build_storage().unwrap().0);
+	t.into()
+}
+
+fn last_event() -> TestEvent {
+	System::events().pop().expect("an event was deposited").event
+}
+
+#[test]
+fn providers_publish_expiring_endpoints() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(1);
+		let ipns = Endpoint::Ipns(b"QmProv1".to_vec());
+		assert_noop!(Discovery::set_endpoint(Origin::signed(3), ipns.clone(), None), "Not a provider");
+		assert_noop!(
+			Discovery::set_endpoint(Origin::signed(1), Endpoint::Ipns(b"QmTooLong".to_vec()), None),
+			"IPNS identity is too long"
+		);
+		assert_noop!(Discovery::set_endpoint(Origin::signed(1), ipns.clone(), Some(4)), "Lifetime is too short");
+		assert_noop!(Discovery::set_endpoint(Origin::signed(1), ipns.clone(), Some(101)), "Lifetime is too long");
+
+		assert_ok!(Discovery::set_endpoint(Origin::signed(1), ipns.clone(), None));
+		assert_eq!(last_event(), TestEvent::discovery(RawEvent::EndpointSet(1, ipns.clone(), 11)));
+		assert_ok!(Discovery::set_endpoint(Origin::signed(2), Endpoint::UrlHash(H256::repeat_byte(1)), Some(20)));
+		assert_eq!(Discovery::endpoint_info(&1), Some(EndpointInfo { endpoint: ipns.clone(), expires_at: 11 }));
+		assert_eq!(Discovery::endpoint_of(&2), Some(Endpoint::UrlHash(H256::repeat_byte(1))));
+
+		System::set_block_number(11);
+		assert_eq!(Discovery::endpoint_of(&1), None);
+		assert_eq!(Discovery::endpoint_of(&2), Some(Endpoint::UrlHash(H256::repeat_byte(1))));
+
+		// Republishing renews the lifetime.
+		assert_ok!(Discovery::set_endpoint(Origin::signed(1), ipns.clone(), None));
+		assert_eq!(Discovery::endpoint_of(&1), Some(ipns));
+		assert_ok!(Discovery::unset_endpoint(Origin::signed(1)));
+		assert_eq!(last_event(), TestEvent::discovery(RawEvent::EndpointUnset(1)));
+		assert_noop!(Discovery::unset_endpoint(Origin::signed(1)), "No endpoint");
+		assert_eq!(Discovery::endpoint_of(&1), None);
+	});
+}
+
+#[test]
+fn stale_endpoints_are_removed_by_anyone() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(1);
+		assert_ok!(Discovery::set_endpoint(Origin::signed(1), Endpoint::Ipns(b"QmProv1".to_vec()), None));
+		assert_ok!(Discovery::set_endpoint(Origin::signed(2), Endpoint::Ipns(b"QmProv2".to_vec()), Some(50)));
+
+		assert_noop!(Discovery::remove_stale_endpoint(Origin::signed(3), 1), "Endpoint is not stale");
+		System::set_block_number(11);
+		assert_ok!(Discovery::remove_stale_endpoint(Origin::signed(3), 1));
+		assert_eq!(last_event(), TestEvent::discovery(RawEvent::EndpointRemoved(1)));
+		assert_noop!(Discovery::remove_stale_endpoint(Origin::signed(3), 1), "No endpoint");
+
+		// Endpoints of accounts that aren't providers anymore are stale too.
+		assert_noop!(Discovery::remove_stale_endpoint(Origin::signed(3), 2), "Endpoint is not stale");
+		PROVIDERS.with(|providers| providers.borrow_mut().retain(|who| *who != 2));
+		assert_eq!(Discovery::endpoint_of(&2), None);
+		assert_ok!(Discovery::remove_stale_endpoint(Origin::signed(3), 2));
+		assert_eq!(Discovery::endpoint_info(&2), None);
+	});
+}
+
+#[test]
+fn root_sets_the_default_lifetime() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_noop!(Discovery::set_default_lifetime(Origin::signed(1), 20), "bad origin: expected to be a root origin");
+		assert_noop!(Discovery::set_default_lifetime(Origin::ROOT, 200), "Lifetime is too long");
+		assert_ok!(Discovery::set_default_lifetime(Origin::ROOT, 20));
+		assert_eq!(last_event(), TestEvent::discovery(RawEvent::DefaultLifetimeSet(20)));
+
+		assert_ok!(Discovery::set_endpoint(Origin::signed(1), Endpoint::Ipns(vec![]), None));
+		assert_eq!(Discovery::endpoint_info(&1).unwrap().expires_at, 20);
+	});
+}
+
+#[test]
+fn endpoints_are_weighed_by_their_size() {
+	with_externalities(&mut new_test_ext(), || {
+		let weight = |endpoint| Call::<Test>::set_endpoint(endpoint, None).get_dispatch_info().weight;
+
+		assert!(weight(Endpoint::Ipns(vec![1])) < weight(Endpoint::Ipns(vec![1; 8])));
+		assert!(weight(Endpoint::Ipns(vec![1])) < weight(Endpoint::UrlHash(H256::repeat_byte(1))));
+	});
+}
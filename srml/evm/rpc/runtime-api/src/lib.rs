@@ -0,0 +1,48 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API definition for the Ethereum RPC of the EVM module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+use rstd::prelude::*;
+use client::decl_runtime_apis;
+use primitives::{U256, H256, H160};
+
+pub use evm::{Account, Log, TransactionStatus};
+
+decl_runtime_apis! {
+	/// The api to query the EVM state for the Ethereum RPC.
+	pub trait EVMApi {
+		/// The chain id of the EVM.
+		fn chain_id() -> u64;
+		/// The minimum gas price of transactions.
+		fn gas_price() -> U256;
+		/// The gas limit of a block.
+		fn block_gas_limit() -> U256;
+		/// The timestamp of the block, as seen by the contracts.
+		fn timestamp() -> U256;
+		/// The nonce and balance of `address`.
+		fn account_basic(address: H160) -> Account;
+		/// The code of the contract at `address`.
+		fn account_code_at(address: H160) -> Vec<u8>;
+		/// The value at `index` in the storage of the contract at `address`.
+		fn storage_at(address: H160, index: H256) -> H256;
+		/// The outcome of the Ethereum transactions of the block.
+		fn current_transactions() -> Vec<TransactionStatus>;
+	}
+}
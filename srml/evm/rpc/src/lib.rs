@@ -0,0 +1,445 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Ethereum RPC API of the EVM module.
+//!
+//! Serves the core of the `eth_*` namespace on top of the EVM module, so that Ethereum wallets
+//! and tooling can talk to the node: the EVM state of addresses, blocks with their Ethereum
+//! transactions, receipts and logs. Raw transactions are decoded and converted into extrinsics by
+//! the `ConvertTransaction` of the node, usually an unsigned `evm::transact` call, before being
+//! submitted to the transaction pool.
+//!
+//! Ethereum blocks are the blocks of the chain: they have the same number and hash, and contain
+//! the Ethereum transactions executed by the EVM module. Receipts are found with a mapping of
+//! transaction hashes kept in the offchain storage of the node, see `mapping`.
+
+#![warn(missing_docs)]
+
+pub mod mapping;
+pub mod types;
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use client::{Client, CallExecutor, backend::{Backend, OffchainStorage}, runtime_api::ConstructRuntimeApi};
+use evm::{Transaction, TransactionStatus};
+use evm_rpc_runtime_api::EVMApi;
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use primitives::{Blake2Hasher, Bytes, H160, H256, U256};
+use sr_primitives::generic::BlockId;
+use sr_primitives::traits::{Block as BlockT, Header as HeaderT, ProvideRuntimeApi, SaturatedConversion};
+use sr_primitives::transaction_validity::TransactionSource;
+use transaction_pool::txpool::{ChainApi as PoolChainApi, ExtrinsicFor, Pool};
+
+use self::mapping::MappingDb;
+use self::types::{Block, BlockNumber, Filter, Log, Receipt};
+
+pub use self::gen_client::Client as EthClient;
+
+/// Base code for all Ethereum RPC errors.
+const BASE_ERROR: i64 = 7000;
+
+/// The maximum number of blocks searched by `eth_getLogs`.
+const MAX_LOG_BLOCKS: u64 = 1024;
+
+/// Conversion of Ethereum transactions into the extrinsics executing them.
+pub trait ConvertTransaction<Extrinsic> {
+	/// The extrinsic executing `transaction`.
+	fn convert_transaction(&self, transaction: Transaction) -> Extrinsic;
+}
+
+/// Ethereum RPC API.
+#[rpc]
+pub trait EthApi {
+	/// The chain id of the EVM.
+	#[rpc(name = "eth_chainId")]
+	fn chain_id(&self) -> Result<U256>;
+
+	/// The number of the best block.
+	#[rpc(name = "eth_blockNumber")]
+	fn block_number(&self) -> Result<U256>;
+
+	/// The minimum gas price of transactions.
+	#[rpc(name = "eth_gasPrice")]
+	fn gas_price(&self) -> Result<U256>;
+
+	/// The EVM balance of `address` at the block `number`, the best block by default.
+	#[rpc(name = "eth_getBalance")]
+	fn balance(&self, address: H160, number: Option<BlockNumber>) -> Result<U256>;
+
+	/// The nonce of `address` at the block `number`, the best block by default.
+	#[rpc(name = "eth_getTransactionCount")]
+	fn transaction_count(&self, address: H160, number: Option<BlockNumber>) -> Result<U256>;
+
+	/// The code of the contract at `address` at the block `number`, the best block by default.
+	#[rpc(name = "eth_getCode")]
+	fn code_at(&self, address: H160, number: Option<BlockNumber>) -> Result<Bytes>;
+
+	/// The value at `index` in the storage of the contract at `address`, at the block `number`.
+	#[rpc(name = "eth_getStorageAt")]
+	fn storage_at(&self, address: H160, index: U256, number: Option<BlockNumber>) -> Result<H256>;
+
+	/// The block with hash `hash`.
+	///
+	/// Blocks only contain the hashes of their transactions, whatever `full` is.
+	#[rpc(name = "eth_getBlockByHash")]
+	fn block_by_hash(&self, hash: H256, full: bool) -> Result<Option<Block>>;
+
+	/// The block `number`.
+	///
+	/// Blocks only contain the hashes of their transactions, whatever `full` is.
+	#[rpc(name = "eth_getBlockByNumber")]
+	fn block_by_number(&self, number: BlockNumber, full: bool) -> Result<Option<Block>>;
+
+	/// Submit the RLP-encoded signed transaction `bytes`, returning its hash.
+	#[rpc(name = "eth_sendRawTransaction")]
+	fn send_raw_transaction(&self, bytes: Bytes) -> Result<H256>;
+
+	/// The receipt of the transaction `hash`, if it is included in a canonical block.
+	#[rpc(name = "eth_getTransactionReceipt")]
+	fn transaction_receipt(&self, hash: H256) -> Result<Option<Receipt>>;
+
+	/// The logs matching `filter`.
+	#[rpc(name = "eth_getLogs")]
+	fn logs(&self, filter: Filter) -> Result<Vec<Log>>;
+}
+
+/// Implementation of the Ethereum RPC API on top of a client and a transaction pool.
+pub struct Eth<B, E, P: PoolChainApi, RA, CT, S> {
+	client: Arc<Client<B, E, P::Block, RA>>,
+	pool: Arc<Pool<P>>,
+	convert_transaction: CT,
+	mapping: MappingDb<S>,
+}
+
+impl<B, E, P: PoolChainApi, RA, CT, S: OffchainStorage> Eth<B, E, P, RA, CT, S> {
+	/// Create new Ethereum RPC handler.
+	///
+	/// Transactions are converted into extrinsics with `convert_transaction`, and the mapping of
+	/// transaction hashes is kept in `mapping_storage`, usually the offchain storage of the node.
+	pub fn new(
+		client: Arc<Client<B, E, P::Block, RA>>,
+		pool: Arc<Pool<P>>,
+		convert_transaction: CT,
+		mapping_storage: S,
+	) -> Self {
+		Eth {
+			client,
+			pool,
+			convert_transaction,
+			mapping: MappingDb::new(mapping_storage),
+		}
+	}
+}
+
+impl<B, E, P, RA, CT, S> Eth<B, E, P, RA, CT, S> where
+	B: Backend<P::Block, Blake2Hasher> + Send + Sync + 'static,
+	E: CallExecutor<P::Block, Blake2Hasher> + Clone + Send + Sync + 'static,
+	P: PoolChainApi + Sync + Send + 'static,
+	P::Block: BlockT<Hash=H256>,
+	RA: ConstructRuntimeApi<P::Block, Client<B, E, P::Block, RA>> + Send + Sync + 'static,
+	<Client<B, E, P::Block, RA> as ProvideRuntimeApi>::Api: EVMApi<P::Block>,
+	S: OffchainStorage,
+{
+	fn best_number(&self) -> u64 {
+		self.client.info().chain.best_number.saturated_into()
+	}
+
+	fn number(&self, number: BlockNumber) -> u64 {
+		match number {
+			BlockNumber::Num(number) => number,
+			BlockNumber::Earliest => 0,
+			BlockNumber::Latest | BlockNumber::Pending => self.best_number(),
+		}
+	}
+
+	fn block_id(&self, number: Option<BlockNumber>) -> BlockId<P::Block> {
+		match number.unwrap_or_default() {
+			BlockNumber::Latest | BlockNumber::Pending => BlockId::Hash(self.client.info().chain.best_hash),
+			number => BlockId::Number(self.number(number).saturated_into()),
+		}
+	}
+
+	fn canonical_hash(&self, number: u64) -> Result<Option<H256>> {
+		self.client.block_hash(number.saturated_into()).map_err(internal_error)
+	}
+
+	fn transactions(&self, hash: H256) -> Result<Vec<TransactionStatus>> {
+		self.client.runtime_api().current_transactions(&BlockId::Hash(hash)).map_err(internal_error)
+	}
+
+	fn block(&self, id: BlockId<P::Block>) -> Result<Option<Block>> {
+		let header = match self.client.header(&id).map_err(internal_error)? {
+			Some(header) => header,
+			None => return Ok(None),
+		};
+		let hash = header.hash();
+		let at = BlockId::Hash(hash);
+		let api = self.client.runtime_api();
+		let transactions = self.transactions(hash)?;
+
+		Ok(Some(Block {
+			hash,
+			parent_hash: *header.parent_hash(),
+			number: U256::from(header.number().saturated_into::<u64>()),
+			state_root: *header.state_root(),
+			gas_limit: api.block_gas_limit(&at).map_err(internal_error)?,
+			gas_used: transactions.iter()
+				.fold(U256::zero(), |gas, transaction| gas.saturating_add(transaction.used_gas)),
+			timestamp: api.timestamp(&at).map_err(internal_error)?,
+			transactions: transactions.iter().map(|transaction| transaction.transaction_hash).collect(),
+		}))
+	}
+
+	/// Index the transactions of the canonical blocks up to the best block.
+	///
+	/// The index is first rewound to the last canonical block it contains, in case blocks were
+	/// retracted. Blocks whose state was pruned are indexed without transactions.
+	fn sync_mapping(&self) -> Result<()> {
+		let mut next = match self.mapping.last_indexed() {
+			None => 0,
+			Some((mut number, mut hash)) => {
+				let indexed = (number, hash);
+				while self.canonical_hash(number)? != Some(hash) {
+					match self.client.header(&BlockId::Hash(hash)).map_err(internal_error)? {
+						Some(header) if number > 0 => {
+							number -= 1;
+							hash = *header.parent_hash();
+						},
+						_ => {
+							number = 0;
+							hash = self.client.info().chain.genesis_hash;
+						},
+					}
+				}
+				if (number, hash) != indexed {
+					self.mapping.rewind(number, hash);
+				}
+				number + 1
+			},
+		};
+
+		let best = self.best_number();
+		while next <= best {
+			let hash = match self.canonical_hash(next)? {
+				Some(hash) => hash,
+				None => break,
+			};
+			let transactions = self.transactions(hash).unwrap_or_default();
+			let hashes: Vec<_> = transactions.iter().map(|transaction| transaction.transaction_hash).collect();
+			self.mapping.index_block(next, hash, &hashes);
+			next += 1;
+		}
+
+		Ok(())
+	}
+}
+
+impl<B, E, P, RA, CT, S> EthApi for Eth<B, E, P, RA, CT, S> where
+	B: Backend<P::Block, Blake2Hasher> + Send + Sync + 'static,
+	E: CallExecutor<P::Block, Blake2Hasher> + Clone + Send + Sync + 'static,
+	P: PoolChainApi + Sync + Send + 'static,
+	P::Block: BlockT<Hash=H256>,
+	RA: ConstructRuntimeApi<P::Block, Client<B, E, P::Block, RA>> + Send + Sync + 'static,
+	<Client<B, E, P::Block, RA> as ProvideRuntimeApi>::Api: EVMApi<P::Block>,
+	CT: ConvertTransaction<ExtrinsicFor<P>> + Send + Sync + 'static,
+	S: OffchainStorage + 'static,
+{
+	fn chain_id(&self) -> Result<U256> {
+		let at = self.block_id(None);
+		self.client.runtime_api().chain_id(&at).map(U256::from).map_err(internal_error)
+	}
+
+	fn block_number(&self) -> Result<U256> {
+		Ok(U256::from(self.best_number()))
+	}
+
+	fn gas_price(&self) -> Result<U256> {
+		let at = self.block_id(None);
+		self.client.runtime_api().gas_price(&at).map_err(internal_error)
+	}
+
+	fn balance(&self, address: H160, number: Option<BlockNumber>) -> Result<U256> {
+		let at = self.block_id(number);
+		self.client.runtime_api().account_basic(&at, address)
+			.map(|account| account.balance)
+			.map_err(internal_error)
+	}
+
+	fn transaction_count(&self, address: H160, number: Option<BlockNumber>) -> Result<U256> {
+		let at = self.block_id(number);
+		self.client.runtime_api().account_basic(&at, address)
+			.map(|account| account.nonce)
+			.map_err(internal_error)
+	}
+
+	fn code_at(&self, address: H160, number: Option<BlockNumber>) -> Result<Bytes> {
+		let at = self.block_id(number);
+		self.client.runtime_api().account_code_at(&at, address)
+			.map(Into::into)
+			.map_err(internal_error)
+	}
+
+	fn storage_at(&self, address: H160, index: U256, number: Option<BlockNumber>) -> Result<H256> {
+		let at = self.block_id(number);
+		let mut key = [0u8; 32];
+		index.to_big_endian(&mut key);
+		self.client.runtime_api().storage_at(&at, address, H256::from(key)).map_err(internal_error)
+	}
+
+	fn block_by_hash(&self, hash: H256, _full: bool) -> Result<Option<Block>> {
+		self.block(BlockId::Hash(hash))
+	}
+
+	fn block_by_number(&self, number: BlockNumber, _full: bool) -> Result<Option<Block>> {
+		self.block(self.block_id(Some(number)))
+	}
+
+	fn send_raw_transaction(&self, bytes: Bytes) -> Result<H256> {
+		let transaction = Transaction::from_rlp(&bytes).ok_or_else(invalid_transaction_error)?;
+		let hash = transaction.hash();
+		let best = BlockId::Hash(self.client.info().chain.best_hash);
+
+		self.pool
+			.submit_one(&best, TransactionSource::Local, self.convert_transaction.convert_transaction(transaction))
+			.map(|_| hash)
+			.map_err(pool_error)
+	}
+
+	fn transaction_receipt(&self, hash: H256) -> Result<Option<Receipt>> {
+		self.sync_mapping()?;
+		let location = match self.mapping.location(&hash) {
+			Some(location) => location,
+			None => return Ok(None),
+		};
+		if self.canonical_hash(location.block_number)? != Some(location.block_hash) {
+			return Ok(None);
+		}
+
+		let transactions = self.transactions(location.block_hash)?;
+		let index = location.index as usize;
+		let status = match transactions.get(index) {
+			Some(status) => status,
+			None => return Ok(None),
+		};
+		let first_log = transactions[..index].iter().map(|transaction| transaction.logs.len()).sum();
+
+		Ok(Some(Receipt {
+			transaction_hash: hash,
+			transaction_index: U256::from(index),
+			block_hash: location.block_hash,
+			block_number: U256::from(location.block_number),
+			from: status.from,
+			to: status.to,
+			gas_used: status.used_gas,
+			contract_address: status.contract_address,
+			logs: logs(location.block_hash, location.block_number, index, first_log, status).collect(),
+			status: U256::from(status.succeeded as u8),
+		}))
+	}
+
+	fn logs(&self, filter: Filter) -> Result<Vec<Log>> {
+		let blocks = match filter.block_hash {
+			Some(hash) => {
+				let number = self.client.header(&BlockId::Hash(hash)).map_err(internal_error)?
+					.map(|header| header.number().saturated_into::<u64>());
+				number.map(|number| vec![(number, hash)]).unwrap_or_default()
+			},
+			None => {
+				let from = self.number(filter.from_block.unwrap_or_default());
+				let to = self.number(filter.to_block.unwrap_or_default()).min(self.best_number());
+				if to >= from && to - from >= MAX_LOG_BLOCKS {
+					return Err(range_error(from, to));
+				}
+				let mut blocks = Vec::new();
+				for number in from..=to {
+					if let Some(hash) = self.canonical_hash(number)? {
+						blocks.push((number, hash));
+					}
+				}
+				blocks
+			},
+		};
+
+		let mut matching = Vec::new();
+		for (number, hash) in blocks {
+			let mut first_log = 0;
+			for (index, status) in self.transactions(hash)?.iter().enumerate() {
+				matching.extend(
+					logs(hash, number, index, first_log, status)
+						.filter(|log| filter.matches(&log.address, &log.topics))
+				);
+				first_log += status.logs.len();
+			}
+		}
+
+		Ok(matching)
+	}
+}
+
+/// The logs of the transaction `status`, the `index`th transaction of the block, whose first log
+/// is the `first_log`th log of the block.
+fn logs<'a>(
+	block_hash: H256,
+	block_number: u64,
+	index: usize,
+	first_log: usize,
+	status: &'a TransactionStatus,
+) -> impl Iterator<Item=Log> + 'a {
+	status.logs.iter().enumerate().map(move |(i, log)| Log {
+		address: log.address,
+		topics: log.topics.clone(),
+		data: log.data.clone().into(),
+		block_hash,
+		block_number: U256::from(block_number),
+		transaction_hash: status.transaction_hash,
+		transaction_index: U256::from(index),
+		log_index: U256::from(first_log + i),
+	})
+}
+
+fn internal_error<T: Debug>(e: T) -> Error {
+	Error {
+		code: ErrorCode::ServerError(BASE_ERROR + 1),
+		message: format!("Failed to query the chain: {:?}", e),
+		data: None,
+	}
+}
+
+fn invalid_transaction_error() -> Error {
+	Error {
+		code: ErrorCode::ServerError(BASE_ERROR + 2),
+		message: "Invalid RLP-encoded transaction".into(),
+		data: None,
+	}
+}
+
+fn pool_error<T: std::error::Error>(e: T) -> Error {
+	Error {
+		code: ErrorCode::ServerError(BASE_ERROR + 3),
+		message: format!("Failed to submit the transaction: {}", e),
+		data: None,
+	}
+}
+
+fn range_error(from: u64, to: u64) -> Error {
+	Error {
+		code: ErrorCode::ServerError(BASE_ERROR + 4),
+		message: format!("Too many blocks between {} and {}, at most {} are searched", from, to, MAX_LOG_BLOCKS),
+		data: None,
+	}
+}
@@ -0,0 +1,108 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Mapping of Ethereum transaction hashes to the blocks including them.
+//!
+//! Ethereum tooling looks transactions up by their hash, which isn't the hash of the extrinsic
+//! carrying them. The mapping is kept in the offchain storage of the node and indexed lazily, up
+//! to the best block, when the RPC needs it.
+
+use client::backend::OffchainStorage;
+use codec::{Encode, Decode};
+use parking_lot::Mutex;
+use primitives::H256;
+
+/// The prefix of the keys of the mapping in the offchain storage.
+const PREFIX: &[u8] = b"evm-rpc-mapping";
+/// The key of the last indexed block.
+const LAST_INDEXED: &[u8] = b":last_indexed";
+
+/// The block including a transaction, and the index of the transaction in it.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct TransactionLocation {
+	/// The number of the block.
+	pub block_number: u64,
+	/// The hash of the block.
+	pub block_hash: H256,
+	/// The index of the transaction in the block.
+	pub index: u32,
+}
+
+/// The mapping of transaction hashes, in the offchain storage `S`.
+pub struct MappingDb<S> {
+	storage: Mutex<S>,
+}
+
+impl<S: OffchainStorage> MappingDb<S> {
+	/// Create a mapping in `storage`.
+	pub fn new(storage: S) -> Self {
+		MappingDb { storage: Mutex::new(storage) }
+	}
+
+	/// The number and hash of the last indexed block, if any.
+	pub fn last_indexed(&self) -> Option<(u64, H256)> {
+		self.storage.lock().get(PREFIX, LAST_INDEXED)
+			.and_then(|value| Decode::decode(&mut &value[..]).ok())
+	}
+
+	/// Record the transactions of the block `number` with hash `hash`, and make it the last
+	/// indexed block.
+	pub fn index_block(&self, number: u64, hash: H256, transactions: &[H256]) {
+		let mut storage = self.storage.lock();
+		for (index, transaction_hash) in transactions.iter().enumerate() {
+			let location = TransactionLocation { block_number: number, block_hash: hash, index: index as u32 };
+			storage.set(PREFIX, transaction_hash.as_bytes(), &location.encode());
+		}
+		storage.set(PREFIX, LAST_INDEXED, &(number, hash).encode());
+	}
+
+	/// Make the block `number` with hash `hash` the last indexed block, without indexing it.
+	///
+	/// Used to rewind the index to the last canonical block after a reorganization.
+	pub fn rewind(&self, number: u64, hash: H256) {
+		self.storage.lock().set(PREFIX, LAST_INDEXED, &(number, hash).encode());
+	}
+
+	/// The block including the transaction `transaction_hash`, if it was indexed.
+	///
+	/// The block might have been retracted since; callers check that it is still canonical.
+	pub fn location(&self, transaction_hash: &H256) -> Option<TransactionLocation> {
+		self.storage.lock().get(PREFIX, transaction_hash.as_bytes())
+			.and_then(|value| Decode::decode(&mut &value[..]).ok())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn indexed_transactions_are_found() {
+		let db = MappingDb::new(client::in_mem::OffchainStorage::default());
+		let transactions = [H256::repeat_byte(1), H256::repeat_byte(2)];
+		assert_eq!(db.last_indexed(), None);
+
+		db.index_block(3, H256::repeat_byte(3), &transactions);
+
+		assert_eq!(db.last_indexed(), Some((3, H256::repeat_byte(3))));
+		assert_eq!(db.location(&transactions[1]), Some(TransactionLocation {
+			block_number: 3,
+			block_hash: H256::repeat_byte(3),
+			index: 1,
+		}));
+		assert_eq!(db.location(&H256::repeat_byte(4)), None);
+	}
+}
@@ -0,0 +1,216 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The JSON types of the Ethereum RPC.
+
+use std::fmt;
+
+use primitives::{Bytes, H160, H256, U256};
+use serde::{Serialize, Deserialize, Serializer, Deserializer};
+use serde::de::{self, Visitor};
+
+/// A block given by number, or one of the tags of the Ethereum RPC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockNumber {
+	/// The block with the given number.
+	Num(u64),
+	/// The best block.
+	Latest,
+	/// The genesis block.
+	Earliest,
+	/// The block being built, which is the best block since the pool isn't executed.
+	Pending,
+}
+
+impl Default for BlockNumber {
+	fn default() -> Self {
+		BlockNumber::Latest
+	}
+}
+
+impl Serialize for BlockNumber {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		match *self {
+			BlockNumber::Num(number) => serializer.serialize_str(&format!("0x{:x}", number)),
+			BlockNumber::Latest => serializer.serialize_str("latest"),
+			BlockNumber::Earliest => serializer.serialize_str("earliest"),
+			BlockNumber::Pending => serializer.serialize_str("pending"),
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for BlockNumber {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		deserializer.deserialize_any(BlockNumberVisitor)
+	}
+}
+
+struct BlockNumberVisitor;
+
+impl<'de> Visitor<'de> for BlockNumberVisitor {
+	type Value = BlockNumber;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		write!(formatter, "a block number or 'latest', 'earliest' or 'pending'")
+	}
+
+	fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+		match value {
+			"latest" => Ok(BlockNumber::Latest),
+			"earliest" => Ok(BlockNumber::Earliest),
+			"pending" => Ok(BlockNumber::Pending),
+			_ if value.starts_with("0x") => u64::from_str_radix(&value[2..], 16)
+				.map(BlockNumber::Num)
+				.map_err(|e| E::custom(format!("Invalid block number: {}", e))),
+			_ => Err(E::custom("Invalid block number: missing 0x prefix")),
+		}
+	}
+
+	fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+		Ok(BlockNumber::Num(value))
+	}
+}
+
+/// A block, with the hashes of its transactions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Block {
+	/// The hash of the block.
+	pub hash: H256,
+	/// The hash of the parent block.
+	pub parent_hash: H256,
+	/// The number of the block.
+	pub number: U256,
+	/// The state root of the block.
+	pub state_root: H256,
+	/// The gas limit of the block.
+	pub gas_limit: U256,
+	/// The gas used by the transactions of the block.
+	pub gas_used: U256,
+	/// The timestamp of the block.
+	pub timestamp: U256,
+	/// The hashes of the Ethereum transactions of the block.
+	pub transactions: Vec<H256>,
+}
+
+/// A log emitted by a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Log {
+	/// The address of the contract.
+	pub address: H160,
+	/// The indexed topics of the log.
+	pub topics: Vec<H256>,
+	/// The data of the log.
+	pub data: Bytes,
+	/// The hash of the block of the transaction.
+	pub block_hash: H256,
+	/// The number of the block of the transaction.
+	pub block_number: U256,
+	/// The hash of the transaction.
+	pub transaction_hash: H256,
+	/// The index of the transaction in its block.
+	pub transaction_index: U256,
+	/// The index of the log in its block.
+	pub log_index: U256,
+}
+
+/// The receipt of a transaction included in a block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Receipt {
+	/// The hash of the transaction.
+	pub transaction_hash: H256,
+	/// The index of the transaction in its block.
+	pub transaction_index: U256,
+	/// The hash of the block of the transaction.
+	pub block_hash: H256,
+	/// The number of the block of the transaction.
+	pub block_number: U256,
+	/// The sender of the transaction.
+	pub from: H160,
+	/// The target of the transaction, `None` for contract creations.
+	pub to: Option<H160>,
+	/// The gas used by the transaction.
+	pub gas_used: U256,
+	/// The address of the created contract, if any.
+	pub contract_address: Option<H160>,
+	/// The logs emitted by the transaction.
+	pub logs: Vec<Log>,
+	/// `1` if the transaction succeeded, `0` otherwise.
+	pub status: U256,
+}
+
+/// The filter of `eth_getLogs`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Filter {
+	/// The first block searched, the best block by default.
+	pub from_block: Option<BlockNumber>,
+	/// The last block searched, the best block by default.
+	pub to_block: Option<BlockNumber>,
+	/// The only block searched, instead of the range.
+	pub block_hash: Option<H256>,
+	/// The address of the contract emitting the logs.
+	pub address: Option<H160>,
+	/// The topics of the logs by position, `None` matching any topic.
+	pub topics: Option<Vec<Option<H256>>>,
+}
+
+impl Filter {
+	/// Whether a log emitted by `address` with `topics` matches the filter.
+	pub fn matches(&self, address: &H160, topics: &[H256]) -> bool {
+		if self.address.as_ref().map_or(false, |filtered| filtered != address) {
+			return false;
+		}
+		self.topics.as_ref().map_or(true, |filtered| {
+			filtered.iter().enumerate().all(|(i, topic)| match topic {
+				Some(topic) => topics.get(i) == Some(topic),
+				None => true,
+			})
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn block_numbers_are_parsed() {
+		let parse = |json| serde_json::from_str::<BlockNumber>(json).unwrap();
+
+		assert_eq!(parse("\"latest\""), BlockNumber::Latest);
+		assert_eq!(parse("\"earliest\""), BlockNumber::Earliest);
+		assert_eq!(parse("\"0x1b4\""), BlockNumber::Num(436));
+		assert!(serde_json::from_str::<BlockNumber>("\"1b4\"").is_err());
+		assert_eq!(serde_json::to_string(&BlockNumber::Num(436)).unwrap(), "\"0x1b4\"");
+	}
+
+	#[test]
+	fn filter_matches_address_and_topics() {
+		let filter: Filter = serde_json::from_str(r#"{
+			"address": "0x3535353535353535353535353535353535353535",
+			"topics": [null, "0x0000000000000000000000000000000000000000000000000000000000000002"]
+		}"#).unwrap();
+		let address = H160::repeat_byte(0x35);
+		let topics = [H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
+
+		assert!(filter.matches(&address, &topics));
+		assert!(!filter.matches(&H160::zero(), &topics));
+		assert!(!filter.matches(&address, &topics[..1]));
+	}
+}
@@ -0,0 +1,206 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The EVM backend, reading and writing the EVM state from and to the storage of the module.
+
+use rstd::prelude::*;
+use rstd::marker::PhantomData;
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
+use codec::{Encode, Decode};
+use primitives::{U256, H256, H160};
+use sr_primitives::traits::UniqueSaturatedInto;
+use srml_support::traits::Get;
+use srml_support::{StorageMap, StorageDoubleMap};
+use runtime_io::keccak_256;
+use evm::backend::{Backend as BackendT, ApplyBackend, Apply, Basic};
+use crate::{Trait, Module, Event, Accounts, AccountCodes, AccountStorages};
+
+/// The EVM state of an account.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub struct Account {
+	/// The number of transactions sent and contracts created by the account.
+	pub nonce: U256,
+	/// The EVM balance of the account.
+	pub balance: U256,
+}
+
+/// A log emitted by a contract.
+#[derive(Clone, Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Log {
+	/// The address of the contract.
+	pub address: H160,
+	/// The indexed topics of the log.
+	pub topics: Vec<H256>,
+	/// The data of the log.
+	pub data: Vec<u8>,
+}
+
+impl From<evm::backend::Log> for Log {
+	fn from(log: evm::backend::Log) -> Self {
+		Log {
+			address: log.address,
+			topics: log.topics,
+			data: log.data,
+		}
+	}
+}
+
+/// The context of a transaction, that doesn't change while it executes.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Vicinity {
+	/// The gas price of the transaction.
+	pub gas_price: U256,
+	/// The address that sent the transaction.
+	pub origin: H160,
+}
+
+/// EVM backend on top of the storage of the module.
+pub struct Backend<'vicinity, T> {
+	vicinity: &'vicinity Vicinity,
+	_marker: PhantomData<T>,
+}
+
+impl<'vicinity, T> Backend<'vicinity, T> {
+	/// Create a backend for a transaction executed in `vicinity`.
+	pub fn new(vicinity: &'vicinity Vicinity) -> Self {
+		Self {
+			vicinity,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<'vicinity, T: Trait> BackendT for Backend<'vicinity, T> {
+	fn gas_price(&self) -> U256 { self.vicinity.gas_price }
+	fn origin(&self) -> H160 { self.vicinity.origin }
+
+	fn block_hash(&self, number: U256) -> H256 {
+		if number > U256::from(u32::max_value()) {
+			return H256::default();
+		}
+		let number = T::BlockNumber::from(number.as_u32());
+		H256::from_slice(system::Module::<T>::block_hash(number).as_ref())
+	}
+
+	fn block_number(&self) -> U256 {
+		let number: u128 = system::Module::<T>::block_number().unique_saturated_into();
+		U256::from(number)
+	}
+
+	fn block_coinbase(&self) -> H160 {
+		H160::default()
+	}
+
+	fn block_timestamp(&self) -> U256 {
+		let now: u128 = timestamp::Module::<T>::get().unique_saturated_into();
+		U256::from(now)
+	}
+
+	fn block_difficulty(&self) -> U256 {
+		U256::zero()
+	}
+
+	fn block_gas_limit(&self) -> U256 {
+		U256::from(T::BlockGasLimit::get())
+	}
+
+	fn chain_id(&self) -> U256 {
+		U256::from(T::ChainId::get())
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		// Empty accounts don't exist, as after EIP-161.
+		let account = Accounts::get(&address);
+		!account.nonce.is_zero() || !account.balance.is_zero() || !AccountCodes::get(&address).is_empty()
+	}
+
+	fn basic(&self, address: H160) -> Basic {
+		let account = Accounts::get(&address);
+
+		Basic {
+			balance: account.balance,
+			nonce: account.nonce,
+		}
+	}
+
+	fn code_hash(&self, address: H160) -> H256 {
+		H256::from_slice(&keccak_256(&AccountCodes::get(&address)))
+	}
+
+	fn code_size(&self, address: H160) -> usize {
+		AccountCodes::get(&address).len()
+	}
+
+	fn code(&self, address: H160) -> Vec<u8> {
+		AccountCodes::get(&address)
+	}
+
+	fn storage(&self, address: H160, index: H256) -> H256 {
+		AccountStorages::get(&address, &index)
+	}
+}
+
+impl<'vicinity, T: Trait> ApplyBackend for Backend<'vicinity, T> {
+	fn apply<A, I, L>(&mut self, values: A, logs: L, delete_empty: bool) where
+		A: IntoIterator<Item=Apply<I>>,
+		I: IntoIterator<Item=(H256, H256)>,
+		L: IntoIterator<Item=evm::backend::Log>,
+	{
+		for apply in values {
+			match apply {
+				Apply::Modify { address, basic, code, storage, reset_storage } => {
+					Accounts::mutate(&address, |account| {
+						account.balance = basic.balance;
+						account.nonce = basic.nonce;
+					});
+
+					if let Some(code) = code {
+						AccountCodes::insert(&address, code);
+					}
+
+					if reset_storage {
+						AccountStorages::remove_prefix(&address);
+					}
+
+					for (index, value) in storage {
+						if value == H256::default() {
+							AccountStorages::remove(&address, &index);
+						} else {
+							AccountStorages::insert(&address, &index, &value);
+						}
+					}
+
+					if delete_empty && Accounts::get(&address) == Account::default() &&
+						AccountCodes::get(&address).is_empty()
+					{
+						Module::<T>::remove_account(&address);
+					}
+				},
+				Apply::Delete { address } => {
+					Module::<T>::remove_account(&address);
+				},
+			}
+		}
+
+		for log in logs {
+			Module::<T>::deposit_event(Event::Log(log.into()));
+		}
+	}
+}
@@ -0,0 +1,486 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # EVM Module
+//!
+//! The EVM module runs Ethereum contracts with an embedded EVM interpreter.
+//!
+//! - [`evm::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! The module keeps its own EVM state: accounts with a nonce and a balance, the code of the
+//! contracts and their storage. Substrate accounts are mapped to EVM addresses by
+//! `ConvertAccountId`, and move funds between their balance in `Currency` and the balance of their
+//! EVM address with `deposit_balance` and `withdraw_balance`. The deposited funds are held by the
+//! account of the module.
+//!
+//! ### Gas
+//!
+//! Calls and contract creations specify a gas limit. The weight of the extrinsic is the gas limit
+//! times `WeightPerGas`, and the weight of the gas that wasn't used is refunded after the
+//! execution. The gas of `call` and `create` is only paid through the weight fee of the
+//! extrinsic, so these run at a gas price of zero.
+//!
+//! The logs emitted by contracts are deposited as `Log` events.
+//!
+//! ### Ethereum Transactions
+//!
+//! Ethereum accounts, which have no substrate account, submit signed Ethereum transactions with
+//! the unsigned `transact` extrinsic, which pays no weight fee. The sender is recovered from the
+//! signature, and pays the gas from its EVM balance at the price of the transaction, which must be
+//! at least the price of `FeeCalculator`. The balance needs to cover the whole gas limit
+//! beforehand, and the gas that wasn't used is refunded.
+//! Unlike `call` and `create`, a failed transaction still increases the nonce of the sender and
+//! pays for the gas it used. The outcome of the transactions of the current block is kept in
+//! `CurrentTransactions`, for the Ethereum RPC.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `deposit_balance` - Move funds from the balance of the sender to the EVM balance of its
+//!   address.
+//! - `withdraw_balance` - Move funds from the EVM balance of the address of the sender to its
+//!   balance.
+//! - `call` - Call a contract, or transfer EVM balance to an address.
+//! - `create` - Create a contract from its init code.
+//! - `transact` - Execute a signed Ethereum transaction.
+//!
+//! ## GenesisConfig
+//!
+//! The EVM module depends on the [`GenesisConfig`](./struct.GenesisConfig.html), which sets the
+//! initial EVM accounts.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+mod backend;
+mod transaction;
+#[cfg(test)]
+mod tests;
+
+pub use crate::backend::{Account, Log, Vicinity, Backend};
+pub use crate::transaction::{
+	Transaction, TransactionAction, TransactionSignature, TransactionStatus,
+};
+
+use rstd::prelude::*;
+use rstd::marker::PhantomData;
+use codec::Encode;
+use primitives::{U256, H256, H160, Hasher};
+use srml_support::{StorageMap, StorageDoubleMap, decl_module, decl_storage, decl_event};
+use srml_support::dispatch::Result;
+use srml_support::traits::{Currency, ExistenceRequirement, Get, WithdrawReason};
+use sr_primitives::{ApplyError, ModuleId};
+use sr_primitives::transaction_validity::{
+	TransactionSource, TransactionValidity, TransactionLongevity, ValidTransaction,
+};
+use sr_primitives::traits::{AccountIdConversion, SaturatedConversion, UniqueSaturatedInto};
+use sr_primitives::weights::{ClassifyDispatch, DispatchClass, PostDispatchInfo, WeighData, Weight};
+use sr_primitives::traits::DispatchResultWithPostInfo;
+use system::{ensure_none, ensure_signed};
+use evm::ExitReason;
+use evm::executor::StackExecutor;
+use evm::backend::ApplyBackend;
+use evm::Config;
+
+const MODULE_ID: ModuleId = ModuleId(*b"py/ethvm");
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+
+/// The minimum price of gas of Ethereum transactions.
+pub trait FeeCalculator {
+	/// The minimum EVM balance paid for one unit of gas.
+	fn gas_price() -> U256;
+}
+
+impl FeeCalculator for () {
+	fn gas_price() -> U256 { U256::zero() }
+}
+
+/// Mapping of substrate accounts to EVM addresses.
+pub trait ConvertAccountId<A> {
+	/// The EVM address of `account_id`.
+	fn convert_account_id(account_id: &A) -> H160;
+}
+
+/// Maps an account to the last 20 bytes of the hash of its encoding, computed with `H`.
+pub struct HashTruncateConvertAccountId<H>(PhantomData<H>);
+
+impl<H: Hasher<Out=H256>, A: Encode> ConvertAccountId<A> for HashTruncateConvertAccountId<H> {
+	fn convert_account_id(account_id: &A) -> H160 {
+		let hash = account_id.using_encoded(H::hash);
+		H160::from_slice(&hash.as_bytes()[12..])
+	}
+}
+
+pub trait Trait: system::Trait + timestamp::Trait {
+	/// The currency the EVM balances are deposited from and withdrawn to.
+	type Currency: Currency<Self::AccountId>;
+
+	/// Mapping of accounts to EVM addresses.
+	type ConvertAccountId: ConvertAccountId<Self::AccountId>;
+
+	/// The minimum price of gas of Ethereum transactions.
+	type FeeCalculator: FeeCalculator;
+
+	/// The weight of one unit of gas.
+	type WeightPerGas: Get<Weight>;
+
+	/// The gas limit of a block, as seen by the contracts.
+	type BlockGasLimit: Get<u32>;
+
+	/// The chain id, as seen by the contracts.
+	type ChainId: Get<u64>;
+
+	/// The overarching event type.
+	type Event: From<Event> + Into<<Self as system::Trait>::Event>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as EVM {
+		/// The EVM state of each address.
+		pub Accounts get(accounts) config(): map H160 => Account;
+		/// The code of the contract at each address.
+		pub AccountCodes get(account_codes): map H160 => Vec<u8>;
+		/// The storage of the contract at each address.
+		pub AccountStorages get(account_storages): double_map H160, blake2_256(H256) => H256;
+		/// The outcome of the Ethereum transactions of the current block.
+		pub CurrentTransactions get(current_transactions): Vec<TransactionStatus>;
+	}
+}
+
+decl_event! {
+	/// Events of the EVM module.
+	pub enum Event {
+		/// A contract emitted a log.
+		Log(Log),
+		/// A contract was created at the given address.
+		Created(H160),
+	}
+}
+
+/// Weight of the extrinsics executing EVM code: their gas limit times `WeightPerGas`.
+pub struct GasWeight<T>(PhantomData<T>);
+
+impl<T> Default for GasWeight<T> {
+	fn default() -> Self {
+		GasWeight(PhantomData)
+	}
+}
+
+impl<T: Trait> GasWeight<T> {
+	/// The weight of `gas` units of gas.
+	pub fn weight(gas: u32) -> Weight {
+		T::WeightPerGas::get().saturating_mul(gas)
+	}
+}
+
+impl<T: Trait> WeighData<(&H160, &Vec<u8>, &U256, &u32)> for GasWeight<T> {
+	fn weigh_data(&self, (_, _, _, gas_limit): (&H160, &Vec<u8>, &U256, &u32)) -> Weight {
+		Self::weight(*gas_limit)
+	}
+}
+
+impl<T: Trait> WeighData<(&Vec<u8>, &U256, &u32)> for GasWeight<T> {
+	fn weigh_data(&self, (_, _, gas_limit): (&Vec<u8>, &U256, &u32)) -> Weight {
+		Self::weight(*gas_limit)
+	}
+}
+
+impl<T: Trait> WeighData<(&Transaction,)> for GasWeight<T> {
+	fn weigh_data(&self, (transaction,): (&Transaction,)) -> Weight {
+		Self::weight(transaction.gas_limit.min(U256::from(u32::max_value())).as_u32())
+	}
+}
+
+impl<T, A> ClassifyDispatch<A> for GasWeight<T> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		fn on_initialize() {
+			CurrentTransactions::kill();
+		}
+
+		/// Move `value` from the balance of the sender to the EVM balance of its address.
+		fn deposit_balance(origin, #[compact] value: BalanceOf<T>) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			let imbalance = T::Currency::withdraw(
+				&sender,
+				value,
+				WithdrawReason::Transfer,
+				ExistenceRequirement::AllowDeath,
+			)?;
+			T::Currency::resolve_creating(&Self::account_id(), imbalance);
+
+			let address = T::ConvertAccountId::convert_account_id(&sender);
+			let value = U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(value));
+			Accounts::mutate(&address, |account| {
+				account.balance = account.balance.saturating_add(value);
+			});
+
+			Ok(())
+		}
+
+		/// Move `value` from the EVM balance of the address of the sender to its balance.
+		fn withdraw_balance(origin, #[compact] value: BalanceOf<T>) -> Result {
+			let sender = ensure_signed(origin)?;
+			let address = T::ConvertAccountId::convert_account_id(&sender);
+			let evm_value = U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(value));
+
+			let mut account = Accounts::get(&address);
+			account.balance = account.balance.checked_sub(evm_value)
+				.ok_or("Insufficient EVM balance")?;
+
+			let imbalance = T::Currency::withdraw(
+				&Self::account_id(),
+				value,
+				WithdrawReason::Transfer,
+				ExistenceRequirement::AllowDeath,
+			)?;
+			Accounts::insert(&address, account);
+			T::Currency::resolve_creating(&sender, imbalance);
+
+			Ok(())
+		}
+
+		/// Call the contract at `target` with `input`, transferring `value` to it.
+		///
+		/// The gas is paid through the weight fee, which is refunded for the gas that wasn't used.
+		#[weight = GasWeight::<T>::default()]
+		fn call(origin, target: H160, input: Vec<u8>, value: U256, gas_limit: u32)
+			-> DispatchResultWithPostInfo
+		{
+			let sender = ensure_signed(origin)?;
+			let source = T::ConvertAccountId::convert_account_id(&sender);
+
+			let execution = Self::execute(source, value, gas_limit, U256::zero(), |executor| {
+				(executor.transact_call(source, target, value, input, gas_limit as usize), ())
+			})?;
+			Self::exit_result(&execution.reason)?;
+
+			Ok(execution.post_info())
+		}
+
+		/// Create a contract from `init`, transferring `value` to it.
+		///
+		/// The gas is paid through the weight fee, which is refunded for the gas that wasn't used.
+		#[weight = GasWeight::<T>::default()]
+		fn create(origin, init: Vec<u8>, value: U256, gas_limit: u32) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			let source = T::ConvertAccountId::convert_account_id(&sender);
+
+			let execution = Self::execute(source, value, gas_limit, U256::zero(), |executor| {
+				let address = executor.create_address(evm::CreateScheme::Dynamic { caller: source });
+				(executor.transact_create(source, value, init, gas_limit as usize), address)
+			})?;
+			Self::exit_result(&execution.reason)?;
+			Self::deposit_event(Event::Created(execution.result));
+
+			Ok(execution.post_info())
+		}
+
+		/// Execute the signed Ethereum transaction `transaction`.
+		#[weight = GasWeight::<T>::default()]
+		fn transact(origin, transaction: Transaction) -> DispatchResultWithPostInfo {
+			ensure_none(origin)?;
+			let source = Self::check_transaction(&transaction)
+				.map_err(|_| "Invalid Ethereum transaction")?;
+			if transaction.nonce != Accounts::get(&source).nonce {
+				return Err("Invalid Ethereum transaction nonce");
+			}
+
+			let gas_limit = transaction.gas_limit.min(U256::from(u32::max_value())).as_u32();
+			let value = transaction.value;
+			let (to, execution) = match transaction.action.clone() {
+				TransactionAction::Call(target) => {
+					let execution = Self::execute(source, value, gas_limit, transaction.gas_price, |executor| {
+						let input = transaction.input.clone();
+						(executor.transact_call(source, target, value, input, gas_limit as usize), None)
+					})?;
+					(Some(target), execution)
+				},
+				TransactionAction::Create => {
+					let execution = Self::execute(source, value, gas_limit, transaction.gas_price, |executor| {
+						let address = executor.create_address(evm::CreateScheme::Dynamic { caller: source });
+						let init = transaction.input.clone();
+						(executor.transact_create(source, value, init, gas_limit as usize), Some(address))
+					})?;
+					(None, execution)
+				},
+			};
+
+			let succeeded = Self::exit_result(&execution.reason).is_ok();
+			if !succeeded {
+				Self::charge_failed(source, transaction.gas_price, execution.used_gas);
+			} else if let Some(address) = execution.result {
+				Self::deposit_event(Event::Created(address));
+			}
+			CurrentTransactions::mutate(|transactions| transactions.push(TransactionStatus {
+				transaction_hash: transaction.hash(),
+				from: source,
+				to,
+				contract_address: execution.result.filter(|_| succeeded),
+				used_gas: U256::from(execution.used_gas),
+				succeeded,
+				logs: execution.logs.clone(),
+			}));
+
+			Ok(execution.post_info())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// The account holding the funds deposited into EVM balances.
+	pub fn account_id() -> T::AccountId {
+		MODULE_ID.into_account()
+	}
+
+	/// Remove the account at `address`, with its code and storage.
+	fn remove_account(address: &H160) {
+		Accounts::remove(address);
+		AccountCodes::remove(address);
+		AccountStorages::remove_prefix(address);
+	}
+
+	/// The sender of `transaction`, if it is valid on this chain.
+	fn check_transaction(transaction: &Transaction) -> rstd::result::Result<H160, ApplyError> {
+		if transaction.chain_id() != Some(T::ChainId::get()) {
+			return Err(ApplyError::BadSignature);
+		}
+		if transaction.gas_price < T::FeeCalculator::gas_price() {
+			return Err(ApplyError::CantPay);
+		}
+		transaction.sender().ok_or(ApplyError::BadSignature)
+	}
+
+	/// Increase the nonce of `source` and make it pay for `used_gas`, after a failed transaction.
+	fn charge_failed(source: H160, gas_price: U256, used_gas: u32) {
+		Accounts::mutate(&source, |account| {
+			account.nonce = account.nonce.saturating_add(U256::one());
+			account.balance = account.balance
+				.saturating_sub(gas_price.saturating_mul(U256::from(used_gas)));
+		});
+	}
+
+	/// The result of a dispatchable function whose execution exited with `reason`.
+	fn exit_result(reason: &ExitReason) -> Result {
+		match reason {
+			ExitReason::Succeed(_) => Ok(()),
+			ExitReason::Error(_) => Err("EVM execution failed"),
+			ExitReason::Revert(_) => Err("EVM execution reverted"),
+			ExitReason::Fatal(_) => Err("EVM execution failed with a fatal error"),
+		}
+	}
+
+	/// Execute `f` in a new executor on behalf of `source`, with a gas limit of `gas_limit`.
+	///
+	/// The fee of the whole gas limit, at `gas_price`, is withdrawn beforehand and the fee of the
+	/// unused gas is refunded. The changes are only applied if the execution succeeds.
+	fn execute<F, R>(source: H160, value: U256, gas_limit: u32, gas_price: U256, f: F)
+		-> rstd::result::Result<Execution<T, R>, &'static str>
+	where
+		F: FnOnce(&mut StackExecutor<Backend<T>>) -> (ExitReason, R),
+	{
+		let total_fee = gas_price.checked_mul(U256::from(gas_limit))
+			.ok_or("Calculating total fee overflowed")?;
+		let total_payment = value.checked_add(total_fee)
+			.ok_or("Calculating total payment overflowed")?;
+		if Accounts::get(&source).balance < total_payment {
+			return Err("Insufficient EVM balance");
+		}
+
+		let vicinity = Vicinity { gas_price, origin: source };
+		let mut backend = Backend::<T>::new(&vicinity);
+		let config = Config::istanbul();
+		let mut executor = StackExecutor::new(&backend, gas_limit as usize, &config);
+		executor.withdraw(source, total_fee).map_err(|_| "Withdrawing fee failed")?;
+
+		let (reason, result) = f(&mut executor);
+		let used_gas = executor.used_gas().saturated_into::<u32>();
+		if Self::exit_result(&reason).is_err() {
+			return Ok(Execution { reason, used_gas, logs: Vec::new(), result, _marker: PhantomData });
+		}
+
+		executor.deposit(source, total_fee.saturating_sub(executor.fee(gas_price)));
+		let (values, logs) = executor.deconstruct();
+		let logs: Vec<_> = logs.into_iter().collect();
+		backend.apply(values, logs.clone(), true);
+
+		let logs = logs.into_iter().map(Into::into).collect();
+		Ok(Execution { reason, used_gas, logs, result, _marker: PhantomData })
+	}
+}
+
+/// The outcome of EVM code run by `Module::execute`.
+struct Execution<T, R> {
+	reason: ExitReason,
+	used_gas: u32,
+	logs: Vec<Log>,
+	result: R,
+	_marker: PhantomData<T>,
+}
+
+impl<T: Trait, R> Execution<T, R> {
+	/// The weight of the gas actually used.
+	fn post_info(&self) -> PostDispatchInfo {
+		Some(GasWeight::<T>::weight(self.used_gas)).into()
+	}
+}
+
+impl<T: Trait> srml_support::unsigned::ValidateUnsigned for Module<T> {
+	type Call = Call<T>;
+
+	fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+		if let Call::transact(transaction) = call {
+			let sender = match Self::check_transaction(transaction) {
+				Ok(sender) => sender,
+				Err(e) => return TransactionValidity::Invalid(e as i8),
+			};
+			let account = Accounts::get(&sender);
+			if transaction.nonce < account.nonce {
+				return TransactionValidity::Invalid(ApplyError::Stale as i8);
+			}
+			let fee = transaction.gas_price.saturating_mul(transaction.gas_limit);
+			if account.balance < transaction.value.saturating_add(fee) {
+				return TransactionValidity::Invalid(ApplyError::CantPay as i8);
+			}
+
+			let requires = if transaction.nonce > account.nonce {
+				vec![(sender, transaction.nonce - U256::one()).encode()]
+			} else {
+				vec![]
+			};
+			return TransactionValidity::Valid(ValidTransaction {
+				priority: transaction.gas_price.min(U256::from(u64::max_value())).low_u64(),
+				requires,
+				provides: vec![(sender, transaction.nonce).encode()],
+				longevity: TransactionLongevity::max_value(),
+				propagate: true,
+			});
+		}
+		TransactionValidity::Invalid(0)
+	}
+}
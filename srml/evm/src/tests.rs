@@ -0,0 +1,263 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use super::*;
+
+use hex_literal::hex;
+use primitives::Blake2Hasher;
+use runtime_io::{with_externalities, keccak_256};
+use srml_support::{assert_noop, assert_ok, impl_test_runtime, parameter_types};
+use ::evm::backend::Backend as _;
+
+mod evm {
+	// Re-export needed for `impl_outer_event!`.
+	pub use super::super::*;
+}
+
+impl_test_runtime! {
+	pub struct Test;
+	balances {
+		ExistentialDeposit = 0;
+		TransferFee = 0;
+		CreationFee = 0;
+	}
+	events {
+		evm,
+	}
+}
+parameter_types! {
+	pub const MinimumPeriod: u64 = 1;
+	pub const WeightPerGas: Weight = 2;
+	pub const BlockGasLimit: u32 = 10_000_000;
+	pub const ChainId: u64 = 1;
+}
+impl timestamp::Trait for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = MinimumPeriod;
+}
+pub struct FixedGasPrice;
+impl FeeCalculator for FixedGasPrice {
+	fn gas_price() -> U256 {
+		U256::one()
+	}
+}
+impl Trait for Test {
+	type Currency = balances::Module<Test>;
+	type ConvertAccountId = HashTruncateConvertAccountId<Blake2Hasher>;
+	type FeeCalculator = FixedGasPrice;
+	type WeightPerGas = WeightPerGas;
+	type BlockGasLimit = BlockGasLimit;
+	type ChainId = ChainId;
+	type Event = TestEvent;
+}
+type System = system::Module<Test>;
+type Balances = balances::Module<Test>;
+type EVM = Module<Test>;
+
+fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap().0;
+	t.extend(balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100)],
+		vesting: vec![],
+	}.build_storage().unwrap().0);
+	t.into()
+}
+
+fn address(account: u64) -> H160 {
+	<Test as Trait>::ConvertAccountId::convert_account_id(&account)
+}
+
+/// The address of the first contract created by `creator`.
+fn events() -> Vec<TestEvent> {
+	System::events().into_iter().map(|record| record.event).collect()
+}
+
+fn contract_address(creator: H160) -> H160 {
+	let mut rlp = vec![0xd6, 0x94];
+	rlp.extend_from_slice(creator.as_bytes());
+	rlp.push(0x80);
+	H160::from_slice(&keccak_256(&rlp)[12..])
+}
+
+#[test]
+fn deposit_and_withdraw_balance_work() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(EVM::deposit_balance(Origin::signed(1), 60));
+		assert_eq!(Balances::free_balance(&1), 40);
+		assert_eq!(Balances::free_balance(&EVM::account_id()), 60);
+		assert_eq!(EVM::accounts(address(1)).balance, U256::from(60));
+
+		assert_ok!(EVM::withdraw_balance(Origin::signed(1), 20));
+		assert_eq!(Balances::free_balance(&1), 60);
+		assert_eq!(EVM::accounts(address(1)).balance, U256::from(40));
+
+		assert_noop!(EVM::withdraw_balance(Origin::signed(1), 41), "Insufficient EVM balance");
+		assert_noop!(EVM::withdraw_balance(Origin::signed(2), 1), "Insufficient EVM balance");
+	});
+}
+
+#[test]
+fn call_transfers_evm_balance() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(EVM::deposit_balance(Origin::signed(1), 50));
+
+		// The gas is only paid through the weight fee, not from the EVM balance.
+		assert_ok!(EVM::call(Origin::signed(1), address(2), vec![], U256::from(30), 100_000));
+		assert_eq!(EVM::accounts(address(1)).balance, U256::from(20));
+		assert_eq!(EVM::accounts(address(2)).balance, U256::from(30));
+
+		assert_noop!(
+			EVM::call(Origin::signed(1), address(2), vec![], U256::from(21), 100_000),
+			"Insufficient EVM balance"
+		);
+	});
+}
+
+#[test]
+fn only_non_empty_accounts_exist() {
+	with_externalities(&mut new_test_ext(), || {
+		let vicinity = Vicinity::default();
+		let backend = Backend::<Test>::new(&vicinity);
+		assert!(!backend.exists(address(1)));
+
+		assert_ok!(EVM::deposit_balance(Origin::signed(1), 10));
+		assert!(backend.exists(address(1)));
+		Accounts::insert(&address(2), Account { nonce: U256::one(), balance: U256::zero() });
+		assert!(backend.exists(address(2)));
+		AccountCodes::insert(&address(3), vec![0]);
+		assert!(backend.exists(address(3)));
+
+		// Accounts emptied by a withdrawal are left in the storage, but don't exist.
+		assert_ok!(EVM::withdraw_balance(Origin::signed(1), 10));
+		assert!(!backend.exists(address(1)));
+	});
+}
+
+#[test]
+fn create_runs_init_code_and_refunds_unused_gas() {
+	with_externalities(&mut new_test_ext(), || {
+		// PUSH1 0x2a PUSH1 0x00 SSTORE STOP: stores 42 in the first slot of the contract.
+		let init = hex!("602a60005500").to_vec();
+		let gas_limit = 1_000_000;
+
+		let post_info = EVM::create(Origin::signed(1), init, U256::zero(), gas_limit).unwrap();
+		let used_weight = post_info.actual_weight.unwrap();
+		assert!(used_weight > 0 && used_weight < GasWeight::<Test>::weight(gas_limit));
+
+		let contract = contract_address(address(1));
+		assert_eq!(EVM::account_storages(&contract, &H256::zero()), H256::from_low_u64_be(42));
+		assert_eq!(EVM::accounts(address(1)).nonce, U256::from(1));
+		assert_eq!(events(), vec![TestEvent::evm(Event::Created(contract))]);
+	});
+}
+
+#[test]
+fn logs_are_deposited_as_events() {
+	with_externalities(&mut new_test_ext(), || {
+		// PUSH1 0x2a PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00 LOG0 STOP: logs the word 42.
+		let init = hex!("602a60005260206000a000").to_vec();
+
+		assert_ok!(EVM::create(Origin::signed(1), init, U256::zero(), 1_000_000));
+		let contract = contract_address(address(1));
+		assert_eq!(events(), vec![
+			TestEvent::evm(Event::Log(Log {
+				address: contract,
+				topics: vec![],
+				data: H256::from_low_u64_be(42).as_bytes().to_vec(),
+			})),
+			TestEvent::evm(Event::Created(contract)),
+		]);
+	});
+}
+
+#[test]
+fn failed_execution_is_not_applied() {
+	with_externalities(&mut new_test_ext(), || {
+		// PUSH1 0x2a PUSH1 0x00 SSTORE PUSH1 0x00 DUP1 REVERT
+		let init = hex!("602a600055600080fd").to_vec();
+
+		assert_noop!(
+			EVM::create(Origin::signed(1), init, U256::zero(), 1_000_000),
+			"EVM execution reverted"
+		);
+		let contract = contract_address(address(1));
+		assert_eq!(EVM::account_storages(&contract, &H256::zero()), H256::zero());
+	});
+}
+
+#[test]
+fn transact_executes_signed_transactions() {
+	with_externalities(&mut new_test_ext(), || {
+		let transaction = Transaction::from_rlp(&transaction::tests::SIGNED).unwrap();
+		let sender = transaction.sender().unwrap();
+		let value = U256::exp10(18);
+		Accounts::insert(&sender, Account { nonce: U256::from(9), balance: value * 2 });
+
+		assert_ok!(EVM::transact(Origin::NONE, transaction.clone()));
+		let fee = transaction.gas_price * U256::from(21_000);
+		assert_eq!(EVM::accounts(&sender), Account { nonce: U256::from(10), balance: value - fee });
+		assert_eq!(EVM::accounts(H160::repeat_byte(0x35)).balance, value);
+		assert_eq!(EVM::current_transactions(), vec![TransactionStatus {
+			transaction_hash: transaction.hash(),
+			from: sender,
+			to: Some(H160::repeat_byte(0x35)),
+			contract_address: None,
+			used_gas: U256::from(21_000),
+			succeeded: true,
+			logs: vec![],
+		}]);
+
+		assert_noop!(
+			EVM::transact(Origin::NONE, transaction),
+			"Invalid Ethereum transaction nonce"
+		);
+	});
+}
+
+#[test]
+fn unsigned_transactions_are_validated() {
+	use srml_support::unsigned::ValidateUnsigned;
+
+	with_externalities(&mut new_test_ext(), || {
+		let transaction = Transaction::from_rlp(&transaction::tests::SIGNED).unwrap();
+		let sender = transaction.sender().unwrap();
+		let validate = |transaction: &Transaction| EVM::validate_unsigned(
+			TransactionSource::External,
+			&Call::transact(transaction.clone()),
+		);
+
+		assert_eq!(validate(&transaction), TransactionValidity::Invalid(ApplyError::CantPay as i8));
+
+		Accounts::insert(&sender, Account { nonce: U256::from(8), balance: U256::exp10(19) });
+		match validate(&transaction) {
+			TransactionValidity::Valid(valid) => {
+				assert_eq!(valid.requires, vec![(sender, U256::from(8)).encode()]);
+				assert_eq!(valid.provides, vec![(sender, U256::from(9)).encode()]);
+			},
+			_ => panic!("transaction should be valid"),
+		}
+
+		Accounts::mutate(&sender, |account| account.nonce = U256::from(10));
+		assert_eq!(validate(&transaction), TransactionValidity::Invalid(ApplyError::Stale as i8));
+
+		let mut other_chain = transaction;
+		other_chain.signature.v += 2;
+		assert_eq!(validate(&other_chain), TransactionValidity::Invalid(ApplyError::BadSignature as i8));
+	});
+}
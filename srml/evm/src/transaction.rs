@@ -0,0 +1,316 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Ethereum transactions, signed by Ethereum accounts.
+//!
+//! The transactions are the legacy Ethereum transactions, signed with a chain id as described in
+//! EIP-155. They are exchanged in their RLP encoding, which is what Ethereum wallets sign and
+//! submit, and carried in extrinsics in the SCALE encoding of `Transaction`.
+
+use rstd::prelude::*;
+use codec::{Encode, Decode};
+use primitives::{U256, H256, H160};
+use runtime_io::{keccak_256, secp256k1_ecdsa_recover};
+use crate::backend::Log;
+
+/// The action of a transaction.
+#[derive(Clone, Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum TransactionAction {
+	/// Call the contract at the given address, or transfer to it.
+	Call(H160),
+	/// Create a contract from the input of the transaction.
+	Create,
+}
+
+/// The signature of a transaction.
+#[derive(Clone, Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct TransactionSignature {
+	/// The recovery id, combined with the chain id: `chain_id * 2 + 35 + recovery_id`.
+	pub v: u64,
+	/// The `r` value of the signature.
+	pub r: H256,
+	/// The `s` value of the signature.
+	pub s: H256,
+}
+
+/// A signed Ethereum transaction.
+#[derive(Clone, Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Transaction {
+	/// The nonce of the sender.
+	pub nonce: U256,
+	/// The price the sender pays for each unit of gas.
+	pub gas_price: U256,
+	/// The maximum gas the transaction can use.
+	pub gas_limit: U256,
+	/// The action of the transaction.
+	pub action: TransactionAction,
+	/// The value transferred to the target.
+	pub value: U256,
+	/// The call data, or the init code of the created contract.
+	pub input: Vec<u8>,
+	/// The signature of the sender.
+	pub signature: TransactionSignature,
+}
+
+impl Transaction {
+	/// Decode a transaction from its RLP encoding.
+	pub fn from_rlp(data: &[u8]) -> Option<Self> {
+		let items = rlp::decode_list(data)?;
+		if items.len() != 9 {
+			return None;
+		}
+		let action = match items[3].len() {
+			0 => TransactionAction::Create,
+			20 => TransactionAction::Call(H160::from_slice(items[3])),
+			_ => return None,
+		};
+		let v = rlp::decode_u256(items[6])?;
+		if v > U256::from(u64::max_value()) || items[7].len() > 32 || items[8].len() > 32 {
+			return None;
+		}
+		Some(Transaction {
+			nonce: rlp::decode_u256(items[0])?,
+			gas_price: rlp::decode_u256(items[1])?,
+			gas_limit: rlp::decode_u256(items[2])?,
+			action,
+			value: rlp::decode_u256(items[4])?,
+			input: items[5].to_vec(),
+			signature: TransactionSignature {
+				v: v.as_u64(),
+				r: H256::from_slice(&rlp::left_pad(items[7])),
+				s: H256::from_slice(&rlp::left_pad(items[8])),
+			},
+		})
+	}
+
+	/// The RLP encoding of the transaction.
+	pub fn rlp(&self) -> Vec<u8> {
+		let mut items = self.unsigned_items();
+		items.push(rlp::encode_u256(U256::from(self.signature.v)));
+		items.push(rlp::encode_u256(U256::from_big_endian(self.signature.r.as_bytes())));
+		items.push(rlp::encode_u256(U256::from_big_endian(self.signature.s.as_bytes())));
+		rlp::encode_list(&items)
+	}
+
+	/// The hash of the transaction, which identifies it.
+	pub fn hash(&self) -> H256 {
+		H256::from(keccak_256(&self.rlp()))
+	}
+
+	/// The chain id the transaction was signed for, `None` if it was signed without one.
+	pub fn chain_id(&self) -> Option<u64> {
+		match self.signature.v {
+			v if v >= 35 => Some((v - 35) / 2),
+			_ => None,
+		}
+	}
+
+	/// Recover the address of the sender from the signature.
+	///
+	/// Only transactions signed with a chain id are accepted.
+	pub fn sender(&self) -> Option<H160> {
+		let chain_id = self.chain_id()?;
+		let mut signature = [0u8; 65];
+		signature[..32].copy_from_slice(self.signature.r.as_bytes());
+		signature[32..64].copy_from_slice(self.signature.s.as_bytes());
+		signature[64] = (self.signature.v - 35 - chain_id * 2) as u8;
+
+		let mut items = self.unsigned_items();
+		items.push(rlp::encode_u256(U256::from(chain_id)));
+		items.push(rlp::encode_u256(U256::zero()));
+		items.push(rlp::encode_u256(U256::zero()));
+		let message = keccak_256(&rlp::encode_list(&items));
+
+		let public = secp256k1_ecdsa_recover(&signature, &message).ok()?;
+		Some(H160::from_slice(&keccak_256(&public)[12..]))
+	}
+
+	fn unsigned_items(&self) -> Vec<Vec<u8>> {
+		let to = match &self.action {
+			TransactionAction::Call(target) => rlp::encode_bytes(target.as_bytes()),
+			TransactionAction::Create => rlp::encode_bytes(&[]),
+		};
+		vec![
+			rlp::encode_u256(self.nonce),
+			rlp::encode_u256(self.gas_price),
+			rlp::encode_u256(self.gas_limit),
+			to,
+			rlp::encode_u256(self.value),
+			rlp::encode_bytes(&self.input),
+		]
+	}
+}
+
+/// The outcome of a transaction included in the current block.
+#[derive(Clone, Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct TransactionStatus {
+	/// The hash of the transaction.
+	pub transaction_hash: H256,
+	/// The sender of the transaction.
+	pub from: H160,
+	/// The target of the transaction, `None` for contract creations.
+	pub to: Option<H160>,
+	/// The address of the created contract, if any.
+	pub contract_address: Option<H160>,
+	/// The gas used by the transaction.
+	pub used_gas: U256,
+	/// Whether the execution succeeded.
+	pub succeeded: bool,
+	/// The logs emitted by the transaction.
+	pub logs: Vec<Log>,
+}
+
+/// The subset of RLP needed by transactions: lists of byte strings.
+mod rlp {
+	use rstd::prelude::*;
+	use primitives::U256;
+
+	fn encode_length(out: &mut Vec<u8>, len: usize, offset: u8) {
+		if len < 56 {
+			out.push(offset + len as u8);
+		} else {
+			let bytes = (len as u64).to_be_bytes();
+			let start = bytes.iter().position(|b| *b != 0).unwrap_or(7);
+			out.push(offset + 55 + (8 - start) as u8);
+			out.extend_from_slice(&bytes[start..]);
+		}
+	}
+
+	pub fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+		let mut out = Vec::with_capacity(bytes.len() + 9);
+		if bytes.len() == 1 && bytes[0] < 0x80 {
+			out.push(bytes[0]);
+		} else {
+			encode_length(&mut out, bytes.len(), 0x80);
+			out.extend_from_slice(bytes);
+		}
+		out
+	}
+
+	pub fn encode_u256(value: U256) -> Vec<u8> {
+		let mut bytes = [0u8; 32];
+		value.to_big_endian(&mut bytes);
+		let start = bytes.iter().position(|b| *b != 0).unwrap_or(32);
+		encode_bytes(&bytes[start..])
+	}
+
+	pub fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+		let len = items.iter().map(Vec::len).sum();
+		let mut out = Vec::with_capacity(len + 9);
+		encode_length(&mut out, len, 0xc0);
+		for item in items {
+			out.extend_from_slice(item);
+		}
+		out
+	}
+
+	/// Split `data` into whether its first item is a list, the payload of the item and the rest.
+	fn decode_item(data: &[u8]) -> Option<(bool, &[u8], &[u8])> {
+		let first = *data.first()?;
+		let (is_list, offset, len) = match first {
+			0x00..=0x7f => return Some((false, &data[..1], &data[1..])),
+			0x80..=0xb7 => (false, 1, (first - 0x80) as usize),
+			0xb8..=0xbf => {
+				let n = (first - 0xb7) as usize;
+				(false, 1 + n, decode_length(data.get(1..1 + n)?)?)
+			},
+			0xc0..=0xf7 => (true, 1, (first - 0xc0) as usize),
+			_ => {
+				let n = (first - 0xf7) as usize;
+				(true, 1 + n, decode_length(data.get(1..1 + n)?)?)
+			},
+		};
+		let end = offset.checked_add(len)?;
+		Some((is_list, data.get(offset..end)?, data.get(end..)?))
+	}
+
+	fn decode_length(bytes: &[u8]) -> Option<usize> {
+		if bytes.len() > 8 || bytes.first() == Some(&0) {
+			return None;
+		}
+		Some(bytes.iter().fold(0usize, |len, b| (len << 8) | *b as usize))
+	}
+
+	/// Decode a list of byte strings, which must be all of `data`.
+	pub fn decode_list(data: &[u8]) -> Option<Vec<&[u8]>> {
+		let (is_list, mut payload, rest) = decode_item(data)?;
+		if !is_list || !rest.is_empty() {
+			return None;
+		}
+		let mut items = Vec::new();
+		while !payload.is_empty() {
+			let (is_list, item, rest) = decode_item(payload)?;
+			if is_list {
+				return None;
+			}
+			items.push(item);
+			payload = rest;
+		}
+		Some(items)
+	}
+
+	pub fn decode_u256(bytes: &[u8]) -> Option<U256> {
+		if bytes.len() > 32 || bytes.first() == Some(&0) {
+			return None;
+		}
+		Some(U256::from_big_endian(bytes))
+	}
+
+	pub fn left_pad(bytes: &[u8]) -> [u8; 32] {
+		let mut padded = [0u8; 32];
+		padded[32 - bytes.len()..].copy_from_slice(bytes);
+		padded
+	}
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use super::*;
+	use hex_literal::hex;
+
+	/// The example of EIP-155: a transfer of 10^18 wei signed for chain 1.
+	pub(crate) const SIGNED: [u8; 110] = hex!("
+		f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a0
+		28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b
+		3800ccf555c9f3dc64214b297fb1966a3b6d83
+	");
+
+	#[test]
+	fn eip155_example_roundtrips_and_recovers_sender() {
+		let transaction = Transaction::from_rlp(&SIGNED).unwrap();
+
+		assert_eq!(transaction.nonce, U256::from(9));
+		assert_eq!(transaction.gas_limit, U256::from(21_000));
+		assert_eq!(transaction.action, TransactionAction::Call(H160::repeat_byte(0x35)));
+		assert_eq!(transaction.chain_id(), Some(1));
+		assert_eq!(transaction.rlp(), SIGNED.to_vec());
+		assert_eq!(
+			transaction.sender(),
+			Some(H160::from(hex!("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"))),
+		);
+	}
+
+	#[test]
+	fn malformed_rlp_is_rejected() {
+		assert!(Transaction::from_rlp(&SIGNED[..100]).is_none());
+		assert!(Transaction::from_rlp(&[0xc0]).is_none());
+		assert!(Transaction::from_rlp(&[]).is_none());
+	}
+}
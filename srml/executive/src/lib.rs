@@ -59,12 +59,12 @@
 //! # pub type Balances = u64;
 //! # pub type AllModules = u64;
 //! # pub enum Runtime {};
-//! # use sr_primitives::transaction_validity::TransactionValidity;
+//! # use sr_primitives::transaction_validity::{TransactionValidity, TransactionSource};
 //! # use sr_primitives::traits::ValidateUnsigned;
 //! # impl ValidateUnsigned for Runtime {
 //! # 	type Call = ();
 //! #
-//! # 	fn validate_unsigned(_call: &Self::Call) -> TransactionValidity {
+//! # 	fn validate_unsigned(_source: TransactionSource, _call: &Self::Call) -> TransactionValidity {
 //! # 		TransactionValidity::Invalid(0)
 //! # 	}
 //! # }
@@ -81,11 +81,11 @@ use sr_primitives::{generic::Digest, traits::{
 	self, Header, Zero, One, Checkable, Applyable, CheckEqual, OnFinalize,
 	OnInitialize, NumberFor, Block as BlockT, OffchainWorker, ValidateUnsigned
 }};
-use srml_support::Dispatchable;
+use srml_support::{Dispatchable, traits::Contains};
 use codec::{Codec, Encode};
 use system::{extrinsics_root, DigestOf};
 use sr_primitives::{ApplyOutcome, ApplyError};
-use sr_primitives::transaction_validity::TransactionValidity;
+use sr_primitives::transaction_validity::{TransactionValidity, TransactionSource};
 use sr_primitives::weights::GetDispatchInfo;
 
 mod internal {
@@ -97,6 +97,9 @@ mod internal {
 		Future,
 		CantPay,
 		FullBlock,
+		CallFiltered,
+		AncientBirthBlock,
+		EraPeriodTooLong,
 	}
 
 	pub enum ApplyOutcome {
@@ -146,6 +149,7 @@ where
 	CallOf<Block::Extrinsic, Context>: Dispatchable,
 	OriginOf<Block::Extrinsic, Context>: From<Option<System::AccountId>>,
 	UnsignedValidator: ValidateUnsigned<Call=CallOf<Block::Extrinsic, Context>>,
+	System::BaseCallFilter: Contains<CallOf<Block::Extrinsic, Context>>,
 {
 	fn execute_block(block: Block) {
 		Executive::<System, Block, Context, UnsignedValidator, AllModules>::execute_block(block);
@@ -165,6 +169,7 @@ where
 	CallOf<Block::Extrinsic, Context>: Dispatchable,
 	OriginOf<Block::Extrinsic, Context>: From<Option<System::AccountId>>,
 	UnsignedValidator: ValidateUnsigned<Call=CallOf<Block::Extrinsic, Context>>,
+	System::BaseCallFilter: Contains<CallOf<Block::Extrinsic, Context>>,
 {
 	/// Start the execution of a particular block.
 	pub fn initialize_block(header: &System::Header) {
@@ -207,9 +212,13 @@ where
 		// any initial checks
 		Self::initial_checks(&block);
 
-		// execute extrinsics
+		// execute extrinsics, verifying their signatures in the background
 		let (header, extrinsics) = block.deconstruct();
+		runtime_io::start_batch_verify();
 		Self::execute_extrinsics_with_book_keeping(extrinsics, *header.number());
+		if !runtime_io::finish_batch_verify() {
+			panic!("Signature verification failed.");
+		}
 
 		// any final checks
 		Self::final_checks(&header);
@@ -250,6 +259,9 @@ where
 			Err(internal::ApplyError::Stale) => Err(ApplyError::Stale),
 			Err(internal::ApplyError::Future) => Err(ApplyError::Future),
 			Err(internal::ApplyError::FullBlock) => Err(ApplyError::FullBlock),
+			Err(internal::ApplyError::CallFiltered) => Err(ApplyError::CallFiltered),
+			Err(internal::ApplyError::AncientBirthBlock) => Err(ApplyError::AncientBirthBlock),
+			Err(internal::ApplyError::EraPeriodTooLong) => Err(ApplyError::EraPeriodTooLong),
 		}
 	}
 
@@ -263,6 +275,9 @@ where
 			Err(internal::ApplyError::BadSignature(_)) => panic!("All extrinsics should be properly signed"),
 			Err(internal::ApplyError::Stale) | Err(internal::ApplyError::Future) => panic!("All extrinsics should have the correct nonce"),
 			Err(internal::ApplyError::FullBlock) => panic!("Extrinsics should not exceed block limit"),
+			Err(internal::ApplyError::CallFiltered) => panic!("All extrinsics should pass the base call filter"),
+			Err(internal::ApplyError::AncientBirthBlock) | Err(internal::ApplyError::EraPeriodTooLong) =>
+				panic!("All extrinsics should be within their era"),
 		}
 	}
 
@@ -273,7 +288,16 @@ where
 		to_note: Option<Vec<u8>>,
 	) -> result::Result<internal::ApplyOutcome, internal::ApplyError> {
 		// Verify that the signature is good.
-		let xt = uxt.check(&Default::default()).map_err(internal::ApplyError::BadSignature)?;
+		let xt = uxt.check(&Default::default()).map_err(|e| match e {
+			sr_primitives::ANCIENT_BIRTH_BLOCK => internal::ApplyError::AncientBirthBlock,
+			sr_primitives::ERA_PERIOD_TOO_LONG => internal::ApplyError::EraPeriodTooLong,
+			e => internal::ApplyError::BadSignature(e),
+		})?;
+
+		// Refuse anything the runtime does not allow to be dispatched at all.
+		if !System::BaseCallFilter::contains(xt.call()) {
+			return Err(internal::ApplyError::CallFiltered);
+		}
 
 		// We don't need to make sure to `note_extrinsic` only after we know it's going to be
 		// executed to prevent it from leaking in storage since at this point, it will either
@@ -322,8 +346,11 @@ where
 	/// Check a given signed transaction for validity. This doesn't execute any
 	/// side-effects; it merely checks whether the transaction would panic if it were included or not.
 	///
+	/// The `source` is passed through to `ValidateUnsigned`, so modules can restrict
+	/// which unsigned transactions they accept from the network.
+	///
 	/// Changes made to storage should be discarded.
-	pub fn validate_transaction(uxt: Block::Extrinsic) -> TransactionValidity {
+	pub fn validate_transaction(source: TransactionSource, uxt: Block::Extrinsic) -> TransactionValidity {
 		// Note errors > 0 are from ApplyError
 		const UNKNOWN_ERROR: i8 = -127;
 		const INVALID_INDEX: i8 = -10;
@@ -337,11 +364,19 @@ where
 			// Technically a bad signature could also imply an out-of-date account index, but
 			// that's more of an edge case.
 			Err(sr_primitives::BAD_SIGNATURE) => return TransactionValidity::Invalid(ApplyError::BadSignature as i8),
+			Err(sr_primitives::ANCIENT_BIRTH_BLOCK) =>
+				return TransactionValidity::Invalid(ApplyError::AncientBirthBlock as i8),
+			Err(sr_primitives::ERA_PERIOD_TOO_LONG) =>
+				return TransactionValidity::Invalid(ApplyError::EraPeriodTooLong as i8),
 			Err(_) => return TransactionValidity::Invalid(UNKNOWN_ERROR),
 		};
 
+		if !System::BaseCallFilter::contains(xt.call()) {
+			return TransactionValidity::Invalid(ApplyError::CallFiltered as i8);
+		}
+
 		let dispatch_info = xt.get_dispatch_info();
-		xt.validate::<UnsignedValidator>(dispatch_info, encoded_len)
+		xt.validate::<UnsignedValidator>(source, dispatch_info, encoded_len)
 	}
 
 	/// Start an offchain worker and generate extrinsics.
@@ -401,26 +436,35 @@ mod tests {
 		type WeightMultiplierUpdate = ();
 		type MaximumBlockWeight = MaximumBlockWeight;
 		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+		type Call = Call<Runtime>;
+		type BaseCallFilter = BaseFilter;
 		type MaximumBlockLength = MaximumBlockLength;
 	}
 	parameter_types! {
 		pub const ExistentialDeposit: u64 = 0;
 		pub const TransferFee: u64 = 0;
 		pub const CreationFee: u64 = 0;
-		pub const TransactionBaseFee: u64 = 10;
-		pub const TransactionByteFee: u64 = 0;
 	}
 	impl balances::Trait for Runtime {
 		type Balance = u64;
 		type OnFreeBalanceZero = ();
 		type OnNewAccount = ();
 		type Event = MetaEvent;
-		type TransactionPayment = ();
 		type DustRemoval = ();
 		type TransferPayment = ();
 		type ExistentialDeposit = ExistentialDeposit;
 		type TransferFee = TransferFee;
 		type CreationFee = CreationFee;
+	}
+
+	parameter_types! {
+		pub const TransactionBaseFee: u64 = 10;
+		pub const TransactionByteFee: u64 = 0;
+	}
+	impl transaction_payment::Trait for Runtime {
+		type Currency = balances::Module<Runtime>;
+		type OnTransactionPayment = ();
 		type TransactionBaseFee = TransactionBaseFee;
 		type TransactionByteFee = TransactionByteFee;
 		type WeightToFee = ConvertInto;
@@ -429,7 +473,7 @@ mod tests {
 	impl ValidateUnsigned for Runtime {
 		type Call = Call<Runtime>;
 
-		fn validate_unsigned(call: &Self::Call) -> TransactionValidity {
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
 			match call {
 				Call::set_balance(_, _, _) => TransactionValidity::Valid(Default::default()),
 				_ => TransactionValidity::Invalid(0),
@@ -437,11 +481,22 @@ mod tests {
 		}
 	}
 
+	pub struct BaseFilter;
+	impl Contains<Call<Runtime>> for BaseFilter {
+		fn contains(call: &Call<Runtime>) -> bool {
+			match call {
+				// transfers to the frozen account `666` are never allowed.
+				Call::transfer(666, _) => false,
+				_ => true,
+			}
+		}
+	}
+
 	type SignedExtra = (
 		system::CheckEra<Runtime>,
 		system::CheckNonce<Runtime>,
 		system::CheckWeight<Runtime>,
-		balances::TakeFees<Runtime>
+		transaction_payment::ChargeTransactionPayment<Runtime>
 	);
 	type TestXt = sr_primitives::testing::TestXt<Call<Runtime>, SignedExtra>;
 	type Executive = super::Executive<Runtime, Block<TestXt>, system::ChainContext<Runtime>, Runtime, ()>;
@@ -451,7 +506,7 @@ mod tests {
 			system::CheckEra::from(Era::Immortal),
 			system::CheckNonce::from(nonce),
 			system::CheckWeight::new(),
-			balances::TakeFees::from(fee)
+			transaction_payment::ChargeTransactionPayment::from(fee)
 		)
 	}
 
@@ -627,11 +682,26 @@ mod tests {
 		let mut t = new_test_ext(1);
 
 		with_externalities(&mut t, || {
-			assert_eq!(Executive::validate_transaction(xt.clone()), valid);
+			assert_eq!(Executive::validate_transaction(TransactionSource::External, xt.clone()), valid);
 			assert_eq!(Executive::apply_extrinsic(xt), Ok(ApplyOutcome::Fail));
 		});
 	}
 
+	#[test]
+	fn base_call_filter_is_enforced() {
+		let xt = sr_primitives::testing::TestXt(sign_extra(1, 0, 0), Call::transfer(666, 69));
+		let filtered = TransactionValidity::Invalid(ApplyError::CallFiltered as i8);
+		let mut t = new_test_ext(1);
+
+		with_externalities(&mut t, || {
+			assert_eq!(Executive::validate_transaction(TransactionSource::External, xt.clone()), filtered);
+			assert_eq!(Executive::apply_extrinsic(xt), Err(ApplyError::CallFiltered));
+			// nothing was charged nor transferred.
+			assert_eq!(<balances::Module<Runtime>>::total_balance(&1), 111);
+			assert_eq!(<balances::Module<Runtime>>::total_balance(&666), 0);
+		});
+	}
+
 	#[test]
 	fn can_pay_for_tx_fee_on_full_lock() {
 		let id: LockIdentifier = *b"0       ";
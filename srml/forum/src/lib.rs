@@ -0,0 +1,487 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Forum Module
+//!
+//! The Forum module hosts discussions in categories, threads and posts.
+//!
+//! - [`forum::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! Categories are created by `ModeratorOrigin`, e.g. a working group, which also appoints the
+//! moderators of each category. Anyone can start a thread in a category that isn't archived,
+//! reserving `ThreadDeposit`, and post in a thread, reserving `PostDeposit`. Authors get their
+//! deposit back when they delete their posts, or their threads once empty.
+//!
+//! Authors can edit their posts: the hashes of the previous versions are kept in the post's
+//! history. Moderators can remove posts and lock threads of their categories, which slashes the
+//! deposit of the author.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `create_category` - Create a category.
+//! - `set_category_archived` - Archive or unarchive a category.
+//! - `set_moderator` - Appoint or dismiss a moderator of a category.
+//! - `create_thread` - Start a thread.
+//! - `delete_thread` - Delete an empty thread.
+//! - `add_post` - Post in a thread.
+//! - `edit_post` - Edit a post.
+//! - `delete_post` - Delete a post.
+//! - `moderate_thread` - Lock a thread as a moderator.
+//! - `moderate_post` - Remove a post as a moderator.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod tests;
+
+use rstd::prelude::*;
+use rstd::marker::PhantomData;
+use codec::{Encode, Decode};
+use srml_support::{StorageValue, StorageMap, Parameter, decl_module, decl_storage, decl_event, ensure};
+use srml_support::dispatch::Result;
+use srml_support::traits::{Currency, ReservableCurrency, OnUnbalanced, Get};
+use sr_primitives::traits::{SimpleArithmetic, EnsureOrigin, StaticLookup, Hash, One, Zero, SaturatedConversion};
+use sr_primitives::weights::{Weight, WeighData, ClassifyDispatch, DispatchClass, SimpleDispatchInfo};
+use system::ensure_signed;
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
+
+/// The weight of the calls besides the text they read or write.
+const BASE_WEIGHT: Weight = 100_000;
+/// The weight of reading or writing a byte of text.
+const BYTE_WEIGHT: Weight = 100;
+/// The weight of reading or writing a hash of the history of a post.
+const HISTORY_WEIGHT: Weight = 1_000;
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The currency the deposits are reserved in.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The identifier of a category.
+	type CategoryId: Parameter + SimpleArithmetic + Default + Copy;
+
+	/// The identifier of a thread.
+	type ThreadId: Parameter + SimpleArithmetic + Default + Copy;
+
+	/// The identifier of a post.
+	type PostId: Parameter + SimpleArithmetic + Default + Copy;
+
+	/// The origin that manages the categories and their moderators.
+	type ModeratorOrigin: EnsureOrigin<Self::Origin>;
+
+	/// The deposit of a thread.
+	type ThreadDeposit: Get<BalanceOf<Self>>;
+
+	/// The deposit of a post.
+	type PostDeposit: Get<BalanceOf<Self>>;
+
+	/// The maximum length of a title or post.
+	type MaxTextLength: Get<u32>;
+
+	/// Handler for the slashed deposits.
+	type Slashed: OnUnbalanced<NegativeImbalanceOf<Self>>;
+}
+
+/// A category of threads.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Category {
+	/// The title of the category.
+	pub title: Vec<u8>,
+	/// Whether the category is archived, so no thread can be started in it.
+	pub archived: bool,
+}
+
+/// A thread of posts.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Thread<AccountId, Balance, CategoryId> {
+	/// The category of the thread.
+	pub category: CategoryId,
+	/// The account that started the thread.
+	pub author: AccountId,
+	/// The title of the thread.
+	pub title: Vec<u8>,
+	/// The deposit of the author.
+	pub deposit: Balance,
+	/// Whether the thread is locked by a moderator, so nothing can be posted in it.
+	pub locked: bool,
+	/// The number of posts in the thread.
+	pub posts: u32,
+}
+
+/// A post in a thread.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Post<AccountId, Balance, Hash, ThreadId> {
+	/// The thread of the post.
+	pub thread: ThreadId,
+	/// The author of the post.
+	pub author: AccountId,
+	/// The text of the post.
+	pub text: Vec<u8>,
+	/// The hashes of the previous versions of the text, from the oldest.
+	pub history: Vec<Hash>,
+	/// The deposit of the author.
+	pub deposit: Balance,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Forum {
+		/// The identifier of the next category.
+		pub NextCategoryId get(next_category_id): T::CategoryId;
+		/// The categories.
+		pub Categories get(category): map T::CategoryId => Option<Category>;
+		/// Whether an account is a moderator of a category.
+		pub Moderators get(is_moderator): map (T::CategoryId, T::AccountId) => bool;
+
+		/// The identifier of the next thread.
+		pub NextThreadId get(next_thread_id): T::ThreadId;
+		/// The threads.
+		pub Threads get(thread): map T::ThreadId => Option<Thread<T::AccountId, BalanceOf<T>, T::CategoryId>>;
+
+		/// The identifier of the next post.
+		pub NextPostId get(next_post_id): T::PostId;
+		/// The posts.
+		pub Posts get(post): map T::PostId => Option<Post<T::AccountId, BalanceOf<T>, T::Hash, T::ThreadId>>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as Trait>::CategoryId,
+		<T as Trait>::ThreadId,
+		<T as Trait>::PostId,
+	{
+		/// A category was created.
+		CategoryCreated(CategoryId),
+		/// A category was archived (`true`) or unarchived (`false`).
+		CategoryArchived(CategoryId, bool),
+		/// An account was appointed (`true`) or dismissed (`false`) as moderator of a category.
+		ModeratorSet(CategoryId, AccountId, bool),
+		/// A thread was started in a category by the author.
+		ThreadCreated(ThreadId, CategoryId, AccountId),
+		/// A thread was deleted.
+		ThreadDeleted(ThreadId),
+		/// A post was added to a thread by the author.
+		PostAdded(PostId, ThreadId, AccountId),
+		/// A post was edited.
+		PostEdited(PostId),
+		/// A post was deleted.
+		PostDeleted(PostId),
+		/// A thread was locked by the moderator.
+		ThreadModerated(ThreadId, AccountId),
+		/// A post was removed by the moderator.
+		PostModerated(PostId, AccountId),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		/// The deposit of a thread.
+		const ThreadDeposit: BalanceOf<T> = T::ThreadDeposit::get();
+
+		/// The deposit of a post.
+		const PostDeposit: BalanceOf<T> = T::PostDeposit::get();
+
+		/// The maximum length of a title or post.
+		const MaxTextLength: u32 = T::MaxTextLength::get();
+
+		fn deposit_event() = default;
+
+		/// Create a category titled `title`.
+		#[weight = TextWeight::<T>::new(DispatchClass::Operational)]
+		fn create_category(origin, title: Vec<u8>) -> Result {
+			T::ModeratorOrigin::ensure_origin(origin)?;
+			Self::ensure_text_length(&title)?;
+
+			let id = Self::next_category_id();
+			<NextCategoryId<T>>::mutate(|id| *id += One::one());
+			<Categories<T>>::insert(id, Category { title, archived: false });
+			Self::deposit_event(RawEvent::CategoryCreated(id));
+
+			Ok(())
+		}
+
+		/// Archive the category `category_id`, or unarchive it if `archived` is `false`.
+		#[weight = SimpleDispatchInfo::FixedOperational(BASE_WEIGHT)]
+		fn set_category_archived(origin, #[compact] category_id: T::CategoryId, archived: bool) -> Result {
+			T::ModeratorOrigin::ensure_origin(origin)?;
+			let mut category = Self::category(category_id).ok_or("Unknown category")?;
+
+			category.archived = archived;
+			<Categories<T>>::insert(category_id, category);
+			Self::deposit_event(RawEvent::CategoryArchived(category_id, archived));
+
+			Ok(())
+		}
+
+		/// Appoint `who` as moderator of the category `category_id`, or dismiss it if `moderator`
+		/// is `false`.
+		#[weight = SimpleDispatchInfo::FixedOperational(BASE_WEIGHT)]
+		fn set_moderator(
+			origin,
+			#[compact] category_id: T::CategoryId,
+			who: <T::Lookup as StaticLookup>::Source,
+			moderator: bool
+		) -> Result {
+			T::ModeratorOrigin::ensure_origin(origin)?;
+			let who = T::Lookup::lookup(who)?;
+			ensure!(<Categories<T>>::exists(category_id), "Unknown category");
+
+			if moderator {
+				<Moderators<T>>::insert((category_id, who.clone()), true);
+			} else {
+				<Moderators<T>>::remove((category_id, who.clone()));
+			}
+			Self::deposit_event(RawEvent::ModeratorSet(category_id, who, moderator));
+
+			Ok(())
+		}
+
+		/// Start a thread titled `title` in the category `category_id`, reserving `ThreadDeposit`.
+		#[weight = TextWeight::<T>::new(DispatchClass::Normal)]
+		fn create_thread(origin, #[compact] category_id: T::CategoryId, title: Vec<u8>) -> Result {
+			let who = ensure_signed(origin)?;
+			let category = Self::category(category_id).ok_or("Unknown category")?;
+			ensure!(!category.archived, "Category is archived");
+			Self::ensure_text_length(&title)?;
+
+			let deposit = T::ThreadDeposit::get();
+			T::Currency::reserve(&who, deposit)?;
+
+			let id = Self::next_thread_id();
+			<NextThreadId<T>>::mutate(|id| *id += One::one());
+			<Threads<T>>::insert(id, Thread {
+				category: category_id,
+				author: who.clone(),
+				title,
+				deposit,
+				locked: false,
+				posts: 0,
+			});
+			Self::deposit_event(RawEvent::ThreadCreated(id, category_id, who));
+
+			Ok(())
+		}
+
+		/// Delete the thread `thread_id` of the sender, which must have no posts.
+		#[weight = SimpleDispatchInfo::FixedNormal(BASE_WEIGHT)]
+		fn delete_thread(origin, #[compact] thread_id: T::ThreadId) -> Result {
+			let who = ensure_signed(origin)?;
+			let thread = Self::thread(thread_id).ok_or("Unknown thread")?;
+			ensure!(thread.author == who, "Not the author of the thread");
+			ensure!(thread.posts == 0, "Thread still has posts");
+
+			T::Currency::unreserve(&who, thread.deposit);
+			<Threads<T>>::remove(thread_id);
+			Self::deposit_event(RawEvent::ThreadDeleted(thread_id));
+
+			Ok(())
+		}
+
+		/// Post `text` in the thread `thread_id`, reserving `PostDeposit`.
+		#[weight = TextWeight::<T>::new(DispatchClass::Normal)]
+		fn add_post(origin, #[compact] thread_id: T::ThreadId, text: Vec<u8>) -> Result {
+			let who = ensure_signed(origin)?;
+			let mut thread = Self::thread(thread_id).ok_or("Unknown thread")?;
+			ensure!(!thread.locked, "Thread is locked");
+			let category = Self::category(thread.category).ok_or("Unknown category")?;
+			ensure!(!category.archived, "Category is archived");
+			Self::ensure_text_length(&text)?;
+
+			thread.posts = thread.posts.checked_add(1).ok_or("Too many posts")?;
+			let deposit = T::PostDeposit::get();
+			T::Currency::reserve(&who, deposit)?;
+
+			let id = Self::next_post_id();
+			<NextPostId<T>>::mutate(|id| *id += One::one());
+			<Threads<T>>::insert(thread_id, thread);
+			<Posts<T>>::insert(id, Post {
+				thread: thread_id,
+				author: who.clone(),
+				text,
+				history: Vec::new(),
+				deposit,
+			});
+			Self::deposit_event(RawEvent::PostAdded(id, thread_id, who));
+
+			Ok(())
+		}
+
+		/// Replace the text of the post `post_id` of the sender by `text`, keeping the hash of the
+		/// previous text in its history.
+		#[weight = PostWeight::<T>::default()]
+		fn edit_post(origin, #[compact] post_id: T::PostId, text: Vec<u8>) -> Result {
+			let who = ensure_signed(origin)?;
+			let mut post = Self::post(post_id).ok_or("Unknown post")?;
+			ensure!(post.author == who, "Not the author of the post");
+			let thread = Self::thread(post.thread).ok_or("Unknown thread")?;
+			ensure!(!thread.locked, "Thread is locked");
+			Self::ensure_text_length(&text)?;
+
+			let previous = rstd::mem::replace(&mut post.text, text);
+			post.history.push(T::Hashing::hash(&previous));
+			<Posts<T>>::insert(post_id, post);
+			Self::deposit_event(RawEvent::PostEdited(post_id));
+
+			Ok(())
+		}
+
+		/// Delete the post `post_id` of the sender, returning its deposit.
+		#[weight = PostWeight::<T>::default()]
+		fn delete_post(origin, #[compact] post_id: T::PostId) -> Result {
+			let who = ensure_signed(origin)?;
+			let post = Self::post(post_id).ok_or("Unknown post")?;
+			ensure!(post.author == who, "Not the author of the post");
+
+			T::Currency::unreserve(&who, post.deposit);
+			Self::remove_post(post_id, post.thread);
+			Self::deposit_event(RawEvent::PostDeleted(post_id));
+
+			Ok(())
+		}
+
+		/// Lock the thread `thread_id`, slashing the deposit of its author. The posts of the
+		/// thread stay.
+		///
+		/// The sender must be a moderator of the category of the thread.
+		#[weight = SimpleDispatchInfo::FixedNormal(BASE_WEIGHT)]
+		fn moderate_thread(origin, #[compact] thread_id: T::ThreadId) -> Result {
+			let who = ensure_signed(origin)?;
+			let mut thread = Self::thread(thread_id).ok_or("Unknown thread")?;
+			ensure!(Self::is_moderator((thread.category, who.clone())), "Not a moderator of the category");
+			ensure!(!thread.locked, "Thread is locked");
+
+			let imbalance = T::Currency::slash_reserved(&thread.author, thread.deposit).0;
+			T::Slashed::on_unbalanced(imbalance);
+			thread.deposit = Zero::zero();
+			thread.locked = true;
+			<Threads<T>>::insert(thread_id, thread);
+			Self::deposit_event(RawEvent::ThreadModerated(thread_id, who));
+
+			Ok(())
+		}
+
+		/// Remove the post `post_id`, slashing the deposit of its author.
+		///
+		/// The sender must be a moderator of the category of the thread of the post.
+		#[weight = PostWeight::<T>::default()]
+		fn moderate_post(origin, #[compact] post_id: T::PostId) -> Result {
+			let who = ensure_signed(origin)?;
+			let post = Self::post(post_id).ok_or("Unknown post")?;
+			let thread = Self::thread(post.thread).ok_or("Unknown thread")?;
+			ensure!(Self::is_moderator((thread.category, who.clone())), "Not a moderator of the category");
+
+			let imbalance = T::Currency::slash_reserved(&post.author, post.deposit).0;
+			T::Slashed::on_unbalanced(imbalance);
+			Self::remove_post(post_id, post.thread);
+			Self::deposit_event(RawEvent::PostModerated(post_id, who));
+
+			Ok(())
+		}
+	}
+}
+
+/// Weight of the calls which write a title or post: a step per byte of the text.
+pub struct TextWeight<T>(DispatchClass, PhantomData<T>);
+
+impl<T> TextWeight<T> {
+	/// The weight of a call of the dispatch `class`.
+	pub fn new(class: DispatchClass) -> Self {
+		TextWeight(class, PhantomData)
+	}
+
+	fn weight(text: &[u8]) -> Weight {
+		let bytes: Weight = text.len().saturated_into();
+		BASE_WEIGHT.saturating_add(BYTE_WEIGHT.saturating_mul(bytes))
+	}
+}
+
+impl<T> WeighData<(&Vec<u8>,)> for TextWeight<T> {
+	fn weigh_data(&self, (text,): (&Vec<u8>,)) -> Weight {
+		Self::weight(text)
+	}
+}
+
+impl<T, I> WeighData<(I, &Vec<u8>)> for TextWeight<T> {
+	fn weigh_data(&self, (_, text): (I, &Vec<u8>)) -> Weight {
+		Self::weight(text)
+	}
+}
+
+impl<T, A> ClassifyDispatch<A> for TextWeight<T> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		self.0
+	}
+}
+
+/// Weight of the calls on a post: a step per byte of its text and per hash of its history, and
+/// per byte of its new text when it is edited.
+pub struct PostWeight<T>(PhantomData<T>);
+
+impl<T> Default for PostWeight<T> {
+	fn default() -> Self {
+		PostWeight(PhantomData)
+	}
+}
+
+impl<T: Trait> WeighData<(&T::PostId,)> for PostWeight<T> {
+	fn weigh_data(&self, (post_id,): (&T::PostId,)) -> Weight {
+		Module::<T>::post(post_id).map_or(BASE_WEIGHT, |post| {
+			let history: Weight = post.history.len().saturated_into();
+			TextWeight::<T>::weight(&post.text).saturating_add(HISTORY_WEIGHT.saturating_mul(history))
+		})
+	}
+}
+
+impl<T: Trait> WeighData<(&T::PostId, &Vec<u8>)> for PostWeight<T> {
+	fn weigh_data(&self, (post_id, text): (&T::PostId, &Vec<u8>)) -> Weight {
+		let bytes: Weight = text.len().saturated_into();
+		self.weigh_data((post_id,)).saturating_add(BYTE_WEIGHT.saturating_mul(bytes))
+	}
+}
+
+impl<T, A> ClassifyDispatch<A> for PostWeight<T> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+impl<T: Trait> Module<T> {
+	fn ensure_text_length(text: &[u8]) -> Result {
+		ensure!(text.len() <= T::MaxTextLength::get() as usize, "Text is too long");
+		Ok(())
+	}
+
+	fn remove_post(post_id: T::PostId, thread_id: T::ThreadId) {
+		<Posts<T>>::remove(post_id);
+		<Threads<T>>::mutate(thread_id, |thread| if let Some(thread) = thread {
+			thread.posts -= 1;
+		});
+	}
+}
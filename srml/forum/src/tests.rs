@@ -0,0 +1,177 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use super::*;
+
+use primitives::Blake2Hasher;
+use runtime_io::with_externalities;
+use srml_support::{assert_noop, assert_ok, impl_test_runtime, parameter_types};
+use sr_primitives::traits::BlakeTwo256;
+use sr_primitives::weights::GetDispatchInfo;
+
+impl_test_runtime! {
+	pub struct Test;
+	balances {
+		ExistentialDeposit = 0;
+		TransferFee = 0;
+		CreationFee = 0;
+	}
+}
+parameter_types! {
+	pub const ThreadDeposit: u64 = 10;
+	pub const PostDeposit: u64 = 5;
+	pub const MaxTextLength: u32 = 8;
+}
+impl Trait for Test {
+	type Event = ();
+	type Currency = balances::Module<Test>;
+	type CategoryId = u32;
+	type ThreadId = u64;
+	type PostId = u64;
+	type ModeratorOrigin = system::EnsureRoot<u64>;
+	type ThreadDeposit = ThreadDeposit;
+	type PostDeposit = PostDeposit;
+	type MaxTextLength = MaxTextLength;
+	type Slashed = ();
+}
+type Balances = balances::Module<Test>;
+type Forum = Module<Test>;
+
+fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap().0;
+	t.extend(balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100), (3, 12)],
+		vesting: vec![],
+	}.build_storage().unwrap().0);
+	t.into()
+}
+
+/// The thread 0 of the account 1 in the category 0, moderated by the account 10.
+fn new_moderated_thread() {
+	assert_ok!(Forum::create_category(Origin::ROOT, b"general".to_vec()));
+	assert_ok!(Forum::set_moderator(Origin::ROOT, 0, 10, true));
+	assert_ok!(Forum::create_thread(Origin::signed(1), 0, b"hello".to_vec()));
+}
+
+#[test]
+fn categories_are_managed_by_the_moderator_origin() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_noop!(
+			Forum::create_category(Origin::signed(1), b"general".to_vec()),
+			"bad origin: expected to be a root origin"
+		);
+		assert_noop!(Forum::create_category(Origin::ROOT, b"announcements".to_vec()), "Text is too long");
+		assert_ok!(Forum::create_category(Origin::ROOT, b"general".to_vec()));
+		assert_eq!(Forum::category(0), Some(Category { title: b"general".to_vec(), archived: false }));
+
+		assert_noop!(Forum::set_moderator(Origin::ROOT, 1, 10, true), "Unknown category");
+		assert_ok!(Forum::set_moderator(Origin::ROOT, 0, 10, true));
+		assert!(Forum::is_moderator((0, 10)));
+		assert_ok!(Forum::set_moderator(Origin::ROOT, 0, 10, false));
+		assert!(!Forum::is_moderator((0, 10)));
+
+		assert_ok!(Forum::set_category_archived(Origin::ROOT, 0, true));
+		assert_noop!(Forum::create_thread(Origin::signed(1), 0, b"hello".to_vec()), "Category is archived");
+		assert_ok!(Forum::set_category_archived(Origin::ROOT, 0, false));
+		assert_ok!(Forum::create_thread(Origin::signed(1), 0, b"hello".to_vec()));
+	});
+}
+
+#[test]
+fn threads_and_posts_reserve_deposits() {
+	with_externalities(&mut new_test_ext(), || {
+		new_moderated_thread();
+		assert_eq!(Balances::reserved_balance(&1), 10);
+
+		assert_ok!(Forum::add_post(Origin::signed(2), 0, b"hi".to_vec()));
+		assert_ok!(Forum::add_post(Origin::signed(3), 0, b"hey".to_vec()));
+		assert_noop!(Forum::add_post(Origin::signed(3), 0, b"hey".to_vec()), "not enough free funds");
+		assert_eq!(Balances::reserved_balance(&2), 5);
+		assert_eq!(Forum::thread(0).unwrap().posts, 2);
+
+		assert_noop!(Forum::delete_thread(Origin::signed(2), 0), "Not the author of the thread");
+		assert_noop!(Forum::delete_thread(Origin::signed(1), 0), "Thread still has posts");
+		assert_noop!(Forum::delete_post(Origin::signed(3), 0), "Not the author of the post");
+		assert_ok!(Forum::delete_post(Origin::signed(2), 0));
+		assert_ok!(Forum::delete_post(Origin::signed(3), 1));
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		assert_eq!(Forum::post(0), None);
+
+		assert_ok!(Forum::delete_thread(Origin::signed(1), 0));
+		assert_eq!(Balances::reserved_balance(&1), 0);
+		assert_eq!(Forum::thread(0), None);
+	});
+}
+
+#[test]
+fn edited_posts_keep_the_hashes_of_their_history() {
+	with_externalities(&mut new_test_ext(), || {
+		new_moderated_thread();
+		assert_ok!(Forum::add_post(Origin::signed(2), 0, b"hi".to_vec()));
+
+		assert_noop!(Forum::edit_post(Origin::signed(1), 0, b"hello".to_vec()), "Not the author of the post");
+		assert_noop!(Forum::edit_post(Origin::signed(2), 0, b"hello you".to_vec()), "Text is too long");
+		assert_ok!(Forum::edit_post(Origin::signed(2), 0, b"hello".to_vec()));
+		assert_ok!(Forum::edit_post(Origin::signed(2), 0, b"hey".to_vec()));
+
+		let post = Forum::post(0).unwrap();
+		assert_eq!(post.text, b"hey".to_vec());
+		assert_eq!(post.history, vec![BlakeTwo256::hash(b"hi"), BlakeTwo256::hash(b"hello")]);
+	});
+}
+
+#[test]
+fn moderators_slash_deposits() {
+	with_externalities(&mut new_test_ext(), || {
+		new_moderated_thread();
+		assert_ok!(Forum::add_post(Origin::signed(2), 0, b"spam".to_vec()));
+
+		assert_noop!(Forum::moderate_post(Origin::signed(1), 0), "Not a moderator of the category");
+		assert_ok!(Forum::moderate_post(Origin::signed(10), 0));
+		assert_eq!(Forum::post(0), None);
+		assert_eq!(Forum::thread(0).unwrap().posts, 0);
+		assert_eq!(Balances::total_balance(&2), 95);
+
+		assert_ok!(Forum::add_post(Origin::signed(2), 0, b"hi".to_vec()));
+		assert_ok!(Forum::moderate_thread(Origin::signed(10), 0));
+		assert_noop!(Forum::moderate_thread(Origin::signed(10), 0), "Thread is locked");
+		assert_eq!(Balances::total_balance(&1), 90);
+		assert_noop!(Forum::add_post(Origin::signed(2), 0, b"hi".to_vec()), "Thread is locked");
+		assert_noop!(Forum::edit_post(Origin::signed(2), 1, b"hey".to_vec()), "Thread is locked");
+
+		// Posts of locked threads can still be deleted, and so can the thread once empty.
+		assert_ok!(Forum::delete_post(Origin::signed(2), 1));
+		assert_ok!(Forum::delete_thread(Origin::signed(1), 0));
+		assert_eq!(Balances::total_balance(&1), 90);
+	});
+}
+
+#[test]
+fn posts_are_weighed_by_their_text_and_history() {
+	with_externalities(&mut new_test_ext(), || {
+		new_moderated_thread();
+		let weight = |call: Call<Test>| call.get_dispatch_info().weight;
+
+		assert!(weight(Call::add_post(0, b"hi".to_vec())) < weight(Call::add_post(0, b"hi there".to_vec())));
+		assert_ok!(Forum::add_post(Origin::signed(2), 0, b"hi".to_vec()));
+		let unedited = weight(Call::delete_post(0));
+		assert_ok!(Forum::edit_post(Origin::signed(2), 0, b"hi".to_vec()));
+		assert!(unedited < weight(Call::delete_post(0)));
+		assert!(weight(Call::delete_post(0)) < weight(Call::edit_post(0, b"hi".to_vec())));
+	});
+}
@@ -1061,6 +1061,9 @@ impl<T: Subtrait> system::Trait for ElevatedTrait<T> {
 	type MaximumBlockWeight = T::MaximumBlockWeight;
 	type MaximumBlockLength = T::MaximumBlockLength;
 	type AvailableBlockRatio = T::AvailableBlockRatio;
+	type Version = T::Version;
+	type Call = T::Call;
+	type BaseCallFilter = T::BaseCallFilter;
 	type WeightMultiplierUpdate = ();
 	type BlockHashCount = T::BlockHashCount;
 }
@@ -55,6 +55,9 @@ impl system::Trait for Test {
 	type MaximumBlockWeight = MaximumBlockWeight;
 	type MaximumBlockLength = MaximumBlockLength;
 	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = ();
+	type Call = ();
+	type BaseCallFilter = ();
 	type BlockHashCount = BlockHashCount;
 }
 
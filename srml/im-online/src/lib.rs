@@ -72,12 +72,11 @@ use primitives::{
 	crypto::TypedKey, offchain::CryptoKey,
 	offchain::OpaqueNetworkState,
 	offchain::StorageKind,
-	sr25519, ed25519,
 };
 use codec::{Encode, Decode};
 use sr_primitives::{
 	ApplyError, traits::{Member, IsMember, Extrinsic as ExtrinsicT},
-	transaction_validity::{TransactionValidity, TransactionLongevity, ValidTransaction},
+	transaction_validity::{TransactionValidity, TransactionSource},
 };
 use rstd::prelude::*;
 use session::SessionIndex;
@@ -85,6 +84,7 @@ use sr_io::Printable;
 use srml_support::{
 	Parameter, StorageValue, decl_module, decl_event, decl_storage,
 	traits::Get, StorageDoubleMap, print,
+	unsigned::{SignedPayload, SignedPayloadValidity, validate_signed_payload},
 };
 use system::ensure_none;
 
@@ -139,6 +139,17 @@ pub struct Heartbeat<BlockNumber, AuthorityId>
 	authority_id: AuthorityId,
 }
 
+impl<BlockNumber, AuthorityId> SignedPayload for Heartbeat<BlockNumber, AuthorityId> where
+	BlockNumber: PartialEq + Eq + Decode + Encode,
+	AuthorityId: TypedKey + AsRef<[u8]> + Clone + Encode,
+{
+	type Public = AuthorityId;
+
+	fn public(&self) -> &AuthorityId {
+		&self.authority_id
+	}
+}
+
 pub trait Trait: system::Trait + session::Trait {
 	/// The overarching event type.
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
@@ -403,7 +414,10 @@ impl<T: Trait> session::OneSessionHandler<T::AccountId> for Module<T> {
 impl<T: Trait> srml_support::unsigned::ValidateUnsigned for Module<T> {
 	type Call = Call<T>;
 
-	fn validate_unsigned(call: &Self::Call) -> srml_support::unsigned::TransactionValidity {
+	fn validate_unsigned(
+		_source: TransactionSource,
+		call: &Self::Call,
+	) -> srml_support::unsigned::TransactionValidity {
 		if let Call::heartbeat(heartbeat, signature) = call {
 			// verify that the incoming (unverified) pubkey is actually an authority id
 			let is_authority = T::IsValidAuthorityId::is_member(&heartbeat.authority_id);
@@ -416,43 +430,13 @@ impl<T: Trait> srml_support::unsigned::ValidateUnsigned for Module<T> {
 				return TransactionValidity::Invalid(ApplyError::BadSignature as i8);
 			}
 
-			if signature.len() != 64 {
-				return TransactionValidity::Invalid(ApplyError::BadSignature as i8);
-			}
-
-			let signature = {
-				  let mut array = [0; 64];
-				  array.copy_from_slice(&signature); // panics if not enough, hence the check above
-				  array
-			};
-
-			let encoded_heartbeat = heartbeat.encode();
-
-			let signature_valid = match <T::AuthorityId as TypedKey>::KEY_TYPE {
-				ed25519::Public::KEY_TYPE =>
-					sr_io::ed25519_verify(&signature, &encoded_heartbeat, &heartbeat.authority_id),
-				sr25519::Public::KEY_TYPE =>
-					sr_io::sr25519_verify(&signature, &encoded_heartbeat, &heartbeat.authority_id),
-				_ => return TransactionValidity::Invalid(ApplyError::BadSignature as i8),
-			};
-
-			if !signature_valid {
-				return TransactionValidity::Invalid(ApplyError::BadSignature as i8);
-			}
-
 			// check if session index from heartbeat is recent
 			let current_session = <session::Module<T>>::current_index();
 			if heartbeat.session_index < current_session {
 				return TransactionValidity::Invalid(ApplyError::BadSignature as i8);
 			}
 
-			return TransactionValidity::Valid(ValidTransaction {
-				priority: 0,
-				requires: vec![],
-				provides: vec![encoded_heartbeat],
-				longevity: TransactionLongevity::max_value(),
-				propagate: true,
-			})
+			return validate_signed_payload(heartbeat, signature, SignedPayloadValidity::default());
 		}
 		TransactionValidity::Invalid(0)
 	}
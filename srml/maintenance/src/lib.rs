@@ -0,0 +1,377 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Maintenance Module
+//!
+//! The Maintenance module allows a privileged origin to pause and unpause dispatchable functions,
+//! either a whole module at once or individual calls, without a runtime upgrade.
+//!
+//! ## Overview
+//!
+//! Modules and calls are identified by their indices in the runtime's aggregated `Call` type, i.e.
+//! the first two bytes of an encoded call: the index of the module in `construct_runtime!` and the
+//! index of the call within the module.
+//!
+//! The module implements `Contains<Call>` and is meant to be (part of) the `BaseCallFilter` of the
+//! system module. A paused call is then refused by the executive: it is invalid for the
+//! transaction queue and can not be included in a block.
+//!
+//! The calls of this module are never paused, so that anything paused can always be unpaused.
+//! Neither are the calls of the `UnpausableModules`, which should include the modules the
+//! `PauseOrigin` dispatches through, e.g. sudo or a collective.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! Only the `PauseOrigin` can call the dispatchable functions of this module.
+//!
+//! * `pause_module` - Pause all calls of a module.
+//! * `unpause_module` - Unpause a module paused by `pause_module`.
+//! * `pause_call` - Pause a single call of a module.
+//! * `unpause_call` - Unpause a call paused by `pause_call`.
+//!
+//! ## Genesis Config
+//!
+//! Modules and calls can be paused from genesis on, e.g. to launch a chain with transfers disabled
+//! and enable them later on through governance.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use rstd::prelude::*;
+use codec::Encode;
+use srml_support::{
+	StorageMap, IsSubType, decl_module, decl_event, decl_storage, ensure, traits::{Contains, Get},
+};
+use sr_primitives::traits::EnsureOrigin;
+use sr_primitives::weights::SimpleDispatchInfo;
+
+/// Index of a module in the runtime's aggregated `Call` type.
+pub type ModuleIndex = u8;
+
+/// Index of a call within its module.
+pub type CallIndex = u8;
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event> + Into<<Self as system::Trait>::Event>;
+
+	/// The origin which may pause and unpause modules and calls.
+	type PauseOrigin: EnsureOrigin<Self::Origin>;
+
+	/// The modules that can't be paused, such as those the `PauseOrigin` dispatches through.
+	type UnpausableModules: Get<Vec<ModuleIndex>>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Maintenance {
+		/// The modules of which all calls are paused.
+		pub PausedModules get(is_module_paused) config(paused_modules): map ModuleIndex => bool;
+
+		/// The individually paused calls.
+		pub PausedCalls get(is_call_paused) config(paused_calls): map (ModuleIndex, CallIndex) => bool;
+	}
+}
+
+decl_event!(
+	pub enum Event {
+		/// All calls of a module have been paused.
+		ModulePaused(ModuleIndex),
+		/// A module has been unpaused.
+		ModuleUnpaused(ModuleIndex),
+		/// A call has been paused.
+		CallPaused(ModuleIndex, CallIndex),
+		/// A call has been unpaused.
+		CallUnpaused(ModuleIndex, CallIndex),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		/// Pause all calls of the module at `module`.
+		///
+		/// The dispatch origin for this call must be the `PauseOrigin`.
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		fn pause_module(origin, module: ModuleIndex) {
+			T::PauseOrigin::ensure_origin(origin)?;
+			ensure!(!T::UnpausableModules::get().contains(&module), "module can't be paused");
+			ensure!(!Self::is_module_paused(module), "module is already paused");
+
+			<PausedModules>::insert(module, true);
+			Self::deposit_event(Event::ModulePaused(module));
+		}
+
+		/// Unpause the module at `module`. Calls of it that were paused individually stay paused.
+		///
+		/// The dispatch origin for this call must be the `PauseOrigin`.
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		fn unpause_module(origin, module: ModuleIndex) {
+			T::PauseOrigin::ensure_origin(origin)?;
+			ensure!(Self::is_module_paused(module), "module is not paused");
+
+			<PausedModules>::remove(module);
+			Self::deposit_event(Event::ModuleUnpaused(module));
+		}
+
+		/// Pause the call at `call` of the module at `module`.
+		///
+		/// The dispatch origin for this call must be the `PauseOrigin`.
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		fn pause_call(origin, module: ModuleIndex, call: CallIndex) {
+			T::PauseOrigin::ensure_origin(origin)?;
+			ensure!(!T::UnpausableModules::get().contains(&module), "module can't be paused");
+			ensure!(!Self::is_call_paused((module, call)), "call is already paused");
+
+			<PausedCalls>::insert((module, call), true);
+			Self::deposit_event(Event::CallPaused(module, call));
+		}
+
+		/// Unpause the call at `call` of the module at `module`.
+		///
+		/// The dispatch origin for this call must be the `PauseOrigin`.
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		fn unpause_call(origin, module: ModuleIndex, call: CallIndex) {
+			T::PauseOrigin::ensure_origin(origin)?;
+			ensure!(Self::is_call_paused((module, call)), "call is not paused");
+
+			<PausedCalls>::remove((module, call));
+			Self::deposit_event(Event::CallUnpaused(module, call));
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Whether `call` is paused, either by itself or because its whole module is.
+	///
+	/// The calls of the `UnpausableModules` are never paused, even by the genesis config.
+	pub fn is_paused<Call: Encode>(call: &Call) -> bool {
+		call.using_encoded(|encoded| {
+			if encoded.len() < 2 {
+				return false;
+			}
+			let (module, call_index) = (encoded[0], encoded[1]);
+			if T::UnpausableModules::get().contains(&module) {
+				return false;
+			}
+			Self::is_module_paused(module) || Self::is_call_paused((module, call_index))
+		})
+	}
+}
+
+impl<T: Trait> Contains<<T as system::Trait>::Call> for Module<T> where
+	<T as system::Trait>::Call: IsSubType<Module<T>, T>,
+{
+	fn contains(call: &<T as system::Trait>::Call) -> bool {
+		// calls of this module can't be paused; otherwise nothing could ever be unpaused.
+		if call.is_aux_sub_type().is_some() {
+			return true;
+		}
+		!Self::is_paused(call)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use runtime_io::with_externalities;
+	use srml_support::{impl_outer_origin, impl_outer_dispatch, assert_ok, assert_noop, parameter_types};
+	use primitives::{H256, Blake2Hasher};
+	use sr_primitives::{Perbill, traits::{BlakeTwo256, IdentityLookup}, testing::Header};
+	use system::EnsureRoot;
+	use balances::Call as BalancesCall;
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	impl_outer_dispatch! {
+		pub enum Call for Test where origin: Origin {
+			balances::Balances,
+			maintenance::Maintenance,
+			system::System,
+		}
+	}
+
+	#[derive(Clone, Eq, PartialEq, Debug)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: u32 = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+	}
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<u64>;
+		type Header = Header;
+		type WeightMultiplierUpdate = ();
+		type Event = ();
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+		type Call = Call;
+		type BaseCallFilter = Maintenance;
+	}
+	parameter_types! {
+		pub const ExistentialDeposit: u64 = 0;
+		pub const TransferFee: u64 = 0;
+		pub const CreationFee: u64 = 0;
+	}
+	impl balances::Trait for Test {
+		type Balance = u64;
+		type OnFreeBalanceZero = ();
+		type OnNewAccount = ();
+		type Event = ();
+		type TransferPayment = ();
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+	}
+	parameter_types! {
+		pub const UnpausableModules: Vec<ModuleIndex> = vec![SYSTEM];
+	}
+	impl Trait for Test {
+		type Event = ();
+		type PauseOrigin = EnsureRoot<u64>;
+		type UnpausableModules = UnpausableModules;
+	}
+
+	type System = system::Module<Test>;
+	type Balances = balances::Module<Test>;
+	type Maintenance = Module<Test>;
+	type BaseCallFilter = <Test as system::Trait>::BaseCallFilter;
+
+	const BALANCES: ModuleIndex = 0;
+	const MAINTENANCE: ModuleIndex = 1;
+	const SYSTEM: ModuleIndex = 2;
+
+	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		system::GenesisConfig::default().build_storage::<Test>().unwrap().0.into()
+	}
+
+	fn transfer() -> Call {
+		Call::Balances(BalancesCall::transfer(2, 10))
+	}
+
+	fn set_balance() -> Call {
+		Call::Balances(BalancesCall::set_balance(2, 10, 0))
+	}
+
+	fn call_index(call: &Call) -> CallIndex {
+		call.encode()[1]
+	}
+
+	#[test]
+	fn pausing_a_module_works() {
+		with_externalities(&mut new_test_ext(), || {
+			assert!(BaseCallFilter::contains(&transfer()));
+
+			assert_ok!(Maintenance::pause_module(Origin::ROOT, BALANCES));
+			assert!(!BaseCallFilter::contains(&transfer()));
+			assert!(!BaseCallFilter::contains(&set_balance()));
+			assert_noop!(Maintenance::pause_module(Origin::ROOT, BALANCES), "module is already paused");
+
+			assert_ok!(Maintenance::unpause_module(Origin::ROOT, BALANCES));
+			assert!(BaseCallFilter::contains(&transfer()));
+			assert!(BaseCallFilter::contains(&set_balance()));
+			assert_noop!(Maintenance::unpause_module(Origin::ROOT, BALANCES), "module is not paused");
+		});
+	}
+
+	#[test]
+	fn pausing_a_call_works() {
+		with_externalities(&mut new_test_ext(), || {
+			let transfer_index = call_index(&transfer());
+
+			assert_ok!(Maintenance::pause_call(Origin::ROOT, BALANCES, transfer_index));
+			assert!(!BaseCallFilter::contains(&transfer()));
+			assert!(BaseCallFilter::contains(&set_balance()));
+
+			// unpausing the module keeps the call paused.
+			assert_ok!(Maintenance::pause_module(Origin::ROOT, BALANCES));
+			assert_ok!(Maintenance::unpause_module(Origin::ROOT, BALANCES));
+			assert!(!BaseCallFilter::contains(&transfer()));
+
+			assert_ok!(Maintenance::unpause_call(Origin::ROOT, BALANCES, transfer_index));
+			assert!(BaseCallFilter::contains(&transfer()));
+			assert_noop!(
+				Maintenance::unpause_call(Origin::ROOT, BALANCES, transfer_index),
+				"call is not paused"
+			);
+		});
+	}
+
+	#[test]
+	fn maintenance_calls_can_not_be_paused() {
+		with_externalities(&mut new_test_ext(), || {
+			let unpause = Call::Maintenance(super::Call::unpause_module(MAINTENANCE));
+
+			assert_ok!(Maintenance::pause_module(Origin::ROOT, MAINTENANCE));
+			assert!(BaseCallFilter::contains(&unpause));
+		});
+	}
+
+	#[test]
+	fn unpausable_modules_can_not_be_paused() {
+		with_externalities(&mut new_test_ext(), || {
+			let remark = Call::System(system::Call::remark(Vec::new()));
+
+			assert_noop!(Maintenance::pause_module(Origin::ROOT, SYSTEM), "module can't be paused");
+			assert_noop!(
+				Maintenance::pause_call(Origin::ROOT, SYSTEM, call_index(&remark)),
+				"module can't be paused"
+			);
+			assert!(BaseCallFilter::contains(&remark));
+		});
+	}
+
+	#[test]
+	fn only_pause_origin_can_pause() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_noop!(Maintenance::pause_module(Origin::signed(1), BALANCES), "Invalid origin");
+			assert_noop!(Maintenance::pause_call(Origin::signed(1), BALANCES, 0), "Invalid origin");
+			assert!(BaseCallFilter::contains(&transfer()));
+		});
+	}
+
+	#[test]
+	fn genesis_config_pauses() {
+		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap().0;
+		t.extend(GenesisConfig {
+			paused_modules: vec![(SYSTEM, true)],
+			paused_calls: vec![((BALANCES, call_index(&transfer())), true)],
+		}.build_storage::<Test>().unwrap().0);
+		let mut t: runtime_io::TestExternalities<Blake2Hasher> = t.into();
+
+		with_externalities(&mut t, || {
+			assert!(!BaseCallFilter::contains(&transfer()));
+			assert!(BaseCallFilter::contains(&set_balance()));
+			// the unpausable modules stay unpaused.
+			assert!(BaseCallFilter::contains(&Call::System(system::Call::remark(Vec::new()))));
+		});
+	}
+}
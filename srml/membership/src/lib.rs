@@ -0,0 +1,463 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Membership Module
+//!
+//! The Membership module registers members, identified by a `MemberId`, with a profile.
+//!
+//! - [`membership::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! Accounts buy a membership under one of the paid terms set by root, paying its fee. Members
+//! have a unique handle, an avatar and a description.
+//!
+//! Each member has two accounts: the root account, which can change both accounts, and the
+//! controller account, which acts on behalf of the member and manages its profile. Both are the
+//! buyer of the membership at first.
+//!
+//! Other modules should refer to members by their identifier, through the `MemberAccounts` trait
+//! implemented by this module, rather than by their accounts.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `add_paid_terms` - Add paid terms.
+//! - `set_paid_terms_active` - Make paid terms available or not.
+//! - `buy_membership` - Buy a membership.
+//! - `update_profile` - Update the profile of a member.
+//! - `set_controller_account` - Change the controller account of a member.
+//! - `set_root_account` - Change the root account of a member.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod tests;
+
+use rstd::prelude::*;
+use rstd::marker::PhantomData;
+use codec::{Encode, Decode};
+use srml_support::{StorageValue, StorageMap, Parameter, decl_module, decl_storage, decl_event, ensure};
+use srml_support::dispatch::Result;
+use srml_support::traits::{
+	Currency, ExistenceRequirement, Get, MemberAccounts, OnUnbalanced, WithdrawReason
+};
+use sr_primitives::traits::{SimpleArithmetic, StaticLookup, One, SaturatedConversion};
+use sr_primitives::weights::{Weight, WeighData, ClassifyDispatch, DispatchClass, SimpleDispatchInfo};
+use system::{ensure_signed, ensure_root};
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
+
+/// The weight of the calls besides the bytes and member identifiers they read or write.
+const BASE_WEIGHT: Weight = 100_000;
+/// The weight of reading or writing a byte of paid terms or profiles.
+const BYTE_WEIGHT: Weight = 100;
+/// The weight of walking through a member identifier of an account.
+const MEMBER_ID_WEIGHT: Weight = 1_000;
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The currency the memberships are paid in.
+	type Currency: Currency<Self::AccountId>;
+
+	/// The identifier of a member.
+	type MemberId: Parameter + SimpleArithmetic + Default + Copy;
+
+	/// The identifier of paid terms.
+	type PaidTermsId: Parameter + SimpleArithmetic + Default + Copy;
+
+	/// Handler for the membership fees.
+	type MembershipFees: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+	/// The minimum length of a handle.
+	type MinHandleLength: Get<u32>;
+
+	/// The maximum length of a handle.
+	type MaxHandleLength: Get<u32>;
+
+	/// The maximum length of an avatar URI.
+	type MaxAvatarUriLength: Get<u32>;
+
+	/// The maximum length of the description of a member.
+	type MaxAboutLength: Get<u32>;
+}
+
+/// Terms under which a membership can be bought.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct PaidTerms<Balance> {
+	/// The fee of the membership.
+	pub fee: Balance,
+	/// The text of the terms.
+	pub text: Vec<u8>,
+	/// Whether a membership can be bought under the terms.
+	pub active: bool,
+}
+
+/// The profile of a member.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Profile<AccountId, BlockNumber, PaidTermsId> {
+	/// The unique handle of the member.
+	pub handle: Vec<u8>,
+	/// The URI of the avatar of the member.
+	pub avatar_uri: Vec<u8>,
+	/// The description of the member.
+	pub about: Vec<u8>,
+	/// The block at which the membership was bought.
+	pub registered_at: BlockNumber,
+	/// The terms under which the membership was bought.
+	pub paid_terms: PaidTermsId,
+	/// The account that can change the accounts of the member.
+	pub root_account: AccountId,
+	/// The account acting on behalf of the member.
+	pub controller_account: AccountId,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Membership {
+		/// The identifier of the next paid terms.
+		pub NextPaidTermsId get(next_paid_terms_id): T::PaidTermsId;
+		/// The paid terms.
+		pub PaidTermsById get(paid_terms): map T::PaidTermsId => Option<PaidTerms<BalanceOf<T>>>;
+
+		/// The identifier of the next member.
+		pub NextMemberId get(next_member_id): T::MemberId;
+		/// The profiles of the members.
+		pub Profiles get(profile): map T::MemberId => Option<Profile<T::AccountId, T::BlockNumber, T::PaidTermsId>>;
+		/// The member of each handle.
+		pub MemberIdByHandle get(member_id_by_handle): map Vec<u8> => Option<T::MemberId>;
+		/// The members of which an account is the root account.
+		pub MemberIdsByRootAccount get(member_ids_by_root_account): map T::AccountId => Vec<T::MemberId>;
+		/// The members of which an account is the controller account.
+		pub MemberIdsByControllerAccount get(member_ids_by_controller_account): map T::AccountId => Vec<T::MemberId>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as Trait>::MemberId,
+		<T as Trait>::PaidTermsId,
+	{
+		/// Paid terms were added.
+		PaidTermsAdded(PaidTermsId),
+		/// Paid terms were made available (`true`) or not (`false`).
+		PaidTermsActive(PaidTermsId, bool),
+		/// A membership was bought by the account.
+		MemberRegistered(MemberId, AccountId),
+		/// The profile of a member was updated.
+		ProfileUpdated(MemberId),
+		/// The controller account of a member was changed.
+		ControllerAccountSet(MemberId, AccountId),
+		/// The root account of a member was changed.
+		RootAccountSet(MemberId, AccountId),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		/// The minimum length of a handle.
+		const MinHandleLength: u32 = T::MinHandleLength::get();
+
+		/// The maximum length of a handle.
+		const MaxHandleLength: u32 = T::MaxHandleLength::get();
+
+		/// The maximum length of an avatar URI.
+		const MaxAvatarUriLength: u32 = T::MaxAvatarUriLength::get();
+
+		/// The maximum length of the description of a member.
+		const MaxAboutLength: u32 = T::MaxAboutLength::get();
+
+		fn deposit_event() = default;
+
+		/// Add paid terms with the fee `fee`.
+		///
+		/// The origin must be root.
+		#[weight = BytesWeight::<T>::new(DispatchClass::Operational)]
+		fn add_paid_terms(origin, fee: BalanceOf<T>, text: Vec<u8>) -> Result {
+			ensure_root(origin)?;
+
+			let id = Self::next_paid_terms_id();
+			<NextPaidTermsId<T>>::mutate(|id| *id += One::one());
+			<PaidTermsById<T>>::insert(id, PaidTerms { fee, text, active: true });
+			Self::deposit_event(RawEvent::PaidTermsAdded(id));
+
+			Ok(())
+		}
+
+		/// Make the paid terms `terms_id` available, or not if `active` is `false`. Existing
+		/// memberships are kept.
+		///
+		/// The origin must be root.
+		#[weight = SimpleDispatchInfo::FixedOperational(BASE_WEIGHT)]
+		fn set_paid_terms_active(origin, #[compact] terms_id: T::PaidTermsId, active: bool) -> Result {
+			ensure_root(origin)?;
+			let mut terms = Self::paid_terms(terms_id).ok_or("Unknown paid terms")?;
+
+			terms.active = active;
+			<PaidTermsById<T>>::insert(terms_id, terms);
+			Self::deposit_event(RawEvent::PaidTermsActive(terms_id, active));
+
+			Ok(())
+		}
+
+		/// Buy a membership under the paid terms `terms_id`, paying its fee.
+		///
+		/// The sender becomes both the root and controller account of the member.
+		#[weight = BytesWeight::<T>::new(DispatchClass::Normal)]
+		fn buy_membership(
+			origin,
+			#[compact] terms_id: T::PaidTermsId,
+			handle: Vec<u8>,
+			avatar_uri: Vec<u8>,
+			about: Vec<u8>
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let terms = Self::paid_terms(terms_id).ok_or("Unknown paid terms")?;
+			ensure!(terms.active, "Paid terms are not active");
+			Self::ensure_handle_available(&handle)?;
+			Self::ensure_avatar_uri_length(&avatar_uri)?;
+			Self::ensure_about_length(&about)?;
+
+			let fee = T::Currency::withdraw(&who, terms.fee, WithdrawReason::Fee, ExistenceRequirement::KeepAlive)?;
+			T::MembershipFees::on_unbalanced(fee);
+
+			let id = Self::next_member_id();
+			<NextMemberId<T>>::mutate(|id| *id += One::one());
+			<MemberIdByHandle<T>>::insert(&handle, id);
+			<MemberIdsByRootAccount<T>>::mutate(&who, |ids| ids.push(id));
+			<MemberIdsByControllerAccount<T>>::mutate(&who, |ids| ids.push(id));
+			<Profiles<T>>::insert(id, Profile {
+				handle,
+				avatar_uri,
+				about,
+				registered_at: <system::Module<T>>::block_number(),
+				paid_terms: terms_id,
+				root_account: who.clone(),
+				controller_account: who.clone(),
+			});
+			Self::deposit_event(RawEvent::MemberRegistered(id, who));
+
+			Ok(())
+		}
+
+		/// Update the given fields of the profile of the member `member_id`.
+		///
+		/// The sender must be the controller account of the member.
+		#[weight = BytesWeight::<T>::new(DispatchClass::Normal)]
+		fn update_profile(
+			origin,
+			#[compact] member_id: T::MemberId,
+			handle: Option<Vec<u8>>,
+			avatar_uri: Option<Vec<u8>>,
+			about: Option<Vec<u8>>
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let mut profile = Self::profile(member_id).ok_or("Unknown member")?;
+			ensure!(profile.controller_account == who, "Not the controller account of the member");
+
+			if let Some(handle) = handle.as_ref() {
+				if *handle != profile.handle {
+					Self::ensure_handle_available(handle)?;
+				}
+			}
+			if let Some(avatar_uri) = avatar_uri.as_ref() {
+				Self::ensure_avatar_uri_length(avatar_uri)?;
+			}
+			if let Some(about) = about.as_ref() {
+				Self::ensure_about_length(about)?;
+			}
+
+			if let Some(handle) = handle {
+				<MemberIdByHandle<T>>::remove(&profile.handle);
+				<MemberIdByHandle<T>>::insert(&handle, member_id);
+				profile.handle = handle;
+			}
+			if let Some(avatar_uri) = avatar_uri {
+				profile.avatar_uri = avatar_uri;
+			}
+			if let Some(about) = about {
+				profile.about = about;
+			}
+			<Profiles<T>>::insert(member_id, profile);
+			Self::deposit_event(RawEvent::ProfileUpdated(member_id));
+
+			Ok(())
+		}
+
+		/// Change the controller account of the member `member_id` to `new`.
+		///
+		/// The sender must be the root account of the member.
+		#[weight = AccountsWeight::<T>::default()]
+		fn set_controller_account(
+			origin,
+			#[compact] member_id: T::MemberId,
+			new: <T::Lookup as StaticLookup>::Source
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let new = T::Lookup::lookup(new)?;
+			let mut profile = Self::profile(member_id).ok_or("Unknown member")?;
+			ensure!(profile.root_account == who, "Not the root account of the member");
+
+			<MemberIdsByControllerAccount<T>>::mutate(&profile.controller_account, |ids| ids.retain(|id| *id != member_id));
+			<MemberIdsByControllerAccount<T>>::mutate(&new, |ids| ids.push(member_id));
+			profile.controller_account = new.clone();
+			<Profiles<T>>::insert(member_id, profile);
+			Self::deposit_event(RawEvent::ControllerAccountSet(member_id, new));
+
+			Ok(())
+		}
+
+		/// Change the root account of the member `member_id` to `new`.
+		///
+		/// The sender must be the root account of the member.
+		#[weight = AccountsWeight::<T>::default()]
+		fn set_root_account(
+			origin,
+			#[compact] member_id: T::MemberId,
+			new: <T::Lookup as StaticLookup>::Source
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let new = T::Lookup::lookup(new)?;
+			let mut profile = Self::profile(member_id).ok_or("Unknown member")?;
+			ensure!(profile.root_account == who, "Not the root account of the member");
+
+			<MemberIdsByRootAccount<T>>::mutate(&who, |ids| ids.retain(|id| *id != member_id));
+			<MemberIdsByRootAccount<T>>::mutate(&new, |ids| ids.push(member_id));
+			profile.root_account = new.clone();
+			<Profiles<T>>::insert(member_id, profile);
+			Self::deposit_event(RawEvent::RootAccountSet(member_id, new));
+
+			Ok(())
+		}
+	}
+}
+
+/// Weight of the calls which write paid terms or a profile: a step per byte they write, and per
+/// byte of the paid terms a membership is bought under.
+pub struct BytesWeight<T>(DispatchClass, PhantomData<T>);
+
+impl<T> BytesWeight<T> {
+	/// The weight of a call of the dispatch `class`.
+	pub fn new(class: DispatchClass) -> Self {
+		BytesWeight(class, PhantomData)
+	}
+
+	fn weight(bytes: usize) -> Weight {
+		let bytes: Weight = bytes.saturated_into();
+		BASE_WEIGHT.saturating_add(BYTE_WEIGHT.saturating_mul(bytes))
+	}
+}
+
+impl<T: Trait> WeighData<(&BalanceOf<T>, &Vec<u8>)> for BytesWeight<T> {
+	fn weigh_data(&self, (_, text): (&BalanceOf<T>, &Vec<u8>)) -> Weight {
+		Self::weight(text.len())
+	}
+}
+
+impl<T: Trait> WeighData<(&T::PaidTermsId, &Vec<u8>, &Vec<u8>, &Vec<u8>)> for BytesWeight<T> {
+	fn weigh_data(
+		&self,
+		(terms_id, handle, avatar_uri, about): (&T::PaidTermsId, &Vec<u8>, &Vec<u8>, &Vec<u8>),
+	) -> Weight {
+		let terms = Module::<T>::paid_terms(terms_id).map_or(0, |terms| terms.text.len());
+		Self::weight(terms + handle.len() + avatar_uri.len() + about.len())
+	}
+}
+
+impl<T: Trait> WeighData<(&T::MemberId, &Option<Vec<u8>>, &Option<Vec<u8>>, &Option<Vec<u8>>)> for BytesWeight<T> {
+	fn weigh_data(
+		&self,
+		(_, handle, avatar_uri, about): (&T::MemberId, &Option<Vec<u8>>, &Option<Vec<u8>>, &Option<Vec<u8>>),
+	) -> Weight {
+		let len = |field: &Option<Vec<u8>>| field.as_ref().map_or(0, Vec::len);
+		Self::weight(len(handle) + len(avatar_uri) + len(about))
+	}
+}
+
+impl<T, A> ClassifyDispatch<A> for BytesWeight<T> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		self.0
+	}
+}
+
+/// Weight of `set_controller_account` and `set_root_account`: a step per member identifier of the
+/// current root and controller accounts of the member.
+pub struct AccountsWeight<T>(PhantomData<T>);
+
+impl<T> Default for AccountsWeight<T> {
+	fn default() -> Self {
+		AccountsWeight(PhantomData)
+	}
+}
+
+impl<T: Trait> WeighData<(&T::MemberId, &<T::Lookup as StaticLookup>::Source)> for AccountsWeight<T> {
+	fn weigh_data(&self, (member_id, _): (&T::MemberId, &<T::Lookup as StaticLookup>::Source)) -> Weight {
+		let member_ids: Weight = Module::<T>::profile(member_id)
+			.map_or(0, |profile| {
+				Module::<T>::member_ids_by_root_account(&profile.root_account).len() +
+					Module::<T>::member_ids_by_controller_account(&profile.controller_account).len()
+			})
+			.saturated_into();
+		BASE_WEIGHT.saturating_add(MEMBER_ID_WEIGHT.saturating_mul(member_ids))
+	}
+}
+
+impl<T, A> ClassifyDispatch<A> for AccountsWeight<T> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Ensure `who` is the controller account of the member `member_id`.
+	pub fn ensure_member_controller(who: &T::AccountId, member_id: &T::MemberId) -> Result {
+		ensure!(Self::is_member_controller(member_id, who), "Not the controller account of the member");
+		Ok(())
+	}
+
+	fn ensure_handle_available(handle: &[u8]) -> Result {
+		ensure!(handle.len() >= T::MinHandleLength::get() as usize, "Handle is too short");
+		ensure!(handle.len() <= T::MaxHandleLength::get() as usize, "Handle is too long");
+		ensure!(!<MemberIdByHandle<T>>::exists(handle.to_vec()), "Handle is already taken");
+		Ok(())
+	}
+
+	fn ensure_avatar_uri_length(avatar_uri: &[u8]) -> Result {
+		ensure!(avatar_uri.len() <= T::MaxAvatarUriLength::get() as usize, "Avatar URI is too long");
+		Ok(())
+	}
+
+	fn ensure_about_length(about: &[u8]) -> Result {
+		ensure!(about.len() <= T::MaxAboutLength::get() as usize, "About is too long");
+		Ok(())
+	}
+}
+
+impl<T: Trait> MemberAccounts<T::AccountId> for Module<T> {
+	type MemberId = T::MemberId;
+
+	fn controller_account(member: &T::MemberId) -> Option<T::AccountId> {
+		Self::profile(member).map(|profile| profile.controller_account)
+	}
+}
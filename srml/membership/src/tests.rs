@@ -0,0 +1,172 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use super::*;
+
+use primitives::Blake2Hasher;
+use runtime_io::with_externalities;
+use sr_primitives::weights::GetDispatchInfo;
+use srml_support::{assert_noop, assert_ok, impl_test_runtime, parameter_types};
+
+impl_test_runtime! {
+	pub struct Test;
+	balances {
+		ExistentialDeposit = 0;
+		TransferFee = 0;
+		CreationFee = 0;
+	}
+}
+parameter_types! {
+	pub const MinHandleLength: u32 = 3;
+	pub const MaxHandleLength: u32 = 8;
+	pub const MaxAvatarUriLength: u32 = 16;
+	pub const MaxAboutLength: u32 = 16;
+}
+impl Trait for Test {
+	type Event = ();
+	type Currency = balances::Module<Test>;
+	type MemberId = u64;
+	type PaidTermsId = u32;
+	type MembershipFees = ();
+	type MinHandleLength = MinHandleLength;
+	type MaxHandleLength = MaxHandleLength;
+	type MaxAvatarUriLength = MaxAvatarUriLength;
+	type MaxAboutLength = MaxAboutLength;
+}
+type System = system::Module<Test>;
+type Balances = balances::Module<Test>;
+type Membership = Module<Test>;
+
+fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap().0;
+	t.extend(balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100), (3, 5)],
+		vesting: vec![],
+	}.build_storage().unwrap().0);
+	t.into()
+}
+
+fn buy(who: u64, handle: &[u8]) -> Result {
+	Membership::buy_membership(Origin::signed(who), 0, handle.to_vec(), vec![], vec![])
+}
+
+#[test]
+fn memberships_are_bought_under_active_paid_terms() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(2);
+		assert_noop!(
+			Membership::add_paid_terms(Origin::signed(1), 10, b"terms".to_vec()),
+			"bad origin: expected to be a root origin"
+		);
+		assert_noop!(buy(1, b"alice"), "Unknown paid terms");
+		assert_ok!(Membership::add_paid_terms(Origin::ROOT, 10, b"terms".to_vec()));
+
+		assert_noop!(buy(3, b"charlie"), "too few free funds in account");
+		assert_ok!(buy(1, b"alice"));
+		assert_eq!(Balances::free_balance(&1), 90);
+		assert_eq!(Membership::profile(0), Some(Profile {
+			handle: b"alice".to_vec(),
+			avatar_uri: vec![],
+			about: vec![],
+			registered_at: 2,
+			paid_terms: 0,
+			root_account: 1,
+			controller_account: 1,
+		}));
+		assert_eq!(Membership::member_id_by_handle(b"alice".to_vec()), Some(0));
+		assert_eq!(Membership::member_ids_by_root_account(1), vec![0]);
+
+		assert_ok!(Membership::set_paid_terms_active(Origin::ROOT, 0, false));
+		assert_noop!(buy(2, b"bob"), "Paid terms are not active");
+		assert!(Membership::profile(0).is_some());
+	});
+}
+
+#[test]
+fn handles_are_unique() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Membership::add_paid_terms(Origin::ROOT, 0, vec![]));
+		assert_noop!(buy(1, b"al"), "Handle is too short");
+		assert_noop!(buy(1, b"alexander"), "Handle is too long");
+		assert_ok!(buy(1, b"alice"));
+		assert_noop!(buy(2, b"alice"), "Handle is already taken");
+		assert_ok!(buy(2, b"bob"));
+
+		assert_noop!(
+			Membership::update_profile(Origin::signed(2), 1, Some(b"alice".to_vec()), None, None),
+			"Handle is already taken"
+		);
+		assert_noop!(
+			Membership::update_profile(Origin::signed(2), 0, Some(b"robert".to_vec()), None, None),
+			"Not the controller account of the member"
+		);
+		assert_ok!(Membership::update_profile(Origin::signed(2), 1, Some(b"bob".to_vec()), None, Some(b"hi".to_vec())));
+		assert_ok!(Membership::update_profile(Origin::signed(2), 1, Some(b"robert".to_vec()), None, None));
+		assert_eq!(Membership::member_id_by_handle(b"bob".to_vec()), None);
+		assert_eq!(Membership::member_id_by_handle(b"robert".to_vec()), Some(1));
+		assert_eq!(Membership::profile(1).unwrap().about, b"hi".to_vec());
+		assert_ok!(buy(1, b"bob"));
+	});
+}
+
+#[test]
+fn root_accounts_change_the_accounts_of_members() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Membership::add_paid_terms(Origin::ROOT, 0, vec![]));
+		assert_ok!(buy(1, b"alice"));
+
+		assert_ok!(Membership::set_controller_account(Origin::signed(1), 0, 2));
+		assert!(Membership::is_member_controller(&0, &2));
+		assert_eq!(Membership::controller_account(&0), Some(2));
+		assert_ok!(Membership::ensure_member_controller(&2, &0));
+		assert_noop!(Membership::ensure_member_controller(&1, &0), "Not the controller account of the member");
+		assert_eq!(Membership::member_ids_by_controller_account(1), Vec::<u64>::new());
+		assert_eq!(Membership::member_ids_by_controller_account(2), vec![0]);
+
+		// The controller account manages the profile, but not the accounts.
+		assert_ok!(Membership::update_profile(Origin::signed(2), 0, None, Some(b"ipfs://a".to_vec()), None));
+		assert_noop!(
+			Membership::update_profile(Origin::signed(1), 0, None, None, None),
+			"Not the controller account of the member"
+		);
+		assert_noop!(Membership::set_root_account(Origin::signed(2), 0, 2), "Not the root account of the member");
+
+		assert_ok!(Membership::set_root_account(Origin::signed(1), 0, 3));
+		assert_noop!(Membership::set_controller_account(Origin::signed(1), 0, 1), "Not the root account of the member");
+		assert_ok!(Membership::set_controller_account(Origin::signed(3), 0, 3));
+		assert_eq!(Membership::member_ids_by_root_account(3), vec![0]);
+		assert_eq!(Membership::controller_account(&1), None);
+	});
+}
+
+#[test]
+fn memberships_are_weighed_by_their_bytes_and_member_identifiers() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Membership::add_paid_terms(Origin::ROOT, 0, vec![]));
+		let weight = |call: Call<Test>| call.get_dispatch_info().weight;
+
+		let buy_call = |about: &[u8]| Call::buy_membership(0, b"alice".to_vec(), vec![], about.to_vec());
+		assert!(weight(buy_call(b"")) < weight(buy_call(b"hello")));
+		assert!(weight(Call::update_profile(0, None, None, None)) < weight(Call::update_profile(0, None, None, Some(b"a".to_vec()))));
+
+		assert_ok!(buy(1, b"alice"));
+		let one_member = weight(Call::set_root_account(0, 2));
+		assert_ok!(buy(1, b"bob"));
+		assert!(one_member < weight(Call::set_root_account(0, 2)));
+	});
+}
@@ -0,0 +1,323 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Minting Module
+//!
+//! The Minting module manages mints: sources of newly issued funds with a limited capacity.
+//!
+//! - [`minting::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! Each mint has a capacity, which every transfer out of the mint draws down. `GovernanceOrigin`
+//! creates the mints and adjusts their capacity.
+//!
+//! A mint can be refilled every period, to an absolute capacity or to a percentage of the total
+//! issuance. Refills never lower the capacity.
+//!
+//! Other modules, like the recurring rewards and the treasury, are funded from mints through the
+//! `MintFunds` trait.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `create_mint` - Create a mint.
+//! - `set_capacity` - Set the capacity of a mint.
+//! - `set_refill` - Set or unset the refill of a mint.
+//! - `remove_mint` - Remove a mint.
+//!
+//! ### Public Functions
+//!
+//! - `transfer_tokens` - Transfer funds out of a mint.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod tests;
+
+use rstd::prelude::*;
+use rstd::marker::PhantomData;
+use codec::{Encode, Decode};
+use srml_support::{StorageValue, StorageMap, Parameter, decl_module, decl_storage, decl_event, ensure};
+use srml_support::dispatch::Result;
+use srml_support::traits::{Currency, Imbalance, MintFunds};
+use sr_primitives::Permill;
+use sr_primitives::traits::{SimpleArithmetic, EnsureOrigin, One, Zero, SaturatedConversion};
+use sr_primitives::weights::{Weight, WeighData, ClassifyDispatch, DispatchClass, SimpleDispatchInfo};
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+
+/// The weight of the calls besides the mints refilled at the same block they walk through.
+const BASE_WEIGHT: Weight = 100_000;
+/// The weight of walking through a mint refilled at the same block.
+const MINT_ID_WEIGHT: Weight = 1_000;
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The currency minted.
+	type Currency: Currency<Self::AccountId>;
+
+	/// The identifier of a mint.
+	type MintId: Parameter + SimpleArithmetic + Default + Copy;
+
+	/// The origin that manages the mints.
+	type GovernanceOrigin: EnsureOrigin<Self::Origin>;
+}
+
+/// The capacity a mint is refilled to.
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum RefillPolicy<Balance> {
+	/// The given capacity.
+	Absolute(Balance),
+	/// The given part of the total issuance.
+	Percentage(Permill),
+}
+
+/// The periodic refill of a mint.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Refill<Balance, BlockNumber> {
+	/// The capacity the mint is refilled to.
+	pub policy: RefillPolicy<Balance>,
+	/// The number of blocks between refills.
+	pub period: BlockNumber,
+	/// The block of the next refill.
+	pub next_at: BlockNumber,
+}
+
+/// A mint.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Mint<Balance, BlockNumber> {
+	/// The amount that can still be transferred out of the mint.
+	pub capacity: Balance,
+	/// The periodic refill of the mint.
+	pub refill: Option<Refill<Balance, BlockNumber>>,
+	/// The amount transferred out of the mint so far.
+	pub total_minted: Balance,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Minting {
+		/// The identifier of the next mint.
+		pub NextMintId get(next_mint_id): T::MintId;
+		/// The mints.
+		pub Mints get(mint): map T::MintId => Option<Mint<BalanceOf<T>, T::BlockNumber>>;
+		/// The mints refilled at a block.
+		pub Refills get(refills): map T::BlockNumber => Vec<T::MintId>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as Trait>::MintId,
+		Balance = BalanceOf<T>,
+	{
+		/// A mint was created.
+		MintCreated(MintId),
+		/// The capacity of a mint was set.
+		CapacitySet(MintId, Balance),
+		/// The refill of a mint was set or unset.
+		RefillSet(MintId),
+		/// A mint was removed.
+		MintRemoved(MintId),
+		/// A mint was refilled to the capacity.
+		Refilled(MintId, Balance),
+		/// Funds were transferred out of a mint to the account.
+		Minted(MintId, AccountId, Balance),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		/// Create a mint with the capacity `capacity`, refilled every `period` blocks according
+		/// to `policy` if `refill` is given.
+		#[weight = RefillWeight::<T>::default()]
+		fn create_mint(
+			origin,
+			#[compact] capacity: BalanceOf<T>,
+			refill: Option<(RefillPolicy<BalanceOf<T>>, T::BlockNumber)>
+		) -> Result {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			let refill = Self::new_refill(refill)?;
+
+			let id = Self::next_mint_id();
+			<NextMintId<T>>::mutate(|id| *id += One::one());
+			if let Some(refill) = refill.as_ref() {
+				<Refills<T>>::mutate(refill.next_at, |mints| mints.push(id));
+			}
+			<Mints<T>>::insert(id, Mint { capacity, refill, total_minted: Zero::zero() });
+			Self::deposit_event(RawEvent::MintCreated(id));
+
+			Ok(())
+		}
+
+		/// Set the capacity of the mint `mint_id` to `capacity`.
+		#[weight = SimpleDispatchInfo::FixedOperational(BASE_WEIGHT)]
+		fn set_capacity(origin, #[compact] mint_id: T::MintId, #[compact] capacity: BalanceOf<T>) -> Result {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			let mut mint = Self::mint(mint_id).ok_or("Unknown mint")?;
+
+			mint.capacity = capacity;
+			<Mints<T>>::insert(mint_id, mint);
+			Self::deposit_event(RawEvent::CapacitySet(mint_id, capacity));
+
+			Ok(())
+		}
+
+		/// Refill the mint `mint_id` every `period` blocks according to `policy`, starting
+		/// `period` blocks from now, or stop refilling it if `refill` is `None`.
+		#[weight = RefillWeight::<T>::default()]
+		fn set_refill(
+			origin,
+			#[compact] mint_id: T::MintId,
+			refill: Option<(RefillPolicy<BalanceOf<T>>, T::BlockNumber)>
+		) -> Result {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			let mut mint = Self::mint(mint_id).ok_or("Unknown mint")?;
+			let refill = Self::new_refill(refill)?;
+
+			// The previous refill is skipped once due, as its block doesn't match anymore.
+			if let Some(refill) = refill.as_ref() {
+				<Refills<T>>::mutate(refill.next_at, |mints| mints.push(mint_id));
+			}
+			mint.refill = refill;
+			<Mints<T>>::insert(mint_id, mint);
+			Self::deposit_event(RawEvent::RefillSet(mint_id));
+
+			Ok(())
+		}
+
+		/// Remove the mint `mint_id`.
+		#[weight = SimpleDispatchInfo::FixedOperational(BASE_WEIGHT)]
+		fn remove_mint(origin, #[compact] mint_id: T::MintId) -> Result {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			ensure!(<Mints<T>>::exists(mint_id), "Unknown mint");
+
+			<Mints<T>>::remove(mint_id);
+			Self::deposit_event(RawEvent::MintRemoved(mint_id));
+
+			Ok(())
+		}
+
+		fn on_initialize(now: T::BlockNumber) {
+			for mint_id in <Refills<T>>::take(now) {
+				let mut mint = match Self::mint(mint_id) {
+					Some(mint) => mint,
+					None => continue,
+				};
+				let (capacity, next_at) = match mint.refill.as_mut() {
+					Some(refill) if refill.next_at == now => {
+						refill.next_at = now + refill.period;
+						let capacity = match refill.policy {
+							RefillPolicy::Absolute(capacity) => capacity,
+							RefillPolicy::Percentage(part) => part * T::Currency::total_issuance(),
+						};
+						(capacity, refill.next_at)
+					}
+					_ => continue,
+				};
+
+				if capacity > mint.capacity {
+					mint.capacity = capacity;
+					Self::deposit_event(RawEvent::Refilled(mint_id, capacity));
+				}
+				<Refills<T>>::mutate(next_at, |mints| mints.push(mint_id));
+				<Mints<T>>::insert(mint_id, mint);
+			}
+		}
+	}
+}
+
+/// Weight of `create_mint` and `set_refill`: a step per mint refilled at the block of the first
+/// refill.
+pub struct RefillWeight<T>(PhantomData<T>);
+
+impl<T> Default for RefillWeight<T> {
+	fn default() -> Self {
+		RefillWeight(PhantomData)
+	}
+}
+
+impl<T: Trait, M> WeighData<(M, &Option<(RefillPolicy<BalanceOf<T>>, T::BlockNumber)>)> for RefillWeight<T> {
+	fn weigh_data(&self, (_, refill): (M, &Option<(RefillPolicy<BalanceOf<T>>, T::BlockNumber)>)) -> Weight {
+		let mints: Weight = refill.as_ref()
+			.map_or(0, |(_, period)| {
+				Module::<T>::refills(<system::Module<T>>::block_number() + *period).len()
+			})
+			.saturated_into();
+		BASE_WEIGHT.saturating_add(MINT_ID_WEIGHT.saturating_mul(mints))
+	}
+}
+
+impl<T, A> ClassifyDispatch<A> for RefillWeight<T> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		DispatchClass::Operational
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Transfer `amount` out of the mint `mint_id` into the account `to`, drawing down the
+	/// capacity of the mint.
+	///
+	/// Fails without drawing down the capacity if `amount` can't be deposited, for instance
+	/// because it is below the existential deposit and `to` doesn't exist.
+	pub fn transfer_tokens(mint_id: T::MintId, to: &T::AccountId, amount: BalanceOf<T>) -> Result {
+		let mut mint = Self::mint(mint_id).ok_or("Unknown mint")?;
+		ensure!(amount <= mint.capacity, "Mint capacity exceeded");
+
+		// Nothing is deposited if the account can't be created.
+		let imbalance = T::Currency::deposit_creating(to, amount);
+		ensure!(imbalance.peek() == amount, "Destination account can't be created");
+		drop(imbalance);
+
+		mint.capacity -= amount;
+		mint.total_minted += amount;
+		<Mints<T>>::insert(mint_id, mint);
+		Self::deposit_event(RawEvent::Minted(mint_id, to.clone(), amount));
+
+		Ok(())
+	}
+
+	fn new_refill(
+		refill: Option<(RefillPolicy<BalanceOf<T>>, T::BlockNumber)>
+	) -> rstd::result::Result<Option<Refill<BalanceOf<T>, T::BlockNumber>>, &'static str> {
+		match refill {
+			Some((policy, period)) => {
+				ensure!(!period.is_zero(), "Refill period is zero");
+				let next_at = <system::Module<T>>::block_number() + period;
+				Ok(Some(Refill { policy, period, next_at }))
+			}
+			None => Ok(None),
+		}
+	}
+}
+
+impl<T: Trait> MintFunds<T::AccountId, BalanceOf<T>> for Module<T> {
+	type MintId = T::MintId;
+
+	fn mint_funds(mint: &T::MintId, to: &T::AccountId, amount: BalanceOf<T>) -> Result {
+		Self::transfer_tokens(*mint, to, amount)
+	}
+}
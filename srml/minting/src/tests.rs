@@ -0,0 +1,130 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use super::*;
+
+use primitives::Blake2Hasher;
+use runtime_io::with_externalities;
+use sr_primitives::weights::GetDispatchInfo;
+use srml_support::{assert_noop, assert_ok, impl_test_runtime, traits::OnInitialize};
+
+impl_test_runtime! {
+	pub struct Test;
+	balances {
+		ExistentialDeposit = 5;
+		TransferFee = 0;
+		CreationFee = 0;
+	}
+}
+impl Trait for Test {
+	type Event = ();
+	type Currency = balances::Module<Test>;
+	type MintId = u32;
+	type GovernanceOrigin = system::EnsureRoot<u64>;
+}
+type System = system::Module<Test>;
+type Balances = balances::Module<Test>;
+type Minting = Module<Test>;
+
+fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap().0;
+	t.extend(balances::GenesisConfig::<Test> {
+		balances: vec![(1, 1000)],
+		vesting: vec![],
+	}.build_storage().unwrap().0);
+	t.into()
+}
+
+fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		Minting::on_initialize(System::block_number());
+	}
+}
+
+#[test]
+fn transfers_draw_down_the_capacity() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_noop!(Minting::create_mint(Origin::signed(1), 100, None), "bad origin: expected to be a root origin");
+		assert_ok!(Minting::create_mint(Origin::ROOT, 100, None));
+
+		assert_ok!(Minting::transfer_tokens(0, &2, 60));
+		assert_noop!(Minting::transfer_tokens(0, &2, 50), "Mint capacity exceeded");
+		assert_noop!(Minting::transfer_tokens(0, &3, 4), "Destination account can't be created");
+		assert_noop!(Minting::transfer_tokens(1, &2, 10), "Unknown mint");
+		assert_ok!(<Minting as MintFunds<u64, u64>>::mint_funds(&0, &3, 40));
+		assert_eq!(Balances::free_balance(&2), 60);
+		assert_eq!(Balances::free_balance(&3), 40);
+		assert_eq!(Balances::total_issuance(), 1100);
+		assert_eq!(Minting::mint(0), Some(Mint { capacity: 0, refill: None, total_minted: 100 }));
+
+		assert_ok!(Minting::set_capacity(Origin::ROOT, 0, 10));
+		assert_ok!(Minting::transfer_tokens(0, &2, 10));
+		assert_ok!(Minting::remove_mint(Origin::ROOT, 0));
+		assert_noop!(Minting::transfer_tokens(0, &2, 0), "Unknown mint");
+	});
+}
+
+#[test]
+fn mints_are_refilled_every_period() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(1);
+		assert_noop!(
+			Minting::create_mint(Origin::ROOT, 0, Some((RefillPolicy::Absolute(50), 0))),
+			"Refill period is zero"
+		);
+		assert_ok!(Minting::create_mint(Origin::ROOT, 0, Some((RefillPolicy::Absolute(50), 5))));
+		assert_ok!(Minting::create_mint(Origin::ROOT, 0, Some((RefillPolicy::Percentage(Permill::from_percent(10)), 5))));
+
+		run_to_block(6);
+		assert_eq!(Minting::mint(0).unwrap().capacity, 50);
+		assert_eq!(Minting::mint(1).unwrap().capacity, 100);
+
+		// Refills top up the capacity, without lowering it.
+		assert_ok!(Minting::transfer_tokens(0, &2, 30));
+		assert_ok!(Minting::set_capacity(Origin::ROOT, 1, 500));
+		run_to_block(11);
+		assert_eq!(Minting::mint(0).unwrap().capacity, 50);
+		assert_eq!(Minting::mint(1).unwrap().capacity, 500);
+
+		// A new refill replaces the previous one.
+		assert_ok!(Minting::transfer_tokens(0, &2, 50));
+		assert_ok!(Minting::set_refill(Origin::ROOT, 0, Some((RefillPolicy::Absolute(20), 3))));
+		run_to_block(14);
+		assert_eq!(Minting::mint(0).unwrap().capacity, 20);
+		assert_ok!(Minting::set_refill(Origin::ROOT, 0, None));
+		assert_ok!(Minting::transfer_tokens(0, &2, 20));
+		run_to_block(30);
+		assert_eq!(Minting::mint(0).unwrap().capacity, 0);
+	});
+}
+
+#[test]
+fn refills_are_weighed_by_the_mints_refilled_at_the_same_block() {
+	with_externalities(&mut new_test_ext(), || {
+		let refill = Some((RefillPolicy::Absolute(100), 10));
+		let create = || Call::<Test>::create_mint(100, refill.clone()).get_dispatch_info();
+		let first = create();
+		assert_eq!(first.class, DispatchClass::Operational);
+
+		assert_ok!(Minting::create_mint(Origin::ROOT, 100, refill.clone()));
+		assert!(first.weight < create().weight);
+		assert_eq!(create().weight, Call::<Test>::set_refill(0, refill.clone()).get_dispatch_info().weight);
+		assert!(Call::<Test>::create_mint(100, None).get_dispatch_info().weight < create().weight);
+	});
+}
@@ -0,0 +1,403 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Oracle Module
+//!
+//! The Oracle module brings off-chain values, e.g. prices, on chain.
+//!
+//! - [`oracle::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! Values are fed for keys by feeders, which are either the accounts set with `set_feeders` or
+//! the current authorities, as decided by `IsValidAuthorityId`. The offchain worker of a node
+//! fetches values from `Source` and feeds them:
+//!
+//! - with a signed transaction for each key of `SubmitTransaction` whose account is a feeder,
+//! - with an unsigned transaction signed by the local authority key, if it is a current
+//!   authority.
+//!
+//! The current value of a key is the median of the values fed by the current feeders in the last
+//! `ExpiresIn` blocks, the lower one if there is an even number of them. Other modules read it
+//! through `DataProvider`.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `set_feeders` - Set the accounts allowed to feed values.
+//! - `feed_values` - Feed values from a feeder account.
+//! - `feed_values_unsigned` - Feed values signed by an authority.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod tests;
+
+use primitives::{crypto::TypedKey, offchain::CryptoKey};
+use codec::{Encode, Decode};
+use sr_primitives::{
+	ApplyError,
+	traits::{Member, IsMember, Extrinsic as ExtrinsicT, SaturatedConversion},
+	transaction_validity::{TransactionValidity, TransactionSource},
+};
+use rstd::prelude::*;
+use sr_io::Printable;
+use srml_support::{
+	Parameter, StorageValue, StorageMap, decl_module, decl_event, decl_storage, ensure, print,
+	traits::Get,
+	offchain::SubmitSignedTransaction,
+	unsigned::{SignedPayload, SignedPayloadValidity, validate_signed_payload},
+};
+use srml_support::dispatch::Result;
+use system::{ensure_none, ensure_root, ensure_signed};
+
+// Error which may occur while executing the off-chain code.
+enum OffchainErr {
+	DecodeAuthorityId,
+	ExtrinsicCreation,
+	FailedSigning,
+	SubmitTransaction,
+}
+
+impl Printable for OffchainErr {
+	fn print(self) {
+		match self {
+			OffchainErr::DecodeAuthorityId => print("Offchain error: decoding AuthorityId failed!"),
+			OffchainErr::ExtrinsicCreation => print("Offchain error: extrinsic creation failed!"),
+			OffchainErr::FailedSigning => print("Offchain error: signing failed!"),
+			OffchainErr::SubmitTransaction => print("Offchain error: submitting transaction failed!"),
+		}
+	}
+}
+
+/// Something that fetches the values to feed, e.g. from an HTTP API.
+///
+/// It is called by the offchain worker on every block.
+pub trait FetchValues<Key, Value> {
+	/// The values of the keys to feed, possibly none.
+	fn fetch_values() -> Vec<(Key, Value)>;
+}
+
+impl<Key, Value> FetchValues<Key, Value> for () {
+	fn fetch_values() -> Vec<(Key, Value)> {
+		Vec::new()
+	}
+}
+
+/// Something that provides the current values of keys.
+pub trait DataProvider<Key, Value> {
+	/// The current value of `key`, `None` if there is no fresh value.
+	fn get(key: &Key) -> Option<Value>;
+}
+
+/// The feeder of a value.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum Feeder<AccountId, AuthorityId> {
+	/// An account set with `set_feeders`.
+	Account(AccountId),
+	/// An authority.
+	Authority(AuthorityId),
+}
+
+/// A value and the block it was fed at.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct TimestampedValue<Value, BlockNumber> {
+	/// The value.
+	pub value: Value,
+	/// The block number the value was fed at.
+	pub at: BlockNumber,
+}
+
+/// Values fed by an authority in an unsigned transaction.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct FeedPayload<Key, Value, BlockNumber, AuthorityId> {
+	/// The values of the keys.
+	pub values: Vec<(Key, Value)>,
+	/// The block number the values were fetched at.
+	pub block_number: BlockNumber,
+	/// The authority signing the payload.
+	pub authority_id: AuthorityId,
+}
+
+impl<Key, Value, BlockNumber, AuthorityId> SignedPayload for FeedPayload<Key, Value, BlockNumber, AuthorityId> where
+	Key: Encode,
+	Value: Encode,
+	BlockNumber: Encode,
+	AuthorityId: TypedKey + AsRef<[u8]> + Clone + Encode,
+{
+	type Public = AuthorityId;
+
+	fn public(&self) -> &AuthorityId {
+		&self.authority_id
+	}
+}
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The function call.
+	type Call: From<Call<Self>>;
+
+	/// A extrinsic right from the external world. This is unchecked and so
+	/// can contain a signature.
+	type UncheckedExtrinsic: ExtrinsicT<Call=<Self as Trait>::Call> + Encode + Decode;
+
+	/// Submits the signed transactions of the offchain worker.
+	type SubmitTransaction: SubmitSignedTransaction<<Self as Trait>::Call, Public=Self::FeederKey>;
+
+	/// The key of a feeder account, which signs its transactions.
+	type FeederKey: Clone + Into<Self::AccountId>;
+
+	/// The identifier type for an authority.
+	type AuthorityId: Member + Parameter + Default + TypedKey + AsRef<[u8]>;
+
+	/// Determine if an `AuthorityId` is a current authority.
+	type IsValidAuthorityId: IsMember<Self::AuthorityId>;
+
+	/// The key of a value.
+	type Key: Parameter;
+
+	/// The value fed for a key.
+	type Value: Parameter + Ord;
+
+	/// The source of the values fed by the offchain worker.
+	type Source: FetchValues<Self::Key, Self::Value>;
+
+	/// The number of blocks after which a fed value is ignored.
+	type ExpiresIn: Get<Self::BlockNumber>;
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as Trait>::Key,
+		<T as Trait>::Value,
+		Feeder = Feeder<<T as system::Trait>::AccountId, <T as Trait>::AuthorityId>,
+	{
+		/// The feeder accounts changed.
+		FeedersChanged(Vec<AccountId>),
+		/// The feeder fed values of keys.
+		ValuesFed(Feeder, Vec<(Key, Value)>),
+	}
+);
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Oracle {
+		/// The accounts allowed to feed values, besides the authorities.
+		pub Feeders get(feeders) config(): Vec<T::AccountId>;
+
+		/// The values fed for each key that may not have expired, by feeder.
+		pub RawValues get(raw_values):
+			map T::Key => Vec<(Feeder<T::AccountId, T::AuthorityId>, TimestampedValue<T::Value, T::BlockNumber>)>;
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		/// The number of blocks after which a fed value is ignored.
+		const ExpiresIn: T::BlockNumber = T::ExpiresIn::get();
+
+		fn deposit_event() = default;
+
+		/// Set the accounts allowed to feed values.
+		fn set_feeders(origin, feeders: Vec<T::AccountId>) {
+			ensure_root(origin)?;
+			<Feeders<T>>::put(&feeders);
+			Self::deposit_event(RawEvent::FeedersChanged(feeders));
+		}
+
+		/// Feed the values of keys. The sender must be a feeder account.
+		fn feed_values(origin, values: Vec<(T::Key, T::Value)>) -> Result {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::feeders().contains(&who), "Not a feeder");
+
+			let now = <system::Module<T>>::block_number();
+			Self::do_feed_values(Feeder::Account(who), values, now);
+			Ok(())
+		}
+
+		/// Feed the values of keys of `payload`, signed by a current authority.
+		///
+		/// The values are timestamped with the block number of the payload, which must be newer
+		/// than the one of the values the authority fed before.
+		fn feed_values_unsigned(
+			origin,
+			payload: FeedPayload<T::Key, T::Value, T::BlockNumber, T::AuthorityId>,
+			_signature: Vec<u8>
+		) -> Result {
+			ensure_none(origin)?;
+			ensure!(T::IsValidAuthorityId::is_member(&payload.authority_id), "Not an authority");
+			ensure!(payload.block_number <= <system::Module<T>>::block_number(), "Payload is from the future");
+
+			let feeder = Feeder::Authority(payload.authority_id);
+			ensure!(
+				!Self::is_replayed(&feeder, &payload.values, payload.block_number),
+				"Payload is not newer than the fed values"
+			);
+
+			Self::do_feed_values(feeder, payload.values, payload.block_number);
+			Ok(())
+		}
+
+		// Runs after every block.
+		fn offchain_worker(now: T::BlockNumber) {
+			let values = T::Source::fetch_values();
+			if values.is_empty() {
+				return;
+			}
+
+			if let Err(err) = Self::submit_unsigned(now, values.clone()) {
+				print(err);
+			}
+			Self::submit_signed(values);
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// The current value of `key`: the median of the values fed by the current feeders that
+	/// haven't expired.
+	pub fn current_value(key: &T::Key) -> Option<T::Value> {
+		let now = <system::Module<T>>::block_number();
+		let expires_in = T::ExpiresIn::get();
+		let feeders = Self::feeders();
+
+		let mut values = Self::raw_values(key).into_iter()
+			.filter(|(feeder, fed)| fed.at + expires_in > now && match feeder {
+				Feeder::Account(who) => feeders.contains(who),
+				Feeder::Authority(authority_id) => T::IsValidAuthorityId::is_member(authority_id),
+			})
+			.map(|(_, fed)| fed.value)
+			.collect::<Vec<_>>();
+		if values.is_empty() {
+			return None;
+		}
+
+		values.sort();
+		let median = (values.len() - 1) / 2;
+		Some(values.swap_remove(median))
+	}
+
+	/// Whether `feeder` already fed a value of one of the keys of `values` at block `at` or later.
+	fn is_replayed(
+		feeder: &Feeder<T::AccountId, T::AuthorityId>,
+		values: &[(T::Key, T::Value)],
+		at: T::BlockNumber,
+	) -> bool {
+		values.iter().any(|(key, _)| Self::raw_values(key).iter()
+			.any(|(other, fed)| other == feeder && fed.at >= at)
+		)
+	}
+
+	fn do_feed_values(feeder: Feeder<T::AccountId, T::AuthorityId>, values: Vec<(T::Key, T::Value)>, at: T::BlockNumber) {
+		let now = <system::Module<T>>::block_number();
+		let expires_in = T::ExpiresIn::get();
+
+		for (key, value) in values.iter().cloned() {
+			<RawValues<T>>::mutate(key, |raw| {
+				// Replace the previous value of the feeder and drop the expired ones.
+				raw.retain(|(other, fed)| *other != feeder && fed.at + expires_in > now);
+				raw.push((feeder.clone(), TimestampedValue { value, at }));
+			});
+		}
+
+		Self::deposit_event(RawEvent::ValuesFed(feeder, values));
+	}
+
+	fn submit_unsigned(block_number: T::BlockNumber, values: Vec<(T::Key, T::Value)>) -> rstd::result::Result<(), OffchainErr> {
+		// we only submit when a local authority key is configured
+		let key = match sr_io::pubkey(CryptoKey::AuthorityKey) {
+			Ok(key) => key,
+			Err(_) => return Ok(()),
+		};
+		let authority_id = T::AuthorityId::decode(&mut &key[..])
+			.map_err(|_| OffchainErr::DecodeAuthorityId)?;
+		if !T::IsValidAuthorityId::is_member(&authority_id) {
+			return Ok(());
+		}
+
+		let payload = FeedPayload { values, block_number, authority_id };
+		let signature = sr_io::sign(CryptoKey::AuthorityKey, &payload.encode())
+			.map_err(|_| OffchainErr::FailedSigning)?;
+		let call = Call::<T>::feed_values_unsigned(payload, signature);
+		let ex = T::UncheckedExtrinsic::new_unsigned(call.into())
+			.ok_or(OffchainErr::ExtrinsicCreation)?;
+		sr_io::submit_transaction(&ex).map_err(|_| OffchainErr::SubmitTransaction)
+	}
+
+	fn submit_signed(values: Vec<(T::Key, T::Value)>) {
+		let feeders = Self::feeders();
+		for key in T::SubmitTransaction::public_keys() {
+			if !feeders.contains(&key.clone().into()) {
+				continue;
+			}
+			if T::SubmitTransaction::sign_and_submit(Call::<T>::feed_values(values.clone()), key).is_err() {
+				print(OffchainErr::SubmitTransaction);
+			}
+		}
+	}
+}
+
+impl<T: Trait> DataProvider<T::Key, T::Value> for Module<T> {
+	fn get(key: &T::Key) -> Option<T::Value> {
+		Self::current_value(key)
+	}
+}
+
+impl<T: Trait> srml_support::unsigned::ValidateUnsigned for Module<T> {
+	type Call = Call<T>;
+
+	fn validate_unsigned(
+		_source: TransactionSource,
+		call: &Self::Call,
+	) -> srml_support::unsigned::TransactionValidity {
+		if let Call::feed_values_unsigned(payload, signature) = call {
+			// verify that the incoming (unverified) pubkey is actually an authority id
+			if !T::IsValidAuthorityId::is_member(&payload.authority_id) {
+				return TransactionValidity::Invalid(ApplyError::BadSignature as i8);
+			}
+
+			// the values would already be expired
+			let expires_in = T::ExpiresIn::get();
+			let now = <system::Module<T>>::block_number();
+			if payload.block_number + expires_in <= now {
+				return TransactionValidity::Invalid(ApplyError::Stale as i8);
+			}
+			if payload.block_number > now {
+				return TransactionValidity::Invalid(ApplyError::Future as i8);
+			}
+
+			// the authority already fed values that are as recent, e.g. with this payload
+			let feeder = Feeder::Authority(payload.authority_id.clone());
+			if Self::is_replayed(&feeder, &payload.values, payload.block_number) {
+				return TransactionValidity::Invalid(ApplyError::Stale as i8);
+			}
+
+			let validity = SignedPayloadValidity {
+				longevity: expires_in.saturated_into::<u64>(),
+				..Default::default()
+			};
+			return validate_signed_payload(payload, signature, validity);
+		}
+		TransactionValidity::Invalid(0)
+	}
+}
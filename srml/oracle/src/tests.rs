@@ -0,0 +1,218 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use super::*;
+
+use primitives::Blake2Hasher;
+use sr_io::with_externalities;
+use sr_primitives::testing::{TestXt, UintAuthorityId};
+use srml_support::{assert_noop, assert_ok, impl_test_runtime, parameter_types};
+use srml_support::unsigned::ValidateUnsigned;
+
+impl_test_runtime! {
+	pub struct Test;
+}
+parameter_types! {
+	pub const ExpiresIn: u64 = 5;
+}
+
+/// The offchain worker isn't run in the tests, so no transaction is submitted.
+pub struct NoSubmitter;
+impl SubmitSignedTransaction<Call<Test>> for NoSubmitter {
+	type Public = u64;
+
+	fn public_keys() -> Vec<u64> {
+		Vec::new()
+	}
+
+	fn sign_and_submit(_call: impl Into<Call<Test>>, _public: u64) -> std::result::Result<(), ()> {
+		Err(())
+	}
+}
+
+/// The authorities are the ids from 10.
+pub struct Authorities;
+impl IsMember<UintAuthorityId> for Authorities {
+	fn is_member(authority_id: &UintAuthorityId) -> bool {
+		authority_id.0 >= 10
+	}
+}
+
+impl Trait for Test {
+	type Event = ();
+	type Call = Call<Test>;
+	type UncheckedExtrinsic = TestXt<Call<Test>, ()>;
+	type SubmitTransaction = NoSubmitter;
+	type FeederKey = u64;
+	type AuthorityId = UintAuthorityId;
+	type IsValidAuthorityId = Authorities;
+	type Key = u32;
+	type Value = u64;
+	type Source = ();
+	type ExpiresIn = ExpiresIn;
+}
+type System = system::Module<Test>;
+type Oracle = Module<Test>;
+
+fn new_test_ext() -> sr_io::TestExternalities<Blake2Hasher> {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap().0;
+	t.extend(GenesisConfig::<Test> {
+		feeders: vec![1, 2, 3],
+	}.build_storage().unwrap().0);
+	t.into()
+}
+
+fn payload(authority: u64, block_number: u64, values: Vec<(u32, u64)>) -> FeedPayload<u32, u64, u64, UintAuthorityId> {
+	FeedPayload { values, block_number, authority_id: UintAuthorityId(authority) }
+}
+
+#[test]
+fn the_current_value_is_the_median_of_the_fed_values() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(1);
+		assert_noop!(Oracle::feed_values(Origin::signed(4), vec![(0, 10)]), "Not a feeder");
+		assert_eq!(Oracle::current_value(&0), None);
+
+		assert_ok!(Oracle::feed_values(Origin::signed(1), vec![(0, 10), (1, 100)]));
+		assert_ok!(Oracle::feed_values(Origin::signed(2), vec![(0, 30)]));
+		assert_eq!(Oracle::current_value(&0), Some(10));
+		assert_eq!(<Oracle as DataProvider<_, _>>::get(&1), Some(100));
+
+		assert_ok!(Oracle::feed_values(Origin::signed(3), vec![(0, 20)]));
+		assert_eq!(Oracle::current_value(&0), Some(20));
+
+		// Feeding again replaces the previous value of the feeder.
+		assert_ok!(Oracle::feed_values(Origin::signed(1), vec![(0, 40)]));
+		assert_eq!(Oracle::raw_values(0).len(), 3);
+		assert_eq!(Oracle::current_value(&0), Some(30));
+	});
+}
+
+#[test]
+fn stale_values_and_removed_feeders_are_ignored() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(1);
+		assert_ok!(Oracle::feed_values(Origin::signed(1), vec![(0, 10)]));
+		System::set_block_number(3);
+		assert_ok!(Oracle::feed_values(Origin::signed(2), vec![(0, 30)]));
+		assert_eq!(Oracle::current_value(&0), Some(10));
+
+		System::set_block_number(6);
+		assert_eq!(Oracle::current_value(&0), Some(30));
+
+		// Expired values are dropped when values are fed.
+		assert_ok!(Oracle::feed_values(Origin::signed(3), vec![(0, 50)]));
+		assert_eq!(Oracle::raw_values(0).len(), 2);
+
+		assert_noop!(Oracle::set_feeders(Origin::signed(1), vec![3]), "bad origin: expected to be a root origin");
+		assert_ok!(Oracle::set_feeders(Origin::ROOT, vec![3]));
+		assert_eq!(Oracle::current_value(&0), Some(50));
+		assert_noop!(Oracle::feed_values(Origin::signed(2), vec![(0, 30)]), "Not a feeder");
+
+		System::set_block_number(11);
+		assert_eq!(Oracle::current_value(&0), None);
+	});
+}
+
+#[test]
+fn authorities_feed_values_in_unsigned_transactions() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(1);
+		assert_noop!(
+			Oracle::feed_values_unsigned(Origin::NONE, payload(1, 1, vec![(0, 10)]), vec![]),
+			"Not an authority"
+		);
+		assert_noop!(
+			Oracle::feed_values_unsigned(Origin::signed(1), payload(10, 1, vec![(0, 10)]), vec![]),
+			"bad origin: expected to be no origin"
+		);
+
+		assert_ok!(Oracle::feed_values_unsigned(Origin::NONE, payload(10, 1, vec![(0, 10)]), vec![]));
+		assert_ok!(Oracle::feed_values(Origin::signed(1), vec![(0, 20)]));
+		assert_ok!(Oracle::feed_values_unsigned(Origin::NONE, payload(11, 1, vec![(0, 30)]), vec![]));
+		assert_eq!(Oracle::current_value(&0), Some(20));
+	});
+}
+
+#[test]
+fn unsigned_feeds_are_validated() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(10);
+		let validate = |payload| Oracle::validate_unsigned(
+			TransactionSource::External,
+			&Call::feed_values_unsigned(payload, vec![0; 64]),
+		);
+
+		assert_eq!(
+			validate(payload(1, 10, vec![(0, 10)])),
+			TransactionValidity::Invalid(ApplyError::BadSignature as i8)
+		);
+		assert_eq!(
+			validate(payload(10, 5, vec![(0, 10)])),
+			TransactionValidity::Invalid(ApplyError::Stale as i8)
+		);
+		assert_eq!(
+			validate(payload(10, 10, vec![(0, 10)])),
+			TransactionValidity::Invalid(ApplyError::BadSignature as i8)
+		);
+		assert_eq!(
+			Oracle::validate_unsigned(TransactionSource::External, &Call::feed_values(vec![(0, 10)])),
+			TransactionValidity::Invalid(0)
+		);
+	});
+}
+
+#[test]
+fn unsigned_feeds_cant_be_replayed() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(3);
+		assert_ok!(Oracle::feed_values_unsigned(Origin::NONE, payload(10, 2, vec![(0, 10)]), vec![]));
+		assert_eq!(Oracle::raw_values(0)[0].1, TimestampedValue { value: 10, at: 2 });
+
+		System::set_block_number(4);
+		let validate = |payload| Oracle::validate_unsigned(
+			TransactionSource::External,
+			&Call::feed_values_unsigned(payload, vec![0; 64]),
+		);
+		assert_eq!(
+			validate(payload(10, 2, vec![(0, 10)])),
+			TransactionValidity::Invalid(ApplyError::Stale as i8)
+		);
+		assert_eq!(
+			validate(payload(10, 5, vec![(0, 10)])),
+			TransactionValidity::Invalid(ApplyError::Future as i8)
+		);
+		assert_noop!(
+			Oracle::feed_values_unsigned(Origin::NONE, payload(10, 2, vec![(0, 10)]), vec![]),
+			"Payload is not newer than the fed values"
+		);
+		assert_noop!(
+			Oracle::feed_values_unsigned(Origin::NONE, payload(10, 1, vec![(1, 20), (0, 10)]), vec![]),
+			"Payload is not newer than the fed values"
+		);
+		assert_noop!(
+			Oracle::feed_values_unsigned(Origin::NONE, payload(10, 5, vec![(0, 10)]), vec![]),
+			"Payload is from the future"
+		);
+
+		// a newer payload replaces the value, and the other authorities aren't affected
+		assert_ok!(Oracle::feed_values_unsigned(Origin::NONE, payload(10, 4, vec![(0, 30)]), vec![]));
+		assert_ok!(Oracle::feed_values_unsigned(Origin::NONE, payload(11, 2, vec![(0, 20)]), vec![]));
+		assert_eq!(Oracle::current_value(&0), Some(20));
+	});
+}
@@ -0,0 +1,528 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Proposals Module
+//!
+//! The Proposals module lets members submit typed proposals which are voted by the council.
+//!
+//! - [`proposals::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! A proposal is either a text, a runtime upgrade, a spending from the account of the module or a
+//! parameter change, i.e. a call dispatched as root. Each kind of proposal has its own
+//! parameters, set by root: the stake reserved from the proposer, the voting and grace periods,
+//! and the quorum and threshold of approval, in percent.
+//!
+//! Council members approve, reject or abstain. Votes are tallied once the whole council voted, or
+//! at the end of the voting period:
+//!
+//! - if less than the quorum of the council voted, the proposal expires;
+//! - if at least the threshold of the votes approve, the proposal is approved and executed at the
+//!   end of the grace period;
+//! - otherwise, the proposal is rejected and the stake of the proposer is slashed.
+//!
+//! The stake is returned to the proposer in the other cases. `VetoOrigin` can veto proposals
+//! until they are executed.
+//!
+//! Proposers are members, identified by their `MemberId` through `MemberAccounts`, acting with
+//! their controller account.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `set_parameters` - Set the parameters of a kind of proposal.
+//! - `propose` - Submit a proposal.
+//! - `vote` - Vote on a proposal as a council member.
+//! - `veto` - Veto a proposal.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod tests;
+
+use rstd::prelude::*;
+use rstd::marker::PhantomData;
+use codec::{Encode, Decode};
+use srml_support::{StorageValue, StorageMap, Parameter, decl_module, decl_storage, decl_event, ensure};
+use srml_support::dispatch::Result;
+use srml_support::traits::{
+	ChangeMembers, Currency, MemberAccounts, OnUnbalanced, ReservableCurrency
+};
+use sr_primitives::ModuleId;
+use sr_primitives::traits::{AccountIdConversion, Dispatchable, EnsureOrigin, SaturatedConversion};
+use sr_primitives::weights::{Weight, WeighData, ClassifyDispatch, DispatchClass, SimpleDispatchInfo};
+use system::{ensure_signed, ensure_root};
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
+
+const MODULE_ID: ModuleId = ModuleId(*b"py/prpsl");
+
+/// The weight of the calls besides the bytes and council members they read.
+const BASE_WEIGHT: Weight = 100_000;
+/// The weight of storing a byte of a proposal.
+const BYTE_WEIGHT: Weight = 100;
+/// The weight of reading a council member.
+const COUNCIL_MEMBER_WEIGHT: Weight = 1_000;
+
+/// The identifier of a proposal.
+pub type ProposalId = u32;
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The currency the stakes are reserved, and the spendings paid, in.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The identifier of a member.
+	type MemberId: Parameter;
+
+	/// The members that can submit proposals.
+	type Members: MemberAccounts<Self::AccountId, MemberId = Self::MemberId>;
+
+	/// The call dispatched by parameter change proposals.
+	type Proposal: Parameter + Dispatchable<Origin = Self::Origin>;
+
+	/// The origin that can veto proposals.
+	type VetoOrigin: EnsureOrigin<Self::Origin>;
+
+	/// Handler for the stakes slashed from the proposers of rejected proposals.
+	type Slashed: OnUnbalanced<NegativeImbalanceOf<Self>>;
+}
+
+/// A kind of proposal.
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum ProposalKind {
+	Text,
+	RuntimeUpgrade,
+	Spending,
+	ParameterChange,
+}
+
+/// What a proposal does once executed.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum ProposalDetails<AccountId, Balance, Call> {
+	/// Signal the given text.
+	Text(Vec<u8>),
+	/// Replace the runtime code by the given one.
+	RuntimeUpgrade(Vec<u8>),
+	/// Transfer the given amount from the account of the module to the given account.
+	Spending(Balance, AccountId),
+	/// Dispatch the given call as root.
+	ParameterChange(Box<Call>),
+}
+
+impl<AccountId, Balance, Call> ProposalDetails<AccountId, Balance, Call> {
+	/// The kind of the proposal.
+	pub fn kind(&self) -> ProposalKind {
+		match self {
+			ProposalDetails::Text(_) => ProposalKind::Text,
+			ProposalDetails::RuntimeUpgrade(_) => ProposalKind::RuntimeUpgrade,
+			ProposalDetails::Spending(..) => ProposalKind::Spending,
+			ProposalDetails::ParameterChange(_) => ProposalKind::ParameterChange,
+		}
+	}
+}
+
+/// The parameters of a kind of proposal.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct ProposalParameters<BlockNumber, Balance> {
+	/// The stake reserved from the proposer.
+	pub stake: Balance,
+	/// The number of blocks the council can vote for.
+	pub voting_period: BlockNumber,
+	/// The number of blocks between the approval and the execution.
+	pub grace_period: BlockNumber,
+	/// The percentage of the council that must vote.
+	pub approval_quorum: u32,
+	/// The percentage of the votes that must approve.
+	pub approval_threshold: u32,
+}
+
+/// A vote of a council member.
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum VoteKind {
+	Approve,
+	Reject,
+	Abstain,
+}
+
+/// The votes on a proposal.
+#[derive(Clone, Default, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Tally {
+	pub approvals: u32,
+	pub rejections: u32,
+	pub abstentions: u32,
+}
+
+impl Tally {
+	/// The number of votes.
+	pub fn votes(&self) -> u32 {
+		self.approvals + self.rejections + self.abstentions
+	}
+}
+
+/// The status of a proposal.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum ProposalStatus<BlockNumber> {
+	/// The council is voting.
+	Active,
+	/// The proposal was approved and will be executed at the given block.
+	Approved(BlockNumber),
+	/// The proposal was rejected.
+	Rejected,
+	/// Too few council members voted.
+	Expired,
+	/// The proposal was vetoed.
+	Vetoed,
+	/// The proposal was executed.
+	Executed,
+	/// The execution of the proposal failed with the given error.
+	ExecutionFailed(Vec<u8>),
+}
+
+/// A proposal.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Proposal<AccountId, Balance, BlockNumber, Call, MemberId> {
+	/// The member that submitted the proposal.
+	pub proposer: MemberId,
+	/// The account the stake is reserved from.
+	pub account: AccountId,
+	/// What the proposal does.
+	pub details: ProposalDetails<AccountId, Balance, Call>,
+	/// The parameters of the proposal, at the time it was submitted.
+	pub parameters: ProposalParameters<BlockNumber, Balance>,
+	/// The block at which the proposal was submitted.
+	pub created_at: BlockNumber,
+	/// The votes on the proposal.
+	pub tally: Tally,
+	/// The status of the proposal.
+	pub status: ProposalStatus<BlockNumber>,
+}
+
+type ProposalOf<T> = Proposal<
+	<T as system::Trait>::AccountId,
+	BalanceOf<T>,
+	<T as system::Trait>::BlockNumber,
+	<T as Trait>::Proposal,
+	<T as Trait>::MemberId,
+>;
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Proposals {
+		/// The parameters of each kind of proposal. Kinds without parameters can't be proposed.
+		pub Parameters get(parameters): map ProposalKind => Option<ProposalParameters<T::BlockNumber, BalanceOf<T>>>;
+
+		/// The council members, sorted.
+		pub Council get(council) config(): Vec<T::AccountId>;
+
+		/// The identifier of the next proposal.
+		pub NextProposalId get(next_proposal_id): ProposalId;
+		/// The proposals.
+		pub Proposals get(proposal): map ProposalId => Option<ProposalOf<T>>;
+		/// The vote of a council member on a proposal.
+		pub Votes get(vote_of): map (ProposalId, T::AccountId) => Option<VoteKind>;
+
+		/// The proposals the council is voting on, by the block at which their voting period ends.
+		pub ActiveProposals get(active_proposals): map T::BlockNumber => Vec<ProposalId>;
+		/// The approved proposals to be executed at a block.
+		pub PendingExecution get(pending_execution): map T::BlockNumber => Vec<ProposalId>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as Trait>::MemberId,
+	{
+		/// A proposal was submitted by the member.
+		Proposed(ProposalId, MemberId),
+		/// A council member voted on a proposal.
+		Voted(ProposalId, AccountId, VoteKind),
+		/// A proposal was approved.
+		Approved(ProposalId),
+		/// A proposal was rejected.
+		Rejected(ProposalId),
+		/// A proposal expired.
+		Expired(ProposalId),
+		/// A proposal was vetoed.
+		Vetoed(ProposalId),
+		/// An approved proposal was executed, successfully (`true`) or not (`false`).
+		Executed(ProposalId, bool),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		/// Set the parameters of the proposals of the kind `kind`, or disable it if `parameters`
+		/// is `None`. Submitted proposals keep their parameters.
+		///
+		/// The origin must be root.
+		#[weight = SimpleDispatchInfo::FixedOperational(BASE_WEIGHT)]
+		fn set_parameters(
+			origin,
+			kind: ProposalKind,
+			parameters: Option<ProposalParameters<T::BlockNumber, BalanceOf<T>>>
+		) -> Result {
+			ensure_root(origin)?;
+			if let Some(parameters) = parameters.as_ref() {
+				ensure!(
+					parameters.approval_quorum <= 100 && parameters.approval_threshold <= 100,
+					"Invalid percentage"
+				);
+				ensure!(parameters.approval_quorum > 0, "Approval quorum must not be zero");
+			}
+
+			match parameters {
+				Some(parameters) => <Parameters<T>>::insert(kind, parameters),
+				None => <Parameters<T>>::remove(kind),
+			}
+
+			Ok(())
+		}
+
+		/// Submit a proposal on behalf of the member `member_id`, reserving the stake of its kind.
+		///
+		/// The sender must be the controller account of the member.
+		#[weight = ProposeWeight::<T>::default()]
+		fn propose(
+			origin,
+			member_id: T::MemberId,
+			details: ProposalDetails<T::AccountId, BalanceOf<T>, T::Proposal>
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			ensure!(T::Members::is_member_controller(&member_id, &who), "Not the controller account of the member");
+			let parameters = Self::parameters(details.kind()).ok_or("Proposal kind is disabled")?;
+
+			T::Currency::reserve(&who, parameters.stake)?;
+
+			let id = Self::next_proposal_id();
+			NextProposalId::put(id + 1);
+			let proposal = Proposal {
+				proposer: member_id.clone(),
+				account: who,
+				details,
+				parameters,
+				created_at: <system::Module<T>>::block_number(),
+				tally: Tally::default(),
+				status: ProposalStatus::Active,
+			};
+			<ActiveProposals<T>>::mutate(Self::voting_end(&proposal), |proposals| proposals.push(id));
+			<Proposals<T>>::insert(id, proposal);
+			Self::deposit_event(RawEvent::Proposed(id, member_id));
+
+			Ok(())
+		}
+
+		/// Vote `vote` on the active proposal `proposal_id`. The votes are tallied right away once
+		/// the whole council voted.
+		///
+		/// The sender must be a council member.
+		#[weight = CouncilWeight::<T>::default()]
+		fn vote(origin, #[compact] proposal_id: ProposalId, vote: VoteKind) -> Result {
+			let who = ensure_signed(origin)?;
+			let council = Self::council();
+			ensure!(council.binary_search(&who).is_ok(), "Not a council member");
+			let mut proposal = Self::proposal(proposal_id).ok_or("Unknown proposal")?;
+			ensure!(proposal.status == ProposalStatus::Active, "Proposal is not active");
+			ensure!(!<Votes<T>>::exists((proposal_id, who.clone())), "Already voted");
+
+			match vote {
+				VoteKind::Approve => proposal.tally.approvals += 1,
+				VoteKind::Reject => proposal.tally.rejections += 1,
+				VoteKind::Abstain => proposal.tally.abstentions += 1,
+			}
+			<Votes<T>>::insert((proposal_id, who.clone()), vote);
+			Self::deposit_event(RawEvent::Voted(proposal_id, who, vote));
+
+			if proposal.tally.votes() as usize >= council.len() {
+				Self::tally(proposal_id, &mut proposal);
+				Self::deactivate(proposal_id, &proposal);
+			}
+			<Proposals<T>>::insert(proposal_id, proposal);
+
+			Ok(())
+		}
+
+		/// Veto the proposal `proposal_id`, if it's active or approved but not executed yet.
+		#[weight = SimpleDispatchInfo::FixedOperational(BASE_WEIGHT)]
+		fn veto(origin, #[compact] proposal_id: ProposalId) -> Result {
+			T::VetoOrigin::ensure_origin(origin)?;
+			let mut proposal = Self::proposal(proposal_id).ok_or("Unknown proposal")?;
+
+			match proposal.status {
+				ProposalStatus::Active => {
+					T::Currency::unreserve(&proposal.account, proposal.parameters.stake);
+					Self::deactivate(proposal_id, &proposal);
+				}
+				ProposalStatus::Approved(execute_at) => {
+					<PendingExecution<T>>::mutate(execute_at, |proposals| proposals.retain(|id| *id != proposal_id));
+				}
+				_ => return Err("Proposal can't be vetoed"),
+			}
+			proposal.status = ProposalStatus::Vetoed;
+			<Proposals<T>>::insert(proposal_id, proposal);
+			Self::deposit_event(RawEvent::Vetoed(proposal_id));
+
+			Ok(())
+		}
+
+		fn on_finalize(now: T::BlockNumber) {
+			// Tally the proposals at the end of their voting period.
+			for id in <ActiveProposals<T>>::take(now) {
+				if let Some(mut proposal) = Self::proposal(id) {
+					Self::tally(id, &mut proposal);
+					<Proposals<T>>::insert(id, proposal);
+				}
+			}
+
+			for id in <PendingExecution<T>>::take(now) {
+				if let Some(mut proposal) = Self::proposal(id) {
+					let result = Self::execute(proposal.details.clone());
+					Self::deposit_event(RawEvent::Executed(id, result.is_ok()));
+					proposal.status = match result {
+						Ok(()) => ProposalStatus::Executed,
+						Err(e) => ProposalStatus::ExecutionFailed(e.as_bytes().to_vec()),
+					};
+					<Proposals<T>>::insert(id, proposal);
+				}
+			}
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// The account of the module, which spending proposals are paid from.
+	pub fn account_id() -> T::AccountId {
+		MODULE_ID.into_account()
+	}
+
+	/// The block at which the voting period of `proposal` ends.
+	fn voting_end(proposal: &ProposalOf<T>) -> T::BlockNumber {
+		proposal.created_at + proposal.parameters.voting_period
+	}
+
+	/// Remove the proposal `id` from the active proposals before the end of its voting period.
+	fn deactivate(id: ProposalId, proposal: &ProposalOf<T>) {
+		<ActiveProposals<T>>::mutate(Self::voting_end(proposal), |proposals| proposals.retain(|i| *i != id));
+	}
+
+	/// Decide the outcome of the proposal `id` from its votes, settling the stake of the proposer.
+	fn tally(id: ProposalId, proposal: &mut ProposalOf<T>) {
+		let council = Self::council().len() as u64;
+		let votes = proposal.tally.votes() as u64;
+		let parameters = &proposal.parameters;
+
+		// without votes, e.g. because the council is empty, nothing is approved
+		if votes == 0 || votes * 100 < parameters.approval_quorum as u64 * council {
+			T::Currency::unreserve(&proposal.account, parameters.stake);
+			proposal.status = ProposalStatus::Expired;
+			Self::deposit_event(RawEvent::Expired(id));
+		} else if proposal.tally.approvals as u64 * 100 >= parameters.approval_threshold as u64 * votes {
+			T::Currency::unreserve(&proposal.account, parameters.stake);
+			let execute_at = <system::Module<T>>::block_number() + parameters.grace_period;
+			<PendingExecution<T>>::mutate(execute_at, |proposals| proposals.push(id));
+			proposal.status = ProposalStatus::Approved(execute_at);
+			Self::deposit_event(RawEvent::Approved(id));
+		} else {
+			let imbalance = T::Currency::slash_reserved(&proposal.account, parameters.stake).0;
+			T::Slashed::on_unbalanced(imbalance);
+			proposal.status = ProposalStatus::Rejected;
+			Self::deposit_event(RawEvent::Rejected(id));
+		}
+	}
+
+	fn execute(details: ProposalDetails<T::AccountId, BalanceOf<T>, T::Proposal>) -> Result {
+		match details {
+			ProposalDetails::Text(_) => Ok(()),
+			ProposalDetails::RuntimeUpgrade(code) =>
+				<system::Module<T>>::set_code(system::RawOrigin::Root.into(), code),
+			ProposalDetails::Spending(amount, to) =>
+				T::Currency::transfer(&Self::account_id(), &to, amount),
+			ProposalDetails::ParameterChange(call) =>
+				call.dispatch(system::RawOrigin::Root.into()),
+		}
+	}
+}
+
+/// Weight of `propose`: a step per byte of the proposal.
+pub struct ProposeWeight<T>(PhantomData<T>);
+
+impl<T> Default for ProposeWeight<T> {
+	fn default() -> Self {
+		ProposeWeight(PhantomData)
+	}
+}
+
+impl<T: Trait> WeighData<(&T::MemberId, &ProposalDetails<T::AccountId, BalanceOf<T>, T::Proposal>)> for ProposeWeight<T> {
+	fn weigh_data(
+		&self,
+		(_, details): (&T::MemberId, &ProposalDetails<T::AccountId, BalanceOf<T>, T::Proposal>),
+	) -> Weight {
+		let bytes: Weight = details.using_encoded(|encoded| encoded.len()).saturated_into();
+		BASE_WEIGHT.saturating_add(BYTE_WEIGHT.saturating_mul(bytes))
+	}
+}
+
+impl<T, A> ClassifyDispatch<A> for ProposeWeight<T> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+/// Weight of `vote`: a step per council member.
+pub struct CouncilWeight<T>(PhantomData<T>);
+
+impl<T> Default for CouncilWeight<T> {
+	fn default() -> Self {
+		CouncilWeight(PhantomData)
+	}
+}
+
+impl<T: Trait, A> WeighData<A> for CouncilWeight<T> {
+	fn weigh_data(&self, _: A) -> Weight {
+		let council: Weight = Module::<T>::council().len().saturated_into();
+		BASE_WEIGHT.saturating_add(COUNCIL_MEMBER_WEIGHT.saturating_mul(council))
+	}
+}
+
+impl<T, A> ClassifyDispatch<A> for CouncilWeight<T> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+impl<T: Trait> ChangeMembers<T::AccountId> for Module<T> {
+	fn change_members(_incoming: &[T::AccountId], _outgoing: &[T::AccountId], new: &[T::AccountId]) {
+		let mut council = new.to_vec();
+		council.sort();
+		<Council<T>>::put(council);
+	}
+}
@@ -0,0 +1,235 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use super::*;
+
+use primitives::{Blake2Hasher, storage::well_known_keys};
+use runtime_io::with_externalities;
+use sr_primitives::weights::GetDispatchInfo;
+use srml_support::{assert_noop, assert_ok, impl_test_runtime, traits::OnFinalize};
+
+impl_test_runtime! {
+	pub struct Test;
+	balances {
+		ExistentialDeposit = 0;
+		TransferFee = 0;
+		CreationFee = 0;
+	}
+}
+
+/// The members `0` to `9`, controlled by the accounts of the same number.
+pub struct Members;
+impl MemberAccounts<u64> for Members {
+	type MemberId = u64;
+
+	fn controller_account(member: &u64) -> Option<u64> {
+		if *member < 10 { Some(*member) } else { None }
+	}
+}
+
+impl Trait for Test {
+	type Event = ();
+	type Currency = balances::Module<Test>;
+	type MemberId = u64;
+	type Members = Members;
+	type Proposal = balances::Call<Test>;
+	type VetoOrigin = system::EnsureRoot<u64>;
+	type Slashed = ();
+}
+type System = system::Module<Test>;
+type Balances = balances::Module<Test>;
+type Proposals = Module<Test>;
+
+fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap().0;
+	t.extend(balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100), (Proposals::account_id(), 50)],
+		vesting: vec![],
+	}.build_storage().unwrap().0);
+	t.extend(GenesisConfig::<Test> {
+		council: vec![10, 11, 12],
+	}.build_storage().unwrap().0);
+	t.into()
+}
+
+fn parameters(voting_period: u64, grace_period: u64) -> ProposalParameters<u64, u64> {
+	ProposalParameters { stake: 10, voting_period, grace_period, approval_quorum: 60, approval_threshold: 50 }
+}
+
+fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		Proposals::on_finalize(System::block_number());
+		System::set_block_number(System::block_number() + 1);
+	}
+}
+
+#[test]
+fn only_enabled_kinds_are_proposed_by_members() {
+	with_externalities(&mut new_test_ext(), || {
+		let text = ProposalDetails::Text(b"hello".to_vec());
+		assert_noop!(Proposals::propose(Origin::signed(1), 1, text.clone()), "Proposal kind is disabled");
+		assert_noop!(
+			Proposals::set_parameters(Origin::signed(1), ProposalKind::Text, Some(parameters(10, 0))),
+			"bad origin: expected to be a root origin"
+		);
+		assert_noop!(
+			Proposals::set_parameters(Origin::ROOT, ProposalKind::Text, Some(ProposalParameters {
+				approval_quorum: 101,
+				..parameters(10, 0)
+			})),
+			"Invalid percentage"
+		);
+		assert_ok!(Proposals::set_parameters(Origin::ROOT, ProposalKind::Text, Some(parameters(10, 0))));
+
+		assert_noop!(Proposals::propose(Origin::signed(2), 1, text.clone()), "Not the controller account of the member");
+		assert_noop!(Proposals::propose(Origin::signed(1), 1, ProposalDetails::RuntimeUpgrade(vec![])), "Proposal kind is disabled");
+		assert_ok!(Proposals::propose(Origin::signed(1), 1, text));
+		assert_eq!(Balances::reserved_balance(&1), 10);
+		assert_eq!(Proposals::active_proposals(10), vec![0]);
+		assert_eq!(Proposals::proposal(0).unwrap().status, ProposalStatus::Active);
+	});
+}
+
+#[test]
+fn approved_proposals_are_executed_after_the_grace_period() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(1);
+		assert_ok!(Proposals::set_parameters(Origin::ROOT, ProposalKind::Spending, Some(parameters(10, 2))));
+		assert_ok!(Proposals::set_parameters(Origin::ROOT, ProposalKind::ParameterChange, Some(parameters(10, 2))));
+		assert_ok!(Proposals::propose(Origin::signed(1), 1, ProposalDetails::Spending(30, 3)));
+		let call = balances::Call::set_balance(4, 20, 0);
+		assert_ok!(Proposals::propose(Origin::signed(1), 1, ProposalDetails::ParameterChange(Box::new(call))));
+
+		assert_noop!(Proposals::vote(Origin::signed(1), 0, VoteKind::Approve), "Not a council member");
+		assert_ok!(Proposals::vote(Origin::signed(10), 0, VoteKind::Approve));
+		assert_noop!(Proposals::vote(Origin::signed(10), 0, VoteKind::Reject), "Already voted");
+		assert_ok!(Proposals::vote(Origin::signed(11), 0, VoteKind::Reject));
+		assert_ok!(Proposals::vote(Origin::signed(11), 1, VoteKind::Approve));
+		assert_ok!(Proposals::vote(Origin::signed(12), 1, VoteKind::Abstain));
+		assert_eq!(Proposals::proposal(0).unwrap().status, ProposalStatus::Active);
+
+		// The whole council voted on the proposal 0.
+		assert_ok!(Proposals::vote(Origin::signed(12), 0, VoteKind::Approve));
+		assert_eq!(Proposals::proposal(0).unwrap().status, ProposalStatus::Approved(3));
+		assert_eq!(Proposals::active_proposals(11), vec![1]);
+
+		run_to_block(4);
+		assert_eq!(Proposals::proposal(0).unwrap().status, ProposalStatus::Executed);
+		assert_eq!(Balances::free_balance(&3), 30);
+
+		// The proposal 1 is tallied at the end of its voting period.
+		assert_eq!(Proposals::proposal(1).unwrap().status, ProposalStatus::Active);
+		run_to_block(12);
+		assert_eq!(Proposals::proposal(1).unwrap().status, ProposalStatus::Approved(13));
+		assert!(!<ActiveProposals<Test>>::exists(11));
+		run_to_block(14);
+		assert_eq!(Proposals::proposal(1).unwrap().status, ProposalStatus::Executed);
+		assert_eq!(Balances::free_balance(&4), 20);
+		assert_eq!(Balances::reserved_balance(&1), 0);
+	});
+}
+
+#[test]
+fn rejected_proposals_slash_the_stake_and_expired_ones_return_it() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(1);
+		assert_ok!(Proposals::set_parameters(Origin::ROOT, ProposalKind::Text, Some(parameters(10, 0))));
+		assert_ok!(Proposals::propose(Origin::signed(1), 1, ProposalDetails::Text(vec![])));
+		assert_ok!(Proposals::propose(Origin::signed(2), 2, ProposalDetails::Text(vec![])));
+
+		assert_ok!(Proposals::vote(Origin::signed(10), 0, VoteKind::Reject));
+		assert_ok!(Proposals::vote(Origin::signed(11), 0, VoteKind::Reject));
+		assert_ok!(Proposals::vote(Origin::signed(12), 0, VoteKind::Approve));
+		assert_eq!(Proposals::proposal(0).unwrap().status, ProposalStatus::Rejected);
+		assert_eq!(Balances::total_balance(&1), 90);
+
+		// One vote out of three misses the quorum.
+		assert_ok!(Proposals::vote(Origin::signed(10), 1, VoteKind::Approve));
+		run_to_block(12);
+		assert_eq!(Proposals::proposal(1).unwrap().status, ProposalStatus::Expired);
+		assert_eq!(Balances::free_balance(&2), 100);
+		assert_noop!(Proposals::vote(Origin::signed(11), 1, VoteKind::Approve), "Proposal is not active");
+	});
+}
+
+#[test]
+fn proposals_without_votes_expire() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(1);
+		assert_noop!(
+			Proposals::set_parameters(Origin::ROOT, ProposalKind::Text, Some(ProposalParameters {
+				approval_quorum: 0,
+				..parameters(10, 0)
+			})),
+			"Approval quorum must not be zero"
+		);
+		assert_ok!(Proposals::set_parameters(Origin::ROOT, ProposalKind::Text, Some(parameters(10, 0))));
+		assert_ok!(Proposals::propose(Origin::signed(1), 1, ProposalDetails::Text(vec![])));
+
+		// Nobody can vote with an empty council.
+		<Council<Test>>::put(Vec::<u64>::new());
+		assert_noop!(Proposals::vote(Origin::signed(10), 0, VoteKind::Approve), "Not a council member");
+		run_to_block(12);
+		assert_eq!(Proposals::proposal(0).unwrap().status, ProposalStatus::Expired);
+		assert_eq!(Balances::free_balance(&1), 100);
+	});
+}
+
+#[test]
+fn proposals_are_vetoed_until_executed() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(1);
+		assert_ok!(Proposals::set_parameters(Origin::ROOT, ProposalKind::RuntimeUpgrade, Some(parameters(10, 2))));
+		for _ in 0..3 {
+			assert_ok!(Proposals::propose(Origin::signed(1), 1, ProposalDetails::RuntimeUpgrade(vec![1, 2, 3])));
+		}
+		for id in 1..3 {
+			for voter in 10..13 {
+				assert_ok!(Proposals::vote(Origin::signed(voter), id, VoteKind::Approve));
+			}
+		}
+
+		assert_noop!(Proposals::veto(Origin::signed(1), 0), "bad origin: expected to be a root origin");
+		assert_ok!(Proposals::veto(Origin::ROOT, 0));
+		assert_ok!(Proposals::veto(Origin::ROOT, 1));
+		assert_noop!(Proposals::veto(Origin::ROOT, 1), "Proposal can't be vetoed");
+		assert_eq!(Proposals::proposal(0).unwrap().status, ProposalStatus::Vetoed);
+		assert_eq!(Proposals::active_proposals(11), Vec::<ProposalId>::new());
+		assert_eq!(Balances::reserved_balance(&1), 0);
+
+		run_to_block(4);
+		assert_eq!(Proposals::proposal(1).unwrap().status, ProposalStatus::Vetoed);
+		assert_eq!(Proposals::proposal(2).unwrap().status, ProposalStatus::Executed);
+		assert_eq!(runtime_io::storage(well_known_keys::CODE), Some(vec![1, 2, 3]));
+		assert_noop!(Proposals::veto(Origin::ROOT, 2), "Proposal can't be vetoed");
+	});
+}
+
+#[test]
+fn proposals_are_weighed_by_their_size_and_votes_by_the_council() {
+	with_externalities(&mut new_test_ext(), || {
+		let weight = |call: Call<Test>| call.get_dispatch_info().weight;
+
+		let upgrade = |code: Vec<u8>| Call::propose(1, ProposalDetails::RuntimeUpgrade(code));
+		assert!(weight(upgrade(vec![])) < weight(upgrade(vec![0; 100])));
+
+		let three_members = weight(Call::vote(0, VoteKind::Approve));
+		<Council<Test>>::put(vec![10, 11, 12, 13]);
+		assert!(three_members < weight(Call::vote(0, VoteKind::Approve)));
+	});
+}
@@ -0,0 +1,305 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Recurring Reward Module
+//!
+//! The Recurring Reward module pays recurring rewards out of mints.
+//!
+//! - [`recurring_reward::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! A reward relationship pays a recipient an amount out of a mint every payout interval. The
+//! payouts due at a block are made when it's initialized: if the mint lacks capacity, the payout
+//! is missed and the next one is still scheduled.
+//!
+//! Each payout is assumed to weigh `PayoutWeight`, and the payouts of a block can't weigh more
+//! than `MaxPayoutWeight`. The payouts over this budget are deferred to the next blocks.
+//!
+//! Relationships are added and removed by other modules, through the `RecurringRewards` trait, or
+//! through the dispatchable functions by `ManagerOrigin`.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `create_relationship` - Add a reward relationship.
+//! - `end_relationship` - Remove a reward relationship.
+//!
+//! ### Public Functions
+//!
+//! - `add_relationship` - Add a reward relationship.
+//! - `remove_relationship` - Remove a reward relationship.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod tests;
+
+use rstd::prelude::*;
+use rstd::marker::PhantomData;
+use codec::{Encode, Decode};
+use srml_support::{StorageValue, StorageMap, Parameter, decl_module, decl_storage, decl_event, ensure};
+use srml_support::dispatch::Result;
+use srml_support::traits::{Get, MintFunds, RecurringRewards};
+use sr_primitives::traits::{SimpleArithmetic, EnsureOrigin, One, Zero, SaturatedConversion};
+use sr_primitives::weights::{Weight, WeighData, ClassifyDispatch, DispatchClass, SimpleDispatchInfo};
+
+/// The identifier of a reward relationship.
+pub type RelationshipId = u64;
+
+/// The weight of the calls besides the payouts due at the same block they walk through.
+const BASE_WEIGHT: Weight = 100_000;
+/// The weight of walking through a payout due at the same block.
+const DUE_PAYOUT_WEIGHT: Weight = 1_000;
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The balance of the rewards.
+	type Balance: Parameter + SimpleArithmetic + Default + Copy;
+
+	/// The identifier of a mint.
+	type MintId: Parameter;
+
+	/// The mints the rewards are paid from.
+	type Mint: MintFunds<Self::AccountId, Self::Balance, MintId = Self::MintId>;
+
+	/// The origin that can add and remove reward relationships.
+	type ManagerOrigin: EnsureOrigin<Self::Origin>;
+
+	/// The weight of a payout.
+	type PayoutWeight: Get<Weight>;
+
+	/// The maximum weight of the payouts of a block.
+	type MaxPayoutWeight: Get<Weight>;
+}
+
+/// A recurring reward.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct RewardRelationship<AccountId, Balance, BlockNumber, MintId> {
+	/// The account paid.
+	pub recipient: AccountId,
+	/// The mint the reward is paid from.
+	pub mint: MintId,
+	/// The amount of each payout.
+	pub amount_per_payout: Balance,
+	/// The number of blocks between payouts.
+	pub payout_interval: BlockNumber,
+	/// The block of the next payout.
+	pub next_payout_at: BlockNumber,
+	/// The amount paid so far.
+	pub total_paid: Balance,
+	/// The amount of the missed payouts so far.
+	pub total_missed: Balance,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as RecurringReward {
+		/// The identifier of the next reward relationship.
+		pub NextRelationshipId get(next_relationship_id): RelationshipId;
+		/// The reward relationships.
+		pub Relationships get(relationship):
+			map RelationshipId => Option<RewardRelationship<T::AccountId, T::Balance, T::BlockNumber, T::MintId>>;
+		/// The reward relationships paid at a block.
+		pub DuePayouts get(due_payouts): map T::BlockNumber => Vec<RelationshipId>;
+		/// The payouts deferred over the budget of the previous blocks, paid first.
+		pub DeferredPayouts get(deferred_payouts): Vec<RelationshipId>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as Trait>::Balance,
+	{
+		/// A reward relationship was added for the recipient.
+		RelationshipCreated(RelationshipId, AccountId),
+		/// A reward relationship was removed.
+		RelationshipEnded(RelationshipId),
+		/// A payout was made to the recipient.
+		PayoutMade(RelationshipId, AccountId, Balance),
+		/// A payout was missed as the mint lacks capacity.
+		PayoutMissed(RelationshipId, Balance),
+		/// Some due payouts were deferred to the next block.
+		PayoutsDeferred(u32),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		/// The weight of a payout.
+		const PayoutWeight: Weight = T::PayoutWeight::get();
+
+		/// The maximum weight of the payouts of a block.
+		const MaxPayoutWeight: Weight = T::MaxPayoutWeight::get();
+
+		fn deposit_event() = default;
+
+		/// Pay `amount_per_payout` to `recipient` out of the mint `mint` every `payout_interval`
+		/// blocks, starting at `first_payout_at`.
+		#[weight = RelationshipWeight::<T>::default()]
+		fn create_relationship(
+			origin,
+			recipient: T::AccountId,
+			mint: T::MintId,
+			amount_per_payout: T::Balance,
+			payout_interval: T::BlockNumber,
+			first_payout_at: T::BlockNumber
+		) -> Result {
+			T::ManagerOrigin::ensure_origin(origin)?;
+			Self::add_relationship(recipient, mint, amount_per_payout, payout_interval, first_payout_at).map(|_| ())
+		}
+
+		/// Remove the reward relationship `relationship_id`.
+		#[weight = SimpleDispatchInfo::FixedOperational(BASE_WEIGHT)]
+		fn end_relationship(origin, #[compact] relationship_id: RelationshipId) -> Result {
+			T::ManagerOrigin::ensure_origin(origin)?;
+			Self::remove_relationship(relationship_id)
+		}
+
+		fn on_initialize(now: T::BlockNumber) {
+			let mut due = DeferredPayouts::take();
+			due.extend(<DuePayouts<T>>::take(now));
+
+			let payout_weight = T::PayoutWeight::get();
+			let mut budget = T::MaxPayoutWeight::get();
+			let mut deferred = Vec::new();
+			for id in due {
+				if budget < payout_weight {
+					deferred.push(id);
+					continue;
+				}
+				// Relationships removed meanwhile don't use the budget.
+				if let Some(relationship) = Self::relationship(id) {
+					budget -= payout_weight;
+					Self::pay(id, relationship, now);
+				}
+			}
+
+			if !deferred.is_empty() {
+				Self::deposit_event(RawEvent::PayoutsDeferred(deferred.len() as u32));
+				DeferredPayouts::put(deferred);
+			}
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Add a reward relationship paying `amount_per_payout` to `recipient` out of the mint `mint`
+	/// every `payout_interval` blocks, starting at `first_payout_at`, which must be in the future.
+	pub fn add_relationship(
+		recipient: T::AccountId,
+		mint: T::MintId,
+		amount_per_payout: T::Balance,
+		payout_interval: T::BlockNumber,
+		first_payout_at: T::BlockNumber,
+	) -> rstd::result::Result<RelationshipId, &'static str> {
+		ensure!(!payout_interval.is_zero(), "Payout interval is zero");
+		ensure!(first_payout_at > <system::Module<T>>::block_number(), "First payout is in the past");
+
+		let id = Self::next_relationship_id();
+		NextRelationshipId::put(id + 1);
+		<DuePayouts<T>>::mutate(first_payout_at, |ids| ids.push(id));
+		<Relationships<T>>::insert(id, RewardRelationship {
+			recipient: recipient.clone(),
+			mint,
+			amount_per_payout,
+			payout_interval,
+			next_payout_at: first_payout_at,
+			total_paid: Zero::zero(),
+			total_missed: Zero::zero(),
+		});
+		Self::deposit_event(RawEvent::RelationshipCreated(id, recipient));
+
+		Ok(id)
+	}
+
+	/// Remove the reward relationship `id`. Its scheduled payouts are skipped.
+	pub fn remove_relationship(id: RelationshipId) -> Result {
+		ensure!(<Relationships<T>>::exists(id), "Unknown reward relationship");
+
+		<Relationships<T>>::remove(id);
+		Self::deposit_event(RawEvent::RelationshipEnded(id));
+
+		Ok(())
+	}
+
+	fn pay(
+		id: RelationshipId,
+		mut relationship: RewardRelationship<T::AccountId, T::Balance, T::BlockNumber, T::MintId>,
+		now: T::BlockNumber,
+	) {
+		let amount = relationship.amount_per_payout;
+		if T::Mint::mint_funds(&relationship.mint, &relationship.recipient, amount).is_ok() {
+			relationship.total_paid += amount;
+			Self::deposit_event(RawEvent::PayoutMade(id, relationship.recipient.clone(), amount));
+		} else {
+			relationship.total_missed += amount;
+			Self::deposit_event(RawEvent::PayoutMissed(id, amount));
+		}
+
+		// Deferred payouts don't shift the schedule, unless they are late by a whole interval.
+		let next = relationship.next_payout_at + relationship.payout_interval;
+		relationship.next_payout_at = if next > now { next } else { now + One::one() };
+		<DuePayouts<T>>::mutate(relationship.next_payout_at, |ids| ids.push(id));
+		<Relationships<T>>::insert(id, relationship);
+	}
+}
+
+/// Weight of `create_relationship`: a step per payout due at the block of the first payout.
+pub struct RelationshipWeight<T>(PhantomData<T>);
+
+impl<T> Default for RelationshipWeight<T> {
+	fn default() -> Self {
+		RelationshipWeight(PhantomData)
+	}
+}
+
+impl<T: Trait, R, M, A, P> WeighData<(R, M, A, P, &T::BlockNumber)> for RelationshipWeight<T> {
+	fn weigh_data(&self, (_, _, _, _, first_payout_at): (R, M, A, P, &T::BlockNumber)) -> Weight {
+		let due: Weight = Module::<T>::due_payouts(first_payout_at).len().saturated_into();
+		BASE_WEIGHT.saturating_add(DUE_PAYOUT_WEIGHT.saturating_mul(due))
+	}
+}
+
+impl<T, A> ClassifyDispatch<A> for RelationshipWeight<T> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		DispatchClass::Operational
+	}
+}
+
+impl<T: Trait> RecurringRewards<T::AccountId, T::Balance, T::BlockNumber> for Module<T> {
+	type MintId = T::MintId;
+	type RelationshipId = RelationshipId;
+
+	fn add_relationship(
+		recipient: T::AccountId,
+		mint: T::MintId,
+		amount_per_payout: T::Balance,
+		payout_interval: T::BlockNumber,
+		first_payout_at: T::BlockNumber,
+	) -> rstd::result::Result<RelationshipId, &'static str> {
+		Self::add_relationship(recipient, mint, amount_per_payout, payout_interval, first_payout_at)
+	}
+
+	fn remove_relationship(relationship: &RelationshipId) -> Result {
+		Self::remove_relationship(*relationship)
+	}
+}
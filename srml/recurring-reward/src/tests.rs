@@ -0,0 +1,173 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use super::*;
+
+use primitives::Blake2Hasher;
+use runtime_io::with_externalities;
+use sr_primitives::weights::GetDispatchInfo;
+use srml_support::{assert_noop, assert_ok, impl_test_runtime, parameter_types, traits::OnInitialize};
+
+mod recurring_reward {
+	// Re-export needed for `impl_outer_event!`.
+	pub use super::super::*;
+}
+
+impl_test_runtime! {
+	pub struct Test;
+	balances {
+		ExistentialDeposit = 0;
+		TransferFee = 0;
+		CreationFee = 0;
+	}
+	events {
+		recurring_reward<T>,
+	}
+}
+impl minting::Trait for Test {
+	type Event = ();
+	type Currency = balances::Module<Test>;
+	type MintId = u32;
+	type GovernanceOrigin = system::EnsureRoot<u64>;
+}
+parameter_types! {
+	pub const PayoutWeight: Weight = 10;
+	pub const MaxPayoutWeight: Weight = 20;
+}
+impl Trait for Test {
+	type Event = TestEvent;
+	type Balance = u64;
+	type MintId = u32;
+	type Mint = minting::Module<Test>;
+	type ManagerOrigin = system::EnsureRoot<u64>;
+	type PayoutWeight = PayoutWeight;
+	type MaxPayoutWeight = MaxPayoutWeight;
+}
+type System = system::Module<Test>;
+type Balances = balances::Module<Test>;
+type Minting = minting::Module<Test>;
+type Rewards = Module<Test>;
+
+fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+	system::GenesisConfig::default().build_storage::<Test>().unwrap().0.into()
+}
+
+fn events() -> Vec<TestEvent> {
+	System::events().into_iter().map(|record| record.event).collect()
+}
+
+fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		Rewards::on_initialize(System::block_number());
+	}
+}
+
+#[test]
+fn relationships_are_paid_every_interval() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(1);
+		assert_ok!(Minting::create_mint(Origin::ROOT, 100, None));
+		assert_noop!(Rewards::create_relationship(Origin::signed(1), 1, 0, 10, 5, 3), "bad origin: expected to be a root origin");
+		assert_noop!(Rewards::create_relationship(Origin::ROOT, 1, 0, 10, 0, 3), "Payout interval is zero");
+		assert_noop!(Rewards::create_relationship(Origin::ROOT, 1, 0, 10, 5, 1), "First payout is in the past");
+		assert_ok!(Rewards::create_relationship(Origin::ROOT, 1, 0, 10, 5, 3));
+		assert_eq!(events(), vec![TestEvent::recurring_reward(RawEvent::RelationshipCreated(0, 1))]);
+
+		run_to_block(2);
+		assert_eq!(Balances::free_balance(&1), 0);
+		run_to_block(3);
+		assert_eq!(Balances::free_balance(&1), 10);
+		assert_eq!(events().pop(), Some(TestEvent::recurring_reward(RawEvent::PayoutMade(0, 1, 10))));
+		run_to_block(12);
+		assert_eq!(Balances::free_balance(&1), 20);
+		run_to_block(13);
+		assert_eq!(Balances::free_balance(&1), 30);
+		let relationship = Rewards::relationship(0).unwrap();
+		assert_eq!((relationship.total_paid, relationship.next_payout_at), (30, 18));
+
+		assert_noop!(Rewards::end_relationship(Origin::ROOT, 1), "Unknown reward relationship");
+		assert_ok!(Rewards::end_relationship(Origin::ROOT, 0));
+		assert_eq!(events().pop(), Some(TestEvent::recurring_reward(RawEvent::RelationshipEnded(0))));
+		run_to_block(20);
+		assert_eq!(Balances::free_balance(&1), 30);
+	});
+}
+
+#[test]
+fn payouts_are_missed_when_the_mint_lacks_capacity() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(1);
+		assert_ok!(Minting::create_mint(Origin::ROOT, 15, None));
+		assert_ok!(Rewards::add_relationship(1, 0, 10, 5, 2));
+		assert_ok!(Rewards::add_relationship(2, 1, 10, 5, 2));
+
+		run_to_block(7);
+		assert_eq!(Balances::free_balance(&1), 10);
+		assert_eq!(Balances::free_balance(&2), 0);
+		let relationship = Rewards::relationship(0).unwrap();
+		assert_eq!((relationship.total_paid, relationship.total_missed), (10, 10));
+		assert_eq!(Rewards::relationship(1).unwrap().total_missed, 20);
+		assert_eq!(events(), vec![
+			RawEvent::RelationshipCreated(0, 1),
+			RawEvent::RelationshipCreated(1, 2),
+			RawEvent::PayoutMade(0, 1, 10),
+			RawEvent::PayoutMissed(1, 10),
+			RawEvent::PayoutMissed(0, 10),
+			RawEvent::PayoutMissed(1, 10),
+		].into_iter().map(TestEvent::recurring_reward).collect::<Vec<_>>());
+	});
+}
+
+#[test]
+fn payouts_over_the_weight_budget_are_deferred() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(1);
+		assert_ok!(Minting::create_mint(Origin::ROOT, 1000, None));
+		for who in 1..6 {
+			assert_ok!(Rewards::add_relationship(who, 0, 10, 10, 2));
+		}
+
+		run_to_block(2);
+		assert_eq!((1..6).map(|who| Balances::free_balance(&who)).collect::<Vec<_>>(), vec![10, 10, 0, 0, 0]);
+		assert_eq!(Rewards::deferred_payouts(), vec![2, 3, 4]);
+		assert_eq!(events().pop(), Some(TestEvent::recurring_reward(RawEvent::PayoutsDeferred(3))));
+
+		// Removed relationships don't use the budget.
+		assert_ok!(Rewards::remove_relationship(3));
+		run_to_block(3);
+		assert_eq!((1..6).map(|who| Balances::free_balance(&who)).collect::<Vec<_>>(), vec![10, 10, 10, 0, 10]);
+		assert_eq!(Rewards::deferred_payouts(), Vec::<RelationshipId>::new());
+
+		// Deferred payouts keep their schedule.
+		assert_eq!(Rewards::relationship(2).unwrap().next_payout_at, 12);
+	});
+}
+
+#[test]
+fn relationships_are_weighed_by_the_payouts_due_at_the_same_block() {
+	with_externalities(&mut new_test_ext(), || {
+		let create = || Call::<Test>::create_relationship(1, 0, 10, 5, 3).get_dispatch_info();
+		let first = create();
+		assert_eq!(first.class, DispatchClass::Operational);
+
+		assert_ok!(Rewards::create_relationship(Origin::ROOT, 1, 0, 10, 5, 3));
+		assert!(first.weight < create().weight);
+		assert_eq!(first.weight, Call::<Test>::create_relationship(1, 0, 10, 5, 4).get_dispatch_info().weight);
+	});
+}
@@ -120,7 +120,7 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use rstd::{prelude::*, marker::PhantomData, ops::{Sub, Rem}};
-use codec::Decode;
+use codec::{Encode, Decode};
 use sr_primitives::KeyTypeId;
 use sr_primitives::weights::SimpleDispatchInfo;
 use sr_primitives::traits::{Convert, Zero, Member, OpaqueKeys, TypedKey};
@@ -340,10 +340,12 @@ decl_storage! {
 				let queued_keys: Vec<_> = initial_validators
 					.iter()
 					.cloned()
-					.map(|v| (
-						v.clone(),
-						<Module<T>>::load_keys(&v).unwrap_or_default(),
-					))
+					.map(|v| {
+						let keys = <Module<T>>::load_keys(&v).unwrap_or_else(||
+							panic!("genesis config contains no session keys for initial validator {:?}", v)
+						);
+						(v, keys)
+					})
 					.collect();
 
 				<Validators<T>>::put(initial_validators);
@@ -373,6 +375,9 @@ decl_module! {
 		/// Allows an account to set its session key prior to becoming a validator.
 		/// This doesn't take effect until the next session.
 		///
+		/// `proof` has to prove that the caller owns the keys, see `OpaqueKeys::ownership_proof_is_valid`.
+		/// The `author_rotateKeys` RPC of a node returns both the keys and the proof.
+		///
 		/// The dispatch origin of this function must be signed.
 		///
 		/// # <weight>
@@ -383,7 +388,7 @@ decl_module! {
 		fn set_keys(origin, keys: T::Keys, proof: Vec<u8>) -> Result {
 			let who = ensure_signed(origin)?;
 
-			ensure!(keys.ownership_proof_is_valid(&proof), "invalid ownership proof");
+			ensure!(keys.ownership_proof_is_valid(&who.encode(), &proof), "invalid ownership proof");
 
 			let who = match T::ValidatorIdOf::convert(who) {
 				Some(val_id) => val_id,
@@ -455,6 +460,17 @@ impl<T: Trait> Module<T> {
 		T::SessionHandler::on_new_session::<T::Keys>(changed, &session_keys, &queued_amalgamated);
 	}
 
+	/// Rotate sessions until the session `index` has started. Only used for tests.
+	///
+	/// Skips the blocks in between: the session manager is told about every session ending,
+	/// so e.g. the staking module moves to new eras on the way.
+	#[cfg(any(feature = "std", test))]
+	pub fn rotate_to_session(index: SessionIndex) {
+		while CurrentIndex::get() < index {
+			Self::rotate_session();
+		}
+	}
+
 	/// Disable the validator of index `i`.
 	pub fn disable_index(i: usize) {
 		T::SessionHandler::on_disabled(i);
@@ -568,8 +584,9 @@ mod tests {
 		testing::UintAuthorityId,
 	};
 	use mock::{
-		NEXT_VALIDATORS, SESSION_CHANGED, TEST_SESSION_CHANGED, authorities, force_new_session,
-		set_next_validators, set_session_length, session_changed, Test, Origin, System, Session,
+		NEXT_VALIDATORS, SESSION_CHANGED, TEST_SESSION_CHANGED, INITIAL_VALIDATORS, authorities,
+		force_new_session, set_next_validators, set_session_length, session_changed, Test, Origin,
+		System, Session,
 	};
 
 	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
@@ -596,6 +613,17 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn rotate_to_session_skips_sessions() {
+		with_externalities(&mut new_test_ext(), || {
+			Session::rotate_to_session(5);
+			assert_eq!(Session::current_index(), 5);
+
+			Session::rotate_to_session(3);
+			assert_eq!(Session::current_index(), 5);
+		});
+	}
+
 	#[test]
 	fn put_get_keys() {
 		with_externalities(&mut new_test_ext(), || {
@@ -799,4 +827,11 @@ mod tests {
 
 		assert!(P::should_end_session(13));
 	}
+
+	#[test]
+	#[should_panic(expected = "genesis config contains no session keys for initial validator 4")]
+	fn initial_validators_must_have_session_keys() {
+		INITIAL_VALIDATORS.with(|l| *l.borrow_mut() = Some(vec![1, 4]));
+		new_test_ext();
+	}
 }
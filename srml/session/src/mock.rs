@@ -39,6 +39,7 @@ thread_local! {
 	pub static SESSION_LENGTH: RefCell<u64> = RefCell::new(2);
 	pub static SESSION_CHANGED: RefCell<bool> = RefCell::new(false);
 	pub static TEST_SESSION_CHANGED: RefCell<bool> = RefCell::new(false);
+	pub static INITIAL_VALIDATORS: RefCell<Option<Vec<u64>>> = RefCell::new(None);
 }
 
 pub struct TestShouldEndSession;
@@ -64,6 +65,13 @@ impl SessionHandler<u64> for TestSessionHandler {
 	fn on_disabled(_validator_index: usize) {}
 }
 
+pub struct TestSelectInitialValidators;
+impl SelectInitialValidators<u64> for TestSelectInitialValidators {
+	fn select_initial_validators() -> Option<Vec<u64>> {
+		INITIAL_VALIDATORS.with(|l| l.borrow().clone())
+	}
+}
+
 pub struct TestOnSessionEnding;
 impl OnSessionEnding<u64> for TestOnSessionEnding {
 	fn on_session_ending(_: SessionIndex, _: SessionIndex) -> Option<Vec<u64>> {
@@ -133,6 +141,9 @@ impl system::Trait for Test {
 	type BlockHashCount = BlockHashCount;
 	type MaximumBlockWeight = MaximumBlockWeight;
 	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = ();
+	type Call = ();
+	type BaseCallFilter = ();
 	type MaximumBlockLength = MaximumBlockLength;
 }
 impl timestamp::Trait for Test {
@@ -153,7 +164,7 @@ impl Trait for Test {
 	type ValidatorIdOf = ConvertInto;
 	type Keys = UintAuthorityId;
 	type Event = ();
-	type SelectInitialValidators = ();
+	type SelectInitialValidators = TestSelectInitialValidators;
 }
 
 #[cfg(feature = "historical")]
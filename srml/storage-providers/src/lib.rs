@@ -0,0 +1,356 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Storage Providers Module
+//!
+//! The Storage Providers module records which storage provider is accountable for each data
+//! object uploaded to the network.
+//!
+//! - [`storage_providers::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! An account becomes a storage provider by staking at least `MinimumStake`, which is reserved.
+//! Each data object added is assigned an active storage provider as its liaison, in turn. The
+//! liaison receives the upload off chain and then accepts or rejects the data object.
+//!
+//! A storage provider leaves by requesting to exit: it isn't assigned new data objects anymore
+//! and its stake is returned after `UnbondingPeriod` blocks, when it withdraws. Its stake can be
+//! slashed by `SlashOrigin` until then. A storage provider whose stake is slashed below
+//! `MinimumStake` exits.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `register` - Become a storage provider.
+//! - `request_exit` - Stop being assigned data objects and start unbonding.
+//! - `withdraw` - Get the stake back after unbonding.
+//! - `slash` - Slash the stake of a storage provider.
+//! - `add_data_object` - Add a data object and assign it a liaison.
+//! - `accept_data_object` - Accept a data object as its liaison.
+//! - `reject_data_object` - Reject a data object as its liaison.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod tests;
+
+use rstd::prelude::*;
+use rstd::marker::PhantomData;
+use codec::{Encode, Decode};
+use srml_support::{StorageValue, StorageMap, Parameter, decl_module, decl_storage, decl_event, ensure};
+use srml_support::dispatch::Result;
+use srml_support::traits::{Contains, Currency, ReservableCurrency, OnUnbalanced, Get};
+use sr_primitives::traits::{SimpleArithmetic, EnsureOrigin, StaticLookup, One, SaturatedConversion};
+use sr_primitives::weights::{Weight, WeighData, ClassifyDispatch, DispatchClass, SimpleDispatchInfo};
+use system::ensure_signed;
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
+
+/// The weight of the calls besides the active storage providers they walk through.
+const BASE_WEIGHT: Weight = 50_000;
+/// The weight of walking through an active storage provider.
+const PROVIDER_WEIGHT: Weight = 1_000;
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The currency the stakes are reserved in.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The identifier of a data object.
+	type DataObjectId: Parameter + SimpleArithmetic + Default + Copy;
+
+	/// The minimum stake of a storage provider.
+	type MinimumStake: Get<BalanceOf<Self>>;
+
+	/// The number of blocks after an exit request before the stake can be withdrawn.
+	type UnbondingPeriod: Get<Self::BlockNumber>;
+
+	/// The origin that slashes storage providers.
+	type SlashOrigin: EnsureOrigin<Self::Origin>;
+
+	/// Handler for the slashed stakes.
+	type Slashed: OnUnbalanced<NegativeImbalanceOf<Self>>;
+}
+
+/// A storage provider.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct StorageProvider<Balance, BlockNumber> {
+	/// The reserved stake.
+	pub stake: Balance,
+	/// The block from which the stake can be withdrawn, if the storage provider is exiting.
+	pub exit_at: Option<BlockNumber>,
+}
+
+/// The status of a data object.
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum DataObjectStatus {
+	/// The data object waits for its liaison.
+	Pending,
+	/// The liaison stores the data object.
+	Accepted,
+	/// The liaison rejected the data object.
+	Rejected,
+}
+
+/// A data object.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct DataObject<AccountId, Hash, BlockNumber> {
+	/// The account that added the data object.
+	pub owner: AccountId,
+	/// The hash of the content of the data object.
+	pub content_hash: Hash,
+	/// The size of the data object, in bytes.
+	pub size: u64,
+	/// The storage provider receiving the data object.
+	pub liaison: AccountId,
+	/// The status of the data object.
+	pub status: DataObjectStatus,
+	/// The block the data object was added at.
+	pub added_at: BlockNumber,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as StorageProviders {
+		/// The storage providers, active or exiting.
+		pub Providers get(provider): map T::AccountId => Option<StorageProvider<BalanceOf<T>, T::BlockNumber>>;
+		/// The storage providers that are assigned data objects.
+		pub ActiveProviders get(active_providers): Vec<T::AccountId>;
+		/// The index in `ActiveProviders` of the liaison of the next data object.
+		NextLiaison: u32;
+
+		/// The identifier of the next data object.
+		pub NextDataObjectId get(next_data_object_id): T::DataObjectId;
+		/// The data objects.
+		pub DataObjects get(data_object):
+			map T::DataObjectId => Option<DataObject<T::AccountId, T::Hash, T::BlockNumber>>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as system::Trait>::BlockNumber,
+		<T as Trait>::DataObjectId,
+		Balance = BalanceOf<T>,
+	{
+		/// An account became a storage provider with the stake.
+		ProviderRegistered(AccountId, Balance),
+		/// A storage provider started exiting, and can withdraw from the block.
+		ExitRequested(AccountId, BlockNumber),
+		/// A storage provider withdrew its stake.
+		ProviderExited(AccountId, Balance),
+		/// The stake of a storage provider was slashed by the amount.
+		ProviderSlashed(AccountId, Balance),
+		/// A data object was added by the account, with the storage provider as its liaison.
+		DataObjectAdded(DataObjectId, AccountId, AccountId),
+		/// A data object was accepted by its liaison.
+		DataObjectAccepted(DataObjectId),
+		/// A data object was rejected by its liaison.
+		DataObjectRejected(DataObjectId),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		/// The minimum stake of a storage provider.
+		const MinimumStake: BalanceOf<T> = T::MinimumStake::get();
+
+		/// The number of blocks after an exit request before the stake can be withdrawn.
+		const UnbondingPeriod: T::BlockNumber = T::UnbondingPeriod::get();
+
+		fn deposit_event() = default;
+
+		/// Become a storage provider, reserving `stake`.
+		#[weight = ProvidersWeight::<T>::new(DispatchClass::Normal)]
+		fn register(origin, #[compact] stake: BalanceOf<T>) -> Result {
+			let who = ensure_signed(origin)?;
+			ensure!(!<Providers<T>>::exists(&who), "Already a storage provider");
+			ensure!(stake >= T::MinimumStake::get(), "Stake is too low");
+
+			T::Currency::reserve(&who, stake)?;
+			<Providers<T>>::insert(&who, StorageProvider { stake, exit_at: None });
+			<ActiveProviders<T>>::mutate(|providers| providers.push(who.clone()));
+			Self::deposit_event(RawEvent::ProviderRegistered(who, stake));
+
+			Ok(())
+		}
+
+		/// Stop being assigned data objects, and start unbonding the stake.
+		#[weight = ProvidersWeight::<T>::new(DispatchClass::Normal)]
+		fn request_exit(origin) -> Result {
+			let who = ensure_signed(origin)?;
+			let provider = Self::provider(&who).ok_or("Not a storage provider")?;
+			ensure!(provider.exit_at.is_none(), "Already exiting");
+
+			Self::start_exit(&who, provider);
+			Ok(())
+		}
+
+		/// Unreserve the stake of the sender, which must have finished unbonding.
+		#[weight = SimpleDispatchInfo::FixedNormal(BASE_WEIGHT)]
+		fn withdraw(origin) -> Result {
+			let who = ensure_signed(origin)?;
+			let provider = Self::provider(&who).ok_or("Not a storage provider")?;
+			let exit_at = provider.exit_at.ok_or("Not exiting")?;
+			ensure!(<system::Module<T>>::block_number() >= exit_at, "Stake is still unbonding");
+
+			T::Currency::unreserve(&who, provider.stake);
+			<Providers<T>>::remove(&who);
+			Self::deposit_event(RawEvent::ProviderExited(who, provider.stake));
+
+			Ok(())
+		}
+
+		/// Slash up to `amount` of the stake of the storage provider `who`.
+		///
+		/// The storage provider exits if its stake drops below `MinimumStake`.
+		#[weight = ProvidersWeight::<T>::new(DispatchClass::Operational)]
+		fn slash(
+			origin,
+			who: <T::Lookup as StaticLookup>::Source,
+			#[compact] amount: BalanceOf<T>
+		) -> Result {
+			T::SlashOrigin::ensure_origin(origin)?;
+			let who = T::Lookup::lookup(who)?;
+			let mut provider = Self::provider(&who).ok_or("Not a storage provider")?;
+
+			let amount = amount.min(provider.stake);
+			let (imbalance, remaining) = T::Currency::slash_reserved(&who, amount);
+			T::Slashed::on_unbalanced(imbalance);
+			let slashed = amount - remaining;
+			provider.stake -= slashed;
+			Self::deposit_event(RawEvent::ProviderSlashed(who.clone(), slashed));
+
+			if provider.exit_at.is_none() && provider.stake < T::MinimumStake::get() {
+				Self::start_exit(&who, provider);
+			} else {
+				<Providers<T>>::insert(&who, provider);
+			}
+
+			Ok(())
+		}
+
+		/// Add a data object of `size` bytes whose content hashes to `content_hash`, and assign it
+		/// the next active storage provider as its liaison.
+		#[weight = ProvidersWeight::<T>::new(DispatchClass::Normal)]
+		fn add_data_object(origin, content_hash: T::Hash, #[compact] size: u64) -> Result {
+			let who = ensure_signed(origin)?;
+			let providers = Self::active_providers();
+			ensure!(!providers.is_empty(), "No storage provider available");
+
+			let index = NextLiaison::get() as usize % providers.len();
+			let liaison = providers[index].clone();
+			NextLiaison::put(((index + 1) % providers.len()) as u32);
+
+			let id = Self::next_data_object_id();
+			<NextDataObjectId<T>>::mutate(|id| *id += One::one());
+			<DataObjects<T>>::insert(id, DataObject {
+				owner: who.clone(),
+				content_hash,
+				size,
+				liaison: liaison.clone(),
+				status: DataObjectStatus::Pending,
+				added_at: <system::Module<T>>::block_number(),
+			});
+			Self::deposit_event(RawEvent::DataObjectAdded(id, who, liaison));
+
+			Ok(())
+		}
+
+		/// Accept the pending data object `id`, of which the sender is the liaison.
+		#[weight = SimpleDispatchInfo::FixedNormal(BASE_WEIGHT)]
+		fn accept_data_object(origin, #[compact] id: T::DataObjectId) -> Result {
+			let who = ensure_signed(origin)?;
+			Self::decide(&who, id, DataObjectStatus::Accepted)?;
+			Self::deposit_event(RawEvent::DataObjectAccepted(id));
+			Ok(())
+		}
+
+		/// Reject the pending data object `id`, of which the sender is the liaison.
+		#[weight = SimpleDispatchInfo::FixedNormal(BASE_WEIGHT)]
+		fn reject_data_object(origin, #[compact] id: T::DataObjectId) -> Result {
+			let who = ensure_signed(origin)?;
+			Self::decide(&who, id, DataObjectStatus::Rejected)?;
+			Self::deposit_event(RawEvent::DataObjectRejected(id));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Whether `who` is a storage provider that is assigned data objects.
+	pub fn is_active_provider(who: &T::AccountId) -> bool {
+		Self::provider(who).map_or(false, |provider| provider.exit_at.is_none())
+	}
+
+	fn start_exit(who: &T::AccountId, mut provider: StorageProvider<BalanceOf<T>, T::BlockNumber>) {
+		let exit_at = <system::Module<T>>::block_number() + T::UnbondingPeriod::get();
+		provider.exit_at = Some(exit_at);
+		<Providers<T>>::insert(who, provider);
+		<ActiveProviders<T>>::mutate(|providers| providers.retain(|provider| provider != who));
+		Self::deposit_event(RawEvent::ExitRequested(who.clone(), exit_at));
+	}
+
+	fn decide(who: &T::AccountId, id: T::DataObjectId, status: DataObjectStatus) -> Result {
+		let mut object = Self::data_object(id).ok_or("Unknown data object")?;
+		ensure!(object.liaison == *who, "Not the liaison of the data object");
+		ensure!(object.status == DataObjectStatus::Pending, "Data object is not pending");
+
+		object.status = status;
+		<DataObjects<T>>::insert(id, object);
+		Ok(())
+	}
+}
+
+/// Weight of the calls which read or write `ActiveProviders`: a step per active storage provider.
+pub struct ProvidersWeight<T>(DispatchClass, PhantomData<T>);
+
+impl<T> ProvidersWeight<T> {
+	/// The weight of a call of the dispatch `class`.
+	pub fn new(class: DispatchClass) -> Self {
+		ProvidersWeight(class, PhantomData)
+	}
+}
+
+impl<T: Trait, A> WeighData<A> for ProvidersWeight<T> {
+	fn weigh_data(&self, _: A) -> Weight {
+		let providers: Weight = Module::<T>::active_providers().len().saturated_into();
+		BASE_WEIGHT.saturating_add(PROVIDER_WEIGHT.saturating_mul(providers))
+	}
+}
+
+impl<T, A> ClassifyDispatch<A> for ProvidersWeight<T> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		self.0
+	}
+}
+
+/// The active storage providers, e.g. for the discovery module.
+impl<T: Trait> Contains<T::AccountId> for Module<T> {
+	fn contains(who: &T::AccountId) -> bool {
+		Self::is_active_provider(who)
+	}
+}
@@ -0,0 +1,163 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use super::*;
+
+use primitives::{Blake2Hasher, H256};
+use runtime_io::with_externalities;
+use sr_primitives::weights::GetDispatchInfo;
+use srml_support::{assert_noop, assert_ok, impl_test_runtime, parameter_types};
+
+impl_test_runtime! {
+	pub struct Test;
+	balances {
+		ExistentialDeposit = 0;
+		TransferFee = 0;
+		CreationFee = 0;
+	}
+}
+parameter_types! {
+	pub const MinimumStake: u64 = 50;
+	pub const UnbondingPeriod: u64 = 10;
+}
+impl Trait for Test {
+	type Event = ();
+	type Currency = balances::Module<Test>;
+	type DataObjectId = u64;
+	type MinimumStake = MinimumStake;
+	type UnbondingPeriod = UnbondingPeriod;
+	type SlashOrigin = system::EnsureRoot<u64>;
+	type Slashed = ();
+}
+type System = system::Module<Test>;
+type Balances = balances::Module<Test>;
+type Providers = Module<Test>;
+
+fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap().0;
+	t.extend(balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100), (3, 100)],
+		vesting: vec![],
+	}.build_storage().unwrap().0);
+	t.into()
+}
+
+#[test]
+fn storage_providers_stake_to_register() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_noop!(Providers::register(Origin::signed(1), 49), "Stake is too low");
+		assert_noop!(Providers::register(Origin::signed(1), 101), "not enough free funds");
+		assert_ok!(Providers::register(Origin::signed(1), 60));
+		assert_noop!(Providers::register(Origin::signed(1), 60), "Already a storage provider");
+
+		assert_eq!(Balances::reserved_balance(&1), 60);
+		assert_eq!(Providers::provider(&1), Some(StorageProvider { stake: 60, exit_at: None }));
+		assert_eq!(Providers::active_providers(), vec![1]);
+		assert!(Providers::is_active_provider(&1));
+	});
+}
+
+#[test]
+fn exiting_storage_providers_withdraw_after_unbonding() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(1);
+		assert_ok!(Providers::register(Origin::signed(1), 60));
+		assert_noop!(Providers::withdraw(Origin::signed(1)), "Not exiting");
+
+		assert_ok!(Providers::request_exit(Origin::signed(1)));
+		assert_noop!(Providers::request_exit(Origin::signed(1)), "Already exiting");
+		assert_eq!(Providers::active_providers(), Vec::<u64>::new());
+		assert!(!Providers::is_active_provider(&1));
+
+		System::set_block_number(10);
+		assert_noop!(Providers::withdraw(Origin::signed(1)), "Stake is still unbonding");
+		System::set_block_number(11);
+		assert_ok!(Providers::withdraw(Origin::signed(1)));
+		assert_eq!(Balances::reserved_balance(&1), 0);
+		assert_eq!(Providers::provider(&1), None);
+	});
+}
+
+#[test]
+fn slashed_storage_providers_exit_below_the_minimum_stake() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Providers::register(Origin::signed(1), 60));
+		assert_noop!(Providers::slash(Origin::signed(2), 1, 10), "bad origin: expected to be a root origin");
+		assert_noop!(Providers::slash(Origin::ROOT, 2, 10), "Not a storage provider");
+
+		assert_ok!(Providers::slash(Origin::ROOT, 1, 10));
+		assert_eq!(Providers::provider(&1), Some(StorageProvider { stake: 50, exit_at: None }));
+		assert_eq!(Balances::total_balance(&1), 90);
+
+		assert_ok!(Providers::slash(Origin::ROOT, 1, 1));
+		assert_eq!(Providers::provider(&1), Some(StorageProvider { stake: 49, exit_at: Some(10) }));
+		assert_eq!(Providers::active_providers(), Vec::<u64>::new());
+
+		// Exiting storage providers can still be slashed, up to their stake.
+		assert_ok!(Providers::slash(Origin::ROOT, 1, 100));
+		assert_eq!(Providers::provider(&1).unwrap().stake, 0);
+		assert_eq!(Balances::total_balance(&1), 40);
+	});
+}
+
+#[test]
+fn data_objects_are_decided_by_their_liaison() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_noop!(
+			Providers::add_data_object(Origin::signed(3), H256::repeat_byte(1), 1024),
+			"No storage provider available"
+		);
+		assert_ok!(Providers::register(Origin::signed(1), 50));
+		assert_ok!(Providers::register(Origin::signed(2), 50));
+
+		// Liaisons are assigned in turn.
+		for i in 0..3 {
+			assert_ok!(Providers::add_data_object(Origin::signed(3), H256::repeat_byte(i), 1024));
+		}
+		let liaisons = (0..3).map(|id| Providers::data_object(id).unwrap().liaison).collect::<Vec<_>>();
+		assert_eq!(liaisons, vec![1, 2, 1]);
+
+		assert_noop!(Providers::accept_data_object(Origin::signed(2), 0), "Not the liaison of the data object");
+		assert_ok!(Providers::accept_data_object(Origin::signed(1), 0));
+		assert_noop!(Providers::reject_data_object(Origin::signed(1), 0), "Data object is not pending");
+		assert_ok!(Providers::reject_data_object(Origin::signed(2), 1));
+		assert_eq!(Providers::data_object(0).unwrap().status, DataObjectStatus::Accepted);
+		assert_eq!(Providers::data_object(1).unwrap().status, DataObjectStatus::Rejected);
+
+		// Exiting storage providers aren't assigned data objects, but decide their pending ones.
+		assert_ok!(Providers::request_exit(Origin::signed(1)));
+		assert_ok!(Providers::add_data_object(Origin::signed(3), H256::zero(), 1024));
+		assert_eq!(Providers::data_object(3).unwrap().liaison, 2);
+		assert_ok!(Providers::accept_data_object(Origin::signed(1), 2));
+	});
+}
+
+#[test]
+fn data_objects_are_weighed_by_the_active_storage_providers() {
+	with_externalities(&mut new_test_ext(), || {
+		let add = || Call::<Test>::add_data_object(H256::repeat_byte(1), 10).get_dispatch_info();
+		assert_ok!(Providers::register(Origin::signed(1), 50));
+		let one_provider = add().weight;
+		assert_ok!(Providers::register(Origin::signed(2), 50));
+		assert!(one_provider < add().weight);
+
+		let slash = Call::<Test>::slash(1, 10).get_dispatch_info();
+		assert_eq!(slash.class, DispatchClass::Operational);
+		assert_eq!(slash.weight, add().weight);
+	});
+}
@@ -26,14 +26,27 @@ pub use srml_metadata::{
 	ModuleConstantMetadata, DefaultByte, DefaultByteGetter,
 };
 pub use sr_primitives::weights::{SimpleDispatchInfo, GetDispatchInfo, DispatchInfo, WeighData,
-	ClassifyDispatch,
+	ClassifyDispatch, PostDispatchInfo,
 	TransactionPriority
 };
-pub use sr_primitives::traits::{Dispatchable, DispatchResult};
+pub use sr_primitives::traits::{
+	Dispatchable, DispatchResult, DispatchResultWithPostInfo, IntoDispatchResultWithPostInfo,
+};
 
 /// A type that cannot be instantiated.
 pub enum Never {}
 
+/// Run `f` within a tracing span called `name`.
+///
+/// Used by `decl_module!` to trace the dispatch of every call.
+#[doc(hidden)]
+pub fn traced<R, F: FnOnce() -> R>(name: &'static str, f: F) -> R {
+	runtime_io::trace_enter_span(name);
+	let result = f();
+	runtime_io::trace_exit_span();
+	result
+}
+
 /// Result of a module function call; either nothing (functions are only called for "side effects")
 /// or an error message.
 pub type Result = DispatchResult;
@@ -1212,14 +1225,25 @@ macro_rules! decl_module {
 		{
 			type Trait = $trait_instance;
 			type Origin = $origin_type;
-			fn dispatch(self, _origin: Self::Origin) -> $crate::dispatch::Result {
+			fn dispatch(self, origin: Self::Origin) -> $crate::dispatch::Result {
+				$crate::dispatch::Dispatchable::dispatch_with_post_info(self, origin).0
+			}
+			fn dispatch_with_post_info(
+				self,
+				_origin: Self::Origin,
+			) -> ($crate::dispatch::Result, $crate::dispatch::PostDispatchInfo) {
 				match self {
 					$(
 						$call_type::$fn_name( $( $param_name ),* ) => {
-							$crate::decl_module!(
-								@call
-								$from
-								$mod_type<$trait_instance $(, $fn_instance)?> $fn_name _origin $system [ $( $param_name ),* ]
+							$crate::dispatch::traced(
+								concat!(module_path!(), "::", stringify!($fn_name)),
+								|| $crate::dispatch::IntoDispatchResultWithPostInfo::split_post_info(
+									$crate::decl_module!(
+										@call
+										$from
+										$mod_type<$trait_instance $(, $fn_instance)?> $fn_name _origin $system [ $( $param_name ),* ]
+									)
+								),
 							)
 						},
 					)*
@@ -1301,6 +1325,15 @@ macro_rules! impl_outer_dispatch {
 					$( $call_type::$camelcase(call) => call.dispatch(origin), )*
 				}
 			}
+			fn dispatch_with_post_info(
+				self,
+				origin: $origin,
+			) -> ($crate::dispatch::Result, $crate::dispatch::PostDispatchInfo) {
+				match self {
+					$( $call_type::$camelcase(call) =>
+						$crate::dispatch::Dispatchable::dispatch_with_post_info(call, origin), )*
+				}
+			}
 		}
 		$(
 			impl $crate::dispatch::IsSubType<$camelcase, $runtime> for $call_type {
@@ -58,6 +58,9 @@ mod runtime;
 pub mod inherent;
 #[macro_use]
 pub mod unsigned;
+#[macro_use]
+mod test_runtime;
+pub mod offchain;
 mod double_map;
 pub mod traits;
 
@@ -0,0 +1,135 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Helpers for submitting signed transactions from offchain workers.
+//!
+//! A module declares a `SubmitSignedTransaction` type in its trait and its offchain worker calls
+//! `sign_and_submit` with a call and the public key to sign with. The runtime usually sets it to
+//! a `TransactionSubmitter` that signs with the node's keystore through `KeystoreSigner` and
+//! builds the extrinsic with its own `CreateTransaction` implementation.
+
+use crate::rstd::{prelude::*, marker::PhantomData};
+use crate::codec::{Encode, Decode};
+use primitives::crypto::TypedKey;
+
+/// Something that can sign a payload with a given public key.
+pub trait Signer<Public, Signature> {
+	/// Returns the public keys this signer can sign with.
+	fn public_keys() -> Vec<Public>;
+
+	/// Sign the SCALE-encoded `payload` with the key `public`.
+	///
+	/// Returns `None` if the key is not available.
+	fn sign<Payload: Encode>(public: Public, payload: &Payload) -> Option<Signature>;
+}
+
+/// A `Signer` backed by the node's keystore.
+///
+/// Keys are looked up by the key type of `Public`. The raw signature returned by the keystore
+/// is decoded into `Signature`.
+pub struct KeystoreSigner;
+
+impl<Public, Signature> Signer<Public, Signature> for KeystoreSigner where
+	Public: TypedKey + AsRef<[u8]> + Decode,
+	Signature: Decode,
+{
+	fn public_keys() -> Vec<Public> {
+		runtime_io::keystore_public_keys(Public::KEY_TYPE)
+			.unwrap_or_default()
+			.into_iter()
+			.filter_map(|key| Public::decode(&mut &key[..]).ok())
+			.collect()
+	}
+
+	fn sign<Payload: Encode>(public: Public, payload: &Payload) -> Option<Signature> {
+		let signature = payload.using_encoded(|payload| {
+			runtime_io::keystore_sign(Public::KEY_TYPE, public.as_ref(), payload)
+		}).ok()?;
+		Signature::decode(&mut &signature[..]).ok()
+	}
+}
+
+/// Creates a runtime specific signed transaction.
+///
+/// Implemented by the runtime, which knows the extrinsic format and the signed extensions.
+pub trait CreateTransaction<Call> {
+	/// The public key type that signs the transaction.
+	type Public;
+	/// The account that sends the transaction.
+	type AccountId;
+	/// The transaction index of the sender.
+	type Index;
+	/// The signature type of the transaction.
+	type Signature;
+	/// The extrinsic that is created.
+	type Extrinsic: Encode;
+
+	/// Create a transaction of `call` sent by `account` with the transaction index `index`,
+	/// signed by `public` using `S`.
+	///
+	/// Returns `None` if the transaction could not be created or signed.
+	fn create_transaction<S: Signer<Self::Public, Self::Signature>>(
+		call: Call,
+		public: Self::Public,
+		account: Self::AccountId,
+		index: Self::Index,
+	) -> Option<Self::Extrinsic>;
+}
+
+/// Something that knows the next transaction index of an account.
+pub trait AccountIndex<AccountId, Index> {
+	/// Returns the index the next transaction of `who` must have.
+	fn account_index(who: &AccountId) -> Index;
+}
+
+/// Something that can sign a call and submit it as a transaction to the local transaction pool.
+pub trait SubmitSignedTransaction<Call> {
+	/// The public key type that signs transactions.
+	type Public;
+
+	/// Returns the public keys that transactions can be signed with.
+	fn public_keys() -> Vec<Self::Public>;
+
+	/// Sign `call` with the key `public` and submit the resulting extrinsic.
+	///
+	/// The transaction is sent by the account of `public`.
+	fn sign_and_submit(call: impl Into<Call>, public: Self::Public) -> Result<(), ()>;
+}
+
+/// Default `SubmitSignedTransaction` implementation.
+///
+/// Signs with `S`, creates the extrinsic with `C` and takes the transaction index from `N`.
+pub struct TransactionSubmitter<S, C, N>(PhantomData<(S, C, N)>);
+
+impl<Call, S, C, N> SubmitSignedTransaction<Call> for TransactionSubmitter<S, C, N> where
+	C: CreateTransaction<Call>,
+	C::Public: Clone + Into<C::AccountId>,
+	S: Signer<C::Public, C::Signature>,
+	N: AccountIndex<C::AccountId, C::Index>,
+{
+	type Public = C::Public;
+
+	fn public_keys() -> Vec<C::Public> {
+		S::public_keys()
+	}
+
+	fn sign_and_submit(call: impl Into<Call>, public: C::Public) -> Result<(), ()> {
+		let account = public.clone().into();
+		let index = N::account_index(&account);
+		let extrinsic = C::create_transaction::<S>(call.into(), public, account, index).ok_or(())?;
+		runtime_io::submit_transaction(&extrinsic)
+	}
+}
@@ -0,0 +1,101 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The final storage keys of the items declared with `decl_storage!`.
+//!
+//! Lets tests, migrations and tools outside of the runtime compute where an item is stored from
+//! the names of its module and item and its keys. `module` is the name given after `as` in
+//! `decl_storage!`, e.g. `Balances`, and `item` the name of the storage item, e.g.
+//! `FreeBalance`. `instance` is the name of the module instance, e.g. `Instance1`, or `None` for
+//! modules that are not instantiable and for the default instance.
+//!
+//! The hashers are the ones given in the declaration: `Blake2_256` for maps declared without
+//! `hasher(..)`.
+
+use crate::rstd::prelude::*;
+use codec::Encode;
+use runtime_io::twox_128;
+use super::hashed::generator::StorageHasher;
+
+/// The prefix of a storage item, that all its keys are derived from.
+pub fn prefix(module: &str, item: &str, instance: Option<&str>) -> Vec<u8> {
+	let mut prefix = instance.unwrap_or_default().as_bytes().to_vec();
+	prefix.extend(module.as_bytes());
+	prefix.push(b' ');
+	prefix.extend(item.as_bytes());
+	prefix
+}
+
+/// The key of a storage value.
+pub fn value_key(module: &str, item: &str, instance: Option<&str>) -> Vec<u8> {
+	twox_128(&prefix(module, item, instance)).to_vec()
+}
+
+/// The key of `key` in a storage map or linked map hashed with `H`.
+///
+/// Linked maps store the value together with the links to the previous and next keys.
+pub fn map_key<H: StorageHasher, K: Encode + ?Sized>(
+	module: &str,
+	item: &str,
+	instance: Option<&str>,
+	key: &K,
+) -> Vec<u8> {
+	let mut final_key = prefix(module, item, instance);
+	key.encode_to(&mut final_key);
+	H::hash(&final_key).as_ref().to_vec()
+}
+
+/// The key under which a linked map hashed with `H` stores its first key.
+pub fn linked_map_head_key<H: StorageHasher>(
+	module: &str,
+	item: &str,
+	instance: Option<&str>,
+) -> Vec<u8> {
+	let mut head_key = instance.unwrap_or_default().as_bytes().to_vec();
+	head_key.extend(b"head of ");
+	head_key.extend(prefix(module, item, None));
+	H::hash(&head_key).as_ref().to_vec()
+}
+
+/// The prefix of all keys of a storage double map that share the first key `k1`, hashed with
+/// `H1`.
+pub fn double_map_prefix<H1: StorageHasher, K1: Encode + ?Sized>(
+	module: &str,
+	item: &str,
+	instance: Option<&str>,
+	k1: &K1,
+) -> Vec<u8> {
+	map_key::<H1, K1>(module, item, instance, k1)
+}
+
+/// The key of (`k1`, `k2`) in a storage double map whose first key is hashed with `H1` and
+/// second key with `H2`.
+pub fn double_map_key<H1, H2, K1, K2>(
+	module: &str,
+	item: &str,
+	instance: Option<&str>,
+	k1: &K1,
+	k2: &K2,
+) -> Vec<u8> where
+	H1: StorageHasher,
+	H2: StorageHasher,
+	K1: Encode + ?Sized,
+	K2: Encode + ?Sized,
+{
+	let mut final_key = double_map_prefix::<H1, K1>(module, item, instance, k1);
+	k2.using_encoded(|k2| final_key.extend(H2::hash(k2).as_ref()));
+	final_key
+}
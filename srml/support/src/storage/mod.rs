@@ -26,6 +26,7 @@ use unhashed::generator::UnhashedStorage;
 pub mod storage_items;
 pub mod unhashed;
 pub mod hashed;
+pub mod keys;
 
 /// The underlying runtime storage.
 pub struct RuntimeStorage;
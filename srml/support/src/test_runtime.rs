@@ -0,0 +1,170 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Macro for declaring the runtime of module unit tests.
+
+/// Declare the runtime used by the unit tests of a module.
+///
+/// Generates the runtime type, its `Origin` and its `system::Trait` implementation with the
+/// usual test types: `u64` account ids, indices and block numbers, `H256` hashes and the testing
+/// `Header`.
+///
+/// If a `balances` block is given, `balances::Trait` is implemented too, with `u64` balances and
+/// the given existential deposit and fees. The balances events are `()`.
+///
+/// If an `events` block is given, the outer `TestEvent` of the listed modules is generated, as
+/// with `impl_outer_event!`, and used as the events of the runtime. Likewise, a `calls` block
+/// generates the outer `Call` of the listed modules, as with `impl_outer_dispatch!`, which are
+/// all allowed by the base call filter. The events and calls of the runtime are `()` otherwise.
+///
+/// The `system` and `balances` modules must be in scope under these names. Modules that need
+/// other types in the runtime still have to implement the traits by hand.
+///
+/// # Example
+///
+/// ```
+/// # use srml_support::impl_test_runtime;
+/// use srml_system as system;
+///
+/// impl_test_runtime! {
+/// 	pub struct Test;
+/// }
+///
+/// # fn main() {
+/// let _ = system::Module::<Test>::block_number();
+/// # }
+/// ```
+///
+/// With balances, and the events and calls of the module under test, named `example`:
+///
+/// ```ignore
+/// mod example {
+/// 	pub use crate::Event;
+/// }
+///
+/// impl_test_runtime! {
+/// 	pub struct Test;
+/// 	balances {
+/// 		ExistentialDeposit = 0;
+/// 		TransferFee = 0;
+/// 		CreationFee = 0;
+/// 	}
+/// 	events {
+/// 		example<T>,
+/// 	}
+/// 	calls {
+/// 		example::Example,
+/// 	}
+/// }
+/// ```
+#[macro_export]
+macro_rules! impl_test_runtime {
+	(@event) => { () };
+	(@event $events:tt) => { TestEvent };
+	(@call) => { () };
+	(@call $calls:tt) => { Call };
+	(@call_filter) => { () };
+	(@call_filter $calls:tt) => { AllCalls };
+	(
+		$(#[$attr:meta])*
+		pub struct $runtime:ident;
+		$(
+			balances {
+				ExistentialDeposit = $existential_deposit:expr;
+				TransferFee = $transfer_fee:expr;
+				CreationFee = $creation_fee:expr;
+			}
+		)?
+		$( events $events:tt )?
+		$( calls $calls:tt )?
+	) => {
+		$crate::impl_outer_origin! {
+			pub enum Origin for $runtime {}
+		}
+
+		$(
+			$crate::impl_outer_event! {
+				pub enum TestEvent for $runtime $events
+			}
+		)?
+
+		$(
+			$crate::impl_outer_dispatch! {
+				pub enum Call for $runtime where origin: Origin $calls
+			}
+
+			/// The base call filter of the runtime, which contains every call.
+			pub struct AllCalls;
+			impl $crate::traits::Contains<Call> for AllCalls {
+				fn contains(_: &Call) -> bool {
+					true
+				}
+			}
+		)?
+
+		$(#[$attr])*
+		#[derive(Clone, Eq, PartialEq, Debug)]
+		pub struct $runtime;
+
+		$crate::parameter_types! {
+			pub const BlockHashCount: u64 = 250;
+			pub const MaximumBlockWeight: u32 = 1024;
+			pub const MaximumBlockLength: u32 = 2 * 1024;
+			pub const AvailableBlockRatio: $crate::sr_primitives::Perbill =
+				$crate::sr_primitives::Perbill::one();
+		}
+
+		impl system::Trait for $runtime {
+			type Origin = Origin;
+			type Index = u64;
+			type BlockNumber = u64;
+			type Hash = $crate::sr_primitives::testing::H256;
+			type Hashing = $crate::sr_primitives::traits::BlakeTwo256;
+			type AccountId = u64;
+			type Lookup = $crate::sr_primitives::traits::IdentityLookup<Self::AccountId>;
+			type Header = $crate::sr_primitives::testing::Header;
+			type WeightMultiplierUpdate = ();
+			type Event = $crate::impl_test_runtime!(@event $( $events )?);
+			type BlockHashCount = BlockHashCount;
+			type MaximumBlockWeight = MaximumBlockWeight;
+			type AvailableBlockRatio = AvailableBlockRatio;
+			type Version = ();
+			type Call = $crate::impl_test_runtime!(@call $( $calls )?);
+			type BaseCallFilter = $crate::impl_test_runtime!(@call_filter $( $calls )?);
+			type MaximumBlockLength = MaximumBlockLength;
+		}
+
+		$(
+			$crate::parameter_types! {
+				pub const ExistentialDeposit: u64 = $existential_deposit;
+				pub const TransferFee: u64 = $transfer_fee;
+				pub const CreationFee: u64 = $creation_fee;
+			}
+
+			impl balances::Trait for $runtime {
+				type Balance = u64;
+				type OnNewAccount = ();
+				type OnFreeBalanceZero = ();
+				type Event = ();
+				type TransferPayment = ();
+				type DustRemoval = ();
+				type ExistentialDeposit = ExistentialDeposit;
+				type TransferFee = TransferFee;
+				type CreationFee = CreationFee;
+			}
+		)?
+	};
+}
@@ -21,17 +21,25 @@
 use crate::rstd::{result, marker::PhantomData, ops::Div};
 use crate::codec::{Codec, Encode, Decode};
 use primitives::u32_trait::Value as U32;
-use crate::sr_primitives::traits::{MaybeSerializeDebug, SimpleArithmetic, Saturating};
+use crate::sr_primitives::traits::{
+	MaybeSerializeDebug, MaybeDebug, SimpleArithmetic, Saturating, EnsureOrigin,
+};
 use crate::sr_primitives::ConsensusEngineId;
 
 use super::for_each_tuple;
 
+pub use either::Either;
+
 /// A trait for querying a single fixed value from a type.
 pub trait Get<T> {
 	/// Return a constant value.
 	fn get() -> T;
 }
 
+impl<T: Default> Get<T> for () {
+	fn get() -> T { T::default() }
+}
+
 /// A trait for querying whether a type can be said to statically "contain" a value. Similar
 /// in nature to `Get`, except it is designed to be lazy rather than active (you can't ask it to
 /// enumerate all values that it contains) and work for multiple values rather than just one.
@@ -46,6 +54,17 @@ impl<V: PartialEq, T: Get<V>> Contains<V> for T {
 	}
 }
 
+/// Origin check that succeeds if either `L` or `R` does; `L` is tried first.
+pub struct EnsureOneOf<L, R>(PhantomData<(L, R)>);
+
+impl<O, L: EnsureOrigin<O>, R: EnsureOrigin<O>> EnsureOrigin<O> for EnsureOneOf<L, R> {
+	type Success = Either<L::Success, R::Success>;
+	fn try_origin(o: O) -> result::Result<Self::Success, O> {
+		L::try_origin(o).map(Either::Left)
+			.or_else(|o| R::try_origin(o).map(Either::Right))
+	}
+}
+
 /// The account with the given id was killed.
 pub trait OnFreeBalanceZero<AccountId> {
 	/// The account was the given id was killed.
@@ -615,6 +634,173 @@ bitmask! {
 	}
 }
 
+/// Abstraction over a system of several fungible assets, identified by their `AssetId`.
+///
+/// Lets modules handle the native currency and other assets uniformly, see `NativeOrAsset`.
+pub trait MultiAsset<AccountId> {
+	/// The identifier of an asset.
+	type AssetId: Codec + Copy + Eq + MaybeDebug;
+
+	/// The balance of an account.
+	type Balance: SimpleArithmetic + Codec + Copy + MaybeDebug + Default;
+
+	/// The total amount of `asset` in the system.
+	fn total_issuance(asset: Self::AssetId) -> Self::Balance;
+
+	/// The free balance of `asset` of `who`.
+	fn free_balance(asset: Self::AssetId, who: &AccountId) -> Self::Balance;
+
+	/// Transfer `value` of the free balance of `asset` of `source` to `dest`.
+	fn transfer(
+		asset: Self::AssetId,
+		source: &AccountId,
+		dest: &AccountId,
+		value: Self::Balance,
+	) -> result::Result<(), &'static str>;
+
+	/// Create `value` of `asset` in the free balance of `who`, increasing the total issuance.
+	fn mint(asset: Self::AssetId, who: &AccountId, value: Self::Balance) -> result::Result<(), &'static str>;
+
+	/// Destroy `value` of the free balance of `asset` of `who`, decreasing the total issuance.
+	///
+	/// If the free balance is lower than `value`, nothing is destroyed and an `Err` is returned.
+	fn burn(asset: Self::AssetId, who: &AccountId, value: Self::Balance) -> result::Result<(), &'static str>;
+}
+
+/// A system of several assets where funds can be reserved from the user.
+pub trait MultiReservableAsset<AccountId>: MultiAsset<AccountId> {
+	/// The reserved balance of `asset` of `who`.
+	fn reserved_balance(asset: Self::AssetId, who: &AccountId) -> Self::Balance;
+
+	/// Move `value` of `asset` from the free balance of `who` to its reserved balance.
+	///
+	/// If the free balance is lower than `value`, nothing is moved and an `Err` is returned.
+	fn reserve(asset: Self::AssetId, who: &AccountId, value: Self::Balance) -> result::Result<(), &'static str>;
+
+	/// Move up to `value` of `asset` from the reserved balance of `who` to its free balance. This
+	/// function cannot fail.
+	///
+	/// If the reserved balance is lower than `value`, the remaining amount is returned.
+	fn unreserve(asset: Self::AssetId, who: &AccountId, value: Self::Balance) -> Self::Balance;
+
+	/// Move up to `value` of `asset` from the reserved balance of `slashed` to the free balance of
+	/// `beneficiary`.
+	///
+	/// If the reserved balance is lower than `value`, the remaining amount is returned.
+	fn repatriate_reserved(
+		asset: Self::AssetId,
+		slashed: &AccountId,
+		beneficiary: &AccountId,
+		value: Self::Balance,
+	) -> result::Result<Self::Balance, &'static str>;
+}
+
+/// The assets of `Assets`, with the native currency `Native` as the asset `NativeId`.
+pub struct NativeOrAsset<Native, Assets, NativeId>(PhantomData<(Native, Assets, NativeId)>);
+
+impl<AccountId, Native, Assets, NativeId> MultiAsset<AccountId> for NativeOrAsset<Native, Assets, NativeId>
+where
+	Native: Currency<AccountId>,
+	Assets: MultiAsset<AccountId, Balance=Native::Balance>,
+	NativeId: Get<Assets::AssetId>,
+{
+	type AssetId = Assets::AssetId;
+	type Balance = Native::Balance;
+
+	fn total_issuance(asset: Self::AssetId) -> Self::Balance {
+		if asset == NativeId::get() {
+			Native::total_issuance()
+		} else {
+			Assets::total_issuance(asset)
+		}
+	}
+
+	fn free_balance(asset: Self::AssetId, who: &AccountId) -> Self::Balance {
+		if asset == NativeId::get() {
+			Native::free_balance(who)
+		} else {
+			Assets::free_balance(asset, who)
+		}
+	}
+
+	fn transfer(
+		asset: Self::AssetId,
+		source: &AccountId,
+		dest: &AccountId,
+		value: Self::Balance,
+	) -> result::Result<(), &'static str> {
+		if asset == NativeId::get() {
+			Native::transfer(source, dest, value)
+		} else {
+			Assets::transfer(asset, source, dest, value)
+		}
+	}
+
+	fn mint(asset: Self::AssetId, who: &AccountId, value: Self::Balance) -> result::Result<(), &'static str> {
+		if asset == NativeId::get() {
+			// The positive imbalance increases the total issuance when dropped.
+			let _ = Native::deposit_creating(who, value);
+			Ok(())
+		} else {
+			Assets::mint(asset, who, value)
+		}
+	}
+
+	fn burn(asset: Self::AssetId, who: &AccountId, value: Self::Balance) -> result::Result<(), &'static str> {
+		if asset == NativeId::get() {
+			// The negative imbalance decreases the total issuance when dropped.
+			Native::withdraw(who, value, WithdrawReason::Transfer, ExistenceRequirement::AllowDeath)
+				.map(|_| ())
+		} else {
+			Assets::burn(asset, who, value)
+		}
+	}
+}
+
+impl<AccountId, Native, Assets, NativeId> MultiReservableAsset<AccountId> for NativeOrAsset<Native, Assets, NativeId>
+where
+	Native: ReservableCurrency<AccountId>,
+	Assets: MultiReservableAsset<AccountId, Balance=Native::Balance>,
+	NativeId: Get<Assets::AssetId>,
+{
+	fn reserved_balance(asset: Self::AssetId, who: &AccountId) -> Self::Balance {
+		if asset == NativeId::get() {
+			Native::reserved_balance(who)
+		} else {
+			Assets::reserved_balance(asset, who)
+		}
+	}
+
+	fn reserve(asset: Self::AssetId, who: &AccountId, value: Self::Balance) -> result::Result<(), &'static str> {
+		if asset == NativeId::get() {
+			Native::reserve(who, value)
+		} else {
+			Assets::reserve(asset, who, value)
+		}
+	}
+
+	fn unreserve(asset: Self::AssetId, who: &AccountId, value: Self::Balance) -> Self::Balance {
+		if asset == NativeId::get() {
+			Native::unreserve(who, value)
+		} else {
+			Assets::unreserve(asset, who, value)
+		}
+	}
+
+	fn repatriate_reserved(
+		asset: Self::AssetId,
+		slashed: &AccountId,
+		beneficiary: &AccountId,
+		value: Self::Balance,
+	) -> result::Result<Self::Balance, &'static str> {
+		if asset == NativeId::get() {
+			Native::repatriate_reserved(slashed, beneficiary, value)
+		} else {
+			Assets::repatriate_reserved(asset, slashed, beneficiary, value)
+		}
+	}
+}
+
 pub trait Time {
 	type Moment: SimpleArithmetic + Codec + Clone + Default;
 
@@ -640,3 +826,72 @@ pub trait ChangeMembers<AccountId> {
 impl<T> ChangeMembers<T> for () {
 	fn change_members(_incoming: &[T], _outgoing: &[T], _new_set: &[T]) {}
 }
+
+/// The accounts of the members of a membership system, identified by their `MemberId`.
+///
+/// Lets modules refer to members by their identifier rather than by their accounts, which can
+/// change.
+pub trait MemberAccounts<AccountId> {
+	/// The identifier of a member.
+	type MemberId;
+
+	/// The controller account of `member`, acting on its behalf, or `None` if it isn't a member.
+	fn controller_account(member: &Self::MemberId) -> Option<AccountId>;
+
+	/// Whether `who` is the controller account of `member`.
+	fn is_member_controller(member: &Self::MemberId, who: &AccountId) -> bool where AccountId: PartialEq {
+		Self::controller_account(member).map_or(false, |controller| &controller == who)
+	}
+}
+
+/// A source of funds minted within a limited capacity, identified by their `MintId`.
+pub trait MintFunds<AccountId, Balance> {
+	/// The identifier of a mint.
+	type MintId;
+
+	/// Mint `amount` into the account `to`, drawing down the capacity of `mint`.
+	fn mint_funds(mint: &Self::MintId, to: &AccountId, amount: Balance) -> result::Result<(), &'static str>;
+}
+
+impl<AccountId, Balance> MintFunds<AccountId, Balance> for () {
+	type MintId = ();
+
+	fn mint_funds(_: &(), _: &AccountId, _: Balance) -> result::Result<(), &'static str> {
+		Err("No mint")
+	}
+}
+
+/// Recurring payouts out of mints, identified by their `RelationshipId`.
+pub trait RecurringRewards<AccountId, Balance, BlockNumber> {
+	/// The identifier of a mint.
+	type MintId;
+
+	/// The identifier of a reward relationship.
+	type RelationshipId;
+
+	/// Pay `amount_per_payout` to `recipient` out of `mint` every `payout_interval` blocks,
+	/// starting at `first_payout_at`.
+	fn add_relationship(
+		recipient: AccountId,
+		mint: Self::MintId,
+		amount_per_payout: Balance,
+		payout_interval: BlockNumber,
+		first_payout_at: BlockNumber,
+	) -> result::Result<Self::RelationshipId, &'static str>;
+
+	/// Stop paying the reward relationship `relationship`.
+	fn remove_relationship(relationship: &Self::RelationshipId) -> result::Result<(), &'static str>;
+}
+
+impl<AccountId, Balance, BlockNumber> RecurringRewards<AccountId, Balance, BlockNumber> for () {
+	type MintId = ();
+	type RelationshipId = ();
+
+	fn add_relationship(_: AccountId, _: (), _: Balance, _: BlockNumber, _: BlockNumber) -> result::Result<(), &'static str> {
+		Err("No recurring rewards")
+	}
+
+	fn remove_relationship(_: &()) -> result::Result<(), &'static str> {
+		Err("No recurring rewards")
+	}
+}
@@ -17,10 +17,103 @@
 #[doc(hidden)]
 pub use crate::sr_primitives::traits::ValidateUnsigned;
 #[doc(hidden)]
-pub use crate::sr_primitives::transaction_validity::TransactionValidity;
+pub use crate::sr_primitives::transaction_validity::{TransactionValidity, TransactionSource};
 #[doc(hidden)]
 pub use crate::sr_primitives::ApplyError;
 
+use crate::rstd::prelude::*;
+use crate::codec::Encode;
+use crate::sr_primitives::transaction_validity::{
+	ValidTransaction, TransactionPriority, TransactionLongevity,
+};
+use primitives::crypto::{TypedKey, key_types};
+
+/// The payload of an unsigned transaction that is signed by one of the runtime's keys, e.g. a
+/// heartbeat signed by a validator.
+///
+/// The transaction doesn't pay fees, so `validate_unsigned` must check the signature before the
+/// transaction enters the pool. `validate_signed_payload` does this and builds the validity.
+pub trait SignedPayload: Encode {
+	/// The public key type that signs the payload.
+	type Public: TypedKey + AsRef<[u8]> + Clone;
+
+	/// Returns the key that claims to have signed the payload.
+	fn public(&self) -> &Self::Public;
+
+	/// Check that `signature` is a valid signature of the SCALE-encoded payload made by `public`.
+	///
+	/// Only ed25519 and sr25519 keys are supported, any other key type fails verification.
+	fn verify(&self, signature: &[u8]) -> bool {
+		if signature.len() != 64 {
+			return false;
+		}
+		let mut sig = [0; 64];
+		sig.copy_from_slice(signature);
+
+		let public = self.public();
+		self.using_encoded(|payload| match Self::Public::KEY_TYPE {
+			key_types::ED25519 => runtime_io::ed25519_verify(&sig, payload, public),
+			key_types::SR25519 => runtime_io::sr25519_verify(&sig, payload, public),
+			_ => false,
+		})
+	}
+
+	/// Sign the payload with its public key using `S`, e.g. `offchain::KeystoreSigner`.
+	fn sign<S, Signature>(&self) -> Option<Signature> where
+		S: crate::offchain::Signer<Self::Public, Signature>,
+		Self: Sized,
+	{
+		S::sign(self.public().clone(), self)
+	}
+}
+
+/// How unsigned transactions carrying a `SignedPayload` are prioritized and how long they stay
+/// valid.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct SignedPayloadValidity {
+	/// Priority of the transaction in the pool.
+	pub priority: TransactionPriority,
+	/// Number of blocks the transaction stays valid for.
+	pub longevity: TransactionLongevity,
+	/// Whether the transaction is gossiped to other nodes.
+	pub propagate: bool,
+}
+
+impl Default for SignedPayloadValidity {
+	fn default() -> Self {
+		SignedPayloadValidity {
+			priority: 0,
+			longevity: TransactionLongevity::max_value(),
+			propagate: true,
+		}
+	}
+}
+
+/// Validate an unsigned transaction carrying `payload` and its `signature`.
+///
+/// The transaction is invalid if the signature doesn't verify. Otherwise it provides the encoded
+/// payload as its tag, so the same payload is only included once. Any other checks, e.g. that
+/// the signer is a current authority, are up to the caller and should be done first since they
+/// are cheaper than signature verification.
+pub fn validate_signed_payload<P: SignedPayload>(
+	payload: &P,
+	signature: &[u8],
+	validity: SignedPayloadValidity,
+) -> TransactionValidity {
+	if !payload.verify(signature) {
+		return TransactionValidity::Invalid(ApplyError::BadSignature as i8);
+	}
+
+	TransactionValidity::Valid(ValidTransaction {
+		priority: validity.priority,
+		requires: vec![],
+		provides: vec![payload.encode()],
+		longevity: validity.longevity,
+		propagate: validity.propagate,
+	})
+}
+
 
 /// Implement `ValidateUnsigned` for `Runtime`.
 /// All given modules need to implement `ValidateUnsigned`.
@@ -34,7 +127,10 @@ pub use crate::sr_primitives::ApplyError;
 /// # 	impl srml_support::unsigned::ValidateUnsigned for Module {
 /// # 		type Call = Call;
 /// #
-/// # 		fn validate_unsigned(call: &Self::Call) -> srml_support::unsigned::TransactionValidity {
+/// # 		fn validate_unsigned(
+/// # 			_source: srml_support::unsigned::TransactionSource,
+/// # 			_call: &Self::Call,
+/// # 		) -> srml_support::unsigned::TransactionValidity {
 /// # 			unimplemented!();
 /// # 		}
 /// # 	}
@@ -68,10 +164,15 @@ macro_rules! impl_outer_validate_unsigned {
 		impl $crate::unsigned::ValidateUnsigned for $runtime {
 			type Call = Call;
 
-			fn validate_unsigned(call: &Self::Call) -> $crate::unsigned::TransactionValidity {
+			fn validate_unsigned(
+				source: $crate::unsigned::TransactionSource,
+				call: &Self::Call,
+			) -> $crate::unsigned::TransactionValidity {
+				// silence the unused variable warning if there are no modules
+				let _ = source;
 				#[allow(unreachable_patterns)]
 				match call {
-					$( Call::$module(inner_call) => $module::validate_unsigned(inner_call), )*
+					$( Call::$module(inner_call) => $module::validate_unsigned(source, inner_call), )*
 					_ => $crate::unsigned::TransactionValidity::Invalid($crate::unsigned::ApplyError::BadSignature as i8),
 				}
 			}
@@ -101,7 +202,10 @@ mod test_partial_and_full_call {
 		impl super::super::ValidateUnsigned for Module {
 			type Call = Call;
 
-			fn validate_unsigned(_call: &Self::Call) -> super::super::TransactionValidity {
+			fn validate_unsigned(
+				_source: super::super::TransactionSource,
+				_call: &Self::Call,
+			) -> super::super::TransactionValidity {
 				unimplemented!();
 			}
 		}
@@ -152,3 +256,61 @@ mod test_partial_and_full_call {
 		}
 	}
 }
+
+#[cfg(test)]
+mod test_signed_payload {
+	use super::*;
+	use primitives::{Pair, ed25519};
+	use codec::Encode;
+
+	#[derive(Encode)]
+	struct Payload {
+		value: u32,
+		public: ed25519::Public,
+	}
+
+	impl SignedPayload for Payload {
+		type Public = ed25519::Public;
+
+		fn public(&self) -> &ed25519::Public {
+			&self.public
+		}
+	}
+
+	fn payload(pair: &ed25519::Pair) -> Payload {
+		Payload { value: 42, public: pair.public() }
+	}
+
+	#[test]
+	fn valid_signature_is_accepted() {
+		let pair = ed25519::Pair::from_seed(&[1; 32]);
+		let payload = payload(&pair);
+		let signature = pair.sign(&payload.encode());
+
+		let validity = SignedPayloadValidity { priority: 10, longevity: 5, propagate: false };
+		match validate_signed_payload(&payload, signature.as_ref(), validity) {
+			TransactionValidity::Valid(valid) => {
+				assert_eq!(valid.priority, 10);
+				assert_eq!(valid.longevity, 5);
+				assert!(!valid.propagate);
+				assert_eq!(valid.provides, vec![payload.encode()]);
+			},
+			_ => panic!("expected a valid transaction"),
+		}
+	}
+
+	#[test]
+	fn invalid_signature_is_rejected() {
+		let pair = ed25519::Pair::from_seed(&[1; 32]);
+		let other = ed25519::Pair::from_seed(&[2; 32]);
+		let payload = payload(&pair);
+		let signature = other.sign(&payload.encode());
+
+		assert!(!payload.verify(signature.as_ref()));
+		assert!(!payload.verify(&[0; 10]));
+		assert_eq!(
+			validate_signed_payload(&payload, signature.as_ref(), Default::default()),
+			TransactionValidity::Invalid(ApplyError::BadSignature as i8),
+		);
+	}
+}
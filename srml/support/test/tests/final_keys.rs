@@ -16,7 +16,8 @@
 
 use runtime_io::{with_externalities, Blake2Hasher};
 use srml_support::{StorageValue, StorageMap, StorageDoubleMap};
-use srml_support::storage::unhashed;
+use srml_support::storage::{unhashed, keys};
+use srml_support::{Blake2_128, Blake2_256, Twox128};
 use codec::{Encode, Decode};
 
 pub trait Trait {
@@ -97,3 +98,31 @@ fn final_keys() {
 		assert_eq!(unhashed::get::<u32>(&k), Some(3u32));
 	});
 }
+
+#[test]
+fn final_keys_are_computed_by_helpers() {
+	with_externalities(&mut new_test_ext(), || {
+		Value::put(1);
+		assert_eq!(unhashed::get::<u32>(&keys::value_key("FinalKeys", "Value", None)), Some(1u32));
+
+		Map::insert(1, 2);
+		let k = keys::map_key::<Blake2_256, _>("FinalKeys", "Map", None, &1u32);
+		assert_eq!(unhashed::get::<u32>(&k), Some(2u32));
+
+		Map2::insert(1, 2);
+		let k = keys::map_key::<Twox128, _>("FinalKeys", "Map2", None, &1u32);
+		assert_eq!(unhashed::get::<u32>(&k), Some(2u32));
+
+		LinkedMap::insert(1, 2);
+		let k = keys::linked_map_head_key::<Blake2_256>("FinalKeys", "LinkedMap", None);
+		assert_eq!(unhashed::get::<u32>(&k), Some(1u32));
+
+		DoubleMap2::insert(&1, &2, &3);
+		let k = keys::double_map_key::<Twox128, Blake2_128, _, _>(
+			"FinalKeys", "DoubleMap2", None, &1u32, &2u32,
+		);
+		assert_eq!(unhashed::get::<u32>(&k), Some(3u32));
+		let prefix = keys::double_map_prefix::<Twox128, _>("FinalKeys", "DoubleMap2", None, &1u32);
+		assert!(k.starts_with(&prefix));
+	});
+}
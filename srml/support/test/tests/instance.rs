@@ -505,3 +505,22 @@ fn instance_prefix_is_prefix_of_entries() {
 	assert!(module2::Instance2::PREFIX_FOR_LinkedMap.starts_with(prefix));
 	assert!(module2::Instance2::PREFIX_FOR_DoubleMap.starts_with(prefix));
 }
+
+#[test]
+fn instance_keys_are_computed_by_helpers() {
+	use module2::Instance;
+	use srml_support::storage::keys;
+
+	assert_eq!(
+		keys::prefix("Module2", "Value", Some("Instance2")),
+		module2::Instance2::PREFIX_FOR_Value.as_bytes(),
+	);
+	assert_eq!(
+		keys::value_key("Module2", "Value", Some("Instance2")),
+		runtime_io::twox_128(module2::Instance2::PREFIX_FOR_Value.as_bytes()).to_vec(),
+	);
+	assert_eq!(
+		keys::linked_map_head_key::<srml_support::Blake2_256>("Module2", "LinkedMap", Some("Instance2")),
+		runtime_io::blake2_256(module2::Instance2::HEAD_KEY_FOR_LinkedMap.as_bytes()).to_vec(),
+	);
+}
@@ -80,7 +80,7 @@ use rstd::marker::PhantomData;
 use sr_primitives::generic::{self, Era};
 use sr_primitives::Perbill;
 use sr_primitives::weights::{
-	Weight, DispatchInfo, DispatchClass, WeightMultiplier, SimpleDispatchInfo
+	Weight, DispatchInfo, PostDispatchInfo, DispatchClass, WeightMultiplier, SimpleDispatchInfo
 };
 use sr_primitives::transaction_validity::{
 	ValidTransaction, TransactionPriority, TransactionLongevity
@@ -88,14 +88,16 @@ use sr_primitives::transaction_validity::{
 use sr_primitives::traits::{self, CheckEqual, SimpleArithmetic, Zero, SignedExtension, Convert,
 	SimpleBitOps, Hash, Member, MaybeDisplay, EnsureOrigin, DispatchError, SaturatedConversion,
 	MaybeSerializeDebugButNotDeserialize, MaybeSerializeDebug, StaticLookup, One, Bounded, Lookup,
+	OnInitialize, OnFinalize,
 };
 use primitives::storage::well_known_keys;
 use srml_support::{
 	storage, decl_module, decl_event, decl_storage, StorageDoubleMap, StorageValue, StorageMap,
-	Parameter, for_each_tuple, traits::{Contains, Get}
+	Parameter, for_each_tuple, traits::{Contains, Get}, offchain,
 };
 use safe_mix::TripletMix;
 use codec::{Encode, Decode};
+use sr_version::RuntimeVersion;
 
 #[cfg(any(feature = "std", test))]
 use runtime_io::{twox_128, TestExternalities, Blake2Hasher};
@@ -213,6 +215,16 @@ pub trait Trait: 'static + Eq + Clone {
 	/// module, including weight and length.
 	type AvailableBlockRatio: Get<Perbill>;
 
+	/// Get the chain's current version.
+	type Version: Get<RuntimeVersion>;
+
+	/// The aggregated `Call` type of the runtime.
+	type Call: Parameter;
+
+	/// The basic call filter of the runtime. Calls that it does not contain are refused before
+	/// dispatch: they can neither enter the transaction queue nor be included in a block.
+	type BaseCallFilter: Contains<Self::Call>;
+
 }
 
 pub type DigestOf<T> = generic::Digest<<T as Trait>::Hash>;
@@ -694,6 +706,33 @@ impl<T: Trait> Module<T> {
 		<Number<T>>::put(n);
 	}
 
+	/// Advance to block `n`, running the hooks of the modules `M` for every block on the way.
+	///
+	/// `M` is usually the module under test, or a tuple of the modules under test. Unlike with
+	/// `initialize`, the events of the previous blocks are kept.
+	#[cfg(any(feature = "std", test))]
+	pub fn run_to_block<M>(n: T::BlockNumber) where
+		M: OnInitialize<T::BlockNumber> + OnFinalize<T::BlockNumber>,
+	{
+		while Self::block_number() < n {
+			M::on_finalize(Self::block_number());
+			Self::set_block_number(Self::block_number() + One::one());
+			M::on_initialize(Self::block_number());
+		}
+	}
+
+	/// Returns a checkpoint of the events deposited so far, to be passed to `events_since`.
+	#[cfg(any(feature = "std", test))]
+	pub fn events_checkpoint() -> usize {
+		Self::events().len()
+	}
+
+	/// Returns the events deposited since the `checkpoint` was taken.
+	#[cfg(any(feature = "std", test))]
+	pub fn events_since(checkpoint: usize) -> Vec<T::Event> {
+		Self::events().into_iter().skip(checkpoint).map(|record| record.event).collect()
+	}
+
 	/// Sets the index of extrinsic that is currently executing.
 	#[cfg(any(feature = "std", test))]
 	pub fn set_extrinsic_index(extrinsic_index: u32) {
@@ -707,6 +746,14 @@ impl<T: Trait> Module<T> {
 		<ParentHash<T>>::put(n);
 	}
 
+	/// Set the block hashes `random` is computed from, instead of those of the previous blocks.
+	///
+	/// Makes `random` return fixed values for tests that don't initialize blocks.
+	#[cfg(any(feature = "std", test))]
+	pub fn set_random_material(material: Vec<T::Hash>) {
+		<RandomMaterial<T>>::put((0, material));
+	}
+
 	/// Get the basic random seed.
 	///
 	/// In general you won't want to use this, but rather `Self::random` which
@@ -860,15 +907,17 @@ impl<T: Trait + Send + Sync> CheckWeight<T> {
 	}
 
 	/// get the priority of an extrinsic denoted by `info`.
+	///
+	/// Normal extrinsics don't get any priority from their weight, their priority is left to the
+	/// fee they pay (see `transaction_payment::ChargeTransactionPayment`).
 	fn get_priority(info: DispatchInfo) -> TransactionPriority {
 		match info.class {
-			DispatchClass::Normal => info.weight.into(),
+			DispatchClass::Normal => 0,
 			DispatchClass::Operational => Bounded::max_value()
 		}
 	}
 
-	/// Utility constructor for tests and client code.
-	#[cfg(feature = "std")]
+	/// Utility constructor for tests, client code and offchain workers.
 	pub fn new() -> Self {
 		Self(PhantomData)
 	}
@@ -877,6 +926,7 @@ impl<T: Trait + Send + Sync> CheckWeight<T> {
 impl<T: Trait + Send + Sync> SignedExtension for CheckWeight<T> {
 	type AccountId = T::AccountId;
 	type AdditionalSigned = ();
+	type Pre = ();
 
 	fn additional_signed(&self) -> rstd::result::Result<(), &'static str> { Ok(()) }
 
@@ -893,6 +943,23 @@ impl<T: Trait + Send + Sync> SignedExtension for CheckWeight<T> {
 		Ok(())
 	}
 
+	fn post_dispatch(
+		_pre: Self::Pre,
+		info: DispatchInfo,
+		post_info: PostDispatchInfo,
+		_len: usize,
+	) {
+		// give back the block weight that the call did not use. Only what `pre_dispatch` has
+		// actually added (i.e. capped to the limit) can be given back.
+		let limit = Self::get_dispatch_limit_ratio(info.class) * T::MaximumBlockWeight::get();
+		let added_weight = info.weight.min(limit);
+		let unspent = added_weight.saturating_sub(post_info.calc_actual_weight(&info));
+		if unspent > 0 {
+			let current_weight = Module::<T>::all_extrinsics_weight();
+			AllExtrinsicsWeight::put(current_weight.saturating_sub(unspent));
+		}
+	}
+
 	fn validate(
 		&self,
 		_who: &Self::AccountId,
@@ -915,13 +982,18 @@ impl<T: Trait + Send + Sync> rstd::fmt::Debug for CheckWeight<T> {
 	}
 }
 
+impl<T: Trait> offchain::AccountIndex<T::AccountId, T::Index> for Module<T> {
+	fn account_index(who: &T::AccountId) -> T::Index {
+		Self::account_nonce(who)
+	}
+}
+
 /// Nonce check and increment to give replay protection for transactions.
 #[derive(Encode, Decode, Clone, Eq, PartialEq)]
 pub struct CheckNonce<T: Trait>(#[codec(compact)] T::Index);
 
-#[cfg(feature = "std")]
 impl<T: Trait> CheckNonce<T> {
-	/// utility constructor. Used only in client/factory code.
+	/// utility constructor. Used in client/factory code and offchain workers.
 	pub fn from(nonce: T::Index) -> Self {
 		Self(nonce)
 	}
@@ -937,6 +1009,7 @@ impl<T: Trait> rstd::fmt::Debug for CheckNonce<T> {
 impl<T: Trait> SignedExtension for CheckNonce<T> {
 	type AccountId = T::AccountId;
 	type AdditionalSigned = ();
+	type Pre = ();
 
 	fn additional_signed(&self) -> rstd::result::Result<(), &'static str> { Ok(()) }
 
@@ -959,7 +1032,7 @@ impl<T: Trait> SignedExtension for CheckNonce<T> {
 	fn validate(
 		&self,
 		who: &Self::AccountId,
-		info: DispatchInfo,
+		_info: DispatchInfo,
 		_len: usize,
 	) -> Result<ValidTransaction, DispatchError> {
 		// check index
@@ -976,7 +1049,7 @@ impl<T: Trait> SignedExtension for CheckNonce<T> {
 		};
 
 		Ok(ValidTransaction {
-			priority: info.weight as TransactionPriority,
+			priority: 0,
 			requires,
 			provides,
 			longevity: TransactionLongevity::max_value(),
@@ -985,13 +1058,15 @@ impl<T: Trait> SignedExtension for CheckNonce<T> {
 	}
 }
 
-/// Nonce check and increment to give replay protection for transactions.
+/// Check for transaction mortality.
+///
+/// Mortal eras must not be longer than `BlockHashCount`, otherwise the hash of the birth block
+/// could be pruned while the transaction is still alive.
 #[derive(Encode, Decode, Clone, Eq, PartialEq)]
 pub struct CheckEra<T: Trait + Send + Sync>((Era, rstd::marker::PhantomData<T>));
 
-#[cfg(feature = "std")]
 impl<T: Trait + Send + Sync> CheckEra<T> {
-	/// utility constructor. Used only in client/factory code.
+	/// utility constructor. Used in client/factory code and offchain workers.
 	pub fn from(era: Era) -> Self {
 		Self((era, rstd::marker::PhantomData))
 	}
@@ -1007,12 +1082,32 @@ impl<T: Trait + Send + Sync> rstd::fmt::Debug for CheckEra<T> {
 impl<T: Trait + Send + Sync> SignedExtension for CheckEra<T> {
 	type AccountId = T::AccountId;
 	type AdditionalSigned = T::Hash;
+	type Pre = ();
 	fn additional_signed(&self) -> Result<Self::AdditionalSigned, &'static str> {
+		let era = (self.0).0;
+		let block_hash_count = T::BlockHashCount::get().saturated_into::<u64>();
+		if era.period().map_or(false, |period| period > block_hash_count) {
+			Err(sr_primitives::ERA_PERIOD_TOO_LONG)?
+		}
 		let current_u64 = <Module<T>>::block_number().saturated_into::<u64>();
-		let n = (self.0).0.birth(current_u64).saturated_into::<T::BlockNumber>();
-		if !<BlockHash<T>>::exists(n) { Err("transaction birth block ancient")? }
+		let n = era.birth(current_u64).saturated_into::<T::BlockNumber>();
+		if !<BlockHash<T>>::exists(n) { Err(sr_primitives::ANCIENT_BIRTH_BLOCK)? }
 		Ok(<Module<T>>::block_hash(n))
 	}
+
+	fn validate(
+		&self,
+		_who: &Self::AccountId,
+		_info: DispatchInfo,
+		_len: usize,
+	) -> Result<ValidTransaction, DispatchError> {
+		let current_u64 = <Module<T>>::block_number().saturated_into::<u64>();
+		let valid_till = (self.0).0.death(current_u64);
+		Ok(ValidTransaction {
+			longevity: valid_till.saturating_sub(current_u64),
+			..Default::default()
+		})
+	}
 }
 
 /// Nonce check and increment to give replay protection for transactions.
@@ -1026,21 +1121,112 @@ impl<T: Trait + Send + Sync> rstd::fmt::Debug for CheckGenesis<T> {
 	}
 }
 
-#[cfg(feature = "std")]
 impl<T: Trait + Send + Sync> CheckGenesis<T> {
 	pub fn new() -> Self {
-		Self(std::marker::PhantomData)
+		Self(rstd::marker::PhantomData)
 	}
 }
 
 impl<T: Trait + Send + Sync> SignedExtension for CheckGenesis<T> {
 	type AccountId = T::AccountId;
 	type AdditionalSigned = T::Hash;
+	type Pre = ();
 	fn additional_signed(&self) -> Result<Self::AdditionalSigned, &'static str> {
 		Ok(<Module<T>>::block_hash(T::BlockNumber::zero()))
 	}
 }
 
+/// Ensure the transaction version registered in the transaction is the same as at present.
+///
+/// Only the `transaction_version` of the runtime goes into the signed payload, so runtime upgrades
+/// that keep all existing dispatchables intact do not invalidate transactions signed in advance.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct CheckTxVersion<T: Trait + Send + Sync>(rstd::marker::PhantomData<T>);
+
+#[cfg(feature = "std")]
+impl<T: Trait + Send + Sync> rstd::fmt::Debug for CheckTxVersion<T> {
+	fn fmt(&self, f: &mut rstd::fmt::Formatter) -> rstd::fmt::Result {
+		write!(f, "CheckTxVersion<T>")
+	}
+}
+
+impl<T: Trait + Send + Sync> CheckTxVersion<T> {
+	/// Utility constructor. Used in client/factory code and offchain workers.
+	pub fn new() -> Self {
+		Self(rstd::marker::PhantomData)
+	}
+}
+
+impl<T: Trait + Send + Sync> SignedExtension for CheckTxVersion<T> {
+	type AccountId = T::AccountId;
+	type AdditionalSigned = u32;
+	type Pre = ();
+	fn additional_signed(&self) -> Result<Self::AdditionalSigned, &'static str> {
+		Ok(T::Version::get().transaction_version)
+	}
+}
+
+/// Sign a chain-specific value `V`, provided by `D`, into every transaction.
+///
+/// The value itself is never transmitted, only signed over, so chains that share a genesis (e.g.
+/// a family of testnets) can tell their transactions apart by configuring a distinct network id
+/// here, without having to touch the extrinsic format.
+pub struct CheckDomain<T, D, V>(rstd::marker::PhantomData<(T, D, V)>);
+
+impl<T, D, V> Clone for CheckDomain<T, D, V> {
+	fn clone(&self) -> Self {
+		Self(rstd::marker::PhantomData)
+	}
+}
+
+impl<T, D, V> PartialEq for CheckDomain<T, D, V> {
+	fn eq(&self, _: &Self) -> bool {
+		true
+	}
+}
+
+impl<T, D, V> Eq for CheckDomain<T, D, V> {}
+
+impl<T, D, V> Encode for CheckDomain<T, D, V> {
+	fn encode_to<O: codec::Output>(&self, _dest: &mut O) {}
+}
+
+impl<T, D, V> codec::EncodeLike for CheckDomain<T, D, V> {}
+
+impl<T, D, V> Decode for CheckDomain<T, D, V> {
+	fn decode<I: codec::Input>(_input: &mut I) -> Result<Self, codec::Error> {
+		Ok(Self(rstd::marker::PhantomData))
+	}
+}
+
+#[cfg(feature = "std")]
+impl<T, D, V> rstd::fmt::Debug for CheckDomain<T, D, V> {
+	fn fmt(&self, f: &mut rstd::fmt::Formatter) -> rstd::fmt::Result {
+		write!(f, "CheckDomain<T, D, V>")
+	}
+}
+
+#[cfg(feature = "std")]
+impl<T, D, V> CheckDomain<T, D, V> {
+	/// Utility constructor. Used only in client/factory code.
+	pub fn new() -> Self {
+		Self(std::marker::PhantomData)
+	}
+}
+
+impl<T, D, V> SignedExtension for CheckDomain<T, D, V> where
+	T: Trait + Send + Sync,
+	D: Get<V> + Send + Sync,
+	V: Encode + Send + Sync,
+{
+	type AccountId = T::AccountId;
+	type AdditionalSigned = V;
+	type Pre = ();
+	fn additional_signed(&self) -> Result<Self::AdditionalSigned, &'static str> {
+		Ok(D::get())
+	}
+}
+
 pub struct ChainContext<T>(::rstd::marker::PhantomData<T>);
 impl<T> Default for ChainContext<T> {
 	fn default() -> Self {
@@ -1062,7 +1248,7 @@ mod tests {
 	use runtime_io::with_externalities;
 	use primitives::H256;
 	use sr_primitives::{traits::{BlakeTwo256, IdentityLookup}, testing::Header};
-	use srml_support::{impl_outer_origin, parameter_types};
+	use srml_support::{impl_outer_origin, parameter_types, traits::{EnsureOneOf, Either}};
 
 	impl_outer_origin!{
 		pub enum Origin for Test where system = super {}
@@ -1076,6 +1262,16 @@ mod tests {
 		pub const MaximumBlockWeight: Weight = 1024;
 		pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
 		pub const MaximumBlockLength: u32 = 1024;
+		pub const NetworkId: u32 = 42;
+		pub const Version: RuntimeVersion = RuntimeVersion {
+			spec_name: sr_version::create_runtime_str!("test"),
+			impl_name: sr_version::create_runtime_str!("system-test"),
+			authoring_version: 1,
+			spec_version: 7,
+			impl_version: 7,
+			apis: sr_version::create_apis_vec!([]),
+			transaction_version: 3,
+		};
 	}
 
 	impl Trait for Test {
@@ -1092,6 +1288,9 @@ mod tests {
 		type BlockHashCount = BlockHashCount;
 		type MaximumBlockWeight = MaximumBlockWeight;
 		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = Version;
+		type Call = ();
+		type BaseCallFilter = ();
 		type MaximumBlockLength = MaximumBlockLength;
 	}
 
@@ -1125,6 +1324,57 @@ mod tests {
 		assert_eq!(x, Ok(RawOrigin::<u64>::Signed(1u64)));
 	}
 
+	#[test]
+	fn ensure_one_of_works() {
+		type EnsureRootOrSigned = EnsureOneOf<EnsureRoot<u64>, EnsureSigned<u64>>;
+
+		let root = Origin::from(RawOrigin::<u64>::Root);
+		assert_eq!(EnsureRootOrSigned::try_origin(root).ok(), Some(Either::Left(())));
+		let signed = Origin::from(RawOrigin::<u64>::Signed(1));
+		assert_eq!(EnsureRootOrSigned::try_origin(signed).ok(), Some(Either::Right(1)));
+		let none = Origin::from(RawOrigin::<u64>::None);
+		assert!(EnsureRootOrSigned::try_origin(none).is_err());
+	}
+
+	struct DepositBlockNumber;
+
+	impl OnInitialize<u64> for DepositBlockNumber {
+		fn on_initialize(n: u64) {
+			System::deposit_event(n as u16);
+		}
+	}
+
+	impl OnFinalize<u64> for DepositBlockNumber {}
+
+	#[test]
+	fn run_to_block_keeps_events_since_checkpoint() {
+		with_externalities(&mut new_test_ext(), || {
+			System::set_block_number(1);
+			System::deposit_event(1u16);
+			let checkpoint = System::events_checkpoint();
+
+			System::run_to_block::<DepositBlockNumber>(3);
+
+			assert_eq!(System::block_number(), 3);
+			assert_eq!(System::events_since(checkpoint), vec![2u16, 3]);
+		});
+	}
+
+	#[test]
+	fn random_uses_set_material() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_eq!(System::random(b"subject"), H256::default());
+
+			System::set_random_material(vec![H256::repeat_byte(1); 81]);
+			let random = System::random(b"subject");
+			assert_ne!(random, H256::default());
+			assert_ne!(System::random(b"other subject"), random);
+
+			System::set_random_material(vec![H256::repeat_byte(2); 81]);
+			assert_ne!(System::random(b"subject"), random);
+		});
+	}
+
 	#[test]
 	fn deposit_event_should_work() {
 		with_externalities(&mut new_test_ext(), || {
@@ -1310,6 +1560,22 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn signed_ext_check_weight_refunds_unspent_weight() {
+		with_externalities(&mut new_test_ext(), || {
+			let info = DispatchInfo { weight: 512, ..Default::default() };
+			let post_info = PostDispatchInfo { actual_weight: Some(128) };
+			let len = 0_usize;
+
+			AllExtrinsicsWeight::put(256);
+			let pre = CheckWeight::<Test>(PhantomData).pre_dispatch(&1, info, len).unwrap();
+			assert_eq!(System::all_extrinsics_weight(), 256 + 512);
+
+			CheckWeight::<Test>::post_dispatch(pre, info, post_info, len);
+			assert_eq!(System::all_extrinsics_weight(), 256 + 128);
+		})
+	}
+
 	#[test]
 	fn signed_ext_check_weight_max_works() {
 		with_externalities(&mut new_test_ext(), || {
@@ -1356,7 +1622,7 @@ mod tests {
 
 			assert_eq!(
 				CheckWeight::<Test>(PhantomData).validate(&1, normal, len).unwrap().priority,
-				100,
+				0,
 			);
 			assert_eq!(
 				CheckWeight::<Test>(PhantomData).validate(&1, op, len).unwrap().priority,
@@ -1404,4 +1670,62 @@ mod tests {
 			assert!(CheckEra::<Test>::from(Era::mortal(4, 12)).additional_signed().is_ok());
 		})
 	}
+
+	#[test]
+	fn signed_ext_check_era_rejects_periods_beyond_block_hash_retention() {
+		with_externalities(&mut new_test_ext(), || {
+			System::set_block_number(13);
+			<BlockHash<Test>>::insert(12, H256::repeat_byte(1));
+
+			// `BlockHashCount` is 10 in tests.
+			assert_eq!(
+				CheckEra::<Test>::from(Era::mortal(16, 12)).additional_signed().err().unwrap(),
+				sr_primitives::ERA_PERIOD_TOO_LONG
+			);
+			let era = Era::mortal_with_max_period(16, 12, BlockHashCount::get());
+			assert_eq!(era.period(), Some(8));
+			assert!(CheckEra::<Test>::from(era).additional_signed().is_ok());
+		})
+	}
+
+	#[test]
+	fn signed_ext_check_era_should_change_longevity() {
+		with_externalities(&mut new_test_ext(), || {
+			let normal = DispatchInfo { weight: 100, class: DispatchClass::Normal };
+			let len = 0_usize;
+			let ext = (
+				CheckWeight::<Test>(PhantomData),
+				CheckEra::<Test>::from(Era::mortal(16, 256)),
+			);
+			System::set_block_number(17);
+			<BlockHash<Test>>::insert(16, H256::repeat_byte(1));
+
+			assert_eq!(ext.validate(&1, normal, len).unwrap().longevity, 15);
+
+			// immortal transactions stay valid forever
+			let ext = CheckEra::<Test>::from(Era::immortal());
+			assert_eq!(ext.validate(&1, normal, len).unwrap().longevity, TransactionLongevity::max_value());
+		})
+	}
+
+	#[test]
+	fn signed_ext_check_domain_signs_but_does_not_encode() {
+		let ext = CheckDomain::<Test, NetworkId, u32>::new();
+		assert_eq!(ext.additional_signed(), Ok(42));
+		assert!(ext.encode().is_empty());
+		assert_eq!(CheckDomain::<Test, NetworkId, u32>::decode(&mut &[][..]).unwrap(), ext);
+
+		let ext = (CheckGenesis::<Test>::new(), CheckDomain::<Test, NetworkId, u32>::new());
+		with_externalities(&mut new_test_ext(), || {
+			assert_eq!(ext.additional_signed(), Ok((H256::repeat_byte(69), 42)));
+		})
+	}
+
+	#[test]
+	fn signed_ext_check_tx_version_signs_the_transaction_version() {
+		let ext = CheckTxVersion::<Test>::new();
+		// Not the spec version, which changes with every runtime upgrade.
+		assert_eq!(ext.additional_signed(), Ok(3));
+		assert!(ext.encode().is_empty());
+	}
 }
@@ -277,10 +277,16 @@ impl<T: Trait> Module<T> {
 	}
 
 	/// Set the timestamp to something in particular. Only used for tests.
-	#[cfg(feature = "std")]
+	#[cfg(any(feature = "std", test))]
 	pub fn set_timestamp(now: T::Moment) {
 		<Self as Store>::Now::put(now);
 	}
+
+	/// Move the timestamp forward by `duration`. Only used for tests.
+	#[cfg(any(feature = "std", test))]
+	pub fn advance_timestamp(duration: T::Moment) {
+		<Self as Store>::Now::mutate(|now| *now += duration);
+	}
 }
 
 fn extract_inherent_data(data: &InherentData) -> Result<InherentType, RuntimeString> {
@@ -368,6 +374,9 @@ mod tests {
 		type BlockHashCount = BlockHashCount;
 		type MaximumBlockWeight = MaximumBlockWeight;
 		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+		type Call = ();
+		type BaseCallFilter = ();
 		type MaximumBlockLength = MaximumBlockLength;
 	}
 	parameter_types! {
@@ -0,0 +1,49 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API definition for the transaction payment module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+use client::decl_runtime_apis;
+use codec::{Encode, Decode, Codec};
+use sr_primitives::weights::{Weight, DispatchClass};
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
+
+/// Some information related to a dispatchable that can be queried from the runtime.
+#[derive(Eq, PartialEq, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct RuntimeDispatchInfo<Balance> {
+	/// Weight of this dispatch.
+	pub weight: Weight,
+	/// Class of this dispatch.
+	pub class: DispatchClass,
+	/// The partial inclusion fee of this dispatch. This does not include tip or anything else
+	/// that depends on the signature (i.e. depends on a `SignedExtension`).
+	pub partial_fee: Balance,
+}
+
+decl_runtime_apis! {
+	/// The api to query the fees of an extrinsic.
+	pub trait TransactionPaymentApi<Balance: Codec> {
+		/// Query information about the given extrinsic: its weight, class and the inclusion fee
+		/// it would pay if included in the next block.
+		fn query_info(uxt: Block::Extrinsic, len: u32) -> RuntimeDispatchInfo<Balance>;
+	}
+}
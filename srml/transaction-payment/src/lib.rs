@@ -0,0 +1,555 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Transaction Payment Module
+//!
+//! This module provides the basic logic needed to pay the absolute minimum amount needed for a
+//! transaction to be included. This includes:
+//!   - _length fee_: A fee proportional to the encoded length of the transaction.
+//!   - _weight fee_: A fee proportional to the weight of the transaction, adjusted by the weight
+//!     multiplier of the system module. The multiplier is updated at the end of each block based
+//!     on the fullness of the block, so that fees slowly rise when blocks are congested.
+//!   - (optional) _tip_: An extra amount added on top of the fee, giving the transaction a higher
+//!     priority in the transaction queue.
+//!
+//! The fees are charged through the [`ChargeTransactionPayment`] signed extension. The fee is
+//! withdrawn up front based on the pre-declared weight of the call. Should the call report a lower
+//! actual weight after dispatch (see `PostDispatchInfo`), the difference is refunded. The inclusion
+//! fee of an extrinsic can be queried with [`Module::query_info`], which is exposed to the outside
+//! world through the `TransactionPaymentApi` runtime api.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Encode, Decode};
+use srml_support::{
+	decl_module,
+	traits::{Currency, Get, OnUnbalanced, ExistenceRequirement, WithdrawReason, Imbalance},
+};
+use sr_primitives::{
+	weights::{Weight, DispatchInfo, PostDispatchInfo, GetDispatchInfo},
+	transaction_validity::{TransactionPriority, ValidTransaction},
+	traits::{SignedExtension, SaturatedConversion, Saturating, Convert, DispatchError, Zero},
+};
+use transaction_payment_runtime_api::RuntimeDispatchInfo;
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
+
+pub trait Trait: system::Trait {
+	/// The currency type in which fees will be paid.
+	type Currency: Currency<Self::AccountId>;
+
+	/// Handler for the unbalanced reduction when taking transaction fees.
+	type OnTransactionPayment: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+	/// The fee to be paid for making a transaction; the base.
+	type TransactionBaseFee: Get<BalanceOf<Self>>;
+
+	/// The fee to be paid for making a transaction; the per-byte portion.
+	type TransactionByteFee: Get<BalanceOf<Self>>;
+
+	/// Convert a weight value into a deductible fee based on the currency type.
+	type WeightToFee: Convert<Weight, BalanceOf<Self>>;
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		/// The fee to be paid for making a transaction; the base.
+		const TransactionBaseFee: BalanceOf<T> = T::TransactionBaseFee::get();
+
+		/// The fee to be paid for making a transaction; the per-byte portion.
+		const TransactionByteFee: BalanceOf<T> = T::TransactionByteFee::get();
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Query the data that we know about the fee of a given `call`.
+	///
+	/// As this module is not and cannot be aware of the internals of a signed extension, it only
+	/// interprets the extrinsic as some encoded value and accounts for its weight and length, the
+	/// runtime's extrinsic base weight, and the current fee multiplier. The tip is not included.
+	pub fn query_info<Extrinsic: GetDispatchInfo>(
+		unchecked_extrinsic: Extrinsic,
+		len: u32,
+	) -> RuntimeDispatchInfo<BalanceOf<T>> {
+		let dispatch_info = <Extrinsic as GetDispatchInfo>::get_dispatch_info(&unchecked_extrinsic);
+
+		let partial_fee = ChargeTransactionPayment::<T>::compute_fee(len, dispatch_info, Zero::zero());
+		let DispatchInfo { weight, class } = dispatch_info;
+
+		RuntimeDispatchInfo { weight, class, partial_fee }
+	}
+}
+
+/// Require the transactor pay for themselves and maybe include a tip to gain additional priority
+/// in the queue.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct ChargeTransactionPayment<T: Trait + Send + Sync>(#[codec(compact)] BalanceOf<T>);
+
+impl<T: Trait + Send + Sync> ChargeTransactionPayment<T> {
+	/// utility constructor. Used only in client/factory code.
+	pub fn from(fee: BalanceOf<T>) -> Self {
+		Self(fee)
+	}
+
+	/// Compute the final fee value for a particular transaction.
+	///
+	/// The final fee is composed of:
+	///   - _length-fee_: This is the amount paid merely to pay for size of the transaction.
+	///   - _weight-fee_: This amount is computed based on the weight of the transaction. Unlike
+	///      size-fee, this is not input dependent and reflects the _complexity_ of the execution
+	///      and the time it consumes.
+	///   - (optional) _tip_: if included in the transaction, it will be added on top. Only signed
+	///      transactions can have a tip.
+	pub fn compute_fee(len: u32, info: DispatchInfo, tip: BalanceOf<T>) -> BalanceOf<T> {
+		let len_fee = if info.pay_length_fee() {
+			let len = <BalanceOf<T>>::from(len);
+			let base = T::TransactionBaseFee::get();
+			let per_byte = T::TransactionByteFee::get();
+			base.saturating_add(per_byte.saturating_mul(len))
+		} else {
+			Zero::zero()
+		};
+
+		let weight_fee = {
+			// cap the weight to the maximum defined in runtime, otherwise it will be the `Bounded`
+			// maximum of its data type, which is not desired.
+			let capped_weight = info.weight.min(<T as system::Trait>::MaximumBlockWeight::get());
+			let weight_update = <system::Module<T>>::next_weight_multiplier();
+			let adjusted_weight = weight_update.apply_to(capped_weight);
+			T::WeightToFee::convert(adjusted_weight)
+		};
+
+		len_fee.saturating_add(weight_fee).saturating_add(tip)
+	}
+
+	/// Compute the fee of a transaction after its dispatch, taking into account the actual weight
+	/// that it has consumed as reported in `post_info`.
+	pub fn compute_actual_fee(
+		len: u32,
+		info: DispatchInfo,
+		post_info: PostDispatchInfo,
+		tip: BalanceOf<T>,
+	) -> BalanceOf<T> {
+		let info = DispatchInfo { weight: post_info.calc_actual_weight(&info), ..info };
+		Self::compute_fee(len, info, tip)
+	}
+
+	/// Withdraw the (worst case) fee of the transaction from `who`.
+	///
+	/// Returns the withdrawn fee along with the imbalance that it created, if any.
+	fn withdraw_fee(
+		&self,
+		who: &T::AccountId,
+		info: DispatchInfo,
+		len: usize,
+	) -> rstd::result::Result<(BalanceOf<T>, Option<NegativeImbalanceOf<T>>), DispatchError> {
+		let fee = Self::compute_fee(len as u32, info, self.0);
+		if fee.is_zero() {
+			return Ok((fee, None));
+		}
+
+		let imbalance = T::Currency::withdraw(
+			who,
+			fee,
+			WithdrawReason::TransactionPayment,
+			ExistenceRequirement::KeepAlive,
+		).map_err(|_| DispatchError::Payment)?;
+		Ok((fee, Some(imbalance)))
+	}
+}
+
+#[cfg(feature = "std")]
+impl<T: Trait + Send + Sync> rstd::fmt::Debug for ChargeTransactionPayment<T> {
+	fn fmt(&self, f: &mut rstd::fmt::Formatter) -> rstd::fmt::Result {
+		write!(f, "ChargeTransactionPayment<{:?}>", self.0)
+	}
+}
+
+impl<T: Trait + Send + Sync> SignedExtension for ChargeTransactionPayment<T>
+	where BalanceOf<T>: Send + Sync
+{
+	type AccountId = T::AccountId;
+	type AdditionalSigned = ();
+	/// The tip, the payer, the fee that was withdrawn up front and its imbalance.
+	type Pre = (BalanceOf<T>, Self::AccountId, BalanceOf<T>, Option<NegativeImbalanceOf<T>>);
+	fn additional_signed(&self) -> rstd::result::Result<(), &'static str> { Ok(()) }
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		info: DispatchInfo,
+		len: usize,
+	) -> rstd::result::Result<ValidTransaction, DispatchError> {
+		// only check that the fees can be paid; the withdrawal is discarded along with the state.
+		let (fee, _) = self.withdraw_fee(who, info, len)?;
+
+		let mut r = ValidTransaction::default();
+		// NOTE: we probably want to maximize the _fee (of any type) per weight unit_ here, which
+		// will be a bit more than setting the priority to tip. For now, this is enough.
+		r.priority = fee.saturated_into::<TransactionPriority>();
+		Ok(r)
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		info: DispatchInfo,
+		len: usize,
+	) -> rstd::result::Result<Self::Pre, DispatchError> {
+		let (fee, imbalance) = self.withdraw_fee(who, info, len)?;
+		Ok((self.0, who.clone(), fee, imbalance))
+	}
+
+	fn post_dispatch(
+		pre: Self::Pre,
+		info: DispatchInfo,
+		post_info: PostDispatchInfo,
+		len: usize,
+	) {
+		let (tip, who, fee, imbalance) = pre;
+		if let Some(payed) = imbalance {
+			let actual_fee = Self::compute_actual_fee(len as u32, info, post_info, tip);
+			let refund = fee.saturating_sub(actual_fee);
+			let actual_payment = if refund.is_zero() {
+				payed
+			} else {
+				match T::Currency::deposit_into_existing(&who, refund) {
+					// the refund can never be larger than what was payed up front.
+					Ok(refund_imbalance) => match payed.offset(refund_imbalance) {
+						Ok(actual_payment) => actual_payment,
+						Err(_) => return,
+					},
+					// the account is gone; it does not get recreated by the refund.
+					Err(_) => payed,
+				}
+			};
+			T::OnTransactionPayment::on_unbalanced(actual_payment);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use runtime_io::with_externalities;
+	use srml_support::{impl_outer_origin, impl_outer_dispatch, parameter_types};
+	use primitives::{H256, Blake2Hasher};
+	use sr_primitives::{
+		Perbill,
+		traits::{BlakeTwo256, IdentityLookup},
+		testing::{Header, TestXt},
+		weights::DispatchClass,
+	};
+	use balances::Call as BalancesCall;
+	use std::cell::RefCell;
+
+	#[derive(Clone, PartialEq, Eq, Debug)]
+	pub struct Runtime;
+
+	impl_outer_origin!{
+		pub enum Origin for Runtime {}
+	}
+
+	impl_outer_dispatch! {
+		pub enum Call for Runtime where origin: Origin {
+			balances::Balances,
+		}
+	}
+
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: u32 = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+	}
+
+	impl system::Trait for Runtime {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<u64>;
+		type Header = Header;
+		type WeightMultiplierUpdate = ();
+		type Event = ();
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+		type Call = ();
+		type BaseCallFilter = ();
+	}
+
+	parameter_types! {
+		pub const ExistentialDeposit: u64 = 0;
+		pub const TransferFee: u64 = 0;
+		pub const CreationFee: u64 = 0;
+	}
+
+	impl balances::Trait for Runtime {
+		type Balance = u64;
+		type OnFreeBalanceZero = ();
+		type OnNewAccount = ();
+		type Event = ();
+		type TransferPayment = ();
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+	}
+
+	thread_local! {
+		static TRANSACTION_BASE_FEE: RefCell<u64> = RefCell::new(0);
+		static TRANSACTION_BYTE_FEE: RefCell<u64> = RefCell::new(1);
+		static WEIGHT_TO_FEE: RefCell<u64> = RefCell::new(1);
+	}
+
+	pub struct TransactionBaseFee;
+	impl Get<u64> for TransactionBaseFee {
+		fn get() -> u64 { TRANSACTION_BASE_FEE.with(|v| *v.borrow()) }
+	}
+
+	pub struct TransactionByteFee;
+	impl Get<u64> for TransactionByteFee {
+		fn get() -> u64 { TRANSACTION_BYTE_FEE.with(|v| *v.borrow()) }
+	}
+
+	pub struct WeightToFee(u64);
+	impl Convert<Weight, u64> for WeightToFee {
+		fn convert(t: Weight) -> u64 {
+			WEIGHT_TO_FEE.with(|v| *v.borrow() * (t as u64))
+		}
+	}
+
+	impl Trait for Runtime {
+		type Currency = balances::Module<Runtime>;
+		type OnTransactionPayment = ();
+		type TransactionBaseFee = TransactionBaseFee;
+		type TransactionByteFee = TransactionByteFee;
+		type WeightToFee = WeightToFee;
+	}
+
+	type Balances = balances::Module<Runtime>;
+	type TransactionPayment = Module<Runtime>;
+
+	pub struct ExtBuilder {
+		balance_factor: u64,
+		base_fee: u64,
+		byte_fee: u64,
+		weight_to_fee: u64
+	}
+
+	impl Default for ExtBuilder {
+		fn default() -> Self {
+			Self {
+				balance_factor: 1,
+				base_fee: 0,
+				byte_fee: 1,
+				weight_to_fee: 1,
+			}
+		}
+	}
+
+	impl ExtBuilder {
+		pub fn fees(mut self, base: u64, byte: u64, weight: u64) -> Self {
+			self.base_fee = base;
+			self.byte_fee = byte;
+			self.weight_to_fee = weight;
+			self
+		}
+		pub fn balance_factor(mut self, factor: u64) -> Self {
+			self.balance_factor = factor;
+			self
+		}
+		fn set_constants(&self) {
+			TRANSACTION_BASE_FEE.with(|v| *v.borrow_mut() = self.base_fee);
+			TRANSACTION_BYTE_FEE.with(|v| *v.borrow_mut() = self.byte_fee);
+			WEIGHT_TO_FEE.with(|v| *v.borrow_mut() = self.weight_to_fee);
+		}
+		pub fn build(self) -> runtime_io::TestExternalities<Blake2Hasher> {
+			self.set_constants();
+			let mut t = system::GenesisConfig::default().build_storage::<Runtime>().unwrap().0;
+			t.extend(balances::GenesisConfig::<Runtime> {
+				balances: vec![
+					(1, 10 * self.balance_factor),
+					(2, 20 * self.balance_factor),
+					(3, 30 * self.balance_factor),
+					(4, 40 * self.balance_factor),
+					(5, 50 * self.balance_factor),
+					(6, 60 * self.balance_factor)
+				],
+				vesting: vec![],
+			}.build_storage().unwrap().0);
+			t.into()
+		}
+	}
+
+	/// create a transaction info struct from weight. Handy to avoid building the whole struct.
+	pub fn info_from_weight(w: Weight) -> DispatchInfo {
+		DispatchInfo { weight: w, ..Default::default() }
+	}
+
+	#[test]
+	fn signed_extension_transaction_payment_work() {
+		with_externalities(&mut ExtBuilder::default()
+			.balance_factor(10) // 100
+			.fees(5, 1, 1) // 5 fixed, 1 per byte, 1 per weight
+			.build(),
+		|| {
+			let len = 10;
+			assert!(
+				ChargeTransactionPayment::<Runtime>::from(0)
+					.pre_dispatch(&1, info_from_weight(5), len)
+					.is_ok()
+			);
+			assert_eq!(Balances::free_balance(&1), 100 - 5 - 5 - 10);
+
+			assert!(
+				ChargeTransactionPayment::<Runtime>::from(5 /* tipped */)
+					.pre_dispatch(&2, info_from_weight(3), len)
+					.is_ok()
+			);
+			assert_eq!(Balances::free_balance(&2), 200 - 5 - 10 - 3 - 5);
+		});
+	}
+
+	#[test]
+	fn signed_extension_transaction_payment_refunds_unspent_weight() {
+		with_externalities(&mut ExtBuilder::default()
+			.balance_factor(10) // 100
+			.fees(5, 1, 1) // 5 fixed, 1 per byte, 1 per weight
+			.build(),
+		|| {
+			let len = 10;
+			let info = info_from_weight(50);
+
+			// the call does not report its actual weight: nothing is refunded.
+			let pre = ChargeTransactionPayment::<Runtime>::from(0)
+				.pre_dispatch(&1, info, len)
+				.unwrap();
+			assert_eq!(Balances::free_balance(&1), 100 - 5 - 10 - 50);
+			ChargeTransactionPayment::<Runtime>::post_dispatch(pre, info, Default::default(), len);
+			assert_eq!(Balances::free_balance(&1), 100 - 5 - 10 - 50);
+
+			// the call consumed only a part of its weight: the rest is refunded, the tip is not.
+			let pre = ChargeTransactionPayment::<Runtime>::from(5 /* tipped */)
+				.pre_dispatch(&2, info, len)
+				.unwrap();
+			assert_eq!(Balances::free_balance(&2), 200 - 5 - 10 - 50 - 5);
+			let post_info = PostDispatchInfo { actual_weight: Some(20) };
+			ChargeTransactionPayment::<Runtime>::post_dispatch(pre, info, post_info, len);
+			assert_eq!(Balances::free_balance(&2), 200 - 5 - 10 - 20 - 5);
+		});
+	}
+
+	#[test]
+	fn signed_extension_transaction_payment_is_bounded() {
+		with_externalities(&mut ExtBuilder::default()
+			.balance_factor(1000)
+			.fees(0, 0, 1)
+			.build(),
+		|| {
+			// maximum weight possible
+			assert!(
+				ChargeTransactionPayment::<Runtime>::from(0)
+					.pre_dispatch(&1, info_from_weight(Weight::max_value()), 10)
+					.is_ok()
+			);
+			// fee will be proportional to what is the actual maximum weight in the runtime.
+			assert_eq!(
+				Balances::free_balance(&1),
+				(10000 - <Runtime as system::Trait>::MaximumBlockWeight::get()) as u64
+			);
+		});
+	}
+
+	#[test]
+	fn signed_extension_transaction_payment_priority_follows_tip() {
+		with_externalities(&mut ExtBuilder::default()
+			.balance_factor(10)
+			.fees(10, 1, 5)
+			.build(),
+		|| {
+			let len = 10;
+			let priority = |tip| ChargeTransactionPayment::<Runtime>::from(tip)
+				.validate(&4, info_from_weight(5), len)
+				.unwrap()
+				.priority;
+
+			assert_eq!(priority(0), 20 + 25);
+			assert_eq!(priority(5), 20 + 25 + 5);
+			assert!(priority(10) > priority(5));
+		});
+	}
+
+	#[test]
+	fn signed_extension_transaction_payment_fails_without_funds() {
+		with_externalities(&mut ExtBuilder::default()
+			.fees(100, 1, 1)
+			.build(),
+		|| {
+			assert!(
+				ChargeTransactionPayment::<Runtime>::from(0)
+					.pre_dispatch(&1, info_from_weight(5), 10)
+					.is_err()
+			);
+			assert_eq!(Balances::free_balance(&1), 10);
+		});
+	}
+
+	#[test]
+	fn query_info_works() {
+		let call = Call::Balances(BalancesCall::transfer(2, 69));
+		let origin = 111111;
+		let extra = ();
+		let xt = TestXt(Some((origin, extra)), call);
+		let info = xt.get_dispatch_info();
+		let ext = xt.encode();
+		let len = ext.len() as u32;
+
+		with_externalities(&mut ExtBuilder::default()
+			.fees(5, 1, 2)
+			.build(),
+		|| {
+			assert_eq!(
+				TransactionPayment::query_info(xt, len),
+				RuntimeDispatchInfo {
+					weight: info.weight,
+					class: info.class,
+					partial_fee: 5 /* base */ + len as u64 /* len * 1 */ + info.weight as u64 * 2,
+				},
+			);
+		});
+	}
+
+	#[test]
+	fn query_info_skips_length_fee_of_operational_calls() {
+		with_externalities(&mut ExtBuilder::default()
+			.fees(5, 1, 1)
+			.build(),
+		|| {
+			let info = DispatchInfo { weight: 100, class: DispatchClass::Operational };
+			assert_eq!(ChargeTransactionPayment::<Runtime>::compute_fee(10, info, 0), 100);
+			let info = DispatchInfo { weight: 100, class: DispatchClass::Normal };
+			assert_eq!(ChargeTransactionPayment::<Runtime>::compute_fee(10, info, 0), 5 + 10 + 100);
+		});
+	}
+}
@@ -60,6 +60,7 @@
 //! - `configure` - Configure the module's proposal requirements.
 //! - `reject_proposal` - Reject a proposal, slashing the deposit.
 //! - `approve_proposal` - Accept the proposal, returning the deposit.
+//! - `fund_from_mint` - Fund the pot out of a mint.
 //!
 //! ## GenesisConfig
 //!
@@ -70,9 +71,9 @@
 #[cfg(feature = "std")]
 use serde::{Serialize, Deserialize};
 use rstd::prelude::*;
-use srml_support::{StorageValue, StorageMap, decl_module, decl_storage, decl_event, ensure, print};
+use srml_support::{StorageValue, StorageMap, Parameter, decl_module, decl_storage, decl_event, ensure, print};
 use srml_support::traits::{
-	Currency, ExistenceRequirement, Get, Imbalance, OnDilution, OnUnbalanced,
+	Currency, ExistenceRequirement, Get, Imbalance, MintFunds, OnDilution, OnUnbalanced,
 	ReservableCurrency, WithdrawReason
 };
 use sr_primitives::{Permill, ModuleId};
@@ -125,6 +126,12 @@ pub trait Trait: system::Trait {
 
 	/// Percentage of spare funds (if any) that are burnt per spend period.
 	type Burn: Get<Permill>;
+
+	/// The identifier of a mint.
+	type MintId: Parameter;
+
+	/// The mints the pot can be funded from.
+	type Mint: MintFunds<Self::AccountId, BalanceOf<Self>, MintId = Self::MintId>;
 }
 
 type ProposalIndex = u32;
@@ -208,6 +215,22 @@ decl_module! {
 			Approvals::mutate(|v| v.push(proposal_id));
 		}
 
+		/// Fund the pot with `amount` out of the mint `mint_id`, drawing down its capacity.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - Limited storage reads.
+		/// - Two DB changes.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(100_000)]
+		fn fund_from_mint(origin, mint_id: T::MintId, #[compact] amount: BalanceOf<T>) {
+			T::ApproveOrigin::ensure_origin(origin)?;
+
+			T::Mint::mint_funds(&mint_id, &Self::account_id(), amount)?;
+
+			Self::deposit_event(RawEvent::FundedFromMint(mint_id, amount));
+		}
+
 		fn on_finalize(n: T::BlockNumber) {
 			// Check to see if we should spend some funds!
 			if (n % T::SpendPeriod::get()).is_zero() {
@@ -244,7 +267,8 @@ decl_event!(
 	pub enum Event<T>
 	where
 		Balance = BalanceOf<T>,
-		<T as system::Trait>::AccountId
+		<T as system::Trait>::AccountId,
+		<T as Trait>::MintId
 	{
 		/// New proposal.
 		Proposed(ProposalIndex),
@@ -256,6 +280,8 @@ decl_event!(
 		Burnt(Balance),
 		/// Spending has finished; this is the amount that rolls over until next spend.
 		Rollover(Balance),
+		/// The pot was funded out of a mint.
+		FundedFromMint(MintId, Balance),
 	}
 );
 
@@ -362,59 +388,17 @@ mod tests {
 	use super::*;
 
 	use runtime_io::with_externalities;
-	use srml_support::{assert_noop, assert_ok, impl_outer_origin, parameter_types};
-	use primitives::{H256, Blake2Hasher};
-	use sr_primitives::{Perbill, traits::{BlakeTwo256, OnFinalize, IdentityLookup}, testing::Header};
-
-	impl_outer_origin! {
-		pub enum Origin for Test {}
-	}
-
-	#[derive(Clone, Eq, PartialEq)]
-	pub struct Test;
-	parameter_types! {
-		pub const BlockHashCount: u64 = 250;
-		pub const MaximumBlockWeight: u32 = 1024;
-		pub const MaximumBlockLength: u32 = 2 * 1024;
-		pub const AvailableBlockRatio: Perbill = Perbill::one();
-	}
-	impl system::Trait for Test {
-		type Origin = Origin;
-		type Index = u64;
-		type BlockNumber = u64;
-		type Hash = H256;
-		type Hashing = BlakeTwo256;
-		type AccountId = u64;
-		type Lookup = IdentityLookup<Self::AccountId>;
-		type Header = Header;
-		type WeightMultiplierUpdate = ();
-		type Event = ();
-		type BlockHashCount = BlockHashCount;
-		type MaximumBlockWeight = MaximumBlockWeight;
-		type AvailableBlockRatio = AvailableBlockRatio;
-		type MaximumBlockLength = MaximumBlockLength;
-	}
-	parameter_types! {
-		pub const ExistentialDeposit: u64 = 0;
-		pub const TransferFee: u64 = 0;
-		pub const CreationFee: u64 = 0;
-		pub const TransactionBaseFee: u64 = 0;
-		pub const TransactionByteFee: u64 = 0;
-	}
-	impl balances::Trait for Test {
-		type Balance = u64;
-		type OnNewAccount = ();
-		type OnFreeBalanceZero = ();
-		type Event = ();
-		type TransactionPayment = ();
-		type TransferPayment = ();
-		type DustRemoval = ();
-		type ExistentialDeposit = ExistentialDeposit;
-		type TransferFee = TransferFee;
-		type CreationFee = CreationFee;
-		type TransactionBaseFee = TransactionBaseFee;
-		type TransactionByteFee = TransactionByteFee;
-		type WeightToFee = ();
+	use srml_support::{assert_noop, assert_ok, impl_test_runtime, parameter_types};
+	use primitives::Blake2Hasher;
+	use sr_primitives::traits::OnFinalize;
+
+	impl_test_runtime! {
+		pub struct Test;
+		balances {
+			ExistentialDeposit = 0;
+			TransferFee = 0;
+			CreationFee = 0;
+		}
 	}
 	parameter_types! {
 		pub const ProposalBond: Permill = Permill::from_percent(5);
@@ -433,10 +417,25 @@ mod tests {
 		type ProposalBondMinimum = ProposalBondMinimum;
 		type SpendPeriod = SpendPeriod;
 		type Burn = Burn;
+		type MintId = u32;
+		type Mint = TestMint;
 	}
 	type Balances = balances::Module<Test>;
 	type Treasury = Module<Test>;
 
+	/// A single mint `0`, which can mint up to 50 at once.
+	pub struct TestMint;
+	impl MintFunds<u64, u64> for TestMint {
+		type MintId = u32;
+
+		fn mint_funds(mint: &u32, to: &u64, amount: u64) -> Result<(), &'static str> {
+			ensure!(*mint == 0, "Unknown mint");
+			ensure!(amount <= 50, "Mint capacity exceeded");
+			drop(Balances::deposit_creating(to, amount));
+			Ok(())
+		}
+	}
+
 	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
 		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap().0;
 		t.extend(balances::GenesisConfig::<Test>{
@@ -463,6 +462,17 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn pot_is_funded_from_mints() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_noop!(Treasury::fund_from_mint(Origin::signed(0), 0, 40), "bad origin: expected to be a root origin");
+			assert_noop!(Treasury::fund_from_mint(Origin::ROOT, 1, 40), "Unknown mint");
+			assert_noop!(Treasury::fund_from_mint(Origin::ROOT, 0, 60), "Mint capacity exceeded");
+			assert_ok!(Treasury::fund_from_mint(Origin::ROOT, 0, 40));
+			assert_eq!(Treasury::pot(), 40);
+		});
+	}
+
 	#[test]
 	fn spend_proposal_takes_min_deposit() {
 		with_externalities(&mut new_test_ext(), || {
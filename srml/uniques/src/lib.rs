@@ -0,0 +1,541 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Uniques Module
+//!
+//! The Uniques module records the ownership of non-fungible items, grouped in collections.
+//!
+//! - [`uniques::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! A collection has an owner, who created it, and an admin, who mints its items and describes
+//! them with metadata and attributes. The owner of an item can transfer it, or approve another
+//! account to transfer it once. Items can be burned by their owner or the admin.
+//!
+//! The owner of a collection reserves `CollectionDeposit` for it, and a deposit for each metadata
+//! and attribute of the collection and its items, of `MetadataDepositBase` and `DepositPerByte`
+//! for each byte of data. The deposits are returned when the data is removed. The new owner of a
+//! collection takes over its deposits.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `create` - Create a collection.
+//! - `destroy` - Destroy a collection without items.
+//! - `mint` - Mint an item of a collection.
+//! - `burn` - Destroy an item.
+//! - `transfer` - Transfer an item.
+//! - `approve_transfer` - Approve an account to transfer an item.
+//! - `cancel_approval` - Cancel the approval of the transfer of an item.
+//! - `set_admin` - Change the admin of a collection.
+//! - `transfer_ownership` - Change the owner of a collection.
+//! - `set_collection_metadata` - Set the metadata of a collection.
+//! - `set_metadata` - Set the metadata of an item.
+//! - `clear_metadata` - Remove the metadata of an item.
+//! - `set_attribute` - Set an attribute of an item.
+//! - `clear_attribute` - Remove an attribute of an item.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod tests;
+
+use rstd::prelude::*;
+use codec::{Encode, Decode};
+use srml_support::{
+	StorageValue, StorageMap, StorageDoubleMap, Parameter, decl_module, decl_storage, decl_event,
+	ensure,
+};
+use srml_support::dispatch::Result;
+use srml_support::traits::{Currency, ReservableCurrency, Get};
+use sr_primitives::traits::{SimpleArithmetic, StaticLookup, One, Zero, Saturating};
+use system::ensure_signed;
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The currency the deposits are reserved in.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The identifier of a collection.
+	type CollectionId: Parameter + SimpleArithmetic + Default + Copy;
+
+	/// The identifier of an item in its collection.
+	type ItemId: Parameter + Copy;
+
+	/// The deposit of a collection.
+	type CollectionDeposit: Get<BalanceOf<Self>>;
+
+	/// The deposit of each metadata or attribute.
+	type MetadataDepositBase: Get<BalanceOf<Self>>;
+
+	/// The deposit of each byte of a metadata or attribute.
+	type DepositPerByte: Get<BalanceOf<Self>>;
+
+	/// The maximum length of a metadata, or of the key and value of an attribute.
+	type StringLimit: Get<u32>;
+}
+
+/// A collection of items.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct CollectionDetails<AccountId, Balance> {
+	/// The owner of the collection, who reserved its deposits.
+	pub owner: AccountId,
+	/// The account minting and describing the items.
+	pub admin: AccountId,
+	/// The number of items.
+	pub items: u32,
+	/// The total deposit of the collection, its metadata and its items.
+	pub total_deposit: Balance,
+}
+
+/// An item of a collection.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct ItemDetails<AccountId, Balance> {
+	/// The owner of the item.
+	pub owner: AccountId,
+	/// The account approved to transfer the item.
+	pub approved: Option<AccountId>,
+	/// The deposit of the metadata and attributes of the item.
+	pub deposit: Balance,
+}
+
+/// The metadata of a collection or item, or the value of an attribute.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Metadata<Balance> {
+	/// The data.
+	pub data: Vec<u8>,
+	/// The deposit reserved for the data.
+	pub deposit: Balance,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Uniques {
+		/// The identifier of the next collection.
+		pub NextCollectionId get(next_collection_id): T::CollectionId;
+		/// The collections.
+		pub Collections get(collection):
+			map T::CollectionId => Option<CollectionDetails<T::AccountId, BalanceOf<T>>>;
+		/// The items of each collection.
+		pub Items get(item):
+			map (T::CollectionId, T::ItemId) => Option<ItemDetails<T::AccountId, BalanceOf<T>>>;
+		/// The metadata of each collection.
+		pub CollectionMetadata get(collection_metadata): map T::CollectionId => Option<Metadata<BalanceOf<T>>>;
+		/// The metadata of each item.
+		pub ItemMetadata get(item_metadata): map (T::CollectionId, T::ItemId) => Option<Metadata<BalanceOf<T>>>;
+		/// The attributes of each item, by key.
+		pub Attributes get(attribute):
+			double_map (T::CollectionId, T::ItemId), blake2_256(Vec<u8>) => Option<Metadata<BalanceOf<T>>>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as Trait>::CollectionId,
+		<T as Trait>::ItemId,
+	{
+		/// A collection was created by the owner, with the admin.
+		Created(CollectionId, AccountId, AccountId),
+		/// A collection was destroyed.
+		Destroyed(CollectionId),
+		/// An item was minted for the owner.
+		Issued(CollectionId, ItemId, AccountId),
+		/// An item was transferred from an account to another.
+		Transferred(CollectionId, ItemId, AccountId, AccountId),
+		/// An item of the owner was burned.
+		Burned(CollectionId, ItemId, AccountId),
+		/// The owner of an item approved the account to transfer it.
+		ApprovedTransfer(CollectionId, ItemId, AccountId, AccountId),
+		/// The approval of the transfer of an item was cancelled.
+		ApprovalCancelled(CollectionId, ItemId),
+		/// The admin of a collection changed.
+		AdminChanged(CollectionId, AccountId),
+		/// The owner of a collection changed.
+		OwnerChanged(CollectionId, AccountId),
+		/// The metadata of a collection was set.
+		CollectionMetadataSet(CollectionId),
+		/// The metadata of an item was set.
+		MetadataSet(CollectionId, ItemId),
+		/// The metadata of an item was removed.
+		MetadataCleared(CollectionId, ItemId),
+		/// The attribute of an item with the key was set.
+		AttributeSet(CollectionId, ItemId, Vec<u8>),
+		/// The attribute of an item with the key was removed.
+		AttributeCleared(CollectionId, ItemId, Vec<u8>),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		/// Create a collection owned by the sender, whose items are minted by `admin`.
+		///
+		/// The sender reserves `CollectionDeposit`.
+		fn create(origin, admin: <T::Lookup as StaticLookup>::Source) -> Result {
+			let owner = ensure_signed(origin)?;
+			let admin = T::Lookup::lookup(admin)?;
+
+			let deposit = T::CollectionDeposit::get();
+			T::Currency::reserve(&owner, deposit)?;
+
+			let id = Self::next_collection_id();
+			<NextCollectionId<T>>::mutate(|id| *id += One::one());
+			<Collections<T>>::insert(id, CollectionDetails {
+				owner: owner.clone(),
+				admin: admin.clone(),
+				items: 0,
+				total_deposit: deposit,
+			});
+			Self::deposit_event(RawEvent::Created(id, owner, admin));
+
+			Ok(())
+		}
+
+		/// Destroy the collection `collection` of the sender, which must have no items.
+		///
+		/// Its deposits are returned.
+		fn destroy(origin, #[compact] collection: T::CollectionId) -> Result {
+			let who = ensure_signed(origin)?;
+			let details = Self::collection(collection).ok_or("Unknown collection")?;
+			ensure!(details.owner == who, "Not the owner of the collection");
+			ensure!(details.items == 0, "Collection still has items");
+
+			T::Currency::unreserve(&details.owner, details.total_deposit);
+			<Collections<T>>::remove(collection);
+			<CollectionMetadata<T>>::remove(collection);
+			Self::deposit_event(RawEvent::Destroyed(collection));
+
+			Ok(())
+		}
+
+		/// Mint the item `item` of `collection` for `owner`.
+		///
+		/// The sender must be the admin of the collection.
+		fn mint(
+			origin,
+			#[compact] collection: T::CollectionId,
+			item: T::ItemId,
+			owner: <T::Lookup as StaticLookup>::Source
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let owner = T::Lookup::lookup(owner)?;
+			let mut details = Self::collection(collection).ok_or("Unknown collection")?;
+			ensure!(details.admin == who, "Not the admin of the collection");
+			ensure!(!<Items<T>>::exists((collection, item)), "Item already exists");
+
+			details.items = details.items.checked_add(1).ok_or("Too many items")?;
+			<Collections<T>>::insert(collection, details);
+			<Items<T>>::insert((collection, item), ItemDetails {
+				owner: owner.clone(),
+				approved: None,
+				deposit: Zero::zero(),
+			});
+			Self::deposit_event(RawEvent::Issued(collection, item, owner));
+
+			Ok(())
+		}
+
+		/// Destroy the item `item` of `collection`, with its metadata and attributes.
+		///
+		/// The sender must be the owner of the item or the admin of the collection.
+		fn burn(origin, #[compact] collection: T::CollectionId, item: T::ItemId) -> Result {
+			let who = ensure_signed(origin)?;
+			let mut details = Self::collection(collection).ok_or("Unknown collection")?;
+			let item_details = Self::item((collection, item)).ok_or("Unknown item")?;
+			ensure!(
+				item_details.owner == who || details.admin == who,
+				"Not the owner of the item or the admin of the collection"
+			);
+
+			T::Currency::unreserve(&details.owner, item_details.deposit);
+			details.total_deposit -= item_details.deposit;
+			details.items -= 1;
+			<Collections<T>>::insert(collection, details);
+			<Items<T>>::remove((collection, item));
+			<ItemMetadata<T>>::remove((collection, item));
+			<Attributes<T>>::remove_prefix(&(collection, item));
+			Self::deposit_event(RawEvent::Burned(collection, item, item_details.owner));
+
+			Ok(())
+		}
+
+		/// Transfer the item `item` of `collection` to `dest`.
+		///
+		/// The sender must be the owner of the item or the account approved to transfer it.
+		fn transfer(
+			origin,
+			#[compact] collection: T::CollectionId,
+			item: T::ItemId,
+			dest: <T::Lookup as StaticLookup>::Source
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+			let mut item_details = Self::item((collection, item)).ok_or("Unknown item")?;
+			ensure!(
+				item_details.owner == who || item_details.approved.as_ref() == Some(&who),
+				"Not allowed to transfer the item"
+			);
+
+			let source = rstd::mem::replace(&mut item_details.owner, dest.clone());
+			item_details.approved = None;
+			<Items<T>>::insert((collection, item), item_details);
+			Self::deposit_event(RawEvent::Transferred(collection, item, source, dest));
+
+			Ok(())
+		}
+
+		/// Approve `delegate` to transfer the item `item` of `collection` of the sender, once.
+		fn approve_transfer(
+			origin,
+			#[compact] collection: T::CollectionId,
+			item: T::ItemId,
+			delegate: <T::Lookup as StaticLookup>::Source
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let delegate = T::Lookup::lookup(delegate)?;
+			let mut item_details = Self::item((collection, item)).ok_or("Unknown item")?;
+			ensure!(item_details.owner == who, "Not the owner of the item");
+
+			item_details.approved = Some(delegate.clone());
+			<Items<T>>::insert((collection, item), item_details);
+			Self::deposit_event(RawEvent::ApprovedTransfer(collection, item, who, delegate));
+
+			Ok(())
+		}
+
+		/// Cancel the approval of the transfer of the item `item` of `collection` of the sender.
+		fn cancel_approval(origin, #[compact] collection: T::CollectionId, item: T::ItemId) -> Result {
+			let who = ensure_signed(origin)?;
+			let mut item_details = Self::item((collection, item)).ok_or("Unknown item")?;
+			ensure!(item_details.owner == who, "Not the owner of the item");
+			ensure!(item_details.approved.is_some(), "No approved transfer");
+
+			item_details.approved = None;
+			<Items<T>>::insert((collection, item), item_details);
+			Self::deposit_event(RawEvent::ApprovalCancelled(collection, item));
+
+			Ok(())
+		}
+
+		/// Make `admin` the admin of the collection `collection` of the sender.
+		fn set_admin(
+			origin,
+			#[compact] collection: T::CollectionId,
+			admin: <T::Lookup as StaticLookup>::Source
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let admin = T::Lookup::lookup(admin)?;
+			let mut details = Self::collection(collection).ok_or("Unknown collection")?;
+			ensure!(details.owner == who, "Not the owner of the collection");
+
+			details.admin = admin.clone();
+			<Collections<T>>::insert(collection, details);
+			Self::deposit_event(RawEvent::AdminChanged(collection, admin));
+
+			Ok(())
+		}
+
+		/// Make `owner` the owner of the collection `collection` of the sender.
+		///
+		/// The new owner reserves the deposits of the collection, which are returned to the sender.
+		fn transfer_ownership(
+			origin,
+			#[compact] collection: T::CollectionId,
+			owner: <T::Lookup as StaticLookup>::Source
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let owner = T::Lookup::lookup(owner)?;
+			let mut details = Self::collection(collection).ok_or("Unknown collection")?;
+			ensure!(details.owner == who, "Not the owner of the collection");
+			if owner == who {
+				return Ok(());
+			}
+
+			T::Currency::reserve(&owner, details.total_deposit)?;
+			T::Currency::unreserve(&who, details.total_deposit);
+			details.owner = owner.clone();
+			<Collections<T>>::insert(collection, details);
+			Self::deposit_event(RawEvent::OwnerChanged(collection, owner));
+
+			Ok(())
+		}
+
+		/// Set the metadata of the collection `collection` to `data`.
+		///
+		/// The sender must be the owner or the admin of the collection.
+		fn set_collection_metadata(origin, #[compact] collection: T::CollectionId, data: Vec<u8>) -> Result {
+			let who = ensure_signed(origin)?;
+			let mut details = Self::collection(collection).ok_or("Unknown collection")?;
+			ensure!(details.owner == who || details.admin == who, "Not the owner or the admin of the collection");
+			ensure!(data.len() <= T::StringLimit::get() as usize, "Data is too long");
+
+			let old_deposit = Self::collection_metadata(collection)
+				.map(|metadata| metadata.deposit)
+				.unwrap_or_else(Zero::zero);
+			let deposit = Self::deposit_for(data.len());
+			Self::update_deposit(&mut details, old_deposit, deposit)?;
+
+			<Collections<T>>::insert(collection, details);
+			<CollectionMetadata<T>>::insert(collection, Metadata { data, deposit });
+			Self::deposit_event(RawEvent::CollectionMetadataSet(collection));
+
+			Ok(())
+		}
+
+		/// Set the metadata of the item `item` of `collection` to `data`.
+		///
+		/// The sender must be the admin of the collection.
+		fn set_metadata(origin, #[compact] collection: T::CollectionId, item: T::ItemId, data: Vec<u8>) -> Result {
+			let who = ensure_signed(origin)?;
+			let (mut details, mut item_details) = Self::admin_item(&who, collection, item)?;
+			ensure!(data.len() <= T::StringLimit::get() as usize, "Data is too long");
+
+			let old_deposit = Self::item_metadata((collection, item))
+				.map(|metadata| metadata.deposit)
+				.unwrap_or_else(Zero::zero);
+			let deposit = Self::deposit_for(data.len());
+			Self::update_deposit(&mut details, old_deposit, deposit)?;
+			item_details.deposit = item_details.deposit - old_deposit + deposit;
+
+			<Collections<T>>::insert(collection, details);
+			<Items<T>>::insert((collection, item), item_details);
+			<ItemMetadata<T>>::insert((collection, item), Metadata { data, deposit });
+			Self::deposit_event(RawEvent::MetadataSet(collection, item));
+
+			Ok(())
+		}
+
+		/// Remove the metadata of the item `item` of `collection`.
+		///
+		/// The sender must be the admin of the collection.
+		fn clear_metadata(origin, #[compact] collection: T::CollectionId, item: T::ItemId) -> Result {
+			let who = ensure_signed(origin)?;
+			let (mut details, mut item_details) = Self::admin_item(&who, collection, item)?;
+			let metadata = Self::item_metadata((collection, item)).ok_or("No metadata")?;
+
+			Self::update_deposit(&mut details, metadata.deposit, Zero::zero())?;
+			item_details.deposit -= metadata.deposit;
+
+			<Collections<T>>::insert(collection, details);
+			<Items<T>>::insert((collection, item), item_details);
+			<ItemMetadata<T>>::remove((collection, item));
+			Self::deposit_event(RawEvent::MetadataCleared(collection, item));
+
+			Ok(())
+		}
+
+		/// Set the attribute `key` of the item `item` of `collection` to `value`.
+		///
+		/// The sender must be the admin of the collection.
+		fn set_attribute(
+			origin,
+			#[compact] collection: T::CollectionId,
+			item: T::ItemId,
+			key: Vec<u8>,
+			value: Vec<u8>
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let (mut details, mut item_details) = Self::admin_item(&who, collection, item)?;
+			let limit = T::StringLimit::get() as usize;
+			ensure!(key.len() <= limit && value.len() <= limit, "Data is too long");
+
+			let old_deposit = Self::attribute(&(collection, item), &key)
+				.map(|attribute| attribute.deposit)
+				.unwrap_or_else(Zero::zero);
+			let deposit = Self::deposit_for(key.len() + value.len());
+			Self::update_deposit(&mut details, old_deposit, deposit)?;
+			item_details.deposit = item_details.deposit - old_deposit + deposit;
+
+			<Collections<T>>::insert(collection, details);
+			<Items<T>>::insert((collection, item), item_details);
+			<Attributes<T>>::insert(&(collection, item), &key, &Metadata { data: value, deposit });
+			Self::deposit_event(RawEvent::AttributeSet(collection, item, key));
+
+			Ok(())
+		}
+
+		/// Remove the attribute `key` of the item `item` of `collection`.
+		///
+		/// The sender must be the admin of the collection.
+		fn clear_attribute(
+			origin,
+			#[compact] collection: T::CollectionId,
+			item: T::ItemId,
+			key: Vec<u8>
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let (mut details, mut item_details) = Self::admin_item(&who, collection, item)?;
+			let attribute = Self::attribute(&(collection, item), &key).ok_or("No attribute")?;
+
+			Self::update_deposit(&mut details, attribute.deposit, Zero::zero())?;
+			item_details.deposit -= attribute.deposit;
+
+			<Collections<T>>::insert(collection, details);
+			<Items<T>>::insert((collection, item), item_details);
+			<Attributes<T>>::remove(&(collection, item), &key);
+			Self::deposit_event(RawEvent::AttributeCleared(collection, item, key));
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// The deposit of a metadata or attribute of `len` bytes.
+	pub fn deposit_for(len: usize) -> BalanceOf<T> {
+		let len = BalanceOf::<T>::from(len as u32);
+		T::MetadataDepositBase::get().saturating_add(T::DepositPerByte::get().saturating_mul(len))
+	}
+
+	/// The collection `collection` and its item `item`, if `who` is the admin of the collection.
+	fn admin_item(who: &T::AccountId, collection: T::CollectionId, item: T::ItemId) -> rstd::result::Result<
+		(CollectionDetails<T::AccountId, BalanceOf<T>>, ItemDetails<T::AccountId, BalanceOf<T>>),
+		&'static str,
+	> {
+		let details = Self::collection(collection).ok_or("Unknown collection")?;
+		ensure!(details.admin == *who, "Not the admin of the collection");
+		let item_details = Self::item((collection, item)).ok_or("Unknown item")?;
+		Ok((details, item_details))
+	}
+
+	/// Replace a deposit of `old` reserved by the owner of the collection `details` by one of
+	/// `new`.
+	fn update_deposit(
+		details: &mut CollectionDetails<T::AccountId, BalanceOf<T>>,
+		old: BalanceOf<T>,
+		new: BalanceOf<T>,
+	) -> Result {
+		if new > old {
+			T::Currency::reserve(&details.owner, new - old)?;
+			details.total_deposit += new - old;
+		} else {
+			T::Currency::unreserve(&details.owner, old - new);
+			details.total_deposit -= old - new;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,174 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use super::*;
+
+use primitives::Blake2Hasher;
+use runtime_io::with_externalities;
+use srml_support::{assert_noop, assert_ok, impl_test_runtime, parameter_types};
+
+impl_test_runtime! {
+	pub struct Test;
+	balances {
+		ExistentialDeposit = 0;
+		TransferFee = 0;
+		CreationFee = 0;
+	}
+}
+parameter_types! {
+	pub const CollectionDeposit: u64 = 10;
+	pub const MetadataDepositBase: u64 = 5;
+	pub const DepositPerByte: u64 = 1;
+	pub const StringLimit: u32 = 8;
+}
+impl Trait for Test {
+	type Event = ();
+	type Currency = balances::Module<Test>;
+	type CollectionId = u32;
+	type ItemId = u32;
+	type CollectionDeposit = CollectionDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type DepositPerByte = DepositPerByte;
+	type StringLimit = StringLimit;
+}
+type Balances = balances::Module<Test>;
+type Uniques = Module<Test>;
+
+fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap().0;
+	t.extend(balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100), (3, 5)],
+		vesting: vec![],
+	}.build_storage().unwrap().0);
+	t.into()
+}
+
+#[test]
+fn collections_are_created_and_destroyed() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Uniques::create(Origin::signed(1), 2));
+		assert_ok!(Uniques::create(Origin::signed(1), 2));
+		assert_eq!(Uniques::next_collection_id(), 2);
+		assert_eq!(Balances::reserved_balance(&1), 20);
+		assert_noop!(Uniques::create(Origin::signed(3), 3), "not enough free funds");
+
+		assert_ok!(Uniques::mint(Origin::signed(2), 0, 42, 3));
+		assert_noop!(Uniques::destroy(Origin::signed(2), 0), "Not the owner of the collection");
+		assert_noop!(Uniques::destroy(Origin::signed(1), 0), "Collection still has items");
+
+		assert_ok!(Uniques::burn(Origin::signed(3), 0, 42));
+		assert_ok!(Uniques::destroy(Origin::signed(1), 0));
+		assert_eq!(Uniques::collection(0), None);
+		assert_eq!(Balances::reserved_balance(&1), 10);
+	});
+}
+
+#[test]
+fn items_are_minted_by_the_admin() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Uniques::create(Origin::signed(1), 2));
+		assert_noop!(Uniques::mint(Origin::signed(1), 0, 42, 3), "Not the admin of the collection");
+		assert_noop!(Uniques::mint(Origin::signed(2), 1, 42, 3), "Unknown collection");
+
+		assert_ok!(Uniques::mint(Origin::signed(2), 0, 42, 3));
+		assert_noop!(Uniques::mint(Origin::signed(2), 0, 42, 1), "Item already exists");
+		assert_eq!(Uniques::item((0, 42)).map(|item| item.owner), Some(3));
+		assert_eq!(Uniques::collection(0).unwrap().items, 1);
+
+		assert_ok!(Uniques::set_admin(Origin::signed(1), 0, 1));
+		assert_ok!(Uniques::mint(Origin::signed(1), 0, 43, 3));
+		assert_noop!(Uniques::burn(Origin::signed(2), 0, 43), "Not the owner of the item or the admin of the collection");
+		assert_ok!(Uniques::burn(Origin::signed(1), 0, 43));
+		assert_eq!(Uniques::item((0, 43)), None);
+	});
+}
+
+#[test]
+fn items_are_transferred_by_their_owner_or_approved_account() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Uniques::create(Origin::signed(1), 1));
+		assert_ok!(Uniques::mint(Origin::signed(1), 0, 42, 2));
+
+		assert_noop!(Uniques::transfer(Origin::signed(1), 0, 42, 3), "Not allowed to transfer the item");
+		assert_ok!(Uniques::transfer(Origin::signed(2), 0, 42, 3));
+		assert_eq!(Uniques::item((0, 42)).map(|item| item.owner), Some(3));
+
+		assert_noop!(Uniques::approve_transfer(Origin::signed(2), 0, 42, 1), "Not the owner of the item");
+		assert_ok!(Uniques::approve_transfer(Origin::signed(3), 0, 42, 1));
+		assert_ok!(Uniques::transfer(Origin::signed(1), 0, 42, 2));
+		assert_eq!(Uniques::item((0, 42)).map(|item| item.owner), Some(2));
+
+		// The approval is used up by the transfer.
+		assert_noop!(Uniques::transfer(Origin::signed(1), 0, 42, 3), "Not allowed to transfer the item");
+
+		assert_ok!(Uniques::approve_transfer(Origin::signed(2), 0, 42, 1));
+		assert_ok!(Uniques::cancel_approval(Origin::signed(2), 0, 42));
+		assert_noop!(Uniques::cancel_approval(Origin::signed(2), 0, 42), "No approved transfer");
+		assert_noop!(Uniques::transfer(Origin::signed(1), 0, 42, 3), "Not allowed to transfer the item");
+	});
+}
+
+#[test]
+fn metadata_and_attributes_reserve_deposits() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Uniques::create(Origin::signed(1), 2));
+		assert_ok!(Uniques::mint(Origin::signed(2), 0, 42, 3));
+
+		assert_noop!(Uniques::set_metadata(Origin::signed(2), 0, 42, vec![0; 9]), "Data is too long");
+		assert_noop!(Uniques::set_metadata(Origin::signed(1), 0, 42, vec![0; 4]), "Not the admin of the collection");
+
+		assert_ok!(Uniques::set_collection_metadata(Origin::signed(1), 0, vec![0; 2]));
+		assert_ok!(Uniques::set_metadata(Origin::signed(2), 0, 42, vec![0; 4]));
+		assert_ok!(Uniques::set_attribute(Origin::signed(2), 0, 42, b"key".to_vec(), b"value".to_vec()));
+		assert_eq!(Balances::reserved_balance(&1), 10 + 7 + 9 + 13);
+		assert_eq!(Uniques::attribute(&(0, 42), &b"key".to_vec()).unwrap().data, b"value".to_vec());
+
+		// Replacing data only reserves the difference of the deposits.
+		assert_ok!(Uniques::set_metadata(Origin::signed(2), 0, 42, vec![0; 1]));
+		assert_eq!(Balances::reserved_balance(&1), 10 + 7 + 6 + 13);
+		assert_eq!(Uniques::item((0, 42)).unwrap().deposit, 6 + 13);
+
+		assert_ok!(Uniques::clear_attribute(Origin::signed(2), 0, 42, b"key".to_vec()));
+		assert_noop!(Uniques::clear_attribute(Origin::signed(2), 0, 42, b"key".to_vec()), "No attribute");
+		assert_eq!(Balances::reserved_balance(&1), 10 + 7 + 6);
+
+		assert_ok!(Uniques::set_attribute(Origin::signed(2), 0, 42, b"key".to_vec(), vec![]));
+		assert_ok!(Uniques::burn(Origin::signed(3), 0, 42));
+		assert_eq!(Uniques::attribute(&(0, 42), &b"key".to_vec()), None);
+		assert_eq!(Uniques::item_metadata((0, 42)), None);
+		assert_eq!(Balances::reserved_balance(&1), 10 + 7);
+		assert_eq!(Uniques::collection(0).unwrap().total_deposit, 10 + 7);
+	});
+}
+
+#[test]
+fn the_new_owner_takes_over_the_deposits() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Uniques::create(Origin::signed(1), 1));
+		assert_ok!(Uniques::set_collection_metadata(Origin::signed(1), 0, vec![0; 4]));
+
+		assert_noop!(Uniques::transfer_ownership(Origin::signed(2), 0, 2), "Not the owner of the collection");
+		assert_noop!(Uniques::transfer_ownership(Origin::signed(1), 0, 3), "not enough free funds");
+
+		assert_ok!(Uniques::transfer_ownership(Origin::signed(1), 0, 2));
+		assert_eq!(Balances::reserved_balance(&1), 0);
+		assert_eq!(Balances::reserved_balance(&2), 19);
+		assert_eq!(Uniques::collection(0).unwrap().owner, 2);
+		assert_eq!(Uniques::collection(0).unwrap().admin, 1);
+	});
+}
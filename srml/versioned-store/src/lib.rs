@@ -0,0 +1,574 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Versioned Store Module
+//!
+//! The Versioned Store module stores structured data as entities of classes.
+//!
+//! - [`versioned_store::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! A class has typed properties, grouped in schemas. Schemas are only ever added, each one a new
+//! version of the class, and can reuse the properties of the previous ones.
+//!
+//! An entity is an instance of a class, owned by its creator. It supports some schemas of its
+//! class, and has values for their properties: required properties must have a value. A value
+//! can reference another entity, of the class given by the type of the property.
+//!
+//! Root creates the classes. Root and the admins of a class add its schemas and set its
+//! permissions: who creates entities, and who updates them. When anyone can create entities of a
+//! class, `EntityDeposit` is reserved from the creator of each entity.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `create_class` - Create a class.
+//! - `set_class_permissions` - Set the permissions of a class.
+//! - `add_class_schema` - Add a schema to a class.
+//! - `create_entity` - Create an entity of a class.
+//! - `add_schema_support_to_entity` - Make an entity support a schema of its class.
+//! - `update_entity_property_values` - Update the values of an entity.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod tests;
+
+use rstd::prelude::*;
+use rstd::marker::PhantomData;
+use codec::{Encode, Decode};
+use srml_support::{StorageValue, StorageMap, decl_module, decl_storage, decl_event, ensure};
+use srml_support::dispatch::Result;
+use srml_support::traits::{Currency, ReservableCurrency, Get};
+use sr_primitives::traits::SaturatedConversion;
+use sr_primitives::weights::{Weight, WeighData, ClassifyDispatch, DispatchClass, SimpleDispatchInfo};
+use system::{ensure_signed, ensure_root};
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+
+/// The identifier of a class.
+pub type ClassId = u64;
+
+/// The identifier of an entity.
+pub type EntityId = u64;
+
+/// The index of a schema in its class.
+pub type SchemaId = u16;
+
+/// The index of a property in its class.
+pub type PropertyId = u16;
+
+/// The weight of the calls besides the bytes they read or write and the values they check.
+const BASE_WEIGHT: Weight = 100_000;
+/// The weight of reading or writing a byte of a class, entity or value.
+const BYTE_WEIGHT: Weight = 100;
+/// The weight of checking a value against the type of its property, which may read the entity
+/// it references.
+const VALUE_WEIGHT: Weight = 50_000;
+/// The weight of comparing the identifiers of two properties.
+const COMPARISON_WEIGHT: Weight = 100;
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The currency the entity deposits are reserved in.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The deposit of an entity of a class anyone can create entities of.
+	type EntityDeposit: Get<BalanceOf<Self>>;
+
+	/// The maximum length of the name or description of a class or property.
+	type MaxNameLength: Get<u32>;
+}
+
+/// The type of a property.
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum PropertyType {
+	Bool,
+	Uint64,
+	Int64,
+	/// A text of at most the given length.
+	Text(u16),
+	/// A reference to an entity of the given class.
+	Reference(ClassId),
+}
+
+/// The value of a property.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum PropertyValue {
+	Bool(bool),
+	Uint64(u64),
+	Int64(i64),
+	Text(Vec<u8>),
+	Reference(EntityId),
+}
+
+/// A property of a class.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Property {
+	/// The type of the values of the property.
+	pub prop_type: PropertyType,
+	/// Whether the entities supporting a schema of the property must have a value for it.
+	pub required: bool,
+	/// The name of the property.
+	pub name: Vec<u8>,
+	/// The description of the property.
+	pub description: Vec<u8>,
+}
+
+/// Who can do what with the entities of a class.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct ClassPermissions<AccountId> {
+	/// The accounts that can add schemas and set permissions, besides root.
+	pub admins: Vec<AccountId>,
+	/// The accounts that can create entities, or anyone if `None`.
+	pub entity_creators: Option<Vec<AccountId>>,
+	/// The accounts that can update all the entities.
+	pub entity_maintainers: Vec<AccountId>,
+	/// Whether the owners of entities can update them.
+	pub owners_can_update: bool,
+}
+
+impl<AccountId> Default for ClassPermissions<AccountId> {
+	fn default() -> Self {
+		ClassPermissions {
+			admins: Vec::new(),
+			entity_creators: Some(Vec::new()),
+			entity_maintainers: Vec::new(),
+			owners_can_update: true,
+		}
+	}
+}
+
+/// A class of entities.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Class<AccountId> {
+	/// The name of the class.
+	pub name: Vec<u8>,
+	/// The description of the class.
+	pub description: Vec<u8>,
+	/// The properties of all the schemas.
+	pub properties: Vec<Property>,
+	/// The properties of each schema, from the first version.
+	pub schemas: Vec<Vec<PropertyId>>,
+	/// The permissions of the class.
+	pub permissions: ClassPermissions<AccountId>,
+}
+
+/// An entity of a class.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Entity<AccountId> {
+	/// The class of the entity.
+	pub class_id: ClassId,
+	/// The account that created the entity.
+	pub owner: AccountId,
+	/// The schemas of the class supported by the entity.
+	pub supported_schemas: Vec<SchemaId>,
+	/// The values of the properties of the entity, sorted by property.
+	pub values: Vec<(PropertyId, PropertyValue)>,
+}
+
+impl<AccountId> Entity<AccountId> {
+	/// The value of the property `id`.
+	pub fn value(&self, id: PropertyId) -> Option<&PropertyValue> {
+		self.values.binary_search_by_key(&id, |(id, _)| *id).ok().map(|index| &self.values[index].1)
+	}
+
+	fn set_value(&mut self, id: PropertyId, value: PropertyValue) {
+		match self.values.binary_search_by_key(&id, |(id, _)| *id) {
+			Ok(index) => self.values[index].1 = value,
+			Err(index) => self.values.insert(index, (id, value)),
+		}
+	}
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as VersionedStore {
+		/// The identifier of the next class.
+		pub NextClassId get(next_class_id): ClassId;
+		/// The classes.
+		pub Classes get(class): map ClassId => Option<Class<T::AccountId>>;
+
+		/// The identifier of the next entity.
+		pub NextEntityId get(next_entity_id): EntityId;
+		/// The entities.
+		pub Entities get(entity): map EntityId => Option<Entity<T::AccountId>>;
+		/// The deposits reserved from the owners of entities.
+		pub EntityDeposits get(entity_deposit): map EntityId => Option<BalanceOf<T>>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+	{
+		/// A class was created.
+		ClassCreated(ClassId),
+		/// The permissions of a class were set.
+		ClassPermissionsSet(ClassId),
+		/// A schema was added to a class.
+		SchemaAdded(ClassId, SchemaId),
+		/// An entity of a class was created by the account.
+		EntityCreated(ClassId, EntityId, AccountId),
+		/// An entity supports a new schema.
+		SchemaSupportAdded(EntityId, SchemaId),
+		/// The values of an entity were updated.
+		EntityUpdated(EntityId),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		/// The deposit of an entity of a class anyone can create entities of.
+		const EntityDeposit: BalanceOf<T> = T::EntityDeposit::get();
+
+		/// The maximum length of the name or description of a class or property.
+		const MaxNameLength: u32 = T::MaxNameLength::get();
+
+		fn deposit_event() = default;
+
+		/// Create a class without schemas, with the default permissions: only root can create
+		/// entities, which their owners update.
+		///
+		/// The origin must be root.
+		#[weight = SimpleDispatchInfo::FixedOperational(BASE_WEIGHT)]
+		fn create_class(origin, name: Vec<u8>, description: Vec<u8>) -> Result {
+			ensure_root(origin)?;
+			Self::ensure_name_length(&name)?;
+			Self::ensure_name_length(&description)?;
+
+			let id = Self::next_class_id();
+			NextClassId::put(id + 1);
+			<Classes<T>>::insert(id, Class {
+				name,
+				description,
+				properties: Vec::new(),
+				schemas: Vec::new(),
+				permissions: ClassPermissions::default(),
+			});
+			Self::deposit_event(RawEvent::ClassCreated(id));
+
+			Ok(())
+		}
+
+		/// Set the permissions of the class `class_id`.
+		///
+		/// The origin must be root or an admin of the class.
+		#[weight = StoreWeight::<T>::default()]
+		fn set_class_permissions(
+			origin,
+			#[compact] class_id: ClassId,
+			permissions: ClassPermissions<T::AccountId>
+		) -> Result {
+			let mut class = Self::class(class_id).ok_or("Unknown class")?;
+			Self::ensure_class_admin(origin, &class)?;
+
+			class.permissions = permissions;
+			<Classes<T>>::insert(class_id, class);
+			Self::deposit_event(RawEvent::ClassPermissionsSet(class_id));
+
+			Ok(())
+		}
+
+		/// Add a schema to the class `class_id`, with the properties `existing_properties` of the
+		/// class and the new properties `new_properties`.
+		///
+		/// The origin must be root or an admin of the class.
+		#[weight = StoreWeight::<T>::default()]
+		fn add_class_schema(
+			origin,
+			#[compact] class_id: ClassId,
+			existing_properties: Vec<PropertyId>,
+			new_properties: Vec<Property>
+		) -> Result {
+			let mut class = Self::class(class_id).ok_or("Unknown class")?;
+			Self::ensure_class_admin(origin, &class)?;
+			ensure!(!existing_properties.is_empty() || !new_properties.is_empty(), "Schema has no properties");
+			ensure!(class.schemas.len() < SchemaId::max_value() as usize, "Too many schemas");
+			ensure!(
+				class.properties.len() + new_properties.len() <= PropertyId::max_value() as usize,
+				"Too many properties"
+			);
+			ensure!(
+				existing_properties.iter().all(|id| (*id as usize) < class.properties.len()),
+				"Unknown property"
+			);
+			for property in &new_properties {
+				Self::ensure_name_length(&property.name)?;
+				Self::ensure_name_length(&property.description)?;
+				if let PropertyType::Reference(class_id) = property.prop_type {
+					ensure!(<Classes<T>>::exists(class_id), "Unknown referenced class");
+				}
+			}
+
+			let mut schema = existing_properties;
+			schema.extend((class.properties.len()..class.properties.len() + new_properties.len()).map(|id| id as PropertyId));
+			schema.sort();
+			schema.dedup();
+			class.properties.extend(new_properties);
+			let schema_id = class.schemas.len() as SchemaId;
+			class.schemas.push(schema);
+			<Classes<T>>::insert(class_id, class);
+			Self::deposit_event(RawEvent::SchemaAdded(class_id, schema_id));
+
+			Ok(())
+		}
+
+		/// Create an entity of the class `class_id`, owned by the sender, without schemas.
+		///
+		/// The sender must be an entity creator of the class, or reserve `EntityDeposit` if anyone
+		/// can create entities of the class.
+		#[weight = StoreWeight::<T>::default()]
+		fn create_entity(origin, #[compact] class_id: ClassId) -> Result {
+			let who = ensure_signed(origin)?;
+			let class = Self::class(class_id).ok_or("Unknown class")?;
+			let deposit = match class.permissions.entity_creators.as_ref() {
+				Some(creators) => {
+					ensure!(creators.contains(&who), "Not an entity creator of the class");
+					None
+				}
+				None => Some(T::EntityDeposit::get()),
+			};
+			if let Some(deposit) = deposit {
+				T::Currency::reserve(&who, deposit)?;
+			}
+
+			let id = Self::next_entity_id();
+			NextEntityId::put(id + 1);
+			if let Some(deposit) = deposit {
+				<EntityDeposits<T>>::insert(id, deposit);
+			}
+			<Entities<T>>::insert(id, Entity {
+				class_id,
+				owner: who.clone(),
+				supported_schemas: Vec::new(),
+				values: Vec::new(),
+			});
+			Self::deposit_event(RawEvent::EntityCreated(class_id, id, who));
+
+			Ok(())
+		}
+
+		/// Make the entity `entity_id` support the schema `schema_id` of its class, setting the
+		/// values `values` of properties of the schema. The required properties of the schema
+		/// must all have a value.
+		///
+		/// The sender must be able to update the entity.
+		#[weight = StoreWeight::<T>::default()]
+		fn add_schema_support_to_entity(
+			origin,
+			#[compact] entity_id: EntityId,
+			schema_id: SchemaId,
+			values: Vec<(PropertyId, PropertyValue)>
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let mut entity = Self::entity(entity_id).ok_or("Unknown entity")?;
+			let class = Self::class(entity.class_id).ok_or("Unknown class")?;
+			Self::ensure_can_update(&who, &class, &entity)?;
+			let schema = class.schemas.get(schema_id as usize).ok_or("Unknown schema")?;
+			ensure!(!entity.supported_schemas.contains(&schema_id), "Schema is already supported");
+
+			for (id, value) in values {
+				ensure!(schema.contains(&id), "Property is not in the schema");
+				Self::ensure_valid_value(&class.properties[id as usize], &value)?;
+				entity.set_value(id, value);
+			}
+			ensure!(
+				schema.iter().all(|id| !class.properties[*id as usize].required || entity.value(*id).is_some()),
+				"Missing required property"
+			);
+
+			entity.supported_schemas.push(schema_id);
+			<Entities<T>>::insert(entity_id, entity);
+			Self::deposit_event(RawEvent::SchemaSupportAdded(entity_id, schema_id));
+
+			Ok(())
+		}
+
+		/// Update the values `values` of the entity `entity_id`, which must be of properties of
+		/// the schemas it supports.
+		///
+		/// The sender must be able to update the entity.
+		#[weight = StoreWeight::<T>::default()]
+		fn update_entity_property_values(
+			origin,
+			#[compact] entity_id: EntityId,
+			values: Vec<(PropertyId, PropertyValue)>
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let mut entity = Self::entity(entity_id).ok_or("Unknown entity")?;
+			let class = Self::class(entity.class_id).ok_or("Unknown class")?;
+			Self::ensure_can_update(&who, &class, &entity)?;
+
+			for (id, value) in values {
+				ensure!(
+					entity.supported_schemas.iter().any(|schema| class.schemas[*schema as usize].contains(&id)),
+					"Property is not in a supported schema"
+				);
+				Self::ensure_valid_value(&class.properties[id as usize], &value)?;
+				entity.set_value(id, value);
+			}
+
+			<Entities<T>>::insert(entity_id, entity);
+			Self::deposit_event(RawEvent::EntityUpdated(entity_id));
+
+			Ok(())
+		}
+	}
+}
+
+/// Weight of the calls on classes and entities: a step per byte of the classes, entities and
+/// values they read or write, and per value they check.
+pub struct StoreWeight<T>(PhantomData<T>);
+
+impl<T> Default for StoreWeight<T> {
+	fn default() -> Self {
+		StoreWeight(PhantomData)
+	}
+}
+
+impl<T: Trait> StoreWeight<T> {
+	/// The weight of reading or writing `bytes` bytes.
+	fn bytes(bytes: usize) -> Weight {
+		let bytes: Weight = bytes.saturated_into();
+		BYTE_WEIGHT.saturating_mul(bytes)
+	}
+
+	/// The weight of reading and writing the class `class_id`.
+	fn class(class_id: ClassId) -> Weight {
+		Self::bytes(Module::<T>::class(class_id).map_or(0, |class| class.using_encoded(|encoded| encoded.len())))
+	}
+
+	/// The weight of setting `values` of the entity `entity_id`: reading and writing the entity
+	/// and reading its class, and checking each value against the schemas of the class.
+	fn values(entity_id: EntityId, values: &[(PropertyId, PropertyValue)]) -> Weight {
+		let entity = match Module::<T>::entity(entity_id) {
+			Some(entity) => entity,
+			None => return BASE_WEIGHT,
+		};
+		let schema_properties: Weight = Module::<T>::class(entity.class_id)
+			.map_or(0, |class| class.schemas.iter().map(Vec::len).sum::<usize>())
+			.saturated_into();
+		let values_count: Weight = values.len().saturated_into();
+		let value_weight = VALUE_WEIGHT.saturating_add(COMPARISON_WEIGHT.saturating_mul(schema_properties));
+
+		BASE_WEIGHT
+			.saturating_add(Self::bytes(entity.using_encoded(|encoded| encoded.len())))
+			.saturating_add(Self::class(entity.class_id))
+			.saturating_add(Self::bytes(values.using_encoded(|encoded| encoded.len())))
+			.saturating_add(value_weight.saturating_mul(values_count))
+	}
+}
+
+impl<T: Trait> WeighData<(&ClassId, &ClassPermissions<T::AccountId>)> for StoreWeight<T> {
+	fn weigh_data(&self, (class_id, permissions): (&ClassId, &ClassPermissions<T::AccountId>)) -> Weight {
+		BASE_WEIGHT
+			.saturating_add(Self::class(*class_id))
+			.saturating_add(Self::bytes(permissions.using_encoded(|encoded| encoded.len())))
+	}
+}
+
+impl<T: Trait> WeighData<(&ClassId, &Vec<PropertyId>, &Vec<Property>)> for StoreWeight<T> {
+	fn weigh_data(
+		&self,
+		(class_id, existing_properties, new_properties): (&ClassId, &Vec<PropertyId>, &Vec<Property>),
+	) -> Weight {
+		BASE_WEIGHT
+			.saturating_add(Self::class(*class_id))
+			.saturating_add(Self::bytes(existing_properties.using_encoded(|encoded| encoded.len())))
+			.saturating_add(Self::bytes(new_properties.using_encoded(|encoded| encoded.len())))
+	}
+}
+
+impl<T: Trait> WeighData<(&ClassId,)> for StoreWeight<T> {
+	fn weigh_data(&self, (class_id,): (&ClassId,)) -> Weight {
+		BASE_WEIGHT.saturating_add(Self::class(*class_id))
+	}
+}
+
+impl<T: Trait> WeighData<(&EntityId, &SchemaId, &Vec<(PropertyId, PropertyValue)>)> for StoreWeight<T> {
+	fn weigh_data(
+		&self,
+		(entity_id, _, values): (&EntityId, &SchemaId, &Vec<(PropertyId, PropertyValue)>),
+	) -> Weight {
+		Self::values(*entity_id, values)
+	}
+}
+
+impl<T: Trait> WeighData<(&EntityId, &Vec<(PropertyId, PropertyValue)>)> for StoreWeight<T> {
+	fn weigh_data(&self, (entity_id, values): (&EntityId, &Vec<(PropertyId, PropertyValue)>)) -> Weight {
+		Self::values(*entity_id, values)
+	}
+}
+
+impl<T, A> ClassifyDispatch<A> for StoreWeight<T> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+impl<T: Trait> Module<T> {
+	fn ensure_name_length(name: &[u8]) -> Result {
+		ensure!(name.len() <= T::MaxNameLength::get() as usize, "Name is too long");
+		Ok(())
+	}
+
+	fn ensure_class_admin(origin: T::Origin, class: &Class<T::AccountId>) -> Result {
+		let origin: rstd::result::Result<system::RawOrigin<T::AccountId>, T::Origin> = origin.into();
+		match origin {
+			Ok(system::RawOrigin::Root) => Ok(()),
+			Ok(system::RawOrigin::Signed(ref who)) if class.permissions.admins.contains(who) => Ok(()),
+			_ => Err("Not an admin of the class"),
+		}
+	}
+
+	fn ensure_can_update(who: &T::AccountId, class: &Class<T::AccountId>, entity: &Entity<T::AccountId>) -> Result {
+		let permissions = &class.permissions;
+		ensure!(
+			permissions.entity_maintainers.contains(who) || (permissions.owners_can_update && entity.owner == *who),
+			"Can't update the entity"
+		);
+		Ok(())
+	}
+
+	fn ensure_valid_value(property: &Property, value: &PropertyValue) -> Result {
+		match (property.prop_type, value) {
+			(PropertyType::Bool, PropertyValue::Bool(_)) |
+			(PropertyType::Uint64, PropertyValue::Uint64(_)) |
+			(PropertyType::Int64, PropertyValue::Int64(_)) => Ok(()),
+			(PropertyType::Text(max_length), PropertyValue::Text(text)) => {
+				ensure!(text.len() <= max_length as usize, "Text is too long");
+				Ok(())
+			}
+			(PropertyType::Reference(class_id), PropertyValue::Reference(entity_id)) => {
+				let entity = Self::entity(entity_id).ok_or("Unknown referenced entity")?;
+				ensure!(entity.class_id == class_id, "Referenced entity is of another class");
+				Ok(())
+			}
+			_ => Err("Value doesn't match the property type"),
+		}
+	}
+}
@@ -0,0 +1,241 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use super::*;
+
+use primitives::Blake2Hasher;
+use runtime_io::with_externalities;
+use sr_primitives::weights::GetDispatchInfo;
+use srml_support::{assert_noop, assert_ok, impl_test_runtime, parameter_types};
+
+impl_test_runtime! {
+	pub struct Test;
+	balances {
+		ExistentialDeposit = 0;
+		TransferFee = 0;
+		CreationFee = 0;
+	}
+}
+
+parameter_types! {
+	pub const EntityDeposit: u64 = 10;
+	pub const MaxNameLength: u32 = 16;
+}
+impl Trait for Test {
+	type Event = ();
+	type Currency = balances::Module<Test>;
+	type EntityDeposit = EntityDeposit;
+	type MaxNameLength = MaxNameLength;
+}
+type Balances = balances::Module<Test>;
+type Store = Module<Test>;
+
+fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap().0;
+	t.extend(balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 5)],
+		vesting: vec![],
+	}.build_storage().unwrap().0);
+	t.into()
+}
+
+fn property(prop_type: PropertyType, required: bool) -> Property {
+	Property { prop_type, required, name: b"name".to_vec(), description: Vec::new() }
+}
+
+fn open_permissions() -> ClassPermissions<u64> {
+	ClassPermissions { entity_creators: None, ..Default::default() }
+}
+
+#[test]
+fn classes_are_managed_by_root_and_admins() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_noop!(
+			Store::create_class(Origin::signed(1), b"Video".to_vec(), Vec::new()),
+			"bad origin: expected to be a root origin"
+		);
+		assert_noop!(
+			Store::create_class(Origin::ROOT, b"A name that is too long".to_vec(), Vec::new()),
+			"Name is too long"
+		);
+		assert_ok!(Store::create_class(Origin::ROOT, b"Video".to_vec(), Vec::new()));
+
+		let schema = vec![property(PropertyType::Text(8), true)];
+		assert_noop!(Store::add_class_schema(Origin::signed(1), 0, Vec::new(), schema.clone()), "Not an admin of the class");
+		assert_noop!(Store::add_class_schema(Origin::ROOT, 0, Vec::new(), Vec::new()), "Schema has no properties");
+		assert_noop!(Store::add_class_schema(Origin::ROOT, 0, vec![0], Vec::new()), "Unknown property");
+		assert_noop!(
+			Store::add_class_schema(Origin::ROOT, 0, Vec::new(), vec![property(PropertyType::Reference(1), false)]),
+			"Unknown referenced class"
+		);
+		assert_ok!(Store::add_class_schema(Origin::ROOT, 0, Vec::new(), schema));
+
+		let permissions = ClassPermissions { admins: vec![1], ..Default::default() };
+		assert_noop!(Store::set_class_permissions(Origin::signed(1), 0, permissions.clone()), "Not an admin of the class");
+		assert_ok!(Store::set_class_permissions(Origin::ROOT, 0, permissions));
+
+		// The second version reuses the property of the first one.
+		assert_ok!(Store::add_class_schema(Origin::signed(1), 0, vec![0], vec![property(PropertyType::Bool, false)]));
+		let class = Store::class(0).unwrap();
+		assert_eq!(class.properties.len(), 2);
+		assert_eq!(class.schemas, vec![vec![0], vec![0, 1]]);
+	});
+}
+
+#[test]
+fn entities_have_values_for_the_required_properties() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Store::create_class(Origin::ROOT, b"Video".to_vec(), Vec::new()));
+		assert_ok!(Store::set_class_permissions(Origin::ROOT, 0, open_permissions()));
+		assert_ok!(Store::add_class_schema(
+			Origin::ROOT,
+			0,
+			Vec::new(),
+			vec![property(PropertyType::Text(8), true), property(PropertyType::Uint64, false)]
+		));
+		assert_ok!(Store::create_entity(Origin::signed(1), 0));
+		assert_eq!(Store::entity(0).unwrap().owner, 1);
+
+		assert_noop!(Store::add_schema_support_to_entity(Origin::signed(1), 0, 1, Vec::new()), "Unknown schema");
+		assert_noop!(
+			Store::add_schema_support_to_entity(Origin::signed(1), 0, 0, vec![(1, PropertyValue::Uint64(60))]),
+			"Missing required property"
+		);
+		assert_noop!(
+			Store::add_schema_support_to_entity(Origin::signed(1), 0, 0, vec![(0, PropertyValue::Text(b"Too long text".to_vec()))]),
+			"Text is too long"
+		);
+		assert_noop!(
+			Store::add_schema_support_to_entity(Origin::signed(1), 0, 0, vec![(0, PropertyValue::Bool(true))]),
+			"Value doesn't match the property type"
+		);
+		assert_noop!(
+			Store::update_entity_property_values(Origin::signed(1), 0, vec![(0, PropertyValue::Text(b"Intro".to_vec()))]),
+			"Property is not in a supported schema"
+		);
+
+		assert_ok!(Store::add_schema_support_to_entity(Origin::signed(1), 0, 0, vec![
+			(1, PropertyValue::Uint64(60)),
+			(0, PropertyValue::Text(b"Intro".to_vec())),
+		]));
+		assert_noop!(
+			Store::add_schema_support_to_entity(Origin::signed(1), 0, 0, vec![(0, PropertyValue::Text(b"Intro".to_vec()))]),
+			"Schema is already supported"
+		);
+		assert_ok!(Store::update_entity_property_values(Origin::signed(1), 0, vec![(1, PropertyValue::Uint64(90))]));
+
+		let entity = Store::entity(0).unwrap();
+		assert_eq!(entity.supported_schemas, vec![0]);
+		assert_eq!(entity.values, vec![
+			(0, PropertyValue::Text(b"Intro".to_vec())),
+			(1, PropertyValue::Uint64(90)),
+		]);
+	});
+}
+
+#[test]
+fn references_point_to_entities_of_the_class() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Store::create_class(Origin::ROOT, b"Channel".to_vec(), Vec::new()));
+		assert_ok!(Store::create_class(Origin::ROOT, b"Video".to_vec(), Vec::new()));
+		for class_id in 0..2 {
+			assert_ok!(Store::set_class_permissions(Origin::ROOT, class_id, open_permissions()));
+		}
+		assert_ok!(Store::add_class_schema(Origin::ROOT, 0, Vec::new(), vec![property(PropertyType::Bool, false)]));
+		assert_ok!(Store::add_class_schema(Origin::ROOT, 1, Vec::new(), vec![property(PropertyType::Reference(0), true)]));
+		assert_ok!(Store::create_entity(Origin::signed(1), 0));
+		assert_ok!(Store::create_entity(Origin::signed(1), 1));
+
+		assert_noop!(
+			Store::add_schema_support_to_entity(Origin::signed(1), 1, 0, vec![(0, PropertyValue::Reference(2))]),
+			"Unknown referenced entity"
+		);
+		assert_noop!(
+			Store::add_schema_support_to_entity(Origin::signed(1), 1, 0, vec![(0, PropertyValue::Reference(1))]),
+			"Referenced entity is of another class"
+		);
+		assert_ok!(Store::add_schema_support_to_entity(Origin::signed(1), 1, 0, vec![(0, PropertyValue::Reference(0))]));
+		assert_eq!(Store::entity(1).unwrap().value(0), Some(&PropertyValue::Reference(0)));
+	});
+}
+
+#[test]
+fn permissions_restrict_creating_and_updating_entities() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Store::create_class(Origin::ROOT, b"Video".to_vec(), Vec::new()));
+		assert_ok!(Store::add_class_schema(Origin::ROOT, 0, Vec::new(), vec![property(PropertyType::Int64, false)]));
+		assert_noop!(Store::create_entity(Origin::signed(1), 0), "Not an entity creator of the class");
+
+		assert_ok!(Store::set_class_permissions(Origin::ROOT, 0, ClassPermissions {
+			entity_creators: Some(vec![1]),
+			entity_maintainers: vec![2],
+			owners_can_update: false,
+			..Default::default()
+		}));
+		assert_noop!(Store::create_entity(Origin::signed(2), 0), "Not an entity creator of the class");
+		assert_ok!(Store::create_entity(Origin::signed(1), 0));
+
+		assert_noop!(Store::add_schema_support_to_entity(Origin::signed(1), 0, 0, Vec::new()), "Can't update the entity");
+		assert_noop!(Store::add_schema_support_to_entity(Origin::signed(3), 0, 0, Vec::new()), "Can't update the entity");
+		assert_ok!(Store::add_schema_support_to_entity(Origin::signed(2), 0, 0, vec![(0, PropertyValue::Int64(-1))]));
+		assert_eq!(Store::entity(0).unwrap().value(0), Some(&PropertyValue::Int64(-1)));
+	});
+}
+
+#[test]
+fn values_are_weighed_by_their_size_and_the_schemas_of_the_class() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Store::create_class(Origin::ROOT, b"Video".to_vec(), Vec::new()));
+		assert_ok!(Store::set_class_permissions(Origin::ROOT, 0, open_permissions()));
+		assert_ok!(Store::add_class_schema(Origin::ROOT, 0, Vec::new(), vec![property(PropertyType::Text(8), false)]));
+		assert_ok!(Store::create_entity(Origin::signed(1), 0));
+		let update = |values| Call::<Test>::update_entity_property_values(0, values).get_dispatch_info().weight;
+		let text = |text: &[u8]| (0, PropertyValue::Text(text.to_vec()));
+
+		assert!(update(vec![]) < update(vec![text(b"a")]));
+		assert!(update(vec![text(b"a")]) < update(vec![text(b"a longer")]));
+		assert!(update(vec![text(b"a")]) < update(vec![text(b"a"), text(b"b")]));
+
+		let one_schema = update(vec![text(b"a")]);
+		assert_ok!(Store::add_class_schema(Origin::ROOT, 0, vec![0], Vec::new()));
+		assert!(one_schema < update(vec![text(b"a")]));
+	});
+}
+
+#[test]
+fn entities_of_open_classes_reserve_a_deposit() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Store::create_class(Origin::ROOT, b"Video".to_vec(), Vec::new()));
+		assert_ok!(Store::create_class(Origin::ROOT, b"Channel".to_vec(), Vec::new()));
+		assert_ok!(Store::set_class_permissions(Origin::ROOT, 0, open_permissions()));
+		assert_ok!(Store::set_class_permissions(Origin::ROOT, 1, ClassPermissions {
+			entity_creators: Some(vec![2]),
+			..Default::default()
+		}));
+
+		assert_noop!(Store::create_entity(Origin::signed(2), 0), "not enough free funds");
+		assert_ok!(Store::create_entity(Origin::signed(1), 0));
+		assert_eq!(Balances::reserved_balance(&1), 10);
+		assert_eq!(Store::entity_deposit(0), Some(10));
+
+		// Entity creators of a class don't reserve a deposit.
+		assert_ok!(Store::create_entity(Origin::signed(2), 1));
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		assert_eq!(Store::entity_deposit(1), None);
+	});
+}
@@ -0,0 +1,639 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Working Group Module
+//!
+//! The Working Group module hires and manages the workers of a group, led by a lead.
+//!
+//! - [`working_group::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! The module is instantiable, so that a runtime can have several working groups.
+//!
+//! Root sets the lead of the group and its mint, which the rewards of the workers are paid from,
+//! e.g. a mint of the minting module. The rewards are paid by `Rewards`, e.g. the recurring reward
+//! module.
+//!
+//! The lead hires workers through openings:
+//!
+//! - the lead adds an opening, with the stake required from the applicants and the reward of the
+//!   workers;
+//! - members apply on the opening, reserving at least the required stake, and can withdraw their
+//!   application until the opening is filled;
+//! - the lead begins the review of the applications, which closes the opening to new ones;
+//! - the lead fills the opening: the successful applicants become workers, keeping their stake
+//!   reserved as role stake, and the other applicants get their stake back.
+//!
+//! Workers hired with a reward get a reward relationship out of the mint of the group, paying them
+//! at every interval as long as the mint has enough capacity, until they leave. Changing the mint
+//! of the group doesn't affect the rewards of the current workers. The lead can slash the stake of workers and fire them. Workers can leave their role, getting their
+//! stake back.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `set_lead` - Set or unset the lead.
+//! - `set_mint` - Set or unset the mint.
+//! - `add_opening` - Add an opening as the lead.
+//! - `apply_on_opening` - Apply on an opening as a member.
+//! - `withdraw_application` - Withdraw an application.
+//! - `begin_review` - Begin the review of the applications as the lead.
+//! - `fill_opening` - Hire the successful applicants as the lead.
+//! - `slash_stake` - Slash the stake of a worker as the lead.
+//! - `fire_worker` - Fire a worker as the lead.
+//! - `leave_role` - Leave the role of a worker.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod tests;
+
+use rstd::prelude::*;
+use rstd::marker::PhantomData;
+use codec::{Encode, Decode};
+use srml_support::{StorageValue, StorageMap, Parameter, decl_module, decl_storage, decl_event, ensure};
+use srml_support::dispatch::Result;
+use srml_support::traits::{Currency, MemberAccounts, OnUnbalanced, RecurringRewards, ReservableCurrency};
+use sr_primitives::traits::{Zero, SaturatedConversion};
+use sr_primitives::weights::{Weight, WeighData, ClassifyDispatch, DispatchClass, SimpleDispatchInfo};
+use system::{ensure_signed, ensure_root};
+
+type BalanceOf<T, I> = <<T as Trait<I>>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T, I> =
+	<<T as Trait<I>>::Currency as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
+
+/// The weight of the calls besides the bytes and applications they read or write.
+const BASE_WEIGHT: Weight = 100_000;
+/// The weight of reading or writing a byte of an opening.
+const BYTE_WEIGHT: Weight = 100;
+/// The weight of settling an application, returning its stake or hiring its applicant.
+const APPLICATION_WEIGHT: Weight = 100_000;
+/// The weight of comparing the identifiers of two applications.
+const COMPARISON_WEIGHT: Weight = 100;
+
+/// The identifier of an opening.
+pub type OpeningId = u32;
+
+/// The identifier of an application.
+pub type ApplicationId = u32;
+
+/// The identifier of a worker.
+pub type WorkerId = u32;
+
+pub trait Trait<I=DefaultInstance>: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self, I>> + Into<<Self as system::Trait>::Event>;
+
+	/// The currency the stakes are reserved, and the rewards paid, in.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The identifier of a member.
+	type MemberId: Parameter;
+
+	/// The members that can lead, apply and work.
+	type Members: MemberAccounts<Self::AccountId, MemberId = Self::MemberId>;
+
+	/// The identifier of a mint.
+	type MintId: Parameter;
+
+	/// The identifier of a reward relationship.
+	type RelationshipId: Parameter;
+
+	/// The recurring rewards the workers are paid by.
+	type Rewards: RecurringRewards<
+		Self::AccountId,
+		BalanceOf<Self, I>,
+		Self::BlockNumber,
+		MintId = Self::MintId,
+		RelationshipId = Self::RelationshipId,
+	>;
+
+	/// Handler for the slashed stakes.
+	type Slashed: OnUnbalanced<NegativeImbalanceOf<Self, I>>;
+}
+
+/// The lead of a working group.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Lead<AccountId, MemberId> {
+	/// The member leading the group.
+	pub member_id: MemberId,
+	/// The account acting as the lead.
+	pub role_account: AccountId,
+}
+
+/// A reward paid every `interval` blocks.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct RewardPolicy<Balance, BlockNumber> {
+	/// The amount paid.
+	pub amount: Balance,
+	/// The number of blocks between payments.
+	pub interval: BlockNumber,
+}
+
+/// The stage of an opening.
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum OpeningStage {
+	/// New applications are accepted.
+	AcceptingApplications,
+	/// The lead reviews the applications.
+	InReview,
+}
+
+/// An opening for workers.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Opening<Balance, BlockNumber> {
+	/// The description of the opening.
+	pub description: Vec<u8>,
+	/// The minimum stake of the applications.
+	pub application_stake: Balance,
+	/// The reward of the workers hired from the opening.
+	pub reward: Option<RewardPolicy<Balance, BlockNumber>>,
+	/// The stage of the opening.
+	pub stage: OpeningStage,
+	/// The applications to the opening.
+	pub applications: Vec<ApplicationId>,
+}
+
+/// An application to an opening.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Application<AccountId, Balance, MemberId> {
+	/// The opening applied on.
+	pub opening_id: OpeningId,
+	/// The applicant.
+	pub member_id: MemberId,
+	/// The account acting as the worker, which the stake is reserved from.
+	pub role_account: AccountId,
+	/// The stake of the application.
+	pub stake: Balance,
+}
+
+/// A worker of the group.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Worker<AccountId, Balance, MemberId, RelationshipId> {
+	/// The member working.
+	pub member_id: MemberId,
+	/// The account acting as the worker, which the stake is reserved from and the reward paid to.
+	pub role_account: AccountId,
+	/// The role stake of the worker.
+	pub stake: Balance,
+	/// The reward relationship paying the worker.
+	pub reward_relationship: Option<RelationshipId>,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait<I>, I: Instance=DefaultInstance> as WorkingGroup {
+		/// The lead of the group.
+		pub CurrentLead get(lead): Option<Lead<T::AccountId, T::MemberId>>;
+		/// The mint the rewards are paid from.
+		pub RewardMint get(mint): Option<T::MintId>;
+
+		/// The identifier of the next opening.
+		pub NextOpeningId get(next_opening_id): OpeningId;
+		/// The openings.
+		pub Openings get(opening): map OpeningId => Option<Opening<BalanceOf<T, I>, T::BlockNumber>>;
+
+		/// The identifier of the next application.
+		pub NextApplicationId get(next_application_id): ApplicationId;
+		/// The applications.
+		pub Applications get(application): map ApplicationId => Option<Application<T::AccountId, BalanceOf<T, I>, T::MemberId>>;
+
+		/// The identifier of the next worker.
+		pub NextWorkerId get(next_worker_id): WorkerId;
+		/// The workers.
+		pub Workers get(worker): map WorkerId => Option<Worker<T::AccountId, BalanceOf<T, I>, T::MemberId, T::RelationshipId>>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T, I=DefaultInstance> where
+		<T as system::Trait>::AccountId,
+		<T as Trait<I>>::MemberId,
+		<T as Trait<I>>::MintId,
+		Balance = BalanceOf<T, I>,
+	{
+		/// The lead was set to the member, with the role account.
+		LeadSet(MemberId, AccountId),
+		/// The lead was unset.
+		LeadUnset,
+		/// The mint was set or unset.
+		MintSet(Option<MintId>),
+		/// An opening was added.
+		OpeningAdded(OpeningId),
+		/// A member applied on an opening.
+		Applied(ApplicationId, OpeningId, MemberId),
+		/// An application was withdrawn.
+		ApplicationWithdrawn(ApplicationId),
+		/// The review of the applications to an opening began.
+		ReviewBegan(OpeningId),
+		/// An opening was filled, hiring the given workers.
+		OpeningFilled(OpeningId, Vec<WorkerId>),
+		/// The stake of a worker was slashed by the amount.
+		StakeSlashed(WorkerId, Balance),
+		/// A worker was fired.
+		WorkerFired(WorkerId),
+		/// A worker left its role.
+		WorkerLeft(WorkerId),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait<I>, I: Instance=DefaultInstance> for enum Call where origin: T::Origin {
+		fn deposit_event<T, I>() = default;
+
+		/// Set the lead to the member `member_id`, acting with `role_account`, or unset it if
+		/// `lead` is `None`.
+		///
+		/// The origin must be root.
+		#[weight = SimpleDispatchInfo::FixedOperational(BASE_WEIGHT)]
+		fn set_lead(origin, lead: Option<(T::MemberId, T::AccountId)>) -> Result {
+			ensure_root(origin)?;
+
+			match lead {
+				Some((member_id, role_account)) => {
+					ensure!(T::Members::controller_account(&member_id).is_some(), "Unknown member");
+					<CurrentLead<T, I>>::put(Lead { member_id: member_id.clone(), role_account: role_account.clone() });
+					Self::deposit_event(RawEvent::LeadSet(member_id, role_account));
+				}
+				None => {
+					<CurrentLead<T, I>>::kill();
+					Self::deposit_event(RawEvent::LeadUnset);
+				}
+			}
+
+			Ok(())
+		}
+
+		/// Set the mint the rewards of the workers hired from now on are paid from to `mint`, or
+		/// unset it if `None`.
+		///
+		/// The origin must be root.
+		#[weight = SimpleDispatchInfo::FixedOperational(BASE_WEIGHT)]
+		fn set_mint(origin, mint: Option<T::MintId>) -> Result {
+			ensure_root(origin)?;
+
+			match mint.clone() {
+				Some(mint) => <RewardMint<T, I>>::put(mint),
+				None => <RewardMint<T, I>>::kill(),
+			}
+			Self::deposit_event(RawEvent::MintSet(mint));
+
+			Ok(())
+		}
+
+		/// Add an opening requiring `application_stake` from the applicants, with the reward
+		/// `reward`.
+		///
+		/// The sender must be the lead.
+		#[weight = DescriptionWeight::<T, I>::default()]
+		fn add_opening(
+			origin,
+			description: Vec<u8>,
+			#[compact] application_stake: BalanceOf<T, I>,
+			reward: Option<RewardPolicy<BalanceOf<T, I>, T::BlockNumber>>
+		) -> Result {
+			Self::ensure_lead(origin)?;
+			if let Some(reward) = reward.as_ref() {
+				ensure!(!reward.interval.is_zero(), "Reward interval is zero");
+			}
+
+			let id = Self::next_opening_id();
+			<NextOpeningId<I>>::put(id + 1);
+			<Openings<T, I>>::insert(id, Opening {
+				description,
+				application_stake,
+				reward,
+				stage: OpeningStage::AcceptingApplications,
+				applications: Vec::new(),
+			});
+			Self::deposit_event(RawEvent::OpeningAdded(id));
+
+			Ok(())
+		}
+
+		/// Apply on the opening `opening_id` on behalf of the member `member_id`, reserving
+		/// `stake`. The sender is the role account of the future worker.
+		///
+		/// The sender must be the controller account of the member.
+		#[weight = OpeningWeight::<T, I>::default()]
+		fn apply_on_opening(
+			origin,
+			member_id: T::MemberId,
+			#[compact] opening_id: OpeningId,
+			#[compact] stake: BalanceOf<T, I>
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			ensure!(T::Members::is_member_controller(&member_id, &who), "Not the controller account of the member");
+			let mut opening = Self::opening(opening_id).ok_or("Unknown opening")?;
+			ensure!(opening.stage == OpeningStage::AcceptingApplications, "Opening is not accepting applications");
+			ensure!(stake >= opening.application_stake, "Stake is too low");
+
+			T::Currency::reserve(&who, stake)?;
+
+			let id = Self::next_application_id();
+			<NextApplicationId<I>>::put(id + 1);
+			opening.applications.push(id);
+			<Openings<T, I>>::insert(opening_id, opening);
+			<Applications<T, I>>::insert(id, Application {
+				opening_id,
+				member_id: member_id.clone(),
+				role_account: who,
+				stake,
+			});
+			Self::deposit_event(RawEvent::Applied(id, opening_id, member_id));
+
+			Ok(())
+		}
+
+		/// Withdraw the application `application_id`, returning its stake.
+		///
+		/// The sender must be the role account of the application.
+		#[weight = ApplicationWeight::<T, I>::default()]
+		fn withdraw_application(origin, #[compact] application_id: ApplicationId) -> Result {
+			let who = ensure_signed(origin)?;
+			let application = Self::application(application_id).ok_or("Unknown application")?;
+			ensure!(application.role_account == who, "Not the role account of the application");
+
+			T::Currency::unreserve(&who, application.stake);
+			<Openings<T, I>>::mutate(application.opening_id, |opening| if let Some(opening) = opening {
+				opening.applications.retain(|id| *id != application_id);
+			});
+			<Applications<T, I>>::remove(application_id);
+			Self::deposit_event(RawEvent::ApplicationWithdrawn(application_id));
+
+			Ok(())
+		}
+
+		/// Begin the review of the applications to the opening `opening_id`, closing it to new
+		/// applications.
+		///
+		/// The sender must be the lead.
+		#[weight = OpeningWeight::<T, I>::default()]
+		fn begin_review(origin, #[compact] opening_id: OpeningId) -> Result {
+			Self::ensure_lead(origin)?;
+			let mut opening = Self::opening(opening_id).ok_or("Unknown opening")?;
+			ensure!(opening.stage == OpeningStage::AcceptingApplications, "Opening is not accepting applications");
+
+			opening.stage = OpeningStage::InReview;
+			<Openings<T, I>>::insert(opening_id, opening);
+			Self::deposit_event(RawEvent::ReviewBegan(opening_id));
+
+			Ok(())
+		}
+
+		/// Fill the opening `opening_id` under review, hiring the applicants of
+		/// `successful_applications`. The stakes of the other applications are returned.
+		///
+		/// If the opening has a reward, the group must have a mint.
+		///
+		/// The sender must be the lead.
+		#[weight = OpeningWeight::<T, I>::default()]
+		fn fill_opening(
+			origin,
+			#[compact] opening_id: OpeningId,
+			successful_applications: Vec<ApplicationId>
+		) -> Result {
+			Self::ensure_lead(origin)?;
+			let opening = Self::opening(opening_id).ok_or("Unknown opening")?;
+			ensure!(opening.stage == OpeningStage::InReview, "Opening is not in review");
+			ensure!(
+				successful_applications.iter().all(|id| opening.applications.contains(id)),
+				"Application is not to the opening"
+			);
+			let mint = match opening.reward {
+				Some(_) => Some(Self::mint().ok_or("No mint")?),
+				None => None,
+			};
+
+			let now = <system::Module<T>>::block_number();
+			let mut hired = Vec::new();
+			for application_id in opening.applications {
+				let application = match <Applications<T, I>>::take(application_id) {
+					Some(application) => application,
+					None => continue,
+				};
+				if !successful_applications.contains(&application_id) {
+					T::Currency::unreserve(&application.role_account, application.stake);
+					continue;
+				}
+
+				let reward_relationship = match (opening.reward.as_ref(), mint.as_ref()) {
+					(Some(reward), Some(mint)) => Some(T::Rewards::add_relationship(
+						application.role_account.clone(),
+						mint.clone(),
+						reward.amount,
+						reward.interval,
+						now + reward.interval,
+					)?),
+					_ => None,
+				};
+				let worker_id = Self::next_worker_id();
+				<NextWorkerId<I>>::put(worker_id + 1);
+				<Workers<T, I>>::insert(worker_id, Worker {
+					member_id: application.member_id,
+					role_account: application.role_account,
+					stake: application.stake,
+					reward_relationship,
+				});
+				hired.push(worker_id);
+			}
+			<Openings<T, I>>::remove(opening_id);
+			Self::deposit_event(RawEvent::OpeningFilled(opening_id, hired));
+
+			Ok(())
+		}
+
+		/// Slash up to `amount` of the stake of the worker `worker_id`.
+		///
+		/// The sender must be the lead.
+		#[weight = SimpleDispatchInfo::FixedNormal(BASE_WEIGHT)]
+		fn slash_stake(origin, #[compact] worker_id: WorkerId, #[compact] amount: BalanceOf<T, I>) -> Result {
+			Self::ensure_lead(origin)?;
+			let mut worker = Self::worker(worker_id).ok_or("Unknown worker")?;
+
+			let amount = amount.min(worker.stake);
+			Self::slash(&worker.role_account, amount);
+			worker.stake -= amount;
+			<Workers<T, I>>::insert(worker_id, worker);
+			Self::deposit_event(RawEvent::StakeSlashed(worker_id, amount));
+
+			Ok(())
+		}
+
+		/// Fire the worker `worker_id`, slashing its whole stake if `slash` is `true`, or else
+		/// returning it.
+		///
+		/// The sender must be the lead.
+		#[weight = SimpleDispatchInfo::FixedNormal(BASE_WEIGHT)]
+		fn fire_worker(origin, #[compact] worker_id: WorkerId, slash: bool) -> Result {
+			Self::ensure_lead(origin)?;
+			let worker = <Workers<T, I>>::take(worker_id).ok_or("Unknown worker")?;
+
+			Self::end_reward(&worker);
+			if slash {
+				Self::slash(&worker.role_account, worker.stake);
+				Self::deposit_event(RawEvent::StakeSlashed(worker_id, worker.stake));
+			} else {
+				T::Currency::unreserve(&worker.role_account, worker.stake);
+			}
+			Self::deposit_event(RawEvent::WorkerFired(worker_id));
+
+			Ok(())
+		}
+
+		/// Leave the role of the worker `worker_id`, getting its stake back.
+		///
+		/// The sender must be the role account of the worker.
+		#[weight = SimpleDispatchInfo::FixedNormal(BASE_WEIGHT)]
+		fn leave_role(origin, #[compact] worker_id: WorkerId) -> Result {
+			let who = ensure_signed(origin)?;
+			let worker = Self::worker(worker_id).ok_or("Unknown worker")?;
+			ensure!(worker.role_account == who, "Not the role account of the worker");
+
+			Self::end_reward(&worker);
+			T::Currency::unreserve(&who, worker.stake);
+			<Workers<T, I>>::remove(worker_id);
+			Self::deposit_event(RawEvent::WorkerLeft(worker_id));
+
+			Ok(())
+		}
+	}
+}
+
+/// Weight of `add_opening`: a step per byte of the description.
+pub struct DescriptionWeight<T, I = DefaultInstance>(PhantomData<(T, I)>);
+
+impl<T, I> Default for DescriptionWeight<T, I> {
+	fn default() -> Self {
+		DescriptionWeight(PhantomData)
+	}
+}
+
+impl<T, I, S, R> WeighData<(&Vec<u8>, S, R)> for DescriptionWeight<T, I> {
+	fn weigh_data(&self, (description, _, _): (&Vec<u8>, S, R)) -> Weight {
+		let bytes: Weight = description.len().saturated_into();
+		BASE_WEIGHT.saturating_add(BYTE_WEIGHT.saturating_mul(bytes))
+	}
+}
+
+impl<T, I, A> ClassifyDispatch<A> for DescriptionWeight<T, I> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+/// Weight of the calls on an opening: a step per byte of the opening, and for `fill_opening`, per
+/// application settled and per comparison with the successful applications.
+pub struct OpeningWeight<T, I = DefaultInstance>(PhantomData<(T, I)>);
+
+impl<T, I> Default for OpeningWeight<T, I> {
+	fn default() -> Self {
+		OpeningWeight(PhantomData)
+	}
+}
+
+impl<T: Trait<I>, I: Instance> OpeningWeight<T, I> {
+	/// The weight of reading and writing the opening `opening_id`.
+	fn weight(opening_id: OpeningId) -> Weight {
+		let bytes: Weight = Module::<T, I>::opening(opening_id)
+			.map_or(0, |opening| opening.using_encoded(|encoded| encoded.len()))
+			.saturated_into();
+		BASE_WEIGHT.saturating_add(BYTE_WEIGHT.saturating_mul(bytes))
+	}
+}
+
+impl<T: Trait<I>, I: Instance, M, S> WeighData<(M, &OpeningId, S)> for OpeningWeight<T, I> {
+	fn weigh_data(&self, (_, opening_id, _): (M, &OpeningId, S)) -> Weight {
+		Self::weight(*opening_id)
+	}
+}
+
+impl<T: Trait<I>, I: Instance> WeighData<(&OpeningId,)> for OpeningWeight<T, I> {
+	fn weigh_data(&self, (opening_id,): (&OpeningId,)) -> Weight {
+		Self::weight(*opening_id)
+	}
+}
+
+impl<T: Trait<I>, I: Instance> WeighData<(&OpeningId, &Vec<ApplicationId>)> for OpeningWeight<T, I> {
+	fn weigh_data(&self, (opening_id, successful_applications): (&OpeningId, &Vec<ApplicationId>)) -> Weight {
+		let applications: Weight = Module::<T, I>::opening(opening_id)
+			.map_or(0, |opening| opening.applications.len())
+			.saturated_into();
+		let successful_applications: Weight = successful_applications.len().saturated_into();
+		let comparisons = applications.saturating_mul(successful_applications);
+
+		Self::weight(*opening_id)
+			.saturating_add(APPLICATION_WEIGHT.saturating_mul(applications))
+			.saturating_add(COMPARISON_WEIGHT.saturating_mul(comparisons))
+	}
+}
+
+impl<T, I, A> ClassifyDispatch<A> for OpeningWeight<T, I> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+/// Weight of `withdraw_application`: the weight of the calls on the opening of the application.
+pub struct ApplicationWeight<T, I = DefaultInstance>(PhantomData<(T, I)>);
+
+impl<T, I> Default for ApplicationWeight<T, I> {
+	fn default() -> Self {
+		ApplicationWeight(PhantomData)
+	}
+}
+
+impl<T: Trait<I>, I: Instance> WeighData<(&ApplicationId,)> for ApplicationWeight<T, I> {
+	fn weigh_data(&self, (application_id,): (&ApplicationId,)) -> Weight {
+		Module::<T, I>::application(application_id)
+			.map_or(BASE_WEIGHT, |application| OpeningWeight::<T, I>::weight(application.opening_id))
+	}
+}
+
+impl<T, I, A> ClassifyDispatch<A> for ApplicationWeight<T, I> {
+	fn classify_dispatch(&self, _: A) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+impl<T: Trait<I>, I: Instance> Module<T, I> {
+	fn ensure_lead(origin: T::Origin) -> Result {
+		let who = ensure_signed(origin)?;
+		let lead = Self::lead().ok_or("No lead")?;
+		ensure!(lead.role_account == who, "Not the lead");
+		Ok(())
+	}
+
+	fn end_reward(worker: &Worker<T::AccountId, BalanceOf<T, I>, T::MemberId, T::RelationshipId>) {
+		if let Some(relationship) = worker.reward_relationship.as_ref() {
+			// The relationship may have been ended by its manager already.
+			let _ = T::Rewards::remove_relationship(relationship);
+		}
+	}
+
+	fn slash(who: &T::AccountId, amount: BalanceOf<T, I>) {
+		let imbalance = T::Currency::slash_reserved(who, amount).0;
+		T::Slashed::on_unbalanced(imbalance);
+	}
+}
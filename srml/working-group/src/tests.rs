@@ -0,0 +1,249 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use super::*;
+
+use primitives::Blake2Hasher;
+use runtime_io::with_externalities;
+use sr_primitives::weights::{GetDispatchInfo, Weight};
+use srml_support::{assert_noop, assert_ok, impl_test_runtime, parameter_types, traits::OnInitialize};
+
+impl_test_runtime! {
+	pub struct Test;
+	balances {
+		ExistentialDeposit = 0;
+		TransferFee = 0;
+		CreationFee = 0;
+	}
+}
+
+/// The members `0` to `9`, controlled by the accounts of the same number.
+pub struct Members;
+impl MemberAccounts<u64> for Members {
+	type MemberId = u64;
+
+	fn controller_account(member: &u64) -> Option<u64> {
+		if *member < 10 { Some(*member) } else { None }
+	}
+}
+
+impl minting::Trait for Test {
+	type Event = ();
+	type Currency = balances::Module<Test>;
+	type MintId = u32;
+	type GovernanceOrigin = system::EnsureRoot<u64>;
+}
+parameter_types! {
+	pub const PayoutWeight: Weight = 10;
+	pub const MaxPayoutWeight: Weight = 100;
+}
+impl recurring_reward::Trait for Test {
+	type Event = ();
+	type Balance = u64;
+	type MintId = u32;
+	type Mint = minting::Module<Test>;
+	type ManagerOrigin = system::EnsureRoot<u64>;
+	type PayoutWeight = PayoutWeight;
+	type MaxPayoutWeight = MaxPayoutWeight;
+}
+impl Trait for Test {
+	type Event = ();
+	type Currency = balances::Module<Test>;
+	type MemberId = u64;
+	type Members = Members;
+	type MintId = u32;
+	type RelationshipId = recurring_reward::RelationshipId;
+	type Rewards = recurring_reward::Module<Test>;
+	type Slashed = ();
+}
+impl Trait<Instance1> for Test {
+	type Event = ();
+	type Currency = balances::Module<Test>;
+	type MemberId = u64;
+	type Members = Members;
+	type MintId = u32;
+	type RelationshipId = recurring_reward::RelationshipId;
+	type Rewards = recurring_reward::Module<Test>;
+	type Slashed = ();
+}
+type System = system::Module<Test>;
+type Balances = balances::Module<Test>;
+type Minting = minting::Module<Test>;
+type Rewards = recurring_reward::Module<Test>;
+type Group = Module<Test>;
+type OtherGroup = Module<Test, Instance1>;
+
+fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap().0;
+	t.extend(balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100), (3, 100)],
+		vesting: vec![],
+	}.build_storage().unwrap().0);
+	t.into()
+}
+
+/// The opening 0, with the applications 0 to 2 of the members 1 to 3 staking 20 each, led by the
+/// account 10.
+fn new_opening_with_applications(reward: Option<RewardPolicy<u64, u64>>) {
+	assert_ok!(Group::set_lead(Origin::ROOT, Some((0, 10))));
+	assert_ok!(Group::add_opening(Origin::signed(10), b"curator".to_vec(), 20, reward));
+	for who in 1..4 {
+		assert_ok!(Group::apply_on_opening(Origin::signed(who), who, 0, 20));
+	}
+}
+
+fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		Rewards::on_initialize(System::block_number());
+	}
+}
+
+#[test]
+fn groups_are_led_by_their_own_lead() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_noop!(Group::set_lead(Origin::signed(1), Some((0, 10))), "bad origin: expected to be a root origin");
+		assert_noop!(Group::set_lead(Origin::ROOT, Some((10, 10))), "Unknown member");
+		assert_noop!(Group::add_opening(Origin::signed(10), vec![], 0, None), "No lead");
+		assert_ok!(Group::set_lead(Origin::ROOT, Some((0, 10))));
+		assert_eq!(Group::lead(), Some(Lead { member_id: 0, role_account: 10 }));
+
+		assert_noop!(Group::add_opening(Origin::signed(1), vec![], 0, None), "Not the lead");
+		assert_noop!(
+			Group::add_opening(Origin::signed(10), vec![], 0, Some(RewardPolicy { amount: 1, interval: 0 })),
+			"Reward interval is zero"
+		);
+		assert_ok!(Group::add_opening(Origin::signed(10), vec![], 0, None));
+
+		// The other group has its own lead and openings.
+		assert_eq!(OtherGroup::lead(), None);
+		assert_eq!(OtherGroup::opening(0), None);
+		assert_noop!(OtherGroup::begin_review(Origin::signed(10), 0), "No lead");
+
+		assert_ok!(Group::set_lead(Origin::ROOT, None));
+		assert_noop!(Group::begin_review(Origin::signed(10), 0), "No lead");
+	});
+}
+
+#[test]
+fn openings_are_filled_after_review() {
+	with_externalities(&mut new_test_ext(), || {
+		assert_ok!(Group::set_lead(Origin::ROOT, Some((0, 10))));
+		assert_ok!(Group::add_opening(Origin::signed(10), b"curator".to_vec(), 20, None));
+		assert_noop!(Group::apply_on_opening(Origin::signed(2), 1, 0, 20), "Not the controller account of the member");
+		assert_noop!(Group::apply_on_opening(Origin::signed(1), 1, 0, 19), "Stake is too low");
+		for who in 1..4 {
+			assert_ok!(Group::apply_on_opening(Origin::signed(who), who, 0, 20 + who));
+		}
+		assert_eq!(Balances::reserved_balance(&1), 21);
+
+		assert_noop!(Group::withdraw_application(Origin::signed(1), 2), "Not the role account of the application");
+		assert_ok!(Group::withdraw_application(Origin::signed(3), 2));
+		assert_eq!(Balances::reserved_balance(&3), 0);
+
+		assert_noop!(Group::fill_opening(Origin::signed(10), 0, vec![0]), "Opening is not in review");
+		assert_ok!(Group::begin_review(Origin::signed(10), 0));
+		assert_noop!(Group::apply_on_opening(Origin::signed(3), 3, 0, 20), "Opening is not accepting applications");
+		assert_noop!(Group::fill_opening(Origin::signed(10), 0, vec![2]), "Application is not to the opening");
+
+		assert_ok!(Group::fill_opening(Origin::signed(10), 0, vec![1]));
+		assert_eq!(Group::worker(0), Some(Worker { member_id: 2, role_account: 2, stake: 22, reward_relationship: None }));
+		assert_eq!(Balances::reserved_balance(&1), 0);
+		assert_eq!(Balances::reserved_balance(&2), 22);
+		assert_eq!(Group::opening(0), None);
+		assert_eq!(Group::application(0), None);
+	});
+}
+
+#[test]
+fn workers_are_rewarded_from_the_mint() {
+	with_externalities(&mut new_test_ext(), || {
+		System::set_block_number(1);
+		new_opening_with_applications(Some(RewardPolicy { amount: 10, interval: 5 }));
+		assert_ok!(Group::begin_review(Origin::signed(10), 0));
+		assert_noop!(Group::fill_opening(Origin::signed(10), 0, vec![0, 1]), "No mint");
+		assert_ok!(Minting::create_mint(Origin::ROOT, 30, None));
+		assert_ok!(Group::set_mint(Origin::ROOT, Some(0)));
+		assert_ok!(Group::fill_opening(Origin::signed(10), 0, vec![0, 1]));
+		assert_eq!(Group::worker(1).unwrap().reward_relationship, Some(1));
+		assert_eq!(Rewards::relationship(1).unwrap().recipient, 2);
+
+		run_to_block(7);
+		assert_eq!(Balances::free_balance(&1), 90);
+		assert_eq!(Balances::free_balance(&2), 90);
+		assert_eq!(Minting::mint(0).unwrap().capacity, 10);
+
+		// The mint runs short for the second worker.
+		run_to_block(12);
+		assert_eq!(Balances::free_balance(&1), 100);
+		assert_eq!(Balances::free_balance(&2), 90);
+		assert_eq!(Minting::mint(0).unwrap().capacity, 0);
+
+		// Workers that left aren't paid anymore.
+		assert_ok!(Group::leave_role(Origin::signed(1), 0));
+		assert_eq!(Rewards::relationship(0), None);
+		assert_ok!(Minting::set_capacity(Origin::ROOT, 0, 30));
+		run_to_block(17);
+		assert_eq!(Balances::free_balance(&1), 120);
+		assert_eq!(Balances::free_balance(&2), 100);
+	});
+}
+
+#[test]
+fn leads_slash_and_fire_workers() {
+	with_externalities(&mut new_test_ext(), || {
+		new_opening_with_applications(None);
+		assert_ok!(Group::begin_review(Origin::signed(10), 0));
+		assert_ok!(Group::fill_opening(Origin::signed(10), 0, vec![0, 1, 2]));
+
+		assert_noop!(Group::slash_stake(Origin::signed(1), 0, 5), "Not the lead");
+		assert_ok!(Group::slash_stake(Origin::signed(10), 0, 5));
+		assert_eq!(Group::worker(0).unwrap().stake, 15);
+		assert_ok!(Group::slash_stake(Origin::signed(10), 0, 50));
+		assert_eq!(Group::worker(0).unwrap().stake, 0);
+		assert_eq!(Balances::total_balance(&1), 80);
+
+		assert_ok!(Group::fire_worker(Origin::signed(10), 1, true));
+		assert_ok!(Group::fire_worker(Origin::signed(10), 2, false));
+		assert_eq!(Group::worker(1), None);
+		assert_eq!(Balances::total_balance(&2), 80);
+		assert_eq!(Balances::total_balance(&3), 100);
+		assert_eq!(Balances::reserved_balance(&3), 0);
+
+		assert_noop!(Group::leave_role(Origin::signed(2), 0), "Not the role account of the worker");
+		assert_ok!(Group::leave_role(Origin::signed(1), 0));
+		assert_noop!(Group::fire_worker(Origin::signed(10), 0, false), "Unknown worker");
+	});
+}
+
+#[test]
+fn filling_openings_is_weighed_by_their_applications() {
+	with_externalities(&mut new_test_ext(), || {
+		let fill = |successful_applications: Vec<ApplicationId>| {
+			Call::<Test>::fill_opening(0, successful_applications).get_dispatch_info().weight
+		};
+		let empty = fill(vec![]);
+		new_opening_with_applications(None);
+
+		assert!(empty < fill(vec![]));
+		assert!(fill(vec![]) < fill(vec![0, 1]));
+		let three_applications = fill(vec![]);
+		assert_ok!(Group::withdraw_application(Origin::signed(1), 0));
+		assert!(fill(vec![]) < three_applications);
+	});
+}
@@ -29,7 +29,7 @@ use primitives::{
 use codec::{Encode, Decode};
 use sr_primitives::generic::Era;
 use node_primitives::{Balance, Index, Hash};
-use node_runtime::{Call, UncheckedExtrinsic, BalancesCall, Runtime};
+use node_runtime::{Call, UncheckedExtrinsic, BalancesCall, Runtime, VERSION};
 
 mod vanity;
 
@@ -73,10 +73,6 @@ struct Ed25519;
 impl Crypto for Ed25519 {
 	type Pair = ed25519::Pair;
 	type Public = ed25519::Public;
-
-	fn pair_from_suri(suri: &str, password_override: Option<&str>) -> Self::Pair {
-		ed25519::Pair::from_legacy_string(suri, password_override)
-	}
 }
 
 struct Sr25519;
@@ -92,11 +88,12 @@ fn execute<C: Crypto>(matches: clap::ArgMatches) where
 {
 	let extra = |i: Index, f: Balance| {
 		(
+			system::CheckTxVersion::<Runtime>::new(),
 			system::CheckGenesis::<Runtime>::new(),
 			system::CheckEra::<Runtime>::from(Era::Immortal),
 			system::CheckNonce::<Runtime>::from(i),
 			system::CheckWeight::<Runtime>::new(),
-			balances::TakeFees::<Runtime>::from(f),
+			transaction_payment::ChargeTransactionPayment::<Runtime>::from(f),
 		)
 	};
 	let password = matches.value_of("password");
@@ -174,7 +171,7 @@ fn execute<C: Crypto>(matches: clap::ArgMatches) where
 			let raw_payload = (
 				function,
 				extra(index, 0),
-				(&genesis_hash, &genesis_hash),
+				(VERSION.transaction_version, &genesis_hash, &genesis_hash),
 			);
 			let signature = raw_payload.using_encoded(|payload| if payload.len() > 256 {
 				signer.sign(&blake2_256(payload)[..])
@@ -217,7 +214,7 @@ fn execute<C: Crypto>(matches: clap::ArgMatches) where
 			let raw_payload = (
 				function,
 				extra(index, 0),
-				(&genesis_hash, &genesis_hash),
+				(VERSION.transaction_version, &genesis_hash, &genesis_hash),
 			);
 			let signature = raw_payload.using_encoded(|payload|
 				if payload.len() > 256 {
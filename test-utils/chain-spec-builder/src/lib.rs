@@ -0,0 +1,176 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Construction of chain specs for the node from a description of the chain.
+//!
+//! All keys are given as secret key URIs, e.g. `//Alice` or a secret phrase. The stash account
+//! of an authority is derived with the `//stash` junction from its URI.
+
+#![warn(missing_docs)]
+
+use std::path::Path;
+use serde::Deserialize;
+use primitives::{ed25519, sr25519, Pair};
+use node_cli::chain_spec::{self, ChainSpec, GenesisConfig};
+
+/// Description of a chain, e.g. read from a TOML file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct ChainDescription {
+	/// Name of the chain.
+	pub name: String,
+	/// Id of the chain.
+	pub id: String,
+	/// Bootnode addresses.
+	#[serde(default)]
+	pub boot_nodes: Vec<String>,
+	/// Secret key URIs of the initial authorities.
+	pub authorities: Vec<String>,
+	/// Secret key URIs of the endowed accounts.
+	pub endowed_accounts: Vec<String>,
+	/// Secret key URI of the sudo key.
+	pub sudo: String,
+	/// Whether contracts may print to the console of the node.
+	#[serde(default)]
+	pub enable_println: bool,
+}
+
+impl ChainDescription {
+	/// Parse a description from TOML.
+	pub fn from_toml(toml: &str) -> Result<Self, String> {
+		toml::from_str(toml).map_err(|e| format!("Error parsing chain description: {}", e))
+	}
+
+	/// Build the genesis configuration of the chain.
+	pub fn genesis(&self) -> Result<GenesisConfig, String> {
+		let authorities = self.authorities.iter()
+			.map(|suri| authority_keys_from_suri(suri))
+			.collect::<Result<Vec<_>, _>>()?;
+		let endowed_accounts = self.endowed_accounts.iter()
+			.map(|suri| public_from_suri::<sr25519::Pair>(suri))
+			.collect::<Result<Vec<_>, _>>()?;
+		let sudo = public_from_suri::<sr25519::Pair>(&self.sudo)?;
+
+		Ok(chain_spec::testnet_genesis(authorities, sudo, Some(endowed_accounts), self.enable_println))
+	}
+
+	/// Build the chain spec of the chain.
+	pub fn chain_spec(&self) -> Result<ChainSpec, String> {
+		// `ChainSpec::from_genesis` only takes a function pointer, so we go through the json
+		// representation to embed the genesis configuration.
+		let json = serde_json::json!({
+			"name": self.name,
+			"id": self.id,
+			"bootNodes": self.boot_nodes,
+			"telemetryEndpoints": null,
+			"protocolId": null,
+			"consensusEngine": null,
+			"properties": null,
+			"genesis": { "runtime": self.genesis()? },
+		});
+		let bytes = serde_json::to_vec(&json)
+			.map_err(|e| format!("Error generating spec json: {}", e))?;
+		ChainSpec::from_json_bytes(bytes)
+	}
+}
+
+fn public_from_suri<P: Pair>(suri: &str) -> Result<P::Public, String> {
+	P::from_string(suri, None)
+		.map(|pair| pair.public())
+		.map_err(|_| format!("Invalid secret key URI: {}", suri))
+}
+
+/// Derive the stash, controller, BABE and GRANDPA keys of an authority from its secret key URI.
+pub fn authority_keys_from_suri(suri: &str) -> Result<(
+	sr25519::Public,
+	sr25519::Public,
+	sr25519::Public,
+	ed25519::Public,
+), String> {
+	Ok((
+		public_from_suri::<sr25519::Pair>(&format!("{}//stash", suri))?,
+		public_from_suri::<sr25519::Pair>(suri)?,
+		public_from_suri::<sr25519::Pair>(suri)?,
+		public_from_suri::<ed25519::Pair>(suri)?,
+	))
+}
+
+/// Generate a fresh secret phrase for each of `count` authorities and insert their session keys
+/// into a keystore `auth-<index>` below `keystore_path`. Returns the secret phrases.
+pub fn generate_authority_keys_and_store(
+	count: usize,
+	keystore_path: &Path,
+) -> Result<Vec<String>, String> {
+	(0..count).map(|index| {
+		let (_, phrase, _) = sr25519::Pair::generate_with_phrase(None);
+		let store = keystore::Store::open(keystore_path.join(format!("auth-{}", index)), String::new().into())
+			.map_err(|e| format!("Failed to open keystore: {}", e))?;
+		store.insert::<sr25519::Pair>(&phrase)
+			.and_then(|_| store.insert::<ed25519::Pair>(&phrase))
+			.map_err(|e| format!("Failed to insert key: {}", e))?;
+		Ok(phrase)
+	}).collect()
+}
+
+/// Generate `count` fresh secret phrases for accounts.
+pub fn generate_accounts(count: usize) -> Vec<String> {
+	(0..count).map(|_| sr25519::Pair::generate_with_phrase(None).1).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tempdir::TempDir;
+
+	const DESCRIPTION: &str = r#"
+		name = "Test"
+		id = "test"
+		authorities = ["//Alice", "//Bob"]
+		endowed-accounts = ["//Alice", "//Bob", "//Charlie"]
+		sudo = "//Alice"
+	"#;
+
+	#[test]
+	fn seeds_match_the_node_helpers() {
+		assert_eq!(
+			authority_keys_from_suri("//Alice").unwrap(),
+			chain_spec::get_authority_keys_from_seed("Alice"),
+		);
+	}
+
+	#[test]
+	fn builds_chain_spec_from_toml() {
+		let description = ChainDescription::from_toml(DESCRIPTION).unwrap();
+		let spec = description.chain_spec().unwrap();
+
+		assert_eq!(spec.name(), "Test");
+		assert_eq!(spec.id(), "test");
+		assert!(spec.to_json(false).unwrap().contains("\"runtime\""));
+	}
+
+	#[test]
+	fn generated_authority_keys_are_stored() {
+		let temp_dir = TempDir::new("chain-spec-builder").unwrap();
+		let phrases = generate_authority_keys_and_store(2, temp_dir.path()).unwrap();
+		assert_eq!(phrases.len(), 2);
+
+		let store = keystore::Store::open(temp_dir.path().join("auth-1"), String::new().into()).unwrap();
+		let (_, _, babe, grandpa) = authority_keys_from_suri(&phrases[1]).unwrap();
+		assert_eq!(store.contents::<sr25519::Public>().unwrap(), vec![babe]);
+		assert_eq!(store.contents::<ed25519::Public>().unwrap(), vec![grandpa]);
+	}
+}
@@ -1,49 +1,123 @@
-use clap::{App, load_yaml};
+use std::{fs, path::Path, str::FromStr};
+use clap::{App, ArgMatches, load_yaml};
 
-use node_cli::chain_spec;
+use chain_spec_builder::{ChainDescription, generate_accounts, generate_authority_keys_and_store};
 use substrate_service::chain_ops::build_spec;
 
-fn genesis_constructor() -> chain_spec::GenesisConfig {
-	let yaml = load_yaml!("./cli.yml");
-	let matches = App::from_yaml(yaml).get_matches();
-	let authorities = matches.values_of("initial_authority_seed")
-		.unwrap()
-		.map(chain_spec::get_authority_keys_from_seed)
-		.collect();
+fn description_from_seeds(name: &str, id: &str, matches: &ArgMatches) -> ChainDescription {
+	let seeds = |arg| matches.values_of(arg)
+		.expect("argument is required; qed")
+		.map(|seed| format!("//{}", seed))
+		.collect::<Vec<_>>();
 
-	let endowed_accounts = matches.values_of("endowed_account_seed")
-		.unwrap()
-		.map(chain_spec::get_account_id_from_seed)
-		.collect();
+	ChainDescription {
+		name: name.into(),
+		id: id.into(),
+		boot_nodes: vec![],
+		authorities: seeds("initial_authority_seed"),
+		endowed_accounts: seeds("endowed_account_seed"),
+		sudo: format!("//{}", matches.value_of("sudo_key_seed").expect("argument is required; qed")),
+		enable_println: true,
+	}
+}
 
-	let sudo_key_seed = matches.value_of("sudo_key_seed").unwrap();
-	let sudo_key = chain_spec::get_account_id_from_seed(sudo_key_seed);
+fn generate_description(name: &str, id: &str, matches: &ArgMatches) -> Result<ChainDescription, String> {
+	let count = |arg| matches.value_of(arg)
+		.map(usize::from_str)
+		.expect("argument is required or has a default; qed")
+		.map_err(|e| format!("Invalid number for {}: {}", arg, e));
+	let keystore_path = Path::new(matches.value_of("keystore_path").expect("argument is required; qed"));
 
-	let enable_println = true;
+	let authorities = generate_authority_keys_and_store(count("authorities")?, keystore_path)?;
+	let endowed = generate_accounts(count("endowed")?);
+	for (index, phrase) in authorities.iter().enumerate() {
+		eprintln!("Authority {}: {}", index, phrase);
+	}
+	for (index, phrase) in endowed.iter().enumerate() {
+		eprintln!("Endowed account {}: {}", index, phrase);
+	}
 
-	chain_spec::testnet_genesis(
+	let sudo = authorities.first().cloned().ok_or("At least one authority is required")?;
+	Ok(ChainDescription {
+		name: name.into(),
+		id: id.into(),
+		boot_nodes: vec![],
+		endowed_accounts: authorities.iter().chain(endowed.iter()).cloned().collect(),
 		authorities,
-		sudo_key.into(),
-		Some(endowed_accounts),
-		enable_println,
-	)
+		sudo,
+		enable_println: false,
+	})
 }
 
-fn generate_chain_spec() -> String {
-	let chain_spec = chain_spec::ChainSpec::from_genesis(
-		"Custom",
-		"custom",
-		genesis_constructor,
-		vec![],
-		None,
-		None,
-		None,
-		None,
-	);
-	build_spec(chain_spec, false).unwrap()
+fn description(matches: &ArgMatches) -> Result<ChainDescription, String> {
+	let name = matches.value_of("name").expect("argument has a default; qed");
+	let id = matches.value_of("id").expect("argument has a default; qed");
+
+	Ok(match matches.subcommand() {
+		("new", Some(matches)) => description_from_seeds(name, id, matches),
+		("generate", Some(matches)) => generate_description(name, id, matches)?,
+		("from-toml", Some(matches)) => {
+			let file = matches.value_of("file").expect("argument is required; qed");
+			let toml = fs::read_to_string(file).map_err(|e| format!("Error reading {}: {}", file, e))?;
+			ChainDescription::from_toml(&toml)?
+		},
+		// the seeds are given without a subcommand, as before the subcommands existed
+		_ => description_from_seeds(name, id, matches),
+	})
+}
+
+fn generate_chain_spec() -> Result<String, String> {
+	let yaml = load_yaml!("./cli.yml");
+	let matches = App::from_yaml(yaml).get_matches();
+	let description = description(&matches)?;
+
+	build_spec(description.chain_spec()?, matches.is_present("raw")).map_err(|e| format!("{}", e))
 }
 
 fn main() {
-	let json = generate_chain_spec();
-	println!("{}", json);
+	match generate_chain_spec() {
+		Ok(json) => println!("{}", json),
+		Err(e) => {
+			eprintln!("{}", e);
+			std::process::exit(1);
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(args: &[&str]) -> Result<ChainDescription, String> {
+		let yaml = load_yaml!("./cli.yml");
+		let matches = App::from_yaml(yaml)
+			.get_matches_from_safe(Some("chain-spec-builder").iter().chain(args))
+			.map_err(|e| e.message)?;
+		description(&matches)
+	}
+
+	#[test]
+	fn seeds_are_accepted_without_subcommand() {
+		let description = parse(&["-a", "Alice", "-a", "Bob", "-e", "Charlie", "-u", "Alice"]).unwrap();
+		assert_eq!(description.authorities, vec!["//Alice".to_string(), "//Bob".to_string()]);
+		assert_eq!(description.endowed_accounts, vec!["//Charlie".to_string()]);
+		assert_eq!(description.sudo, "//Alice");
+		assert_eq!(description.name, "Custom");
+	}
+
+	#[test]
+	fn seeds_are_accepted_with_the_new_subcommand() {
+		let without_subcommand = parse(&["-a", "Alice", "-e", "Bob", "-u", "Alice"]).unwrap();
+		let with_subcommand = parse(&["--name", "Test", "new", "-a", "Alice", "-e", "Bob", "-u", "Alice"]).unwrap();
+		assert_eq!(with_subcommand.name, "Test");
+		assert_eq!(with_subcommand.authorities, without_subcommand.authorities);
+		assert_eq!(with_subcommand.endowed_accounts, without_subcommand.endowed_accounts);
+		assert_eq!(with_subcommand.sudo, without_subcommand.sudo);
+	}
+
+	#[test]
+	fn seeds_are_required() {
+		assert!(parse(&["-a", "Alice", "-e", "Bob"]).is_err());
+		assert!(parse(&["new", "-a", "Alice", "-e", "Bob"]).is_err());
+	}
 }